@@ -0,0 +1,168 @@
+//! Carrying on past the items that fail in commands working through many
+//! repos or baums
+//!
+//! Each failure is warned about as it happens. At the end the failed items
+//! are listed together, grouped by what went wrong, and the command exits
+//! with [`PARTIAL_FAILURE_EXIT`] if some items went through.
+
+use anyhow::{Error, Result};
+use thiserror::Error;
+
+use crate::output::Output;
+
+/// Exit code of a command that failed for some of its items but not all
+/// (2 is taken by usage errors)
+pub const PARTIAL_FAILURE_EXIT: u8 = 3;
+
+/// The outcomes of a command's items, for example each repo of a fetch
+pub struct BulkResult {
+    /// What an item is, e.g. `repo`
+    noun: &'static str,
+    /// What is done to each item, e.g. `fetch`
+    verb: &'static str,
+    total: usize,
+    failures: Vec<(String, Error)>,
+}
+
+impl BulkResult {
+    /// Collect the outcomes of doing `verb` to `total` items
+    pub fn new(noun: &'static str, verb: &'static str, total: usize) -> Self {
+        Self {
+            noun,
+            verb,
+            total,
+            failures: Vec::new(),
+        }
+    }
+
+    /// Record how `item` went, warning if it failed; the value if it
+    /// succeeded
+    pub fn record<T>(&mut self, item: &str, result: Result<T>, out: &Output) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                out.warn(&format!("Failed to {} {}: {:#}", self.verb, item, e));
+                self.failures.push((item.to_string(), e));
+                None
+            }
+        }
+    }
+
+    /// The items that failed, in the order they were recorded
+    pub fn failed(&self) -> Vec<String> {
+        self.failures.iter().map(|(item, _)| item.clone()).collect()
+    }
+
+    pub fn has_failures(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    /// Ok if every item went through, else a [`PartialFailure`] listing the
+    /// failed items by cause, followed by `hint`
+    pub fn finish(self, hint: Option<&str>) -> Result<()> {
+        if self.failures.is_empty() {
+            return Ok(());
+        }
+
+        let mut message = format!(
+            "{} of {} {}(s) failed to {}:",
+            self.failures.len(),
+            self.total,
+            self.noun,
+            self.verb
+        );
+        for (cause, items) in group_by_cause(&self.failures) {
+            message.push_str(&format!("\n  {}", cause));
+            for item in items {
+                message.push_str(&format!("\n    {}", item));
+            }
+        }
+        if let Some(hint) = hint {
+            message.push('\n');
+            message.push_str(hint);
+        }
+        Err(PartialFailure {
+            failed: self.failures.len(),
+            total: self.total,
+            message,
+        }
+        .into())
+    }
+}
+
+/// A bulk command's error when some of its items failed
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct PartialFailure {
+    pub failed: usize,
+    pub total: usize,
+    message: String,
+}
+
+impl PartialFailure {
+    /// The partial failure behind `error`, if that's what it is
+    pub fn find(error: &Error) -> Option<&PartialFailure> {
+        error
+            .chain()
+            .find_map(|e| e.downcast_ref::<PartialFailure>())
+    }
+
+    /// Whether other items went through
+    pub fn is_partial(&self) -> bool {
+        self.failed < self.total
+    }
+}
+
+/// The failed items under the first line of their root cause, causes in
+/// the order they first occurred
+fn group_by_cause(failures: &[(String, Error)]) -> Vec<(String, Vec<&str>)> {
+    let mut groups: Vec<(String, Vec<&str>)> = Vec::new();
+    for (item, e) in failures {
+        let cause = e.root_cause().to_string();
+        let cause = cause.lines().next().unwrap_or_default().trim().to_string();
+        match groups.iter_mut().find(|(c, _)| *c == cause) {
+            Some((_, items)) => items.push(item),
+            None => groups.push((cause, vec![item])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::OutputFormat;
+    use anyhow::{Context, anyhow};
+
+    #[test]
+    fn test_finish_groups_failures_by_cause() {
+        let out = Output::new(OutputFormat::Json, false);
+        let mut bulk = BulkResult::new("repo", "fetch", 4);
+        let offline = || Err::<(), _>(anyhow!("could not resolve host"));
+        bulk.record("a", offline().context("git fetch failed"), &out);
+        bulk.record("b", Ok(()), &out);
+        bulk.record("c", Err::<(), _>(anyhow!("permission denied\nmore")), &out);
+        bulk.record("d", offline(), &out);
+        assert_eq!(bulk.failed(), vec!["a", "c", "d"]);
+
+        let err = bulk.finish(Some("Re-run to retry them")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "3 of 4 repo(s) failed to fetch:\n  \
+             could not resolve host\n    a\n    d\n  \
+             permission denied\n    c\n\
+             Re-run to retry them"
+        );
+        let partial = PartialFailure::find(&err.context("sync failed")).map(|p| p.is_partial());
+        assert_eq!(partial, Some(true));
+    }
+
+    #[test]
+    fn test_finish_without_failures() {
+        let out = Output::new(OutputFormat::Json, false);
+        let mut bulk = BulkResult::new("repo", "clone", 1);
+        assert_eq!(bulk.record("a", Ok(1), &out), Some(1));
+        assert!(!bulk.has_failures());
+        assert!(bulk.finish(None).is_ok());
+    }
+}
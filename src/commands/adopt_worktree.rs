@@ -0,0 +1,141 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+
+use crate::git;
+use crate::id::{format_wald_branch, parse_wald_branch};
+use crate::naming::unique_worktree_dir_name;
+use crate::output::Output;
+use crate::workspace::baum::{load_baum, save_baum};
+use crate::workspace::gitignore::add_worktree_to_gitignore;
+use crate::workspace::{
+    Workspace, configure_worktree, is_baum, lock_baum, validate_workspace_path,
+};
+
+/// Options for adopt-worktree command
+pub struct AdoptWorktreeOptions {
+    pub baum_path: PathBuf,
+    /// Worktree made with `git worktree add` inside the baum
+    pub worktree_path: PathBuf,
+    /// Rename its branch into the baum's `wald/<baum_id>/` namespace
+    pub rename_branch: bool,
+}
+
+/// Record a worktree added with plain `git worktree add` in a baum's manifest
+///
+/// The branch is taken from the worktree's HEAD and the worktree moves to
+/// the usual `_<branch>.wt` directory.
+pub fn adopt_worktree(ws: &Workspace, opts: AdoptWorktreeOptions, out: &Output) -> Result<()> {
+    out.require_human("adopt-worktree")?;
+
+    let container = validate_workspace_path(&ws.root, &opts.baum_path)?;
+    if !is_baum(&container) {
+        bail!(
+            "not a baum: {} (.baum directory not found)",
+            ws.display_path(&container)
+        );
+    }
+    let worktree_path = validate_workspace_path(&ws.root, &opts.worktree_path)?
+        .canonicalize()
+        .with_context(|| format!("worktree not found: {}", opts.worktree_path.display()))?;
+    let container = container
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", ws.display_path(&container)))?;
+    if worktree_path.parent() != Some(container.as_path()) {
+        bail!(
+            "{} is not directly inside the baum at {}",
+            ws.display_path(&worktree_path),
+            ws.display_path(&container)
+        );
+    }
+
+    let _lock = lock_baum(&container, "adopt-worktree")?;
+    let mut baum_manifest = load_baum(&container)?;
+
+    let bare_path = ws.bare_repo_path(&baum_manifest.repo_id)?;
+    let Some(info) = git::find_worktree(&bare_path, &worktree_path)? else {
+        bail!(
+            "{} is not a worktree of {}",
+            ws.display_path(&worktree_path),
+            baum_manifest.repo_id
+        );
+    };
+    let Some(actual_branch) = info.branch else {
+        bail!(
+            "cannot adopt {}: HEAD is detached; check out a branch first",
+            ws.display_path(&worktree_path)
+        );
+    };
+
+    let dir_name = worktree_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if baum_manifest.worktrees.iter().any(|wt| wt.path == dir_name) {
+        bail!(
+            "{} is already in the baum manifest",
+            ws.display_path(&worktree_path)
+        );
+    }
+
+    let baum_id = ws.ensure_baum_id(&mut baum_manifest).to_string();
+
+    // The logical branch: wald/<id>/<branch> branches name theirs
+    let (branch, local_branch) = match parse_wald_branch(&actual_branch) {
+        Some((_, branch)) => (branch.to_string(), actual_branch.clone()),
+        None if opts.rename_branch => {
+            let local_branch = format_wald_branch(&baum_id, &actual_branch);
+            out.status(
+                "Renaming",
+                &format!("{} -> {}", actual_branch, local_branch),
+            );
+            git::rename_branch(&bare_path, &actual_branch, &local_branch)?;
+            (actual_branch.clone(), local_branch)
+        }
+        None => (actual_branch.clone(), actual_branch.clone()),
+    };
+
+    if baum_manifest.worktrees.iter().any(|wt| wt.branch == branch) {
+        bail!(
+            "worktree for branch '{}' already exists in baum at {}",
+            branch,
+            ws.display_path(&container)
+        );
+    }
+
+    // Walkers skip `_*.wt` directories, so move it to the usual name
+    let taken: HashSet<String> = baum_manifest
+        .worktrees
+        .iter()
+        .map(|wt| wt.path.clone())
+        .collect();
+    let worktree_name = if dir_name.starts_with('_') && dir_name.ends_with(".wt") {
+        dir_name
+    } else {
+        let worktree_name = unique_worktree_dir_name(&branch, &taken);
+        out.status("Moving", &format!("{} -> {}", dir_name, worktree_name));
+        git::worktree_move(&bare_path, &worktree_path, &container.join(&worktree_name))?;
+        worktree_name
+    };
+
+    if let Some(identity) = configure_worktree(
+        ws,
+        &baum_manifest.repo_id,
+        &bare_path,
+        &container.join(&worktree_name),
+    )? {
+        out.status("Pinning identity", identity);
+    }
+
+    baum_manifest.add_worktree_with_local(&branch, &worktree_name, &local_branch);
+    save_baum(&container, &baum_manifest)?;
+    add_worktree_to_gitignore(&container, &worktree_name)?;
+
+    out.success(&format!(
+        "Adopted worktree for branch {} as {}",
+        branch, worktree_name
+    ));
+
+    Ok(())
+}
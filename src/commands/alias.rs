@@ -0,0 +1,133 @@
+use anyhow::{Result, bail};
+
+use crate::output::{Output, OutputFormat};
+use crate::workspace::Workspace;
+
+/// List aliases, all of them or one repository's
+pub fn alias_list(ws: &Workspace, repo_ref: Option<&str>, out: &Output) -> Result<()> {
+    let only = repo_ref.map(|r| resolve(ws, r)).transpose()?;
+
+    let mut aliases: Vec<(&str, &str)> = ws
+        .manifest
+        .repos
+        .iter()
+        .filter(|(id, _)| only.as_deref().is_none_or(|only| only == id.as_str()))
+        .flat_map(|(id, entry)| entry.aliases.iter().map(move |a| (a.as_str(), id.as_str())))
+        .collect();
+    aliases.sort();
+
+    match out.format {
+        OutputFormat::Human => {
+            if aliases.is_empty() {
+                match &only {
+                    Some(repo_id) => out.info(&format!("No aliases for {}", repo_id)),
+                    None => out.info("No aliases defined"),
+                }
+                return Ok(());
+            }
+            let width = aliases.iter().map(|(a, _)| a.len()).max().unwrap_or(0);
+            for (alias, repo_id) in &aliases {
+                println!("  {:width$}  {}", alias, repo_id, width = width);
+            }
+        }
+        OutputFormat::Json => {
+            let entries: Vec<_> = aliases
+                .iter()
+                .map(|(alias, repo_id)| serde_json::json!({ "alias": alias, "repo": repo_id }))
+                .collect();
+            out.print_json(&entries)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Give a repository another alias
+///
+/// An alias can't be another repo's ID or alias. One that equals the
+/// name of other repos (`tools`, `owner/tools`) is allowed but takes
+/// precedence over fuzzy resolution, so those repos lose that shorthand.
+pub fn alias_add(ws: &mut Workspace, repo_ref: &str, alias: &str, out: &Output) -> Result<()> {
+    out.require_human("alias add")?;
+
+    if alias.is_empty() || alias.chars().any(char::is_whitespace) {
+        bail!(
+            "invalid alias '{}': must be non-empty without whitespace",
+            alias
+        );
+    }
+    let repo_id = resolve(ws, repo_ref)?;
+
+    if ws.manifest.repos[&repo_id]
+        .aliases
+        .iter()
+        .any(|a| a == alias)
+    {
+        out.info(&format!("{} is already an alias of {}", alias, repo_id));
+        return Ok(());
+    }
+    if ws.manifest.repos.contains_key(alias) {
+        bail!("alias '{}' is a repository ID", alias);
+    }
+    if let Some((owner, _)) = ws
+        .manifest
+        .repos
+        .iter()
+        .find(|(_, entry)| entry.aliases.iter().any(|a| a == alias))
+    {
+        bail!("alias '{}' already in use by repository: {}", alias, owner);
+    }
+
+    let shadowed: Vec<&str> = ws
+        .manifest
+        .name_matches(alias)
+        .into_iter()
+        .filter(|id| *id != repo_id)
+        .collect();
+    if !shadowed.is_empty() {
+        out.warn(&format!(
+            "alias '{}' shadows {}; `{}` now always means {}",
+            alias,
+            shadowed.join(", "),
+            alias,
+            repo_id
+        ));
+    }
+
+    ws.manifest
+        .repos
+        .get_mut(&repo_id)
+        .expect("resolved repo is registered")
+        .aliases
+        .push(alias.to_string());
+    ws.save_manifest()?;
+
+    out.success(&format!("Added alias {} for {}", alias, repo_id));
+    Ok(())
+}
+
+/// Drop one of a repository's aliases
+pub fn alias_remove(ws: &mut Workspace, repo_ref: &str, alias: &str, out: &Output) -> Result<()> {
+    out.require_human("alias remove")?;
+
+    let repo_id = resolve(ws, repo_ref)?;
+    let entry = ws
+        .manifest
+        .repos
+        .get_mut(&repo_id)
+        .expect("resolved repo is registered");
+    let Some(index) = entry.aliases.iter().position(|a| a == alias) else {
+        bail!("'{}' is not an alias of {}", alias, repo_id);
+    };
+    entry.aliases.remove(index);
+    ws.save_manifest()?;
+
+    out.success(&format!("Removed alias {} from {}", alias, repo_id));
+    Ok(())
+}
+
+fn resolve(ws: &Workspace, repo_ref: &str) -> Result<String> {
+    ws.resolve_repo(repo_ref)
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow::anyhow!("repository not found: {}", repo_ref))
+}
@@ -0,0 +1,200 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::output::{JSON_SCHEMA_VERSION, Output, OutputFormat};
+
+/// A field of a command's JSON output
+#[derive(Serialize)]
+pub struct JsonField {
+    /// Path within the document: `a.b` for nested objects, `a[].b` for
+    /// the items of a list, `a.*.b` for the values of a map
+    pub name: &'static str,
+    /// Left out when it doesn't apply (e.g. `status` without `--verify`)
+    pub optional: bool,
+}
+
+const fn field(name: &'static str) -> JsonField {
+    JsonField {
+        name,
+        optional: false,
+    }
+}
+
+const fn optional(name: &'static str) -> JsonField {
+    JsonField {
+        name,
+        optional: true,
+    }
+}
+
+/// The commands whose JSON output is versioned, with their fields
+pub const JSON_SCHEMAS: &[(&str, &[JsonField])] = &[
+    (
+        "worktrees",
+        &[
+            field("schema_version"),
+            field("worktrees[].repo_id"),
+            field("worktrees[].container"),
+            field("worktrees[].branch"),
+            field("worktrees[].path"),
+            optional("worktrees[].status"),
+            optional("worktrees[].lock_reason"),
+            optional("worktrees[].created_at"),
+            optional("worktrees[].last_used"),
+            field("baums[].container"),
+            field("baums[].repo_id"),
+            optional("baums[].owner"),
+            field("baums[].problems"),
+        ],
+    ),
+    (
+        "status",
+        &[
+            field("schema_version"),
+            field("workspace.standalone"),
+            field("workspace.clean"),
+            field("workspace.ahead"),
+            field("workspace.behind"),
+            field("last_sync"),
+            field("repos_count"),
+            field("repos[].repo_id"),
+            field("repos[].cloned"),
+            field("repos[].branches"),
+            field("repos[].branches_behind"),
+            field("repos[].last_fetch"),
+            field("baums_count"),
+            field("worktrees_count"),
+            optional("owner"),
+            optional("group"),
+        ],
+    ),
+    (
+        "repo list",
+        &[
+            field("schema_version"),
+            field("repos.*.lfs"),
+            field("repos.*.depth"),
+            field("repos.*.filter"),
+            optional("repos.*.tags"),
+            optional("repos.*.url"),
+            optional("repos.*.upstream"),
+            optional("repos.*.reference"),
+            optional("repos.*.aliases"),
+            optional("repos.*.git_config"),
+            optional("repos.*.identity"),
+            optional("repos.*.branching"),
+            optional("repos.*.group"),
+            optional("repos.*.size"),
+            optional("repos.*.post_fetch"),
+        ],
+    ),
+    (
+        "prune --branches",
+        &[
+            field("schema_version"),
+            field("dry_run"),
+            field("branches[].repo_id"),
+            field("branches[].branch"),
+            field("branches[].baum_id"),
+            field("branches[].logical_branch"),
+            field("branches[].reason"),
+            field("branches[].unpushed"),
+            field("branches[].action"),
+            optional("branches[].error"),
+        ],
+    ),
+    (
+        "here",
+        &[
+            field("schema_version"),
+            field("container"),
+            field("worktree"),
+            field("repo_id"),
+            field("baum_id"),
+            field("branch"),
+            field("local_branch"),
+            field("upstream"),
+        ],
+    ),
+    (
+        "lint",
+        &[
+            field("schema_version"),
+            field("valid"),
+            field("errors"),
+            field("warnings"),
+            field("baums"),
+            field("findings[].severity"),
+            field("findings[].file"),
+            field("findings[].message"),
+        ],
+    ),
+    (
+        "doctor",
+        &[
+            field("schema_version"),
+            field("healthy"),
+            field("errors"),
+            field("warnings"),
+            field("baums"),
+            field("issues[].severity"),
+            field("issues[].message"),
+            field("issues[].fixable"),
+            optional("issues[].fixed"),
+            optional("issues[].fix_error"),
+        ],
+    ),
+];
+
+/// Report what this wald supports, for tools built on its JSON output
+///
+/// `commands` holds every command path (e.g. `repo add`). Field names are
+/// given in the output's vocabulary, as the commands print them.
+pub fn api_capabilities(commands: &[String], out: &Output) -> Result<()> {
+    match out.format {
+        OutputFormat::Human => {
+            println!("wald {}", env!("CARGO_PKG_VERSION"));
+            println!("JSON schema version: {}", JSON_SCHEMA_VERSION);
+            println!();
+            println!("Commands:");
+            for command in commands {
+                println!("  {}", command);
+            }
+            println!();
+            println!("Versioned JSON output:");
+            for (command, fields) in JSON_SCHEMAS {
+                println!("  {}", command);
+                for field in *fields {
+                    let note = if field.optional { " (optional)" } else { "" };
+                    println!("    {}{}", out.vocabulary.field_path(field.name), note);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let schemas: serde_json::Map<_, _> = JSON_SCHEMAS
+                .iter()
+                .map(|(command, fields)| {
+                    let fields: Vec<_> = fields
+                        .iter()
+                        .map(|field| {
+                            serde_json::json!({
+                                "name": out.vocabulary.field_path(field.name),
+                                "optional": field.optional,
+                            })
+                        })
+                        .collect();
+                    (command.to_string(), serde_json::json!(fields))
+                })
+                .collect();
+            let capabilities = serde_json::json!({
+                "schema_version": JSON_SCHEMA_VERSION,
+                "version": env!("CARGO_PKG_VERSION"),
+                "vocabulary": out.vocabulary,
+                "commands": commands,
+                "json": schemas,
+            });
+            out.print_json(&capabilities)?;
+        }
+    }
+    Ok(())
+}
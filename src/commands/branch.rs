@@ -2,19 +2,30 @@ use std::path::PathBuf;
 
 use anyhow::{Result, bail};
 
+use crate::bulk::BulkResult;
 use crate::git;
-use crate::naming::worktree_dir_name;
+use crate::id::format_wald_branch;
+use crate::naming::unique_worktree_dir_name;
 use crate::output::Output;
+use crate::types::{BaumManifest, Branching};
 use crate::workspace::baum::{load_baum, save_baum};
 use crate::workspace::gitignore::{add_worktree_to_gitignore, ensure_gitignore_section};
-use crate::workspace::{Workspace, collect_baum_ids, is_baum, validate_workspace_path};
+use crate::workspace::{
+    Tracking, Workspace, configure_tracking, configure_worktree, ensure_force_allowed,
+    ensure_tracked_branch, find_enclosing_baum, lock_baum, validate_workspace_path,
+};
 
 /// Options for branch command
 pub struct BranchOptions {
     pub baum_path: PathBuf,
-    pub branch: String,
+    /// Branches to add a worktree for, in order
+    pub branches: Vec<String>,
     pub force: bool,
+    /// Let --force overwrite branches of protected logical branches
+    pub yes_protected: bool,
     pub reuse: bool,
+    /// Track `<remote>[/<branch>]` instead of `origin/<branch>`
+    pub track: Option<String>,
 }
 
 impl BranchOptions {
@@ -29,75 +40,196 @@ impl BranchOptions {
     }
 }
 
-/// Add a worktree for a branch to an existing baum
+/// Add worktrees for branches to an existing baum
+///
+/// The baum is looked up, locked and its manifest written once for all
+/// branches. With several branches a failed one doesn't stop the others;
+/// the failures are listed at the end.
 pub fn branch(ws: &Workspace, opts: BranchOptions, out: &Output) -> Result<()> {
     out.require_human("branch")?;
+    if opts.branches.is_empty() {
+        bail!("no branches given");
+    }
 
     // Resolve path relative to workspace (with path traversal protection)
-    let container = validate_workspace_path(&ws.root, &opts.baum_path)?;
-
-    // Check if it's a baum
-    if !is_baum(&container) {
+    let path = validate_workspace_path(&ws.root, &opts.baum_path)?;
+
+    // A directory inside a baum (`wald branch . <branch>` from a
+    // worktree) stands for that baum
+    let container = if path.is_dir() {
+        find_enclosing_baum(&path, Some(&ws.root))
+    } else {
+        None
+    };
+    let Some(container) = container else {
         bail!(
             "not a baum: {} (.baum directory not found)",
-            container.display()
+            ws.display_path(&path)
         );
-    }
+    };
 
     // Ensure workspace-level .gitignore has wald section
     ensure_gitignore_section(&ws.root)?;
 
+    // Hold the baum while its manifest is read, changed, and written back
+    let _lock = lock_baum(&container, "branch")?;
+
     // Load baum manifest
     let mut baum_manifest = load_baum(&container)?;
 
-    // Check if branch already has a worktree
-    if baum_manifest
-        .worktrees
-        .iter()
-        .any(|wt| wt.branch == opts.branch)
-    {
+    // Get bare repo path
+    let bare_path = ws.bare_repo_path(&baum_manifest.repo_id)?;
+    if !bare_path.exists() {
+        bail!("bare repo not found: {}", ws.display_path(&bare_path));
+    }
+
+    let tracking = opts.track.as_deref().map(Tracking::parse).transpose()?;
+    let branching = ws.manifest.branching(&baum_manifest.repo_id);
+    if tracking.is_some() && branching == Branching::Direct {
         bail!(
-            "worktree for branch '{}' already exists in baum",
-            opts.branch
+            "--track is not available for {}: it uses direct branching",
+            baum_manifest.repo_id
         );
     }
+    if tracking.as_ref().is_some_and(|t| t.branch.is_some()) && opts.branches.len() > 1 {
+        bail!("--track with a branch can only be used when adding a single branch");
+    }
 
-    // Get bare repo path
-    let bare_path = ws.bare_repo_path(&baum_manifest.repo_id)?;
-    if !bare_path.exists() {
-        bail!("bare repo not found: {}", bare_path.display());
+    // Ensure the baum has an ID (generate if legacy baum)
+    let baum_id = ws.ensure_baum_id(&mut baum_manifest).to_string();
+
+    let target = Target {
+        container,
+        bare_path,
+        baum_id,
+        branching,
+        tracking,
+    };
+
+    if let [branch] = opts.branches.as_slice() {
+        add_worktree(ws, &target, &mut baum_manifest, branch, &opts, out)?;
+        return save_baum(&target.container, &baum_manifest);
+    }
+
+    let mut results = BulkResult::new("branch", "add", opts.branches.len());
+    let mut added = 0;
+    for branch in &opts.branches {
+        let result = add_worktree(ws, &target, &mut baum_manifest, branch, &opts, out);
+        if results.record(branch, result, out).is_some() {
+            added += 1;
+        }
     }
 
+    // Keep the worktrees that were added, even if others failed
+    save_baum(&target.container, &baum_manifest)?;
+    out.info(&format!(
+        "Added {} of {} worktree(s)",
+        added,
+        opts.branches.len()
+    ));
+
+    results.finish(None)
+}
+
+/// The baum that `branch` adds worktrees to
+struct Target {
+    container: PathBuf,
+    bare_path: PathBuf,
+    baum_id: String,
+    branching: Branching,
+    tracking: Option<Tracking>,
+}
+
+/// Add the worktree for one branch, recording it in `baum_manifest`
+/// (which the caller saves)
+fn add_worktree(
+    ws: &Workspace,
+    target: &Target,
+    baum_manifest: &mut BaumManifest,
+    branch: &str,
+    opts: &BranchOptions,
+    out: &Output,
+) -> Result<()> {
+    let bare_path = &target.bare_path;
+
+    // Check if branch already has a worktree
+    if baum_manifest.worktrees.iter().any(|wt| wt.branch == branch) {
+        bail!("worktree for branch '{}' already exists in baum", branch);
+    }
+
+    let upstream = match &target.tracking {
+        Some(tracking) => {
+            ensure_tracked_branch(ws, &baum_manifest.repo_id, bare_path, tracking, branch)?;
+            tracking.upstream_for(branch)
+        }
+        None => format!("origin/{}", branch),
+    };
+
     // Create worktree
-    let worktree_name = worktree_dir_name(&opts.branch);
-    let worktree_path = container.join(&worktree_name);
+    let taken = baum_manifest
+        .worktrees
+        .iter()
+        .map(|wt| wt.path.clone())
+        .collect();
+    let worktree_name = unique_worktree_dir_name(branch, &taken);
+    let worktree_path = target.container.join(&worktree_name);
 
     out.status(
         "Adding worktree",
-        &format!("{} -> {}", opts.branch, worktree_name),
+        &format!("{} -> {}", branch, worktree_name),
     );
 
-    // Ensure the baum has an ID (generate if legacy baum)
-    let existing_ids = collect_baum_ids(&ws.root);
-    let baum_id = baum_manifest.ensure_id(&existing_ids).to_string();
+    if opts.force {
+        let existing = match target.branching {
+            Branching::Tracking => format_wald_branch(&target.baum_id, branch),
+            Branching::Direct => branch.to_string(),
+        };
+        ensure_force_allowed(ws, bare_path, branch, &existing, opts.yes_protected)?;
+    }
 
-    // Add worktree with tracking branch (wald/<baum_id>/<branch>)
-    let local_branch = git::add_worktree_with_tracking_mode(
-        &bare_path,
+    // Add worktree with tracking branch (wald/<baum_id>/<branch>), or
+    // the branch itself
+    let local_branch = match target.branching {
+        Branching::Tracking => git::prepare_tracking_branch_from(
+            bare_path,
+            branch,
+            &target.baum_id,
+            opts.branch_mode(),
+            &upstream,
+        )?,
+        Branching::Direct => git::prepare_direct_branch(bare_path, branch, opts.branch_mode())?,
+    };
+    git::add_worktree_for_existing_branch(
+        bare_path,
         &worktree_path,
-        &opts.branch,
-        &baum_id,
-        opts.branch_mode(),
+        &local_branch,
+        out.show_progress(),
     )?;
 
-    // Update baum manifest with local branch info
-    baum_manifest.add_worktree_with_local(&opts.branch, &worktree_name, &local_branch);
-    save_baum(&container, &baum_manifest)?;
+    if target.tracking.is_some() {
+        configure_tracking(bare_path, &worktree_path, &local_branch, branch)?;
+        out.status(
+            "Tracking",
+            &format!("{} (pushing to origin/{})", upstream, branch),
+        );
+    }
+
+    if let Some(identity) =
+        configure_worktree(ws, &baum_manifest.repo_id, bare_path, &worktree_path)?
+    {
+        out.status("Pinning identity", identity);
+    }
+
+    // Update baum manifest with local branch info and where it started
+    let entry = baum_manifest.add_worktree_with_local(branch, &worktree_name, &local_branch);
+    entry.created_from = git::resolve_commit(bare_path, &upstream).map(|_| upstream.clone());
+    entry.created_commit = git::resolve_commit(bare_path, &local_branch);
+    entry.track = target.tracking.as_ref().map(|_| upstream);
 
     // Add to .gitignore
-    add_worktree_to_gitignore(&container, &worktree_name)?;
+    add_worktree_to_gitignore(&target.container, &worktree_name)?;
 
-    out.success(&format!("Added worktree for branch: {}", opts.branch));
+    out.success(&format!("Added worktree for branch: {}", branch));
 
     Ok(())
 }
@@ -1,11 +1,13 @@
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Instant;
 
 use anyhow::{Result, bail};
 
 use crate::commands;
 use crate::output::Output;
-use crate::workspace::Workspace;
+use crate::timings::TimedCommand;
+use crate::workspace::{Workspace, notify_finished};
 
 pub struct CloneOptions {
     pub url: String,
@@ -14,6 +16,7 @@ pub struct CloneOptions {
 
 pub fn clone(opts: CloneOptions, out: &Output) -> Result<()> {
     out.require_human("clone")?;
+    let started = Instant::now();
 
     // Determine target directory
     let dir = match &opts.dir {
@@ -30,7 +33,7 @@ pub fn clone(opts: CloneOptions, out: &Output) -> Result<()> {
     let status = Command::new("git")
         .args(["clone", &opts.url])
         .arg(&dir)
-        .status()?;
+        .timed_status()?;
 
     if !status.success() {
         bail!("git clone failed");
@@ -43,10 +46,19 @@ pub fn clone(opts: CloneOptions, out: &Output) -> Result<()> {
         force: false,
         push: false,
         offline: false,
+        on_conflict: commands::sync::ConflictMode::Abort,
+        // A team workspace hydrates just this user's baums, as sync does
+        mine: false,
+        all: false,
     };
 
     out.status("Hydrating", "cloning missing repos");
-    commands::sync(&mut ws, sync_opts, out)?;
+    let result = commands::sync(&mut ws, sync_opts, out);
+    let error = result.as_ref().err().map(|e| format!("{:#}", e));
+    if let Err(e) = notify_finished(&ws, "clone", started.elapsed(), error.as_deref()) {
+        out.warn(&format!("Failed to send notification: {:#}", e));
+    }
+    result?;
 
     out.success(&format!(
         "Cloned and hydrated workspace at {}",
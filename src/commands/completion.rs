@@ -0,0 +1,249 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::output::Output;
+
+/// First line of every file `--install` writes, so reinstalls can tell
+/// their own files from someone else's
+const MARKER: &str = "# Installed by `wald completion --install`; re-run it to update";
+
+/// Shells `--install` knows where to put files for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl InstallShell {
+    /// Map clap_complete's shell name (e.g. "bash")
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "bash" => Ok(InstallShell::Bash),
+            "zsh" => Ok(InstallShell::Zsh),
+            "fish" => Ok(InstallShell::Fish),
+            other => bail!(
+                "--install supports bash, zsh and fish; redirect `wald completion {}` to a file instead",
+                other
+            ),
+        }
+    }
+}
+
+/// Options for completion --install
+pub struct CompletionInstallOptions {
+    pub shell: InstallShell,
+    /// Also install the `wald jump` shell function
+    pub wrapper: bool,
+    /// Overwrite files that weren't written by wald
+    pub force: bool,
+}
+
+/// Where one shell's files go
+struct Locations {
+    completion: PathBuf,
+    wrapper: PathBuf,
+    /// Startup file that must source the wrapper (fish autoloads it)
+    rc: Option<PathBuf>,
+}
+
+fn locations(shell: InstallShell, home: &Path) -> Locations {
+    let data_home = env_dir("XDG_DATA_HOME").unwrap_or_else(|| home.join(".local/share"));
+    let config_home = env_dir("XDG_CONFIG_HOME").unwrap_or_else(|| home.join(".config"));
+    let zdotdir = env_dir("ZDOTDIR").unwrap_or_else(|| home.to_path_buf());
+
+    match shell {
+        // Loaded on demand by bash-completion
+        InstallShell::Bash => Locations {
+            completion: data_home.join("bash-completion/completions/wald"),
+            wrapper: data_home.join("wald/wald.bash"),
+            rc: Some(home.join(".bashrc")),
+        },
+        // Needs the directory on $fpath before compinit
+        InstallShell::Zsh => Locations {
+            completion: zdotdir.join(".zfunc/_wald"),
+            wrapper: data_home.join("wald/wald.zsh"),
+            rc: Some(zdotdir.join(".zshrc")),
+        },
+        InstallShell::Fish => Locations {
+            completion: config_home.join("fish/completions/wald.fish"),
+            wrapper: config_home.join("fish/functions/wald.fish"),
+            rc: None,
+        },
+    }
+}
+
+/// A directory from the environment; unset and empty are the same
+fn env_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Install the completion script (and optionally the shell wrapper) for a shell
+pub fn install_completion(
+    opts: CompletionInstallOptions,
+    script: &str,
+    out: &Output,
+) -> Result<()> {
+    let home = env::var_os("HOME")
+        .map(PathBuf::from)
+        .context("HOME is not set; can't tell where to install")?;
+    let locations = locations(opts.shell, &home);
+
+    let completion = match opts.shell {
+        // zsh reads `#compdef` from the first line
+        InstallShell::Zsh => {
+            let (compdef, rest) = script.split_once('\n').unwrap_or((script, ""));
+            format!("{}\n{}\n{}", compdef, MARKER, rest)
+        }
+        _ => format!("{}\n{}", MARKER, script),
+    };
+    // Check every target before writing any
+    let mut files = vec![(&locations.completion, completion)];
+    if opts.wrapper {
+        files.push((&locations.wrapper, wrapper_script(opts.shell)));
+    }
+    for (path, _) in &files {
+        check_conflict(path, opts.force)?;
+    }
+
+    for (path, content) in &files {
+        if fs::read_to_string(path).is_ok_and(|existing| existing == *content) {
+            out.info(&format!("Up to date: {}", path.display()));
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create directory: {}", parent.display()))?;
+        }
+        fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))?;
+        out.status("Installed", &path.display().to_string());
+    }
+
+    if opts.wrapper
+        && let Some(rc) = &locations.rc
+    {
+        source_from_rc(rc, &locations.wrapper, out)?;
+    }
+
+    if opts.shell == InstallShell::Zsh {
+        let fpath_dir = locations.completion.parent().unwrap_or(&home);
+        let configured = locations.rc.as_ref().is_some_and(|rc| {
+            fs::read_to_string(rc).is_ok_and(|content| content.contains(".zfunc"))
+        });
+        if !configured {
+            out.info(&format!(
+                "Add `fpath+=({})` before `compinit` in your .zshrc",
+                fpath_dir.display()
+            ));
+        }
+    }
+
+    check_path(out);
+    out.success("Open a new shell to use the completions");
+    Ok(())
+}
+
+/// Refuse to replace a file wald didn't write, unless forced
+fn check_conflict(path: &Path, force: bool) -> Result<()> {
+    let Ok(existing) = fs::read_to_string(path) else {
+        return Ok(());
+    };
+    if !force && !existing.lines().take(2).any(|line| line == MARKER) {
+        bail!(
+            "{} exists and wasn't installed by wald; use --force to replace it",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Make a shell startup file source the wrapper, once
+fn source_from_rc(rc: &Path, wrapper: &Path, out: &Output) -> Result<()> {
+    let line = format!("[ -f \"{0}\" ] && . \"{0}\"", wrapper.display());
+    let mut content = fs::read_to_string(rc).unwrap_or_default();
+    if content.contains(&line) {
+        return Ok(());
+    }
+    if !content.is_empty() {
+        content.push_str(if content.ends_with('\n') {
+            "\n"
+        } else {
+            "\n\n"
+        });
+    }
+    content.push_str(&format!(
+        "# wald shell integration (`wald jump`)\n{}\n",
+        line
+    ));
+    fs::write(rc, content).with_context(|| format!("failed to write {}", rc.display()))?;
+    out.status("Updated", &rc.display().to_string());
+    Ok(())
+}
+
+/// Shell function that adds `wald jump <repo> [branch]` on top of the binary
+fn wrapper_script(shell: InstallShell) -> String {
+    let body = match shell {
+        InstallShell::Bash | InstallShell::Zsh => {
+            r#"# `wald jump <repo> [branch]` changes to the repo's worktree; everything
+# else runs the wald binary
+wald() {
+    if [ "$1" = "jump" ]; then
+        shift
+        local dir
+        dir="$(command wald which "$@" | head -n 1)" || return
+        [ -n "$dir" ] && cd "$dir"
+    else
+        command wald "$@"
+    fi
+}
+"#
+        }
+        InstallShell::Fish => {
+            r#"# `wald jump <repo> [branch]` changes to the repo's worktree; everything
+# else runs the wald binary
+function wald --wraps wald
+    if test "$argv[1]" = jump
+        set -l dir (command wald which $argv[2..-1] | head -n 1)
+        and test -n "$dir"
+        and cd $dir
+    else
+        command wald $argv
+    end
+end
+"#
+        }
+    };
+    format!("{}\n{}", MARKER, body)
+}
+
+/// Warn when the shell wouldn't find this binary as `wald`
+fn check_path(out: &Output) {
+    let on_path = env::var_os("PATH")
+        .map(|path| {
+            env::split_paths(&path)
+                .map(|dir| dir.join("wald"))
+                .find(|candidate| candidate.is_file())
+        })
+        .unwrap_or_default();
+    let current = env::current_exe().ok().and_then(|p| p.canonicalize().ok());
+
+    match on_path {
+        None => out.warn("`wald` is not on your PATH; completions and `wald jump` call it by name"),
+        Some(found) => {
+            if let Some(current) = current
+                && found.canonicalize().is_ok_and(|found| found != current)
+            {
+                out.warn(&format!(
+                    "`wald` on your PATH is {}, not this binary ({})",
+                    found.display(),
+                    current.display()
+                ));
+            }
+        }
+    }
+}
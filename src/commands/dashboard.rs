@@ -0,0 +1,137 @@
+use anyhow::Result;
+
+use super::status::{repo_freshness, workspace_git_status};
+use crate::output::{JSON_SCHEMA_VERSION, Output, OutputFormat};
+use crate::time::format_iso;
+use crate::workspace::baum::load_baum;
+use crate::workspace::{Workspace, find_baum_dirs};
+
+/// Errors of the last doctor run listed before the rest are summed up
+const ERRORS_SHOWN: usize = 5;
+
+/// Summarize the workspace: its sync status, repo and baum counts, and
+/// what the last `wald doctor` found (`wald` without a command)
+pub fn dashboard(ws: &Workspace, out: &Output) -> Result<()> {
+    // A workspace repo that can't be inspected shouldn't hide the rest
+    let git_status = if ws.config.standalone {
+        None
+    } else {
+        workspace_git_status(ws).ok()
+    };
+
+    let repos = repo_freshness(ws, &|_| true);
+    let not_cloned = repos.iter().filter(|r| !r.cloned).count();
+    let behind = repos.iter().filter(|r| r.behind > 0).count();
+
+    let mut baum_count = 0;
+    let mut worktree_count = 0;
+    for baum_path in find_baum_dirs(&ws.root, &ws.root, &ws.config) {
+        baum_count += 1;
+        if let Ok(baum) = load_baum(&baum_path) {
+            worktree_count += baum.worktrees.len();
+        }
+    }
+
+    let doctor = ws.state.last_doctor.as_ref();
+
+    match out.format {
+        OutputFormat::Human => {
+            let workspace = match git_status {
+                None if ws.config.standalone => "standalone, sync disabled".to_string(),
+                None => "git status unknown".to_string(),
+                Some((clean, ahead, behind)) => {
+                    let changes = if clean {
+                        "clean"
+                    } else {
+                        "uncommitted changes"
+                    };
+                    let sync = match (ahead, behind) {
+                        (0, 0) => "up to date".to_string(),
+                        (a, 0) => format!("{} ahead", a),
+                        (0, b) => format!("{} behind", b),
+                        (a, b) => format!("diverged, {} ahead, {} behind", a, b),
+                    };
+                    format!("{}, {}", changes, sync)
+                }
+            };
+            println!("Workspace: {} ({})", ws.root.display(), workspace);
+
+            let mut repo_notes = Vec::new();
+            if not_cloned > 0 {
+                repo_notes.push(format!("{} not cloned", not_cloned));
+            }
+            if behind > 0 {
+                repo_notes.push(format!("{} with branches behind", behind));
+            }
+            if repo_notes.is_empty() {
+                println!("Repos: {} registered", repos.len());
+            } else {
+                println!(
+                    "Repos: {} registered ({})",
+                    repos.len(),
+                    repo_notes.join(", ")
+                );
+            }
+            println!(
+                "{}",
+                out.words(&format!(
+                    "Baums: {} planted ({} worktrees)",
+                    baum_count, worktree_count
+                ))
+            );
+
+            match doctor {
+                None => println!("Doctor: not run yet"),
+                Some(check) => {
+                    let when = out.time(check.checked_at);
+                    if check.errors.is_empty() {
+                        println!(
+                            "Doctor: no errors, {} warning(s) as of {}",
+                            check.warnings, when
+                        );
+                    } else {
+                        println!(
+                            "Doctor: {} error(s), {} warning(s) as of {}",
+                            check.errors.len(),
+                            check.warnings,
+                            when
+                        );
+                        for error in check.errors.iter().take(ERRORS_SHOWN) {
+                            println!("  {}", error);
+                        }
+                        if check.errors.len() > ERRORS_SHOWN {
+                            println!("  ... and {} more", check.errors.len() - ERRORS_SHOWN);
+                        }
+                    }
+                }
+            }
+
+            println!();
+            println!("`wald status` and `wald doctor` for details, `wald help` for commands");
+        }
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "schema_version": JSON_SCHEMA_VERSION,
+                "workspace": {
+                    "standalone": ws.config.standalone,
+                    "clean": git_status.map(|(clean, _, _)| clean),
+                    "ahead": git_status.map(|(_, ahead, _)| ahead),
+                    "behind": git_status.map(|(_, _, behind)| behind),
+                },
+                "repos_count": repos.len(),
+                "repos_not_cloned": not_cloned,
+                "repos_behind": behind,
+                "baums_count": baum_count,
+                "worktrees_count": worktree_count,
+                "doctor": doctor.map(|check| serde_json::json!({
+                    "checked_at": format_iso(check.checked_at),
+                    "errors": check.errors,
+                    "warnings": check.warnings,
+                })),
+            });
+            out.print_json(&json)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use crate::commands::init::InitOptions;
+use crate::commands::plant::PlantOptions;
+use crate::commands::repo::RepoAddOptions;
+use crate::output::Output;
+use crate::timings::TimedCommand;
+use crate::workspace::Workspace;
+
+/// Host of the fixture's repo IDs; its clone URLs are rewritten to the
+/// fixture's own remotes
+pub const FIXTURE_HOST: &str = "fixture.local";
+
+/// Options for `debug make-fixture`
+pub struct FixtureOptions {
+    /// Directory to build the fixture in (missing or empty)
+    pub dir: PathBuf,
+    /// Number of repos to register and plant
+    pub repos: usize,
+}
+
+/// A workspace built by [`build_fixture`]
+#[derive(Debug)]
+pub struct Fixture {
+    /// The workspace root (`<dir>/workspace`)
+    pub root: PathBuf,
+    /// The bare repos standing in for the forge (`<dir>/remotes`)
+    pub remotes: PathBuf,
+    /// IDs of the registered repos, each planted with a `main` worktree at
+    /// a container named after the repo
+    pub repos: Vec<String>,
+}
+
+/// Build a fixture workspace and describe it
+pub fn make_fixture(opts: FixtureOptions, out: &Output) -> Result<()> {
+    out.require_human("debug make-fixture")?;
+
+    let fixture = build_fixture(&opts, out)?;
+    out.success(&format!(
+        "Built fixture workspace at {}",
+        fixture.root.display()
+    ));
+    out.info(&format!("  remotes: {}", fixture.remotes.display()));
+    for repo_id in &fixture.repos {
+        out.info(&format!("  repo:    {}", repo_id));
+    }
+    Ok(())
+}
+
+/// Build a workspace with local remotes, registered repos and planted baums
+///
+/// Everything is offline: each repo's remote is a bare repo under
+/// `<dir>/remotes` with a `main` and a `dev` branch, reached through the
+/// workspace's `url_rewrites`. Commits have fixed authors and dates, so
+/// two fixtures have the same history.
+pub fn build_fixture(opts: &FixtureOptions, out: &Output) -> Result<Fixture> {
+    if opts.repos == 0 {
+        bail!("a fixture needs at least one repo");
+    }
+    let dir = &opts.dir;
+    if dir.exists() {
+        let mut entries =
+            fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?;
+        if entries.next().is_some() {
+            bail!("fixture directory is not empty: {}", dir.display());
+        }
+    }
+    let root = dir.join("workspace");
+    let remotes = dir.join("remotes");
+    fs::create_dir_all(&root).with_context(|| format!("failed to create {}", root.display()))?;
+    fs::create_dir_all(&remotes)
+        .with_context(|| format!("failed to create {}", remotes.display()))?;
+    let root = root.canonicalize()?;
+    let remotes = remotes.canonicalize()?;
+
+    let names: Vec<String> = (1..=opts.repos).map(|i| format!("repo-{}", i)).collect();
+    for name in &names {
+        create_remote(&remotes, name)?;
+    }
+
+    crate::commands::init(
+        InitOptions {
+            path: Some(root.clone()),
+            force: false,
+            no_git: false,
+            no_sync: false,
+            remote: None,
+            create_github: None,
+        },
+        out,
+    )?;
+
+    let mut ws = Workspace::load_from(root.clone())?;
+    ws.config.url_rewrites = BTreeMap::from([(
+        format!("git@{}:test/", FIXTURE_HOST),
+        format!("file://{}/", remotes.display()),
+    )]);
+    ws.save_config()?;
+
+    let mut repos = Vec::new();
+    for name in &names {
+        let repo_id = format!("{}/test/{}", FIXTURE_HOST, name);
+        crate::commands::repo_add(&mut ws, add_options(&repo_id), out)?;
+        crate::commands::plant(&mut ws, plant_options(&repo_id, name), out)?;
+        repos.push(repo_id);
+    }
+
+    Ok(Fixture {
+        root,
+        remotes,
+        repos,
+    })
+}
+
+/// Create the bare remote `<remotes>/<name>.git`: two commits on `main`
+/// and one more on `dev`
+fn create_remote(remotes: &Path, name: &str) -> Result<()> {
+    let work = remotes.join(format!("{}.work", name));
+    run_git(
+        remotes,
+        &[
+            "init",
+            "--quiet",
+            "--initial-branch=main",
+            &format!("{}.work", name),
+        ],
+    )?;
+
+    fs::write(work.join("README.md"), format!("# {}\n", name))?;
+    commit(&work, "Initial commit", 1)?;
+    fs::write(
+        work.join("README.md"),
+        format!("# {}\n\nProject description\n", name),
+    )?;
+    commit(&work, "Add description", 2)?;
+    run_git(&work, &["checkout", "--quiet", "-b", "dev"])?;
+    fs::write(work.join("feature.txt"), "Feature in progress\n")?;
+    commit(&work, "Start feature development", 3)?;
+    run_git(&work, &["checkout", "--quiet", "main"])?;
+
+    let bare = format!("{}.git", name);
+    run_git(
+        remotes,
+        &[
+            "clone",
+            "--bare",
+            "--quiet",
+            &format!("{}.work", name),
+            &bare,
+        ],
+    )?;
+    // Let partial clones through, as forges do
+    run_git(
+        &remotes.join(&bare),
+        &["config", "uploadpack.allowFilter", "true"],
+    )?;
+    fs::remove_dir_all(&work).with_context(|| format!("failed to remove {}", work.display()))
+}
+
+/// Commit everything in `work` as the fixture author, `day` days after the
+/// fixture epoch
+fn commit(work: &Path, message: &str, day: u64) -> Result<()> {
+    run_git(work, &["add", "--all"])?;
+    let date = format!("{} +0000", 1_700_000_000 + day * 86_400);
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(work)
+        .args([
+            "-c",
+            "user.name=Wald Fixture",
+            "-c",
+            "user.email=fixture@wald.local",
+        ])
+        .args(["commit", "--quiet", "--no-verify", "-m", message])
+        .env("GIT_AUTHOR_DATE", &date)
+        .env("GIT_COMMITTER_DATE", &date)
+        .timed_output()
+        .context("failed to execute git commit")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git commit failed: {}", stderr.trim());
+    }
+    Ok(())
+}
+
+fn add_options(repo_id: &str) -> RepoAddOptions {
+    RepoAddOptions {
+        repo_id: repo_id.to_string(),
+        detect: false,
+        adopt: false,
+        lfs: None,
+        depth: None,
+        filter: None,
+        tags: None,
+        upstream: None,
+        reference: None,
+        aliases: Vec::new(),
+        git_config: BTreeMap::new(),
+        identity: None,
+        branching: None,
+        group: None,
+        mirror_of: None,
+        clone: true,
+        retry_clone: false,
+        manifest_file: None,
+        dry_run: false,
+    }
+}
+
+fn plant_options(repo_id: &str, container: &str) -> PlantOptions {
+    PlantOptions {
+        repo_ref: repo_id.to_string(),
+        container: PathBuf::from(container),
+        branches: vec!["main".to_string()],
+        force: false,
+        yes_protected: false,
+        reuse: false,
+        track: None,
+        into_existing: false,
+        template: None,
+        continue_on_conflict: false,
+        at: None,
+    }
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .timed_output()
+        .with_context(|| format!("failed to execute git {}", args[0]))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed: {}", args[0], stderr.trim());
+    }
+    Ok(())
+}
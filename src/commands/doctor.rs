@@ -1,25 +1,307 @@
+use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use walkdir::WalkDir;
+use anyhow::{Context, Result, bail};
+use rayon::prelude::*;
+use serde_json::json;
 
-use crate::git;
-use crate::output::Output;
-use crate::workspace::baum::load_baum;
-use crate::workspace::{Workspace, is_baum};
+use crate::bulk::BulkResult;
+use crate::filesystem;
+use crate::forge;
+use crate::git::{self, WorktreeInfo};
+use crate::humanize::short_sha;
+use crate::id::parse_wald_branch;
+use crate::output::{JSON_SCHEMA_VERSION, Output, OutputFormat};
+use crate::serve::{self, HealthSnapshot, SharedSnapshot};
+use crate::time::{format_iso, now_secs};
+use crate::timings::TimedCommand;
+use crate::types::{
+    Backup, BaumManifest, CorruptFile, DoctorCheck, RepoEntry, RepoId, SyncState, WorktreeEntry,
+    find_corrupt_files, restore_backup,
+};
+use crate::workspace::baum::{load_baum, save_baum};
+use crate::workspace::gitignore::{
+    add_worktree_to_gitignore, ensure_gitignore_section, has_gitignore_section,
+    reconcile_worktree_gitignore, update_worktree_exclude, worktree_exclude_outdated,
+    worktree_gitignore_drift, worktree_ignore_file,
+};
+use crate::workspace::{
+    WALD_DIR, Workspace, find_baum_dirs, find_stale_worktrees, forget_worktree, is_baum, lock_baum,
+    pin_identity, read_baum_lock,
+};
 
 /// Options for doctor command
 pub struct DoctorOptions {
     pub fix: bool,
+    /// Warn about worktrees with unpushed commits older than this many days
+    pub unpushed_days: Option<u64>,
+    /// Serve the results over HTTP on this address, rechecking periodically
+    pub serve: Option<String>,
+    /// Write the results to this Prometheus textfile
+    pub textfile: Option<PathBuf>,
+    /// Seconds between checks with `serve`/`textfile` (`textfile` alone: once)
+    pub interval: Option<u64>,
+    /// Fix a worktree on the wrong branch by recording that branch in the
+    /// baum manifest instead of switching the worktree back
+    pub record_branch: bool,
+    /// Report only errors, and fail if there are any (e.g. in a pre-push hook)
+    pub errors_only: bool,
+    /// Look up every registered repo on its forge (archived, moved, gone)
+    pub forge: bool,
 }
 
+/// Seconds between checks with --serve unless --interval is given
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+
 /// Check workspace health and optionally repair issues
 pub fn doctor(ws: &Workspace, opts: DoctorOptions, out: &Output) -> Result<()> {
-    out.require_human("doctor")?;
+    if opts.serve.is_some() || opts.textfile.is_some() {
+        out.require_human("doctor --serve/--textfile")?;
+        return monitor(ws, &opts, out);
+    }
+    if opts.interval.is_some() {
+        bail!("--interval only applies with --serve or --textfile");
+    }
+
+    let checks = CheckOptions {
+        unpushed: opts.unpushed_days.is_some(),
+        unpushed_days: opts.unpushed_days,
+        record_branch: opts.record_branch,
+        now: now_secs(),
+    };
+    let mut findings = check_workspace(ws, checks, Some(out));
+    if opts.forge {
+        out.status("Checking", "repositories on their forges");
+        findings.issues.extend(check_forge(ws));
+    }
+    if opts.errors_only {
+        findings.issues.retain(|i| i.severity == Severity::Error);
+    }
+    record_check(ws, &findings.issues, opts.fix);
+    if out.format == OutputFormat::Json {
+        report_json(&findings, opts.fix, out)?;
+        return fail_on_errors(&findings.issues, &opts);
+    }
+    report_human(&findings.issues, opts.fix, out)?;
+    fail_on_errors(&findings.issues, &opts)
+}
+
+/// Check a workspace that fails to load because its YAML files are
+/// damaged, restoring them from backups with `fix`
+///
+/// Returns `error` (why loading failed) if no file is damaged, or if they
+/// weren't all restored.
+pub fn doctor_unloadable(root: &Path, fix: bool, error: anyhow::Error, out: &Output) -> Result<()> {
+    let corrupt = find_corrupt_files(&root.join(WALD_DIR));
+    if corrupt.is_empty() {
+        return Err(error);
+    }
+    let findings = Findings {
+        issues: corrupt
+            .into_iter()
+            .map(|file| corrupt_file_issue(root, file))
+            .collect(),
+        baums: 0,
+        unpushed: Vec::new(),
+    };
+
+    let reported = if out.format == OutputFormat::Json {
+        report_json(&findings, fix, out)
+    } else {
+        report_human(&findings.issues, fix, out)
+    };
+
+    if !fix || !find_corrupt_files(&root.join(WALD_DIR)).is_empty() {
+        return Err(error);
+    }
+    reported?;
+    out.info("Run `wald doctor` again to check the rest of the workspace");
+    Ok(())
+}
+
+/// Why a workspace failed to load, if damaged YAML files are to blame
+pub fn corrupt_files_hint(root: &Path) -> Option<String> {
+    let corrupt = find_corrupt_files(&root.join(WALD_DIR));
+    if corrupt.is_empty() {
+        return None;
+    }
+    let files: Vec<String> = corrupt
+        .iter()
+        .map(|file| relative_display(root, &file.path))
+        .collect();
+    Some(format!(
+        "{} no longer parse(s); `wald doctor --fix` restores from backups",
+        files.join(", ")
+    ))
+}
+
+/// Print the results, applying the fixes with `fix`
+/// Print the results, applying the fixes with `fix`; fails if a fix does
+fn report_human(issues: &[Issue], fix: bool, out: &Output) -> Result<()> {
+    println!();
+    let errors = count(issues, Severity::Error);
+    let warnings = count(issues, Severity::Warning);
+
+    if errors + warnings == 0 {
+        out.success("No issues found");
+    } else {
+        println!(
+            "Found {} issue(s) ({} errors, {} warnings)",
+            errors + warnings,
+            errors,
+            warnings
+        );
+    }
+
+    let mut fixes = BulkResult::new("issue", "fix", fixable(issues));
+    if !issues.is_empty() {
+        println!();
+
+        for issue in issues {
+            println!("  [{}] {}", issue.severity.label(), issue.message);
+
+            if fix
+                && let Some(action) = &issue.fix
+                && fixes
+                    .record(&issue.message, apply_fix(action), out)
+                    .is_some()
+            {
+                println!("         Fixed!");
+            }
+        }
+
+        if !fix && issues.iter().any(|i| i.fix.is_some()) {
+            println!();
+            println!("Run with --fix to automatically repair fixable issues");
+        }
+    }
+    fixes.finish(None)
+}
+
+/// How many issues `--fix` can repair
+fn fixable(issues: &[Issue]) -> usize {
+    issues.iter().filter(|i| i.fix.is_some()).count()
+}
+
+/// A workspace file that no longer parses, fixable if it has a backup
+fn corrupt_file_issue(root: &Path, file: CorruptFile) -> Issue {
+    let name = relative_display(root, &file.path);
+    match file.backup {
+        Some(backup) => Issue {
+            severity: Severity::Error,
+            message: format!(
+                "{} doesn't parse ({}); --fix restores the backup from {}",
+                name,
+                file.error,
+                format_iso((backup.saved_at_ms / 1000) as u64)
+            ),
+            fix: Some(FixAction::RestoreBackup(file.path, backup)),
+        },
+        None => Issue {
+            severity: Severity::Error,
+            message: format!(
+                "{} doesn't parse ({}) and has no intact backup; repair it by hand",
+                name, file.error
+            ),
+            fix: None,
+        },
+    }
+}
+
+fn relative_display(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// With `--errors-only`, fail if errors were found
+fn fail_on_errors(issues: &[Issue], opts: &DoctorOptions) -> Result<()> {
+    let errors = count(issues, Severity::Error);
+    if opts.errors_only && errors > 0 {
+        bail!("wald doctor found {} error(s)", errors);
+    }
+    Ok(())
+}
+
+/// Print the results for `--json`, applying the fixes with `fix`; fails if
+/// a fix does
+fn report_json(findings: &Findings, fix: bool, out: &Output) -> Result<()> {
+    let mut fixes = BulkResult::new("issue", "fix", fixable(&findings.issues));
+    let issues: Vec<_> = findings
+        .issues
+        .iter()
+        .map(|issue| {
+            let mut entry = json!({
+                "severity": issue.severity.label().to_lowercase(),
+                "message": issue.message,
+                "fixable": issue.fix.is_some(),
+            });
+            if fix && let Some(action) = &issue.fix {
+                let result = apply_fix(action);
+                entry["fixed"] = json!(result.is_ok());
+                if let Err(e) = &result {
+                    entry["fix_error"] = json!(format!("{:#}", e));
+                }
+                fixes.record(&issue.message, result, out);
+            }
+            entry
+        })
+        .collect();
+
+    let report = json!({
+        "schema_version": JSON_SCHEMA_VERSION,
+        "healthy": count(&findings.issues, Severity::Error) == 0,
+        "errors": count(&findings.issues, Severity::Error),
+        "warnings": count(&findings.issues, Severity::Warning),
+        "baums": findings.baums,
+        "issues": issues,
+    });
+    out.print_json(&report)?;
+    fixes.finish(None)
+}
+
+/// What a check looks at beyond the workspace structure
+#[derive(Clone, Copy)]
+struct CheckOptions {
+    /// Look up the oldest unpushed commit of each worktree
+    unpushed: bool,
+    /// Warn about unpushed commits older than this many days
+    unpushed_days: Option<u64>,
+    /// Offer to record a worktree's actual branch rather than switch back
+    record_branch: bool,
+    /// Current time (seconds since the Unix epoch)
+    now: u64,
+}
+
+/// Everything one check of the workspace found
+struct Findings {
+    issues: Vec<Issue>,
+    baums: usize,
+    /// Worktrees with unpushed commits, with the oldest one's commit time
+    unpushed: Vec<(PathBuf, u64)>,
+}
+
+/// Issues and unpushed worktrees of one baum
+type BaumFindings = (Vec<Issue>, Vec<(PathBuf, u64)>);
+
+/// Run all checks; progress goes to `out` if given
+fn check_workspace(ws: &Workspace, checks: CheckOptions, out: Option<&Output>) -> Findings {
+    let status = |action: &str, target: &str| {
+        if let Some(out) = out {
+            out.status(action, target);
+        }
+    };
 
     let mut issues = Vec::new();
 
-    out.status("Checking", "workspace structure");
+    status("Checking", "workspace structure");
 
     // Check .wald directory structure
     let wald_dir = ws.wald_dir();
@@ -31,6 +313,41 @@ pub fn doctor(ws: &Workspace, opts: DoctorOptions, out: &Output) -> Result<()> {
         });
     }
 
+    // Damaged files that loading passed over (config.yaml and state.yaml
+    // fall back to defaults)
+    for file in find_corrupt_files(&wald_dir) {
+        issues.push(corrupt_file_issue(&ws.root, file));
+    }
+
+    // Standalone workspaces have no workspace repo to keep in shape
+    if !ws.config.standalone && Workspace::is_git_repo(&ws.root) {
+        issues.extend(check_workspace_repo(ws));
+    }
+
+    // Locks of other hosts can only be taken over once they are old
+    if let Some(fs_type) =
+        filesystem::fs_type(&ws.root).filter(|_| filesystem::is_network_fs(&ws.root))
+    {
+        issues.push(Issue {
+            severity: Severity::Info,
+            message: format!(
+                "Workspace is on a network filesystem ({}); baum locks held on other hosts expire after a day",
+                fs_type
+            ),
+            fix: None,
+        });
+    }
+    if ws.is_read_only() {
+        issues.push(Issue {
+            severity: Severity::Info,
+            message: format!(
+                "{} is read-only; commands that change the workspace are refused",
+                ws.display_path(&ws.wald_dir())
+            ),
+            fix: None,
+        });
+    }
+
     // Check repos directory
     let repos_dir = ws.repos_dir();
     if !repos_dir.exists() {
@@ -41,132 +358,671 @@ pub fn doctor(ws: &Workspace, opts: DoctorOptions, out: &Output) -> Result<()> {
         });
     }
 
-    out.status("Checking", "registered repositories");
+    status("Checking", "registered repositories");
+
+    issues.extend(check_bare_paths(ws));
 
     // Check each registered repo
-    for repo_id in ws.manifest.repos.keys() {
-        if let Ok(bare_path) = ws.bare_repo_path(repo_id)
-            && !bare_path.exists()
-        {
+    for (repo_id, entry) in &ws.manifest.repos {
+        let Ok(bare_path) = ws.bare_repo_path(repo_id) else {
+            continue;
+        };
+        if !bare_path.exists() {
+            // Registered, but the clone failed or was skipped with --no-clone.
+            // Not cloned onto the disk of a `bare_path` that isn't there.
+            let unmounted = entry.bare_path.is_some()
+                && !bare_path.parent().is_some_and(|parent| parent.is_dir());
+            let fix = match (RepoId::parse(repo_id), ws.clone_options(repo_id, entry)) {
+                (Ok(id), Ok(opts)) if !unmounted => Some(FixAction::Clone(id, bare_path, opts)),
+                _ => None,
+            };
             issues.push(Issue {
                 severity: Severity::Warning,
-                message: format!("Bare repo not cloned: {}", repo_id),
-                fix: None,
+                message: format!(
+                    "Bare repo not cloned: {} (`wald repo add --retry-clone {}` clones it)",
+                    repo_id, repo_id
+                ),
+                fix,
             });
+            continue;
         }
+        issues.extend(check_alternates(ws, repo_id, entry, &bare_path));
     }
 
-    out.status("Checking", "planted baums");
-
-    // Find and check all baums
-    for entry in WalkDir::new(&ws.root)
-        .follow_links(false)
+    // Registrations of worktrees removed by hand outside any baum; those
+    // of baum worktrees are reported with the baum
+    for stale in find_stale_worktrees(ws)
         .into_iter()
-        .filter_entry(|e| {
-            // Skip .git directories, .wald/repos, and _*.wt worktree directories
-            let name = e.file_name().to_string_lossy();
-            if name == ".git" {
-                return false;
-            }
-            if name == "repos"
-                && e.path()
-                    .parent()
-                    .map(|p| p.ends_with(".wald"))
-                    .unwrap_or(false)
-            {
-                return false;
+        .filter(|s| s.baum.is_none())
+    {
+        issues.push(Issue {
+            severity: Severity::Warning,
+            message: format!(
+                "Git still registers removed worktree {} in {}",
+                ws.display_path(&stale.path),
+                stale.repo_id
+            ),
+            fix: Some(FixAction::PruneWorktrees(stale.bare_path)),
+        });
+    }
+
+    // Collect baums first; the walk itself is cheap compared to the git calls
+    let baum_paths = find_baum_dirs(&ws.root, &ws.root, &ws.config);
+
+    status("Checking", &format!("{} planted baum(s)", baum_paths.len()));
+
+    // Check baums in parallel, reporting each one as it finishes.
+    // Results are collected in walk order so the final report is stable.
+    let cache = WorktreeCache::default();
+    let done = AtomicUsize::new(0);
+    let total = baum_paths.len();
+
+    let baum_findings: Vec<BaumFindings> = baum_paths
+        .par_iter()
+        .map(|baum_path| {
+            let found = check_baum(ws, baum_path, &cache, checks);
+            let n = done.fetch_add(1, Ordering::Relaxed) + 1;
+            let display = ws.display_path(baum_path);
+            let problems = found
+                .0
+                .iter()
+                .filter(|i| i.severity != Severity::Info)
+                .count();
+            if problems > 0 {
+                status(
+                    "Found",
+                    &format!("{} issue(s) in {} [{}/{}]", problems, display, n, total),
+                );
+            } else if out.is_some_and(|o| o.verbose) {
+                status("Checked", &format!("{} [{}/{}]", display, n, total));
             }
-            // Skip worktree directories (no need to descend into them)
-            if e.file_type().is_dir() && name.starts_with('_') && name.ends_with(".wt") {
-                return false;
+            found
+        })
+        .collect();
+
+    let mut unpushed = Vec::new();
+    for (found, baum_unpushed) in baum_findings {
+        issues.extend(found);
+        unpushed.extend(baum_unpushed);
+    }
+
+    Findings {
+        issues,
+        baums: total,
+        unpushed,
+    }
+}
+
+/// Check what `wald sync` needs from the workspace repo
+fn check_workspace_repo(ws: &Workspace) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    let mut manifests = vec![ws.manifest_path()];
+    if ws.manifest_dir().is_dir() {
+        manifests.push(ws.manifest_dir());
+    }
+    for manifest in manifests {
+        let changes = Command::new("git")
+            .arg("-C")
+            .arg(&ws.root)
+            .arg("status")
+            .arg("--porcelain")
+            .arg("--")
+            .arg(&manifest)
+            .timed_output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_default();
+        if changes.is_empty() {
+            continue;
+        }
+        let state = if changes.lines().all(|l| l.starts_with("??")) {
+            "was never committed"
+        } else {
+            "has uncommitted changes"
+        };
+        issues.push(Issue {
+            severity: Severity::Warning,
+            message: format!(
+                "{} {}; `wald sync` refuses to run and other machines don't see its repos until it is committed",
+                ws.display_path(&manifest),
+                state
+            ),
+            fix: Some(FixAction::CommitManifest(ws.root.clone(), manifest)),
+        });
+    }
+
+    if !git::has_upstream(&ws.root) {
+        let branch = git::current_branch(&ws.root).unwrap_or_else(|| "<branch>".to_string());
+        issues.push(Issue {
+            severity: Severity::Warning,
+            message: format!(
+                "Workspace repo has no upstream, so `wald sync` can't pull or push; run `git push -u origin {}` (after `git remote add origin <url>` if there's no remote yet)",
+                branch
+            ),
+            fix: None,
+        });
+    }
+
+    if !has_gitignore_section(&ws.root) {
+        issues.push(Issue {
+            severity: Severity::Warning,
+            message: ".gitignore lacks the wald-managed section, so bare repos and worktrees could be committed to the workspace repo".to_string(),
+            fix: Some(FixAction::GitignoreSection(ws.root.clone())),
+        });
+    }
+
+    if worktree_exclude_outdated(&ws.root) {
+        issues.push(Issue {
+            severity: Severity::Warning,
+            message: "The workspace repo's info/exclude doesn't match the baums' .baum/gitignore files, so worktrees could be committed to the workspace repo".to_string(),
+            fix: Some(FixAction::UpdateExclude(ws.root.clone())),
+        });
+    }
+
+    issues
+}
+
+/// Warn about registered repos that were archived, renamed or transferred,
+/// or are gone from their forge; their fetches fail (or go stale) later
+fn check_forge(ws: &Workspace) -> Vec<Issue> {
+    let repos: Vec<_> = ws.manifest.repos.iter().collect();
+    repos
+        .par_iter()
+        .filter_map(|(repo_id, entry)| {
+            // Look up where the clone URL points, which may not be the ID
+            let remote = match &entry.url {
+                Some(url) => RepoId::from_url(url),
+                None => RepoId::parse(repo_id),
             }
-            true
+            .ok()?;
+            let location = format!("{}/{}", remote.host, remote.path.join("/"));
+            let (severity, message) = match forge::lookup_repo(&remote) {
+                Ok(None) => (
+                    Severity::Warning,
+                    format!(
+                        "Repo {} not found at {} (deleted, or made private and not visible to your token); `wald repo remove {}` if it's gone",
+                        repo_id, location, repo_id
+                    ),
+                ),
+                Ok(Some(found)) => {
+                    if let Some(path) = found.moved_from(&remote) {
+                        let moved = format!("{}/{}", remote.host, path);
+                        (
+                            Severity::Warning,
+                            format!(
+                                "Repo {} moved to {}; register it there with `wald repo add {}` and drop the old ID with `wald repo remove {}`",
+                                repo_id, moved, moved, repo_id
+                            ),
+                        )
+                    } else if found.archived {
+                        (
+                            Severity::Warning,
+                            format!(
+                                "Repo {} is archived at {}; `wald repo remove {}` if you no longer need it",
+                                repo_id, location, repo_id
+                            ),
+                        )
+                    } else {
+                        return None;
+                    }
+                }
+                Err(e) => (
+                    Severity::Info,
+                    format!("Could not look up {} at {}: {:#}", repo_id, location, e),
+                ),
+            };
+            Some(Issue {
+                severity,
+                message,
+                fix: None,
+            })
         })
-    {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
+        .collect()
+}
+
+/// Check the `bare_path` overrides: each names a directory of its own,
+/// holding a bare repo once cloned, on a disk that is there
+fn check_bare_paths(ws: &Workspace) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut claimed: HashMap<PathBuf, &str> = HashMap::new();
+
+    for (repo_id, entry) in &ws.manifest.repos {
+        let Ok(bare_path) = ws.bare_repo_path(repo_id) else {
+            continue;
         };
+        let key = filesystem_key(&bare_path);
+        if let Some(other) = claimed.get(&key) {
+            issues.push(Issue {
+                severity: Severity::Error,
+                message: format!(
+                    "Repos {} and {} share the bare clone {}; give one of them another bare_path",
+                    other,
+                    repo_id,
+                    ws.display_path(&bare_path)
+                ),
+                fix: None,
+            });
+        }
+        claimed.insert(key, repo_id);
 
-        if entry.file_type().is_dir() && is_baum(entry.path()) {
-            check_baum(ws, entry.path(), &mut issues, out)?;
+        let Some(configured) = &entry.bare_path else {
+            continue;
+        };
+        if bare_path.exists() {
+            if git::open_bare(&bare_path).is_err() {
+                issues.push(Issue {
+                    severity: Severity::Error,
+                    message: format!(
+                        "bare_path of {} holds no bare repo: {}",
+                        repo_id,
+                        ws.display_path(&bare_path)
+                    ),
+                    fix: None,
+                });
+            }
+        } else if !bare_path.parent().is_some_and(|parent| parent.is_dir()) {
+            issues.push(Issue {
+                severity: Severity::Warning,
+                message: format!(
+                    "bare_path of {} ({}) is in a directory that doesn't exist; is its disk mounted?",
+                    repo_id, configured
+                ),
+                fix: None,
+            });
         }
     }
 
-    // Report findings
-    println!();
-    if issues.is_empty() {
-        out.success("No issues found");
-    } else {
-        let errors = issues
-            .iter()
-            .filter(|i| i.severity == Severity::Error)
-            .count();
-        let warnings = issues
-            .iter()
-            .filter(|i| i.severity == Severity::Warning)
-            .count();
+    issues
+}
 
-        println!(
-            "Found {} issue(s) ({} errors, {} warnings)",
-            issues.len(),
-            errors,
-            warnings
+/// A path as compared with others: canonical where it exists
+fn filesystem_key(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Check the object stores a bare repo borrows from
+fn check_alternates(
+    ws: &Workspace,
+    repo_id: &str,
+    entry: &RepoEntry,
+    bare_path: &Path,
+) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let alternates = git::alternates(bare_path);
+
+    // Without them, objects the repo never stored itself are gone
+    for alternate in alternates.iter().filter(|alt| !alt.is_dir()) {
+        issues.push(Issue {
+            severity: Severity::Error,
+            message: format!(
+                "Bare repo {} borrows objects from {}, which is missing (restore it or re-clone the repo)",
+                repo_id,
+                alternate.display()
+            ),
+            fix: None,
+        });
+    }
+
+    let Some(reference) = &entry.reference else {
+        return issues;
+    };
+    if !ws.manifest.has_repo(reference) {
+        issues.push(Issue {
+            severity: Severity::Warning,
+            message: format!(
+                "{} borrows objects from {}, which is not registered",
+                repo_id, reference
+            ),
+            fix: None,
+        });
+        return issues;
+    }
+    let Some(reference_objects) = ws
+        .bare_repo_path(reference)
+        .ok()
+        .and_then(|p| p.join("objects").canonicalize().ok())
+    else {
+        // Not cloned yet; reported above
+        return issues;
+    };
+    let shared = alternates
+        .iter()
+        .any(|alt| alt.canonicalize().is_ok_and(|alt| alt == reference_objects));
+    if !shared {
+        issues.push(Issue {
+            severity: Severity::Warning,
+            message: format!("{} doesn't share objects with {}", repo_id, reference),
+            fix: Some(FixAction::AddAlternate(
+                bare_path.to_path_buf(),
+                reference_objects
+                    .parent()
+                    .unwrap_or(&reference_objects)
+                    .to_path_buf(),
+                ws.root.clone(),
+            )),
+        });
+    }
+
+    issues
+}
+
+fn count(issues: &[Issue], severity: Severity) -> usize {
+    issues.iter().filter(|i| i.severity == severity).count()
+}
+
+/// Remember what a check found for the summary `wald` shows without a
+/// command; with `fix`, errors that have a fix are taken as fixed
+fn record_check(ws: &Workspace, issues: &[Issue], fix: bool) {
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error && !(fix && i.fix.is_some()))
+        .map(|i| i.message.clone())
+        .collect();
+    let path = ws.state_path();
+    let mut state = SyncState::load(&path).unwrap_or_default();
+    state.last_doctor = Some(DoctorCheck {
+        checked_at: now_secs(),
+        errors,
+        warnings: count(issues, Severity::Warning),
+    });
+    // Only a summary: a read-only workspace just doesn't get one
+    let _ = state.save(&path);
+}
+
+/// Keep checking and publish the results for monitoring
+///
+/// With only a textfile and no interval, checks once and returns.
+fn monitor(ws: &Workspace, opts: &DoctorOptions, out: &Output) -> Result<()> {
+    let once = opts.serve.is_none() && opts.interval.is_none();
+    let interval = opts.interval.unwrap_or(DEFAULT_INTERVAL_SECS).max(1);
+
+    let snapshot = SharedSnapshot::default();
+    if let Some(addr) = &opts.serve {
+        let local = serve::spawn(addr, snapshot.clone())?;
+        out.status(
+            "Serving",
+            &format!("http://{}/metrics (checking every {}s)", local, interval),
         );
-        println!();
+    }
 
-        for issue in &issues {
-            let prefix = match issue.severity {
-                Severity::Error => "ERROR",
-                Severity::Warning => "WARN",
-            };
-            println!("  [{}] {}", prefix, issue.message);
+    loop {
+        let started = Instant::now();
+        let now = now_secs();
+        let checks = CheckOptions {
+            unpushed: true,
+            unpushed_days: opts.unpushed_days,
+            record_branch: false,
+            now,
+        };
 
-            if opts.fix
-                && let Some(fix) = &issue.fix
-            {
-                match apply_fix(fix) {
-                    Ok(_) => println!("         Fixed!"),
-                    Err(e) => println!("         Failed to fix: {}", e),
-                }
-            }
+        // Pick up repos and config changed since the last check
+        let findings = match Workspace::load_from(ws.root.clone()) {
+            Ok(current) => check_workspace(&current, checks, None),
+            Err(e) => Findings {
+                issues: vec![Issue {
+                    severity: Severity::Error,
+                    message: format!("Failed to load workspace: {:#}", e),
+                    fix: None,
+                }],
+                baums: 0,
+                unpushed: Vec::new(),
+            },
+        };
+        let elapsed = started.elapsed();
+
+        let current = HealthSnapshot {
+            metrics: render_metrics(&ws.root, &findings, now, elapsed),
+            health: render_health(&ws.root, &findings, now),
+            healthy: count(&findings.issues, Severity::Error) == 0,
+        };
+        if let Some(path) = &opts.textfile {
+            write_textfile(path, &current.metrics)?;
         }
+        out.verbose(&format!(
+            "Checked {} baum(s) in {:.1}s: {} error(s), {} warning(s)",
+            findings.baums,
+            elapsed.as_secs_f64(),
+            count(&findings.issues, Severity::Error),
+            count(&findings.issues, Severity::Warning)
+        ));
+        *snapshot.lock().unwrap_or_else(|e| e.into_inner()) = current;
 
-        if !opts.fix && issues.iter().any(|i| i.fix.is_some()) {
-            println!();
-            println!("Run with --fix to automatically repair fixable issues");
+        if once {
+            if let Some(path) = &opts.textfile {
+                out.success(&format!("Wrote {}", path.display()));
+            }
+            return Ok(());
         }
+        thread::sleep(Duration::from_secs(interval));
     }
+}
+
+/// Results in the Prometheus text exposition format
+fn render_metrics(root: &Path, findings: &Findings, now: u64, elapsed: Duration) -> String {
+    let ws = format!("workspace=\"{}\"", escape_label(&root.to_string_lossy()));
+    let oldest_age = findings
+        .unpushed
+        .iter()
+        .map(|(_, time)| now.saturating_sub(*time))
+        .max()
+        .unwrap_or(0);
+
+    let mut m = String::new();
+    let mut gauge = |name: &str, help: &str, samples: &[(&str, String)]| {
+        m.push_str(&format!(
+            "# HELP {} {}\n# TYPE {} gauge\n",
+            name, help, name
+        ));
+        for (labels, value) in samples {
+            let labels = if labels.is_empty() {
+                ws.clone()
+            } else {
+                format!("{},{}", ws, labels)
+            };
+            m.push_str(&format!("{}{{{}}} {}\n", name, labels, value));
+        }
+    };
+
+    gauge(
+        "wald_doctor_issues",
+        "Issues found by the last check, by severity",
+        &[
+            (
+                "severity=\"error\"",
+                count(&findings.issues, Severity::Error).to_string(),
+            ),
+            (
+                "severity=\"warning\"",
+                count(&findings.issues, Severity::Warning).to_string(),
+            ),
+        ],
+    );
+    gauge(
+        "wald_baums",
+        "Planted baums",
+        &[("", findings.baums.to_string())],
+    );
+    gauge(
+        "wald_unpushed_worktrees",
+        "Worktrees with commits not pushed to their upstream",
+        &[("", findings.unpushed.len().to_string())],
+    );
+    gauge(
+        "wald_unpushed_oldest_age_seconds",
+        "Age of the oldest unpushed commit in any worktree (0 if none)",
+        &[("", oldest_age.to_string())],
+    );
+    gauge(
+        "wald_doctor_last_check_timestamp_seconds",
+        "When the last check ran",
+        &[("", now.to_string())],
+    );
+    gauge(
+        "wald_doctor_check_duration_seconds",
+        "How long the last check took",
+        &[("", format!("{:.3}", elapsed.as_secs_f64()))],
+    );
+
+    m
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
 
+/// Results as the JSON body of `/health`
+fn render_health(root: &Path, findings: &Findings, now: u64) -> String {
+    let issues: Vec<_> = findings
+        .issues
+        .iter()
+        .filter(|i| i.severity != Severity::Info)
+        .map(|i| json!({ "severity": i.severity.label().to_lowercase(), "message": i.message }))
+        .collect();
+    let unpushed: Vec<_> = findings
+        .unpushed
+        .iter()
+        .map(|(path, time)| json!({ "worktree": path, "oldest_commit": format_iso(*time) }))
+        .collect();
+
+    json!({
+        "workspace": root,
+        "healthy": count(&findings.issues, Severity::Error) == 0,
+        "checked_at": format_iso(now),
+        "errors": count(&findings.issues, Severity::Error),
+        "warnings": count(&findings.issues, Severity::Warning),
+        "baums": findings.baums,
+        "issues": issues,
+        "unpushed": unpushed,
+    })
+    .to_string()
+}
+
+/// Replace a textfile in one step, so collectors never read half of it
+fn write_textfile(path: &Path, content: &str) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    fs::write(&tmp, content).with_context(|| format!("failed to write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("failed to write {}", path.display()))?;
     Ok(())
 }
 
+/// Worktree lists per bare repo, shared across parallel baum checks
+///
+/// Baums of the same repo share one bare repo, so `git worktree list` only
+/// needs to run once per repo rather than once per baum.
+#[derive(Default)]
+struct WorktreeCache {
+    lists: Mutex<HashMap<PathBuf, Arc<OnceLock<WorktreeList>>>>,
+}
+
+type WorktreeList = Arc<Vec<WorktreeInfo>>;
+
+impl WorktreeCache {
+    fn get(&self, bare_path: &Path) -> WorktreeList {
+        let cell = self
+            .lists
+            .lock()
+            .unwrap()
+            .entry(bare_path.to_path_buf())
+            .or_default()
+            .clone();
+        // Computed outside the map lock so other repos aren't blocked
+        cell.get_or_init(|| Arc::new(git::list_worktrees(bare_path).unwrap_or_default()))
+            .clone()
+    }
+}
+
 fn check_baum(
     ws: &Workspace,
-    baum_path: &std::path::Path,
-    issues: &mut Vec<Issue>,
-    _out: &Output,
-) -> Result<()> {
+    baum_path: &Path,
+    cache: &WorktreeCache,
+    checks: CheckOptions,
+) -> BaumFindings {
+    let mut issues = Vec::new();
+    let mut unpushed = Vec::new();
+
     // Load baum manifest
     let baum = match load_baum(baum_path) {
         Ok(b) => b,
         Err(e) => {
             issues.push(Issue {
                 severity: Severity::Error,
-                message: format!("Invalid baum manifest at {}: {}", baum_path.display(), e),
+                message: format!(
+                    "Invalid baum manifest at {}: {}",
+                    ws.display_path(baum_path),
+                    e
+                ),
                 fix: None,
             });
-            return Ok(());
+            return (issues, unpushed);
         }
     };
 
+    // A lock left behind by a wald process that died blocks every change
+    if let Some((lock_path, holder)) = read_baum_lock(baum_path) {
+        if holder.is_stale(checks.now) {
+            issues.push(Issue {
+                severity: Severity::Warning,
+                message: format!(
+                    "Stale lock on baum {} from {}",
+                    ws.display_path(baum_path),
+                    holder.describe()
+                ),
+                fix: Some(FixAction::RemoveStaleLock(lock_path)),
+            });
+        } else {
+            issues.push(Issue {
+                severity: Severity::Info,
+                message: format!(
+                    "Baum {} is locked by {}",
+                    ws.display_path(baum_path),
+                    holder.describe()
+                ),
+                fix: None,
+            });
+        }
+    }
+
+    // Check the container ignores exactly its worktrees (prunes by older
+    // versions, or by hand, leave patterns behind)
+    let worktree_paths: Vec<String> = baum.worktrees.iter().map(|wt| wt.path.clone()).collect();
+    let drift = worktree_gitignore_drift(baum_path, &worktree_paths);
+    if !drift.is_empty() {
+        let mut details = Vec::new();
+        if !drift.stale.is_empty() {
+            details.push(format!("stale: {}", drift.stale.join(", ")));
+        }
+        if !drift.missing.is_empty() {
+            details.push(format!("not ignored: {}", drift.missing.join(", ")));
+        }
+        let ignore_file = worktree_ignore_file(baum_path);
+        issues.push(Issue {
+            severity: Severity::Warning,
+            message: format!(
+                "Baum {} {} doesn't match its worktrees ({})",
+                ws.display_path(baum_path),
+                ignore_file
+                    .strip_prefix(baum_path)
+                    .unwrap_or(&ignore_file)
+                    .display(),
+                details.join("; ")
+            ),
+            fix: Some(FixAction::ReconcileGitignore(baum_path.to_path_buf())),
+        });
+    }
+
     // Check if repo is registered
     if !ws.manifest.has_repo(&baum.repo_id) {
         issues.push(Issue {
             severity: Severity::Warning,
             message: format!(
                 "Baum {} references unregistered repo: {}",
-                baum_path.display(),
+                ws.display_path(baum_path),
                 baum.repo_id
             ),
             fix: None,
@@ -180,30 +1036,87 @@ fn check_baum(
                 severity: Severity::Error,
                 message: format!(
                     "Baum {} missing bare repo: {}",
-                    baum_path.display(),
-                    bare_path.display()
+                    ws.display_path(baum_path),
+                    ws.display_path(&bare_path)
                 ),
                 fix: None,
             });
-            return Ok(());
+            return (issues, unpushed);
         }
 
         // Check worktrees
-        let worktree_list = git::list_worktrees(&bare_path).unwrap_or_default();
+        let worktree_list = cache.get(&bare_path);
+        let mut renamed = Vec::new();
 
         for wt in &baum.worktrees {
             let wt_path = baum_path.join(&wt.path);
+            let git_entry = git::match_worktree(&worktree_list, &wt_path);
+
+            // Locked worktrees are deliberately kept; an absent directory is
+            // expected while e.g. a removable drive is unmounted
+            if let Some(info) = git_entry.filter(|i| i.locked) {
+                let reason = info
+                    .lock_reason
+                    .as_ref()
+                    .map(|r| format!(": {}", r))
+                    .unwrap_or_default();
+                let state = if wt_path.exists() {
+                    "locked"
+                } else {
+                    "locked, directory unavailable"
+                };
+                issues.push(Issue {
+                    severity: Severity::Info,
+                    message: format!(
+                        "Worktree {} ({}{})",
+                        ws.display_path(&wt_path),
+                        state,
+                        reason
+                    ),
+                    fix: None,
+                });
+                continue;
+            }
 
             // Check worktree directory exists
             if !wt_path.exists() {
+                let expected = wt.local_branch.as_deref().unwrap_or(&wt.branch);
+                let (hint, fix) = match find_renamed_worktree(baum_path, &baum, expected) {
+                    Some(dir) => {
+                        renamed.push(dir.clone());
+                        (
+                            format!("; it seems to have been renamed to {}", dir),
+                            Some(FixAction::RelinkWorktree(
+                                bare_path.clone(),
+                                baum_path.to_path_buf(),
+                                wt.path.clone(),
+                                dir,
+                            )),
+                        )
+                    }
+                    // Removed here by hand rather than with `wald prune`
+                    None if git_entry.is_some_and(|i| i.prunable) => (
+                        format!(
+                            "{}; git still registers it, so it was removed here without `wald prune`",
+                            planted_drift(&bare_path, wt).unwrap_or_default()
+                        ),
+                        Some(FixAction::ForgetWorktree(
+                            bare_path.clone(),
+                            baum_path.to_path_buf(),
+                            wt.path.clone(),
+                        )),
+                    ),
+                    None => (planted_drift(&bare_path, wt).unwrap_or_default(), None),
+                };
                 issues.push(Issue {
                     severity: Severity::Error,
                     message: format!(
-                        "Missing worktree directory: {} (branch: {})",
-                        wt_path.display(),
-                        wt.branch
+                        "Missing worktree directory: {} (branch: {}){}",
+                        ws.display_path(&wt_path),
+                        wt.branch,
+                        hint
                     ),
-                    fix: None,
+                    fix,
                 });
                 continue;
             }
@@ -212,17 +1125,127 @@ fn check_baum(
             if !wt_path.join(".git").exists() {
                 issues.push(Issue {
                     severity: Severity::Error,
-                    message: format!("Invalid worktree (missing .git): {}", wt_path.display()),
+                    message: format!(
+                        "Invalid worktree (missing .git): {}",
+                        ws.display_path(&wt_path)
+                    ),
                     fix: None,
                 });
             }
 
+            // Check the worktree and its repo still point at each other
+            // (`mv` instead of `wald move` breaks the link)
+            let broken_link = git::check_worktree_link(&wt_path);
+            if let Some(problem) = &broken_link {
+                issues.push(Issue {
+                    severity: Severity::Error,
+                    message: format!(
+                        "Worktree {} is disconnected from its repo ({}); moved without `wald move`?",
+                        ws.display_path(&wt_path),
+                        problem
+                    ),
+                    fix: Some(FixAction::RelinkWorktree(
+                        bare_path.clone(),
+                        baum_path.to_path_buf(),
+                        wt.path.clone(),
+                        wt.path.clone(),
+                    )),
+                });
+            }
+
+            // Check the worktree is still on its branch (`git switch` inside
+            // it leaves the manifest describing another branch)
+            let expected = wt.local_branch.as_deref().unwrap_or(&wt.branch);
+            if let Some(info) = git_entry {
+                match &info.branch {
+                    Some(actual) if actual != expected => {
+                        let fix = if checks.record_branch {
+                            FixAction::RecordBranch(
+                                baum_path.to_path_buf(),
+                                wt.path.clone(),
+                                actual.clone(),
+                            )
+                        } else {
+                            FixAction::SwitchBranch(wt_path.clone(), expected.to_string())
+                        };
+                        issues.push(Issue {
+                            severity: Severity::Warning,
+                            message: format!(
+                                "Worktree {} is on branch {}, expected {}",
+                                ws.display_path(&wt_path),
+                                actual,
+                                expected
+                            ),
+                            fix: Some(fix),
+                        });
+                    }
+                    Some(_) => {}
+                    // e.g. in the middle of a bisect or rebase
+                    None if info.detached => issues.push(Issue {
+                        severity: Severity::Info,
+                        message: format!(
+                            "Worktree {} has a detached HEAD (branch: {})",
+                            ws.display_path(&wt_path),
+                            expected
+                        ),
+                        fix: None,
+                    }),
+                    None => {}
+                }
+            }
+
+            // Check the worktree commits with the expected identity
+            if let Some(expected) = ws.manifest.expected_identity(&baum.repo_id)
+                && let Ok(effective) = git::get_config(&wt_path, "user.email")
+                && effective.as_deref() != Some(expected)
+            {
+                issues.push(Issue {
+                    severity: Severity::Warning,
+                    message: format!(
+                        "Worktree {} commits as {}, expected {}",
+                        ws.display_path(&wt_path),
+                        effective.as_deref().unwrap_or("<unset>"),
+                        expected
+                    ),
+                    fix: Some(FixAction::SetIdentity(
+                        bare_path.clone(),
+                        wt_path.clone(),
+                        expected.to_string(),
+                    )),
+                });
+            }
+
+            // Check for work that only exists on this machine
+            if checks.unpushed
+                && let Ok(Some(oldest)) = git::oldest_unpushed_commit(
+                    &bare_path,
+                    wt.local_branch.as_deref().unwrap_or(&wt.branch),
+                )
+            {
+                let age_days = checks.now.saturating_sub(oldest) / 86_400;
+                if checks.unpushed_days.is_some_and(|days| age_days >= days) {
+                    issues.push(Issue {
+                        severity: Severity::Warning,
+                        message: format!(
+                            "Worktree {} has unpushed commits from {} day(s) ago",
+                            ws.display_path(&wt_path),
+                            age_days
+                        ),
+                        fix: None,
+                    });
+                }
+                unpushed.push((wt_path.clone(), oldest));
+            }
+
             // Check worktree is in git's list
             // Use paths_equal to handle symlinks (e.g., /tmp -> /private/tmp on macOS)
-            if !worktree_list.iter().any(|w| paths_equal(&wt_path, &w.path)) {
+            if broken_link.is_none()
+                && git_entry.is_none()
+                && !worktree_list.iter().any(|w| paths_equal(&wt_path, &w.path))
+            {
                 issues.push(Issue {
                     severity: Severity::Warning,
-                    message: format!("Worktree not in git's list: {}", wt_path.display()),
+                    message: format!("Worktree not in git's list: {}", ws.display_path(&wt_path)),
                     fix: Some(FixAction::RepairWorktree(
                         bare_path.clone(),
                         wt_path.clone(),
@@ -230,15 +1253,80 @@ fn check_baum(
                 });
             }
         }
+
+        // Worktrees made with plain `git worktree add` inside this baum
+        let canonical_baum = baum_path.canonicalize().ok();
+        for info in worktree_list.iter().filter(|w| !w.bare) {
+            let path = Path::new(&info.path);
+            let in_this_baum = path
+                .ancestors()
+                .skip(1)
+                .find(|p| is_baum(p))
+                .is_some_and(|p| p.canonicalize().ok() == canonical_baum);
+            let known = baum
+                .worktrees
+                .iter()
+                .any(|wt| paths_equal(&baum_path.join(&wt.path), &info.path));
+            if in_this_baum && !known {
+                // One named like wald's own (e.g. left by a crashed plant)
+                // can be recorded as it is
+                let fix = match (orphan_worktree_name(baum_path, path), &info.branch) {
+                    (Some(name), Some(branch)) => Some(FixAction::AdoptWorktree(
+                        baum_path.to_path_buf(),
+                        name,
+                        branch.clone(),
+                    )),
+                    _ => None,
+                };
+                issues.push(Issue {
+                    severity: Severity::Warning,
+                    message: format!(
+                        "Worktree {} is not in the baum manifest; record it with `wald adopt-worktree {} {}`",
+                        info.path,
+                        ws.display_path(baum_path),
+                        info.path
+                    ),
+                    fix,
+                });
+            }
+        }
+
+        // Worktree-like directories that neither the manifest nor git knows
+        for dir in orphan_worktree_dirs(baum_path, &baum) {
+            let path = baum_path.join(&dir);
+            if renamed.contains(&dir) || worktree_list.iter().any(|w| paths_equal(&path, &w.path)) {
+                continue;
+            }
+            issues.push(Issue {
+                severity: Severity::Warning,
+                message: format!(
+                    "Directory {} looks like a worktree but isn't in the baum manifest or git's list",
+                    ws.display_path(&path)
+                ),
+                fix: Some(FixAction::RemoveOrphanDir(path)),
+            });
+        }
     }
 
-    Ok(())
+    (issues, unpushed)
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Severity {
     Error,
     Warning,
+    /// Not a problem, but worth knowing (e.g. locked worktrees)
+    Info,
+}
+
+impl Severity {
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "ERROR",
+            Severity::Warning => "WARN",
+            Severity::Info => "INFO",
+        }
+    }
 }
 
 struct Issue {
@@ -247,17 +1335,145 @@ struct Issue {
     fix: Option<FixAction>,
 }
 
+/// How far the remote branch a worktree was created from has moved since,
+/// for recreating it elsewhere: a new checkout starts at the newer commit
+fn planted_drift(bare_path: &Path, wt: &WorktreeEntry) -> Option<String> {
+    let (from, commit) = (wt.created_from.as_deref()?, wt.created_commit.as_deref()?);
+    let short = short_sha(commit);
+    if !git::has_commit(bare_path, commit) {
+        return Some(format!(
+            "; it was planted at {}, which is no longer in the repo (was {} force-pushed?)",
+            short, from
+        ));
+    }
+    match git::count_commits_between(bare_path, commit, from)? {
+        0 => None,
+        moved => Some(format!(
+            "; {} has moved {} commit(s) since it was planted at {}",
+            from, moved, short
+        )),
+    }
+}
+
 enum FixAction {
     CreateDir(PathBuf),
     RepairWorktree(PathBuf, PathBuf), // (bare_repo_path, worktree_path)
+    RelinkWorktree(PathBuf, PathBuf, String, String), // (bare_repo_path, baum_path, entry path, directory)
+    SetIdentity(PathBuf, PathBuf, String),            // (bare_repo_path, worktree_path, email)
+    RemoveStaleLock(PathBuf),
+    SwitchBranch(PathBuf, String),           // (worktree_path, branch)
+    RecordBranch(PathBuf, String, String),   // (baum_path, worktree entry path, branch)
+    AddAlternate(PathBuf, PathBuf, PathBuf), // (bare_repo_path, reference bare repo, workspace_root)
+    Clone(RepoId, PathBuf, git::CloneOptions), // (repo, bare_repo_path, options)
+    ReconcileGitignore(PathBuf),             // (baum_path)
+    CommitManifest(PathBuf, PathBuf),        // (workspace_root, manifest_path)
+    GitignoreSection(PathBuf),               // (workspace_root)
+    UpdateExclude(PathBuf),                  // (workspace_root)
+    AdoptWorktree(PathBuf, String, String),  // (baum_path, directory, checked-out branch)
+    RemoveOrphanDir(PathBuf),                // (directory)
+    ForgetWorktree(PathBuf, PathBuf, String), // (bare_repo_path, baum_path, entry path)
+    PruneWorktrees(PathBuf),                 // (bare_repo_path)
+    RestoreBackup(PathBuf, Backup),          // (workspace file, backup)
 }
 
 fn apply_fix(fix: &FixAction) -> Result<()> {
     match fix {
+        FixAction::RestoreBackup(path, backup) => {
+            let restored = restore_backup(path, backup)?;
+            // Shown on stderr, which --json leaves alone
+            eprintln!(
+                "         Kept the damaged version as {}",
+                restored.corrupt_copy.display()
+            );
+            if !restored.lost.is_empty() {
+                eprintln!("         Lost by restoring:");
+                for line in restored.lost.lines() {
+                    eprintln!("           {}", line);
+                }
+            }
+            Ok(())
+        }
         FixAction::CreateDir(path) => {
             std::fs::create_dir_all(path)?;
             Ok(())
         }
+        FixAction::RemoveStaleLock(path) => {
+            std::fs::remove_file(path)?;
+            Ok(())
+        }
+        FixAction::SetIdentity(bare_repo, worktree_path, email) => {
+            pin_identity(bare_repo, worktree_path, email)
+        }
+        FixAction::SwitchBranch(worktree_path, branch) => git::switch_branch(worktree_path, branch),
+        FixAction::AddAlternate(bare_repo, reference, root) => {
+            git::add_alternate(bare_repo, reference, Some(root))
+        }
+        FixAction::Clone(id, bare_repo, opts) => git::clone_bare(id, bare_repo, opts.clone()),
+        FixAction::RecordBranch(baum_path, wt_path, branch) => {
+            let _lock = lock_baum(baum_path, "doctor")?;
+            let mut baum = load_baum(baum_path)?;
+            let Some(entry) = baum.worktrees.iter_mut().find(|wt| &wt.path == wt_path) else {
+                bail!("worktree {} no longer in the baum manifest", wt_path);
+            };
+            entry.branch = parse_wald_branch(branch)
+                .map(|(_, b)| b)
+                .unwrap_or(branch)
+                .to_string();
+            entry.local_branch = Some(branch.clone());
+            // The recorded upstream belonged to the old branch
+            entry.track = None;
+            save_baum(baum_path, &baum)
+        }
+        FixAction::CommitManifest(root, manifest) => {
+            git::commit_file(root, manifest, "Update wald manifest")
+        }
+        FixAction::GitignoreSection(root) => ensure_gitignore_section(root),
+        FixAction::UpdateExclude(root) => update_worktree_exclude(root),
+        FixAction::ReconcileGitignore(baum_path) => {
+            let _lock = lock_baum(baum_path, "doctor")?;
+            let baum = load_baum(baum_path)?;
+            let paths: Vec<String> = baum.worktrees.into_iter().map(|wt| wt.path).collect();
+            reconcile_worktree_gitignore(baum_path, &paths)
+        }
+        FixAction::RelinkWorktree(bare_repo, baum_path, entry_path, dir) => {
+            git::repair_worktree(bare_repo, &baum_path.join(dir))?;
+            if entry_path == dir {
+                return Ok(());
+            }
+            // The directory was renamed: record its new name
+            let _lock = lock_baum(baum_path, "doctor")?;
+            let mut baum = load_baum(baum_path)?;
+            let Some(entry) = baum.worktrees.iter_mut().find(|wt| &wt.path == entry_path) else {
+                bail!("worktree {} no longer in the baum manifest", entry_path);
+            };
+            entry.path = dir.clone();
+            save_baum(baum_path, &baum)?;
+            let paths: Vec<String> = baum.worktrees.into_iter().map(|wt| wt.path).collect();
+            reconcile_worktree_gitignore(baum_path, &paths)
+        }
+        FixAction::AdoptWorktree(baum_path, dir, local_branch) => {
+            let _lock = lock_baum(baum_path, "doctor")?;
+            let mut baum = load_baum(baum_path)?;
+            let branch = parse_wald_branch(local_branch)
+                .map(|(_, b)| b)
+                .unwrap_or(local_branch);
+            if baum.worktrees.iter().any(|wt| wt.branch == branch) {
+                bail!(
+                    "the baum already has a worktree for branch '{}'; look at {} and remove it by hand",
+                    branch,
+                    dir
+                );
+            }
+            baum.add_worktree_with_local(branch, dir, local_branch);
+            save_baum(baum_path, &baum)?;
+            add_worktree_to_gitignore(baum_path, dir)
+        }
+        FixAction::RemoveOrphanDir(path) => remove_orphan_dir(path),
+        FixAction::ForgetWorktree(bare_repo, baum_path, entry_path) => {
+            forget_worktree(baum_path, entry_path, "doctor")?;
+            git::shell::worktree_prune(bare_repo)
+        }
+        FixAction::PruneWorktrees(bare_repo) => git::shell::worktree_prune(bare_repo),
         FixAction::RepairWorktree(_bare_repo, worktree_path) => {
             use std::process::Command;
 
@@ -269,7 +1485,7 @@ fn apply_fix(fix: &FixAction) -> Result<()> {
                 .arg(worktree_path)
                 .arg("worktree")
                 .arg("repair")
-                .output()?;
+                .timed_output()?;
 
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
@@ -280,6 +1496,84 @@ fn apply_fix(fix: &FixAction) -> Result<()> {
     }
 }
 
+/// The name of a directory directly in the baum that is named like a
+/// worktree (`_*.wt`)
+fn orphan_worktree_name(baum_path: &Path, path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let parent = path.parent()?.canonicalize().ok()?;
+    (is_worktree_dir_name(&name) && baum_path.canonicalize().ok()? == parent).then_some(name)
+}
+
+/// Directories in the baum named like worktrees (`_*.wt`) that its
+/// manifest doesn't list, sorted
+fn orphan_worktree_dirs(baum_path: &Path, baum: &BaumManifest) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(baum_path) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| {
+            is_worktree_dir_name(name) && !baum.worktrees.iter().any(|wt| &wt.path == name)
+        })
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+fn is_worktree_dir_name(name: &str) -> bool {
+    name.len() > 4 && name.starts_with('_') && name.ends_with(".wt")
+}
+
+/// Delete a leftover worktree directory, unless it may hold work: a
+/// checkout with uncommitted changes, a clone of its own, or files outside
+/// any checkout
+fn remove_orphan_dir(path: &Path) -> Result<()> {
+    let dot_git = path.join(".git");
+    if dot_git.is_dir() {
+        bail!(
+            "{} is a separate clone; move its work elsewhere and remove it by hand",
+            path.display()
+        );
+    }
+    if dot_git.is_file() {
+        let status = git::working_status(path).with_context(|| {
+            format!(
+                "can't check {} for uncommitted work; remove it by hand",
+                path.display()
+            )
+        })?;
+        if !status.is_clean() {
+            bail!(
+                "{} has uncommitted changes; save them and remove it by hand",
+                path.display()
+            );
+        }
+    } else if fs::read_dir(path)?.next().is_some() {
+        bail!(
+            "{} holds files outside a git checkout; remove it by hand if they aren't needed",
+            path.display()
+        );
+    }
+    fs::remove_dir_all(path).with_context(|| format!("failed to remove {}", path.display()))
+}
+
+/// A directory in the baum, not in its manifest, holding a worktree of
+/// `branch`: where a worktree renamed with `mv` went
+fn find_renamed_worktree(baum_path: &Path, baum: &BaumManifest, branch: &str) -> Option<String> {
+    let entries = fs::read_dir(baum_path).ok()?;
+    entries.flatten().find_map(|entry| {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let known = baum.worktrees.iter().any(|wt| wt.path == name);
+        let path = entry.path();
+        (!known
+            && path.join(".git").is_file()
+            && git::linked_branch(&path).as_deref() == Some(branch))
+        .then_some(name)
+    })
+}
+
 /// Compare two paths for equality, handling symlinks.
 ///
 /// On macOS, /tmp is a symlink to /private/tmp. Git commands return
@@ -347,6 +1641,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_worktree_cache_reuses_list() {
+        let dir = TempDir::new().unwrap();
+        let cache = WorktreeCache::default();
+
+        // Not a repo: the failed listing is cached as empty, not retried
+        let first = cache.get(dir.path());
+        let second = cache.get(dir.path());
+        assert!(first.is_empty());
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn test_render_metrics() {
+        let findings = Findings {
+            issues: vec![
+                Issue {
+                    severity: Severity::Error,
+                    message: "Missing manifest.yaml".to_string(),
+                    fix: None,
+                },
+                Issue {
+                    severity: Severity::Info,
+                    message: "Worktree x (locked)".to_string(),
+                    fix: None,
+                },
+            ],
+            baums: 2,
+            unpushed: vec![(PathBuf::from("/ws/a/_main.wt"), 1_000)],
+        };
+        let metrics = render_metrics(
+            Path::new("/ws/\"q\""),
+            &findings,
+            1_000 + 3 * 86_400,
+            Duration::from_millis(1_500),
+        );
+
+        let ws = r#"workspace="/ws/\"q\"""#;
+        for expected in [
+            format!("wald_doctor_issues{{{},severity=\"error\"}} 1\n", ws),
+            format!("wald_doctor_issues{{{},severity=\"warning\"}} 0\n", ws),
+            format!("wald_baums{{{}}} 2\n", ws),
+            format!("wald_unpushed_worktrees{{{}}} 1\n", ws),
+            format!("wald_unpushed_oldest_age_seconds{{{}}} 259200\n", ws),
+            format!("wald_doctor_check_duration_seconds{{{}}} 1.500\n", ws),
+            "# TYPE wald_baums gauge\n".to_string(),
+        ] {
+            assert!(metrics.contains(&expected), "missing {:?}", expected);
+        }
+
+        let health: serde_json::Value =
+            serde_json::from_str(&render_health(Path::new("/ws"), &findings, 1_000)).unwrap();
+        assert_eq!(health["healthy"], false);
+        assert_eq!(health["issues"].as_array().unwrap().len(), 1);
+        assert_eq!(health["unpushed"][0]["worktree"], "/ws/a/_main.wt");
+    }
+
     #[test]
     fn test_paths_equal_nonexistent() {
         // For non-existent paths, fall back to string comparison
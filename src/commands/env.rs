@@ -0,0 +1,95 @@
+use std::path::{Component, PathBuf};
+
+use anyhow::{Result, bail};
+
+use crate::output::{Output, OutputFormat};
+use crate::workspace::baum::{load_baum, load_baum_local};
+use crate::workspace::{Workspace, is_baum, validate_workspace_path};
+
+/// Options for env command
+pub struct EnvOptions {
+    /// A worktree, or a baum container for the baum-wide environment
+    pub path: PathBuf,
+}
+
+/// Print the environment set for a worktree in its baum's
+/// `.baum/manifest.local.yaml`, as `export` lines for `eval` or direnv
+pub fn env(ws: &Workspace, opts: EnvOptions, out: &Output) -> Result<()> {
+    let path = validate_workspace_path(&ws.root, &opts.path)?;
+    let root = ws.root.canonicalize().unwrap_or_else(|_| ws.root.clone());
+    let path = path.canonicalize().unwrap_or(path);
+
+    // The nearest baum at or above the path
+    let Some(container) = path
+        .ancestors()
+        .take_while(|dir| dir.starts_with(&root))
+        .find(|dir| is_baum(dir))
+    else {
+        bail!("not inside a baum: {}", ws.display_path(&path));
+    };
+
+    // Inside a worktree directory, its branch's settings apply as well
+    let baum = load_baum(container)?;
+    let branch = match path.strip_prefix(container)?.components().next() {
+        Some(Component::Normal(name)) => baum
+            .worktrees
+            .iter()
+            .find(|wt| name == wt.path.as_str())
+            .map(|wt| wt.branch.as_str()),
+        _ => None,
+    };
+
+    let env = load_baum_local(container)?.env_for(branch);
+    if let Some(name) = env.keys().find(|name| !is_env_name(name)) {
+        bail!(
+            "invalid environment variable name '{}' in {}/.baum/manifest.local.yaml",
+            name,
+            ws.display_path(container)
+        );
+    }
+
+    match out.format {
+        OutputFormat::Human => {
+            for (name, value) in &env {
+                println!("export {}={}", name, shell_quote(value));
+            }
+        }
+        OutputFormat::Json => out.print_json(&env)?,
+    }
+
+    Ok(())
+}
+
+/// Whether a shell would accept this as a variable name
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Single-quote a value for POSIX shells
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_env_name() {
+        assert!(is_env_name("PORT"));
+        assert!(is_env_name("_DATA_2"));
+        assert!(!is_env_name("2PORT"));
+        assert!(!is_env_name("MY-VAR"));
+        assert!(!is_env_name(""));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/data sets"), "'/data sets'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{Result, bail};
+use serde_json::{Map, Value, json};
+
+use crate::git::shell::get_head_commit;
+use crate::output::Output;
+use crate::types::{BaumManifest, DepthPolicy};
+use crate::vocabulary::Vocabulary;
+use crate::workspace::Workspace;
+
+/// Output format for `wald export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+/// Which inventory table to export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportWhat {
+    Repos,
+    Baums,
+    Worktrees,
+}
+
+impl ExportWhat {
+    fn name(self) -> &'static str {
+        match self {
+            ExportWhat::Repos => "repos",
+            ExportWhat::Baums => "baums",
+            ExportWhat::Worktrees => "worktrees",
+        }
+    }
+
+    /// CSV columns, in order (also the keys of each JSON record)
+    fn columns(self) -> &'static [&'static str] {
+        match self {
+            ExportWhat::Repos => &[
+                "repo_id",
+                "url",
+                "upstream",
+                "reference",
+                "aliases",
+                "lfs",
+                "depth",
+                "filter",
+                "tags",
+                "identity",
+                "cloned",
+            ],
+            ExportWhat::Baums => &["path", "id", "repo_id", "branches"],
+            ExportWhat::Worktrees => &[
+                "baum",
+                "baum_id",
+                "repo_id",
+                "branch",
+                "local_branch",
+                "track",
+                "path",
+                "commit",
+            ],
+        }
+    }
+}
+
+/// Options for export command
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Only this table; all of them if None (JSON only)
+    pub what: Option<ExportWhat>,
+}
+
+/// Print an inventory of the workspace's repos, baums and worktrees
+pub fn export(ws: &Workspace, opts: ExportOptions, out: &Output) -> Result<()> {
+    match (opts.format, opts.what) {
+        (ExportFormat::Json, Some(what)) => {
+            out.print_json(&records(ws, what))?;
+        }
+        (ExportFormat::Json, None) => {
+            let mut all = Map::new();
+            for what in [ExportWhat::Repos, ExportWhat::Baums, ExportWhat::Worktrees] {
+                all.insert(what.name().to_string(), Value::Array(records(ws, what)));
+            }
+            out.print_json(&all)?;
+        }
+        (ExportFormat::Csv, Some(what)) => {
+            print!(
+                "{}",
+                to_csv(what.columns(), &records(ws, what), out.vocabulary)
+            );
+        }
+        (ExportFormat::Csv, None) => {
+            bail!("CSV holds one table; choose it with --what repos|baums|worktrees");
+        }
+    }
+    Ok(())
+}
+
+/// One JSON object per repo, baum or worktree, sorted for stable output
+fn records(ws: &Workspace, what: ExportWhat) -> Vec<Value> {
+    match what {
+        ExportWhat::Repos => {
+            let mut repo_ids: Vec<_> = ws.manifest.repos.keys().collect();
+            repo_ids.sort();
+            repo_ids
+                .into_iter()
+                .map(|repo_id| {
+                    let entry = &ws.manifest.repos[repo_id];
+                    let depth = match entry.depth {
+                        DepthPolicy::Full => json!("full"),
+                        DepthPolicy::Depth(d) => json!(d),
+                    };
+                    json!({
+                        "repo_id": repo_id,
+                        "url": entry.url,
+                        "upstream": entry.upstream,
+                        "reference": entry.reference,
+                        "aliases": entry.aliases,
+                        "lfs": entry.lfs,
+                        "depth": depth,
+                        "filter": entry.filter,
+                        "tags": entry.tags,
+                        "identity": ws.manifest.expected_identity(repo_id),
+                        "cloned": ws.bare_repo_path(repo_id).is_ok_and(|p| p.exists()),
+                    })
+                })
+                .collect()
+        }
+        ExportWhat::Baums => baums(ws)
+            .into_iter()
+            .map(|(path, baum)| {
+                let branches: Vec<_> = baum.worktrees.iter().map(|wt| &wt.branch).collect();
+                json!({
+                    "path": path,
+                    "id": baum.id,
+                    "repo_id": baum.repo_id,
+                    "branches": branches,
+                })
+            })
+            .collect(),
+        ExportWhat::Worktrees => baums(ws)
+            .into_iter()
+            .flat_map(|(path, baum)| {
+                let container = ws.root.join(&path);
+                baum.worktrees
+                    .iter()
+                    .map(|wt| {
+                        json!({
+                            "baum": path,
+                            "baum_id": baum.id,
+                            "repo_id": baum.repo_id,
+                            "branch": wt.branch,
+                            "local_branch": wt.local_branch,
+                            "track": wt.track,
+                            "path": wt.path,
+                            "commit": get_head_commit(&container.join(&wt.path)).ok(),
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+    }
+}
+
+/// Baums by path relative to the workspace root, sorted
+fn baums(ws: &Workspace) -> Vec<(String, BaumManifest)> {
+    let mut baums: Vec<_> = ws
+        .find_all_baums()
+        .into_iter()
+        .map(|(path, baum)| {
+            let rel = path.strip_prefix(&ws.root).unwrap_or(&path);
+            (rel.to_string_lossy().to_string(), baum)
+        })
+        .collect();
+    baums.sort_by(|a, b| a.0.cmp(&b.0));
+    baums
+}
+
+/// Render records as CSV with a header row
+///
+/// Missing values are empty, lists are joined with `;`.
+/// Records as CSV, the header naming the columns in `vocabulary`
+fn to_csv(columns: &[&str], records: &[Value], vocabulary: Vocabulary) -> String {
+    let mut csv = String::new();
+    let mut push_row = |fields: Vec<String>| {
+        let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    };
+
+    push_row(
+        columns
+            .iter()
+            .map(|c| vocabulary.field(c).into_owned())
+            .collect(),
+    );
+    for record in records {
+        push_row(
+            columns
+                .iter()
+                .map(|c| csv_value(record.get(c).unwrap_or(&Value::Null)))
+                .collect(),
+        );
+    }
+    csv
+}
+
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(csv_value).collect::<Vec<_>>().join(";"),
+        other => other.to_string(),
+    }
+}
+
+/// Quote a field if it contains a separator, quote or line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_csv() {
+        let records = vec![
+            json!({"repo_id": "github.com/a/b", "aliases": ["b", "bee"], "url": null}),
+            json!({"repo_id": "github.com/c/d", "aliases": [], "url": "https://x/d,e\"f"}),
+        ];
+        assert_eq!(
+            to_csv(&["repo_id", "aliases", "url"], &records, Vocabulary::Wald),
+            "repo_id,aliases,url\n\
+             github.com/a/b,b;bee,\n\
+             github.com/c/d,,\"https://x/d,e\"\"f\"\n"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeSet;
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::bulk::BulkResult;
+use crate::git;
+use crate::output::Output;
+use crate::workspace::{Workspace, find_stale_worktrees, forget_worktree};
+
+/// Options for gc command
+pub struct GcOptions {
+    /// Prune stale worktree registrations and the baum entries left with them
+    pub worktrees: bool,
+    /// Report what would be cleaned up without changing anything
+    pub dry_run: bool,
+}
+
+/// Clean up workspace-wide leftovers
+pub fn gc(ws: &Workspace, opts: GcOptions, out: &Output) -> Result<()> {
+    out.require_human("gc")?;
+
+    if !opts.worktrees {
+        bail!("nothing to clean up; use --worktrees");
+    }
+    gc_worktrees(ws, opts.dry_run, out)
+}
+
+/// Prune git's registrations of worktrees whose directories are gone, and
+/// drop them from the baum manifests that still list them
+fn gc_worktrees(ws: &Workspace, dry_run: bool, out: &Output) -> Result<()> {
+    let stale = find_stale_worktrees(ws);
+    if stale.is_empty() {
+        out.info("No stale worktrees");
+        return Ok(());
+    }
+
+    let mut forgotten = 0;
+    let mut results = BulkResult::new("stale worktree", "clean up", stale.len());
+    let mut failed = Vec::new();
+    for worktree in &stale {
+        let path = ws.display_path(&worktree.path);
+        let target = match &worktree.baum {
+            Some((container, _)) => format!("{} (listed in {})", path, ws.display_path(container)),
+            None => path.clone(),
+        };
+        out.status(if dry_run { "Would prune" } else { "Pruning" }, &target);
+        let Some((container, entry_path)) = worktree.baum.as_ref().filter(|_| !dry_run) else {
+            continue;
+        };
+        let result = forget_worktree(container, entry_path, "gc")
+            .context("failed to remove it from its baum");
+        match results.record(&path, result, out) {
+            Some(()) => forgotten += 1,
+            None => failed.push(worktree),
+        }
+    }
+
+    if dry_run {
+        out.info(&format!(
+            "Would prune {} stale worktree registration(s)",
+            stale.len()
+        ));
+        return Ok(());
+    }
+
+    // A repo whose baum entry couldn't be removed keeps its registrations,
+    // so the next run (or `wald doctor`) still finds them
+    let repos: BTreeSet<_> = stale
+        .iter()
+        .filter(|w| !failed.iter().any(|f| f.repo_id == w.repo_id))
+        .map(|w| (&w.repo_id, &w.bare_path))
+        .collect();
+    for (repo_id, bare_path) in repos {
+        let result = git::shell::worktree_prune(bare_path)
+            .with_context(|| format!("failed to prune the worktrees of {}", repo_id));
+        // Every stale worktree of the repo stays registered
+        for worktree in stale.iter().filter(|w| &w.repo_id == repo_id) {
+            let path = ws.display_path(&worktree.path);
+            let result = result.as_ref().map(|_| ()).map_err(|e| anyhow!("{:#}", e));
+            results.record(&path, result, out);
+        }
+    }
+    results.finish(None)?;
+
+    out.success(&format!(
+        "Pruned {} stale worktree registration(s), {} removed from baum manifests",
+        stale.len(),
+        forgotten
+    ));
+    out.info("Their local branches are left; `wald prune --branches` deletes them");
+
+    Ok(())
+}
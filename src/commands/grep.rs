@@ -0,0 +1,193 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use rayon::prelude::*;
+
+use crate::git::{self, GrepMatch};
+use crate::output::{Output, OutputFormat};
+use crate::workspace::baum::load_baum;
+use crate::workspace::{Workspace, find_baum_dirs};
+
+/// Options for grep command
+pub struct GrepOptions {
+    /// Pattern for `git grep`
+    pub pattern: String,
+    /// Only search this repository (ID or alias)
+    pub repo_ref: Option<String>,
+    /// Search this branch instead of every worktree
+    pub branch: Option<String>,
+}
+
+/// Where one repo/branch gets searched
+enum Source {
+    /// The worktree's files, uncommitted changes included
+    Worktree(PathBuf),
+    /// A revision in the bare repo, for branches without a worktree
+    Revision(PathBuf, String),
+}
+
+struct Target {
+    repo_id: String,
+    branch: String,
+    source: Source,
+}
+
+/// Run `git grep` across the workspace's repos
+///
+/// Searches every worktree, or with `--branch` the branch's worktree where
+/// there is one and the bare repo's copy of the branch otherwise. Repos
+/// without worktrees are searched on their default branch.
+pub fn grep(ws: &Workspace, opts: GrepOptions, out: &Output) -> Result<()> {
+    let repo_ids: Vec<String> = match &opts.repo_ref {
+        Some(reference) => vec![
+            ws.resolve_repo(reference)
+                .map(|s| s.to_string())
+                .ok_or_else(|| anyhow::anyhow!("repository not found: {}", reference))?,
+        ],
+        None => {
+            let mut ids: Vec<String> = ws.manifest.repos.keys().cloned().collect();
+            ids.sort();
+            ids
+        }
+    };
+
+    let targets = find_targets(ws, &repo_ids, opts.branch.as_deref(), out);
+
+    let results: Vec<(&Target, Result<Vec<GrepMatch>>)> = targets
+        .par_iter()
+        .map(|target| {
+            let found = match &target.source {
+                Source::Worktree(path) => git::grep_worktree(path, &opts.pattern),
+                Source::Revision(bare, revision) => {
+                    git::grep_revision(bare, revision, &opts.pattern)
+                }
+            };
+            (target, found)
+        })
+        .collect();
+
+    let mut total = 0;
+    let mut entries = Vec::new();
+    for (target, found) in results {
+        let matches = match found {
+            Ok(matches) => matches,
+            Err(e) => {
+                out.warn(&format!(
+                    "Skipped {} ({}): {:#}",
+                    target.repo_id, target.branch, e
+                ));
+                continue;
+            }
+        };
+        total += matches.len();
+
+        for m in matches {
+            match out.format {
+                OutputFormat::Human => println!(
+                    "{}@{}:{}:{}:{}",
+                    target.repo_id, target.branch, m.path, m.line, m.text
+                ),
+                OutputFormat::Json => {
+                    let worktree = match &target.source {
+                        Source::Worktree(path) => Some(ws.display_path(path)),
+                        Source::Revision(..) => None,
+                    };
+                    entries.push(serde_json::json!({
+                        "repo_id": target.repo_id,
+                        "branch": target.branch,
+                        "worktree": worktree,
+                        "path": m.path,
+                        "line": m.line,
+                        "text": m.text,
+                    }));
+                }
+            }
+        }
+    }
+
+    match out.format {
+        OutputFormat::Human => {
+            if total == 0 {
+                out.info(&format!("No matches for {}", opts.pattern));
+            }
+        }
+        OutputFormat::Json => out.print_json(&entries)?,
+    }
+
+    Ok(())
+}
+
+/// Pick what to search for each repo, in repo and branch order
+fn find_targets(
+    ws: &Workspace,
+    repo_ids: &[String],
+    branch: Option<&str>,
+    out: &Output,
+) -> Vec<Target> {
+    // Worktrees by repo, first one per branch (by container path)
+    let mut worktrees: Vec<(String, String, PathBuf)> = Vec::new();
+    let mut containers = find_baum_dirs(&ws.root, &ws.root, &ws.config);
+    containers.sort();
+    for container in containers {
+        let Ok(baum) = load_baum(&container) else {
+            continue;
+        };
+        if !repo_ids.contains(&baum.repo_id) {
+            continue;
+        }
+        for wt in baum.worktrees {
+            let path = container.join(&wt.path);
+            let known = worktrees
+                .iter()
+                .any(|(repo, b, _)| *repo == baum.repo_id && *b == wt.branch);
+            if path.is_dir() && !known && branch.is_none_or(|b| b == wt.branch) {
+                worktrees.push((baum.repo_id.clone(), wt.branch, path));
+            }
+        }
+    }
+
+    let mut targets = Vec::new();
+    for repo_id in repo_ids {
+        let mut repo_worktrees: Vec<_> =
+            worktrees.iter().filter(|(r, _, _)| r == repo_id).collect();
+        repo_worktrees.sort_by(|a, b| a.1.cmp(&b.1));
+        if !repo_worktrees.is_empty() {
+            targets.extend(repo_worktrees.into_iter().map(|(repo, b, path)| Target {
+                repo_id: repo.clone(),
+                branch: b.clone(),
+                source: Source::Worktree(path.clone()),
+            }));
+            continue;
+        }
+
+        // No worktree to search: read the branch from the bare repo
+        let Ok(bare_path) = ws.bare_repo_path(repo_id) else {
+            continue;
+        };
+        if !bare_path.exists() {
+            out.verbose(&format!("Skipping {}: not cloned", repo_id));
+            continue;
+        }
+        let branch = match branch {
+            Some(b) => b.to_string(),
+            None => match git::bare::get_default_branch(&bare_path) {
+                Ok(b) => b,
+                Err(_) => continue,
+            },
+        };
+        let revision = if git::has_remote_branch(&bare_path, "origin", &branch) {
+            format!("origin/{}", branch)
+        } else if git::check_branch_exists(&bare_path, &branch).unwrap_or(false) {
+            branch.clone()
+        } else {
+            out.verbose(&format!("Skipping {}: no branch {}", repo_id, branch));
+            continue;
+        };
+        targets.push(Target {
+            repo_id: repo_id.clone(),
+            branch,
+            source: Source::Revision(bare_path, revision),
+        });
+    }
+    targets
+}
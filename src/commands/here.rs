@@ -0,0 +1,127 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+
+use crate::output::{JSON_SCHEMA_VERSION, Output, OutputFormat};
+use crate::types::{BaumManifest, WorktreeEntry};
+use crate::workspace::baum::load_baum;
+use crate::workspace::{Workspace, find_enclosing_baum, validate_workspace_path};
+
+/// Options for here command
+pub struct HereOptions {
+    /// Any path inside a worktree
+    pub path: PathBuf,
+}
+
+/// The worktree a path is in, and its baum
+pub struct Here {
+    /// Absolute baum container path
+    pub container: PathBuf,
+    pub baum: BaumManifest,
+    pub worktree: WorktreeEntry,
+}
+
+impl Here {
+    /// Absolute worktree path
+    pub fn worktree_path(&self) -> PathBuf {
+        self.container.join(&self.worktree.path)
+    }
+
+    /// The branch checked out in the worktree
+    pub fn local_branch(&self) -> &str {
+        self.worktree
+            .local_branch
+            .as_deref()
+            .unwrap_or(&self.worktree.branch)
+    }
+
+    /// The remote branch the worktree's branch tracks
+    pub fn upstream(&self) -> String {
+        self.worktree
+            .track
+            .clone()
+            .unwrap_or_else(|| format!("origin/{}", self.worktree.branch))
+    }
+}
+
+/// Find the wald worktree holding `path`, from the nearest baum above it
+/// (and within `root`, if given)
+pub fn find_here(path: &Path, root: Option<&Path>) -> Result<Here> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let Some(container) = find_enclosing_baum(&path, root) else {
+        bail!("not inside a wald worktree: {}", path.display());
+    };
+
+    let baum = load_baum(&container)?;
+    let worktree = match path.strip_prefix(&container)?.components().next() {
+        Some(Component::Normal(name)) => baum
+            .worktrees
+            .iter()
+            .find(|wt| name == wt.path.as_str())
+            .cloned(),
+        _ => None,
+    };
+    let Some(worktree) = worktree else {
+        bail!(
+            "not inside a wald worktree: {} is in baum {} but none of its worktrees",
+            path.display(),
+            container.display()
+        );
+    };
+
+    Ok(Here {
+        container,
+        baum,
+        worktree,
+    })
+}
+
+#[derive(Serialize)]
+struct HereDisplay {
+    schema_version: u32,
+    container: String,
+    worktree: String,
+    repo_id: String,
+    baum_id: Option<String>,
+    branch: String,
+    local_branch: String,
+    upstream: String,
+}
+
+/// Print which baum, repo and branch a path belongs to, failing outside a
+/// wald worktree (for shell prompts and editor status bars)
+pub fn here(ws: &Workspace, opts: HereOptions, out: &Output) -> Result<()> {
+    let path = validate_workspace_path(&ws.root, &opts.path)?;
+    let here = find_here(&path, Some(&ws.root))?;
+
+    let display = HereDisplay {
+        schema_version: JSON_SCHEMA_VERSION,
+        container: ws.display_path(&here.container),
+        worktree: ws.display_path(&here.worktree_path()),
+        local_branch: here.local_branch().to_string(),
+        upstream: here.upstream(),
+        repo_id: here.baum.repo_id,
+        baum_id: here.baum.id,
+        branch: here.worktree.branch,
+    };
+
+    match out.format {
+        OutputFormat::Human => {
+            println!("{:<14}{}", out.words("baum:"), display.container);
+            println!("worktree:     {}", display.worktree);
+            println!("repo:         {}", display.repo_id);
+            println!(
+                "{:<14}{}",
+                out.words("baum id:"),
+                display.baum_id.as_deref().unwrap_or("-")
+            );
+            println!("branch:       {}", display.branch);
+            println!("local branch: {}", display.local_branch);
+            println!("upstream:     {}", display.upstream);
+        }
+        OutputFormat::Json => out.print_json(&display)?,
+    }
+
+    Ok(())
+}
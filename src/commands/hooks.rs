@@ -0,0 +1,105 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use crate::git;
+use crate::output::Output;
+use crate::workspace::Workspace;
+
+/// Marks hooks wald wrote, so re-running replaces them but leaves others be
+const MARKER: &str = "# Installed by `wald hooks install-workspace`; re-run it to update";
+
+/// Options for hooks install-workspace command
+pub struct HooksInstallOptions {
+    /// Replace hooks wald didn't write
+    pub force: bool,
+}
+
+/// The workspace repo's hooks: name and the wald command it runs
+const WORKSPACE_HOOKS: &[(&str, &str)] = &[
+    ("pre-commit", "wald lint"),
+    ("pre-push", "wald doctor --errors-only"),
+];
+
+/// Install hooks into the workspace repo that keep broken metadata from
+/// being committed or pushed to other machines
+///
+/// Honors `core.hooksPath`. A machine without wald on its `PATH` skips the
+/// checks with a note rather than refusing every commit.
+pub fn hooks_install_workspace(
+    ws: &Workspace,
+    opts: HooksInstallOptions,
+    out: &Output,
+) -> Result<()> {
+    out.require_human("hooks install-workspace")?;
+
+    if ws.config.standalone || !Workspace::is_git_repo(&ws.root) {
+        bail!(
+            "{} is not a git repository; hooks need a workspace repo (see `wald init`)",
+            ws.root.display()
+        );
+    }
+    let Some(hooks_dir) = git::git_path(&ws.root, "hooks") else {
+        bail!(
+            "failed to find the hooks directory of {}",
+            ws.root.display()
+        );
+    };
+
+    // Check every hook before writing any
+    let hooks: Vec<_> = WORKSPACE_HOOKS
+        .iter()
+        .map(|(name, command)| (hooks_dir.join(name), hook_script(command)))
+        .collect();
+    for (path, _) in &hooks {
+        check_conflict(path, opts.force)?;
+    }
+
+    fs::create_dir_all(&hooks_dir)
+        .with_context(|| format!("failed to create directory: {}", hooks_dir.display()))?;
+    for (path, content) in &hooks {
+        if fs::read_to_string(path).is_ok_and(|existing| existing == *content) {
+            out.info(&format!("Up to date: {}", ws.display_path(path)));
+            continue;
+        }
+        fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+                .with_context(|| format!("failed to make executable: {}", path.display()))?;
+        }
+        out.status("Installed", &ws.display_path(path));
+    }
+
+    out.info("Bypass them once with `git commit --no-verify` or `git push --no-verify`");
+    Ok(())
+}
+
+fn hook_script(command: &str) -> String {
+    format!(
+        "#!/bin/sh\n\
+         {}\n\
+         if ! command -v wald >/dev/null 2>&1; then\n\
+         \x20   echo \"wald not found on PATH; skipping \\`{}\\`\" >&2\n\
+         \x20   exit 0\n\
+         fi\n\
+         exec {}\n",
+        MARKER, command, command
+    )
+}
+
+/// Refuse to replace a hook wald didn't write, unless forced
+fn check_conflict(path: &Path, force: bool) -> Result<()> {
+    let Ok(existing) = fs::read_to_string(path) else {
+        return Ok(());
+    };
+    if !force && !existing.lines().take(2).any(|line| line == MARKER) {
+        bail!(
+            "{} exists and wasn't installed by wald; use --force to replace it",
+            path.display()
+        );
+    }
+    Ok(())
+}
@@ -1,9 +1,12 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 
+use crate::forge;
+use crate::git;
 use crate::output::Output;
+use crate::timings::TimedCommand;
 use crate::workspace::Workspace;
 
 /// Options for the init command
@@ -14,6 +17,12 @@ pub struct InitOptions {
     pub force: bool,
     /// Don't run git init (error if not already a git repo)
     pub no_git: bool,
+    /// Standalone workspace without a workspace git repo or sync
+    pub no_sync: bool,
+    /// Add this URL as origin, push the initial commit and track it
+    pub remote: Option<String>,
+    /// Create this GitHub repository (`owner/name`) to use as origin
+    pub create_github: Option<String>,
 }
 
 /// Initialize a new wald workspace
@@ -23,12 +32,13 @@ pub fn init(opts: InitOptions, out: &Output) -> Result<()> {
     // Determine target path
     let target = opts
         .path
+        .clone()
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
 
     let target = target.canonicalize().unwrap_or_else(|_| target.clone());
 
-    // Check if target is a git repository
-    if !Workspace::is_git_repo(&target) {
+    // Check if target is a git repository (standalone workspaces don't need one)
+    if !opts.no_sync && !Workspace::is_git_repo(&target) {
         if opts.no_git {
             bail!(
                 "{} is not a git repository. Remove --no-git to initialize one.",
@@ -44,20 +54,45 @@ pub fn init(opts: InitOptions, out: &Output) -> Result<()> {
         let status = Command::new("git")
             .args(["init"])
             .current_dir(&target)
-            .status()?;
+            .timed_status()?;
 
         if !status.success() {
             bail!("git init failed");
         }
     }
 
+    let publish = opts.remote.is_some() || opts.create_github.is_some();
+    if publish && git::has_remote(&target, "origin") {
+        bail!(
+            "{} already has an origin remote; leave out --remote/--create-github",
+            target.display()
+        );
+    }
+
     // Initialize workspace
     Workspace::init(&target, opts.force)?;
 
-    out.success(&format!(
-        "Initialized wald workspace at {}",
-        target.display()
-    ));
+    if publish {
+        publish_workspace(&target, &opts, out).with_context(|| {
+            format!(
+                "initialized the workspace at {}, but could not publish it",
+                target.display()
+            )
+        })?;
+    }
+
+    if opts.no_sync {
+        let mut ws = Workspace::load_from(target.clone())?;
+        ws.config.standalone = true;
+        ws.save_config()?;
+    }
+
+    let kind = if opts.no_sync {
+        "standalone wald workspace"
+    } else {
+        "wald workspace"
+    };
+    out.success(&format!("Initialized {} at {}", kind, target.display()));
     out.info("");
     out.info("Next steps:");
     out.info("  1. Add repositories: wald repo add github.com/user/repo");
@@ -65,3 +100,52 @@ pub fn init(opts: InitOptions, out: &Output) -> Result<()> {
 
     Ok(())
 }
+
+/// Wire up the workspace's origin: create it on GitHub if asked, commit the
+/// new workspace, and push it with upstream tracking
+fn publish_workspace(target: &Path, opts: &InitOptions, out: &Output) -> Result<()> {
+    let url = match &opts.create_github {
+        Some(slug) => {
+            out.status("Creating", &format!("github.com/{}", slug));
+            let ssh_url = forge::create_github_repo(slug)?;
+            opts.remote.clone().unwrap_or(ssh_url)
+        }
+        None => opts.remote.clone().unwrap_or_default(),
+    };
+
+    git::add_remote(target, "origin", &url)?;
+
+    run_git(target, &["add", "--", ".wald", ".gitignore"])?;
+    let staged = Command::new("git")
+        .arg("-C")
+        .arg(target)
+        .args(["diff", "--cached", "--quiet"])
+        .timed_status()?;
+    if !staged.success() {
+        run_git(
+            target,
+            &["commit", "--quiet", "-m", "Initialize wald workspace"],
+        )?;
+    }
+
+    out.status("Pushing", &url);
+    run_git(
+        target,
+        &["push", "--quiet", "--set-upstream", "origin", "HEAD"],
+    )
+}
+
+fn run_git(repo: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .timed_output()
+        .with_context(|| format!("failed to execute git {}", args[0]))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git {} failed: {}", args[0], stderr.trim());
+    }
+    Ok(())
+}
@@ -0,0 +1,578 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+use serde_json::json;
+use serde_yml::{Mapping, Value};
+
+use crate::output::{JSON_SCHEMA_VERSION, Output, OutputFormat};
+use crate::types::{BaumManifest, MANIFEST_DIR, Manifest, RepoEntry, RepoId};
+use crate::workspace::{WALD_DIR, find_baum_dirs, walk_config};
+
+/// Keys of the central manifest (and each manifest.d/ file)
+const MANIFEST_KEYS: &[&str] = &["version", "identities", "repos"];
+
+/// Keys of a repo entry
+const REPO_KEYS: &[&str] = &[
+    "lfs",
+    "depth",
+    "filter",
+    "tags",
+    "url",
+    "upstream",
+    "reference",
+    "aliases",
+    "git_config",
+    "identity",
+    "branching",
+    "group",
+    "size",
+    "bare_path",
+    "post_fetch",
+];
+
+/// Keys of a baum manifest
+const BAUM_KEYS: &[&str] = &[
+    "version",
+    "id",
+    "repo_id",
+    "default_branch",
+    "owner",
+    "worktrees",
+];
+
+/// Keys of a baum's worktree entry
+const WORKTREE_KEYS: &[&str] = &[
+    "branch",
+    "path",
+    "local_branch",
+    "track",
+    "created_at",
+    "created_from",
+    "created_commit",
+    "last_used",
+];
+
+/// Options for lint command
+pub struct LintOptions {
+    /// Workspace root
+    pub root: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "ERROR",
+            Severity::Warning => "WARN",
+        }
+    }
+}
+
+/// A problem in one of the workspace's manifests
+#[derive(Debug, Serialize)]
+struct Finding {
+    severity: Severity,
+    /// The file, relative to the workspace root
+    file: String,
+    message: String,
+}
+
+/// Collects findings while the files are checked
+#[derive(Default)]
+struct Lint {
+    findings: Vec<Finding>,
+}
+
+impl Lint {
+    fn error(&mut self, file: &str, message: String) {
+        self.push(Severity::Error, file, message);
+    }
+
+    fn warn(&mut self, file: &str, message: String) {
+        self.push(Severity::Warning, file, message);
+    }
+
+    fn push(&mut self, severity: Severity, file: &str, message: String) {
+        self.findings.push(Finding {
+            severity,
+            file: file.to_string(),
+            message,
+        });
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    /// Warn about keys of `map` (at `at`) that wald doesn't know
+    fn unknown_keys(&mut self, file: &str, at: &str, map: &Mapping, known: &[&str]) {
+        for key in map.keys() {
+            let name = key
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{:?}", key));
+            if !known.contains(&name.as_str()) {
+                self.warn(file, format!("unknown key '{}'{}", name, at));
+            }
+        }
+    }
+}
+
+/// Check the central manifest and every baum manifest against what wald
+/// reads, without loading the workspace (so a broken manifest is reported
+/// rather than stopping the check)
+///
+/// Fails when there are errors, for use in a pre-commit hook.
+pub fn lint(opts: LintOptions, out: &Output) -> Result<()> {
+    let mut lint = Lint::default();
+    let repos = lint_central_manifest(&opts.root, &mut lint);
+    let baums = lint_baums(&opts.root, &repos, &mut lint);
+    lint.findings.sort_by_key(|f| f.severity);
+
+    let errors = lint.count(Severity::Error);
+    let warnings = lint.count(Severity::Warning);
+
+    match out.format {
+        OutputFormat::Json => {
+            let report = json!({
+                "schema_version": JSON_SCHEMA_VERSION,
+                "valid": errors == 0,
+                "errors": errors,
+                "warnings": warnings,
+                "baums": baums,
+                "findings": lint.findings,
+            });
+            out.print_json(&report)?;
+        }
+        OutputFormat::Human => {
+            if lint.findings.is_empty() {
+                out.success(&format!(
+                    "No issues in the manifest and {} baum manifest(s)",
+                    baums
+                ));
+            } else {
+                println!(
+                    "Found {} issue(s) ({} errors, {} warnings)",
+                    errors + warnings,
+                    errors,
+                    warnings
+                );
+                println!();
+                for finding in &lint.findings {
+                    println!(
+                        "  [{}] {}: {}",
+                        finding.severity.label(),
+                        finding.file,
+                        finding.message
+                    );
+                }
+            }
+        }
+    }
+
+    if errors > 0 {
+        bail!("the workspace manifests have {} error(s)", errors);
+    }
+    Ok(())
+}
+
+/// Read a YAML file as a mapping, recording why it can't be
+fn read_mapping(path: &Path, file: &str, lint: &mut Lint) -> Option<Mapping> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            lint.error(file, format!("can't be read: {}", e));
+            return None;
+        }
+    };
+    match serde_yml::from_str::<Value>(&content) {
+        Ok(Value::Mapping(map)) => Some(map),
+        Ok(Value::Null) => Some(Mapping::new()),
+        Ok(_) => {
+            lint.error(file, "expected a mapping at the top level".to_string());
+            None
+        }
+        Err(e) => {
+            lint.error(file, format!("invalid YAML: {}", e));
+            None
+        }
+    }
+}
+
+/// Flag a `version:` this wald can't read
+fn check_version(map: &Mapping, current: u32, file: &str, lint: &mut Lint) -> bool {
+    match map.get("version") {
+        None => true,
+        Some(v) => match v.as_u64() {
+            Some(v) if v <= u64::from(current) => true,
+            Some(v) => {
+                lint.error(
+                    file,
+                    format!(
+                        "format version {} is newer than this wald supports ({})",
+                        v, current
+                    ),
+                );
+                false
+            }
+            None => {
+                lint.error(file, "invalid version: expected a number".to_string());
+                false
+            }
+        },
+    }
+}
+
+/// Check manifest.yaml and manifest.d/*.yaml, then the repos across them;
+/// returns the repos that parsed
+fn lint_central_manifest(root: &Path, lint: &mut Lint) -> BTreeMap<String, RepoEntry> {
+    let wald_dir = root.join(WALD_DIR);
+    let mut files = vec![format!("{}/manifest.yaml", WALD_DIR)];
+    if let Ok(entries) = fs::read_dir(wald_dir.join(MANIFEST_DIR)) {
+        let mut names: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name.ends_with(".yaml") && !name.starts_with('.'))
+            .collect();
+        names.sort();
+        files.extend(
+            names
+                .into_iter()
+                .map(|name| format!("{}/{}/{}", WALD_DIR, MANIFEST_DIR, name)),
+        );
+    }
+
+    let mut repos: BTreeMap<String, RepoEntry> = BTreeMap::new();
+    let mut defined_in: BTreeMap<String, String> = BTreeMap::new();
+    let mut identities: BTreeMap<String, String> = BTreeMap::new();
+    for file in &files {
+        let Some(map) = read_mapping(&root.join(file), file, lint) else {
+            continue;
+        };
+        if !check_version(&map, Manifest::default().version, file, lint) {
+            continue;
+        }
+        lint.unknown_keys(file, "", &map, MANIFEST_KEYS);
+
+        if let Some(value) = map.get("identities") {
+            match serde_yml::from_value::<BTreeMap<String, String>>(value.clone()) {
+                Ok(hosts) => {
+                    for host in hosts.into_keys() {
+                        if let Some(first) = identities.insert(host.clone(), file.clone()) {
+                            lint.error(
+                                file,
+                                format!("identity for {} is also defined in {}", host, first),
+                            );
+                        }
+                    }
+                }
+                Err(e) => lint.error(file, format!("invalid identities: {}", e)),
+            }
+        }
+
+        let Some(value) = map.get("repos") else {
+            continue;
+        };
+        let Some(entries) = value.as_mapping() else {
+            if !value.is_null() {
+                lint.error(file, "repos must be a mapping of repo IDs".to_string());
+            }
+            continue;
+        };
+        for (key, entry) in entries {
+            let Some(repo_id) = key.as_str() else {
+                lint.error(file, format!("repo ID {:?} is not a string", key));
+                continue;
+            };
+            if let Err(e) = RepoId::parse(repo_id) {
+                lint.error(file, e.to_string());
+            }
+            if let Some(map) = entry.as_mapping() {
+                lint.unknown_keys(file, &format!(" in repo {}", repo_id), map, REPO_KEYS);
+            }
+            let entry = match entry {
+                Value::Null => RepoEntry::default(),
+                _ => match serde_yml::from_value::<RepoEntry>(entry.clone()) {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        lint.error(file, format!("invalid repo {}: {}", repo_id, e));
+                        continue;
+                    }
+                },
+            };
+            if let Some(first) = defined_in.insert(repo_id.to_string(), file.clone()) {
+                lint.error(
+                    file,
+                    format!("repository {} is also defined in {}", repo_id, first),
+                );
+                continue;
+            }
+            repos.insert(repo_id.to_string(), entry);
+        }
+    }
+
+    // References between repos
+    let mut alias_owners: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (repo_id, entry) in &repos {
+        let file = &defined_in[repo_id];
+        for alias in &entry.aliases {
+            alias_owners.entry(alias).or_default().push(repo_id);
+            if alias != repo_id && repos.contains_key(alias) {
+                lint.warn(
+                    file,
+                    format!(
+                        "alias '{}' of {} is the ID of another repo, which always wins",
+                        alias, repo_id
+                    ),
+                );
+            }
+        }
+        if let Some(upstream) = &entry.upstream
+            && !repos.contains_key(upstream)
+        {
+            lint.warn(
+                file,
+                format!(
+                    "upstream {} of {} is not a registered repo",
+                    upstream, repo_id
+                ),
+            );
+        }
+        if let Some(reference) = &entry.reference {
+            if reference == repo_id {
+                lint.error(file, format!("{} borrows objects from itself", repo_id));
+            } else if !repos.contains_key(reference) {
+                lint.error(
+                    file,
+                    format!(
+                        "reference {} of {} is not a registered repo",
+                        reference, repo_id
+                    ),
+                );
+            }
+        }
+    }
+    for (alias, owners) in alias_owners {
+        if owners.len() > 1 {
+            lint.error(
+                &defined_in[owners[0]],
+                format!("alias '{}' is used by {}", alias, owners.join(" and ")),
+            );
+        }
+    }
+
+    repos
+}
+
+/// Check every baum manifest in the workspace; returns how many there are
+fn lint_baums(root: &Path, repos: &BTreeMap<String, RepoEntry>, lint: &mut Lint) -> usize {
+    let baum_dirs = find_baum_dirs(root, root, &walk_config(root));
+    let mut ids: BTreeMap<(String, String), String> = BTreeMap::new();
+
+    for container in &baum_dirs {
+        let path = container.join(".baum").join("manifest.yaml");
+        let file = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        let Some(map) = read_mapping(&path, &file, lint) else {
+            continue;
+        };
+        if !check_version(&map, BaumManifest::new("").version, &file, lint) {
+            continue;
+        }
+        lint.unknown_keys(&file, "", &map, BAUM_KEYS);
+        if let Some(worktrees) = map.get("worktrees").and_then(Value::as_sequence) {
+            for (i, wt) in worktrees.iter().enumerate() {
+                if let Some(wt) = wt.as_mapping() {
+                    lint.unknown_keys(&file, &format!(" in worktree {}", i + 1), wt, WORKTREE_KEYS);
+                }
+            }
+        }
+
+        let baum = match BaumManifest::load(&path) {
+            Ok(baum) => baum,
+            Err(e) => {
+                lint.error(&file, format!("{:#}", e));
+                continue;
+            }
+        };
+
+        if let Err(e) = RepoId::parse(&baum.repo_id) {
+            lint.error(&file, e.to_string());
+        } else if !repos.contains_key(&baum.repo_id) {
+            lint.warn(
+                &file,
+                format!("repo {} is not registered in the manifest", baum.repo_id),
+            );
+        }
+        if let Some(id) = &baum.id
+            && let Some(first) = ids.insert((baum.repo_id.clone(), id.clone()), file.clone())
+        {
+            lint.error(
+                &file,
+                format!("baum ID {} is also used by {} for the same repo", id, first),
+            );
+        }
+
+        let mut paths = BTreeSet::new();
+        let mut branches = BTreeSet::new();
+        for wt in &baum.worktrees {
+            if !paths.insert(wt.path.as_str()) {
+                lint.error(&file, format!("worktree path {} is listed twice", wt.path));
+            }
+            if !branches.insert(wt.branch.as_str()) {
+                lint.error(&file, format!("branch {} has two worktrees", wt.branch));
+            }
+            if wt.path.is_empty()
+                || wt.path.contains('/')
+                || wt.path.contains('\\')
+                || wt.path == "."
+                || wt.path == ".."
+            {
+                lint.error(
+                    &file,
+                    format!(
+                        "worktree path '{}' must be a directory name inside the container",
+                        wt.path
+                    ),
+                );
+            }
+        }
+    }
+
+    baum_dirs.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace(manifest: &str) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join(WALD_DIR)).unwrap();
+        fs::write(dir.path().join(WALD_DIR).join("manifest.yaml"), manifest).unwrap();
+        dir
+    }
+
+    fn plant(root: &Path, container: &str, manifest: &str) {
+        let baum = root.join(container).join(".baum");
+        fs::create_dir_all(&baum).unwrap();
+        fs::write(baum.join("manifest.yaml"), manifest).unwrap();
+    }
+
+    fn messages(lint: &Lint, severity: Severity) -> Vec<&str> {
+        lint.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .map(|f| f.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_central_manifest_checks() {
+        let ws = workspace(
+            "repos:\n  \
+               github.com/a/one:\n    aliases: [x]\n    upstream: github.com/up/one\n    colour: red\n  \
+               github.com/a/two:\n    aliases: [x]\n    reference: github.com/a/three\n  \
+               not-a-repo-id: {}\n\
+             extra: 1\n",
+        );
+        let mut lint = Lint::default();
+        let repos = lint_central_manifest(ws.path(), &mut lint);
+
+        assert_eq!(repos.len(), 3);
+        let errors = messages(&lint, Severity::Error);
+        assert!(
+            errors.iter().any(|m| m.contains("not-a-repo-id")),
+            "{:?}",
+            errors
+        );
+        assert!(errors.contains(&"alias 'x' is used by github.com/a/one and github.com/a/two"));
+        assert!(errors.contains(
+            &"reference github.com/a/three of github.com/a/two is not a registered repo"
+        ));
+        let warnings = messages(&lint, Severity::Warning);
+        assert!(warnings.contains(&"unknown key 'extra'"));
+        assert!(warnings.contains(&"unknown key 'colour' in repo github.com/a/one"));
+        assert!(
+            warnings.contains(
+                &"upstream github.com/up/one of github.com/a/one is not a registered repo"
+            )
+        );
+    }
+
+    #[test]
+    fn test_repo_defined_twice_across_files() {
+        let ws = workspace("repos:\n  github.com/a/one: {}\n");
+        let dir = ws.path().join(WALD_DIR).join(MANIFEST_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("team.yaml"), "repos:\n  github.com/a/one: {}\n").unwrap();
+
+        let mut lint = Lint::default();
+        lint_central_manifest(ws.path(), &mut lint);
+        assert_eq!(
+            messages(&lint, Severity::Error),
+            vec!["repository github.com/a/one is also defined in .wald/manifest.yaml"]
+        );
+        assert_eq!(lint.findings[0].file, ".wald/manifest.d/team.yaml");
+    }
+
+    #[test]
+    fn test_baum_checks() {
+        let ws = workspace("repos:\n  github.com/a/one: {}\n");
+        plant(
+            ws.path(),
+            "tools/one",
+            "repo_id: github.com/a/one\nworktrees:\n\
+             - branch: main\n  path: _main.wt\n  colour: red\n\
+             - branch: dev\n  path: _main.wt\n\
+             - branch: main\n  path: ../escape\n",
+        );
+        plant(ws.path(), "tools/two", "repo_id: github.com/a/two\n");
+
+        let mut lint = Lint::default();
+        let repos = lint_central_manifest(ws.path(), &mut lint);
+        assert_eq!(lint_baums(ws.path(), &repos, &mut lint), 2);
+
+        let errors = messages(&lint, Severity::Error);
+        assert!(errors.contains(&"worktree path _main.wt is listed twice"));
+        assert!(errors.contains(&"branch main has two worktrees"));
+        assert!(
+            errors.contains(
+                &"worktree path '../escape' must be a directory name inside the container"
+            )
+        );
+        let warnings = messages(&lint, Severity::Warning);
+        assert!(warnings.contains(&"unknown key 'colour' in worktree 1"));
+        assert!(warnings.contains(&"repo github.com/a/two is not registered in the manifest"));
+    }
+
+    #[test]
+    fn test_newer_version_is_an_error() {
+        let ws = workspace("version: 999\nrepos: {}\n");
+        let mut lint = Lint::default();
+        lint_central_manifest(ws.path(), &mut lint);
+        assert_eq!(lint.count(Severity::Error), 1);
+        assert!(
+            lint.findings[0]
+                .message
+                .contains("newer than this wald supports")
+        );
+    }
+}
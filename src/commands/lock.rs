@@ -0,0 +1,121 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+use crate::git;
+use crate::output::Output;
+use crate::workspace::baum::load_baum;
+use crate::workspace::{Workspace, is_baum, validate_workspace_path};
+
+/// Options for lock command
+pub struct LockOptions {
+    pub baum_path: PathBuf,
+    pub branch: String,
+    pub reason: Option<String>,
+}
+
+/// Options for unlock command
+pub struct UnlockOptions {
+    pub baum_path: PathBuf,
+    pub branch: String,
+}
+
+/// Lock a worktree so it can't be pruned, moved or uprooted
+///
+/// Useful for worktrees on removable drives: git keeps the worktree's
+/// metadata even while its directory is unavailable.
+pub fn lock(ws: &Workspace, opts: LockOptions, out: &Output) -> Result<()> {
+    out.require_human("lock")?;
+
+    let (bare_path, worktree_path) = resolve_worktree(ws, &opts.baum_path, &opts.branch)?;
+
+    if let Some(info) = git::find_worktree(&bare_path, &worktree_path)?
+        && info.locked
+    {
+        out.info(&format!("Worktree for {} is already locked", opts.branch));
+        return Ok(());
+    }
+
+    git::lock_worktree(&bare_path, &worktree_path, opts.reason.as_deref())?;
+
+    out.success(&format!("Locked worktree for {}", opts.branch));
+
+    Ok(())
+}
+
+/// Unlock a worktree locked with `wald lock`
+pub fn unlock(ws: &Workspace, opts: UnlockOptions, out: &Output) -> Result<()> {
+    out.require_human("unlock")?;
+
+    let (bare_path, worktree_path) = resolve_worktree(ws, &opts.baum_path, &opts.branch)?;
+
+    match git::find_worktree(&bare_path, &worktree_path)? {
+        Some(info) if info.locked => {}
+        _ => {
+            out.info(&format!("Worktree for {} is not locked", opts.branch));
+            return Ok(());
+        }
+    }
+
+    git::unlock_worktree(&bare_path, &worktree_path)?;
+
+    out.success(&format!("Unlocked worktree for {}", opts.branch));
+
+    Ok(())
+}
+
+/// Fail if a worktree is locked, naming the command that unlocks it
+///
+/// Called before operations that would remove or relocate worktrees.
+pub(crate) fn ensure_unlocked(
+    bare_path: &Path,
+    worktree_path: &Path,
+    baum_path: &Path,
+    branch: &str,
+) -> Result<()> {
+    let Ok(Some(info)) = git::find_worktree(bare_path, worktree_path) else {
+        return Ok(());
+    };
+
+    if info.locked {
+        let reason = info
+            .lock_reason
+            .map(|r| format!(" ({})", r))
+            .unwrap_or_default();
+        bail!(
+            "worktree for '{}' is locked{}; run `wald unlock {} {}` first",
+            branch,
+            reason,
+            baum_path.display(),
+            branch
+        );
+    }
+
+    Ok(())
+}
+
+/// Resolve a baum path and branch to (bare repo path, worktree path)
+fn resolve_worktree(ws: &Workspace, baum_path: &Path, branch: &str) -> Result<(PathBuf, PathBuf)> {
+    // Resolve path relative to workspace (with path traversal protection)
+    let container = validate_workspace_path(&ws.root, baum_path)?;
+
+    if !is_baum(&container) {
+        bail!(
+            "not a baum: {} (.baum directory not found)",
+            ws.display_path(&container)
+        );
+    }
+
+    let baum_manifest = load_baum(&container)?;
+
+    let Some(wt) = baum_manifest
+        .worktrees
+        .iter()
+        .find(|wt| wt.branch == branch)
+    else {
+        bail!("no worktree for branch '{}' in baum", branch);
+    };
+
+    let bare_path = ws.bare_repo_path(&baum_manifest.repo_id)?;
+    Ok((bare_path, container.join(&wt.path)))
+}
@@ -0,0 +1,66 @@
+use anyhow::Result;
+
+use crate::output::{Output, OutputFormat};
+use crate::time::{now_secs, parse_since};
+use crate::workspace::{Outcome, Workspace, read_journal};
+
+/// Options for log command
+pub struct LogOptions {
+    /// Only show operations from this point on (e.g. `3d`, `2026-10-01`)
+    pub since: Option<String>,
+}
+
+/// Show the journal of mutating operations run in this workspace
+pub fn log(ws: &Workspace, opts: LogOptions, out: &Output) -> Result<()> {
+    let since = opts
+        .since
+        .as_deref()
+        .map(|s| parse_since(s, now_secs()))
+        .transpose()?;
+
+    let records = read_journal(ws, since)?;
+
+    match out.format {
+        OutputFormat::Human => {
+            if records.is_empty() {
+                out.info("No operations recorded");
+                return Ok(());
+            }
+            // Journal times are ISO 8601; shown like other times
+            let times: Vec<String> = records
+                .iter()
+                .map(|record| match parse_since(&record.time, 0) {
+                    Ok(secs) => out.time(secs),
+                    Err(_) => record.time.clone(),
+                })
+                .collect();
+            let width = times.iter().map(String::len).max().unwrap_or(0);
+            for (record, time) in records.iter().zip(&times) {
+                let outcome = match record.outcome {
+                    Outcome::Ok => "ok",
+                    Outcome::Failed => "FAILED",
+                };
+                let user = record
+                    .user
+                    .as_deref()
+                    .map(|u| format!(" ({})", u))
+                    .unwrap_or_default();
+                println!(
+                    "{:<width$}  {:<6}  wald {}{}",
+                    time,
+                    outcome,
+                    record.args.join(" "),
+                    user
+                );
+                if let Some(error) = &record.error {
+                    println!("    error: {}", error);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            out.print_json(&records)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,25 +1,69 @@
+pub mod adopt_worktree;
+pub mod alias;
+pub mod api;
 pub mod branch;
 pub mod clone;
+pub mod completion;
+pub mod dashboard;
+pub mod debug;
 pub mod doctor;
+pub mod env;
+pub mod export;
+pub mod gc;
+pub mod grep;
+pub mod here;
+pub mod hooks;
 pub mod init;
+pub mod lint;
+pub mod lock;
+pub mod log;
 pub mod move_cmd;
 pub mod plant;
+pub mod plugin;
+pub mod pr;
+pub mod prompt;
 pub mod prune;
+pub mod pull;
+pub mod relocate;
 pub mod repo;
+pub mod self_update;
 pub mod status;
 pub mod sync;
+pub mod tags;
+pub mod trash;
+pub mod undo;
 pub mod uproot;
 pub mod worktrees;
 
+pub use adopt_worktree::adopt_worktree;
+pub use alias::{alias_add, alias_list, alias_remove};
 pub use branch::branch;
 pub use clone::clone;
+pub use dashboard::dashboard;
 pub use doctor::doctor;
+pub use env::env;
+pub use export::export;
+pub use gc::gc;
+pub use grep::grep;
+pub use here::here;
+pub use hooks::hooks_install_workspace;
 pub use init::init;
+pub use lint::lint;
+pub use lock::{lock, unlock};
+pub use log::log;
 pub use move_cmd::move_baum;
-pub use plant::plant;
+pub use plant::{plant, plant_file, plant_group};
+pub use pr::pr_create;
+pub use prompt::prompt;
 pub use prune::{prune, prune_branches};
-pub use repo::{repo_add, repo_fetch, repo_gc, repo_list, repo_remove};
+pub use pull::pull;
+pub use relocate::relocate;
+pub use repo::{repo_add, repo_fetch, repo_gc, repo_list, repo_relocate, repo_remove};
+pub use self_update::self_update;
 pub use status::status;
 pub use sync::sync;
+pub use tags::tags;
+pub use trash::trash_empty;
+pub use undo::undo_uproot;
 pub use uproot::uproot;
-pub use worktrees::worktrees;
+pub use worktrees::{which, worktrees};
@@ -4,11 +4,16 @@ use std::process::Command;
 
 use anyhow::{Context, Result, bail};
 
+use crate::commands::lock::ensure_unlocked;
 use crate::git::worktree_move;
 use crate::output::Output;
+use crate::timings::TimedCommand;
 use crate::types::WorktreeEntry;
 use crate::workspace::baum::{load_baum, save_baum};
-use crate::workspace::{Workspace, is_baum, validate_workspace_path};
+use crate::workspace::gitignore::{update_worktree_exclude, worktree_ignore_file};
+use crate::workspace::{
+    Workspace, is_baum, lock_baum, validate_container_path, validate_workspace_path,
+};
 
 /// Options for move command
 pub struct MoveOptions {
@@ -22,47 +27,75 @@ pub fn move_baum(ws: &Workspace, opts: MoveOptions, out: &Output) -> Result<()>
 
     // Resolve paths relative to workspace (with path traversal protection)
     let old_container = validate_workspace_path(&ws.root, &opts.old_path)?;
-    let new_container = validate_workspace_path(&ws.root, &opts.new_path)?;
+    let new_container = validate_container_path(&ws.root, &opts.new_path)?;
 
     // Check source exists
     if !old_container.exists() {
-        bail!("source path not found: {}", old_container.display());
+        bail!("source path not found: {}", ws.display_path(&old_container));
     }
 
     // Check source is a baum
     if !is_baum(&old_container) {
         bail!(
             "source is not a baum: {} (.baum directory not found)",
-            old_container.display()
+            ws.display_path(&old_container)
         );
     }
 
     // Check destination doesn't exist
     if new_container.exists() {
-        bail!("destination already exists: {}", new_container.display());
+        bail!(
+            "destination already exists: {}",
+            ws.display_path(&new_container)
+        );
     }
 
-    // Ensure parent of destination exists
+    // Ensure parent of destination exists; a failed move removes it again
+    let mut created = CreatedDirs::new(&new_container);
     if let Some(parent) = new_container.parent() {
-        fs::create_dir_all(parent)?;
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create directory: {}", ws.display_path(parent)))?;
     }
 
+    // Released with the old .baum directory once the move is done
+    let _lock = lock_baum(&old_container, "move")?;
+
     // Load baum manifest for info
     let mut baum_manifest = load_baum(&old_container)?;
 
     out.status(
         "Moving",
-        &format!("{} -> {}", opts.old_path.display(), opts.new_path.display()),
+        &format!(
+            "{} -> {}",
+            ws.display_path(&old_container),
+            ws.display_path(&new_container)
+        ),
     );
 
     // Get bare repo path
     let bare_path = ws.bare_repo_path(&baum_manifest.repo_id)?;
 
+    // Refuse before moving anything so a lock can't leave the baum half-moved
+    for wt in &baum_manifest.worktrees {
+        ensure_unlocked(
+            &bare_path,
+            &old_container.join(&wt.path),
+            &opts.old_path,
+            &wt.branch,
+        )?;
+    }
+
     // Create new container directory first (git worktree move needs parent to exist)
-    fs::create_dir_all(&new_container)?;
+    fs::create_dir_all(&new_container).with_context(|| {
+        format!(
+            "failed to create directory: {}",
+            ws.display_path(&new_container)
+        )
+    })?;
 
     // Move each worktree using git worktree move
     let mut updated_worktrees = Vec::new();
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
     for wt in &baum_manifest.worktrees {
         let old_wt_path = old_container.join(&wt.path);
         let new_wt_path = new_container.join(&wt.path);
@@ -71,18 +104,35 @@ pub fn move_baum(ws: &Workspace, opts: MoveOptions, out: &Output) -> Result<()>
             out.verbose(&format!(
                 "Moving worktree: {} -> {}",
                 wt.path,
-                new_wt_path.display()
+                ws.display_path(&new_wt_path)
             ));
 
             // Use git worktree move to properly update git's internal references
-            worktree_move(&bare_path, &old_wt_path, &new_wt_path)
-                .with_context(|| format!("failed to move worktree {}", wt.branch))?;
+            if let Err(e) = worktree_move(&bare_path, &old_wt_path, &new_wt_path) {
+                // Put back the worktrees already moved, so the baum stays whole
+                for (from, to) in moved.iter().rev() {
+                    if let Err(e) = worktree_move(&bare_path, to, from) {
+                        out.warn(&format!(
+                            "Could not move {} back: {:#}",
+                            ws.display_path(to),
+                            e
+                        ));
+                    }
+                }
+                return Err(e).with_context(|| format!("failed to move worktree {}", wt.branch));
+            }
+            moved.push((old_wt_path, new_wt_path));
         }
 
         updated_worktrees.push(WorktreeEntry {
             branch: wt.branch.clone(),
             path: wt.path.clone(),
             local_branch: wt.local_branch.clone(),
+            track: wt.track.clone(),
+            created_at: wt.created_at.clone(),
+            created_from: wt.created_from.clone(),
+            created_commit: wt.created_commit.clone(),
+            last_used: wt.last_used.clone(),
         });
     }
 
@@ -96,9 +146,10 @@ pub fn move_baum(ws: &Workspace, opts: MoveOptions, out: &Output) -> Result<()>
     // Save manifest to new location
     save_baum(&new_container, &baum_manifest)?;
 
-    // Copy .gitignore if it exists
-    let old_gitignore = old_container.join(".gitignore");
-    let new_gitignore = new_container.join(".gitignore");
+    // Copy the worktree patterns (.gitignore or .baum/gitignore) if any
+    let old_gitignore = worktree_ignore_file(&old_container);
+    let ignore_file = old_gitignore.strip_prefix(&old_container)?.to_path_buf();
+    let new_gitignore = new_container.join(&ignore_file);
     if old_gitignore.exists() {
         fs::copy(&old_gitignore, &new_gitignore)?;
     }
@@ -109,7 +160,7 @@ pub fn move_baum(ws: &Workspace, opts: MoveOptions, out: &Output) -> Result<()>
         fs::remove_dir_all(&old_baum_dir)?;
     }
 
-    // Remove old .gitignore
+    // Remove old .gitignore (gone with .baum/ if it was kept there)
     if old_gitignore.exists() {
         fs::remove_file(&old_gitignore)?;
     }
@@ -119,9 +170,14 @@ pub fn move_baum(ws: &Workspace, opts: MoveOptions, out: &Output) -> Result<()>
         fs::remove_dir(&old_container)?;
     }
 
+    created.keep();
+
     // Stage the changes in git for proper rename detection
     // Since we've manually moved files, use git add/rm to stage the changes
-    stage_baum_move(&ws.root, &old_container, &new_container)?;
+    if !ws.config.standalone {
+        stage_baum_move(&ws.root, &old_container, &new_container, &ignore_file)?;
+    }
+    update_worktree_exclude(&ws.root)?;
 
     out.success(&format!(
         "Moved {} ({} worktree(s))",
@@ -132,26 +188,65 @@ pub fn move_baum(ws: &Workspace, opts: MoveOptions, out: &Output) -> Result<()>
     Ok(())
 }
 
+/// The directories created for a move's destination, removed again
+/// (as far as they are empty) unless the move went through
+struct CreatedDirs {
+    /// The destination container
+    deepest: PathBuf,
+    /// The topmost directory that didn't exist before
+    top: Option<PathBuf>,
+}
+
+impl CreatedDirs {
+    fn new(destination: &Path) -> Self {
+        Self {
+            deepest: destination.to_path_buf(),
+            top: destination
+                .ancestors()
+                .take_while(|dir| !dir.exists())
+                .last()
+                .map(Path::to_path_buf),
+        }
+    }
+
+    fn keep(&mut self) {
+        self.top = None;
+    }
+}
+
+impl Drop for CreatedDirs {
+    fn drop(&mut self) {
+        let Some(top) = &self.top else {
+            return;
+        };
+        for dir in self.deepest.ancestors().filter(|dir| dir.exists()) {
+            if !dir.starts_with(top) || fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+    }
+}
+
 /// Stage a baum move in git for proper rename detection
 /// Uses git add/rm to stage the changes since files are already moved
-fn stage_baum_move(repo: &Path, old: &Path, new: &Path) -> Result<()> {
+fn stage_baum_move(repo: &Path, old: &Path, new: &Path, ignore_file: &Path) -> Result<()> {
     // Stage the new location
     let _ = Command::new("git")
         .arg("-C")
         .arg(repo)
         .arg("add")
         .arg(new.join(".baum"))
-        .output();
+        .timed_output();
 
     // Also stage the new .gitignore if it exists
-    let new_gitignore = new.join(".gitignore");
+    let new_gitignore = new.join(ignore_file);
     if new_gitignore.exists() {
         let _ = Command::new("git")
             .arg("-C")
             .arg(repo)
             .arg("add")
             .arg(&new_gitignore)
-            .output();
+            .timed_output();
     }
 
     // Stage removal of old location (if anything remains tracked)
@@ -164,9 +259,9 @@ fn stage_baum_move(repo: &Path, old: &Path, new: &Path) -> Result<()> {
         .arg("--cached")
         .arg("--ignore-unmatch")
         .arg(&old_baum)
-        .output();
+        .timed_output();
 
-    let old_gitignore = old.join(".gitignore");
+    let old_gitignore = old.join(ignore_file);
     let _ = Command::new("git")
         .arg("-C")
         .arg(repo)
@@ -174,7 +269,7 @@ fn stage_baum_move(repo: &Path, old: &Path, new: &Path) -> Result<()> {
         .arg("--cached")
         .arg("--ignore-unmatch")
         .arg(&old_gitignore)
-        .output();
+        .timed_output();
 
     Ok(())
 }
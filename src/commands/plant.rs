@@ -1,15 +1,23 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
+use rayon::prelude::*;
+use serde::Deserialize;
 
 use crate::git;
-use crate::naming::worktree_dir_name;
+use crate::id::format_wald_branch;
+use crate::naming::unique_worktree_dir_name;
 use crate::output::Output;
-use crate::types::ResolveResult;
+use crate::time::{format_iso, now_secs};
+use crate::types::{Branching, ResolveResult};
 use crate::workspace::baum::{load_baum, save_baum};
 use crate::workspace::gitignore::{add_worktree_to_gitignore, ensure_gitignore_section};
 use crate::workspace::{
-    Workspace, collect_baum_ids, create_baum, is_baum, validate_workspace_path,
+    Tracking, Workspace, configure_tracking, configure_worktree, create_baum, ensure_force_allowed,
+    ensure_tracked_branch, find_enclosing_baum, is_baum, lock_baum, new_baum_owner,
+    validate_container_path, validate_workspace_path,
 };
 
 /// Options for plant command
@@ -18,7 +26,21 @@ pub struct PlantOptions {
     pub container: PathBuf,
     pub branches: Vec<String>,
     pub force: bool,
+    /// Let --force overwrite branches of protected logical branches
+    pub yes_protected: bool,
     pub reuse: bool,
+    /// Track `<remote>[/<branch>]` instead of `origin/<branch>`
+    pub track: Option<String>,
+    /// Allow a container that already holds files (but isn't a baum)
+    pub into_existing: bool,
+    /// Name of a `plant_templates` entry computing the container
+    pub template: Option<String>,
+    /// Plant the branches that don't conflict with existing local branches
+    /// instead of failing
+    pub continue_on_conflict: bool,
+    /// Start the branches at this commit, tag or revision (e.g.
+    /// `origin/main~20`) instead of their remote's tip
+    pub at: Option<String>,
 }
 
 impl PlantOptions {
@@ -33,6 +55,271 @@ impl PlantOptions {
     }
 }
 
+/// Repo argument standing for the baum around the current directory
+pub const HERE: &str = ".";
+
+/// Upper bound on concurrent checkouts; they all read one bare repo's objects
+const MAX_PARALLEL_CHECKOUTS: usize = 4;
+
+/// One entry of a `plant --file` spec
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PlantSpec {
+    repo: String,
+    container: PathBuf,
+    #[serde(default)]
+    branches: Vec<String>,
+}
+
+/// Options for planting every baum listed in a spec file
+pub struct PlantFileOptions {
+    pub file: PathBuf,
+    pub force: bool,
+    pub yes_protected: bool,
+    pub reuse: bool,
+    pub continue_on_conflict: bool,
+}
+
+/// Plant every baum listed in a YAML spec file
+///
+/// Entries are planted in order; a failing entry is reported and skipped so
+/// the rest still get planted. Fails at the end if any entry failed.
+pub fn plant_file(ws: &mut Workspace, opts: PlantFileOptions, out: &Output) -> Result<()> {
+    out.require_human("plant")?;
+
+    let specs = load_plant_specs(&opts.file)?;
+    if specs.is_empty() {
+        out.info(&format!("No entries in {}", opts.file.display()));
+        return Ok(());
+    }
+
+    let modes = PlantModes {
+        force: opts.force,
+        yes_protected: opts.yes_protected,
+        reuse: opts.reuse,
+        continue_on_conflict: opts.continue_on_conflict,
+    };
+    plant_specs(ws, specs, &modes, out)
+}
+
+/// Options for planting a baum for each repo of a group
+pub struct PlantGroupOptions {
+    pub group: String,
+    /// Container of each baum, with placeholders like `{repo}`, or the
+    /// directory they all go in
+    pub container: Option<PathBuf>,
+    /// Name of a `plant_templates` entry to use instead of `container`
+    pub template: Option<String>,
+    pub branches: Vec<String>,
+    pub force: bool,
+    pub yes_protected: bool,
+    pub reuse: bool,
+    pub continue_on_conflict: bool,
+}
+
+/// Plant a baum for each repo of a group, like `plant --file` would
+pub fn plant_group(ws: &mut Workspace, opts: PlantGroupOptions, out: &Output) -> Result<()> {
+    out.require_human("plant")?;
+
+    let container = match (&opts.template, &opts.container) {
+        (Some(name), _) => plant_template(ws, name)?.to_string(),
+        (None, Some(container)) => container.to_string_lossy().into_owned(),
+        (None, None) => bail!("plant --group needs a container path or --template"),
+    };
+    let specs = ws
+        .group_repos(&opts.group)?
+        .into_iter()
+        .map(|repo| {
+            Ok(PlantSpec {
+                container: group_container(&container, &repo)?,
+                repo,
+                branches: opts.branches.clone(),
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    let modes = PlantModes {
+        force: opts.force,
+        yes_protected: opts.yes_protected,
+        reuse: opts.reuse,
+        continue_on_conflict: opts.continue_on_conflict,
+    };
+    plant_specs(ws, specs, &modes, out)
+}
+
+/// A group member's container: the template expanded for the repo, or
+/// the repo's name appended to a plain path
+fn group_container(template: &str, repo_id: &str) -> Result<PathBuf> {
+    if template.contains('{') {
+        expand_template(template, repo_id, now_secs())
+    } else {
+        Ok(Path::new(template).join(repo_name(repo_id)))
+    }
+}
+
+/// A template of `plant_templates` in .wald/config.yaml
+fn plant_template<'a>(ws: &'a Workspace, name: &str) -> Result<&'a str> {
+    match ws.config.plant_templates.get(name) {
+        Some(template) => Ok(template),
+        None if ws.config.plant_templates.is_empty() => bail!(
+            "no plant template '{}'; define it under plant_templates in .wald/config.yaml",
+            name
+        ),
+        None => bail!(
+            "no plant template '{}' (have: {})",
+            name,
+            ws.config
+                .plant_templates
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Last part of a repo ID (`github.com/org/api` -> `api`)
+fn repo_name(repo_id: &str) -> &str {
+    repo_id.rsplit('/').next().unwrap_or(repo_id)
+}
+
+/// Fill in a container template's placeholders for a repo: `{repo}` (its
+/// name), `{owner}` (the path above it), `{host}`, and `{year}`, `{month}`
+/// and `{date}` of `now`
+fn expand_template(template: &str, repo_id: &str, now: u64) -> Result<PathBuf> {
+    let (host, path) = repo_id.split_once('/').unwrap_or(("", repo_id));
+    let owner = path.rsplit_once('/').map_or("", |(owner, _)| owner);
+    let date = &format_iso(now)[..10];
+
+    let mut expanded = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            bail!("unclosed '{{' in template '{}'", template);
+        };
+        expanded.push_str(match &rest[start + 1..start + len] {
+            "repo" => repo_name(repo_id),
+            "owner" => owner,
+            "host" => host,
+            "year" => &date[..4],
+            "month" => &date[5..7],
+            "date" => date,
+            other => bail!(
+                "unknown placeholder {{{}}} in template '{}'; use {{repo}}, {{owner}}, {{host}}, {{year}}, {{month}} or {{date}}",
+                other,
+                template
+            ),
+        });
+        rest = &rest[start + len + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(PathBuf::from(expanded))
+}
+
+/// How existing branches are handled, shared by every entry of a spec
+struct PlantModes {
+    force: bool,
+    yes_protected: bool,
+    reuse: bool,
+    continue_on_conflict: bool,
+}
+
+/// Plant spec entries in order, carrying on past failures
+fn plant_specs(
+    ws: &mut Workspace,
+    specs: Vec<PlantSpec>,
+    modes: &PlantModes,
+    out: &Output,
+) -> Result<()> {
+    let total = specs.len();
+    let mut failed = Vec::new();
+    for (i, spec) in specs.into_iter().enumerate() {
+        let label = format!("{} at {}", spec.repo, spec.container.display());
+        out.status(&format!("[{}/{}]", i + 1, total), &label);
+
+        let plant_opts = PlantOptions {
+            repo_ref: spec.repo,
+            container: spec.container,
+            branches: spec.branches,
+            force: modes.force,
+            yes_protected: modes.yes_protected,
+            reuse: modes.reuse,
+            track: None,
+            into_existing: false,
+            template: None,
+            continue_on_conflict: modes.continue_on_conflict,
+            at: None,
+        };
+        if let Err(e) = plant(ws, plant_opts, out) {
+            out.warn(&format!("Failed to plant {}: {:#}", label, e));
+            failed.push(label);
+        }
+    }
+
+    if failed.is_empty() {
+        out.success(&format!("Planted {} of {} entries", total, total));
+        return Ok(());
+    }
+
+    bail!(
+        "{} of {} entries failed:\n  {}",
+        failed.len(),
+        total,
+        failed.join("\n  ")
+    );
+}
+
+/// Read a list of `{repo, container, branches}` entries
+fn load_plant_specs(path: &Path) -> Result<Vec<PlantSpec>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse_plant_specs(&content).with_context(|| format!("invalid plant file: {}", path.display()))
+}
+
+fn parse_plant_specs(content: &str) -> Result<Vec<PlantSpec>> {
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_yml::from_str(content)?)
+}
+
+/// Refuse to turn a directory holding files into a baum unless asked to
+///
+/// With `into_existing`, the files stay where they are, next to `.baum` and
+/// the worktrees; only entries shaped like worktree directories (`_*.wt`)
+/// are rejected, since wald would take them for its own.
+fn check_existing_content(ws: &Workspace, container: &Path, into_existing: bool) -> Result<()> {
+    let Ok(entries) = fs::read_dir(container) else {
+        return Ok(());
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    if names.is_empty() {
+        return Ok(());
+    }
+    if !into_existing {
+        bail!(
+            "{} already holds files and is not a baum\n\
+             Pass --into-existing to plant alongside them",
+            ws.display_path(container)
+        );
+    }
+
+    names.retain(|name| name.starts_with('_') && name.ends_with(".wt"));
+    if !names.is_empty() {
+        names.sort();
+        bail!(
+            "{} holds {}, which would collide with worktree directories; rename or move them first",
+            ws.display_path(container),
+            names.join(", ")
+        );
+    }
+    Ok(())
+}
+
 /// Plant a baum (create container with worktrees) or add worktrees to existing baum
 pub fn plant(ws: &mut Workspace, opts: PlantOptions, out: &Output) -> Result<()> {
     out.require_human("plant")?;
@@ -40,33 +327,52 @@ pub fn plant(ws: &mut Workspace, opts: PlantOptions, out: &Output) -> Result<()>
     // Ensure workspace-level .gitignore has wald section
     ensure_gitignore_section(&ws.root)?;
 
+    // A template computes the container from the repo
+    let container = match &opts.template {
+        Some(name) => {
+            let Some(repo_id) = ws.resolve_repo(&opts.repo_ref) else {
+                bail!("repository not found in manifest: {}", opts.repo_ref);
+            };
+            expand_template(plant_template(ws, name)?, repo_id, now_secs())?
+        }
+        // `wald plant . <branch>...` adds to the baum around the current
+        // directory
+        None if opts.repo_ref == HERE => enclosing_baum(ws)?,
+        None => opts.container.clone(),
+    };
+
     // Resolve container path (with path traversal protection)
-    let container = validate_workspace_path(&ws.root, &opts.container)?;
+    let container = validate_container_path(&ws.root, &container)?;
 
     // Check if container path exists as file
     if container.exists() && !container.is_dir() {
         bail!(
             "container path exists but is not a directory: {}",
-            container.display()
+            ws.display_path(&container)
         );
     }
 
     // Check if already a baum - if so, add worktrees to it
     let existing_baum = is_baum(&container);
+    if !existing_baum {
+        check_existing_content(ws, &container, opts.into_existing)?;
+    }
 
     // Load existing baum or resolve repo for new baum
-    let (mut baum_manifest, repo_id, is_new_baum) = if existing_baum {
+    let (mut baum_manifest, repo_id, is_new_baum, _lock) = if existing_baum {
+        // Hold the baum while its manifest is read, changed, and written back
+        let lock = lock_baum(&container, "plant")?;
         let manifest = load_baum(&container)?;
         let repo_id = manifest.repo_id.clone();
 
         // If repo_ref was provided and differs from existing baum, that's an error
-        if !opts.repo_ref.is_empty() {
+        if !opts.repo_ref.is_empty() && opts.repo_ref != HERE {
             match ws.manifest.resolve_with_details(&opts.repo_ref) {
                 ResolveResult::Found(resolved_id) => {
                     if resolved_id != repo_id {
                         bail!(
                             "baum at {} is linked to {}, not {}",
-                            container.display(),
+                            ws.display_path(&container),
                             repo_id,
                             resolved_id
                         );
@@ -85,7 +391,7 @@ pub fn plant(ws: &mut Workspace, opts: PlantOptions, out: &Output) -> Result<()>
             }
         }
 
-        (manifest, repo_id, false)
+        (manifest, repo_id, false, lock)
     } else {
         // Resolve repo reference to ID (required for new baum)
         if opts.repo_ref.is_empty() {
@@ -106,8 +412,10 @@ pub fn plant(ws: &mut Workspace, opts: PlantOptions, out: &Output) -> Result<()>
             }
         };
 
-        let manifest = create_baum(&container, &repo_id)?;
-        (manifest, repo_id, true)
+        let mut manifest = create_baum(&container, &repo_id)?;
+        manifest.owner = new_baum_owner(ws);
+        let lock = lock_baum(&container, "plant")?;
+        (manifest, repo_id, true, lock)
     };
 
     // Verify bare repo exists
@@ -115,7 +423,7 @@ pub fn plant(ws: &mut Workspace, opts: PlantOptions, out: &Output) -> Result<()>
     if !bare_path.exists() {
         bail!(
             "bare repo not found: {}\nRun `wald repo add --clone {}` first",
-            bare_path.display(),
+            ws.display_path(&bare_path),
             repo_id
         );
     }
@@ -130,14 +438,54 @@ pub fn plant(ws: &mut Workspace, opts: PlantOptions, out: &Output) -> Result<()>
     let branch_mode = opts.branch_mode();
 
     // Determine branches to create
-    let branches = if opts.branches.is_empty() {
+    // Pin the repo's default branch in the baum (also for baums planted
+    // before it was recorded)
+    if baum_manifest.default_branch.is_none() {
+        baum_manifest.default_branch = git::bare::get_default_branch(&bare_path).ok();
+    }
+
+    let mut branches = if opts.branches.is_empty() {
         // Default to the default branch
-        let default_branch = git::bare::get_default_branch(&bare_path)?;
+        let default_branch = match &baum_manifest.default_branch {
+            Some(branch) => branch.clone(),
+            None => git::bare::get_default_branch(&bare_path)?,
+        };
         vec![default_branch]
     } else {
         opts.branches
     };
 
+    let tracking = opts.track.as_deref().map(Tracking::parse).transpose()?;
+    let branching = ws.manifest.branching(&repo_id);
+    if tracking.is_some() && branching == Branching::Direct {
+        bail!(
+            "--track is not available for {}: it uses direct branching",
+            repo_id
+        );
+    }
+    if let Some(tracking) = &tracking {
+        if tracking.branch.is_some() && branches.len() > 1 {
+            bail!("--track with a branch can only be used when planting a single branch");
+        }
+        for branch in &branches {
+            ensure_tracked_branch(ws, &repo_id, &bare_path, tracking, branch)?;
+        }
+    }
+
+    // The commit the new branches start at, checked before any branch is touched
+    let at = match &opts.at {
+        Some(rev) => match git::resolve_commit(&bare_path, rev) {
+            Some(commit) => Some(commit),
+            None => bail!(
+                "--at {}: no such commit in {} (`wald repo fetch {}` brings in new ones)",
+                rev,
+                repo_id,
+                repo_id
+            ),
+        },
+        None => None,
+    };
+
     // Check for duplicate branches if adding to existing baum
     if !is_new_baum {
         for branch in &branches {
@@ -149,7 +497,7 @@ pub fn plant(ws: &mut Workspace, opts: PlantOptions, out: &Output) -> Result<()>
                 bail!(
                     "worktree for branch '{}' already exists in baum at {}",
                     branch,
-                    container.display()
+                    ws.display_path(&container)
                 );
             }
         }
@@ -158,61 +506,325 @@ pub fn plant(ws: &mut Workspace, opts: PlantOptions, out: &Output) -> Result<()>
     if is_new_baum {
         out.status(
             "Planting",
-            &format!("{} at {}", repo_id, opts.container.display()),
+            &format!("{} at {}", repo_id, ws.display_path(&container)),
         );
     } else {
         out.status(
             "Adding to baum",
-            &format!("{} at {}", repo_id, opts.container.display()),
+            &format!("{} at {}", repo_id, ws.display_path(&container)),
         );
     }
 
-    // Collect existing baum IDs to avoid collisions
-    let existing_ids = collect_baum_ids(&ws.root);
-
     // Ensure the baum has an ID before creating worktrees
-    let baum_id = baum_manifest.ensure_id(&existing_ids).to_string();
+    let baum_id = ws.ensure_baum_id(&mut baum_manifest).to_string();
 
-    // Create worktrees for each branch using tracking branches
-    let mut created_count = 0;
-    for branch in &branches {
-        let worktree_name = worktree_dir_name(branch);
-        let worktree_path = container.join(&worktree_name);
+    // Find every branch in the way before creating any, so they are
+    // reported together
+    let mut skipped = 0;
+    if branch_mode != git::BranchMode::Force {
+        let conflicts = branch_conflicts(&bare_path, &branches, &baum_id, branching)?;
+        if !conflicts.is_empty() {
+            let report = conflict_report(&conflicts, branches.len());
+            if !opts.continue_on_conflict || conflicts.len() == branches.len() {
+                // --reuse doesn't help: it refuses unpushed commits too
+                let rest = if conflicts.len() < branches.len() {
+                    ", or use --continue-on-conflict to plant the others"
+                } else {
+                    ""
+                };
+                bail!(
+                    "{}\nPush them first or use --force to overwrite them{}",
+                    report,
+                    rest
+                );
+            }
+            out.warn(&format!("{}\nPlanting the others", report));
+            branches.retain(|b| !conflicts.iter().any(|c| &c.branch == b));
+            skipped = conflicts.len();
+        }
+    }
 
+    // Create branch refs one at a time; git locks refs in the shared bare repo
+    let mut planned = Vec::with_capacity(branches.len());
+    let mut taken: HashSet<String> = baum_manifest
+        .worktrees
+        .iter()
+        .map(|wt| wt.path.clone())
+        .collect();
+    for branch in &branches {
+        let worktree_name = unique_worktree_dir_name(branch, &taken);
+        taken.insert(worktree_name.clone());
         out.status(
             "Creating worktree",
             &format!("{} -> {}", branch, worktree_name),
         );
 
-        // Add worktree with tracking branch (wald/<baum_id>/<branch>)
-        let local_branch = git::add_worktree_with_tracking_mode(
-            &bare_path,
-            &worktree_path,
-            branch,
-            &baum_id,
-            branch_mode,
-        )?;
+        // Tracking branch wald/<baum_id>/<branch>, or the branch itself
+        let upstream = match &tracking {
+            Some(tracking) => tracking.upstream_for(branch),
+            None => format!("origin/{}", branch),
+        };
+        if branch_mode == git::BranchMode::Force {
+            let existing = match branching {
+                Branching::Tracking => format_wald_branch(&baum_id, branch),
+                Branching::Direct => branch.clone(),
+            };
+            ensure_force_allowed(ws, &bare_path, branch, &existing, opts.yes_protected)?;
+        }
+        let local_branch = match branching {
+            Branching::Tracking => git::prepare_tracking_branch_from(
+                &bare_path,
+                branch,
+                &baum_id,
+                branch_mode,
+                &upstream,
+            )?,
+            Branching::Direct => git::prepare_direct_branch(&bare_path, branch, branch_mode)?,
+        };
+        if let (Some(commit), Some(rev)) = (&at, &opts.at) {
+            out.status(
+                "Starting",
+                &format!("{} at {} ({})", branch, rev, out.sha(commit)),
+            );
+            git::reset_branch(&bare_path, &local_branch, commit)?;
+        }
+        planned.push((branch, worktree_name, local_branch, upstream));
+    }
 
-        // Update baum manifest with local branch info
-        baum_manifest.add_worktree_with_local(branch, &worktree_name, &local_branch);
+    // Checkouts populate the working trees, which is the slow part
+    let checkouts = checkout_worktrees(&bare_path, &container, &planned, out.show_progress())?;
+
+    let mut created_count = 0;
+    let mut failures = Vec::new();
+    for ((branch, worktree_name, local_branch, upstream), result) in planned.iter().zip(checkouts) {
+        if let Err(e) = result {
+            failures.push(format!("{}: {:#}", branch, e));
+            continue;
+        }
+
+        let worktree_path = container.join(worktree_name);
+        if let Some(identity) = configure_worktree(ws, &repo_id, &bare_path, &worktree_path)? {
+            out.status("Pinning identity", identity);
+        }
+
+        if tracking.is_some() {
+            configure_tracking(&bare_path, &worktree_path, local_branch, branch)?;
+        }
+
+        // Update baum manifest with local branch info and where it started
+        let entry = baum_manifest.add_worktree_with_local(branch, worktree_name, local_branch);
+        entry.track = tracking.as_ref().map(|_| upstream.clone());
+        entry.created_from = git::resolve_commit(&bare_path, upstream).map(|_| upstream.clone());
+        entry.created_commit = git::resolve_commit(&bare_path, local_branch);
 
         // Add to container's .gitignore
-        add_worktree_to_gitignore(&container, &worktree_name)?;
+        add_worktree_to_gitignore(&container, worktree_name)?;
 
         created_count += 1;
     }
 
-    // Save updated baum manifest (ID already set)
+    // Save updated baum manifest (ID already set), including the worktrees
+    // that were created if others failed
     save_baum(&container, &baum_manifest)?;
 
+    if !failures.is_empty() {
+        bail!(
+            "failed to create {} of {} worktree(s):\n  {}",
+            failures.len(),
+            planned.len(),
+            failures.join("\n  ")
+        );
+    }
+
+    let skipped = if skipped > 0 {
+        format!(", skipped {} in conflict", skipped)
+    } else {
+        String::new()
+    };
     if is_new_baum {
         out.success(&format!(
-            "Planted {} with {} worktree(s)",
-            repo_id, created_count
+            "Planted {} with {} worktree(s){}",
+            repo_id, created_count, skipped
         ));
     } else {
-        out.success(&format!("Added {} worktree(s) to baum", created_count));
+        out.success(&format!(
+            "Added {} worktree(s) to baum{}",
+            created_count, skipped
+        ));
     }
 
     Ok(())
 }
+
+/// A requested branch whose local branch exists with unpushed commits
+struct BranchConflict {
+    branch: String,
+    local_branch: String,
+    /// Subjects of the unpushed commits, newest first
+    unpushed: Vec<String>,
+}
+
+impl std::fmt::Display for BranchConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.branch)?;
+        if self.local_branch != self.branch {
+            write!(f, " ({})", self.local_branch)?;
+        }
+        match self.unpushed.first() {
+            Some(last) => write!(
+                f,
+                ": {} commit(s) ahead, last \"{}\"",
+                self.unpushed.len(),
+                last
+            ),
+            None => write!(f, ": ahead of its upstream"),
+        }
+    }
+}
+
+/// The requested branches whose local branch (as `prepare_*_branch` would
+/// name it) already exists with unpushed commits
+/// The baum holding the current directory
+fn enclosing_baum(ws: &Workspace) -> Result<PathBuf> {
+    let cwd = validate_workspace_path(&ws.root, Path::new("."))?;
+    match find_enclosing_baum(&cwd, Some(&ws.root)) {
+        Some(container) => Ok(container),
+        None => bail!(
+            "not inside a baum: {}; `wald plant . <branch>` adds branches to the baum around the current directory",
+            ws.display_path(&cwd)
+        ),
+    }
+}
+
+fn branch_conflicts(
+    bare_path: &Path,
+    branches: &[String],
+    baum_id: &str,
+    branching: Branching,
+) -> Result<Vec<BranchConflict>> {
+    let mut conflicts = Vec::new();
+    for branch in branches {
+        let local_branch = match branching {
+            Branching::Tracking => format_wald_branch(baum_id, branch),
+            Branching::Direct => branch.clone(),
+        };
+        if git::check_branch_exists(bare_path, &local_branch)?
+            && git::has_unpushed_commits(bare_path, &local_branch)?
+        {
+            conflicts.push(BranchConflict {
+                branch: branch.clone(),
+                unpushed: git::unpushed_subjects(bare_path, &local_branch)?,
+                local_branch,
+            });
+        }
+    }
+    Ok(conflicts)
+}
+
+/// One message listing all conflicts, e.g. `2 of 3 branch(es) already
+/// exist with unpushed commits:` and a line per branch
+fn conflict_report(conflicts: &[BranchConflict], requested: usize) -> String {
+    let mut report = format!(
+        "{} of {} branch(es) already exist with unpushed commits:",
+        conflicts.len(),
+        requested
+    );
+    for conflict in conflicts {
+        report.push_str(&format!("\n  {}", conflict));
+    }
+    report
+}
+
+/// Check out prepared branches into their worktrees, a few at a time
+///
+/// Worktrees are registered one by one, since concurrent `git worktree add`
+/// runs read each other's half-written entries in the bare repo; only the
+/// checkouts that populate them run in parallel. Results are in the order
+/// of `planned`.
+fn checkout_worktrees(
+    bare_path: &Path,
+    container: &Path,
+    planned: &[(&String, String, String, String)],
+    progress: bool,
+) -> Result<Vec<Result<String>>> {
+    let registered: Vec<Result<()>> = planned
+        .iter()
+        .map(|(_, worktree_name, local_branch, _)| {
+            git::add_worktree_no_checkout(bare_path, &container.join(worktree_name), local_branch)
+        })
+        .collect();
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(planned.len().min(MAX_PARALLEL_CHECKOUTS))
+        .build()?;
+
+    Ok(pool.install(|| {
+        planned
+            .par_iter()
+            .zip(registered)
+            .map(|((_, worktree_name, local_branch, _), registered)| {
+                registered?;
+                git::checkout_registered_worktree(
+                    bare_path,
+                    &container.join(worktree_name),
+                    progress,
+                )?;
+                Ok(local_branch.clone())
+            })
+            .collect()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plant_specs() {
+        let specs = parse_plant_specs(
+            "- repo: github.com/test/api\n  container: code/api\n  branches: [main, dev]\n\
+             - repo: web\n  container: code/web\n",
+        )
+        .unwrap();
+        assert_eq!(specs.len(), 2);
+        assert_eq!(specs[0].repo, "github.com/test/api");
+        assert_eq!(specs[0].branches, vec!["main", "dev"]);
+        assert_eq!(specs[1].container, PathBuf::from("code/web"));
+        assert!(specs[1].branches.is_empty());
+
+        assert!(parse_plant_specs("").unwrap().is_empty());
+        assert!(parse_plant_specs("- repo: api\n  path: code/api\n").is_err());
+    }
+
+    #[test]
+    fn test_group_container() {
+        assert_eq!(
+            group_container("projects/backend", "github.com/org/api").unwrap(),
+            PathBuf::from("projects/backend/api")
+        );
+        assert_eq!(
+            group_container("code/{repo}/src", "gitlab.com/org/sub/billing").unwrap(),
+            PathBuf::from("code/billing/src")
+        );
+    }
+
+    #[test]
+    fn test_expand_template() {
+        // 2026-10-16T14:23:00Z
+        let now = 1_792_160_580;
+        assert_eq!(
+            expand_template("research/{year}/{repo}", "github.com/org/paper", now).unwrap(),
+            PathBuf::from("research/2026/paper")
+        );
+        assert_eq!(
+            expand_template("{host}/{owner}/{month}-{repo}", "gitlab.com/a/b/c", now).unwrap(),
+            PathBuf::from("gitlab.com/a/b/10-c")
+        );
+        assert_eq!(
+            expand_template("scratch/{date}", "github.com/org/x", now).unwrap(),
+            PathBuf::from("scratch/2026-10-16")
+        );
+        assert!(expand_template("x/{name}", "github.com/org/x", now).is_err());
+        assert!(expand_template("x/{repo", "github.com/org/x", now).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::output::{JSON_SCHEMA_VERSION, Output, OutputFormat};
+use crate::workspace::baum::load_baum;
+use crate::workspace::find_enclosing_baum;
+
+/// Prefix of plugin executables: `wald deploy` runs `wald-deploy`
+pub const PLUGIN_PREFIX: &str = "wald-";
+
+/// Options for running a plugin
+pub struct PluginOptions {
+    /// Everything after the subcommand, passed on untouched
+    pub args: Vec<OsString>,
+    /// Workspace the command was run in, if any
+    pub workspace: Option<PathBuf>,
+}
+
+/// What a plugin gets in `WALD_CONTEXT`
+#[derive(Serialize)]
+pub struct PluginContext {
+    pub schema_version: u32,
+    pub wald_version: &'static str,
+    /// The running wald, for plugins that call back into it
+    pub bin: Option<PathBuf>,
+    pub workspace: Option<PathBuf>,
+    pub cwd: Option<PathBuf>,
+    /// The baum around the current directory
+    pub baum: Option<PluginBaum>,
+    /// `--json`, `--verbose` and `--yes`, which plugins should honour too
+    pub json: bool,
+    pub verbose: bool,
+    pub yes: bool,
+}
+
+#[derive(Serialize)]
+pub struct PluginBaum {
+    pub container: PathBuf,
+    pub repo_id: String,
+}
+
+/// The `wald-<name>` executable on PATH, if there is one
+///
+/// Names that could point elsewhere (containing a path separator) never
+/// match.
+pub fn find_plugin(name: &str) -> Option<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return None;
+    }
+    let file = format!("{}{}{}", PLUGIN_PREFIX, name, env::consts::EXE_SUFFIX);
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(&file))
+        .find(|path| is_executable(path))
+}
+
+/// Run the plugin at `path` in place of wald
+///
+/// The plugin inherits the terminal and gets `WALD_BIN`, `WALD_WORKSPACE`
+/// (when run in a workspace) and the [`PluginContext`] as JSON in
+/// `WALD_CONTEXT`. On Unix wald execs the plugin, so signals and the exit
+/// code are the plugin's own; elsewhere wald waits and exits with its code.
+pub fn run_plugin(path: &Path, opts: PluginOptions, out: &Output) -> Result<()> {
+    let context = plugin_context(&opts, out);
+    let mut cmd = Command::new(path);
+    cmd.args(&opts.args)
+        .env("WALD_CONTEXT", serde_json::to_string(&context)?);
+    if let Some(bin) = &context.bin {
+        cmd.env("WALD_BIN", bin);
+    }
+    if let Some(root) = &opts.workspace {
+        cmd.env("WALD_WORKSPACE", root);
+    }
+    out.verbose(&format!("Running plugin {}", path.display()));
+    exec(&mut cmd).with_context(|| format!("failed to run plugin {}", path.display()))
+}
+
+fn plugin_context(opts: &PluginOptions, out: &Output) -> PluginContext {
+    let cwd = env::current_dir().ok();
+    let baum = match (&opts.workspace, &cwd) {
+        (Some(root), Some(cwd)) => find_enclosing_baum(cwd, Some(root)).and_then(|container| {
+            let manifest = load_baum(&container).ok()?;
+            Some(PluginBaum {
+                container,
+                repo_id: manifest.repo_id,
+            })
+        }),
+        _ => None,
+    };
+    PluginContext {
+        schema_version: JSON_SCHEMA_VERSION,
+        wald_version: env!("WALD_VERSION"),
+        bin: env::current_exe().ok(),
+        workspace: opts.workspace.clone(),
+        cwd,
+        baum,
+        json: out.format == OutputFormat::Json,
+        verbose: out.verbose,
+        yes: out.assume_yes,
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(unix)]
+fn exec(cmd: &mut Command) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    // Only returns if the exec failed
+    Err(cmd.exec().into())
+}
+
+#[cfg(not(unix))]
+fn exec(cmd: &mut Command) -> Result<()> {
+    let status = cmd.status()?;
+    std::process::exit(status.code().unwrap_or(1));
+}
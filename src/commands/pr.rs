@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use super::here::find_here;
+use crate::forge::{self, PullRequest};
+use crate::git;
+use crate::output::Output;
+use crate::timings::TimedCommand;
+use crate::types::RepoId;
+use crate::workspace::{Workspace, validate_workspace_path};
+
+/// Options for pr create command
+pub struct PrCreateOptions {
+    /// Any path inside the worktree
+    pub path: PathBuf,
+    /// Overrides the last commit's subject
+    pub title: Option<String>,
+    /// Overrides the baum's recorded default branch
+    pub base: Option<String>,
+    pub draft: bool,
+}
+
+/// Push a worktree's branch to `origin` and open a pull request for it
+///
+/// The branch is pushed under its logical name, whatever it is called
+/// locally. The title and description come from the last commit; the
+/// base is the default branch recorded when the baum was planted.
+pub fn pr_create(ws: &Workspace, opts: PrCreateOptions, out: &Output) -> Result<()> {
+    out.require_human("pr create")?;
+
+    let path = validate_workspace_path(&ws.root, &opts.path)?;
+    let here = find_here(&path, Some(&ws.root))?;
+    let worktree_path = here.worktree_path();
+    if !worktree_path.exists() {
+        bail!(
+            "worktree is missing: {} (run `wald sync` to restore it)",
+            ws.display_path(&worktree_path)
+        );
+    }
+    let repo_id = RepoId::parse(&here.baum.repo_id)
+        .with_context(|| format!("invalid repo ID '{}'", here.baum.repo_id))?;
+
+    let base = match opts.base.or_else(|| here.baum.default_branch.clone()) {
+        Some(base) => base,
+        None => git::bare::get_default_branch(&ws.bare_repo_path(&here.baum.repo_id)?)?,
+    };
+    let branch = &here.worktree.branch;
+    if *branch == base {
+        bail!(
+            "{} is the base branch; open pull requests from a feature branch",
+            branch
+        );
+    }
+
+    let (subject, body) = last_commit_message(&worktree_path)?;
+    let title = opts.title.unwrap_or(subject);
+
+    out.status(
+        "Pushing",
+        &format!("{} to origin/{}", here.local_branch(), branch),
+    );
+    push_branch(&worktree_path, here.local_branch(), branch)?;
+
+    out.status("Opening", &format!("pull request {} -> {}", branch, base));
+    let url = forge::create_pull_request(
+        &repo_id,
+        &PullRequest {
+            head: branch,
+            base: &base,
+            title: &title,
+            body: &body,
+            draft: opts.draft,
+        },
+    )?;
+
+    println!("{}", url);
+    Ok(())
+}
+
+/// The subject and body of the commit checked out in `worktree`
+fn last_commit_message(worktree: &Path) -> Result<(String, String)> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(worktree)
+        .arg("log")
+        .arg("-1")
+        .arg("--format=%s%n%b")
+        .timed_output()
+        .with_context(|| format!("failed to execute git log in {}", worktree.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to read the last commit in {}: {}",
+            worktree.display(),
+            stderr.trim()
+        );
+    }
+
+    let message = String::from_utf8_lossy(&output.stdout);
+    let (subject, body) = message.split_once('\n').unwrap_or((&message, ""));
+    Ok((subject.trim().to_string(), body.trim().to_string()))
+}
+
+/// Push `local_branch` to `origin/<branch>`
+fn push_branch(worktree: &Path, local_branch: &str, branch: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(worktree)
+        .arg("push")
+        .arg("--quiet")
+        .arg("origin")
+        .arg(format!("refs/heads/{}:refs/heads/{}", local_branch, branch))
+        .timed_output()
+        .with_context(|| format!("failed to execute git push in {}", worktree.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("failed to push {} to origin: {}", branch, stderr.trim());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+
+use crate::commands::here::find_here;
+use crate::git;
+use crate::output::Output;
+
+/// How long a computed prompt segment is reused while the worktree's HEAD
+/// and index stay the same (unstaged edits and fetches only show after it)
+const CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Cache file in the worktree's git dir
+const CACHE_FILE: &str = "wald-prompt";
+
+/// Options for prompt command
+pub struct PromptOptions {
+    /// Any path inside a worktree
+    pub path: PathBuf,
+}
+
+/// Print a compact prompt segment for the worktree holding a path, like
+/// `repo@branch ↑2 ✚`
+///
+/// Reads only that worktree and its baum manifest, never the workspace.
+/// Outside a worktree, or if anything fails, it prints nothing, so a
+/// prompt can call it anywhere.
+pub fn prompt(opts: PromptOptions, out: &Output) -> Result<()> {
+    out.require_human("prompt")?;
+    if let Some(segment) = prompt_segment(&opts.path) {
+        println!("{}", segment);
+    }
+    Ok(())
+}
+
+fn prompt_segment(path: &Path) -> Option<String> {
+    let here = find_here(path, None).ok()?;
+    let worktree = here.worktree_path();
+    let git_dir = worktree_git_dir(&worktree)?;
+
+    // The cache is keyed by HEAD and the index, which commits, checkouts
+    // and staging all rewrite
+    let key = format!(
+        "{}:{}",
+        modified_nanos(&git_dir.join("HEAD")),
+        modified_nanos(&git_dir.join("index"))
+    );
+    let cache = git_dir.join(CACHE_FILE);
+    if let Some(segment) = read_cache(&cache, &key) {
+        return Some(segment);
+    }
+
+    let status = git::working_status(&worktree).ok()?;
+    let repo = here.baum.repo_id.rsplit('/').next().unwrap_or_default();
+    let mut segment = format!("{}@{}", repo, here.worktree.branch);
+    if status.ahead > 0 {
+        segment.push_str(&format!(" ↑{}", status.ahead));
+    }
+    if status.behind > 0 {
+        segment.push_str(&format!(" ↓{}", status.behind));
+    }
+    if !status.is_clean() {
+        segment.push_str(" ✚");
+    }
+
+    // A prompt that can't cache still works, just slower
+    let _ = fs::write(&cache, format!("{}\n{}\n", key, segment));
+    Some(segment)
+}
+
+/// A cached segment, if it was written for `key` less than `CACHE_TTL` ago
+fn read_cache(cache: &Path, key: &str) -> Option<String> {
+    let age = fs::metadata(cache).ok()?.modified().ok()?.elapsed().ok()?;
+    if age > CACHE_TTL {
+        return None;
+    }
+    let content = fs::read_to_string(cache).ok()?;
+    let (cached_key, segment) = content.split_once('\n')?;
+    (cached_key == key).then(|| segment.trim_end().to_string())
+}
+
+/// A worktree's git dir, from the `gitdir:` line of its `.git` file
+fn worktree_git_dir(worktree: &Path) -> Option<PathBuf> {
+    let dot_git = worktree.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let content = fs::read_to_string(&dot_git).ok()?;
+    let dir = PathBuf::from(content.strip_prefix("gitdir:")?.trim());
+    Some(if dir.is_relative() {
+        worktree.join(dir)
+    } else {
+        dir
+    })
+}
+
+fn modified_nanos(path: &Path) -> u128 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_cache_checks_key() {
+        let dir = TempDir::new().unwrap();
+        let cache = dir.path().join(CACHE_FILE);
+        fs::write(&cache, "1:2\nrepo@main ↑1\n").unwrap();
+
+        assert_eq!(read_cache(&cache, "1:2").as_deref(), Some("repo@main ↑1"));
+        assert_eq!(read_cache(&cache, "1:3"), None);
+        assert_eq!(read_cache(&dir.path().join("missing"), "1:2"), None);
+    }
+}
@@ -3,12 +3,18 @@ use std::fs;
 use std::path::PathBuf;
 
 use anyhow::{Result, bail};
+use serde::Serialize;
 
+use crate::commands::lock::ensure_unlocked;
 use crate::git;
 use crate::id::parse_wald_branch;
-use crate::output::Output;
+use crate::output::{JSON_SCHEMA_VERSION, Output, OutputFormat};
 use crate::workspace::baum::{load_baum, save_baum};
-use crate::workspace::{Workspace, find_all_baums, is_baum, validate_workspace_path};
+use crate::workspace::gitignore::remove_worktree_from_gitignore;
+use crate::workspace::{
+    Workspace, find_all_baums, is_baum, list_trash, lock_baum, protection_reason,
+    validate_workspace_path,
+};
 
 /// Options for prune command
 pub struct PruneOptions {
@@ -28,16 +34,33 @@ pub fn prune(ws: &Workspace, opts: PruneOptions, out: &Output) -> Result<()> {
     if !is_baum(&container) {
         bail!(
             "not a baum: {} (.baum directory not found)",
-            container.display()
+            ws.display_path(&container)
         );
     }
 
+    // Hold the baum while its manifest is read, changed, and written back
+    let _lock = lock_baum(&container, "prune")?;
+
     // Load baum manifest
     let mut baum_manifest = load_baum(&container)?;
 
     // Get bare repo path
     let bare_path = ws.bare_repo_path(&baum_manifest.repo_id)?;
 
+    // Check locks before removing anything so a lock can't leave the
+    // manifest listing worktrees that are already gone; --force does not
+    // override them
+    for wt in &baum_manifest.worktrees {
+        if opts.branches.contains(&wt.branch) {
+            ensure_unlocked(
+                &bare_path,
+                &container.join(&wt.path),
+                &opts.baum_path,
+                &wt.branch,
+            )?;
+        }
+    }
+
     let mut removed_count = 0;
 
     for branch in &opts.branches {
@@ -63,6 +86,8 @@ pub fn prune(ws: &Workspace, opts: PruneOptions, out: &Output) -> Result<()> {
                 fs::remove_dir_all(&worktree_path)?;
             }
 
+            remove_worktree_from_gitignore(&container, &wt.path)?;
+
             // Remove from manifest
             baum_manifest.worktrees.remove(idx);
             removed_count += 1;
@@ -83,17 +108,126 @@ pub fn prune(ws: &Workspace, opts: PruneOptions, out: &Output) -> Result<()> {
     Ok(())
 }
 
+/// Options for orphan branch cleanup (prune --branches)
+pub struct PruneBranchesOptions {
+    /// Only scan this repository (ID or alias)
+    pub repo_ref: Option<String>,
+    /// Only consider branches belonging to this baum
+    pub baum_path: Option<PathBuf>,
+    pub force: bool,
+    /// Let --force delete unpushed branches of protected logical branches
+    pub yes_protected: bool,
+    /// List orphan branches without deleting them
+    pub dry_run: bool,
+}
+
+/// Why a wald/* branch is orphaned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum OrphanReason {
+    /// No baum has the branch's baum ID any more
+    BaumGone,
+    /// The baum exists but has no worktree for the branch
+    WorktreeGone,
+}
+
+impl OrphanReason {
+    fn describe(self) -> &'static str {
+        match self {
+            OrphanReason::WorktreeGone => "worktree removed",
+            OrphanReason::BaumGone => "baum not found",
+        }
+    }
+}
+
+/// What `prune --branches` did with an orphan branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum OrphanAction {
+    Deleted,
+    WouldDelete,
+    /// Unpushed commits and no --force (or deleting them wasn't confirmed)
+    SkippedUnpushed,
+    /// Unpushed commits on a protected branch and no --yes-protected
+    SkippedProtected,
+    Failed,
+}
+
+/// One orphan branch considered by `prune --branches`, for the report
+#[derive(Debug, Serialize)]
+struct OrphanBranch {
+    repo_id: String,
+    branch: String,
+    baum_id: String,
+    logical_branch: String,
+    reason: OrphanReason,
+    unpushed: bool,
+    action: OrphanAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip)]
+    bare_path: PathBuf,
+}
+
 /// Clean up orphan wald/* branches across all repositories
 ///
 /// A branch is considered orphan if:
 /// - It matches the wald/<baum_id>/<branch> pattern
 /// - No baum with that baum_id exists, OR
 /// - The baum exists but doesn't have a worktree for that branch
-pub fn prune_branches(ws: &Workspace, force: bool, out: &Output) -> Result<()> {
-    out.require_human("prune --branches")?;
+///
+/// The scan can be narrowed to one repo (`--repo`) or to the branches of a
+/// single baum (`--baum`, which implies that baum's repo).
+pub fn prune_branches(ws: &Workspace, opts: PruneBranchesOptions, out: &Output) -> Result<()> {
+    let force = opts.force;
+
+    // Resolve scope before doing any work
+    let mut scope_repo = match &opts.repo_ref {
+        Some(r) => Some(
+            ws.resolve_repo(r)
+                .map(|s| s.to_string())
+                .ok_or_else(|| anyhow::anyhow!("repository not found: {}", r))?,
+        ),
+        None => None,
+    };
+
+    let mut scope_baum_id = None;
+    if let Some(path) = &opts.baum_path {
+        let container = validate_workspace_path(&ws.root, path)?;
+        if !is_baum(&container) {
+            bail!(
+                "not a baum: {} (.baum directory not found)",
+                ws.display_path(&container)
+            );
+        }
+        let manifest = load_baum(&container)?;
+        if let Some(repo_id) = &scope_repo
+            && repo_id != &manifest.repo_id
+        {
+            bail!(
+                "baum {} belongs to {}, not {}",
+                path.display(),
+                manifest.repo_id,
+                repo_id
+            );
+        }
+        let Some(id) = manifest.id else {
+            // Baums get an ID with their first tracking branch
+            out.info("Baum has no ID yet, so it has no wald/* branches");
+            return print_orphan_report(&[], opts.dry_run, out);
+        };
+        scope_repo = Some(manifest.repo_id);
+        scope_baum_id = Some(id);
+    }
 
-    // Collect all baum IDs and their worktrees
-    let baums = find_all_baums(&ws.root);
+    // Collect all baum IDs and their worktrees, counting uprooted baums in
+    // the trash so `wald undo uproot` still finds their branches
+    let mut baums = find_all_baums(&ws.root);
+    for entry in list_trash(ws)? {
+        if let Ok(manifest) = load_baum(&entry.path) {
+            baums.push((entry.path, manifest));
+        }
+    }
 
     // Build a set of (baum_id, branch) pairs that are in use
     let mut in_use: HashSet<(String, String)> = HashSet::new();
@@ -109,10 +243,13 @@ pub fn prune_branches(ws: &Workspace, force: bool, out: &Output) -> Result<()> {
     }
 
     // Scan all repos for wald/* branches
-    let mut total_removed = 0;
-    let mut total_skipped = 0;
+    let mut report = Vec::new();
 
     for repo_id in ws.manifest.repos.keys() {
+        if scope_repo.as_ref().is_some_and(|r| r != repo_id) {
+            continue;
+        }
+
         let bare_path = match ws.bare_repo_path(repo_id) {
             Ok(p) if p.exists() => p,
             _ => continue,
@@ -129,6 +266,10 @@ pub fn prune_branches(ws: &Workspace, force: bool, out: &Output) -> Result<()> {
                 continue;
             };
 
+            if scope_baum_id.as_deref().is_some_and(|id| id != baum_id) {
+                continue;
+            }
+
             // Check if this branch is in use
             let key = (baum_id.to_string(), logical_branch.to_string());
             if in_use.contains(&key) {
@@ -136,48 +277,139 @@ pub fn prune_branches(ws: &Workspace, force: bool, out: &Output) -> Result<()> {
             }
 
             // Check if baum still exists (might be a renamed branch)
-            let baum_exists = baum_ids.contains(baum_id);
-
-            // Check for unpushed commits
-            let has_unpushed = git::has_unpushed_commits(&bare_path, &branch).unwrap_or(false);
+            let reason = if baum_ids.contains(baum_id) {
+                OrphanReason::WorktreeGone
+            } else {
+                OrphanReason::BaumGone
+            };
+            let mut orphan = OrphanBranch {
+                repo_id: repo_id.clone(),
+                branch: branch.clone(),
+                baum_id: baum_id.to_string(),
+                logical_branch: logical_branch.to_string(),
+                reason,
+                unpushed: git::has_unpushed_commits(&bare_path, &branch).unwrap_or(false),
+                action: OrphanAction::Deleted,
+                error: None,
+                bare_path: bare_path.clone(),
+            };
 
-            if has_unpushed && !force {
+            if orphan.unpushed && !force {
                 out.warn(&format!(
                     "{}: {} has unpushed commits, skipping (use --force to delete)",
                     repo_id, branch
                 ));
-                total_skipped += 1;
+                orphan.action = OrphanAction::SkippedUnpushed;
+                report.push(orphan);
+                continue;
+            }
+            if orphan.unpushed
+                && !opts.yes_protected
+                && let Some(reason) = protection_reason(ws, &bare_path, logical_branch)
+            {
+                out.warn(&format!(
+                    "{}: {} has unpushed commits and '{}' is {}, skipping (use --force --yes-protected to delete)",
+                    repo_id, branch, logical_branch, reason
+                ));
+                orphan.action = OrphanAction::SkippedProtected;
+                report.push(orphan);
                 continue;
             }
 
-            // Delete the orphan branch
-            let reason = if baum_exists {
-                "worktree removed"
-            } else {
-                "baum not found"
-            };
+            if opts.dry_run {
+                out.status(
+                    "Would delete",
+                    &format!("{}: {} ({})", repo_id, branch, orphan.reason.describe()),
+                );
+                orphan.action = OrphanAction::WouldDelete;
+            }
+            report.push(orphan);
+        }
+    }
 
-            out.status("Deleting", &format!("{}: {} ({})", repo_id, branch, reason));
+    // Ask once, before deleting anything, for the branches whose commits
+    // would be lost
+    let unpushed = report
+        .iter()
+        .filter(|o| o.action == OrphanAction::Deleted && o.unpushed)
+        .count();
+    let confirmed = unpushed == 0
+        || out.confirm(&format!(
+            "delete {} branch(es) with unpushed commits",
+            unpushed
+        ))?;
+
+    for orphan in report
+        .iter_mut()
+        .filter(|o| o.action == OrphanAction::Deleted)
+    {
+        if orphan.unpushed && !confirmed {
+            orphan.action = OrphanAction::SkippedUnpushed;
+            continue;
+        }
 
-            match git::delete_branch(&bare_path, &branch, force) {
-                Ok(()) => total_removed += 1,
-                Err(e) => {
-                    out.warn(&format!("Failed to delete {}: {}", branch, e));
-                    total_skipped += 1;
-                }
-            }
+        out.status(
+            "Deleting",
+            &format!(
+                "{}: {} ({})",
+                orphan.repo_id,
+                orphan.branch,
+                orphan.reason.describe()
+            ),
+        );
+        if let Err(e) = git::delete_branch(&orphan.bare_path, &orphan.branch, force) {
+            out.warn(&format!("Failed to delete {}: {}", orphan.branch, e));
+            orphan.action = OrphanAction::Failed;
+            orphan.error = Some(format!("{:#}", e));
         }
     }
 
-    if total_removed > 0 {
-        out.success(&format!("Deleted {} orphan branch(es)", total_removed));
+    print_orphan_report(&report, opts.dry_run, out)
+}
+
+/// Summarize a `prune --branches` run, or print its report as JSON
+fn print_orphan_report(report: &[OrphanBranch], dry_run: bool, out: &Output) -> Result<()> {
+    if out.format == OutputFormat::Json {
+        let json = serde_json::json!({
+            "schema_version": JSON_SCHEMA_VERSION,
+            "dry_run": dry_run,
+            "branches": report,
+        });
+        out.print_json(&json)?;
+        return Ok(());
+    }
+
+    let count = |action| report.iter().filter(|o| o.action == action).count();
+    let removed = count(OrphanAction::Deleted) + count(OrphanAction::WouldDelete);
+    if removed > 0 {
+        if dry_run {
+            out.info(&format!(
+                "Would delete {} orphan branch(es) (dry run)",
+                removed
+            ));
+        } else {
+            out.success(&format!("Deleted {} orphan branch(es)", removed));
+        }
     }
 
-    if total_skipped > 0 {
-        out.info(&format!("Skipped {} branch(es)", total_skipped));
+    let skipped: Vec<String> = [
+        (OrphanAction::SkippedUnpushed, "unpushed"),
+        (OrphanAction::SkippedProtected, "protected"),
+        (OrphanAction::Failed, "failed"),
+    ]
+    .into_iter()
+    .filter(|(action, _)| count(*action) > 0)
+    .map(|(action, label)| format!("{} {}", count(action), label))
+    .collect();
+    if !skipped.is_empty() {
+        out.info(&format!(
+            "Skipped {} branch(es): {}",
+            report.len() - removed,
+            skipped.join(", ")
+        ));
     }
 
-    if total_removed == 0 && total_skipped == 0 {
+    if report.is_empty() {
         out.info("No orphan branches found");
     }
 
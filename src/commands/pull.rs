@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+use super::repo::{fetch_with_retry, prepare_remote};
+use crate::git;
+use crate::output::Output;
+use crate::workspace::baum::load_baum;
+use crate::workspace::{Workspace, is_baum, validate_workspace_path};
+
+/// Options for pull command
+pub struct PullOptions {
+    pub baum_path: PathBuf,
+    /// Rebase branches that have diverged from their upstream instead of
+    /// skipping them
+    pub rebase: bool,
+}
+
+/// What pulling did to one worktree
+enum Pulled {
+    Updated,
+    UpToDate,
+    Skipped(String),
+}
+
+/// Fetch a baum's repo once, then bring each of its worktrees up to date
+///
+/// Branches are fast-forwarded; worktrees with local changes, and branches
+/// that have diverged (unless rebasing), are skipped and reported.
+pub fn pull(ws: &Workspace, opts: PullOptions, out: &Output) -> Result<()> {
+    out.require_human("pull")?;
+
+    let container = validate_workspace_path(&ws.root, &opts.baum_path)?;
+    if !is_baum(&container) {
+        bail!(
+            "not a baum: {} (.baum directory not found)",
+            ws.display_path(&container)
+        );
+    }
+    let manifest = load_baum(&container)?;
+
+    let bare_path = ws.bare_repo_path(&manifest.repo_id)?;
+    if !bare_path.exists() {
+        bail!(
+            "bare repo not found: {} (run `wald sync` to clone it)",
+            ws.display_path(&bare_path)
+        );
+    }
+
+    prepare_remote(ws, &manifest.repo_id, &bare_path)?;
+    out.status("Fetching", &manifest.repo_id);
+    fetch_with_retry(&bare_path, false, false, &git::FetchWatch::default(), out)?;
+
+    let (mut updated, mut up_to_date, mut skipped) = (0, 0, 0);
+    let mut failed = Vec::new();
+    for worktree in &manifest.worktrees {
+        let path = container.join(&worktree.path);
+        match pull_worktree(&path, opts.rebase) {
+            Ok(Pulled::Updated) => {
+                out.status("Updated", &worktree.branch);
+                updated += 1;
+            }
+            Ok(Pulled::UpToDate) => {
+                out.verbose(&format!("{} is up to date", worktree.branch));
+                up_to_date += 1;
+            }
+            Ok(Pulled::Skipped(reason)) => {
+                out.warn(&format!("Skipped {}: {}", worktree.branch, reason));
+                skipped += 1;
+            }
+            Err(e) => {
+                out.warn(&format!("Failed to update {}: {:#}", worktree.branch, e));
+                failed.push(worktree.branch.clone());
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!(
+            "{} of {} worktree(s) failed to update:\n  {}",
+            failed.len(),
+            manifest.worktrees.len(),
+            failed.join("\n  ")
+        );
+    }
+
+    out.success(&format!(
+        "Pulled {}: {} updated, {} up to date, {} skipped",
+        ws.display_path(&container),
+        updated,
+        up_to_date,
+        skipped
+    ));
+
+    Ok(())
+}
+
+/// Fast-forward (or rebase) one worktree's branch onto its upstream
+fn pull_worktree(path: &Path, rebase: bool) -> Result<Pulled> {
+    if !path.exists() {
+        return Ok(Pulled::Skipped("worktree is missing".to_string()));
+    }
+
+    let status = git::working_status(path)?;
+    if status.branch.is_none() {
+        return Ok(Pulled::Skipped("HEAD is detached".to_string()));
+    }
+    if status.upstream.is_none() {
+        return Ok(Pulled::Skipped("branch has no upstream".to_string()));
+    }
+    if status.behind == 0 {
+        return Ok(Pulled::UpToDate);
+    }
+    if !status.is_clean() {
+        return Ok(Pulled::Skipped("worktree has local changes".to_string()));
+    }
+
+    if status.ahead == 0 {
+        git::fast_forward(path)?;
+    } else if rebase {
+        git::rebase_onto_upstream(path)?;
+    } else {
+        return Ok(Pulled::Skipped(format!(
+            "branch has diverged ({} ahead, {} behind); use --rebase to rebase it",
+            status.ahead, status.behind
+        )));
+    }
+
+    Ok(Pulled::Updated)
+}
@@ -0,0 +1,190 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::git;
+use crate::output::Output;
+use crate::types::BaumManifest;
+use crate::workspace::{WALD_DIR, Workspace};
+
+/// Options for the relocate command
+pub struct RelocateOptions {
+    /// Where the workspace was before it moved; found from the paths git
+    /// recorded if not given
+    pub old_root: Option<PathBuf>,
+}
+
+/// Reconnect a workspace that was moved as a whole
+///
+/// Git records absolute paths in both directions between a bare repo and
+/// its worktrees, and in the alternates of repos that borrow objects, so a
+/// moved workspace has all of them pointing at its old location.
+pub fn relocate(ws: &Workspace, opts: RelocateOptions, out: &Output) -> Result<()> {
+    out.require_human("relocate")?;
+
+    let root = ws.root.canonicalize().unwrap_or_else(|_| ws.root.clone());
+    let baums = ws.find_all_baums();
+    let old_root = match opts.old_root {
+        Some(path) => Some(
+            std::path::absolute(&path)
+                .with_context(|| format!("invalid path: {}", path.display()))?,
+        ),
+        None => recorded_root(ws, &baums),
+    };
+    if let Some(old) = &old_root {
+        if old == &root {
+            bail!("the workspace is already at {}", old.display());
+        }
+        out.info(&format!(
+            "Relocating from {} to {}",
+            old.display(),
+            root.display()
+        ));
+    }
+
+    // Alternates first, so repaired worktrees can read borrowed objects
+    let mut alternates = 0;
+    if let Some(old) = &old_root {
+        for repo_id in ws.manifest.repos.keys() {
+            let bare_path = ws.bare_repo_path(repo_id)?;
+            if bare_path.is_dir() {
+                alternates += git::relocate_alternates(&bare_path, old, &root, &root)?;
+            }
+        }
+    }
+
+    let mut repaired = 0;
+    let mut failed = 0;
+    for (baum_path, baum) in &baums {
+        let bare_path = ws.bare_repo_path(&baum.repo_id)?;
+        if !bare_path.is_dir() {
+            continue;
+        }
+        for wt in &baum.worktrees {
+            let path = baum_path.join(&wt.path);
+            if !path.is_dir() || git::check_worktree_link(&path).is_none() {
+                continue;
+            }
+            let result =
+                git::repair_worktree(&bare_path, &path).and_then(
+                    |()| match git::check_worktree_link(&path) {
+                        Some(problem) => bail!("still disconnected: {}", problem),
+                        None => Ok(()),
+                    },
+                );
+            match result {
+                Ok(()) => {
+                    out.status("Repaired", &ws.display_path(&path));
+                    repaired += 1;
+                }
+                Err(e) => {
+                    out.warn(&format!(
+                        "Failed to reconnect {}: {:#}",
+                        ws.display_path(&path),
+                        e
+                    ));
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    let cache_moved = match &old_root {
+        Some(old) => move_cache(ws, old, out)?,
+        None => false,
+    };
+
+    if failed > 0 {
+        bail!(
+            "{} worktree(s) could not be reconnected; run `wald doctor` for details",
+            failed
+        );
+    }
+    if repaired == 0 && alternates == 0 && !cache_moved {
+        out.success("Nothing to relocate: every worktree is connected to its repo");
+    } else {
+        out.success(&format!(
+            "Reconnected {} worktree(s) and {} alternate(s)",
+            repaired, alternates
+        ));
+    }
+    Ok(())
+}
+
+/// The workspace root that broken worktree links and alternates point
+/// into, i.e. where the workspace was before it moved
+fn recorded_root(ws: &Workspace, baums: &[(PathBuf, BaumManifest)]) -> Option<PathBuf> {
+    let admin_dirs = baums.iter().flat_map(|(baum_path, baum)| {
+        baum.worktrees
+            .iter()
+            .filter_map(|wt| git::admin_dir(&baum_path.join(&wt.path)))
+    });
+    let alternates = ws
+        .manifest
+        .repos
+        .keys()
+        .filter_map(|repo_id| ws.bare_repo_path(repo_id).ok())
+        .flat_map(|bare_path| git::alternates(&bare_path));
+
+    admin_dirs
+        .chain(alternates)
+        .filter(|path| path.is_absolute() && !path.exists())
+        .find_map(|path| workspace_root_of(&path))
+}
+
+/// The workspace root of a path in its `.wald/repos/`
+fn workspace_root_of(path: &Path) -> Option<PathBuf> {
+    let repos = Path::new(WALD_DIR).join("repos");
+    path.ancestors()
+        .find(|dir| dir.ends_with(&repos))
+        .and_then(|dir| dir.parent()?.parent())
+        .map(Path::to_path_buf)
+}
+
+/// With `cache: xdg`, carry the cache (journal) over to the directory named
+/// after the new location; returns whether it was moved
+fn move_cache(ws: &Workspace, old_root: &Path, out: &Output) -> Result<bool> {
+    let old_cache = ws.cache_dir_for(old_root);
+    let new_cache = ws.cache_dir();
+    if old_cache == new_cache || !old_cache.is_dir() {
+        return Ok(false);
+    }
+    if new_cache.exists() {
+        out.warn(&format!(
+            "Not moving the cache from {}: {} already exists",
+            old_cache.display(),
+            new_cache.display()
+        ));
+        return Ok(false);
+    }
+    fs::rename(&old_cache, &new_cache).with_context(|| {
+        format!(
+            "failed to move the cache from {} to {}",
+            old_cache.display(),
+            new_cache.display()
+        )
+    })?;
+    out.status("Moved", &format!("cache to {}", new_cache.display()));
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_root_of() {
+        assert_eq!(
+            workspace_root_of(Path::new(
+                "/old/ws/.wald/repos/github.com/a/b.git/worktrees/_main.wt"
+            )),
+            Some(PathBuf::from("/old/ws"))
+        );
+        assert_eq!(
+            workspace_root_of(Path::new("/old/ws/.wald/repos/github.com/a/b.git/objects")),
+            Some(PathBuf::from("/old/ws"))
+        );
+        assert_eq!(workspace_root_of(Path::new("/srv/git/b.git/objects")), None);
+    }
+}
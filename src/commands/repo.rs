@@ -1,36 +1,183 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 
+use crate::bulk::BulkResult;
+use crate::filesystem;
+use crate::forge;
 use crate::git;
-use crate::output::{Output, OutputFormat};
-use crate::types::{DepthPolicy, FilterPolicy, LfsPolicy, RepoEntry, RepoId};
-use crate::workspace::Workspace;
+use crate::output::{JSON_SCHEMA_VERSION, Output, OutputFormat};
+use crate::time::now_secs;
+use crate::types::{
+    Branching, DepthPolicy, FilterPolicy, LfsPolicy, RepoEntry, RepoId, TagPolicy,
+    strip_credentials,
+};
+use crate::workspace::{
+    Workspace, adopt_checkout, ensure_gitignore_section, ref_updates, run_post_fetch,
+};
+
+/// How long an unfinished `repo fetch` of all repos can be resumed
+const BULK_RESUME_WINDOW_SECS: u64 = 24 * 3_600;
 
 /// Options for repo add command
 pub struct RepoAddOptions {
+    /// Repository ID (empty with `detect`)
     pub repo_id: String,
+    /// Derive the repo ID from the current checkout's origin
+    pub detect: bool,
+    /// Also turn the current checkout into a baum (requires `detect`)
+    pub adopt: bool,
     pub lfs: Option<LfsPolicy>,
     pub depth: Option<DepthPolicy>,
     pub filter: Option<FilterPolicy>,
+    pub tags: Option<TagPolicy>,
     pub upstream: Option<String>,
+    /// Repo (ID or alias) to borrow objects from
+    pub reference: Option<String>,
     pub aliases: Vec<String>,
+    pub git_config: BTreeMap<String, String>,
+    pub identity: Option<String>,
+    /// Branching mode, if not the host's default
+    pub branching: Option<Branching>,
+    /// Group to put the repo in
+    pub group: Option<String>,
+    /// Local clone to seed the bare repo from instead of downloading
+    /// everything
+    pub mirror_of: Option<PathBuf>,
     pub clone: bool,
+    /// Clone an already registered repo whose clone is missing
+    pub retry_clone: bool,
+    /// Name of the manifest.d/ file to register in
+    pub manifest_file: Option<String>,
+    /// Only estimate what cloning would download; write nothing
+    pub dry_run: bool,
 }
 
 /// Add a repository to the manifest
 pub fn repo_add(ws: &mut Workspace, opts: RepoAddOptions, out: &Output) -> Result<()> {
     out.require_human("repo add")?;
 
-    // Validate repo ID
-    let id = RepoId::parse(&opts.repo_id)?;
+    let checkout = if opts.detect {
+        let cwd = env::current_dir().context("failed to get current directory")?;
+        Some(git::inspect_checkout(&cwd)?)
+    } else {
+        None
+    };
+
+    // Validate repo ID (or URL)
+    let (id, url) = match &checkout {
+        Some(checkout) => {
+            let Some(url) = &checkout.origin_url else {
+                bail!(
+                    "no remote.origin.url in {}; pass the repo ID instead of --detect",
+                    checkout.root.display()
+                );
+            };
+            let id = RepoId::from_url(url)?;
+            let stored = strip_credentials(url);
+            out.info(&format!("Detected {} from origin {}", id, stored));
+            if stored != url.trim() {
+                warn_dropped_credentials(out);
+            }
+            (id, Some(stored))
+        }
+        None => {
+            let (id, url) = RepoId::parse_with_url(&opts.repo_id)?;
+            if url.as_deref().is_some_and(|u| u != opts.repo_id.trim()) {
+                warn_dropped_credentials(out);
+            }
+            (id, url)
+        }
+    };
     let repo_id = id.as_str();
 
-    // Check for duplicates
+    // Only remember URLs that inference wouldn't reproduce
+    let url = url.filter(|u| *u != id.to_clone_url());
+
+    // Check for duplicates; adopting a checkout of a known repo is fine
+    let adopt = opts.adopt;
     if ws.manifest.has_repo(&repo_id) {
-        bail!("repository already registered: {}", repo_id);
+        if opts.retry_clone {
+            return clone_registered(ws, &id, opts.mirror_of.as_deref(), out);
+        }
+        if !adopt {
+            if !ws.has_bare_repo(&repo_id) {
+                bail!(
+                    "repository already registered but not cloned: {}\n\
+                     Run `wald repo add --retry-clone {}` to clone it",
+                    repo_id,
+                    repo_id
+                );
+            }
+            bail!("repository already registered: {}", repo_id);
+        }
+        out.info(&format!("Repository already registered: {}", repo_id));
+    } else if opts.retry_clone {
+        bail!(
+            "repository not registered: {}; add it without --retry-clone",
+            repo_id
+        );
+    } else {
+        register_repo(ws, &id, url, opts, out)?;
+    }
+
+    if let Some(checkout) = checkout.filter(|_| adopt) {
+        ensure_gitignore_section(&ws.root)?;
+        out.status("Adopting", &ws.display_path(&checkout.root));
+        let adopted = adopt_checkout(ws, &repo_id, &checkout)?;
+        if let Some(identity) = &adopted.pinned_identity {
+            out.status("Pinning identity", identity);
+        }
+        let container = ws.display_path(&adopted.container);
+        out.success(&format!(
+            "Adopted checkout as baum {} ({} -> {})",
+            container, adopted.branch, adopted.worktree_name
+        ));
     }
 
+    Ok(())
+}
+
+/// The manifest.d/ file a new repo goes to: the one asked for, else its
+/// host's once the workspace splits its manifest; None for manifest.yaml
+/// The manifest is shared through the workspace repo, so tokens in a clone
+/// URL are not stored with it
+fn warn_dropped_credentials(out: &Output) {
+    out.warn(
+        "Left the credentials in the URL out of the manifest; configure them under `credentials:` in .wald/config.yaml",
+    );
+}
+
+fn split_manifest_file(ws: &Workspace, id: &RepoId, name: Option<&str>) -> Result<Option<String>> {
+    let name = match name {
+        Some(name) => {
+            let name = name.trim_end_matches(".yaml");
+            if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+                bail!("invalid manifest file name '{}'", name);
+            }
+            name.to_string()
+        }
+        None if ws.manifest_dir().is_dir() => id.host.clone(),
+        None => return Ok(None),
+    };
+    Ok(Some(format!("{}.yaml", name)))
+}
+
+/// Add a new repository entry to the manifest, cloning it if requested
+fn register_repo(
+    ws: &mut Workspace,
+    id: &RepoId,
+    url: Option<String>,
+    opts: RepoAddOptions,
+    out: &Output,
+) -> Result<()> {
+    let repo_id = id.as_str();
+    let manifest_file = split_manifest_file(ws, id, opts.manifest_file.as_deref())?;
+
     // Check for alias conflicts
     for alias in &opts.aliases {
         if let Some(existing) = ws.manifest.resolve_alias(alias) {
@@ -42,8 +189,26 @@ pub fn repo_add(ws: &mut Workspace, opts: RepoAddOptions, out: &Output) -> Resul
         }
     }
 
+    // Objects are borrowed from another registered repo's bare clone
+    let reference = match &opts.reference {
+        Some(r) => {
+            let Some(reference) = ws.resolve_repo(r).map(|s| s.to_string()) else {
+                bail!("reference repository not registered: {}; add it first", r);
+            };
+            if reference == repo_id {
+                bail!("a repository can't borrow objects from itself");
+            }
+            Some(reference)
+        }
+        None => None,
+    };
+    let reference_path = match &reference {
+        Some(r) => Some(ws.bare_repo_path(r)?),
+        None => None,
+    };
+
     // Create entry with defaults from config
-    let entry = RepoEntry {
+    let mut entry = RepoEntry {
         lfs: opts.lfs.unwrap_or_else(|| ws.config.default_lfs.clone()),
         depth: opts
             .depth
@@ -51,40 +216,250 @@ pub fn repo_add(ws: &mut Workspace, opts: RepoAddOptions, out: &Output) -> Resul
         filter: opts
             .filter
             .unwrap_or_else(|| ws.config.default_filter.clone()),
+        tags: opts.tags.unwrap_or_else(|| ws.config.default_tags.clone()),
+        url,
         upstream: opts.upstream,
+        reference,
         aliases: opts.aliases,
+        git_config: opts.git_config,
+        identity: opts.identity,
+        branching: opts.branching,
+        group: opts.group,
+        size: None,
+        bare_path: None,
+        post_fetch: Vec::new(),
     };
 
-    // Build clone options
-    let clone_opts = git::CloneOptions {
-        depth: match &entry.depth {
-            DepthPolicy::Full => None,
-            DepthPolicy::Depth(d) => Some(*d),
-        },
-        filter: entry.filter.as_git_arg().map(|s| s.to_string()),
-    };
+    if opts.dry_run {
+        return estimate_clone(ws, id, &entry, out);
+    }
 
-    // Clone bare repo if requested
+    // Clone before registering, so a failed clone leaves no entry behind
+    // and the same command can simply be run again
     if opts.clone {
-        let bare_path = ws.repos_dir().join(id.to_bare_path());
+        let bare_path = ws.default_bare_repo_path(id);
         if !bare_path.exists() {
+            let mut clone_opts = ws.clone_options(&repo_id, &entry)?;
+            match &opts.mirror_of {
+                Some(seed) => clone_opts.seed = Some(check_seed(id, seed, out)?),
+                None => check_fetch_policy(id, &clone_opts, out)?,
+            }
             out.status("Cloning", &repo_id);
-            git::clone_bare(&id, &bare_path, clone_opts)?;
+            git::with_network_retry(
+                || git::clone_bare(id, &bare_path, clone_opts.clone()),
+                |e, wait| out.retrying(e, wait),
+            )
+            .with_context(|| {
+                format!(
+                    "{} was not registered; check the repo ID, URL and credentials, then run the command again",
+                    repo_id
+                )
+            })?;
+        } else {
+            git::apply_config(&bare_path, &entry.git_config)?;
+            git::apply_tag_policy(&bare_path, &entry.tags)?;
+            if let Some(helper) = ws.credential_helper(&repo_id) {
+                git::set_credential_helper(&bare_path, &helper)?;
+            }
+            if let Some(reference_path) = reference_path.filter(|p| p.exists()) {
+                git::add_alternate(&bare_path, &reference_path, Some(&ws.root))?;
+            }
         }
+        // Lets other machines clone the small repos first
+        entry.size = Some(git::disk_usage(&bare_path) / 1024);
     }
 
     // Add to manifest
     ws.manifest.repos.insert(repo_id.clone(), entry);
+    if let Some(file) = manifest_file {
+        ws.manifest.sources.repos.insert(repo_id.clone(), file);
+    }
     ws.save_manifest()?;
 
-    out.success(&format!("Added repository: {}", repo_id));
+    let file = ws.manifest.repo_file(&repo_id);
+    if file == "manifest.yaml" {
+        out.success(&format!("Added repository: {}", repo_id));
+    } else {
+        out.success(&format!("Added repository: {} (in {})", repo_id, file));
+    }
+
+    Ok(())
+}
+
+/// Make sure a `--mirror-of` path is a clone, warning if its origin is
+/// another repo; returns the absolute path
+fn check_seed(id: &RepoId, seed: &Path, out: &Output) -> Result<PathBuf> {
+    let seed = seed
+        .canonicalize()
+        .with_context(|| format!("--mirror-of: no such directory: {}", seed.display()))?;
+    let origin = git::origin_url(&seed).context("--mirror-of needs a clone of the repo")?;
+    if let Some(origin_id) = origin.and_then(|url| RepoId::from_url(&url).ok())
+        && origin_id != *id
+    {
+        out.warn(&format!(
+            "{} is a clone of {}, not {}; only objects both share are reused",
+            seed.display(),
+            origin_id,
+            id
+        ));
+    }
+    out.status("Seeding", &format!("{} from {}", id, seed.display()));
+    Ok(seed)
+}
+
+/// Check that the server supports the depth and filter a repo asks for
+///
+/// A depth the server can't serve fails the clone; a filter it can't serve
+/// is ignored by git, which quietly turns a partial clone into a full one.
+fn check_fetch_policy(id: &RepoId, clone_opts: &git::CloneOptions, out: &Output) -> Result<()> {
+    if clone_opts.depth.is_none() && clone_opts.filter.is_none() {
+        return Ok(());
+    }
+    let url = clone_opts.url.clone().unwrap_or_else(|| id.to_clone_url());
+    let Some(caps) = git::probe_fetch_capabilities(&url, clone_opts) else {
+        out.verbose(&format!("Could not ask {} what it supports", url));
+        return Ok(());
+    };
 
+    if clone_opts.depth.is_some() && !caps.shallow {
+        bail!(
+            "{} doesn't support shallow clones; add it with `--depth full`",
+            url
+        );
+    }
+    if let Some(filter) = clone_opts.filter.as_deref().filter(|_| !caps.filter) {
+        let clone = if clone_opts.depth.is_some() {
+            "only shallow"
+        } else {
+            "full"
+        };
+        out.warn(&format!(
+            "{} doesn't support partial clones (filter {}), so the clone will be {}; \
+             add it with `--filter none` to record that in the manifest",
+            url, filter, clone
+        ));
+    }
+    Ok(())
+}
+
+/// Report what cloning a repo with `entry`'s policies would download
+///
+/// Lists the remote's refs, asks its forge for the repository's size
+/// (GitHub, GitLab) and whether the server supports the depth and filter.
+/// Nothing is cloned or written.
+fn estimate_clone(ws: &Workspace, id: &RepoId, entry: &RepoEntry, out: &Output) -> Result<()> {
+    let repo_id = id.as_str();
+    let clone_opts = ws.clone_options(&repo_id, entry)?;
+    let url = clone_opts.url.clone().unwrap_or_else(|| id.to_clone_url());
+    out.status("Would clone", &format!("{} from {}", repo_id, url));
+
+    let refs = git::list_remote_refs(&url, &clone_opts)?;
+    let mut remote = format!("{} branch(es), {} tag(s)", refs.branches, refs.tags);
+    if let Some(branch) = &refs.default_branch {
+        remote.push_str(&format!(", default branch {}", branch));
+    }
+    out.status("Remote", &remote);
+
+    let size = match forge::lookup_repo(id) {
+        Ok(found) => found.and_then(|f| f.size),
+        Err(e) => {
+            out.verbose(&format!("Could not ask {} for the size: {:#}", id.host, e));
+            None
+        }
+    };
+    match size {
+        Some(bytes) => out.status(
+            "Size",
+            &format!("{} (reported by {})", out.size(bytes), id.host),
+        ),
+        None => out.status("Size", "unknown (the forge didn't say)"),
+    }
+
+    let caps = git::probe_fetch_capabilities(&url, &clone_opts);
+    let mut reduced = false;
+    if let Some(filter) = &clone_opts.filter {
+        if caps.is_some_and(|c| !c.filter) {
+            out.warn(&format!(
+                "{} doesn't support partial clones, so filter {} won't help",
+                url, filter
+            ));
+        } else {
+            out.info(&format!(
+                "filter {}: the objects it leaves out are fetched when a worktree needs them, \
+                 so the clone downloads a fraction of that",
+                filter
+            ));
+            reduced = true;
+        }
+    }
+    if let Some(depth) = clone_opts.depth {
+        if caps.is_some_and(|c| !c.shallow) {
+            out.warn(&format!(
+                "{} doesn't support shallow clones; the clone would fail with depth {} \
+                 (add it with `--depth full`)",
+                url, depth
+            ));
+        } else {
+            out.info(&format!(
+                "depth {}: only the last {} commit(s) of each branch are downloaded",
+                depth, depth
+            ));
+            reduced = true;
+        }
+    }
+    if !reduced {
+        out.info("full clone: expect to download about all of that; `--filter blob:none` or `--depth <n>` would download less");
+    }
+    if entry.tags == TagPolicy::None {
+        out.info("tags: none are fetched");
+    }
+
+    out.info("Dry run: nothing was cloned or registered");
+    Ok(())
+}
+
+/// Clone a registered repo whose bare clone is missing, with its
+/// registered policies
+fn clone_registered(
+    ws: &Workspace,
+    id: &RepoId,
+    mirror_of: Option<&Path>,
+    out: &Output,
+) -> Result<()> {
+    let repo_id = id.as_str();
+    let bare_path = ws.bare_repo_path(&repo_id)?;
+    if bare_path.exists() {
+        out.success(&format!("Already cloned: {}", repo_id));
+        return Ok(());
+    }
+
+    let entry = &ws.manifest.repos[&repo_id];
+    if let Some(reference) = &entry.reference
+        && !ws.has_bare_repo(reference)
+    {
+        out.warn(&format!(
+            "{} borrows objects from {}, which isn't cloned; cloning without it",
+            repo_id, reference
+        ));
+    }
+    let mut clone_opts = ws.clone_options(&repo_id, entry)?;
+    if let Some(seed) = mirror_of {
+        clone_opts.seed = Some(check_seed(id, seed, out)?);
+    }
+
+    out.status("Cloning", &repo_id);
+    git::with_network_retry(
+        || git::clone_bare(id, &bare_path, clone_opts.clone()),
+        |e, wait| out.retrying(e, wait),
+    )?;
+
+    out.success(&format!("Cloned repository: {}", repo_id));
     Ok(())
 }
 
 /// List registered repositories
 pub fn repo_list(ws: &Workspace, out: &Output) -> Result<()> {
-    if ws.manifest.repos.is_empty() {
+    if ws.manifest.repos.is_empty() && out.format == OutputFormat::Human {
         out.info("No repositories registered");
         return Ok(());
     }
@@ -114,6 +489,13 @@ pub fn repo_list(ws: &Workspace, out: &Output) -> Result<()> {
                 };
                 info.push(depth_str);
 
+                // Tags, when not the default
+                match &entry.tags {
+                    TagPolicy::All => {}
+                    TagPolicy::Reachable => info.push("tags:reachable".to_string()),
+                    TagPolicy::None => info.push("tags:none".to_string()),
+                }
+
                 // Check if bare repo exists
                 let bare_path = ws.bare_repo_path(repo_id).ok();
                 let cloned = bare_path.map(|p| p.exists()).unwrap_or(false);
@@ -121,24 +503,50 @@ pub fn repo_list(ws: &Workspace, out: &Output) -> Result<()> {
                     info.push("cloned".to_string());
                 }
 
+                // Clone URL override
+                if let Some(url) = &entry.url {
+                    info.push(format!("url:{}", url));
+                }
+
                 // Upstream
                 if let Some(upstream) = &entry.upstream {
                     info.push(format!("upstream:{}", upstream));
                 }
 
+                // Object sharing
+                if let Some(reference) = &entry.reference {
+                    info.push(format!("reference:{}", reference));
+                }
+
                 // Aliases
                 if !entry.aliases.is_empty() {
                     info.push(format!("aliases:{}", entry.aliases.join(",")));
                 }
 
+                // Expected identity (own or per-host)
+                if let Some(identity) = ws.manifest.expected_identity(repo_id) {
+                    info.push(format!("identity:{}", identity));
+                }
+
+                if let Some(group) = &entry.group {
+                    info.push(format!("group:{}", group));
+                }
+
+                if let Some(kib) = entry.size {
+                    info.push(format!("size:{}", out.size(kib * 1024)));
+                }
+
                 println!("  {} ({})", repo_id, info.join(", "));
             }
         }
         OutputFormat::Json => {
             // Sort keys in JSON output for determinism
             let sorted: std::collections::BTreeMap<_, _> = ws.manifest.repos.iter().collect();
-            let json = serde_json::to_string_pretty(&sorted)?;
-            println!("{}", json);
+            let json = serde_json::json!({
+                "schema_version": JSON_SCHEMA_VERSION,
+                "repos": sorted,
+            });
+            out.print_json(&json)?;
         }
     }
 
@@ -155,6 +563,16 @@ pub fn repo_remove(ws: &mut Workspace, repo_ref: &str, out: &Output) -> Result<(
         .map(|s| s.to_string())
         .ok_or_else(|| anyhow::anyhow!("repository not found: {}", repo_ref))?;
 
+    // Its objects may be all that other clones have
+    let borrowers = ws.manifest.borrowers(&repo_id);
+    if !borrowers.is_empty() {
+        bail!(
+            "{} lends its objects to {}; remove those first",
+            repo_id,
+            borrowers.join(", ")
+        );
+    }
+
     // Remove from manifest
     ws.manifest.repos.remove(&repo_id);
     ws.save_manifest()?;
@@ -164,11 +582,156 @@ pub fn repo_remove(ws: &mut Workspace, repo_ref: &str, out: &Output) -> Result<(
     Ok(())
 }
 
+/// Options for repo relocate command
+pub struct RepoRelocateOptions {
+    /// Repository ID or alias
+    pub repo_ref: String,
+    /// Where the bare clone goes (absolute, or relative to the current
+    /// directory)
+    pub path: PathBuf,
+}
+
+/// Move a repo's bare clone, e.g. to a larger disk
+///
+/// The new place is recorded as the repo's `bare_path` (relative to the
+/// workspace root when inside it), and the repo's worktrees and the repos
+/// borrowing its objects are pointed at it. Moving it back to
+/// `.wald/repos/` drops the `bare_path` again.
+pub fn repo_relocate(ws: &mut Workspace, opts: RepoRelocateOptions, out: &Output) -> Result<()> {
+    out.require_human("repo relocate")?;
+
+    let repo_id = ws
+        .resolve_repo(&opts.repo_ref)
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow::anyhow!("repository not found: {}", opts.repo_ref))?;
+    let id = RepoId::parse(&repo_id)?;
+    let old = ws.bare_repo_path(&repo_id)?;
+    let new = std::path::absolute(&opts.path)
+        .with_context(|| format!("invalid path: {}", opts.path.display()))?;
+
+    if new.exists() {
+        bail!(
+            "{} already exists; pick a path that doesn't",
+            ws.display_path(&new)
+        );
+    }
+    if new.starts_with(&old) {
+        bail!("can't move {} into itself", ws.display_path(&old));
+    }
+
+    let recorded = if new == ws.default_bare_repo_path(&id) {
+        None
+    } else {
+        let root = ws.root.canonicalize().unwrap_or_else(|_| ws.root.clone());
+        Some(
+            match new.strip_prefix(&root).or(new.strip_prefix(&ws.root)) {
+                Ok(rel) => rel.display().to_string(),
+                Err(_) => new.display().to_string(),
+            },
+        )
+    };
+
+    let cloned = old.is_dir();
+    if cloned {
+        if let Some(parent) = new.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        out.status(
+            "Moving",
+            &format!("{} to {}", ws.display_path(&old), ws.display_path(&new)),
+        );
+        filesystem::move_path(&old, &new)?;
+    }
+
+    ws.manifest
+        .repos
+        .get_mut(&repo_id)
+        .expect("resolved repo is registered")
+        .bare_path = recorded;
+    if let Err(e) = ws.save_manifest() {
+        if cloned {
+            let _ = filesystem::move_path(&new, &old);
+        }
+        return Err(e);
+    }
+
+    if !cloned {
+        out.success(&format!(
+            "{} isn't cloned; it will be cloned to {}",
+            repo_id,
+            ws.display_path(&new)
+        ));
+        return Ok(());
+    }
+
+    // Borrowed objects first, so repaired worktrees can read them
+    let mut alternates = git::rebase_alternates(&new, &old, &ws.root)?;
+    for borrower in ws.manifest.borrowers(&repo_id) {
+        let bare_path = ws.bare_repo_path(&borrower)?;
+        if bare_path.is_dir() {
+            alternates += git::relocate_alternates(&bare_path, &old, &new, &ws.root)?;
+        }
+    }
+
+    let mut repaired = 0;
+    let mut failed = 0;
+    for (baum_path, baum) in ws.find_all_baums() {
+        if baum.repo_id != repo_id {
+            continue;
+        }
+        for wt in &baum.worktrees {
+            let path = baum_path.join(&wt.path);
+            if !path.is_dir() {
+                continue;
+            }
+            match git::repair_worktree(&new, &path) {
+                Ok(()) => repaired += 1,
+                Err(e) => {
+                    out.warn(&format!(
+                        "Failed to reconnect {}: {:#}",
+                        ws.display_path(&path),
+                        e
+                    ));
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!(
+            "moved {}, but {} worktree(s) could not be reconnected; run `wald doctor` for details",
+            repo_id,
+            failed
+        );
+    }
+    out.success(&format!(
+        "Moved {} to {} and reconnected {} worktree(s) and {} alternate(s)",
+        repo_id,
+        ws.display_path(&new),
+        repaired,
+        alternates
+    ));
+    Ok(())
+}
+
 /// Options for repo fetch command
 pub struct RepoFetchOptions {
     pub repo_ref: Option<String>,
+    /// Only the repos of this group
+    pub group: Option<String>,
     /// Convert partial clones to full and fetch all objects
     pub full: bool,
+    /// Fetch every tag this once, whatever the repos' tag policy
+    pub tags: bool,
+    /// Show git's transfer progress while fetching
+    pub watch: bool,
+    /// Stop a repo's fetch that runs longer than this
+    pub timeout: Option<Duration>,
+    /// Run the repos' `post_fetch` commands (from the manifest) after
+    /// fetching them
+    pub post_fetch: bool,
 }
 
 /// Fetch updates for repositories
@@ -183,18 +746,21 @@ pub fn repo_fetch(ws: &mut Workspace, opts: RepoFetchOptions, out: &Output) -> R
             .ok_or_else(|| anyhow::anyhow!("repository not found: {}", r))?;
         let bare_path = ws.bare_repo_path(&repo_id)?;
         if !bare_path.exists() {
-            bail!("bare repo not found: {}", bare_path.display());
+            bail!("bare repo not found: {}", ws.display_path(&bare_path));
         }
         vec![(repo_id, bare_path)]
     } else {
-        // Fetch all cloned repos
-        ws.manifest
-            .repos
-            .keys()
+        // Fetch all cloned repos, or those of the group
+        let repo_ids = match &opts.group {
+            Some(group) => ws.group_repos(group)?,
+            None => ws.manifest.repos.keys().cloned().collect(),
+        };
+        repo_ids
+            .into_iter()
             .filter_map(|id| {
-                let path = ws.bare_repo_path(id).ok()?;
+                let path = ws.bare_repo_path(&id).ok()?;
                 if path.exists() {
-                    Some((id.clone(), path))
+                    Some((id, path))
                 } else {
                     None
                 }
@@ -207,26 +773,77 @@ pub fn repo_fetch(ws: &mut Workspace, opts: RepoFetchOptions, out: &Output) -> R
         return Ok(());
     }
 
+    // Fetching everything records its progress so a rerun after a failure
+    // picks up where this one stopped
+    let mut operation = match (opts.full, opts.tags) {
+        (true, true) => "repo fetch --full --tags",
+        (true, false) => "repo fetch --full",
+        (false, true) => "repo fetch --tags",
+        (false, false) => "repo fetch",
+    }
+    .to_string();
+    if let Some(group) = &opts.group {
+        operation.push_str(&format!(" --group {}", group));
+    }
+    let bulk = opts.repo_ref.is_none();
+    let mut repos = repos;
+    if bulk {
+        let done = ws
+            .state
+            .resume_bulk(&operation, now_secs(), BULK_RESUME_WINDOW_SECS);
+        if !done.is_empty() {
+            out.info(&format!(
+                "Resuming: {} of {} repo(s) already fetched",
+                done.len(),
+                repos.len()
+            ));
+            repos.retain(|(id, _)| !done.contains(id));
+        }
+        ws.save_state()?;
+    }
+
+    let hooked: Vec<&str> = repos
+        .iter()
+        .map(|(id, _)| id.as_str())
+        .filter(|id| {
+            ws.manifest
+                .repos
+                .get(*id)
+                .is_some_and(|e| !e.post_fetch.is_empty())
+        })
+        .collect();
+    if !hooked.is_empty() {
+        if opts.post_fetch {
+            out.warn(&format!(
+                "Running post_fetch commands from the manifest for {}; they run unsandboxed, with your permissions",
+                hooked.join(", ")
+            ));
+        } else {
+            out.info(&format!(
+                "Skipping post_fetch commands of {} repo(s); pass --post-fetch to run them",
+                hooked.len()
+            ));
+        }
+    }
+
+    let mut results = BulkResult::new("repo", "fetch", repos.len());
     let mut updated_manifest = false;
+    let progress = |line: &str| out.progress(line);
+    let watch = git::FetchWatch {
+        progress: opts.watch.then_some(&progress as &dyn Fn(&str)),
+        timeout: opts.timeout,
+    };
 
     for (repo_id, bare_path) in repos {
-        if opts.full {
-            let is_partial = git::is_partial_clone(&bare_path)?;
-            if is_partial {
-                out.status("Converting to full clone", &repo_id);
-                git::fetch_full(&bare_path)?;
-                // Update manifest to reflect full clone
-                if let Some(entry) = ws.manifest.repos.get_mut(&repo_id) {
-                    entry.filter = FilterPolicy::None;
-                    updated_manifest = true;
-                }
-            } else {
-                out.status("Fetching", &format!("{} (already full)", repo_id));
-                git::fetch_bare(&bare_path)?;
-            }
-        } else {
-            out.status("Fetching", &repo_id);
-            git::fetch_bare(&bare_path)?;
+        let result = fetch_one(ws, &repo_id, &bare_path, &opts, &watch, out);
+        if !bulk {
+            updated_manifest |= result?;
+            continue;
+        }
+        if let Some(converted) = results.record(&repo_id, result, out) {
+            updated_manifest |= converted;
+            ws.state.mark_bulk_done(&operation, &repo_id);
+            ws.save_state()?;
         }
     }
 
@@ -234,15 +851,133 @@ pub fn repo_fetch(ws: &mut Workspace, opts: RepoFetchOptions, out: &Output) -> R
         ws.save_manifest()?;
     }
 
+    results.finish(Some(&format!("Re-run `wald {}` to retry them", operation)))?;
+
+    if bulk {
+        ws.state.finish_bulk(&operation);
+        ws.save_state()?;
+    }
+
     out.success("Fetch complete");
 
     Ok(())
 }
 
+/// Fetch one repo for `repo fetch` and run its `post_fetch` commands if
+/// asked to; whether it was converted to a full clone (its manifest entry
+/// is updated but not saved)
+fn fetch_one(
+    ws: &mut Workspace,
+    repo_id: &str,
+    bare_path: &Path,
+    opts: &RepoFetchOptions,
+    watch: &git::FetchWatch,
+    out: &Output,
+) -> Result<bool> {
+    let hooked = opts.post_fetch
+        && ws
+            .manifest
+            .repos
+            .get(repo_id)
+            .is_some_and(|e| !e.post_fetch.is_empty());
+    if !hooked {
+        return fetch_objects(ws, repo_id, bare_path, opts, watch, out);
+    }
+
+    let before = git::branch_commits(bare_path)?;
+    let converted = fetch_objects(ws, repo_id, bare_path, opts, watch, out)?;
+    let after = git::branch_commits(bare_path)?;
+    run_post_fetch(ws, repo_id, bare_path, &ref_updates(&before, &after), out)?;
+    Ok(converted)
+}
+
+/// The fetch itself for [`fetch_one`]
+fn fetch_objects(
+    ws: &mut Workspace,
+    repo_id: &str,
+    bare_path: &Path,
+    opts: &RepoFetchOptions,
+    watch: &git::FetchWatch,
+    out: &Output,
+) -> Result<bool> {
+    prepare_remote(ws, repo_id, bare_path)?;
+
+    if !opts.full {
+        out.status("Fetching", repo_id);
+        fetch_with_retry(bare_path, false, opts.tags, watch, out)?;
+        return Ok(false);
+    }
+    if !git::is_partial_clone(bare_path)? {
+        out.status("Fetching", &format!("{} (already full)", repo_id));
+        fetch_with_retry(bare_path, false, opts.tags, watch, out)?;
+        return Ok(false);
+    }
+
+    out.status("Converting to full clone", repo_id);
+    fetch_with_retry(bare_path, true, opts.tags, watch, out)?;
+    // Update manifest to reflect full clone
+    if let Some(entry) = ws.manifest.repos.get_mut(repo_id) {
+        entry.filter = FilterPolicy::None;
+    }
+    Ok(true)
+}
+
+/// Keep a bare repo's remote tagOpt and credentials in line with the
+/// workspace before fetching
+pub(crate) fn prepare_remote(ws: &Workspace, repo_id: &str, bare_path: &Path) -> Result<()> {
+    if let Some(entry) = ws.manifest.repos.get(repo_id) {
+        git::apply_tag_policy(bare_path, &entry.tags)?;
+    }
+    if let Some(helper) = ws.credential_helper(repo_id) {
+        git::set_credential_helper(bare_path, &helper)?;
+    }
+    Ok(())
+}
+
+/// Fetch a bare repo (`full`: convert a partial clone first; `all_tags`:
+/// every tag), retrying network hiccups
+pub(crate) fn fetch_with_retry(
+    bare_path: &Path,
+    full: bool,
+    all_tags: bool,
+    watch: &git::FetchWatch,
+    out: &Output,
+) -> Result<()> {
+    git::with_network_retry(
+        || {
+            if full {
+                git::fetch_full(bare_path, all_tags, watch)
+            } else {
+                git::fetch_bare(bare_path, all_tags, watch)
+            }
+        },
+        |e, wait| out.retrying(e, wait),
+    )
+}
+
 /// Options for repo gc command
 pub struct RepoGcOptions {
     pub repo_ref: Option<String>,
     pub aggressive: bool,
+    /// Expiry for unreachable objects (`--prune=<when>`), git's default if None
+    pub prune: Option<String>,
+    /// Also clean repos with unfinished operations or stashes in their worktrees
+    pub force: bool,
+}
+
+/// Why running gc on a bare repo could lose work, if it could
+fn gc_hazard(bare_path: &Path) -> Option<String> {
+    if let Some((worktree, operation)) = git::unfinished_operations(bare_path).into_iter().next() {
+        return Some(format!(
+            "worktree {} is in the middle of a {}",
+            worktree.display(),
+            operation
+        ));
+    }
+    if git::has_stash(bare_path).unwrap_or(false) {
+        return Some("it has stashed changes".to_string());
+    }
+    None
 }
 
 /// Run garbage collection on repositories
@@ -257,7 +992,7 @@ pub fn repo_gc(ws: &Workspace, opts: RepoGcOptions, out: &Output) -> Result<()>
             .ok_or_else(|| anyhow::anyhow!("repository not found: {}", r))?;
         let bare_path = ws.bare_repo_path(&repo_id)?;
         if !bare_path.exists() {
-            bail!("bare repo not found: {}", bare_path.display());
+            bail!("bare repo not found: {}", ws.display_path(&bare_path));
         }
         vec![(repo_id, bare_path)]
     } else {
@@ -281,12 +1016,62 @@ pub fn repo_gc(ws: &Workspace, opts: RepoGcOptions, out: &Output) -> Result<()>
         return Ok(());
     }
 
+    let mut freed_total: u64 = 0;
+    let mut skipped = 0;
+    let mut results = BulkResult::new("repo", "clean", repos.len());
     for (repo_id, bare_path) in repos {
+        // Work in progress keeps objects alive only through state gc may
+        // expire (reflogs, the stash); leave such repos to the user
+        if !opts.force
+            && let Some(reason) = gc_hazard(&bare_path)
+        {
+            out.warn(&format!(
+                "Skipping {}: {} (use --force to clean it anyway)",
+                repo_id, reason
+            ));
+            skipped += 1;
+            continue;
+        }
+
+        let borrowers = ws.manifest.borrowers(&repo_id);
+        let prune = if borrowers.is_empty() {
+            opts.prune.as_deref()
+        } else {
+            if opts.prune.is_some() {
+                out.warn(&format!(
+                    "Keeping unreachable objects of {}, which lends them to {}",
+                    repo_id,
+                    borrowers.join(", ")
+                ));
+            }
+            Some("never")
+        };
+
         out.status("Cleaning", &repo_id);
-        git::gc(&bare_path, opts.aggressive)?;
+        let before = git::disk_usage(&bare_path);
+        let result = git::gc(&bare_path, opts.aggressive, prune);
+        if results.record(&repo_id, result, out).is_none() {
+            continue;
+        }
+        let after = git::disk_usage(&bare_path);
+        freed_total += before.saturating_sub(after);
+        out.status(
+            "Cleaned",
+            &format!("{}: {} -> {}", repo_id, out.size(before), out.size(after)),
+        );
     }
 
-    out.success("Garbage collection complete");
+    if skipped > 0 {
+        out.warn(&format!("Skipped {} repo(s)", skipped));
+    }
+    if results.has_failures() {
+        out.info(&format!("{} reclaimed", out.size(freed_total)));
+        return results.finish(None);
+    }
+    out.success(&format!(
+        "Garbage collection complete, {} reclaimed",
+        out.size(freed_total)
+    ));
 
     Ok(())
 }
@@ -0,0 +1,315 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use crate::output::Output;
+
+/// GitLab API base for release lookups (project path is URL-encoded)
+const RELEASES_API: &str = "https://git.zib.de/api/v4/projects/tools%2Fwald/releases";
+
+/// Environment variable holding an optional GitLab access token
+const TOKEN_ENV: &str = "WALD_GITLAB_TOKEN";
+
+/// Options for self-update command
+pub struct SelfUpdateOptions {
+    /// Only check whether an update is available
+    pub check: bool,
+    /// Reinstall even if already up to date
+    pub force: bool,
+}
+
+/// Release metadata as returned by the GitLab releases API
+#[derive(Debug, serde::Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: ReleaseAssets,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ReleaseAssets {
+    #[serde(default)]
+    links: Vec<AssetLink>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct AssetLink {
+    name: String,
+    url: String,
+    #[serde(default)]
+    direct_asset_url: Option<String>,
+}
+
+impl AssetLink {
+    fn download_url(&self) -> &str {
+        self.direct_asset_url.as_deref().unwrap_or(&self.url)
+    }
+}
+
+/// Check for a newer release and replace the running executable with it
+pub fn self_update(opts: SelfUpdateOptions, out: &Output) -> Result<()> {
+    out.require_human("self-update")?;
+
+    let current = env!("WALD_VERSION");
+    let platform = platform_name()?;
+
+    out.status("Checking", "latest release");
+    let release = fetch_latest_release()?;
+    let latest = release.tag_name.trim_start_matches('v');
+
+    let newer = is_newer(latest, current);
+    if !newer && !opts.force {
+        out.success(&format!("wald {} is up to date", current));
+        return Ok(());
+    }
+
+    if opts.check {
+        if newer {
+            out.info(&format!("Update available: {} -> {}", current, latest));
+            out.info("Run `wald self-update` to install it");
+        } else {
+            out.info(&format!("wald {} is up to date", current));
+        }
+        return Ok(());
+    }
+
+    let binary_name = format!("wald-{}-{}", platform, latest);
+    let checksums_name = format!("checksums-{}.txt", latest);
+
+    let binary = find_asset(&release, &binary_name)?;
+    let checksums = find_asset(&release, &checksums_name)?;
+
+    let exe = env::current_exe().context("failed to locate running executable")?;
+    let exe = exe.canonicalize().unwrap_or(exe);
+    let staging = staging_path(&exe);
+
+    out.status("Downloading", &binary_name);
+    let result = download(binary.download_url(), &staging)
+        .and_then(|_| verify_and_install(&staging, &exe, checksums, &binary_name, out));
+    // Never leave a partial or unverified download next to the binary
+    if result.is_err() {
+        let _ = fs::remove_file(&staging);
+    }
+    result?;
+
+    out.success(&format!("Updated wald {} -> {}", current, latest));
+
+    Ok(())
+}
+
+fn verify_and_install(
+    staging: &Path,
+    exe: &Path,
+    checksums: &AssetLink,
+    binary_name: &str,
+    out: &Output,
+) -> Result<()> {
+    out.status("Verifying", "checksum");
+    let listing = http_get(checksums.download_url())?;
+    let expected = find_checksum(&listing, binary_name)
+        .ok_or_else(|| anyhow::anyhow!("no checksum listed for {}", binary_name))?;
+    let actual = sha256_file(staging)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        bail!(
+            "checksum mismatch for {}: expected {}, got {}",
+            binary_name,
+            expected,
+            actual
+        );
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(staging, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("failed to make executable: {}", staging.display()))?;
+    }
+
+    out.status("Installing", &exe.display().to_string());
+    // Renaming within the same directory atomically swaps the binary; the running
+    // process keeps its open inode until it exits.
+    fs::rename(staging, exe)
+        .with_context(|| format!("failed to replace executable: {}", exe.display()))?;
+
+    Ok(())
+}
+
+/// Map the compile-time target to the release artifact platform name
+fn platform_name() -> Result<&'static str> {
+    platform_for(env::consts::OS, env::consts::ARCH)
+}
+
+fn platform_for(os: &str, arch: &str) -> Result<&'static str> {
+    match (os, arch) {
+        ("macos", "aarch64") => Ok("darwin-arm64"),
+        ("macos", "x86_64") => Ok("darwin-amd64"),
+        ("linux", "x86_64") => Ok("linux-amd64"),
+        ("linux", "aarch64") => Ok("linux-arm64"),
+        _ => bail!("no release builds available for {}-{}", os, arch),
+    }
+}
+
+/// Parse the leading `X.Y.Z` of a version string
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.trim_start_matches('v');
+    let core = core.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next()??;
+    Some((major, minor, patch))
+}
+
+/// Check whether `latest` is a newer release than `current`
+///
+/// Builds between tags (`0.2.0-3-gabcdef`) count as their base version, so
+/// a release with the same number is not offered as an update.
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(l), Some(c)) => l > c,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+fn find_asset<'a>(release: &'a Release, name: &str) -> Result<&'a AssetLink> {
+    release
+        .assets
+        .links
+        .iter()
+        .find(|l| l.name == name)
+        .ok_or_else(|| anyhow::anyhow!("release {} has no asset {}", release.tag_name, name))
+}
+
+/// Find the checksum for a file in `shasum -a 256` output
+///
+/// Lines are `<hash>  <path>`; the path may be absolute, so match on its file name.
+fn find_checksum<'a>(listing: &'a str, file_name: &str) -> Option<&'a str> {
+    listing.lines().find_map(|line| {
+        let (hash, path) = line.split_once(char::is_whitespace)?;
+        let path = path.trim().trim_start_matches('*');
+        let name = Path::new(path).file_name()?.to_str()?;
+        (name == file_name).then_some(hash)
+    })
+}
+
+fn staging_path(exe: &Path) -> PathBuf {
+    let name = exe
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "wald".to_string());
+    exe.with_file_name(format!(".{}.update", name))
+}
+
+fn fetch_latest_release() -> Result<Release> {
+    let body = http_get(&format!("{}/permalink/latest", RELEASES_API))?;
+    serde_json::from_str(&body).context("failed to parse release metadata")
+}
+
+fn curl() -> Command {
+    let mut cmd = Command::new("curl");
+    cmd.arg("--fail")
+        .arg("--silent")
+        .arg("--show-error")
+        .arg("--location");
+    if let Ok(token) = env::var(TOKEN_ENV)
+        && !token.is_empty()
+    {
+        cmd.arg("--header").arg(format!("PRIVATE-TOKEN: {}", token));
+    }
+    cmd
+}
+
+fn http_get(url: &str) -> Result<String> {
+    let output = curl()
+        .arg(url)
+        .output()
+        .context("failed to execute curl (is it installed?)")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("request failed for {}: {}", url, stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn download(url: &str, target: &Path) -> Result<()> {
+    let output = curl()
+        .arg("--output")
+        .arg(target)
+        .arg(url)
+        .output()
+        .context("failed to execute curl (is it installed?)")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("download failed for {}: {}", url, stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// Compute the SHA-256 of a file using the platform's checksum tool
+fn sha256_file(path: &Path) -> Result<String> {
+    // Linux ships sha256sum, macOS ships shasum
+    let attempts: [(&str, &[&str]); 2] = [("sha256sum", &[]), ("shasum", &["-a", "256"])];
+
+    for (tool, args) in attempts {
+        let Ok(output) = Command::new(tool).args(args).arg(path).output() else {
+            continue;
+        };
+        if output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if let Some(hash) = stdout.split_whitespace().next() {
+                return Ok(hash.to_string());
+            }
+        }
+    }
+
+    bail!("no checksum tool found (need sha256sum or shasum)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_for() {
+        assert_eq!(platform_for("linux", "x86_64").unwrap(), "linux-amd64");
+        assert_eq!(platform_for("macos", "aarch64").unwrap(), "darwin-arm64");
+        assert!(platform_for("windows", "x86_64").is_err());
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("0.2.0"), Some((0, 2, 0)));
+        assert_eq!(parse_version("v1.10.3"), Some((1, 10, 3)));
+        assert_eq!(parse_version("0.1.0-3-gabcdef"), Some((0, 1, 0)));
+        assert_eq!(parse_version("dev"), None);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.2.0", "0.1.9"));
+        assert!(is_newer("1.0.0", "0.0.0-gabc1234"));
+        assert!(!is_newer("0.2.0", "0.2.0"));
+        assert!(!is_newer("0.2.0", "0.2.0-4-gabcdef"));
+        assert!(!is_newer("0.1.0", "0.2.0"));
+    }
+
+    #[test]
+    fn test_find_checksum() {
+        let listing = "\
+abc123  /home/ci/wald/build/wald-linux-amd64-0.2.0
+def456  /home/ci/wald/build/wald-darwin-arm64-0.2.0
+";
+        assert_eq!(
+            find_checksum(listing, "wald-darwin-arm64-0.2.0"),
+            Some("def456")
+        );
+        assert_eq!(find_checksum(listing, "wald-linux-arm64-0.2.0"), None);
+    }
+}
@@ -1,138 +1,211 @@
-use std::process::Command;
+use anyhow::Result;
 
-use anyhow::{Context, Result};
-use walkdir::WalkDir;
-
-use crate::output::{Output, OutputFormat};
+use crate::git;
+use crate::output::{JSON_SCHEMA_VERSION, Output, OutputFormat};
+use crate::time::format_iso;
 use crate::workspace::baum::load_baum;
-use crate::workspace::{Workspace, is_baum};
+use crate::workspace::{Workspace, current_owner, find_baum_dirs};
+
+/// Options for status command
+pub struct StatusOptions {
+    /// Only count the baums owned by this user or machine
+    pub mine: bool,
+    /// Only show the repos of this group, and their baums
+    pub group: Option<String>,
+}
 
 /// Show workspace status
-pub fn status(ws: &Workspace, out: &Output) -> Result<()> {
-    // Get git status
-    let status_output = Command::new("git")
-        .arg("-C")
-        .arg(&ws.root)
-        .arg("status")
-        .arg("--porcelain")
-        .output()
-        .context("failed to check git status")?;
-
-    let git_status = String::from_utf8_lossy(&status_output.stdout);
-    let is_clean = git_status.trim().is_empty();
-
-    // Check ahead/behind
-    let ab_output = Command::new("git")
-        .arg("-C")
-        .arg(&ws.root)
-        .arg("rev-list")
-        .arg("--left-right")
-        .arg("--count")
-        .arg("HEAD...@{upstream}")
-        .output();
-
-    let (ahead, behind) = if let Ok(ab) = ab_output {
-        if ab.status.success() {
-            let ab_str = String::from_utf8_lossy(&ab.stdout);
-            let parts: Vec<&str> = ab_str.trim().split('\t').collect();
-            if parts.len() == 2 {
-                (
-                    parts[0].parse::<u32>().unwrap_or(0),
-                    parts[1].parse::<u32>().unwrap_or(0),
-                )
-            } else {
-                (0, 0)
-            }
-        } else {
-            (0, 0)
-        }
+pub fn status(ws: &Workspace, opts: StatusOptions, out: &Output) -> Result<()> {
+    // Standalone workspaces have no workspace repo to inspect
+    let (is_clean, ahead, behind) = if ws.config.standalone {
+        (true, 0, 0)
     } else {
-        (0, 0)
+        workspace_git_status(ws)?
+    };
+
+    let group_repos = opts
+        .group
+        .as_deref()
+        .map(|group| ws.group_repos(group))
+        .transpose()?;
+    let in_scope = |repo_id: &str| {
+        group_repos
+            .as_ref()
+            .is_none_or(|repos| repos.iter().any(|r| r == repo_id))
     };
 
     // Count baums and worktrees
+    let owner = opts.mine.then(|| current_owner(ws));
     let mut baum_count = 0;
     let mut worktree_count = 0;
 
-    for entry in WalkDir::new(&ws.root)
-        .follow_links(false)
-        .into_iter()
-        .filter_entry(|e| {
-            // Skip .git directories, .wald/repos, and _*.wt worktree directories
-            let name = e.file_name().to_string_lossy();
-            if name == ".git" {
-                return false;
-            }
-            if name == "repos"
-                && e.path()
-                    .parent()
-                    .map(|p| p.ends_with(".wald"))
-                    .unwrap_or(false)
-            {
-                return false;
-            }
-            // Skip worktree directories (no need to descend into them)
-            if e.file_type().is_dir() && name.starts_with('_') && name.ends_with(".wt") {
-                return false;
-            }
-            true
-        })
-        .flatten()
-    {
-        if entry.file_type().is_dir() && is_baum(entry.path()) {
-            baum_count += 1;
-            if let Ok(baum) = load_baum(entry.path()) {
-                worktree_count += baum.worktrees.len();
-            }
+    for baum_path in find_baum_dirs(&ws.root, &ws.root, &ws.config) {
+        let baum = load_baum(&baum_path);
+        if let Some(owner) = &owner
+            && !baum.as_ref().is_ok_and(|b| b.is_owned_by(owner))
+        {
+            continue;
+        }
+        if opts.group.is_some() && !baum.as_ref().is_ok_and(|b| in_scope(&b.repo_id)) {
+            continue;
+        }
+        baum_count += 1;
+        if let Ok(baum) = baum {
+            worktree_count += baum.worktrees.len();
         }
     }
 
+    let repos = repo_freshness(ws, &in_scope);
+
     match out.format {
         OutputFormat::Human => {
-            // Workspace status
-            if is_clean {
-                println!("Workspace: clean");
+            if ws.config.standalone {
+                println!("Workspace: standalone (sync disabled)");
             } else {
-                println!("Workspace: has uncommitted changes");
-            }
+                // Workspace status
+                if is_clean {
+                    println!("Workspace: clean");
+                } else {
+                    println!("Workspace: has uncommitted changes");
+                }
 
-            // Sync status
-            match (ahead, behind) {
-                (0, 0) => println!("Sync: up to date"),
-                (a, 0) => println!("Sync: {} commit(s) ahead of remote", a),
-                (0, b) => println!("Sync: {} commit(s) behind remote", b),
-                (a, b) => println!("Sync: diverged ({} ahead, {} behind)", a, b),
-            }
+                // Sync status
+                match (ahead, behind) {
+                    (0, 0) => println!("Sync: up to date"),
+                    (a, 0) => println!("Sync: {} commit(s) ahead of remote", a),
+                    (0, b) => println!("Sync: {} commit(s) behind remote", b),
+                    (a, b) => println!("Sync: diverged ({} ahead, {} behind)", a, b),
+                }
 
-            // Last sync
-            if let Some(last) = &ws.state.last_sync {
-                println!("Last sync: {}", &last[..8.min(last.len())]);
-            } else {
-                println!("Last sync: never");
+                // Last sync
+                if let Some(last) = &ws.state.last_sync {
+                    println!("Last sync: {}", &last[..8.min(last.len())]);
+                } else {
+                    println!("Last sync: never");
+                }
             }
 
             // Counts
-            println!("Repos: {} registered", ws.manifest.repos.len());
-            println!(
-                "Baums: {} planted ({} worktrees)",
-                baum_count, worktree_count
-            );
+            match &opts.group {
+                Some(group) => println!("Repos: {} in group {}", repos.len(), group),
+                None => println!("Repos: {} registered", repos.len()),
+            }
+            for repo in &repos {
+                println!("  {}: {}", repo.repo_id, repo.describe(out));
+            }
+            let baums = match &owner {
+                Some(owner) => format!(
+                    "Baums: {} planted for {} ({} worktrees)",
+                    baum_count, owner, worktree_count
+                ),
+                None => format!(
+                    "Baums: {} planted ({} worktrees)",
+                    baum_count, worktree_count
+                ),
+            };
+            println!("{}", out.words(&baums));
         }
         OutputFormat::Json => {
-            let status = serde_json::json!({
+            let mut status = serde_json::json!({
+                "schema_version": JSON_SCHEMA_VERSION,
                 "workspace": {
+                    "standalone": ws.config.standalone,
                     "clean": is_clean,
                     "ahead": ahead,
                     "behind": behind,
                 },
                 "last_sync": ws.state.last_sync,
-                "repos_count": ws.manifest.repos.len(),
+                "repos_count": repos.len(),
+                "repos": repos
+                    .iter()
+                    .map(|repo| serde_json::json!({
+                        "repo_id": repo.repo_id,
+                        "cloned": repo.cloned,
+                        "branches": repo.branches,
+                        "branches_behind": repo.behind,
+                        "last_fetch": repo.last_fetch.map(format_iso),
+                    }))
+                    .collect::<Vec<_>>(),
                 "baums_count": baum_count,
                 "worktrees_count": worktree_count,
             });
-            println!("{}", serde_json::to_string_pretty(&status)?);
+            if let Some(owner) = owner {
+                status["owner"] = serde_json::json!(owner);
+            }
+            if let Some(group) = &opts.group {
+                status["group"] = serde_json::json!(group);
+            }
+            out.print_json(&status)?;
         }
     }
 
     Ok(())
 }
+
+/// How up to date a repo's bare clone is, from local refs only
+pub(super) struct RepoFreshness {
+    repo_id: String,
+    pub(super) cloned: bool,
+    /// The last sync tried to clone it and failed
+    clone_failed: bool,
+    /// Local branches with an upstream
+    branches: usize,
+    /// Of those, how many are behind it
+    pub(super) behind: usize,
+    /// When the bare repo was last fetched
+    last_fetch: Option<u64>,
+}
+
+impl RepoFreshness {
+    fn describe(&self, out: &Output) -> String {
+        if self.clone_failed {
+            return "not cloned (failed on the last sync)".to_string();
+        }
+        if !self.cloned {
+            return "not cloned".to_string();
+        }
+        let branches = if self.behind == 0 {
+            "up to date".to_string()
+        } else {
+            format!("{} of {} branch(es) behind", self.behind, self.branches)
+        };
+        match self.last_fetch {
+            Some(at) => format!("{}, fetched {}", branches, out.time(at)),
+            None => format!("{}, never fetched", branches),
+        }
+    }
+}
+
+/// Freshness of the registered repos in scope, sorted by repo ID
+pub(super) fn repo_freshness(
+    ws: &Workspace,
+    in_scope: &dyn Fn(&str) -> bool,
+) -> Vec<RepoFreshness> {
+    let mut repo_ids: Vec<_> = ws.manifest.repos.keys().filter(|id| in_scope(id)).collect();
+    repo_ids.sort();
+
+    repo_ids
+        .into_iter()
+        .map(|repo_id| {
+            let bare_path = ws.bare_repo_path(repo_id).ok().filter(|p| p.exists());
+            let drift = bare_path
+                .as_deref()
+                .and_then(|p| git::branch_drift(p).ok())
+                .unwrap_or_default();
+            RepoFreshness {
+                repo_id: repo_id.clone(),
+                cloned: bare_path.is_some(),
+                clone_failed: bare_path.is_none() && ws.state.failed_clones.contains(repo_id),
+                branches: drift.len(),
+                behind: drift.iter().filter(|d| d.behind > 0).count(),
+                last_fetch: bare_path.as_deref().and_then(git::last_fetch),
+            }
+        })
+        .collect()
+}
+
+/// Check workspace repo cleanliness and ahead/behind counts vs upstream
+pub(super) fn workspace_git_status(ws: &Workspace) -> Result<(bool, u32, u32)> {
+    let status = git::working_status(&ws.root)?;
+    Ok((status.is_clean(), status.ahead, status.behind))
+}
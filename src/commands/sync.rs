@@ -1,15 +1,19 @@
 use std::fs;
+use std::path::Path;
 use std::process::Command;
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
 
+use crate::filesystem::move_path;
 use crate::git;
 use crate::git::history::detect_moves;
 use crate::git::shell::get_head_commit;
 use crate::output::Output;
-use crate::types::{DepthPolicy, RepoId};
+use crate::timings::TimedCommand;
+use crate::types::TeamMode;
 use crate::workspace::baum::load_baum;
-use crate::workspace::{Workspace, find_all_baums, is_baum};
+use crate::workspace::gitignore::update_worktree_exclude;
+use crate::workspace::{Workspace, clone_missing_repos, collect_baum_ids, current_owner, is_baum};
 
 /// Options for sync command
 pub struct SyncOptions {
@@ -17,31 +21,56 @@ pub struct SyncOptions {
     pub force: bool,
     pub push: bool,
     pub offline: bool,
+    /// What to do when pulling stops on a rebase conflict
+    pub on_conflict: ConflictMode,
+    /// Only clone the repos of baums owned by this user or machine
+    pub mine: bool,
+    /// Clone the repos of all baums, even in a team workspace
+    pub all: bool,
+}
+
+/// Handling of rebase conflicts while pulling the workspace
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictMode {
+    /// Abort the rebase, leaving the workspace as it was
+    #[default]
+    Abort,
+    /// Leave the rebase stopped for the user to resolve
+    Resolve,
 }
 
 /// Sync workspace with remote, replaying moves
 pub fn sync(ws: &mut Workspace, opts: SyncOptions, out: &Output) -> Result<()> {
     out.require_human("sync")?;
 
-    // Check for uncommitted changes
-    let status_output = Command::new("git")
-        .arg("-C")
-        .arg(&ws.root)
-        .arg("status")
-        .arg("--porcelain")
-        .output()
-        .context("failed to check git status")?;
+    if ws.config.standalone {
+        out.info("Standalone workspace (initialized with --no-sync): nothing to sync");
+        return Ok(());
+    }
 
-    let status = String::from_utf8_lossy(&status_output.stdout);
-    if !status.trim().is_empty() {
+    // A conflict left for resolution by an earlier `sync --interactive`
+    if rebase_in_progress(&ws.root) {
+        bail!(
+            "a rebase is in progress in the workspace\n{}",
+            resolve_instructions(&ws.root)
+        );
+    }
+
+    // Check for uncommitted changes
+    let status = git::working_status(&ws.root)?;
+    if !status.is_clean() {
         bail!("uncommitted changes in workspace\nCommit or stash changes before syncing");
     }
 
+    // In a team workspace each machine only hydrates its owner's baums
+    let owner =
+        (opts.mine || (ws.config.team != TeamMode::Off && !opts.all)).then(|| current_owner(ws));
+
     // Get current HEAD before pull
     let head_before = get_head_commit(&ws.root)?;
 
     // Check if upstream is configured
-    if !has_upstream(&ws.root) {
+    if status.upstream.is_none() {
         out.info("No upstream configured - updating local state only");
 
         if !opts.dry_run {
@@ -53,9 +82,9 @@ pub fn sync(ws: &mut Workspace, opts: SyncOptions, out: &Output) -> Result<()> {
         return Ok(());
     }
 
-    // Check if local and remote have diverged using git rev-list
+    // Check if local and remote have diverged
     // This is more reliable than parsing error messages
-    let (ahead, behind) = get_ahead_behind(&ws.root)?;
+    let (ahead, behind) = (status.ahead, status.behind);
     if ahead > 0 && behind > 0 && !opts.force {
         bail!(
             "workspace has diverged from remote ({} ahead, {} behind)\nUse --force to force sync",
@@ -77,10 +106,13 @@ pub fn sync(ws: &mut Workspace, opts: SyncOptions, out: &Output) -> Result<()> {
             .arg("pull")
             .arg("--rebase")
             .arg("--quiet")
-            .output()
+            .timed_output()
             .context("failed to pull changes")?;
 
         if !pull_output.status.success() {
+            if rebase_in_progress(&ws.root) {
+                return Err(rebase_conflict(&ws.root, opts.on_conflict));
+            }
             let stderr = String::from_utf8_lossy(&pull_output.stderr);
             bail!("git pull failed: {}", stderr);
         }
@@ -93,6 +125,9 @@ pub fn sync(ws: &mut Workspace, opts: SyncOptions, out: &Output) -> Result<()> {
     if head_before == head_after {
         out.info("Already up to date");
 
+        // Repos that failed to clone on an earlier run
+        let hydrated = hydrate(ws, owner.as_deref(), &opts, out);
+
         // Push if requested and we have unpushed commits
         if opts.push {
             push_changes(ws, &opts, out)?;
@@ -104,7 +139,7 @@ pub fn sync(ws: &mut Workspace, opts: SyncOptions, out: &Output) -> Result<()> {
             ws.save_state()?;
         }
 
-        return Ok(());
+        return hydrated;
     }
 
     // Detect moves since last sync
@@ -124,11 +159,20 @@ pub fn sync(ws: &mut Workspace, opts: SyncOptions, out: &Output) -> Result<()> {
         }
     }
 
-    // Clone missing repos (unless offline mode)
-    if !opts.offline && !opts.dry_run {
-        clone_missing_repos(ws, out)?;
+    // Pulled .baum/gitignore changes only apply once info/exclude has them
+    if !opts.dry_run {
+        update_worktree_exclude(&ws.root)?;
+    }
+
+    // Baums planted elsewhere claim their IDs here too
+    if !opts.dry_run && !ws.state.baum_ids.is_empty() {
+        let ids = collect_baum_ids(&ws.root);
+        ws.state.baum_ids.extend(ids);
     }
 
+    // Clone missing repos (unless offline mode)
+    let hydrated = hydrate(ws, owner.as_deref(), &opts, out);
+
     // Push if requested
     if opts.push {
         push_changes(ws, &opts, out)?;
@@ -140,11 +184,28 @@ pub fn sync(ws: &mut Workspace, opts: SyncOptions, out: &Output) -> Result<()> {
         ws.save_state()?;
     }
 
+    hydrated?;
     out.success("Sync complete");
 
     Ok(())
 }
 
+/// Clone the missing repos unless offline or dry-running
+///
+/// Repos that fail to clone don't hold up the rest of the sync; their
+/// error is returned once it is done.
+fn hydrate(
+    ws: &mut Workspace,
+    owner: Option<&str>,
+    opts: &SyncOptions,
+    out: &Output,
+) -> Result<()> {
+    if opts.offline || opts.dry_run {
+        return Ok(());
+    }
+    clone_missing_repos(ws, owner, out)
+}
+
 fn push_changes(ws: &Workspace, opts: &SyncOptions, out: &Output) -> Result<()> {
     if opts.dry_run {
         out.info("Would push changes to remote");
@@ -158,7 +219,7 @@ fn push_changes(ws: &Workspace, opts: &SyncOptions, out: &Output) -> Result<()>
         .arg(&ws.root)
         .arg("push")
         .arg("--quiet")
-        .output()
+        .timed_output()
         .context("failed to push changes")?;
 
     if !push_output.status.success() {
@@ -193,7 +254,7 @@ fn replay_move(ws: &Workspace, old_path: &str, new_path: &str, out: &Output) ->
             let baum = load_baum(&new_abs)?;
             let bare_path = ws.bare_repo_path(&baum.repo_id)?;
 
-            move_worktrees_with_git(&bare_path, &old_abs, &new_abs, &baum.worktrees, out)?;
+            move_worktrees_with_git(ws, &bare_path, &old_abs, &new_abs, &baum.worktrees, out)?;
 
             // Clean up old directory if empty
             if old_abs.read_dir()?.next().is_none() {
@@ -231,10 +292,10 @@ fn replay_move(ws: &Workspace, old_path: &str, new_path: &str, out: &Output) ->
             let old_baum_dir = old_abs.join(".baum");
             let new_baum_dir = new_abs.join(".baum");
             fs::create_dir_all(&new_abs)?;
-            fs::rename(&old_baum_dir, &new_baum_dir)?;
+            move_path(&old_baum_dir, &new_baum_dir)?;
 
             // Move worktrees using git worktree move
-            move_worktrees_with_git(&bare_path, &old_abs, &new_abs, &baum.worktrees, out)?;
+            move_worktrees_with_git(ws, &bare_path, &old_abs, &new_abs, &baum.worktrees, out)?;
 
             // Clean up old directory if empty
             if old_abs.exists() && old_abs.read_dir()?.next().is_none() {
@@ -248,6 +309,7 @@ fn replay_move(ws: &Workspace, old_path: &str, new_path: &str, out: &Output) ->
 
 /// Move worktrees using `git worktree move` to properly update the registry
 fn move_worktrees_with_git(
+    ws: &Workspace,
     bare_path: &std::path::Path,
     old_container: &std::path::Path,
     new_container: &std::path::Path,
@@ -266,7 +328,11 @@ fn move_worktrees_with_git(
                 Ok(()) => {
                     out.status(
                         "Moved",
-                        &format!("worktree {} -> {}", old_wt.display(), new_wt.display()),
+                        &format!(
+                            "worktree {} -> {}",
+                            ws.display_path(&old_wt),
+                            ws.display_path(&new_wt)
+                        ),
                     );
                 }
                 Err(e) => {
@@ -280,89 +346,94 @@ fn move_worktrees_with_git(
     Ok(())
 }
 
-/// Check if the current branch has an upstream configured
-fn has_upstream(repo_path: &std::path::Path) -> bool {
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(repo_path)
-        .arg("rev-parse")
-        .arg("--abbrev-ref")
-        .arg("--symbolic-full-name")
-        .arg("@{upstream}")
-        .output();
+/// Handle a pull whose rebase stopped on conflicts, returning the error to
+/// report
+fn rebase_conflict(root: &Path, mode: ConflictMode) -> anyhow::Error {
+    let paths: String = conflicted_paths(root)
+        .iter()
+        .map(|p| format!("\n  {}", p))
+        .collect();
+
+    match mode {
+        ConflictMode::Resolve => anyhow!(
+            "pulling stopped on conflicts in:{}\n{}",
+            paths,
+            resolve_instructions(root)
+        ),
+        ConflictMode::Abort => match abort_rebase(root) {
+            Ok(()) => anyhow!(
+                "pulling conflicts with local commits in:{}\n\
+                 The rebase was aborted and the workspace is as before.\n\
+                 Run `wald sync --force --interactive` to resolve the conflicts",
+                paths
+            ),
+            Err(e) => anyhow!(
+                "pulling stopped on conflicts in:{}\nAborting the rebase failed: {:#}\n{}",
+                paths,
+                e,
+                resolve_instructions(root)
+            ),
+        },
+    }
+}
 
-    matches!(output, Ok(o) if o.status.success())
+/// How to finish or give up a stopped rebase
+fn resolve_instructions(root: &Path) -> String {
+    format!(
+        "Resolve the conflicts in {0}, `git -C {0} add` the fixed files and run \
+         `git -C {0} rebase --continue` (or `git -C {0} rebase --abort` to give up), \
+         then run `wald sync` again",
+        root.display()
+    )
 }
 
-/// Get the number of commits ahead and behind the upstream branch
-fn get_ahead_behind(repo_path: &std::path::Path) -> Result<(u32, u32)> {
-    let output = Command::new("git")
+/// Whether the repo is in the middle of a rebase
+fn rebase_in_progress(repo_path: &Path) -> bool {
+    ["rebase-merge", "rebase-apply"].iter().any(|marker| {
+        Command::new("git")
+            .arg("-C")
+            .arg(repo_path)
+            .arg("rev-parse")
+            .arg("--git-path")
+            .arg(marker)
+            .timed_output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| repo_path.join(String::from_utf8_lossy(&o.stdout).trim()))
+            .is_some_and(|path| path.exists())
+    })
+}
+
+/// Files with unresolved conflicts
+fn conflicted_paths(repo_path: &Path) -> Vec<String> {
+    Command::new("git")
         .arg("-C")
         .arg(repo_path)
-        .arg("rev-list")
-        .arg("--left-right")
-        .arg("--count")
-        .arg("HEAD...@{upstream}")
-        .output();
-
-    match output {
-        Ok(o) if o.status.success() => {
-            let stdout = String::from_utf8_lossy(&o.stdout);
-            let parts: Vec<&str> = stdout.trim().split('\t').collect();
-            if parts.len() == 2 {
-                let ahead = parts[0].parse::<u32>().unwrap_or(0);
-                let behind = parts[1].parse::<u32>().unwrap_or(0);
-                Ok((ahead, behind))
-            } else {
-                // Couldn't parse, assume not diverged
-                Ok((0, 0))
-            }
-        }
-        _ => {
-            // No upstream or error, assume not diverged
-            Ok((0, 0))
-        }
-    }
+        .arg("diff")
+        .arg("--name-only")
+        .arg("--diff-filter=U")
+        .timed_output()
+        .map(|o| {
+            String::from_utf8_lossy(&o.stdout)
+                .lines()
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
-/// Clone missing bare repos referenced by baums in the workspace
-fn clone_missing_repos(ws: &Workspace, out: &Output) -> Result<()> {
-    // Discover all baums
-    let baums = find_all_baums(&ws.root);
-
-    // Collect unique repo_ids that are missing
-    let mut missing: Vec<(String, &crate::types::RepoEntry)> = Vec::new();
-    for (_path, manifest) in &baums {
-        let repo_id = &manifest.repo_id;
-        if !ws.has_bare_repo(repo_id)
-            && let Some(entry) = ws.manifest.repos.get(repo_id)
-            && !missing.iter().any(|(id, _)| id == repo_id)
-        {
-            missing.push((repo_id.clone(), entry));
-        }
-    }
-
-    if missing.is_empty() {
-        return Ok(());
-    }
-
-    // Clone each with registered policies
-    out.info(&format!("Cloning {} missing repo(s)...", missing.len()));
-    for (repo_id, entry) in missing {
-        let id = RepoId::parse(&repo_id)?;
-        let bare_path = ws.repos_dir().join(id.to_bare_path());
-
-        let clone_opts = git::CloneOptions {
-            depth: match &entry.depth {
-                DepthPolicy::Full => None,
-                DepthPolicy::Depth(d) => Some(*d),
-            },
-            filter: entry.filter.as_git_arg().map(|s| s.to_string()),
-        };
-
-        out.status("Cloning", &repo_id);
-        git::clone_bare(&id, &bare_path, clone_opts)?;
+fn abort_rebase(repo_path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .arg("rebase")
+        .arg("--abort")
+        .timed_output()
+        .context("failed to abort the rebase")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git rebase --abort failed: {}", stderr.trim());
     }
-
     Ok(())
 }
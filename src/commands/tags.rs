@@ -0,0 +1,54 @@
+use anyhow::{Result, bail};
+
+use crate::git;
+use crate::output::{Output, OutputFormat};
+use crate::time::format_iso;
+use crate::types::TagPolicy;
+use crate::workspace::Workspace;
+
+/// List the tags in a repository's bare clone, newest first
+pub fn tags(ws: &Workspace, repo_ref: &str, out: &Output) -> Result<()> {
+    let repo_id = ws
+        .resolve_repo(repo_ref)
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow::anyhow!("repository not found: {}", repo_ref))?;
+    let bare_path = ws.bare_repo_path(&repo_id)?;
+    if !bare_path.exists() {
+        bail!("bare repo not found: {}", ws.display_path(&bare_path));
+    }
+
+    let tags = git::list_tags(&bare_path)?;
+    let policy = ws.manifest.repos.get(&repo_id).map(|e| &e.tags);
+
+    match out.format {
+        OutputFormat::Human => {
+            if tags.is_empty() {
+                out.info(&format!("No tags in {}", repo_id));
+                if policy.is_some_and(|p| *p != TagPolicy::All) {
+                    out.info("Its tag policy limits fetched tags; `wald repo fetch --tags` fetches them all");
+                }
+                return Ok(());
+            }
+            let dates: Vec<String> = tags.iter().map(|tag| out.time(tag.date)).collect();
+            let width = dates.iter().map(String::len).max().unwrap_or(0);
+            for (tag, date) in tags.iter().zip(&dates) {
+                println!("{:<width$}  {}  {}", date, out.sha(&tag.commit), tag.name);
+            }
+        }
+        OutputFormat::Json => {
+            let entries: Vec<_> = tags
+                .iter()
+                .map(|tag| {
+                    serde_json::json!({
+                        "name": tag.name,
+                        "commit": tag.commit,
+                        "date": format_iso(tag.date),
+                    })
+                })
+                .collect();
+            out.print_json(&entries)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use anyhow::{Result, bail};
+
+use crate::output::Output;
+use crate::workspace::{Workspace, list_trash, purge_trash};
+
+/// Permanently delete every uprooted baum kept in .wald/trash/
+pub fn trash_empty(ws: &Workspace, out: &Output) -> Result<()> {
+    out.require_human("trash")?;
+
+    let trashed = list_trash(ws)?.len();
+    if trashed == 0 {
+        out.info("Trash is already empty");
+        return Ok(());
+    }
+    if !out.confirm(&format!("permanently delete {} trashed baum(s)", trashed))? {
+        bail!("aborted; the trash was left as it was");
+    }
+
+    let purged = purge_trash(ws, None)?;
+    if purged == 0 {
+        out.info("Trash is already empty");
+    } else {
+        out.success(&format!("Deleted {} trashed baum(s)", purged));
+    }
+
+    Ok(())
+}
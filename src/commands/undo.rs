@@ -0,0 +1,81 @@
+use anyhow::{Result, bail};
+
+use crate::git;
+use crate::output::Output;
+use crate::workspace::baum::load_baum;
+use crate::workspace::gitignore::update_worktree_exclude;
+use crate::workspace::{
+    Workspace, configure_tracking, configure_worktree, list_trash, lock_baum, restore_from_trash,
+};
+
+/// Restore the most recently uprooted baum from .wald/trash/
+///
+/// The container goes back to its old path and its worktrees are recreated
+/// from the branches uproot left in the bare repo.
+pub fn undo_uproot(ws: &Workspace, out: &Output) -> Result<()> {
+    out.require_human("undo")?;
+
+    let Some(entry) = list_trash(ws)?.pop() else {
+        bail!("nothing to undo: no uprooted baums in .wald/trash/");
+    };
+
+    let baum_manifest = load_baum(&entry.path)?;
+    let bare_path = ws.bare_repo_path(&baum_manifest.repo_id)?;
+    if !bare_path.exists() {
+        bail!(
+            "bare repo not found: {}\nRun `wald repo add --clone {}` first",
+            ws.display_path(&bare_path),
+            baum_manifest.repo_id
+        );
+    }
+
+    out.status(
+        "Restoring",
+        &format!("{} at {}", baum_manifest.repo_id, entry.origin.display()),
+    );
+    let container = restore_from_trash(ws, &entry)?;
+    let _lock = lock_baum(&container, "undo")?;
+    update_worktree_exclude(&ws.root)?;
+
+    let mut restored = 0;
+    for wt in &baum_manifest.worktrees {
+        let worktree_path = container.join(&wt.path);
+        if worktree_path.exists() {
+            out.warn(&format!(
+                "Skipping {}: {} already exists",
+                wt.branch,
+                ws.display_path(&worktree_path)
+            ));
+            continue;
+        }
+
+        out.status(
+            "Creating worktree",
+            &format!("{} -> {}", wt.branch, wt.path),
+        );
+        let branch = wt.local_branch.as_deref().unwrap_or(&wt.branch);
+        if let Err(e) = git::add_worktree(&bare_path, &worktree_path, branch) {
+            out.warn(&format!("Failed to restore worktree {}: {}", wt.branch, e));
+            continue;
+        }
+        if let Some(identity) =
+            configure_worktree(ws, &baum_manifest.repo_id, &bare_path, &worktree_path)?
+        {
+            out.status("Pinning identity", identity);
+        }
+        // The push target lives in the worktree's own config, which went with it
+        if let (Some(_), Some(local_branch)) = (&wt.track, &wt.local_branch) {
+            configure_tracking(&bare_path, &worktree_path, local_branch, &wt.branch)?;
+        }
+        restored += 1;
+    }
+
+    out.success(&format!(
+        "Restored {} with {} of {} worktree(s)",
+        baum_manifest.repo_id,
+        restored,
+        baum_manifest.worktrees.len()
+    ));
+
+    Ok(())
+}
@@ -1,12 +1,16 @@
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Result, bail};
 
+use crate::commands::lock::ensure_unlocked;
 use crate::git;
 use crate::output::Output;
+use crate::types::BaumManifest;
 use crate::workspace::baum::load_baum;
-use crate::workspace::{Workspace, is_baum, validate_workspace_path};
+use crate::workspace::gitignore::update_worktree_exclude;
+use crate::workspace::{
+    Workspace, apply_trash_retention, is_baum, lock_baum, trash_container, validate_workspace_path,
+};
 
 /// Options for uproot command
 pub struct UprootOptions {
@@ -14,7 +18,10 @@ pub struct UprootOptions {
     pub force: bool,
 }
 
-/// Uproot a baum (remove container and worktrees)
+/// Uproot a baum (remove worktrees, move container to .wald/trash/)
+///
+/// Branch refs stay in the bare repo so `wald undo uproot` can bring the
+/// baum back.
 pub fn uproot(ws: &Workspace, opts: UprootOptions, out: &Output) -> Result<()> {
     out.require_human("uproot")?;
 
@@ -25,17 +32,47 @@ pub fn uproot(ws: &Workspace, opts: UprootOptions, out: &Output) -> Result<()> {
     if !is_baum(&container) {
         bail!(
             "not a baum: {} (.baum directory not found)",
-            container.display()
+            ws.display_path(&container)
         );
     }
 
+    let mut lock = lock_baum(&container, "uproot")?;
+
     // Load baum manifest to get worktree info
     let baum_manifest = load_baum(&container)?;
 
     // Get bare repo path
     let bare_path = ws.bare_repo_path(&baum_manifest.repo_id)?;
 
-    out.status("Uprooting", &format!("{}", container.display()));
+    // Check locks before removing anything; --force does not override them
+    for wt in &baum_manifest.worktrees {
+        ensure_unlocked(
+            &bare_path,
+            &container.join(&wt.path),
+            &opts.path,
+            &wt.branch,
+        )?;
+    }
+
+    // Uncommitted work is not kept in the trash, and unpushed commits are
+    // left on branches that `prune --branches` later treats as orphans
+    let risks = safety_report(&container, &bare_path, &baum_manifest);
+    if !risks.is_empty() && !opts.force {
+        let mut report = format!(
+            "{} has work that uprooting would put at risk:",
+            ws.display_path(&container)
+        );
+        for risk in &risks {
+            report.push_str(&format!("\n  {}", risk));
+        }
+        report.push_str("\nCommit and push it first, or use --force to uproot anyway");
+        bail!(report);
+    }
+    if !risks.is_empty() && !out.confirm(&risk_prompt(&risks))? {
+        bail!("aborted; nothing was uprooted");
+    }
+
+    out.status("Uprooting", &ws.display_path(&container));
 
     // Remove each worktree from git
     for wt in &baum_manifest.worktrees {
@@ -54,14 +91,113 @@ pub fn uproot(ws: &Workspace, opts: UprootOptions, out: &Output) -> Result<()> {
         }
     }
 
-    // Remove the container directory
-    fs::remove_dir_all(&container)?;
+    // Keep the rest of the container around for `wald undo uproot`
+    let trashed = trash_container(ws, &container)?;
+    lock.moved_to(&trashed);
+    out.verbose(&format!("Moved container to {}", ws.display_path(&trashed)));
+    update_worktree_exclude(&ws.root)?;
+
+    match apply_trash_retention(ws) {
+        Ok(0) => {}
+        Ok(n) => out.verbose(&format!("Purged {} expired trash entries", n)),
+        Err(e) => out.warn(&format!("Failed to purge expired trash: {}", e)),
+    }
 
     out.success(&format!(
-        "Uprooted {} ({} worktree(s) removed)",
+        "Uprooted {} ({} worktree(s) removed; `wald undo uproot` restores it)",
         baum_manifest.repo_id,
         baum_manifest.worktrees.len()
     ));
 
     Ok(())
 }
+
+/// A worktree whose uncommitted changes or unpushed commits uprooting
+/// would put at risk
+struct WorktreeRisk {
+    branch: String,
+    /// Changed and untracked files
+    dirty: usize,
+    /// Subjects of the unpushed commits, newest first
+    unpushed: Vec<String>,
+}
+
+impl std::fmt::Display for WorktreeRisk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.branch)?;
+        if self.dirty > 0 {
+            write!(f, " {} uncommitted file(s)", self.dirty)?;
+        }
+        if self.dirty > 0 && !self.unpushed.is_empty() {
+            write!(f, ",")?;
+        }
+        match self.unpushed.first() {
+            Some(last) => write!(
+                f,
+                " {} unpushed commit(s), last \"{}\"",
+                self.unpushed.len(),
+                last
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The baum's worktrees with uncommitted changes or unpushed commits
+///
+/// Nothing is fetched to find out: a worktree that can't be checked
+/// offline is left to `git worktree remove`, which refuses it.
+fn safety_report(
+    container: &Path,
+    bare_path: &Path,
+    baum_manifest: &BaumManifest,
+) -> Vec<WorktreeRisk> {
+    let mut risks = Vec::new();
+    for wt in &baum_manifest.worktrees {
+        let path = container.join(&wt.path);
+        let dirty = if path.exists() {
+            git::working_status_offline(&path)
+                .map(|s| s.changed.len() + s.untracked.len())
+                .unwrap_or(0)
+        } else {
+            0
+        };
+        let local_branch = wt.local_branch.as_deref().unwrap_or(&wt.branch);
+        let unpushed = if git::has_unpushed_commits(bare_path, local_branch).unwrap_or(false) {
+            git::unpushed_subjects(bare_path, local_branch).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        if dirty > 0 || !unpushed.is_empty() {
+            risks.push(WorktreeRisk {
+                branch: wt.branch.clone(),
+                dirty,
+                unpushed,
+            });
+        }
+    }
+    risks
+}
+
+/// What `--force` asks before going ahead, e.g. `discard the local
+/// changes in main`
+fn risk_prompt(risks: &[WorktreeRisk]) -> String {
+    let names = |pick: fn(&WorktreeRisk) -> bool| {
+        risks
+            .iter()
+            .filter(|r| pick(r))
+            .map(|r| r.branch.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let dirty = names(|r| r.dirty > 0);
+    let unpushed = names(|r| !r.unpushed.is_empty());
+    match (dirty.is_empty(), unpushed.is_empty()) {
+        (false, true) => format!("discard the local changes in {}", dirty),
+        (true, false) => format!("leave the unpushed commits of {} behind", unpushed),
+        _ => format!(
+            "discard the local changes in {} and leave the unpushed commits of {} behind",
+            dirty, unpushed
+        ),
+    }
+}
@@ -1,15 +1,34 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
-use walkdir::WalkDir;
+use anyhow::{Result, bail};
 
-use crate::output::{Output, OutputFormat};
-use crate::workspace::baum::load_baum;
-use crate::workspace::{Workspace, is_baum, validate_workspace_path};
+use crate::git::{self, WorktreeInfo};
+use crate::output::{JSON_SCHEMA_VERSION, Output, OutputFormat};
+use crate::time::{format_iso, now_secs, parse_since};
+use crate::types::{BaumManifest, WorktreeEntry};
+use crate::workspace::baum::{last_used, load_baum};
+use crate::workspace::{Workspace, current_owner, find_baum_dirs, validate_workspace_path};
 
 /// Options for worktrees command
 pub struct WorktreesOptions {
     pub filter: Option<PathBuf>,
+    /// Only list worktrees of this repository (ID or alias)
+    pub repo_ref: Option<String>,
+    /// Check each worktree against git's worktree list
+    pub verify: bool,
+    /// Only list worktrees unused since this point (e.g. "90d")
+    pub stale: Option<String>,
+    /// Only list the baums owned by this user or machine
+    pub mine: bool,
+}
+
+/// Options for which command
+pub struct WhichOptions {
+    /// Repository ID or alias
+    pub repo_ref: String,
+    /// Only worktrees for this branch
+    pub branch: Option<String>,
 }
 
 /// List all worktrees in the workspace
@@ -21,61 +40,72 @@ pub fn worktrees(ws: &Workspace, opts: WorktreesOptions, out: &Output) -> Result
         ws.root.clone()
     };
 
-    // Find all baums
+    let repo_id = match &opts.repo_ref {
+        Some(r) => Some(resolve_repo_ref(ws, r)?),
+        None => None,
+    };
+
+    let now = now_secs();
+    let stale_before = match &opts.stale {
+        Some(age) => Some(parse_since(age, now)?),
+        None => None,
+    };
+
     let mut all_worktrees: Vec<WorktreeDisplay> = Vec::new();
 
-    for entry in WalkDir::new(&search_root)
-        .follow_links(false)
-        .into_iter()
-        .filter_entry(|e| {
-            // Skip .git directories, .wald/repos, and _*.wt worktree directories
-            let name = e.file_name().to_string_lossy();
-            if name == ".git" {
-                return false;
-            }
-            if name == "repos"
-                && e.path()
-                    .parent()
-                    .map(|p| p.ends_with(".wald"))
-                    .unwrap_or(false)
-            {
-                return false;
-            }
-            // Skip worktree directories (no need to descend into them)
-            if e.file_type().is_dir() && name.starts_with('_') && name.ends_with(".wt") {
-                return false;
-            }
-            true
-        })
-    {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
+    // git worktree lists per repo, fetched on demand for --verify
+    let mut git_lists: HashMap<String, Vec<WorktreeInfo>> = HashMap::new();
 
-        if entry.file_type().is_dir() && is_baum(entry.path()) {
-            // Load baum and get worktrees
-            if let Ok(baum) = load_baum(entry.path()) {
-                let container_path = entry
-                    .path()
-                    .strip_prefix(&ws.root)
-                    .unwrap_or(entry.path())
-                    .to_path_buf();
-
-                for wt in &baum.worktrees {
-                    all_worktrees.push(WorktreeDisplay {
-                        repo_id: baum.repo_id.clone(),
-                        container: container_path.to_string_lossy().to_string(),
-                        branch: wt.branch.clone(),
-                        path: wt.path.clone(),
-                    });
-                }
-            }
+    let mut found_baums = find_baums(ws, &search_root, repo_id.as_deref());
+    if opts.mine {
+        let owner = current_owner(ws);
+        found_baums.retain(|(_, baum)| baum.as_ref().is_ok_and(|b| b.is_owned_by(&owner)));
+    }
+    let mut baums: Vec<BaumHealth> = found_baums
+        .iter()
+        .map(|(container, baum)| baum_health(ws, container, baum))
+        .collect();
+    baums.sort_by(|a, b| a.container.cmp(&b.container));
+
+    for found in into_worktrees(found_baums) {
+        let last_used = last_used(&found.container, &found.entry);
+        if let Some(cutoff) = stale_before
+            && last_used.is_some_and(|used| used >= cutoff)
+        {
+            continue;
         }
+
+        let (status, lock_reason) = if opts.verify {
+            let list = git_lists.entry(found.repo_id.clone()).or_insert_with(|| {
+                ws.bare_repo_path(&found.repo_id)
+                    .and_then(|p| git::list_worktrees(&p))
+                    .unwrap_or_default()
+            });
+            let (status, reason) = verify_worktree(list, &found.worktree_path());
+            (Some(status), reason)
+        } else {
+            (None, None)
+        };
+
+        all_worktrees.push(WorktreeDisplay {
+            container: found.relative_container(ws),
+            repo_id: found.repo_id,
+            branch: found.entry.branch,
+            path: found.entry.path,
+            status,
+            lock_reason,
+            created_at: found.entry.created_at,
+            last_used: last_used.map(format_iso),
+            idle_since: stale_before.and(last_used),
+        });
     }
 
-    if all_worktrees.is_empty() {
-        out.info("No worktrees found");
+    let broken = baums.iter().any(|b| !b.problems.is_empty());
+    if all_worktrees.is_empty() && !broken && out.format == OutputFormat::Human {
+        match &opts.stale {
+            Some(age) => out.info(&format!("No worktrees unused for {}", age)),
+            None => out.info("No worktrees found"),
+        }
         return Ok(());
     }
 
@@ -84,32 +114,280 @@ pub fn worktrees(ws: &Workspace, opts: WorktreesOptions, out: &Output) -> Result
 
     match out.format {
         OutputFormat::Human => {
-            // Group by container
-            let mut current_container = String::new();
-            for wt in &all_worktrees {
-                if wt.container != current_container {
-                    if !current_container.is_empty() {
-                        println!();
-                    }
-                    println!("{} ({})", wt.container, wt.repo_id);
-                    current_container = wt.container.clone();
+            // Group by container, headed by the baum's health; broken baums
+            // show up even without worktrees to list
+            let mut first = true;
+            for baum in &baums {
+                let listed: Vec<&WorktreeDisplay> = all_worktrees
+                    .iter()
+                    .filter(|wt| wt.container == baum.container)
+                    .collect();
+                if listed.is_empty() && baum.problems.is_empty() {
+                    continue;
+                }
+                if !first {
+                    println!();
+                }
+                first = false;
+                let repo = baum.repo_id.as_deref().unwrap_or("?");
+                match &baum.owner {
+                    Some(owner) => println!(
+                        "{} ({}, owner {}) [{}]",
+                        baum.container,
+                        repo,
+                        owner,
+                        baum.badge()
+                    ),
+                    None => println!("{} ({}) [{}]", baum.container, repo, baum.badge()),
+                }
+                for wt in listed {
+                    print_worktree(wt, opts.stale.is_some(), out);
                 }
-                println!("  {} -> {}", wt.branch, wt.path);
             }
         }
         OutputFormat::Json => {
-            let json = serde_json::to_string_pretty(&all_worktrees)?;
-            println!("{}", json);
+            let json = serde_json::json!({
+                "schema_version": JSON_SCHEMA_VERSION,
+                "worktrees": all_worktrees,
+                "baums": baums,
+            });
+            out.print_json(&json)?;
         }
     }
 
     Ok(())
 }
 
+fn print_worktree(wt: &WorktreeDisplay, stale: bool, out: &Output) {
+    let mut notes = Vec::new();
+    match (&wt.status, &wt.lock_reason) {
+        (Some(status), Some(reason)) => notes.push(format!("{}: {}", status, reason)),
+        (Some(status), None) => notes.push(status.to_string()),
+        _ => {}
+    }
+    if stale {
+        notes.push(match wt.idle_since {
+            Some(used) => format!("last used {}", out.time(used)),
+            None => "never used".to_string(),
+        });
+    }
+    if notes.is_empty() {
+        println!("  {} -> {}", wt.branch, wt.path);
+    } else {
+        println!("  {} -> {} [{}]", wt.branch, wt.path, notes.join(", "));
+    }
+}
+
+/// Print the paths of a repo's worktrees, optionally for one branch
+///
+/// The reverse of `worktrees <path>`: answers "where is this repo checked
+/// out?". Prints one absolute path per line so it composes with `cd`.
+pub fn which(ws: &Workspace, opts: WhichOptions, out: &Output) -> Result<()> {
+    let repo_id = resolve_repo_ref(ws, &opts.repo_ref)?;
+
+    let mut matches: Vec<FoundWorktree> = find_worktrees(ws, &ws.root, Some(&repo_id))
+        .into_iter()
+        .filter(|w| opts.branch.as_ref().is_none_or(|b| &w.entry.branch == b))
+        .collect();
+
+    if matches.is_empty() {
+        match &opts.branch {
+            Some(branch) => bail!("no worktree of {} for branch '{}'", repo_id, branch),
+            None => bail!("no worktrees of {}", repo_id),
+        }
+    }
+
+    matches.sort_by(|a, b| (&a.container, &a.entry.branch).cmp(&(&b.container, &b.entry.branch)));
+
+    match out.format {
+        OutputFormat::Human => {
+            for found in &matches {
+                println!("{}", found.worktree_path().display());
+            }
+        }
+        OutputFormat::Json => {
+            let entries: Vec<_> = matches
+                .iter()
+                .map(|found| {
+                    serde_json::json!({
+                        "repo_id": found.repo_id,
+                        "container": found.relative_container(ws),
+                        "branch": found.entry.branch,
+                        "path": found.worktree_path(),
+                    })
+                })
+                .collect();
+            out.print_json(&entries)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A worktree entry found in a baum manifest
+struct FoundWorktree {
+    /// Absolute container path
+    container: PathBuf,
+    repo_id: String,
+    entry: WorktreeEntry,
+}
+
+impl FoundWorktree {
+    fn worktree_path(&self) -> PathBuf {
+        self.container.join(&self.entry.path)
+    }
+
+    fn relative_container(&self, ws: &Workspace) -> String {
+        ws.display_path(&self.container)
+    }
+}
+
+/// Resolve a repo reference, failing with a clear message
+fn resolve_repo_ref(ws: &Workspace, reference: &str) -> Result<String> {
+    ws.resolve_repo(reference)
+        .map(|s| s.to_string())
+        .ok_or_else(|| anyhow::anyhow!("repository not found: {}", reference))
+}
+
+/// Walk `search_root` for baums, optionally only those of one repo
+///
+/// Baums whose manifest can't be read are kept (with the error) unless a
+/// repo is given, since their repo is unknown.
+fn find_baums(
+    ws: &Workspace,
+    search_root: &Path,
+    repo_id: Option<&str>,
+) -> Vec<(PathBuf, Result<BaumManifest>)> {
+    find_baum_dirs(&ws.root, search_root, &ws.config)
+        .into_iter()
+        .map(|container| {
+            let baum = load_baum(&container);
+            (container, baum)
+        })
+        .filter(|(_, baum)| match (repo_id, baum) {
+            (Some(id), Ok(baum)) => id == baum.repo_id,
+            (Some(_), Err(_)) => false,
+            (None, _) => true,
+        })
+        .collect()
+}
+
+/// The worktrees of the baums whose manifest could be read
+fn into_worktrees(baums: Vec<(PathBuf, Result<BaumManifest>)>) -> Vec<FoundWorktree> {
+    let mut found = Vec::new();
+    for (container, baum) in baums {
+        let Ok(baum) = baum else {
+            continue;
+        };
+        for wt in baum.worktrees {
+            found.push(FoundWorktree {
+                container: container.clone(),
+                repo_id: baum.repo_id.clone(),
+                entry: wt,
+            });
+        }
+    }
+    found
+}
+
+/// Walk `search_root` for baums and collect their worktrees, optionally
+/// only those of one repo
+fn find_worktrees(ws: &Workspace, search_root: &Path, repo_id: Option<&str>) -> Vec<FoundWorktree> {
+    into_worktrees(find_baums(ws, search_root, repo_id))
+}
+
+/// Cheap checks of a baum, short of `wald doctor`: its manifest parses,
+/// its bare repo is there and so are its worktree directories
+fn baum_health(ws: &Workspace, container: &Path, baum: &Result<BaumManifest>) -> BaumHealth {
+    let mut problems = Vec::new();
+    let owner = baum.as_ref().ok().and_then(|b| b.owner.clone());
+    let repo_id = match baum {
+        Ok(baum) => {
+            if !ws
+                .bare_repo_path(&baum.repo_id)
+                .is_ok_and(|path| path.exists())
+            {
+                problems.push("bare repo missing".to_string());
+            }
+            let missing = baum
+                .worktrees
+                .iter()
+                .filter(|wt| !container.join(&wt.path).is_dir())
+                .count();
+            if missing > 0 {
+                problems.push(format!("{} worktree dir(s) missing", missing));
+            }
+            Some(baum.repo_id.clone())
+        }
+        Err(e) => {
+            let error = format!("{:#}", e);
+            let error = error.lines().next().unwrap_or_default();
+            problems.push(format!("manifest unreadable: {}", error));
+            None
+        }
+    };
+    BaumHealth {
+        container: ws.display_path(container),
+        repo_id,
+        owner,
+        problems,
+    }
+}
+
+/// Classify a worktree against git's worktree list
+///
+/// Returns the status label and the lock reason, if any. A locked worktree
+/// whose directory is missing is expected (e.g. an unmounted drive), so it
+/// reports as locked rather than missing.
+fn verify_worktree(list: &[WorktreeInfo], path: &Path) -> (&'static str, Option<String>) {
+    let info = git::match_worktree(list, path);
+
+    match info {
+        Some(info) if info.locked => ("locked", info.lock_reason.clone()),
+        _ if !path.exists() => ("missing", None),
+        None => ("unregistered", None),
+        Some(info) if info.prunable => ("prunable", None),
+        Some(_) => ("ok", None),
+    }
+}
+
+#[derive(serde::Serialize)]
+struct BaumHealth {
+    container: String,
+    /// None if the manifest couldn't be read
+    repo_id: Option<String>,
+    /// Who the baum belongs to in a team workspace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    /// Empty for a healthy baum
+    problems: Vec<String>,
+}
+
+impl BaumHealth {
+    fn badge(&self) -> String {
+        if self.problems.is_empty() {
+            "ok".to_string()
+        } else {
+            format!("broken: {}", self.problems.join(", "))
+        }
+    }
+}
+
 #[derive(serde::Serialize)]
 struct WorktreeDisplay {
     repo_id: String,
     container: String,
     branch: String,
     path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lock_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_used: Option<String>,
+    /// When it was last used (seconds since the Unix epoch), with --stale
+    #[serde(skip)]
+    idle_since: Option<u64>,
 }
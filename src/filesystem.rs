@@ -0,0 +1,251 @@
+//! Filesystem quirks: network mounts, cross-device moves, read-only trees
+//!
+//! Workspaces on NFS or SMB shares, or with subdirectories on another mount,
+//! can't rely on `rename` working everywhere or on `.wald/` being writable.
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+use walkdir::WalkDir;
+
+/// Filesystem types that are mounted over the network
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "fuse.sshfs",
+    "fuse.rclone",
+];
+
+/// Type of the filesystem `path` is on (e.g. `ext4`, `nfs4`), if known
+///
+/// Read from `/proc/self/mounts`, so only available on Linux.
+pub fn fs_type(path: &Path) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    let mounts = fs::read_to_string("/proc/self/mounts").ok()?;
+    mount_fs_type(&mounts, &path)
+}
+
+/// Whether `path` is on a network filesystem
+pub fn is_network_fs(path: &Path) -> bool {
+    fs_type(path).is_some_and(|t| NETWORK_FS_TYPES.contains(&t.as_str()))
+}
+
+/// The type of the innermost mount containing `path`
+fn mount_fs_type(mounts: &str, path: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            // Spaces and the like in mount points are octal-escaped
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            path.starts_with(&mount_point)
+                .then(|| (mount_point.len(), fs_type.to_string()))
+        })
+        // Later mounts shadow earlier ones on the same point
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, fs_type)| fs_type)
+}
+
+/// Whether `a` and `b` (or, if missing, their nearest existing ancestors)
+/// are on the same device, so `rename` works between them
+pub fn same_device(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let device = |path: &Path| {
+            path.ancestors()
+                .find_map(|p| fs::metadata(p).ok())
+                .map(|m| m.dev())
+        };
+        match (device(a), device(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (a, b);
+        true
+    }
+}
+
+/// Move a file or directory, copying and deleting it when `rename` can't
+/// cross filesystems
+pub fn move_path(from: &Path, to: &Path) -> Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_tree(from, to)?;
+            remove_path(from)
+        }
+        Err(e) => {
+            Err(e).with_context(|| format!("failed to move {} to {}", from.display(), to.display()))
+        }
+    }
+}
+
+/// Copy a directory tree (or a single file), keeping symlinks as symlinks
+pub fn copy_tree(from: &Path, to: &Path) -> Result<()> {
+    for entry in WalkDir::new(from) {
+        let entry = entry.with_context(|| format!("failed to read {}", from.display()))?;
+        let rel = entry.path().strip_prefix(from).unwrap_or(entry.path());
+        let target = to.join(rel);
+        let file_type = entry.file_type();
+
+        if file_type.is_dir() {
+            fs::create_dir_all(&target)
+                .with_context(|| format!("failed to create directory: {}", target.display()))?;
+        } else if file_type.is_symlink() {
+            let link = fs::read_link(entry.path())?;
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&link, &target)
+                .with_context(|| format!("failed to create symlink: {}", target.display()))?;
+            #[cfg(not(unix))]
+            fs::copy(entry.path(), &target).map(|_| ())?;
+        } else {
+            fs::copy(entry.path(), &target).with_context(|| {
+                format!(
+                    "failed to copy {} to {}",
+                    entry.path().display(),
+                    target.display()
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> Result<()> {
+    let result = if path.is_dir() && !path.is_symlink() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    result.with_context(|| format!("failed to remove {}", path.display()))
+}
+
+/// Whether files can be created in `dir`
+///
+/// Permission bits don't tell about read-only mounts, so this tries.
+pub fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".wald-write-test-{}", std::process::id()));
+    match fs::File::create(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Normalize a path by resolving `.` and `..` components without requiring the path to exist.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                // Go up one level if possible
+                normalized.pop();
+            }
+            Component::CurDir => {
+                // Skip current dir markers
+            }
+            component => {
+                normalized.push(component);
+            }
+        }
+    }
+
+    normalized
+}
+
+/// The path leading from directory `base` to `target`, both absolute and
+/// normalized, e.g. `../../b.git/objects`
+pub fn relative_path(base: &Path, target: &Path) -> PathBuf {
+    let base: Vec<Component> = base.components().collect();
+    let target: Vec<Component> = target.components().collect();
+    let common = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+
+    let mut path = PathBuf::new();
+    for _ in common..base.len() {
+        path.push("..");
+    }
+    for component in &target[common..] {
+        path.push(component);
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mount_fs_type() {
+        let mounts = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+server:/export /home nfs4 rw 0 0
+tmpfs /home/user/my\\040files tmpfs rw 0 0
+";
+        let fs_type = |p: &str| mount_fs_type(mounts, Path::new(p));
+        assert_eq!(fs_type("/usr/bin").as_deref(), Some("ext4"));
+        assert_eq!(fs_type("/home/user/work").as_deref(), Some("nfs4"));
+        assert_eq!(fs_type("/home/user/my files/x").as_deref(), Some("tmpfs"));
+        // A prefix of the name is not a parent directory
+        assert_eq!(fs_type("/homework").as_deref(), Some("ext4"));
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let rel = |a: &str, b: &str| relative_path(Path::new(a), Path::new(b));
+        assert_eq!(
+            rel(
+                "/ws/.wald/repos/a/b.git/objects",
+                "/ws/.wald/repos/a/c.git/objects"
+            ),
+            Path::new("../../c.git/objects")
+        );
+        assert_eq!(rel("/ws/a", "/ws/a/b"), Path::new("b"));
+        assert_eq!(rel("/ws/a/b", "/ws"), Path::new("../.."));
+    }
+
+    #[test]
+    fn test_copy_tree() {
+        let dir = TempDir::new().unwrap();
+        let from = dir.path().join("from");
+        fs::create_dir_all(from.join("sub")).unwrap();
+        fs::write(from.join("sub/file.txt"), "content").unwrap();
+        fs::write(from.join(".git"), "gitdir: /elsewhere").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("sub/file.txt", from.join("link")).unwrap();
+
+        let to = dir.path().join("to");
+        copy_tree(&from, &to).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(to.join("sub/file.txt")).unwrap(),
+            "content"
+        );
+        assert!(to.join(".git").is_file());
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read_link(to.join("link")).unwrap(),
+            Path::new("sub/file.txt")
+        );
+    }
+}
@@ -0,0 +1,511 @@
+//! Creating repositories and pull requests on code forges, and looking
+//! repositories up
+//!
+//! Through the forges' REST APIs with `curl`, like `self-update` talks to
+//! GitLab. Repositories are only created on GitHub; everything else goes to
+//! GitHub for `github.com` repos and to a GitLab instance on any other host.
+//! The GitLab token only goes to the hosts in `WALD_GITLAB_HOST`
+//! (`gitlab.com` by default); other hosts are looked up without it.
+
+use std::env;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::timings::TimedCommand;
+use crate::types::RepoId;
+
+/// Environment variables checked, in order, for a GitHub access token
+const GITHUB_TOKEN_ENV: &[&str] = &["GITHUB_TOKEN", "GH_TOKEN"];
+
+/// Overrides the API base, for GitHub Enterprise
+const GITHUB_API_ENV: &str = "WALD_GITHUB_API";
+
+const GITHUB_API: &str = "https://api.github.com";
+
+/// Environment variables checked, in order, for a GitLab access token
+const GITLAB_TOKEN_ENV: &[&str] = &["GITLAB_TOKEN", "WALD_GITLAB_TOKEN"];
+
+/// Overrides the API base of every GitLab host, mainly for testing
+const GITLAB_API_ENV: &str = "WALD_GITLAB_API";
+
+/// Comma-separated hosts the GitLab token may be sent to
+const GITLAB_HOST_ENV: &str = "WALD_GITLAB_HOST";
+
+/// The GitLab host trusted with the token when `WALD_GITLAB_HOST` isn't set
+const GITLAB_HOST: &str = "gitlab.com";
+
+#[derive(Deserialize)]
+struct GithubUser {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct GithubRepo {
+    ssh_url: String,
+}
+
+#[derive(Deserialize)]
+struct GithubPull {
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct GitlabMergeRequest {
+    web_url: String,
+}
+
+#[derive(Deserialize)]
+struct GithubRepoInfo {
+    full_name: String,
+    #[serde(default)]
+    archived: bool,
+    /// In KiB
+    #[serde(default)]
+    size: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct GitlabProject {
+    path_with_namespace: String,
+    #[serde(default)]
+    archived: bool,
+    /// Only for tokens with at least reporter access
+    #[serde(default)]
+    statistics: Option<GitlabStatistics>,
+}
+
+#[derive(Deserialize)]
+struct GitlabStatistics {
+    /// In bytes
+    repository_size: u64,
+}
+
+/// What a forge reports about a repository
+#[derive(Debug, PartialEq)]
+pub struct ForgeRepo {
+    /// Where the repository is now (`owner/name`), which differs from the
+    /// path asked for if it was renamed or transferred
+    pub path: String,
+    pub archived: bool,
+    /// Size of the repository in bytes, if the forge says
+    pub size: Option<u64>,
+}
+
+impl ForgeRepo {
+    /// The new path, if the repository no longer lives at `repo`'s
+    pub fn moved_from(&self, repo: &RepoId) -> Option<&str> {
+        (!self.path.eq_ignore_ascii_case(&repo.path.join("/"))).then_some(self.path.as_str())
+    }
+}
+
+/// A pull (or merge) request to open
+pub struct PullRequest<'a> {
+    /// Branch with the changes, on the same repository
+    pub head: &'a str,
+    /// Branch to merge into
+    pub base: &'a str,
+    pub title: &'a str,
+    pub body: &'a str,
+    pub draft: bool,
+}
+
+/// Create the private GitHub repository `owner/name` and return its SSH URL
+///
+/// `owner` may be the token's user or an organization it can create
+/// repositories in.
+pub fn create_github_repo(slug: &str) -> Result<String> {
+    let (owner, name) = parse_slug(slug)?;
+    let Some(token) = find_token(GITHUB_TOKEN_ENV) else {
+        bail!(
+            "creating {} on GitHub needs a token allowed to create repositories in {}; set GITHUB_TOKEN",
+            slug,
+            owner
+        );
+    };
+    let api = github_api();
+    let api = api.trim_end_matches('/');
+
+    let user: GithubUser = serde_json::from_str(&github_request(
+        &token,
+        "GET",
+        &format!("{}/user", api),
+        None,
+    )?)
+    .context("failed to parse the GitHub user")?;
+
+    let endpoint = if user.login.eq_ignore_ascii_case(owner) {
+        format!("{}/user/repos", api)
+    } else {
+        format!("{}/orgs/{}/repos", api, owner)
+    };
+    let body = serde_json::json!({
+        "name": name,
+        "private": true,
+        "description": "wald workspace",
+    });
+    let repo: GithubRepo = serde_json::from_str(
+        &github_request(&token, "POST", &endpoint, Some(&body.to_string()))
+            .with_context(|| format!("failed to create {} on GitHub", slug))?,
+    )
+    .context("failed to parse the created GitHub repository")?;
+
+    Ok(repo.ssh_url)
+}
+
+/// Open a pull request on the forge hosting `repo` and return its URL
+pub fn create_pull_request(repo: &RepoId, pr: &PullRequest) -> Result<String> {
+    if repo.host == "github.com" {
+        create_github_pull(repo, pr)
+    } else {
+        create_gitlab_merge_request(repo, pr)
+    }
+}
+
+fn create_github_pull(repo: &RepoId, pr: &PullRequest) -> Result<String> {
+    let Some(token) = find_token(GITHUB_TOKEN_ENV) else {
+        bail!(
+            "opening a pull request on {} needs a GitHub token; set GITHUB_TOKEN",
+            repo
+        );
+    };
+    let endpoint = format!(
+        "{}/repos/{}/pulls",
+        github_api().trim_end_matches('/'),
+        repo.path.join("/")
+    );
+    let body = serde_json::json!({
+        "head": pr.head,
+        "base": pr.base,
+        "title": pr.title,
+        "body": pr.body,
+        "draft": pr.draft,
+    });
+    let pull: GithubPull = serde_json::from_str(
+        &github_request(&token, "POST", &endpoint, Some(&body.to_string()))
+            .with_context(|| format!("failed to open a pull request on {}", repo))?,
+    )
+    .context("failed to parse the created pull request")?;
+
+    Ok(pull.html_url)
+}
+
+fn create_gitlab_merge_request(repo: &RepoId, pr: &PullRequest) -> Result<String> {
+    if !is_gitlab_host(&repo.host) {
+        bail!(
+            "{} isn't a known GitLab host; add it to {} to send it your GitLab token",
+            repo.host,
+            GITLAB_HOST_ENV
+        );
+    }
+    let Some(token) = find_token(GITLAB_TOKEN_ENV) else {
+        bail!(
+            "opening a merge request on {} needs a GitLab token; set GITLAB_TOKEN",
+            repo
+        );
+    };
+    let api = env::var(GITLAB_API_ENV).unwrap_or_else(|_| format!("https://{}/api/v4", repo.host));
+    let endpoint = format!(
+        "{}/projects/{}/merge_requests",
+        api.trim_end_matches('/'),
+        gitlab_project_id(repo)
+    );
+    // GitLab marks drafts by title
+    let title = if pr.draft {
+        format!("Draft: {}", pr.title)
+    } else {
+        pr.title.to_string()
+    };
+    let body = serde_json::json!({
+        "source_branch": pr.head,
+        "target_branch": pr.base,
+        "title": title,
+        "description": pr.body,
+    });
+    let headers = [format!("PRIVATE-TOKEN: {}", token)];
+    let request: GitlabMergeRequest = serde_json::from_str(
+        &api_request(&headers, "POST", &endpoint, Some(&body.to_string()))
+            .with_context(|| format!("failed to open a merge request on {}", repo))?,
+    )
+    .context("failed to parse the created merge request")?;
+
+    Ok(request.web_url)
+}
+
+/// Look `repo` up on its forge; `None` if it doesn't exist there, or is
+/// private and the token (if any) can't see it
+///
+/// Tokens are used when set but not required, so public repositories can
+/// be checked without one. The GitLab token is only sent to the hosts in
+/// `WALD_GITLAB_HOST`.
+pub fn lookup_repo(repo: &RepoId) -> Result<Option<ForgeRepo>> {
+    let path = repo.path.join("/");
+    let (headers, url) = if repo.host == "github.com" {
+        let mut headers = vec!["Accept: application/vnd.github+json".to_string()];
+        if let Some(token) = find_token(GITHUB_TOKEN_ENV) {
+            headers.push(format!("Authorization: Bearer {}", token));
+        }
+        let url = format!("{}/repos/{}", github_api().trim_end_matches('/'), path);
+        (headers, url)
+    } else {
+        let headers = is_gitlab_host(&repo.host)
+            .then(|| find_token(GITLAB_TOKEN_ENV))
+            .flatten()
+            .map(|token| format!("PRIVATE-TOKEN: {}", token))
+            .into_iter()
+            .collect();
+        let api =
+            env::var(GITLAB_API_ENV).unwrap_or_else(|_| format!("https://{}/api/v4", repo.host));
+        let url = format!(
+            "{}/projects/{}?statistics=true",
+            api.trim_end_matches('/'),
+            gitlab_project_id(repo)
+        );
+        (headers, url)
+    };
+
+    let body = match api_request_status(&headers, "GET", &url, None)? {
+        (404, _) => return Ok(None),
+        (_, Ok(body)) => body,
+        (_, Err(reason)) => bail!("GET {} failed: {}", url, reason),
+    };
+    let found = if repo.host == "github.com" {
+        let info: GithubRepoInfo =
+            serde_json::from_str(&body).context("failed to parse the GitHub repository")?;
+        ForgeRepo {
+            path: info.full_name,
+            archived: info.archived,
+            size: info.size.map(|kib| kib * 1024),
+        }
+    } else {
+        let project: GitlabProject =
+            serde_json::from_str(&body).context("failed to parse the GitLab project")?;
+        ForgeRepo {
+            path: project.path_with_namespace,
+            archived: project.archived,
+            size: project.statistics.map(|s| s.repository_size),
+        }
+    };
+    Ok(Some(found))
+}
+
+/// The first non-empty token among `vars`
+fn find_token(vars: &[&str]) -> Option<String> {
+    vars.iter()
+        .find_map(|var| env::var(var).ok().filter(|t| !t.is_empty()))
+}
+
+/// Whether `host` is trusted with the GitLab token
+fn is_gitlab_host(host: &str) -> bool {
+    gitlab_hosts(env::var(GITLAB_HOST_ENV).ok().as_deref())
+        .iter()
+        .any(|h| h.eq_ignore_ascii_case(host))
+}
+
+/// The hosts listed in a `WALD_GITLAB_HOST` value, or the default
+fn gitlab_hosts(setting: Option<&str>) -> Vec<String> {
+    let hosts: Vec<String> = setting
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|h| !h.is_empty())
+        .map(str::to_string)
+        .collect();
+    if hosts.is_empty() {
+        vec![GITLAB_HOST.to_string()]
+    } else {
+        hosts
+    }
+}
+
+fn github_api() -> String {
+    env::var(GITHUB_API_ENV).unwrap_or_else(|_| GITHUB_API.to_string())
+}
+
+/// A GitLab project's URL-encoded path, which its API accepts as an ID
+fn gitlab_project_id(repo: &RepoId) -> String {
+    repo.path.join("%2F")
+}
+
+/// Split `owner/name`
+fn parse_slug(slug: &str) -> Result<(&str, &str)> {
+    match slug.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+            Ok((owner, name.trim_end_matches(".git")))
+        }
+        _ => bail!("invalid GitHub repository '{}': use owner/name", slug),
+    }
+}
+
+fn github_request(token: &str, method: &str, url: &str, body: Option<&str>) -> Result<String> {
+    let headers = [
+        "Accept: application/vnd.github+json".to_string(),
+        format!("Authorization: Bearer {}", token),
+    ];
+    api_request(&headers, method, url, body)
+}
+
+fn api_request(headers: &[String], method: &str, url: &str, body: Option<&str>) -> Result<String> {
+    match api_request_status(headers, method, url, body)? {
+        (_, Ok(body)) => Ok(body),
+        (_, Err(reason)) => bail!("{} {} failed: {}", method, url, reason),
+    }
+}
+
+/// Send a request and return its HTTP status with the body, or with the
+/// reason it failed
+fn api_request_status(
+    headers: &[String],
+    method: &str,
+    url: &str,
+    body: Option<&str>,
+) -> Result<(u16, std::result::Result<String, String>)> {
+    let mut cmd = Command::new("curl");
+    cmd.arg("--silent")
+        .arg("--show-error")
+        .arg("--location")
+        .arg("--fail-with-body")
+        .arg("--write-out")
+        .arg("\n%{http_code}")
+        .arg("--request")
+        .arg(method);
+    for header in headers {
+        cmd.arg("--header").arg(header);
+    }
+    if let Some(body) = body {
+        cmd.arg("--header")
+            .arg("Content-Type: application/json")
+            .arg("--data")
+            .arg(body);
+    }
+    let output = cmd
+        .arg(url)
+        .timed_output()
+        .context("failed to execute curl (is it installed?)")?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (body, status) = split_status(&stdout);
+    if !output.status.success() {
+        let reason = api_error(body)
+            .unwrap_or_else(|| String::from_utf8_lossy(&output.stderr).trim().to_string());
+        return Ok((status, Err(reason)));
+    }
+    Ok((status, Ok(body.to_string())))
+}
+
+/// Split the status curl's `--write-out` appends off the body (0 if none)
+fn split_status(output: &str) -> (&str, u16) {
+    match output.trim_end().rsplit_once('\n') {
+        Some((body, code)) => (body, code.trim().parse().unwrap_or(0)),
+        None => ("", output.trim().parse().unwrap_or(0)),
+    }
+}
+
+/// The message of a GitHub or GitLab API error body, with the details it
+/// lists
+fn api_error(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let mut message = match value.get("message").or_else(|| value.get("error"))? {
+        serde_json::Value::String(message) => message.clone(),
+        // GitLab lists validation failures
+        serde_json::Value::Array(messages) => messages
+            .iter()
+            .filter_map(|m| m.as_str())
+            .collect::<Vec<_>>()
+            .join("; "),
+        _ => return None,
+    };
+    let details: Vec<&str> = value
+        .get("errors")
+        .and_then(|e| e.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+        .collect();
+    if !details.is_empty() {
+        message = format!("{} ({})", message, details.join("; "));
+    }
+    Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_slug() {
+        assert_eq!(parse_slug("me/ws").unwrap(), ("me", "ws"));
+        assert_eq!(parse_slug("org/ws.git").unwrap(), ("org", "ws"));
+        assert!(parse_slug("ws").is_err());
+        assert!(parse_slug("a/b/c").is_err());
+        assert!(parse_slug("/ws").is_err());
+    }
+
+    #[test]
+    fn test_api_error() {
+        let body = r#"{"message":"Repository creation failed.","errors":[{"resource":"Repository","code":"custom","field":"name","message":"name already exists on this account"}]}"#;
+        assert_eq!(
+            api_error(body).unwrap(),
+            "Repository creation failed. (name already exists on this account)"
+        );
+        assert_eq!(
+            api_error(r#"{"message":"Bad credentials"}"#).unwrap(),
+            "Bad credentials"
+        );
+        assert!(api_error("<html>").is_none());
+    }
+
+    #[test]
+    fn test_api_error_gitlab() {
+        assert_eq!(
+            api_error(r#"{"message":["Another open merge request already exists for this source branch: !4"]}"#)
+                .unwrap(),
+            "Another open merge request already exists for this source branch: !4"
+        );
+        assert_eq!(
+            api_error(r#"{"error":"invalid_token"}"#).unwrap(),
+            "invalid_token"
+        );
+    }
+
+    #[test]
+    fn test_split_status() {
+        assert_eq!(split_status("{\"a\":1}\n200"), ("{\"a\":1}", 200));
+        assert_eq!(split_status("{}\n\n404"), ("{}\n", 404));
+        assert_eq!(split_status("404"), ("", 404));
+        assert_eq!(split_status("{}\n201\n"), ("{}", 201));
+        assert_eq!(split_status(""), ("", 0));
+    }
+
+    #[test]
+    fn test_moved_from() {
+        let repo = RepoId::parse("github.com/old/name").unwrap();
+        let found = |path: &str| ForgeRepo {
+            path: path.to_string(),
+            archived: false,
+            size: None,
+        };
+        assert_eq!(found("old/name").moved_from(&repo), None);
+        assert_eq!(found("Old/Name").moved_from(&repo), None);
+        assert_eq!(found("new/name").moved_from(&repo), Some("new/name"));
+    }
+
+    #[test]
+    fn test_gitlab_hosts() {
+        assert_eq!(gitlab_hosts(None), vec!["gitlab.com"]);
+        assert_eq!(gitlab_hosts(Some("")), vec!["gitlab.com"]);
+        assert_eq!(
+            gitlab_hosts(Some("git.zib.de, gitlab.com")),
+            vec!["git.zib.de", "gitlab.com"]
+        );
+    }
+
+    #[test]
+    fn test_gitlab_project_id() {
+        let repo = RepoId::parse("git.zib.de/tools/wald").unwrap();
+        assert_eq!(gitlab_project_id(&repo), "tools%2Fwald");
+        let repo = RepoId::parse("gitlab.com/group/sub/project").unwrap();
+        assert_eq!(gitlab_project_id(&repo), "group%2Fsub%2Fproject");
+    }
+}
@@ -1,25 +1,59 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
-use std::process::Command;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, bail};
 use git2::{BranchType, Repository};
 
-use crate::types::RepoId;
+use super::credentials::helper_config_args;
+use super::runner;
+use crate::filesystem::{normalize_path, relative_path};
+use crate::timings::{self, TimedCommand};
+use crate::types::{RepoId, TagPolicy};
 
 /// Options for cloning a bare repo
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct CloneOptions {
     /// Shallow clone depth (None = full history)
     pub depth: Option<u32>,
     /// Partial clone filter (None = full clone)
     pub filter: Option<String>,
+    /// Clone from this URL instead of the one inferred from the repo ID
+    pub url: Option<String>,
+    /// Tags to fetch with the clone and on later fetches
+    pub tags: TagPolicy,
+    /// Bare repo to borrow objects from (git alternates); ignored if it
+    /// doesn't exist
+    pub reference: Option<PathBuf>,
+    /// Config set in the new repo before fetching (so e.g. `http.proxy`
+    /// already applies to the clone itself)
+    pub config: BTreeMap<String, String>,
+    /// Local clone of the repo to copy objects and branches from; the
+    /// branches are then brought up to date from the URL
+    pub seed: Option<PathBuf>,
+    /// Credential helper used instead of the ones configured for the user,
+    /// and kept in the new repo for later fetches
+    pub credential_helper: Option<String>,
+    /// Workspace the clone goes into; alternates between repos inside it
+    /// are written relative (see [`add_alternate`])
+    pub workspace_root: Option<PathBuf>,
 }
 
 /// Clone a repository as a bare repo
+///
+/// The clone is made next to `target` and moved into place once complete,
+/// so a clone that was cut off never passes for a cloned repo.
 pub fn clone_bare(repo_id: &RepoId, target: &Path, opts: CloneOptions) -> Result<()> {
     // Ensure parent directory exists
-    if let Some(parent) = target.parent() {
+    if let Some(parent) = target.parent()
+        && !parent.exists()
+        && !runner::simulated(|| format!("mkdir -p {}", parent.display()))
+    {
         fs::create_dir_all(parent)
             .with_context(|| format!("failed to create directory: {}", parent.display()))?;
     }
@@ -29,60 +63,512 @@ pub fn clone_bare(repo_id: &RepoId, target: &Path, opts: CloneOptions) -> Result
         bail!("bare repo already exists: {}", target.display());
     }
 
-    let url = repo_id.to_clone_url();
+    let url = opts.url.clone().unwrap_or_else(|| repo_id.to_clone_url());
+
+    // Leftover of an earlier clone that didn't finish
+    let mut partial = target.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    if partial.exists() && !runner::simulated(|| format!("rm -r {}", partial.display())) {
+        fs::remove_dir_all(&partial)
+            .with_context(|| format!("failed to remove {}", partial.display()))?;
+    }
 
     // Use git command for clone (libgit2 has limited shallow/partial clone support)
     let mut cmd = Command::new("git");
     cmd.arg("clone").arg("--bare").arg("--quiet");
 
-    if let Some(d) = opts.depth {
-        cmd.arg(format!("--depth={}", d));
+    // A local clone copies everything, so depth and filter don't apply
+    if opts.seed.is_none() {
+        if let Some(d) = opts.depth {
+            cmd.arg(format!("--depth={}", d));
+        }
+
+        if let Some(ref f) = opts.filter {
+            cmd.arg(format!("--filter={}", f));
+        }
+    }
+
+    // A clone fetches every tag unless told not to
+    if opts.tags != TagPolicy::All {
+        cmd.arg("--no-tags");
     }
 
-    if let Some(ref f) = opts.filter {
-        cmd.arg(format!("--filter={}", f));
+    if let Some(ref reference) = opts.reference {
+        cmd.arg("--reference-if-able").arg(reference);
     }
 
-    cmd.arg(&url).arg(target);
+    for (key, value) in &opts.config {
+        cmd.arg("-c").arg(format!("{}={}", key, value));
+    }
+    if let Some(helper) = &opts.credential_helper {
+        cmd.args(helper_config_args(helper));
+    }
+
+    match &opts.seed {
+        Some(seed) => cmd.arg(seed),
+        None => cmd.arg(&url),
+    };
+    cmd.arg(&partial);
 
     let output = cmd
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to execute git clone for {}", repo_id))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("git clone failed for {}: {}", repo_id, stderr);
+        match super::policy_hint(&stderr) {
+            Some(hint) => bail!(
+                "git clone failed for {}: {}\n{}",
+                repo_id,
+                stderr.trim(),
+                hint
+            ),
+            None => bail!("git clone failed for {}: {}", repo_id, stderr),
+        }
+    }
+
+    apply_tag_policy(&partial, &opts.tags)?;
+    if opts.seed.is_some() {
+        // The seed's branches may be stale or hold unpushed work: make them
+        // the remote's, fetching only what the seed didn't have
+        set_config_value(&partial, &[], "remote.origin.url", &url)?;
+        let mut args = vec!["--prune", "origin", "+refs/heads/*:refs/heads/*"];
+        if opts.tags == TagPolicy::All {
+            args.insert(0, "--tags");
+        }
+        run_fetch(&partial, &args, &FetchWatch::default())?;
+        set_head_from_origin(&partial);
+    } else if opts.tags == TagPolicy::Reachable {
+        // `--no-tags` skipped them all; fetching the branches again follows
+        // the tags that point into them
+        run_fetch(
+            &partial,
+            &["origin", "+refs/heads/*:refs/heads/*"],
+            &FetchWatch::default(),
+        )?;
+    }
+
+    if runner::simulated(|| format!("mv {} {}", partial.display(), target.display())) {
+        return Ok(());
+    }
+    fs::rename(&partial, target).with_context(|| {
+        format!(
+            "failed to move {} to {}",
+            partial.display(),
+            target.display()
+        )
+    })?;
+
+    // `--reference-if-able` wrote an absolute path, if it borrowed at all
+    if let Some(reference) = &opts.reference
+        && !alternates(target).is_empty()
+    {
+        add_alternate(target, reference, opts.workspace_root.as_deref())?;
     }
 
     Ok(())
 }
 
-/// Open an existing bare repository
-pub fn open_bare(path: &Path) -> Result<Repository> {
-    Repository::open_bare(path)
-        .with_context(|| format!("failed to open bare repo: {}", path.display()))
+/// Point `HEAD` at the remote's default branch, if the remote says which
+/// one that is
+fn set_head_from_origin(path: &Path) {
+    let Ok(output) = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("ls-remote")
+        .arg("--symref")
+        .arg("origin")
+        .arg("HEAD")
+        .timed_output()
+    else {
+        return;
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let Some(target) = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("ref: ")?.strip_suffix("\tHEAD"))
+    else {
+        return;
+    };
+    let _ = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("symbolic-ref")
+        .arg("HEAD")
+        .arg(target)
+        .timed_output();
 }
 
-/// Fetch updates in a bare repository
-pub fn fetch_bare(path: &Path) -> Result<()> {
+/// Make fetches from `origin` follow a tag policy (`remote.origin.tagOpt`)
+pub fn apply_tag_policy(path: &Path, tags: &TagPolicy) -> Result<()> {
+    match tags.as_tag_opt() {
+        Some(opt) => set_config_value(path, &[], "remote.origin.tagOpt", opt),
+        None => {
+            // Unsetting a key that isn't set fails, which is fine
+            let _ = Command::new("git")
+                .arg("-C")
+                .arg(path)
+                .arg("config")
+                .arg("--unset")
+                .arg("remote.origin.tagOpt")
+                .timed_output();
+            Ok(())
+        }
+    }
+}
+
+/// Set config values in a repository's local config
+pub fn apply_config(path: &Path, config: &BTreeMap<String, String>) -> Result<()> {
+    for (key, value) in config {
+        set_config_value(path, &[], key, value)?;
+    }
+    Ok(())
+}
+
+/// Make a repository get its credentials from `helper` alone
+pub fn set_credential_helper(path: &Path, helper: &str) -> Result<()> {
+    set_config_value(path, &["--replace-all"], "credential.helper", "")?;
+    set_config_value(path, &["--add"], "credential.helper", helper)
+}
+
+/// Set config values in a worktree's own config (`config.worktree`)
+///
+/// Enables `extensions.worktreeConfig` in the bare repo, so the values apply
+/// to this worktree only rather than to every worktree of the repo.
+pub fn apply_worktree_config(
+    bare_repo: &Path,
+    worktree_path: &Path,
+    config: &BTreeMap<String, String>,
+) -> Result<()> {
+    if config.is_empty() {
+        return Ok(());
+    }
+
+    enable_worktree_config(bare_repo)?;
+    for (key, value) in config {
+        set_config_value(worktree_path, &["--worktree"], key, value)?;
+    }
+    Ok(())
+}
+
+/// Make a worktree push its branch to `<remote>/<remote_branch>`
+///
+/// Sets `branch.<local_branch>.pushRemote` and, in the worktree's own
+/// config, a `remote.<remote>.push` refspec mapping the local branch to
+/// `remote_branch`. Pulling still follows the branch's upstream, which may
+/// be on a different remote (e.g. pull from `upstream`, push to `origin`).
+pub fn configure_push_to(
+    bare_repo: &Path,
+    worktree_path: &Path,
+    local_branch: &str,
+    remote: &str,
+    remote_branch: &str,
+) -> Result<()> {
+    set_config_value(
+        bare_repo,
+        &[],
+        &format!("branch.{}.pushRemote", local_branch),
+        remote,
+    )?;
+
+    let refspec = BTreeMap::from([(
+        format!("remote.{}.push", remote),
+        format!("refs/heads/{}:refs/heads/{}", local_branch, remote_branch),
+    )]);
+    apply_worktree_config(bare_repo, worktree_path, &refspec)
+}
+
+/// Read a config value as git resolves it in `path` (all scopes)
+pub fn get_config(path: &Path, key: &str) -> Result<Option<String>> {
     let output = Command::new("git")
         .arg("-C")
         .arg(path)
-        .arg("fetch")
-        .arg("--all")
-        .arg("--prune")
-        .arg("--quiet")
-        .output()
-        .with_context(|| format!("failed to execute git fetch in {}", path.display()))?;
+        .arg("config")
+        .arg("--get")
+        .arg(key)
+        .timed_output()
+        .with_context(|| format!("failed to execute git config in {}", path.display()))?;
+
+    // Exit code 1 means the key is unset
+    match output.status.code() {
+        Some(0) => Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        )),
+        Some(1) => Ok(None),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!(
+                "git config --get {} failed in {}: {}",
+                key,
+                path.display(),
+                stderr.trim()
+            );
+        }
+    }
+}
+
+/// Turn on `extensions.worktreeConfig` for a bare repo
+///
+/// With the extension enabled, `core.bare` in the shared config would apply
+/// to every linked worktree and make git treat them as bare. As
+/// git-worktree(1) requires, it moves to the bare repo's own
+/// `config.worktree` first.
+fn enable_worktree_config(bare_repo: &Path) -> Result<()> {
+    if local_config_value(bare_repo, "extensions.worktreeConfig")?.as_deref() == Some("true") {
+        return Ok(());
+    }
+
+    let core_bare = local_config_value(bare_repo, "core.bare")?;
+
+    set_config_value(bare_repo, &[], "extensions.worktreeConfig", "true")?;
+
+    if let Some(value) = core_bare {
+        set_config_value(bare_repo, &["--worktree"], "core.bare", &value)?;
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(bare_repo)
+            .arg("config")
+            .arg("--local")
+            .arg("--unset")
+            .arg("core.bare")
+            .timed_output()
+            .with_context(|| format!("failed to execute git config in {}", bare_repo.display()))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!(
+                "failed to move core.bare to config.worktree in {}: {}",
+                bare_repo.display(),
+                stderr.trim()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a value from a repo's shared config file only (`--local`)
+fn local_config_value(path: &Path, key: &str) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("config")
+        .arg("--local")
+        .arg("--get")
+        .arg(key)
+        .timed_output()
+        .with_context(|| format!("failed to execute git config in {}", path.display()))?;
+
+    if output.status.success() {
+        Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ))
+    } else {
+        Ok(None)
+    }
+}
+
+fn set_config_value(path: &Path, scope: &[&str], key: &str, value: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("config")
+        .args(scope)
+        .arg(key)
+        .arg(value)
+        .timed_output()
+        .with_context(|| format!("failed to execute git config in {}", path.display()))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        bail!("git fetch failed in {}: {}", path.display(), stderr);
+        bail!(
+            "git config {} failed in {}: {}",
+            key,
+            path.display(),
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Open an existing bare repository
+pub fn open_bare(path: &Path) -> Result<Repository> {
+    Repository::open_bare(path)
+        .with_context(|| format!("failed to open bare repo: {}", path.display()))
+}
+
+/// Fetch updates in a bare repository
+///
+/// Tags follow the repo's tag policy unless `all_tags` asks for every tag.
+pub fn fetch_bare(path: &Path, all_tags: bool, watch: &FetchWatch) -> Result<()> {
+    let mut args = vec!["--all", "--prune"];
+    if all_tags {
+        args.push("--tags");
+    }
+    run_fetch(path, &args, watch)
+}
+
+/// Live progress and a time limit for a fetch that may take minutes
+#[derive(Clone, Copy, Default)]
+pub struct FetchWatch<'a> {
+    /// Gets each progress line git prints, e.g. `Receiving objects:  45%
+    /// (450/1000), 12.00 MiB | 3.00 MiB/s`
+    pub progress: Option<&'a dyn Fn(&str)>,
+    /// Stop the fetch once it has run this long
+    pub timeout: Option<Duration>,
+}
+
+impl FetchWatch<'_> {
+    fn is_active(&self) -> bool {
+        self.progress.is_some() || self.timeout.is_some()
+    }
+}
+
+/// How long a fetch stopped at its timeout gets to clean up before it's
+/// killed
+const STOP_GRACE: Duration = Duration::from_secs(5);
+
+fn run_fetch(path: &Path, args: &[&str], watch: &FetchWatch) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(path).arg("fetch").args(args);
+    let (success, stderr) = if watch.is_active() {
+        run_watched(&mut cmd, watch)
+            .with_context(|| format!("git fetch in {} didn't finish", path.display()))?
+    } else {
+        let output = cmd
+            .arg("--quiet")
+            .timed_output()
+            .with_context(|| format!("failed to execute git fetch in {}", path.display()))?;
+        (
+            output.status.success(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    };
+
+    if !success {
+        match super::policy_hint(&stderr) {
+            Some(hint) => bail!(
+                "git fetch failed in {}: {}\n{}",
+                path.display(),
+                stderr.trim(),
+                hint
+            ),
+            None => bail!("git fetch failed in {}: {}", path.display(), stderr),
+        }
     }
 
     Ok(())
 }
 
+/// Run a git command that can report progress, passing its progress lines
+/// to `watch.progress` as they arrive and stopping it at `watch.timeout`
+///
+/// Returns whether it succeeded and the rest of its error output.
+fn run_watched(cmd: &mut Command, watch: &FetchWatch) -> Result<(bool, String)> {
+    if runner::simulated(|| runner::command_line(cmd)) {
+        return Ok((true, String::new()));
+    }
+
+    let started = Instant::now();
+    let mut child = runner::isolate(cmd)
+        .arg("--progress")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to execute git")?;
+    let stderr = child.stderr.take().context("no error output from git")?;
+
+    // Progress updates end in \r, everything else in \n; the server's
+    // lines are padded with spaces
+    let (lines, received) = mpsc::channel();
+    thread::spawn(move || {
+        let mut line = Vec::new();
+        let mut bytes = BufReader::new(stderr).bytes().map_while(|b| b.ok());
+        loop {
+            let byte = bytes.next();
+            if let Some(b) = byte.filter(|b| *b != b'\r' && *b != b'\n') {
+                line.push(b);
+                continue;
+            }
+            let text = String::from_utf8_lossy(&line).trim_end().to_string();
+            if !text.is_empty() && lines.send(text).is_err() {
+                break;
+            }
+            line.clear();
+            if byte.is_none() {
+                break;
+            }
+        }
+    });
+
+    let mut errors = Vec::new();
+    let mut stopped = false;
+    loop {
+        match received.recv_timeout(Duration::from_millis(100)) {
+            Ok(line) if is_progress_line(&line) => {
+                if let Some(progress) = watch.progress {
+                    progress(&line);
+                }
+            }
+            Ok(line) => errors.push(line),
+            Err(RecvTimeoutError::Disconnected) => break,
+            // Helpers git started may keep the pipe open after it's gone
+            Err(RecvTimeoutError::Timeout) if stopped && child.try_wait()?.is_some() => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        if let Some(limit) = watch.timeout
+            && !stopped
+            && started.elapsed() >= limit
+        {
+            stop(&mut child);
+            stopped = true;
+        }
+    }
+
+    let status = child.wait().context("failed to wait for git")?;
+    timings::record("git fetch", started.elapsed());
+    if stopped {
+        bail!(
+            "stopped after {}s (--timeout)",
+            watch.timeout.unwrap_or_default().as_secs()
+        );
+    }
+    Ok((status.success(), errors.join("\n")))
+}
+
+/// Whether git printed `line` to report progress (counting, receiving,
+/// resolving), rather than a result or an error
+fn is_progress_line(line: &str) -> bool {
+    let line = line.strip_prefix("remote: ").unwrap_or(line);
+    ["objects: ", "deltas: "]
+        .iter()
+        .any(|step| line.contains(step))
+}
+
+/// Stop a running git: ask it to finish first, so it removes its lock
+/// files and partly received packs, and kill it if it doesn't
+fn stop(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        let _ = Command::new("kill")
+            .arg("-TERM")
+            .arg(child.id().to_string())
+            .stderr(Stdio::null())
+            .status();
+        let deadline = Instant::now() + STOP_GRACE;
+        while Instant::now() < deadline {
+            if !matches!(child.try_wait(), Ok(None)) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+    let _ = child.kill();
+}
+
 /// Check if a bare repository is a partial clone
 pub fn is_partial_clone(path: &Path) -> Result<bool> {
     let output = Command::new("git")
@@ -91,7 +577,7 @@ pub fn is_partial_clone(path: &Path) -> Result<bool> {
         .arg("config")
         .arg("--get")
         .arg("remote.origin.promisor")
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to check partial clone status: {}", path.display()))?;
 
     // If config exists and is "true", it's a partial clone
@@ -104,7 +590,7 @@ pub fn is_partial_clone(path: &Path) -> Result<bool> {
 }
 
 /// Convert a partial clone to a full clone and fetch all objects
-pub fn fetch_full(path: &Path) -> Result<()> {
+pub fn fetch_full(path: &Path, all_tags: bool, watch: &FetchWatch) -> Result<()> {
     // Remove partial clone configuration
     // These may fail if not set, which is fine
     let _ = Command::new("git")
@@ -113,7 +599,7 @@ pub fn fetch_full(path: &Path) -> Result<()> {
         .arg("config")
         .arg("--unset")
         .arg("remote.origin.promisor")
-        .output();
+        .timed_output();
 
     let _ = Command::new("git")
         .arg("-C")
@@ -121,21 +607,33 @@ pub fn fetch_full(path: &Path) -> Result<()> {
         .arg("config")
         .arg("--unset")
         .arg("remote.origin.partialclonefilter")
-        .output();
+        .timed_output();
 
     // Fetch all objects (--refetch ensures we get everything)
-    let output = Command::new("git")
-        .arg("-C")
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
         .arg(path)
         .arg("fetch")
         .arg("--all")
         .arg("--prune")
-        .arg("--refetch")
-        .output()
-        .with_context(|| format!("failed to fetch full in {}", path.display()))?;
+        .arg("--refetch");
+    if all_tags {
+        cmd.arg("--tags");
+    }
+    let (success, stderr) = if watch.is_active() {
+        run_watched(&mut cmd, watch)
+            .with_context(|| format!("git fetch in {} didn't finish", path.display()))?
+    } else {
+        let output = cmd
+            .timed_output()
+            .with_context(|| format!("failed to fetch full in {}", path.display()))?;
+        (
+            output.status.success(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    };
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    if !success {
         bail!(
             "git fetch --refetch failed in {}: {}",
             path.display(),
@@ -147,16 +645,23 @@ pub fn fetch_full(path: &Path) -> Result<()> {
 }
 
 /// Run garbage collection on a bare repository
-pub fn gc(path: &Path, aggressive: bool) -> Result<()> {
+///
+/// `prune` is passed on as `--prune=<when>` (e.g. `now`, `2.weeks.ago`, or
+/// `never` for repos other repos borrow objects from); git's default
+/// expiry applies if None.
+pub fn gc(path: &Path, aggressive: bool, prune: Option<&str>) -> Result<()> {
     let mut cmd = Command::new("git");
     cmd.arg("-C").arg(path).arg("gc");
 
     if aggressive {
         cmd.arg("--aggressive");
     }
+    if let Some(prune) = prune {
+        cmd.arg(format!("--prune={}", prune));
+    }
 
     let output = cmd
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to run git gc in {}", path.display()))?;
 
     if !output.status.success() {
@@ -167,6 +672,196 @@ pub fn gc(path: &Path, aggressive: bool) -> Result<()> {
     Ok(())
 }
 
+/// Total size in bytes of the files under a directory
+pub fn disk_usage(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Operations left unfinished in a bare repo's worktrees
+///
+/// Returns (worktree path, operation) for each worktree in the middle of a
+/// rebase, merge, cherry-pick, revert or bisect.
+pub fn unfinished_operations(path: &Path) -> Vec<(PathBuf, &'static str)> {
+    const MARKERS: [(&str, &str); 6] = [
+        ("rebase-merge", "rebase"),
+        ("rebase-apply", "rebase"),
+        ("MERGE_HEAD", "merge"),
+        ("CHERRY_PICK_HEAD", "cherry-pick"),
+        ("REVERT_HEAD", "revert"),
+        ("BISECT_LOG", "bisect"),
+    ];
+
+    let Ok(entries) = fs::read_dir(path.join("worktrees")) else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    for admin_dir in entries.flatten().map(|e| e.path()) {
+        let Some((_, operation)) = MARKERS
+            .iter()
+            .find(|(marker, _)| admin_dir.join(marker).exists())
+        else {
+            continue;
+        };
+        // `gitdir` holds the path of the worktree's `.git` file
+        let worktree = fs::read_to_string(admin_dir.join("gitdir"))
+            .ok()
+            .and_then(|gitdir| Path::new(gitdir.trim()).parent().map(Path::to_path_buf))
+            .unwrap_or(admin_dir);
+        found.push((worktree, *operation));
+    }
+    found.sort();
+    found
+}
+
+/// Object stores a bare repository borrows from (its git alternates)
+///
+/// Relative entries are resolved against the repo's `objects` directory.
+pub fn alternates(path: &Path) -> Vec<PathBuf> {
+    let objects = path.join("objects");
+    let Ok(content) = fs::read_to_string(objects.join("info").join("alternates")) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| normalize_path(&objects.join(line)))
+        .collect()
+}
+
+/// How an alternates file in `own_objects` names the object store
+/// `objects`: relative when both are inside `root`, so moving or copying
+/// the workspace keeps it valid, and absolute otherwise
+fn alternate_entry(own_objects: &Path, objects: &Path, root: Option<&Path>) -> String {
+    match root {
+        Some(root) if own_objects.starts_with(root) && objects.starts_with(root) => {
+            relative_path(own_objects, objects).display().to_string()
+        }
+        _ => objects.display().to_string(),
+    }
+}
+
+/// Let a bare repository borrow objects from another one
+///
+/// The alternate is relative when both repos are inside the workspace at
+/// `root`. The lender is set to keep its unreachable objects
+/// (`gc.pruneExpire=never`): the borrower may still use them, and the
+/// `git gc --auto` of a later fetch would otherwise drop them.
+///
+/// Objects already stored in both stay until the next `git gc`, which
+/// drops the local copies.
+pub fn add_alternate(path: &Path, reference: &Path, root: Option<&Path>) -> Result<()> {
+    let reference_objects = reference
+        .join("objects")
+        .canonicalize()
+        .with_context(|| format!("no object store in {}", reference.display()))?;
+    let objects = path
+        .join("objects")
+        .canonicalize()
+        .with_context(|| format!("no object store in {}", path.display()))?;
+    let root = root.map(|root| root.canonicalize().unwrap_or_else(|_| root.to_path_buf()));
+    let entry = alternate_entry(&objects, &reference_objects, root.as_deref());
+
+    let info = objects.join("info");
+    fs::create_dir_all(&info)
+        .with_context(|| format!("failed to create directory: {}", info.display()))?;
+    let file = info.join("alternates");
+    let content = fs::read_to_string(&file).unwrap_or_default();
+    // Replaces any other spelling of the same store, like the absolute one
+    // `git clone --reference` writes
+    let mut lines: Vec<&str> = content
+        .lines()
+        .filter(|line| {
+            objects
+                .join(line.trim())
+                .canonicalize()
+                .map_or(true, |alt| alt != reference_objects)
+        })
+        .collect();
+    lines.push(&entry);
+    let updated = lines.join("\n") + "\n";
+    if updated != content {
+        fs::write(&file, updated).with_context(|| format!("failed to write {}", file.display()))?;
+    }
+
+    set_config_value(reference, &[], "gc.pruneExpire", "never")
+}
+
+/// Point alternates under `from` at the same place under `to`, after the
+/// object stores they name were moved; returns how many were rewritten
+///
+/// Relative alternates stay relative as long as both repos are inside the
+/// workspace at `root`.
+pub fn relocate_alternates(path: &Path, from: &Path, to: &Path, root: &Path) -> Result<usize> {
+    let objects = path.join("objects");
+    let file = objects.join("info").join("alternates");
+    let Ok(content) = fs::read_to_string(&file) else {
+        return Ok(0);
+    };
+    let mut rewritten = 0;
+    let lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            let alt = Path::new(line.trim());
+            match normalize_path(&objects.join(alt)).strip_prefix(from) {
+                Ok(rest) => {
+                    rewritten += 1;
+                    let moved = to.join(rest);
+                    if alt.is_relative() {
+                        alternate_entry(&objects, &moved, Some(root))
+                    } else {
+                        moved.display().to_string()
+                    }
+                }
+                Err(_) => line.to_string(),
+            }
+        })
+        .collect();
+    if rewritten > 0 {
+        fs::write(&file, lines.join("\n") + "\n")
+            .with_context(|| format!("failed to write {}", file.display()))?;
+    }
+    Ok(rewritten)
+}
+
+/// Keep the relative alternates of a bare repository moved from `old`
+/// pointing at the same object stores; returns how many were rewritten
+pub fn rebase_alternates(path: &Path, old: &Path, root: &Path) -> Result<usize> {
+    let objects = path.join("objects");
+    let file = objects.join("info").join("alternates");
+    let Ok(content) = fs::read_to_string(&file) else {
+        return Ok(0);
+    };
+    let old_objects = old.join("objects");
+    let mut rewritten = 0;
+    let lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            let alt = Path::new(line.trim());
+            if line.trim().is_empty() || line.starts_with('#') || alt.is_absolute() {
+                return line.to_string();
+            }
+            rewritten += 1;
+            alternate_entry(
+                &objects,
+                &normalize_path(&old_objects.join(alt)),
+                Some(root),
+            )
+        })
+        .collect();
+    if rewritten > 0 {
+        fs::write(&file, lines.join("\n") + "\n")
+            .with_context(|| format!("failed to write {}", file.display()))?;
+    }
+    Ok(rewritten)
+}
+
 /// List branches in a bare repository
 pub fn list_branches(path: &Path) -> Result<Vec<String>> {
     let repo = open_bare(path)?;
@@ -195,6 +890,179 @@ pub fn list_branches(path: &Path) -> Result<Vec<String>> {
     Ok(branches)
 }
 
+/// How far a local branch is from its upstream, as of the last fetch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchDrift {
+    pub name: String,
+    pub ahead: u32,
+    pub behind: u32,
+}
+
+/// Compare each local branch that has an upstream with the last fetched
+/// state of that upstream (no network access)
+pub fn branch_drift(path: &Path) -> Result<Vec<BranchDrift>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("for-each-ref")
+        .arg("--format=%(refname:short)%09%(upstream)%09%(upstream:track,nobracket)")
+        .arg("refs/heads")
+        .timed_output()
+        .with_context(|| format!("failed to list branches in {}", path.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git for-each-ref failed in {}: {}", path.display(), stderr);
+    }
+
+    Ok(parse_branch_drift(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Commits of a bare repo's branches and remote-tracking branches by ref
+/// name, leaving out wald's own `wald/*` branches
+pub fn branch_commits(path: &Path) -> Result<BTreeMap<String, String>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("for-each-ref")
+        .arg("--format=%(refname)%09%(objectname)")
+        .arg("refs/heads")
+        .arg("refs/remotes")
+        .timed_output()
+        .with_context(|| format!("failed to list branches in {}", path.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git for-each-ref failed in {}: {}", path.display(), stderr);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter(|(name, _)| !name.starts_with("refs/heads/wald/"))
+        .map(|(name, commit)| (name.to_string(), commit.to_string()))
+        .collect())
+}
+
+/// The commit a revision (e.g. `origin/main`) points at, if it resolves
+pub fn resolve_commit(path: &Path, rev: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("rev-parse")
+        .arg("--verify")
+        .arg("--quiet")
+        .arg(format!("{}^{{commit}}", rev))
+        .timed_output()
+        .ok()?;
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !commit.is_empty()).then_some(commit)
+}
+
+/// How many commits `to` has that `from` doesn't (`git rev-list --count
+/// from..to`), if both resolve
+pub fn count_commits_between(path: &Path, from: &str, to: &str) -> Option<u32> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("rev-list")
+        .arg("--count")
+        .arg(format!("{}..{}", from, to))
+        .timed_output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+fn parse_branch_drift(output: &str) -> Vec<BranchDrift> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = fields.next()?;
+            let upstream = fields.next()?;
+            let track = fields.next().unwrap_or_default();
+            // No upstream, or it was deleted on the remote ("gone")
+            if upstream.is_empty() || track == "gone" {
+                return None;
+            }
+            let mut drift = BranchDrift {
+                name: name.to_string(),
+                ahead: 0,
+                behind: 0,
+            };
+            // e.g. "ahead 1, behind 2"
+            for part in track.split(", ") {
+                match part.split_once(' ') {
+                    Some(("ahead", n)) => drift.ahead = n.parse().unwrap_or(0),
+                    Some(("behind", n)) => drift.behind = n.parse().unwrap_or(0),
+                    _ => {}
+                }
+            }
+            Some(drift)
+        })
+        .collect()
+}
+
+/// When a bare repo was last fetched (Unix seconds), if ever
+pub fn last_fetch(path: &Path) -> Option<u64> {
+    fs::metadata(path.join("FETCH_HEAD"))
+        .and_then(|m| m.modified())
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+/// A tag in a bare repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagInfo {
+    pub name: String,
+    /// Commit the tag points to (peeled for annotated tags)
+    pub commit: String,
+    /// Tagger date, or the commit date for lightweight tags (Unix seconds)
+    pub date: u64,
+}
+
+/// List tags in a bare repository, newest first
+pub fn list_tags(path: &Path) -> Result<Vec<TagInfo>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("for-each-ref")
+        .arg("--sort=-creatordate")
+        .arg("--format=%(refname:strip=2)%09%(objectname)%09%(*objectname)%09%(creatordate:unix)")
+        .arg("refs/tags")
+        .timed_output()
+        .with_context(|| format!("failed to list tags in {}", path.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git for-each-ref failed in {}: {}", path.display(), stderr);
+    }
+
+    let tags = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = fields.next()?;
+            let object = fields.next()?;
+            let peeled = fields.next()?;
+            let date = fields.next()?.parse().unwrap_or(0);
+            let commit = if peeled.is_empty() { object } else { peeled };
+            Some(TagInfo {
+                name: name.to_string(),
+                commit: commit.to_string(),
+                date,
+            })
+        })
+        .collect();
+
+    Ok(tags)
+}
+
 /// Check if a branch exists in a bare repository
 pub fn has_branch(path: &Path, branch: &str) -> Result<bool> {
     let repo = open_bare(path)?;
@@ -241,6 +1109,118 @@ pub fn get_default_branch(path: &Path) -> Result<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_worktree_config_keeps_worktrees_usable() {
+        use tempfile::TempDir;
+
+        let dir = TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+            assert!(status.status.success(), "git {:?} failed", args);
+            String::from_utf8_lossy(&status.stdout).trim().to_string()
+        };
+
+        git(&["init", "--quiet", "src"]);
+        git(&[
+            "-C",
+            "src",
+            "-c",
+            "user.email=t@t",
+            "-c",
+            "user.name=t",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "init",
+        ]);
+        git(&["clone", "--quiet", "--bare", "src", "repo.git"]);
+        git(&[
+            "-C", "repo.git", "worktree", "add", "--quiet", "../wt", "HEAD",
+        ]);
+
+        let bare = dir.path().join("repo.git");
+        let wt = dir.path().join("wt");
+        let config = BTreeMap::from([("user.email".to_string(), "w@x".to_string())]);
+        apply_worktree_config(&bare, &wt, &config).unwrap();
+        // Applying again must be harmless
+        apply_worktree_config(&bare, &wt, &config).unwrap();
+
+        // The worktree is still a work tree and sees its own value
+        git(&["-C", "wt", "status", "--short"]);
+        assert_eq!(git(&["-C", "wt", "config", "--get", "user.email"]), "w@x");
+        // The bare repo is still bare
+        assert_eq!(
+            git(&["-C", "repo.git", "rev-parse", "--is-bare-repository"]),
+            "true"
+        );
+    }
+
+    #[test]
+    fn test_parse_branch_drift() {
+        let output = "main\trefs/remotes/origin/main\tbehind 3\n\
+                      dev\trefs/remotes/origin/dev\tahead 1, behind 2\n\
+                      wip\t\t\n\
+                      old\trefs/remotes/origin/old\tgone\n\
+                      fix\trefs/remotes/origin/fix\t\n";
+        let drift = parse_branch_drift(output);
+        assert_eq!(drift.len(), 3);
+        assert_eq!((drift[0].ahead, drift[0].behind), (0, 3));
+        assert_eq!((drift[1].ahead, drift[1].behind), (1, 2));
+        assert_eq!(drift[2].name, "fix");
+        assert_eq!((drift[2].ahead, drift[2].behind), (0, 0));
+    }
+
+    #[test]
+    fn test_relocate_alternates() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let info = dir.path().join("objects/info");
+        fs::create_dir_all(&info).unwrap();
+        fs::write(
+            info.join("alternates"),
+            "/old/ws/.wald/repos/github.com/a/b.git/objects\n../../other.git/objects\n/elsewhere/objects\n",
+        )
+        .unwrap();
+
+        let n = relocate_alternates(
+            dir.path(),
+            Path::new("/old/ws"),
+            Path::new("/new/ws"),
+            Path::new("/new/ws"),
+        )
+        .unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(
+            fs::read_to_string(info.join("alternates")).unwrap(),
+            "/new/ws/.wald/repos/github.com/a/b.git/objects\n../../other.git/objects\n/elsewhere/objects\n"
+        );
+    }
+
+    #[test]
+    fn test_rebase_alternates() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = dir.path().join("mirrors/fork.git");
+        let info = repo.join("objects/info");
+        fs::create_dir_all(&info).unwrap();
+        fs::write(
+            info.join("alternates"),
+            "../../../test/repo.git/objects\n/elsewhere/objects\n",
+        )
+        .unwrap();
+
+        let old = dir.path().join(".wald/repos/github.com/fork/repo.git");
+        let n = rebase_alternates(&repo, &old, dir.path()).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(
+            fs::read_to_string(info.join("alternates")).unwrap(),
+            "../../../.wald/repos/github.com/test/repo.git/objects\n/elsewhere/objects\n"
+        );
+    }
+
     // Note: These tests require network access and are marked as ignored
     // Run with: cargo test -- --ignored
 
@@ -255,7 +1235,7 @@ mod tests {
         let repo_id = RepoId::parse("github.com/octocat/Hello-World").unwrap();
         let opts = CloneOptions {
             depth: Some(1),
-            filter: None,
+            ..Default::default()
         };
         clone_bare(&repo_id, &target, opts).unwrap();
 
@@ -0,0 +1,298 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use git2::{BranchType, Repository};
+
+use crate::timings::TimedCommand;
+
+/// A regular (non-bare) git checkout, as found from a directory inside it
+#[derive(Debug)]
+pub struct CheckoutInfo {
+    /// Top-level directory of the working tree
+    pub root: PathBuf,
+    /// `remote.origin.url`, if set
+    pub origin_url: Option<String>,
+    /// Checked-out branch (None for a detached HEAD)
+    pub branch: Option<String>,
+    /// Whether this is a linked worktree rather than a standalone clone
+    pub is_linked_worktree: bool,
+}
+
+/// Inspect the checkout containing `path`
+pub fn inspect_checkout(path: &Path) -> Result<CheckoutInfo> {
+    let repo = Repository::discover(path)
+        .with_context(|| format!("not inside a git checkout: {}", path.display()))?;
+
+    let Some(root) = repo.workdir() else {
+        bail!(
+            "not inside a git checkout (bare repository): {}",
+            path.display()
+        );
+    };
+
+    let origin_url = repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|r| r.url().map(|u| u.to_string()));
+
+    let branch = match repo.head() {
+        Ok(head) if head.is_branch() => head.shorthand().map(|s| s.to_string()),
+        _ => None,
+    };
+
+    Ok(CheckoutInfo {
+        // Normalizes away the trailing slash libgit2 reports
+        root: root.components().collect(),
+        origin_url,
+        branch,
+        is_linked_worktree: repo.is_worktree(),
+    })
+}
+
+/// `remote.origin.url` of the repository at `path` (bare or not)
+pub fn origin_url(path: &Path) -> Result<Option<String>> {
+    let repo = Repository::open(path)
+        .with_context(|| format!("not a git repository: {}", path.display()))?;
+    Ok(repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|r| r.url().map(|u| u.to_string())))
+}
+
+/// Local branches of a checkout with their tip commits
+pub fn local_branch_tips(checkout: &Path) -> Result<Vec<(String, String)>> {
+    let repo = Repository::open(checkout)
+        .with_context(|| format!("failed to open repository: {}", checkout.display()))?;
+
+    let mut tips = Vec::new();
+    for branch in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+        if let (Some(name), Some(oid)) = (branch.name()?, branch.get().target()) {
+            tips.push((name.to_string(), oid.to_string()));
+        }
+    }
+    Ok(tips)
+}
+
+/// Whether a checkout has stashed changes
+pub fn has_stash(checkout: &Path) -> Result<bool> {
+    let repo = Repository::open(checkout)
+        .with_context(|| format!("failed to open repository: {}", checkout.display()))?;
+    Ok(repo.find_reference("refs/stash").is_ok())
+}
+
+/// Whether a repository contains a commit
+pub fn has_commit(repo: &Path, commit: &str) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("cat-file")
+        .arg("-e")
+        .arg(format!("{}^{{commit}}", commit))
+        .timed_output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Fetch a branch from a local checkout into a bare repo under a new name
+pub fn fetch_branch_from(
+    bare_repo: &Path,
+    source: &Path,
+    branch: &str,
+    local_branch: &str,
+) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(bare_repo)
+        .arg("fetch")
+        .arg("--quiet")
+        .arg(source)
+        .arg(format!(
+            "+refs/heads/{}:refs/heads/{}",
+            branch, local_branch
+        ))
+        .timed_output()
+        .with_context(|| format!("failed to execute git fetch in {}", bare_repo.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to fetch {} from {}: {}",
+            branch,
+            source.display(),
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Point a branch at its upstream; non-fatal since the remote branch may
+/// not exist (yet)
+pub fn set_branch_upstream(bare_repo: &Path, branch: &str, upstream: &str) {
+    let _ = Command::new("git")
+        .arg("-C")
+        .arg(bare_repo)
+        .arg("branch")
+        .arg("--set-upstream-to")
+        .arg(upstream)
+        .arg(branch)
+        .timed_output();
+}
+
+/// Register a worktree for a branch without checking out any files
+///
+/// The directory is populated afterwards (e.g. with an adopted checkout's
+/// files), then `reset_index` makes git's index match HEAD.
+pub fn add_worktree_no_checkout(
+    bare_repo: &Path,
+    worktree_path: &Path,
+    branch: &str,
+) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(bare_repo)
+        .arg("worktree")
+        .arg("add")
+        .arg("--no-checkout")
+        .arg(worktree_path)
+        .arg(branch)
+        .timed_output()
+        .with_context(|| format!("failed to add worktree at {}", worktree_path.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to add worktree for branch {}: {}",
+            branch,
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Rebuild a worktree's index from HEAD without touching its files
+pub fn reset_index(worktree_path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(worktree_path)
+        .arg("reset")
+        .arg("--quiet")
+        .timed_output()
+        .with_context(|| format!("failed to execute git reset in {}", worktree_path.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to reset index in {}: {}",
+            worktree_path.display(),
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Fast-forward a worktree's branch to its upstream
+///
+/// Fails, leaving the branch alone, if it has diverged from the upstream.
+pub fn fast_forward(worktree_path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(worktree_path)
+        .arg("merge")
+        .arg("--ff-only")
+        .arg("--quiet")
+        .arg("@{upstream}")
+        .timed_output()
+        .with_context(|| format!("failed to execute git merge in {}", worktree_path.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to fast-forward {}: {}",
+            worktree_path.display(),
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Rebase a worktree's branch onto its upstream
+///
+/// A rebase that stops on a conflict is aborted, so the worktree is left as
+/// it was.
+pub fn rebase_onto_upstream(worktree_path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(worktree_path)
+        .arg("rebase")
+        .arg("--quiet")
+        .arg("@{upstream}")
+        .timed_output()
+        .with_context(|| {
+            format!(
+                "failed to execute git rebase in {}",
+                worktree_path.display()
+            )
+        })?;
+
+    if !output.status.success() {
+        let _ = Command::new("git")
+            .arg("-C")
+            .arg(worktree_path)
+            .arg("rebase")
+            .arg("--abort")
+            .timed_output();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let reason = if stderr.trim().is_empty() {
+            stdout
+        } else {
+            stderr
+        };
+        bail!(
+            "failed to rebase {} (aborted): {}",
+            worktree_path.display(),
+            reason.trim()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_inspect_checkout() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.remote("origin", "git@github.com:user/repo.git")
+            .unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+
+        let info = inspect_checkout(&dir.path().join("sub")).unwrap();
+        assert_eq!(
+            info.root.canonicalize().unwrap(),
+            dir.path().canonicalize().unwrap()
+        );
+        assert_eq!(
+            info.origin_url.as_deref(),
+            Some("git@github.com:user/repo.git")
+        );
+        assert!(!info.is_linked_worktree);
+        assert!(!has_stash(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn test_inspect_outside_checkout_fails() {
+        let dir = TempDir::new().unwrap();
+        assert!(inspect_checkout(dir.path()).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+//! Credential helpers for hosts that need a token (Overleaf, private HTTPS)
+//!
+//! The token stays in the user's secrets store: the helper set in the bare
+//! repo only names where to read it from, each time git asks.
+
+use std::ffi::OsStr;
+
+use crate::types::{HostCredentials, SecretSource};
+
+use super::runner::quote;
+
+/// The `credential.helper` value that answers git with a host's token
+pub fn credential_helper(creds: &HostCredentials) -> String {
+    let read = match &creds.source {
+        SecretSource::Helper(helper) => return helper.clone(),
+        SecretSource::Pass(entry) => format!("pass show {} | head -n 1", quote(OsStr::new(entry))),
+        SecretSource::Op(reference) => format!("op read {}", quote(OsStr::new(reference))),
+    };
+    let username = creds.username.as_deref().unwrap_or("git");
+    // git runs the helper with `get`, `store` or `erase`; only `get` answers
+    format!(
+        "!f() {{ test \"$1\" = get || return 0; echo username={}; echo \"password=$({})\"; }}; f",
+        quote(OsStr::new(username)),
+        read
+    )
+}
+
+/// `-c` options making a git command use `helper` alone: the empty value
+/// drops the helpers of the user's config
+pub(super) fn helper_config_args(helper: &str) -> [String; 4] {
+    [
+        "-c".to_string(),
+        "credential.helper=".to_string(),
+        "-c".to_string(),
+        format!("credential.helper={}", helper),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credential_helper() {
+        let pass = HostCredentials {
+            source: SecretSource::Pass("overleaf/token".to_string()),
+            username: None,
+        };
+        assert_eq!(
+            credential_helper(&pass),
+            "!f() { test \"$1\" = get || return 0; echo username=git; echo \"password=$(pass show overleaf/token | head -n 1)\"; }; f"
+        );
+
+        let op = HostCredentials {
+            source: SecretSource::Op("op://Work/Git Lab/token".to_string()),
+            username: Some("me".to_string()),
+        };
+        assert!(credential_helper(&op).contains("echo username=me;"));
+        assert!(credential_helper(&op).contains("$(op read 'op://Work/Git Lab/token')"));
+
+        let helper = HostCredentials {
+            source: SecretSource::Helper("osxkeychain".to_string()),
+            username: None,
+        };
+        assert_eq!(credential_helper(&helper), "osxkeychain");
+    }
+}
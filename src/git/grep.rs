@@ -0,0 +1,100 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use crate::timings::TimedCommand;
+
+/// A line found by `git grep`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepMatch {
+    /// File path relative to the repository root
+    pub path: String,
+    pub line: u32,
+    pub text: String,
+}
+
+/// Search the tracked files of a worktree, uncommitted changes included
+pub fn grep_worktree(worktree_path: &Path, pattern: &str) -> Result<Vec<GrepMatch>> {
+    run_grep(worktree_path, pattern, None)
+}
+
+/// Search the files of a revision (e.g. `origin/main`) in a bare repo
+pub fn grep_revision(bare_repo: &Path, revision: &str, pattern: &str) -> Result<Vec<GrepMatch>> {
+    run_grep(bare_repo, pattern, Some(revision))
+}
+
+fn run_grep(repo: &Path, pattern: &str, revision: Option<&str>) -> Result<Vec<GrepMatch>> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(repo)
+        .arg("grep")
+        .arg("--line-number")
+        .arg("-I")
+        .arg("--null")
+        .arg("-e")
+        .arg(pattern);
+    if let Some(revision) = revision {
+        cmd.arg(revision).arg("--");
+    }
+    let output = cmd
+        .timed_output()
+        .with_context(|| format!("failed to run git grep in {}", repo.display()))?;
+
+    // Exit code 1 means nothing matched
+    match output.status.code() {
+        Some(0) => {}
+        Some(1) if output.stderr.is_empty() => return Ok(Vec::new()),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("git grep failed in {}: {}", repo.display(), stderr.trim());
+        }
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(parse_grep(&stdout, revision))
+}
+
+/// Parse `path\0line\0text` lines; searches of a revision prefix each path
+/// with `<revision>:`
+fn parse_grep(output: &str, revision: Option<&str>) -> Vec<GrepMatch> {
+    let prefix = revision.map(|r| format!("{}:", r));
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\0');
+            let path = fields.next()?;
+            let line = fields.next()?.parse().ok()?;
+            let text = fields.next().unwrap_or_default();
+            let path = match &prefix {
+                Some(prefix) => path.strip_prefix(prefix.as_str()).unwrap_or(path),
+                None => path,
+            };
+            Some(GrepMatch {
+                path: path.to_string(),
+                line,
+                text: text.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_grep() {
+        let output = "src/main.rs\x0012\x00fn main() {\nREADME.md\x003\x00a:b\x00c\n";
+        let matches = parse_grep(output, None);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].path, "src/main.rs");
+        assert_eq!(matches[0].line, 12);
+        assert_eq!(matches[0].text, "fn main() {");
+        // Only the first two NULs separate fields
+        assert_eq!(matches[1].text, "a:b\x00c");
+
+        let matches = parse_grep("origin/main:lib.rs\x001\x00todo\n", Some("origin/main"));
+        assert_eq!(matches[0].path, "lib.rs");
+    }
+}
@@ -3,6 +3,8 @@ use std::process::Command;
 
 use anyhow::{Context, Result};
 
+use crate::timings::TimedCommand;
+
 /// A detected move from git history
 #[derive(Debug, Clone)]
 pub struct MoveEntry {
@@ -28,7 +30,7 @@ pub fn detect_moves(
         .arg("--first-parent")
         .arg("--diff-filter=R")
         .arg(format!("{}..{}", from_commit, to_commit))
-        .output()
+        .timed_output()
         .with_context(|| "failed to run git diff for move detection")?;
 
     if !output.status.success() {
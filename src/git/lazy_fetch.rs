@@ -0,0 +1,59 @@
+//! Keeping checks on partial clones off the network
+//!
+//! A blobless clone fetches missing objects on demand (a "lazy fetch"),
+//! which can turn a check before removing a worktree into a network call
+//! that hangs or prompts for credentials. Destructive paths run their git
+//! commands with lazy fetching disabled and report what would have needed
+//! the network instead.
+
+use std::process::Command;
+
+/// Disables lazy fetching (git 2.44+; older versions ignore it)
+const NO_LAZY_FETCH_ENV: &str = "GIT_NO_LAZY_FETCH";
+
+/// Protocols git may use; allowing none makes a lazy fetch fail at once on
+/// older git too
+const ALLOW_PROTOCOL_ENV: &str = "GIT_ALLOW_PROTOCOL";
+
+/// What git says when it needs an object the partial clone doesn't have
+const MISSING_OBJECT_ERRORS: &[&str] = &[
+    "lazy fetching disabled",
+    "missing blob object",
+    "missing tree object",
+    "unable to read",
+    "could not fetch",
+];
+
+/// Run a git command without fetching missing objects
+pub trait NoLazyFetch {
+    fn no_lazy_fetch(&mut self) -> &mut Self;
+}
+
+impl NoLazyFetch for Command {
+    fn no_lazy_fetch(&mut self) -> &mut Self {
+        self.env(NO_LAZY_FETCH_ENV, "1")
+            .env(ALLOW_PROTOCOL_ENV, "none")
+    }
+}
+
+/// Whether git failed for want of an object it wasn't allowed to fetch
+pub fn needs_missing_object(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    MISSING_OBJECT_ERRORS.iter().any(|e| stderr.contains(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_missing_object() {
+        assert!(needs_missing_object(
+            "error: lazy fetching disabled; some objects may not be available\nfatal: unable to read 3f2a"
+        ));
+        assert!(needs_missing_object("fatal: missing blob object 'abc123'"));
+        assert!(!needs_missing_object(
+            "fatal: '_main.wt' contains modified or untracked files, use --force to delete it"
+        ));
+    }
+}
@@ -1,16 +1,45 @@
 pub mod bare;
+pub mod checkout;
+mod credentials;
+mod grep;
 pub mod history;
+mod lazy_fetch;
+mod remote;
+mod retry;
+pub mod runner;
 pub mod shell;
+mod status;
 mod worktree;
 
 pub use bare::{
-    CloneOptions, clone_bare, fetch_bare, fetch_full, gc, is_partial_clone, list_branches,
-    open_bare,
+    BranchDrift, CloneOptions, FetchWatch, TagInfo, add_alternate, alternates, apply_config,
+    apply_tag_policy, apply_worktree_config, branch_commits, branch_drift, clone_bare,
+    configure_push_to, count_commits_between, disk_usage, fetch_bare, fetch_full, gc, get_config,
+    is_partial_clone, last_fetch, list_branches, list_tags, open_bare, rebase_alternates,
+    relocate_alternates, resolve_commit, set_credential_helper, unfinished_operations,
 };
+
+pub use checkout::{
+    CheckoutInfo, add_worktree_no_checkout, fast_forward, fetch_branch_from, has_commit, has_stash,
+    inspect_checkout, local_branch_tips, origin_url, rebase_onto_upstream, reset_index,
+    set_branch_upstream,
+};
+pub use credentials::credential_helper;
+pub use grep::{GrepMatch, grep_revision, grep_worktree};
 pub use history::detect_moves;
-pub use shell::worktree_move;
+pub use remote::{
+    FetchCapabilities, RemoteRefs, add_remote, fetch_remote, has_remote, has_remote_branch,
+    list_remote_refs, policy_hint, probe_fetch_capabilities,
+};
+pub use retry::with_network_retry;
+pub use shell::{commit_file, current_branch, git_path, has_upstream, worktree_move};
+pub use status::{WorkingStatus, working_status, working_status_offline};
 pub use worktree::{
-    BranchMode, add_worktree, add_worktree_with_tracking, add_worktree_with_tracking_mode,
-    check_branch_exists, delete_branch, has_unpushed_commits, list_wald_branches, list_worktrees,
-    remove_worktree,
+    BranchMode, BrokenLink, WorktreeInfo, add_worktree, add_worktree_for_existing_branch,
+    add_worktree_with_tracking, add_worktree_with_tracking_mode, admin_dir, check_branch_exists,
+    check_worktree_link, checkout_registered_worktree, delete_branch, find_worktree,
+    has_unpushed_commits, last_activity, linked_branch, list_wald_branches, list_worktrees,
+    lock_worktree, match_worktree, oldest_unpushed_commit, prepare_direct_branch,
+    prepare_tracking_branch, prepare_tracking_branch_from, remove_worktree, rename_branch,
+    repair_worktree, reset_branch, switch_branch, unlock_worktree, unpushed_subjects,
 };
@@ -0,0 +1,278 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use super::bare::CloneOptions;
+use super::credentials::helper_config_args;
+use crate::timings::TimedCommand;
+
+/// Whether a repository has a remote with this name
+pub fn has_remote(repo: &Path, name: &str) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("remote")
+        .arg("get-url")
+        .arg(name)
+        .timed_output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Add a remote fetching all its branches into `refs/remotes/<name>/`
+pub fn add_remote(repo: &Path, name: &str, url: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("remote")
+        .arg("add")
+        .arg(name)
+        .arg(url)
+        .timed_output()
+        .with_context(|| format!("failed to execute git remote add in {}", repo.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("failed to add remote {}: {}", name, stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// Fetch a remote's branches
+pub fn fetch_remote(repo: &Path, name: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("fetch")
+        .arg("--quiet")
+        .arg(name)
+        .timed_output()
+        .with_context(|| format!("failed to execute git fetch in {}", repo.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("failed to fetch {}: {}", name, stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// Whether `refs/remotes/<remote>/<branch>` exists
+pub fn has_remote_branch(repo: &Path, remote: &str, branch: &str) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("rev-parse")
+        .arg("--verify")
+        .arg("--quiet")
+        .arg(format!("refs/remotes/{}/{}", remote, branch))
+        .timed_output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// What a server supports when fetching, as advertised to `git ls-remote`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchCapabilities {
+    /// Shallow clones and fetches (`--depth`)
+    pub shallow: bool,
+    /// Partial clones (`--filter`)
+    pub filter: bool,
+}
+
+/// Ask the server behind `url` what it supports when fetching
+///
+/// Returns `None` if the server can't be reached or doesn't say (e.g. an
+/// empty repository over protocol v0).
+pub fn probe_fetch_capabilities(url: &str, opts: &CloneOptions) -> Option<FetchCapabilities> {
+    let output = ls_remote(opts)
+        .arg(url)
+        .env("GIT_TRACE_PACKET", "1")
+        .timed_output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_capabilities(&String::from_utf8_lossy(&output.stderr))
+}
+
+/// The refs a remote advertises
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemoteRefs {
+    pub branches: usize,
+    pub tags: usize,
+    /// Branch `HEAD` points to
+    pub default_branch: Option<String>,
+}
+
+/// List the branches and tags of the remote at `url`
+pub fn list_remote_refs(url: &str, opts: &CloneOptions) -> Result<RemoteRefs> {
+    let output = ls_remote(opts)
+        .arg("--symref")
+        .arg(url)
+        .timed_output()
+        .with_context(|| format!("failed to list the refs of {}", url))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("failed to list the refs of {}: {}", url, stderr.trim());
+    }
+    Ok(parse_remote_refs(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// `git ls-remote` with the clone's config and credential helper
+fn ls_remote(opts: &CloneOptions) -> Command {
+    let mut cmd = Command::new("git");
+    for (key, value) in &opts.config {
+        cmd.arg("-c").arg(format!("{}={}", key, value));
+    }
+    if let Some(helper) = &opts.credential_helper {
+        cmd.args(helper_config_args(helper));
+    }
+    cmd.arg("ls-remote");
+    cmd
+}
+
+/// Count the refs in `git ls-remote --symref` output; peeled tags
+/// (`v1^{}`) are the same tags again
+fn parse_remote_refs(stdout: &str) -> RemoteRefs {
+    let mut refs = RemoteRefs::default();
+    for line in stdout.lines() {
+        if let Some(target) = line.strip_prefix("ref: ") {
+            if let Some(branch) = target
+                .strip_suffix("\tHEAD")
+                .and_then(|t| t.strip_prefix("refs/heads/"))
+            {
+                refs.default_branch = Some(branch.to_string());
+            }
+            continue;
+        }
+        let Some((_, name)) = line.split_once('\t') else {
+            continue;
+        };
+        if name.starts_with("refs/heads/") {
+            refs.branches += 1;
+        } else if name.starts_with("refs/tags/") && !name.ends_with("^{}") {
+            refs.tags += 1;
+        }
+    }
+    refs
+}
+
+/// Read the capabilities from a `GIT_TRACE_PACKET` trace
+///
+/// Protocol v2 lists them in a `fetch=...` line; v0 appends them to the
+/// first ref, after a NUL.
+fn parse_capabilities(trace: &str) -> Option<FetchCapabilities> {
+    for line in trace.lines() {
+        let Some((_, packet)) = line.split_once("packet:") else {
+            continue;
+        };
+        let Some((_, payload)) = packet.split_once("< ") else {
+            continue;
+        };
+        let features = if payload == "fetch" {
+            ""
+        } else if let Some(features) = payload.strip_prefix("fetch=") {
+            features
+        } else if let Some((_, features)) = payload.split_once("\\0") {
+            features
+        } else {
+            continue;
+        };
+        let has = |name: &str| features.split_whitespace().any(|f| f == name);
+        return Some(FetchCapabilities {
+            shallow: has("shallow"),
+            filter: has("filter"),
+        });
+    }
+    None
+}
+
+/// Git errors caused by a depth or filter the server can't serve
+const POLICY_ERRORS: &[(&str, &str)] = &[
+    ("does not support shallow", "depth"),
+    ("filtering not recognized by server", "filter"),
+    ("filtering capability not negotiated", "filter"),
+    ("expected 'packfile'", "depth or filter"),
+];
+
+/// What to change when a clone or fetch failed because of the repo's
+/// depth or filter policy
+pub fn policy_hint(stderr: &str) -> Option<String> {
+    let stderr = stderr.to_lowercase();
+    let (_, policy) = POLICY_ERRORS.iter().find(|(e, _)| stderr.contains(e))?;
+    let fix = match *policy {
+        "depth" => "`depth: full`",
+        "filter" => "`filter: none`",
+        _ => "`depth: full` or `filter: none`",
+    };
+    Some(format!(
+        "the server doesn't support this repo's {} policy; set {} for it in .wald/manifest.yaml",
+        policy, fix
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_capabilities() {
+        let v2 = "\
+12:00:00.1 pkt-line.c:80  packet:  upload-pack> version 2
+12:00:00.1 pkt-line.c:80  packet:    ls-remote< version 2
+12:00:00.1 pkt-line.c:80  packet:    ls-remote< fetch=shallow wait-for-done filter
+";
+        assert_eq!(
+            parse_capabilities(v2),
+            Some(FetchCapabilities {
+                shallow: true,
+                filter: true
+            })
+        );
+
+        let v0 =
+            "12:00:00.1 pkt-line.c:80  packet:    git< 1234abcd HEAD\\0multi_ack shallow no-done\n";
+        assert_eq!(
+            parse_capabilities(v0),
+            Some(FetchCapabilities {
+                shallow: true,
+                filter: false
+            })
+        );
+
+        assert_eq!(parse_capabilities("packet:    ls-remote< 0000\n"), None);
+    }
+
+    #[test]
+    fn test_parse_remote_refs() {
+        let stdout = "\
+ref: refs/heads/main\tHEAD
+1111111111111111111111111111111111111111\tHEAD
+1111111111111111111111111111111111111111\trefs/heads/main
+2222222222222222222222222222222222222222\trefs/heads/dev
+3333333333333333333333333333333333333333\trefs/tags/v1
+1111111111111111111111111111111111111111\trefs/tags/v1^{}
+4444444444444444444444444444444444444444\trefs/pull/1/head
+";
+        assert_eq!(
+            parse_remote_refs(stdout),
+            RemoteRefs {
+                branches: 2,
+                tags: 1,
+                default_branch: Some("main".to_string()),
+            }
+        );
+        assert_eq!(parse_remote_refs(""), RemoteRefs::default());
+    }
+
+    #[test]
+    fn test_policy_hint() {
+        let hint = policy_hint("warning: filtering not recognized by server, ignoring").unwrap();
+        assert!(hint.contains("`filter: none`"));
+        assert!(policy_hint("fatal: Server does not support shallow clients").is_some());
+        assert!(policy_hint("fatal: repository not found").is_none());
+    }
+}
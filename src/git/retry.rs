@@ -0,0 +1,147 @@
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+
+/// Tries of a clone or fetch before giving up
+const NETWORK_ATTEMPTS: u32 = 4;
+
+/// Wait before the first retry; doubles after each failed try
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Git error output that points at a passing network problem
+///
+/// Anything else (unknown repo, authentication, bad refspec) fails the same
+/// way on every try, so it isn't retried.
+const TRANSIENT_ERRORS: &[&str] = &[
+    "could not resolve host",
+    "failed to connect",
+    "connection timed out",
+    "connection refused",
+    "connection reset",
+    "operation timed out",
+    "early eof",
+    "unexpected disconnect",
+    "the remote end hung up unexpectedly",
+    "rpc failed",
+    "gnutls_handshake",
+    "ssl_read",
+    "ssl_connect",
+    "tls connection",
+    "the requested url returned error: 5",
+    "temporary failure",
+    "network is unreachable",
+];
+
+/// Whether a failed clone or fetch is worth retrying
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    let message = format!("{:#}", err).to_lowercase();
+    // A server refusing the depth or filter may hang up, but does so every time
+    if super::policy_hint(&message).is_some() {
+        return false;
+    }
+    TRANSIENT_ERRORS.iter().any(|e| message.contains(e))
+}
+
+/// Run a network operation, retrying transient failures with exponential
+/// backoff
+///
+/// `on_retry` is called with the error and the wait before each retry.
+pub fn with_network_retry<T>(
+    op: impl FnMut() -> Result<T>,
+    on_retry: impl FnMut(&anyhow::Error, Duration),
+) -> Result<T> {
+    retry_with_backoff(NETWORK_ATTEMPTS, INITIAL_BACKOFF, op, on_retry)
+}
+
+fn retry_with_backoff<T>(
+    attempts: u32,
+    initial_backoff: Duration,
+    mut op: impl FnMut() -> Result<T>,
+    mut on_retry: impl FnMut(&anyhow::Error, Duration),
+) -> Result<T> {
+    let mut backoff = initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Err(e) if attempt < attempts && is_transient(&e) => {
+                on_retry(&e, backoff);
+                thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(&anyhow!(
+            "git clone failed for x: fatal: unable to access 'https://x/': Could not resolve host: x"
+        )));
+        assert!(is_transient(&anyhow!("fetch-pack: unexpected disconnect")));
+        assert!(!is_transient(&anyhow!(
+            "fatal: repository 'https://x/' not found"
+        )));
+        assert!(!is_transient(&anyhow!("Authentication failed")));
+        assert!(!is_transient(&anyhow!(
+            "fatal: expected 'packfile'\nfatal: the remote end hung up unexpectedly"
+        )));
+    }
+
+    #[test]
+    fn test_retry_transient_only() {
+        let mut tries = 0;
+        let mut waits = Vec::new();
+        let result = retry_with_backoff(
+            4,
+            Duration::from_millis(1),
+            || {
+                tries += 1;
+                if tries < 3 {
+                    Err(anyhow!("early EOF"))
+                } else {
+                    Ok(tries)
+                }
+            },
+            |_, wait| waits.push(wait),
+        );
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(
+            waits,
+            vec![Duration::from_millis(1), Duration::from_millis(2)]
+        );
+
+        let mut tries = 0;
+        let result: Result<()> = retry_with_backoff(
+            4,
+            Duration::from_millis(1),
+            || {
+                tries += 1;
+                Err(anyhow!("repository not found"))
+            },
+            |_, _| {},
+        );
+        assert!(result.is_err());
+        assert_eq!(tries, 1);
+
+        let mut tries = 0;
+        let result: Result<()> = retry_with_backoff(
+            3,
+            Duration::ZERO,
+            || {
+                tries += 1;
+                Err(anyhow!("Connection reset by peer"))
+            },
+            |_, _| {},
+        );
+        assert!(result.is_err());
+        assert_eq!(tries, 3);
+    }
+}
@@ -0,0 +1,425 @@
+//! The backend that runs wald's git subprocesses
+//!
+//! Every `git` command in [`crate::git`] (and the commands that shell out
+//! directly) runs through the active [`GitRunner`]. By default that's the
+//! real git binary; tests can swap in a [`ScriptedRunner`] with canned
+//! answers, and `--simulate` a [`SimulateRunner`] that only runs queries.
+//! Calls through libgit2 are not affected.
+//!
+//! Before they run, git commands lose the variables that would point them
+//! at another repository ([`isolate`]), so wald works the same when called
+//! with `GIT_DIR` exported, as in CI steps, git hooks or direnv setups.
+
+use std::cell::RefCell;
+use std::ffi::OsStr;
+use std::io;
+use std::process::{Command, ExitStatus, Output};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+/// Runs git commands
+pub trait GitRunner: Send + Sync {
+    /// Run `cmd` to completion, capturing its output
+    fn output(&self, cmd: &mut Command) -> io::Result<Output>;
+
+    /// Run `cmd` with inherited stdio
+    fn status(&self, cmd: &mut Command) -> io::Result<ExitStatus> {
+        self.output(cmd).map(|o| o.status)
+    }
+}
+
+/// The git binary on PATH
+pub struct SystemRunner;
+
+impl GitRunner for SystemRunner {
+    fn output(&self, cmd: &mut Command) -> io::Result<Output> {
+        cmd.output()
+    }
+
+    fn status(&self, cmd: &mut Command) -> io::Result<ExitStatus> {
+        cmd.status()
+    }
+}
+
+static RUNNER: RwLock<Option<Arc<dyn GitRunner>>> = RwLock::new(None);
+static SIMULATION: OnceLock<Arc<SimulateRunner>> = OnceLock::new();
+
+thread_local! {
+    static LOCAL_RUNNER: RefCell<Option<Arc<dyn GitRunner>>> = const { RefCell::new(None) };
+}
+
+/// Use `runner` for every git command from now on, in all threads
+pub fn set_runner(runner: Arc<dyn GitRunner>) {
+    *RUNNER.write().unwrap_or_else(|e| e.into_inner()) = Some(runner);
+}
+
+/// Run `f` with `runner` answering the git commands of this thread
+///
+/// Meant for tests, which run in parallel threads of one process.
+pub fn with_runner<T>(runner: Arc<dyn GitRunner>, f: impl FnOnce() -> T) -> T {
+    let previous = LOCAL_RUNNER.with(|r| r.replace(Some(runner)));
+    // Restore even if `f` panics, so a failed test doesn't leak its runner
+    struct Restore(Option<Arc<dyn GitRunner>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            LOCAL_RUNNER.with(|r| *r.borrow_mut() = previous);
+        }
+    }
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Simulate from now on: git queries still run, every other git command
+/// and file write wald makes through [`simulated`] is only recorded
+pub fn simulate() -> Arc<SimulateRunner> {
+    let runner = SIMULATION
+        .get_or_init(|| Arc::new(SimulateRunner::new()))
+        .clone();
+    set_runner(runner.clone());
+    runner
+}
+
+/// When simulating, record `action` and return true: the caller skips it
+pub fn simulated(action: impl FnOnce() -> String) -> bool {
+    match SIMULATION.get() {
+        Some(runner) => {
+            runner.skip(action());
+            true
+        }
+        None => false,
+    }
+}
+
+fn active_runner() -> Option<Arc<dyn GitRunner>> {
+    LOCAL_RUNNER
+        .with(|r| r.borrow().clone())
+        .or_else(|| RUNNER.read().unwrap_or_else(|e| e.into_inner()).clone())
+}
+
+fn is_git(cmd: &Command) -> bool {
+    cmd.get_program() == "git"
+}
+
+/// Variables that make git use a repository other than the one a command
+/// names (`git rev-parse --local-env-vars`, less the config ones, which
+/// should still apply)
+const REPO_ENV: &[&str] = &[
+    "GIT_DIR",
+    "GIT_WORK_TREE",
+    "GIT_IMPLICIT_WORK_TREE",
+    "GIT_COMMON_DIR",
+    "GIT_INDEX_FILE",
+    "GIT_OBJECT_DIRECTORY",
+    "GIT_ALTERNATE_OBJECT_DIRECTORIES",
+    "GIT_GRAFT_FILE",
+    "GIT_SHALLOW_FILE",
+    "GIT_NO_REPLACE_OBJECTS",
+    "GIT_REPLACE_REF_BASE",
+    "GIT_PREFIX",
+    "GIT_INTERNAL_SUPER_PREFIX",
+];
+
+/// Keep the repository variables of wald's own environment from `cmd`
+pub fn isolate(cmd: &mut Command) -> &mut Command {
+    for var in REPO_ENV {
+        cmd.env_remove(var);
+    }
+    cmd
+}
+
+/// Run `cmd` through the active runner if it's a git command
+pub fn output(cmd: &mut Command) -> io::Result<Output> {
+    if !is_git(cmd) {
+        return cmd.output();
+    }
+    match active_runner() {
+        Some(runner) => runner.output(isolate(cmd)),
+        None => isolate(cmd).output(),
+    }
+}
+
+/// Like [`output`], with inherited stdio
+pub fn status(cmd: &mut Command) -> io::Result<ExitStatus> {
+    if !is_git(cmd) {
+        return cmd.status();
+    }
+    match active_runner() {
+        Some(runner) => runner.status(isolate(cmd)),
+        None => isolate(cmd).status(),
+    }
+}
+
+/// A command line as it would be typed, e.g. `git -C /repo fetch --all`
+pub fn command_line(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(super) fn quote(arg: &OsStr) -> String {
+    let arg = arg.to_string_lossy();
+    if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "'\"$`\\*?;&|<>()".contains(c))
+    {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Arguments after git's global options (`-C <dir>`, `-c <k=v>`, ...)
+fn git_args(cmd: &Command) -> Vec<String> {
+    let mut args = cmd.get_args().map(|a| a.to_string_lossy().to_string());
+    let mut rest = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-C" | "-c" => {
+                args.next();
+            }
+            a if a.starts_with('-') => {}
+            _ => {
+                rest.push(arg);
+                rest.extend(args);
+                break;
+            }
+        }
+    }
+    rest
+}
+
+/// An exit status with the given code
+fn exit_status(code: i32) -> ExitStatus {
+    #[cfg(unix)]
+    {
+        std::os::unix::process::ExitStatusExt::from_raw(code << 8)
+    }
+    #[cfg(windows)]
+    {
+        std::os::windows::process::ExitStatusExt::from_raw(code as u32)
+    }
+}
+
+fn fake_output(code: i32, stdout: &str, stderr: &str) -> Output {
+    Output {
+        status: exit_status(code),
+        stdout: stdout.as_bytes().to_vec(),
+        stderr: stderr.as_bytes().to_vec(),
+    }
+}
+
+/// Git commands that only read, so `--simulate` runs them for real
+const QUERIES: &[&str] = &[
+    "cat-file",
+    "check-ignore",
+    "count-objects",
+    "describe",
+    "diff",
+    "for-each-ref",
+    "log",
+    "ls-files",
+    "ls-remote",
+    "merge-base",
+    "rev-list",
+    "rev-parse",
+    "show",
+    "show-ref",
+    "status",
+    "var",
+];
+
+/// Whether a git command (arguments after the global options) only reads
+fn is_query(args: &[String]) -> bool {
+    let Some((sub, rest)) = args.split_first() else {
+        return true;
+    };
+    let has = |flags: &[&str]| rest.iter().any(|a| flags.contains(&a.as_str()));
+    match sub.as_str() {
+        s if QUERIES.contains(&s) => true,
+        "config" => has(&["--get", "--get-all", "--get-regexp", "--list", "-l"]),
+        "branch" => has(&["--list", "-l", "--show-current", "--contains", "--merged"]),
+        "worktree" | "remote" | "stash" => {
+            matches!(
+                rest.first().map(String::as_str),
+                Some("list" | "get-url" | "show")
+            ) || (sub != "stash" && rest.is_empty())
+        }
+        _ => false,
+    }
+}
+
+/// Runs queries and records everything else instead of running it
+///
+/// Skipped commands "succeed" with empty output.
+#[derive(Default)]
+pub struct SimulateRunner {
+    skipped: Mutex<Vec<String>>,
+}
+
+impl SimulateRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The commands that would have changed something, in order
+    pub fn skipped(&self) -> Vec<String> {
+        self.skipped
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn skip(&self, action: String) {
+        self.skipped
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(action);
+    }
+}
+
+impl GitRunner for SimulateRunner {
+    fn output(&self, cmd: &mut Command) -> io::Result<Output> {
+        if is_query(&git_args(cmd)) {
+            return cmd.output();
+        }
+        self.skip(command_line(cmd));
+        Ok(fake_output(0, "", ""))
+    }
+}
+
+/// Answers git commands from a script, for tests
+///
+/// Each rule matches commands whose arguments (after the global options)
+/// start with the given ones; the first match wins. Unmatched commands
+/// fail with exit code 128.
+#[derive(Default)]
+pub struct ScriptedRunner {
+    rules: Vec<(Vec<String>, Output)>,
+    calls: Mutex<Vec<Vec<String>>>,
+}
+
+impl ScriptedRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer commands starting with `args` with `stdout` and exit code 0
+    pub fn respond(self, args: &[&str], stdout: &str) -> Self {
+        self.respond_with(args, 0, stdout, "")
+    }
+
+    /// Answer commands starting with `args` with the given exit code and
+    /// output
+    pub fn respond_with(mut self, args: &[&str], code: i32, stdout: &str, stderr: &str) -> Self {
+        let args = args.iter().map(|a| a.to_string()).collect();
+        self.rules.push((args, fake_output(code, stdout, stderr)));
+        self
+    }
+
+    /// The commands run so far (arguments after the global options)
+    pub fn calls(&self) -> Vec<Vec<String>> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl GitRunner for ScriptedRunner {
+    fn output(&self, cmd: &mut Command) -> io::Result<Output> {
+        let args = git_args(cmd);
+        self.calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(args.clone());
+        let answer = self
+            .rules
+            .iter()
+            .find(|(prefix, _)| args.starts_with(prefix))
+            .map(|(_, output)| output.clone());
+        Ok(answer.unwrap_or_else(|| {
+            fake_output(
+                128,
+                "",
+                &format!("fatal: unscripted git command: {}", args.join(" ")),
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn args(cmd: &[&str]) -> Vec<String> {
+        cmd.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_is_query() {
+        assert!(is_query(&args(&["rev-parse", "HEAD"])));
+        assert!(is_query(&args(&["config", "--get", "remote.origin.url"])));
+        assert!(is_query(&args(&["worktree", "list", "--porcelain"])));
+        assert!(is_query(&args(&["remote"])));
+        assert!(!is_query(&args(&["config", "user.name", "x"])));
+        assert!(!is_query(&args(&["worktree", "add", "/tmp/x", "main"])));
+        assert!(!is_query(&args(&["stash"])));
+        assert!(!is_query(&args(&["fetch", "--all"])));
+    }
+
+    #[test]
+    fn test_command_line() {
+        let mut cmd = Command::new("git");
+        cmd.arg("-C")
+            .arg("/my repo")
+            .arg("commit")
+            .arg("-m")
+            .arg("it's");
+        assert_eq!(command_line(&cmd), "git -C '/my repo' commit -m 'it'\\''s'");
+    }
+
+    #[test]
+    fn test_isolate() {
+        let mut cmd = Command::new("git");
+        cmd.env("GIT_CONFIG_COUNT", "1");
+        isolate(&mut cmd);
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.contains(&(OsStr::new("GIT_DIR"), None)));
+        assert!(envs.contains(&(OsStr::new("GIT_WORK_TREE"), None)));
+        assert!(envs.contains(&(OsStr::new("GIT_CONFIG_COUNT"), Some(OsStr::new("1")))));
+    }
+
+    #[test]
+    fn test_scripted_runner() {
+        let runner = Arc::new(ScriptedRunner::new().respond(
+            &["for-each-ref"],
+            "main\trefs/remotes/origin/main\tbehind 2\nfeature\t\t\n",
+        ));
+
+        let drift = with_runner(runner.clone(), || {
+            crate::git::branch_drift(Path::new("/nonexistent/repo.git")).unwrap()
+        });
+
+        assert_eq!(drift.len(), 1);
+        assert_eq!(drift[0].behind, 2);
+        assert_eq!(runner.calls()[0][0], "for-each-ref");
+
+        // Unscripted commands fail like git would
+        let result = with_runner(runner, || {
+            crate::git::gc(Path::new("/nonexistent/repo.git"), false, None)
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_simulate_runner() {
+        let runner = Arc::new(SimulateRunner::new());
+
+        let output = with_runner(runner.clone(), || {
+            let mut cmd = Command::new("git");
+            cmd.arg("-C").arg("/nonexistent").arg("fetch").arg("--all");
+            output(&mut cmd).unwrap()
+        });
+
+        assert!(output.status.success());
+        assert_eq!(runner.skipped(), vec!["git -C /nonexistent fetch --all"]);
+    }
+}
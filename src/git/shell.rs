@@ -1,12 +1,23 @@
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use anyhow::{Context, Result, bail};
 
+use crate::filesystem::{copy_tree, same_device};
+use crate::git::{check_worktree_link, repair_worktree};
+use crate::timings::TimedCommand;
+
 /// Move a worktree using `git worktree move`
 ///
 /// Note: libgit2 doesn't support worktree move, so we shell out to git.
+/// Git only renames, so moves to another filesystem copy the worktree and
+/// repair its registration instead.
 pub fn worktree_move(bare_repo: &Path, from: &Path, to: &Path) -> Result<()> {
+    if !same_device(from, to.parent().unwrap_or(to)) {
+        return worktree_move_across_devices(bare_repo, from, to);
+    }
+
     let output = Command::new("git")
         .arg("-C")
         .arg(bare_repo)
@@ -14,7 +25,7 @@ pub fn worktree_move(bare_repo: &Path, from: &Path, to: &Path) -> Result<()> {
         .arg("move")
         .arg(from)
         .arg(to)
-        .output()
+        .timed_output()
         .with_context(|| {
             format!(
                 "failed to move worktree from {} to {}",
@@ -36,6 +47,52 @@ pub fn worktree_move(bare_repo: &Path, from: &Path, to: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Copy a worktree to another filesystem, point git at the copy, check
+/// the copy, and only then remove the original
+fn worktree_move_across_devices(bare_repo: &Path, from: &Path, to: &Path) -> Result<()> {
+    let fail = |reason: String| -> Result<()> {
+        bail!(
+            "failed to move worktree from {} to {}: {}",
+            from.display(),
+            to.display(),
+            reason
+        )
+    };
+    let head = get_head_commit(from)?;
+
+    if let Err(e) = copy_tree(from, to) {
+        // The original is still registered and intact
+        let _ = fs::remove_dir_all(to);
+        return fail(format!("{:#}", e));
+    }
+
+    if let Err(e) = repair_worktree(bare_repo, to) {
+        let _ = fs::remove_dir_all(to);
+        return fail(format!("{:#}", e));
+    }
+
+    // Git now knows the worktree by the copy; make sure the copy works
+    // before the original goes
+    let problem = match (check_worktree_link(to), get_head_commit(to)) {
+        (Some(broken), _) => Some(broken.to_string()),
+        (None, Ok(copied)) if copied != head => Some(format!(
+            "the copy is at {} instead of {}",
+            &copied[..copied.len().min(8)],
+            &head[..head.len().min(8)]
+        )),
+        (None, Ok(_)) => None,
+        (None, Err(e)) => Some(format!("{:#}", e)),
+    };
+    if let Some(problem) = problem {
+        // Hand the worktree back to the original
+        let _ = fs::remove_dir_all(to);
+        let _ = repair_worktree(bare_repo, from);
+        return fail(format!("the copy doesn't check out: {}", problem));
+    }
+
+    fs::remove_dir_all(from).with_context(|| format!("failed to remove {}", from.display()))
+}
+
 /// Prune stale worktree entries
 pub fn worktree_prune(bare_repo: &Path) -> Result<()> {
     let output = Command::new("git")
@@ -43,7 +100,7 @@ pub fn worktree_prune(bare_repo: &Path) -> Result<()> {
         .arg(bare_repo)
         .arg("worktree")
         .arg("prune")
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to prune worktrees in {}", bare_repo.display()))?;
 
     if !output.status.success() {
@@ -66,7 +123,7 @@ pub fn git_mv(repo: &Path, from: &Path, to: &Path) -> Result<()> {
         .arg("mv")
         .arg(from)
         .arg(to)
-        .output()
+        .timed_output()
         .with_context(|| {
             format!(
                 "failed to git mv from {} to {}",
@@ -95,7 +152,7 @@ pub fn get_head_commit(repo: &Path) -> Result<String> {
         .arg(repo)
         .arg("rev-parse")
         .arg("HEAD")
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to get HEAD commit in {}", repo.display()))?;
 
     if !output.status.success() {
@@ -112,67 +169,78 @@ pub fn get_head_commit(repo: &Path) -> Result<String> {
     Ok(commit)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use git2::{Repository, Signature};
-    use tempfile::TempDir;
-
-    /// Create a test repository with an initial commit
-    fn create_test_repo() -> (TempDir, Repository) {
-        let dir = TempDir::new().unwrap();
-        let repo = Repository::init(dir.path()).unwrap();
-
-        // Configure user for commits
-        {
-            let mut config = repo.config().unwrap();
-            config.set_str("user.name", "Test User").unwrap();
-            config.set_str("user.email", "test@test.com").unwrap();
-        }
+/// Whether the checked-out branch has an upstream configured
+pub fn has_upstream(repo: &Path) -> bool {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("rev-parse")
+        .arg("--abbrev-ref")
+        .arg("--symbolic-full-name")
+        .arg("@{upstream}")
+        .timed_output();
 
-        // Create initial commit
-        {
-            let sig = Signature::now("Test User", "test@test.com").unwrap();
-            let tree_id = {
-                let mut index = repo.index().unwrap();
-                // Create a file
-                std::fs::write(dir.path().join("README.md"), "# Test").unwrap();
-                index.add_path(std::path::Path::new("README.md")).unwrap();
-                index.write().unwrap();
-                index.write_tree().unwrap()
-            };
-            let tree = repo.find_tree(tree_id).unwrap();
-            repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
-                .unwrap();
-        }
+    matches!(output, Ok(o) if o.status.success())
+}
 
-        (dir, repo)
-    }
+/// Resolve a path inside a repo's git directory (e.g. `info/exclude`),
+/// shared with its linked worktrees where git shares it
+pub fn git_path(repo: &Path, path: &str) -> Option<PathBuf> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("rev-parse")
+        .arg("--path-format=absolute")
+        .arg("--git-path")
+        .arg(path)
+        .timed_output()
+        .ok()?;
+    let resolved = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !resolved.is_empty()).then(|| PathBuf::from(resolved))
+}
 
-    /// Create a bare repository with initial commit
-    fn create_bare_repo_with_commit() -> (TempDir, Repository) {
-        // First create a regular repo with commits
-        let (temp_dir, _repo) = create_test_repo();
+/// The checked-out branch, if any
+pub fn current_branch(repo: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("branch")
+        .arg("--show-current")
+        .timed_output()
+        .ok()?;
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !branch.is_empty()).then_some(branch)
+}
 
-        // Clone to bare using git command (more reliable than git2 for bare clone)
-        let bare_dir = TempDir::new().unwrap();
+/// Commit one file's current content, staging it first if git doesn't
+/// track it yet; other staged changes are left out of the commit
+pub fn commit_file(repo: &Path, file: &Path, message: &str) -> Result<()> {
+    for args in [
+        vec!["add", "--"],
+        vec!["commit", "--quiet", "-m", message, "--"],
+    ] {
         let output = Command::new("git")
-            .arg("clone")
-            .arg("--bare")
-            .arg(temp_dir.path())
-            .arg(bare_dir.path())
-            .output()
-            .unwrap();
-        assert!(
-            output.status.success(),
-            "git clone --bare failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        );
-
-        let bare_repo = Repository::open_bare(bare_dir.path()).unwrap();
-
-        (bare_dir, bare_repo)
+            .arg("-C")
+            .arg(repo)
+            .args(&args)
+            .arg(file)
+            .timed_output()
+            .with_context(|| format!("failed to commit {}", file.display()))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("git {} failed: {}", args[0], stderr.trim());
+        }
     }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{create_bare_repo_with_commit, create_test_repo};
+    use git2::Repository;
+    use tempfile::TempDir;
 
     /// Get the default branch name from a repository
     fn get_default_branch(repo_path: &Path) -> String {
@@ -0,0 +1,156 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use super::lazy_fetch::NoLazyFetch;
+use crate::timings::TimedCommand;
+
+/// A checkout's branch, upstream and changes, from one
+/// `git status --porcelain=v2 --branch -z`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WorkingStatus {
+    /// Checked-out branch (None for a detached HEAD)
+    pub branch: Option<String>,
+    /// Upstream of the branch (e.g. `origin/main`), if configured
+    pub upstream: Option<String>,
+    /// Commits on the branch that aren't in its upstream
+    pub ahead: u32,
+    /// Commits in the upstream that aren't on the branch
+    pub behind: u32,
+    /// Paths with staged, unstaged or conflicting changes
+    pub changed: Vec<String>,
+    /// Untracked paths
+    pub untracked: Vec<String>,
+}
+
+impl WorkingStatus {
+    /// Whether there is nothing to commit (ignored files aside)
+    pub fn is_clean(&self) -> bool {
+        self.changed.is_empty() && self.untracked.is_empty()
+    }
+}
+
+/// Read a checkout's status in a single git call
+pub fn working_status(checkout: &Path) -> Result<WorkingStatus> {
+    read_status(&mut Command::new("git"), checkout)
+}
+
+/// Like [`working_status`], but failing instead of fetching objects
+/// missing from a partial clone
+pub fn working_status_offline(checkout: &Path) -> Result<WorkingStatus> {
+    read_status(Command::new("git").no_lazy_fetch(), checkout)
+}
+
+fn read_status(git: &mut Command, checkout: &Path) -> Result<WorkingStatus> {
+    let output = git
+        .arg("-C")
+        .arg(checkout)
+        .arg("status")
+        .arg("--porcelain=v2")
+        .arg("--branch")
+        .arg("-z")
+        .timed_output()
+        .with_context(|| format!("failed to check git status in {}", checkout.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to check git status in {}: {}",
+            checkout.display(),
+            stderr.trim()
+        );
+    }
+
+    Ok(parse_porcelain_v2(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `git status --porcelain=v2 --branch -z` output
+///
+/// Records are NUL-terminated; a rename or copy (`2`) is followed by an
+/// extra record holding its original path.
+fn parse_porcelain_v2(output: &str) -> WorkingStatus {
+    let mut status = WorkingStatus::default();
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
+
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.head" if value != "(detached)" => status.branch = Some(value.to_string()),
+                "branch.upstream" => status.upstream = Some(value.to_string()),
+                "branch.ab" => {
+                    for count in value.split(' ') {
+                        if let Some(n) = count.strip_prefix('+') {
+                            status.ahead = n.parse().unwrap_or(0);
+                        } else if let Some(n) = count.strip_prefix('-') {
+                            status.behind = n.parse().unwrap_or(0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        // The path is the last field; earlier fields never hold spaces
+        let (fields, path) = match record.as_bytes()[0] {
+            b'1' => (9, &mut status.changed),
+            b'2' => {
+                records.next();
+                (10, &mut status.changed)
+            }
+            b'u' => (11, &mut status.changed),
+            b'?' => (2, &mut status.untracked),
+            _ => continue,
+        };
+        if let Some(name) = record.splitn(fields, ' ').nth(fields - 1) {
+            path.push(name.to_string());
+        }
+    }
+
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain_v2() {
+        let output = [
+            "# branch.oid 1f2e3d4c5b6a",
+            "# branch.head main",
+            "# branch.upstream origin/main",
+            "# branch.ab +2 -1",
+            "1 .M N... 100644 100644 100644 aaa aaa src/main rs.txt",
+            "2 R. N... 100644 100644 100644 bbb bbb R100 new.txt",
+            "old.txt",
+            "u UU N... 100644 100644 100644 100644 ccc ddd eee both.txt",
+            "? notes.md",
+            "",
+        ]
+        .join("\0");
+
+        let status = parse_porcelain_v2(&output);
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((status.ahead, status.behind), (2, 1));
+        assert_eq!(
+            status.changed,
+            vec!["src/main rs.txt", "new.txt", "both.txt"]
+        );
+        assert_eq!(status.untracked, vec!["notes.md"]);
+        assert!(!status.is_clean());
+    }
+
+    #[test]
+    fn test_parse_porcelain_v2_detached_clean() {
+        let output = "# branch.oid 1f2e3d4c5b6a\0# branch.head (detached)\0";
+        let status = parse_porcelain_v2(output);
+        assert_eq!(status.branch, None);
+        assert_eq!(status.upstream, None);
+        assert_eq!((status.ahead, status.behind), (0, 0));
+        assert!(status.is_clean());
+    }
+}
@@ -1,30 +1,38 @@
-use std::path::Path;
-use std::process::Command;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::UNIX_EPOCH;
 
 use anyhow::{Context, Result, bail};
 
+use super::lazy_fetch::{NoLazyFetch, needs_missing_object};
 use crate::id::format_wald_branch;
+use crate::interrupt::run_checkout;
+use crate::timings::TimedCommand;
 
 /// Add a worktree from a bare repository
 ///
 /// If the branch doesn't exist locally, creates it tracking the remote branch.
 pub fn add_worktree(bare_repo: &Path, worktree_path: &Path, branch: &str) -> Result<()> {
     // First, try to add worktree for existing branch
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(bare_repo)
-        .arg("worktree")
-        .arg("add")
-        .arg(worktree_path)
-        .arg(branch)
-        .output()
-        .with_context(|| {
-            format!(
-                "failed to add worktree at {} for branch {}",
-                worktree_path.display(),
-                branch
-            )
-        })?;
+    let output = run_checkout(
+        bare_repo,
+        worktree_path,
+        Command::new("git")
+            .arg("-C")
+            .arg(bare_repo)
+            .arg("worktree")
+            .arg("add")
+            .arg(worktree_path)
+            .arg(branch),
+    )
+    .with_context(|| {
+        format!(
+            "failed to add worktree at {} for branch {}",
+            worktree_path.display(),
+            branch
+        )
+    })?;
 
     if output.status.success() {
         return Ok(());
@@ -34,33 +42,39 @@ pub fn add_worktree(bare_repo: &Path, worktree_path: &Path, branch: &str) -> Res
     let stderr = String::from_utf8_lossy(&output.stderr);
     if stderr.contains("not a valid reference") || stderr.contains("invalid reference") {
         // Try to create branch tracking origin
-        let output = Command::new("git")
-            .arg("-C")
-            .arg(bare_repo)
-            .arg("worktree")
-            .arg("add")
-            .arg("-b")
-            .arg(branch)
-            .arg(worktree_path)
-            .arg(format!("origin/{}", branch))
-            .output()
-            .with_context(|| format!("failed to create branch {} for worktree", branch))?;
+        let output = run_checkout(
+            bare_repo,
+            worktree_path,
+            Command::new("git")
+                .arg("-C")
+                .arg(bare_repo)
+                .arg("worktree")
+                .arg("add")
+                .arg("-b")
+                .arg(branch)
+                .arg(worktree_path)
+                .arg(format!("origin/{}", branch)),
+        )
+        .with_context(|| format!("failed to create branch {} for worktree", branch))?;
 
         if output.status.success() {
             return Ok(());
         }
 
         // If origin/branch doesn't exist either, create from HEAD
-        let output = Command::new("git")
-            .arg("-C")
-            .arg(bare_repo)
-            .arg("worktree")
-            .arg("add")
-            .arg("-b")
-            .arg(branch)
-            .arg(worktree_path)
-            .output()
-            .with_context(|| format!("failed to create new branch {} for worktree", branch))?;
+        let output = run_checkout(
+            bare_repo,
+            worktree_path,
+            Command::new("git")
+                .arg("-C")
+                .arg(bare_repo)
+                .arg("worktree")
+                .arg("add")
+                .arg("-b")
+                .arg(branch)
+                .arg(worktree_path),
+        )
+        .with_context(|| format!("failed to create new branch {} for worktree", branch))?;
 
         if output.status.success() {
             return Ok(());
@@ -84,7 +98,11 @@ pub fn add_worktree(bare_repo: &Path, worktree_path: &Path, branch: &str) -> Res
 /// Remove a worktree
 pub fn remove_worktree(bare_repo: &Path, worktree_path: &Path, force: bool) -> Result<()> {
     let mut cmd = Command::new("git");
-    cmd.arg("-C").arg(bare_repo).arg("worktree").arg("remove");
+    cmd.no_lazy_fetch()
+        .arg("-C")
+        .arg(bare_repo)
+        .arg("worktree")
+        .arg("remove");
 
     if force {
         cmd.arg("--force");
@@ -93,11 +111,18 @@ pub fn remove_worktree(bare_repo: &Path, worktree_path: &Path, force: bool) -> R
     cmd.arg(worktree_path);
 
     let output = cmd
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to remove worktree at {}", worktree_path.display()))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
+        if needs_missing_object(&stderr) {
+            bail!(
+                "can't check {} for changes without fetching objects missing from the partial clone; \
+                 run `wald repo fetch` while online, or use --force to remove it unchecked",
+                worktree_path.display()
+            );
+        }
         bail!(
             "failed to remove worktree at {}: {}",
             worktree_path.display(),
@@ -116,7 +141,7 @@ pub fn list_worktrees(bare_repo: &Path) -> Result<Vec<WorktreeInfo>> {
         .arg("worktree")
         .arg("list")
         .arg("--porcelain")
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to list worktrees for {}", bare_repo.display()))?;
 
     if !output.status.success() {
@@ -132,6 +157,111 @@ pub fn list_worktrees(bare_repo: &Path) -> Result<Vec<WorktreeInfo>> {
     parse_worktree_list(&stdout)
 }
 
+/// Find the entry for a worktree path in git's worktree list
+///
+/// Paths are compared canonicalized so symlinked locations (e.g. /tmp on
+/// macOS) still match.
+pub fn find_worktree(bare_repo: &Path, worktree_path: &Path) -> Result<Option<WorktreeInfo>> {
+    let worktrees = list_worktrees(bare_repo)?;
+    Ok(match_worktree(&worktrees, worktree_path).cloned())
+}
+
+/// Find the entry for a worktree path in an already fetched worktree list
+pub fn match_worktree<'a>(
+    worktrees: &'a [WorktreeInfo],
+    worktree_path: &Path,
+) -> Option<&'a WorktreeInfo> {
+    // A locked worktree's directory may be gone (unmounted drive), so fall
+    // back to canonicalizing its parent
+    let target = worktree_path.canonicalize().unwrap_or_else(|_| {
+        match (worktree_path.parent(), worktree_path.file_name()) {
+            (Some(parent), Some(name)) => parent
+                .canonicalize()
+                .map(|p| p.join(name))
+                .unwrap_or_else(|_| worktree_path.to_path_buf()),
+            _ => worktree_path.to_path_buf(),
+        }
+    });
+
+    worktrees.iter().find(|wt| {
+        let path = Path::new(&wt.path);
+        path == target || path.canonicalize().is_ok_and(|p| p == target)
+    })
+}
+
+/// Lock a worktree so git refuses to prune, move or remove it
+pub fn lock_worktree(bare_repo: &Path, worktree_path: &Path, reason: Option<&str>) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(bare_repo).arg("worktree").arg("lock");
+    if let Some(reason) = reason {
+        cmd.arg("--reason").arg(reason);
+    }
+    let output = cmd
+        .arg(worktree_path)
+        .timed_output()
+        .with_context(|| format!("failed to lock worktree {}", worktree_path.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to lock worktree {}: {}",
+            worktree_path.display(),
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Unlock a previously locked worktree
+pub fn unlock_worktree(bare_repo: &Path, worktree_path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(bare_repo)
+        .arg("worktree")
+        .arg("unlock")
+        .arg(worktree_path)
+        .timed_output()
+        .with_context(|| format!("failed to unlock worktree {}", worktree_path.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to unlock worktree {}: {}",
+            worktree_path.display(),
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Switch a worktree to another existing branch
+///
+/// Refuses (as git does) when local changes would be overwritten.
+pub fn switch_branch(worktree_path: &Path, branch: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(worktree_path)
+        .arg("switch")
+        .arg("--quiet")
+        .arg(branch)
+        .timed_output()
+        .with_context(|| format!("failed to run git switch in {}", worktree_path.display()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to switch {} to {}: {}",
+            worktree_path.display(),
+            branch,
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
 /// Information about a worktree
 #[derive(Debug, Clone, Default)]
 pub struct WorktreeInfo {
@@ -141,6 +271,8 @@ pub struct WorktreeInfo {
     pub bare: bool,
     pub detached: bool,
     pub locked: bool,
+    /// Reason given when the worktree was locked, if any
+    pub lock_reason: Option<String>,
     pub prunable: bool,
 }
 
@@ -172,8 +304,12 @@ fn parse_worktree_list(output: &str) -> Result<Vec<WorktreeInfo>> {
             current.bare = true;
         } else if line == "detached" {
             current.detached = true;
-        } else if line.starts_with("locked") {
+        } else if let Some(rest) = line.strip_prefix("locked") {
             current.locked = true;
+            let reason = rest.trim();
+            if !reason.is_empty() {
+                current.lock_reason = Some(reason.to_string());
+            }
         } else if line.starts_with("prunable") {
             current.prunable = true;
         }
@@ -218,20 +354,94 @@ pub fn add_worktree_with_tracking(
         branch,
         baum_id,
         BranchMode::Default,
+        false,
     )
 }
 
 /// Add a worktree with a local tracking branch, with configurable branch mode
+///
+/// With `progress`, git's checkout progress is shown on stderr.
 pub fn add_worktree_with_tracking_mode(
     bare_repo: &Path,
     worktree_path: &Path,
     branch: &str,
     baum_id: &str,
     mode: BranchMode,
+    progress: bool,
 ) -> Result<String> {
-    let local_branch = format_wald_branch(baum_id, branch);
-    let remote_branch = format!("origin/{}", branch);
+    let local_branch = prepare_tracking_branch(bare_repo, branch, baum_id, mode)?;
+    add_worktree_for_existing_branch(bare_repo, worktree_path, &local_branch, progress)
+}
 
+/// Create (or, with `BranchMode::Reuse`, keep) the local tracking branch
+/// `wald/<baum_id>/<branch>` without checking it out
+///
+/// Returns the local branch name. Split from the checkout so callers can
+/// create refs one at a time and run the slow checkouts concurrently.
+pub fn prepare_tracking_branch(
+    bare_repo: &Path,
+    branch: &str,
+    baum_id: &str,
+    mode: BranchMode,
+) -> Result<String> {
+    prepare_tracking_branch_from(
+        bare_repo,
+        branch,
+        baum_id,
+        mode,
+        &format!("origin/{}", branch),
+    )
+}
+
+/// Like `prepare_tracking_branch`, but start from and track `remote_branch`
+/// (e.g. `upstream/main`) instead of `origin/<branch>`
+pub fn prepare_tracking_branch_from(
+    bare_repo: &Path,
+    branch: &str,
+    baum_id: &str,
+    mode: BranchMode,
+    remote_branch: &str,
+) -> Result<String> {
+    prepare_local_branch(
+        bare_repo,
+        format_wald_branch(baum_id, branch),
+        mode,
+        remote_branch,
+    )
+}
+
+/// Prepare `<branch>` itself tracking `origin/<branch>`, for repos whose
+/// host rejects other branches (e.g. Overleaf, which only has `master`)
+///
+/// Returns the branch name. Git checks a branch out in one worktree at a
+/// time, so there can only be one worktree per branch across all baums.
+pub fn prepare_direct_branch(bare_repo: &Path, branch: &str, mode: BranchMode) -> Result<String> {
+    if let Some(wt) = list_worktrees(bare_repo)?
+        .iter()
+        .find(|wt| wt.branch.as_deref() == Some(branch))
+    {
+        bail!(
+            "branch '{}' is already checked out at {}; with direct branching a branch can only have one worktree",
+            branch,
+            wt.path
+        );
+    }
+    prepare_local_branch(
+        bare_repo,
+        branch.to_string(),
+        mode,
+        &format!("origin/{}", branch),
+    )
+}
+
+/// Create (or keep, see [`BranchMode`]) `local_branch` at `remote_branch`
+/// and make it track it
+fn prepare_local_branch(
+    bare_repo: &Path,
+    local_branch: String,
+    mode: BranchMode,
+    remote_branch: &str,
+) -> Result<String> {
     // Check if local branch already exists
     let branch_exists = check_branch_exists(bare_repo, &local_branch)?;
 
@@ -249,8 +459,8 @@ pub fn add_worktree_with_tracking_mode(
                         local_branch
                     );
                 }
-                // Just add the worktree with the existing branch
-                return add_worktree_for_existing_branch(bare_repo, worktree_path, &local_branch);
+                // Keep the existing branch for the worktree
+                return Ok(local_branch);
             }
             BranchMode::Default => {
                 // Check for unpushed commits and fail if present
@@ -272,8 +482,8 @@ pub fn add_worktree_with_tracking_mode(
         .arg("branch")
         .arg("-f")
         .arg(&local_branch)
-        .arg(&remote_branch)
-        .output()
+        .arg(remote_branch)
+        .timed_output()
         .with_context(|| format!("failed to create branch {}", local_branch))?;
 
     if !output.status.success() {
@@ -291,7 +501,7 @@ pub fn add_worktree_with_tracking_mode(
                 .arg("-f")
                 .arg(&local_branch)
                 .arg("HEAD")
-                .output()
+                .timed_output()
                 .with_context(|| format!("failed to create branch {} from HEAD", local_branch))?;
 
             if !fallback_output.status.success() {
@@ -318,39 +528,50 @@ pub fn add_worktree_with_tracking_mode(
         .arg(bare_repo)
         .arg("branch")
         .arg("--set-upstream-to")
-        .arg(&remote_branch)
+        .arg(remote_branch)
         .arg(&local_branch)
-        .output();
-
-    // Add the worktree checking out the local branch
-    add_worktree_for_existing_branch(bare_repo, worktree_path, &local_branch)?;
+        .timed_output();
 
     Ok(local_branch)
 }
 
 /// Add a worktree for an existing branch
-fn add_worktree_for_existing_branch(
+///
+/// With `progress`, git's stderr is passed through instead of captured, so
+/// it shows checkout progress on a terminal (and its own error message).
+/// A Ctrl-C during the checkout removes the half-created worktree.
+pub fn add_worktree_for_existing_branch(
     bare_repo: &Path,
     worktree_path: &Path,
     branch: &str,
+    progress: bool,
 ) -> Result<String> {
-    let output = Command::new("git")
-        .arg("-C")
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
         .arg(bare_repo)
         .arg("worktree")
         .arg("add")
         .arg(worktree_path)
-        .arg(branch)
-        .output()
-        .with_context(|| {
-            format!(
-                "failed to add worktree at {} for branch {}",
-                worktree_path.display(),
-                branch
-            )
-        })?;
+        .arg(branch);
+    if progress {
+        cmd.stderr(Stdio::inherit());
+    }
+
+    let output = run_checkout(bare_repo, worktree_path, &mut cmd).with_context(|| {
+        format!(
+            "failed to add worktree at {} for branch {}",
+            worktree_path.display(),
+            branch
+        )
+    })?;
 
     if !output.status.success() {
+        if progress {
+            bail!(
+                "failed to add worktree for branch {} (see git output above)",
+                branch
+            );
+        }
         let stderr = String::from_utf8_lossy(&output.stderr);
         bail!(
             "failed to add worktree for branch {}: {}",
@@ -362,6 +583,43 @@ fn add_worktree_for_existing_branch(
     Ok(branch.to_string())
 }
 
+/// Populate a worktree registered with `--no-checkout` from its HEAD
+pub fn checkout_registered_worktree(
+    bare_repo: &Path,
+    worktree_path: &Path,
+    progress: bool,
+) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(worktree_path)
+        .arg("reset")
+        .arg("--hard")
+        .arg("--quiet");
+    if progress {
+        cmd.arg("--progress").stderr(Stdio::inherit());
+    }
+
+    let output = run_checkout(bare_repo, worktree_path, &mut cmd)
+        .with_context(|| format!("failed to check out {}", worktree_path.display()))?;
+
+    if !output.status.success() {
+        if progress {
+            bail!(
+                "failed to check out {} (see git output above)",
+                worktree_path.display()
+            );
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to check out {}: {}",
+            worktree_path.display(),
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
 /// Check if a local branch exists in the repository
 pub fn check_branch_exists(bare_repo: &Path, branch: &str) -> Result<bool> {
     let output = Command::new("git")
@@ -370,22 +628,74 @@ pub fn check_branch_exists(bare_repo: &Path, branch: &str) -> Result<bool> {
         .arg("rev-parse")
         .arg("--verify")
         .arg(format!("refs/heads/{}", branch))
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to check branch {}", branch))?;
 
     Ok(output.status.success())
 }
 
+/// Point a local branch that no worktree has checked out at `commit`,
+/// keeping its upstream
+pub fn reset_branch(bare_repo: &Path, branch: &str, commit: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(bare_repo)
+        .arg("branch")
+        .arg("-f")
+        .arg(branch)
+        .arg(commit)
+        .timed_output()
+        .with_context(|| format!("failed to reset branch {}", branch))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to reset branch {} to {}: {}",
+            branch,
+            commit,
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Rename a local branch, including where a worktree has it checked out
+pub fn rename_branch(bare_repo: &Path, from: &str, to: &str) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(bare_repo)
+        .arg("branch")
+        .arg("-m")
+        .arg(from)
+        .arg(to)
+        .timed_output()
+        .with_context(|| format!("failed to rename branch {}", from))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "failed to rename branch {} to {}: {}",
+            from,
+            to,
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
 /// Delete a local branch
 pub fn delete_branch(bare_repo: &Path, branch: &str, force: bool) -> Result<()> {
     let flag = if force { "-D" } else { "-d" };
     let output = Command::new("git")
+        .no_lazy_fetch()
         .arg("-C")
         .arg(bare_repo)
         .arg("branch")
         .arg(flag)
         .arg(branch)
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to delete branch {}", branch))?;
 
     if !output.status.success() {
@@ -404,7 +714,7 @@ pub fn list_wald_branches(bare_repo: &Path) -> Result<Vec<String>> {
         .arg("branch")
         .arg("--list")
         .arg("wald/*")
-        .output()
+        .timed_output()
         .with_context(|| "failed to list wald branches")?;
 
     if !output.status.success() {
@@ -434,7 +744,7 @@ pub fn has_unpushed_commits(bare_repo: &Path, branch: &str) -> Result<bool> {
         .arg("rev-parse")
         .arg("--abbrev-ref")
         .arg(format!("{}@{{upstream}}", branch))
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to check upstream for {}", branch))?;
 
     if !upstream_output.status.success() {
@@ -448,12 +758,13 @@ pub fn has_unpushed_commits(bare_repo: &Path, branch: &str) -> Result<bool> {
 
     // Check if there are commits in branch that aren't in upstream
     let output = Command::new("git")
+        .no_lazy_fetch()
         .arg("-C")
         .arg(bare_repo)
         .arg("rev-list")
         .arg("--count")
         .arg(format!("{}..{}", upstream, branch))
-        .output()
+        .timed_output()
         .with_context(|| format!("failed to count unpushed commits for {}", branch))?;
 
     if !output.status.success() {
@@ -469,10 +780,206 @@ pub fn has_unpushed_commits(bare_repo: &Path, branch: &str) -> Result<bool> {
     Ok(count > 0)
 }
 
+/// Subjects of the commits on a branch that aren't in its upstream, newest
+/// first (empty if everything is pushed or the branch has no upstream)
+pub fn unpushed_subjects(bare_repo: &Path, branch: &str) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .no_lazy_fetch()
+        .arg("-C")
+        .arg(bare_repo)
+        .arg("log")
+        .arg("--format=%s")
+        .arg(format!("{}@{{upstream}}..{}", branch, branch))
+        .arg("--")
+        .timed_output()
+        .with_context(|| format!("failed to list unpushed commits for {}", branch))?;
+
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.to_string())
+        .collect())
+}
+
+/// Commit time of the oldest commit on a branch that isn't in its upstream
+///
+/// Returns seconds since the Unix epoch, or None if everything is pushed or
+/// the branch has no upstream configured.
+pub fn oldest_unpushed_commit(bare_repo: &Path, branch: &str) -> Result<Option<u64>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(bare_repo)
+        .arg("log")
+        .arg("--format=%ct")
+        .arg(format!("{}@{{upstream}}..{}", branch, branch))
+        .arg("--")
+        .timed_output()
+        .with_context(|| format!("failed to list unpushed commits for {}", branch))?;
+
+    if !output.status.success() {
+        // No upstream configured
+        return Ok(None);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|l| l.trim().parse().ok())
+        .min())
+}
+
+/// When a worktree was last worked in (seconds since the Unix epoch)
+///
+/// Commits, checkouts and resets touch the HEAD reflog, staging touches the
+/// index; both live in the worktree's admin directory in the bare repo. A
+/// worktree without one counts by its directory's mtime.
+pub fn last_activity(worktree_path: &Path) -> Option<u64> {
+    let mtime = |path: &Path| {
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+    };
+
+    let from_git = admin_dir(worktree_path).and_then(|dir| {
+        ["logs/HEAD", "index", "HEAD"]
+            .iter()
+            .filter_map(|file| mtime(&dir.join(file)))
+            .max()
+    });
+    from_git.or_else(|| mtime(worktree_path))
+}
+
+/// The admin directory a linked worktree's `.git` file points at
+/// (`<bare>/worktrees/<name>`), whether or not it exists
+pub fn admin_dir(worktree_path: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(worktree_path.join(".git")).ok()?;
+    let dir = content.trim().strip_prefix("gitdir:")?.trim();
+    Some(worktree_path.join(dir))
+}
+
+/// A broken link between a linked worktree and its bare repo, as left by
+/// moving the worktree (or its container) with plain `mv`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrokenLink {
+    /// `.git` points at an admin directory that doesn't exist
+    MissingGitdir(PathBuf),
+    /// The admin directory still names the worktree's old location
+    StaleBacklink(PathBuf),
+}
+
+impl std::fmt::Display for BrokenLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BrokenLink::MissingGitdir(dir) => {
+                write!(f, ".git points to {}, which doesn't exist", dir.display())
+            }
+            BrokenLink::StaleBacklink(old) => {
+                write!(f, "git still has it at {}", old.display())
+            }
+        }
+    }
+}
+
+/// Check that a worktree's `.git` file and its admin directory point at
+/// each other
+///
+/// Returns `None` for intact links and for directories without a `.git`
+/// file (nothing to check).
+pub fn check_worktree_link(worktree_path: &Path) -> Option<BrokenLink> {
+    let admin = admin_dir(worktree_path)?;
+    if !admin.is_dir() {
+        return Some(BrokenLink::MissingGitdir(admin));
+    }
+
+    // `<admin>/gitdir` holds the path of the worktree's `.git` file
+    let backlink = fs::read_to_string(admin.join("gitdir")).ok()?;
+    let recorded = Path::new(backlink.trim());
+    let recorded = recorded.parent().unwrap_or(recorded);
+    let same = match (recorded.canonicalize(), worktree_path.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    };
+    (!same).then(|| BrokenLink::StaleBacklink(recorded.to_path_buf()))
+}
+
+/// The branch checked out in the worktree at `worktree_path`, read from its
+/// admin directory (works while the link is broken)
+pub fn linked_branch(worktree_path: &Path) -> Option<String> {
+    let head = fs::read_to_string(admin_dir(worktree_path)?.join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(|b| b.to_string())
+}
+
+/// Reconnect a worktree and its bare repo in both directions
+/// (`git worktree repair <path>`, run in the bare repo)
+pub fn repair_worktree(bare_repo: &Path, worktree_path: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(bare_repo)
+        .arg("worktree")
+        .arg("repair")
+        .arg(worktree_path)
+        .timed_output()
+        .with_context(|| {
+            format!(
+                "failed to run git worktree repair in {}",
+                bare_repo.display()
+            )
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("git worktree repair failed: {}", stderr.trim());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_check_worktree_link() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let wt = dir.path().join("_main.wt");
+        let admin = dir.path().join("repo.git/worktrees/main");
+        fs::create_dir_all(&wt).unwrap();
+        fs::create_dir_all(&admin).unwrap();
+        fs::write(wt.join(".git"), format!("gitdir: {}\n", admin.display())).unwrap();
+        fs::write(admin.join("HEAD"), "ref: refs/heads/wald/abc123/main\n").unwrap();
+
+        fs::write(
+            admin.join("gitdir"),
+            format!("{}\n", wt.join(".git").display()),
+        )
+        .unwrap();
+        assert_eq!(check_worktree_link(&wt), None);
+        assert_eq!(linked_branch(&wt).as_deref(), Some("wald/abc123/main"));
+
+        // The container was moved away from where git last saw it
+        let old = dir.path().join("old/_main.wt");
+        fs::write(
+            admin.join("gitdir"),
+            format!("{}\n", old.join(".git").display()),
+        )
+        .unwrap();
+        assert_eq!(
+            check_worktree_link(&wt),
+            Some(BrokenLink::StaleBacklink(old))
+        );
+
+        fs::remove_dir_all(&admin).unwrap();
+        assert_eq!(
+            check_worktree_link(&wt),
+            Some(BrokenLink::MissingGitdir(admin))
+        );
+    }
+
     #[test]
     fn test_parse_worktree_list() {
         let output = r#"worktree /path/to/bare.git
@@ -501,4 +1008,47 @@ branch refs/heads/feature
         assert_eq!(worktrees[2].path, "/path/to/feature");
         assert_eq!(worktrees[2].branch, Some("feature".to_string()));
     }
+
+    #[test]
+    fn test_parse_worktree_list_locked() {
+        let output = r#"worktree /path/to/usb
+HEAD abc123
+branch refs/heads/main
+locked on external drive
+
+worktree /path/to/plain
+HEAD def456
+branch refs/heads/dev
+locked
+"#;
+
+        let worktrees = parse_worktree_list(output).unwrap();
+        assert!(worktrees[0].locked);
+        assert_eq!(
+            worktrees[0].lock_reason.as_deref(),
+            Some("on external drive")
+        );
+        assert!(worktrees[1].locked);
+        assert_eq!(worktrees[1].lock_reason, None);
+    }
+
+    #[test]
+    fn test_last_activity() {
+        use tempfile::TempDir;
+
+        let dir = TempDir::new().unwrap();
+        let admin = dir.path().join("repo.git/worktrees/main");
+        fs::create_dir_all(admin.join("logs")).unwrap();
+        fs::write(admin.join("logs/HEAD"), "").unwrap();
+        let worktree = dir.path().join("_main.wt");
+        fs::create_dir_all(&worktree).unwrap();
+        fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", admin.display()),
+        )
+        .unwrap();
+
+        assert!(last_activity(&worktree).is_some());
+        assert!(last_activity(&dir.path().join("missing")).is_none());
+    }
 }
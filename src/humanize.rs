@@ -0,0 +1,86 @@
+//! Short, readable forms of commit SHAs, times and sizes
+//!
+//! Human output goes through [`Output::sha`], [`Output::time`] and
+//! [`Output::size`], which use these forms and switch to the full values
+//! with `--verbose`. JSON output keeps the raw values.
+//!
+//! [`Output::sha`]: crate::output::Output::sha
+//! [`Output::time`]: crate::output::Output::time
+//! [`Output::size`]: crate::output::Output::size
+
+/// Length of a short commit SHA
+pub const SHORT_SHA_LEN: usize = 8;
+
+/// The first [`SHORT_SHA_LEN`] characters of a commit SHA
+pub fn short_sha(sha: &str) -> &str {
+    sha.get(..SHORT_SHA_LEN).unwrap_or(sha)
+}
+
+/// How long before `now` a point in time was, e.g. `3 days ago`
+///
+/// Times within the last minute, or ahead of `now` by clock skew, are
+/// `just now`. Months count as 30 days and years as 365.
+pub fn relative_time(secs: u64, now: u64) -> String {
+    let ago = now.saturating_sub(secs);
+    let (count, unit) = match ago {
+        s if s < 60 => return "just now".to_string(),
+        s if s < 3_600 => (s / 60, "minute"),
+        s if s < 86_400 => (s / 3_600, "hour"),
+        s if s < 30 * 86_400 => (s / 86_400, "day"),
+        s if s < 365 * 86_400 => (s / (30 * 86_400), "month"),
+        s => (s / (365 * 86_400), "year"),
+    };
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+/// A byte count with a binary unit, e.g. `12.3 MiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_sha() {
+        assert_eq!(
+            short_sha("912ce88e2c744860297e7b39b2d37402403c8293"),
+            "912ce88e"
+        );
+        assert_eq!(short_sha("912c"), "912c");
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let now = 1_800_000_000;
+        assert_eq!(relative_time(now - 30, now), "just now");
+        assert_eq!(relative_time(now + 5, now), "just now");
+        assert_eq!(relative_time(now - 60, now), "1 minute ago");
+        assert_eq!(relative_time(now - 5 * 3_600 - 59, now), "5 hours ago");
+        assert_eq!(relative_time(now - 3 * 86_400, now), "3 days ago");
+        assert_eq!(relative_time(now - 200 * 86_400, now), "6 months ago");
+        assert_eq!(relative_time(now - 800 * 86_400, now), "2 years ago");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(2 * 1024 * 1024), "2.0 MiB");
+        assert_eq!(format_size(12_900_000), "12.3 MiB");
+    }
+}
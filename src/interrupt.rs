@@ -0,0 +1,184 @@
+//! Ctrl-C handling for worktree checkouts
+//!
+//! A `git worktree add` killed halfway leaves a partly populated directory
+//! and a registration in the bare repo. Checkouts run through
+//! `run_checkout`, so on interrupt the handler can wait for the running git
+//! processes to stop, remove what they left behind, and exit.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::timings::TimedCommand;
+
+/// Exit code for a run cut short by SIGINT (128 + 2)
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// How long to wait for running git processes after an interrupt
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+/// Checkouts started but not finished: (bare repo, worktree path)
+static IN_PROGRESS: Mutex<Vec<(PathBuf, PathBuf)>> = Mutex::new(Vec::new());
+
+/// Install the Ctrl-C handler (once, at startup)
+pub fn install_handler() {
+    // Failing to install only loses the cleanup; not worth aborting over
+    let _ = ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            // Second Ctrl-C: stop waiting
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+
+        // Git got the same SIGINT from the terminal; give it time to exit
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while RUNNING.load(Ordering::SeqCst) > 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        let removed = cleanup_in_progress();
+        if removed > 0 {
+            eprintln!(
+                "\n{:>12} removed {} unfinished worktree(s)",
+                "Interrupted", removed
+            );
+        } else {
+            eprintln!("\n{:>12}", "Interrupted");
+        }
+        std::process::exit(EXIT_INTERRUPTED);
+    });
+}
+
+/// Whether Ctrl-C was pressed
+fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Run a command that checks out `worktree` from `bare_repo`
+///
+/// If the run is interrupted, the worktree is removed again before wald
+/// exits. A checkout that finishes normally is left alone.
+pub fn run_checkout(bare_repo: &Path, worktree: &Path, cmd: &mut Command) -> io::Result<Output> {
+    // Never clean up a directory that held files before (git refuses those
+    // anyway, but the interrupt may come first). One registered with
+    // `--no-checkout` holds just its `.git` file.
+    let fresh = std::fs::read_dir(worktree).map_or(true, |d| {
+        d.flatten()
+            .all(|e| e.file_name() == ".git" && e.path().is_file())
+    });
+
+    let entry = (bare_repo.to_path_buf(), worktree.to_path_buf());
+    if fresh {
+        lock_in_progress().push(entry.clone());
+    }
+    RUNNING.fetch_add(1, Ordering::SeqCst);
+
+    let result = cmd.timed_output();
+
+    RUNNING.fetch_sub(1, Ordering::SeqCst);
+    if interrupted() {
+        // The handler cleans up and exits the process; don't race it
+        loop {
+            thread::park();
+        }
+    }
+
+    let mut in_progress = lock_in_progress();
+    if let Some(pos) = in_progress.iter().position(|e| *e == entry) {
+        in_progress.remove(pos);
+    }
+
+    result
+}
+
+/// Remove the worktrees of unfinished checkouts; returns how many
+fn cleanup_in_progress() -> usize {
+    let entries: Vec<_> = lock_in_progress().drain(..).collect();
+    for (bare_repo, worktree) in &entries {
+        let _ = Command::new("git")
+            .arg("-C")
+            .arg(bare_repo)
+            .arg("worktree")
+            .arg("remove")
+            .arg("--force")
+            .arg(worktree)
+            .timed_output();
+        let _ = std::fs::remove_dir_all(worktree);
+        let _ = Command::new("git")
+            .arg("-C")
+            .arg(bare_repo)
+            .arg("worktree")
+            .arg("prune")
+            .timed_output();
+    }
+    entries.len()
+}
+
+fn lock_in_progress() -> std::sync::MutexGuard<'static, Vec<(PathBuf, PathBuf)>> {
+    IN_PROGRESS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_cleanup_removes_unfinished_checkout() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir(&src).unwrap();
+        git(&src, &["init", "--quiet", "--initial-branch=main"]);
+        std::fs::write(src.join("file.txt"), "content").unwrap();
+        git(&src, &["add", "file.txt"]);
+        git(
+            &src,
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                "init",
+            ],
+        );
+        let bare = dir.path().join("repo.git");
+        git(
+            dir.path(),
+            &["clone", "--quiet", "--bare", "src", "repo.git"],
+        );
+
+        // A checkout that completed but is still registered, as if the
+        // interrupt arrived just before run_checkout returned
+        let worktree = dir.path().join("_main.wt");
+        git(
+            &bare,
+            &["worktree", "add", "--quiet", "../_main.wt", "main"],
+        );
+        lock_in_progress().push((bare.clone(), worktree.clone()));
+
+        assert_eq!(cleanup_in_progress(), 1);
+        assert!(!worktree.exists());
+        assert!(!bare.join("worktrees").join("_main.wt").exists());
+        assert!(lock_in_progress().is_empty());
+    }
+}
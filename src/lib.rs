@@ -1,7 +1,19 @@
+pub mod bulk;
 pub mod commands;
+pub mod filesystem;
+pub mod forge;
 pub mod git;
+pub mod humanize;
 pub mod id;
+pub mod interrupt;
 pub mod naming;
 pub mod output;
+pub mod serve;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod time;
+pub mod timings;
+pub mod topics;
 pub mod types;
+pub mod vocabulary;
 pub mod workspace;
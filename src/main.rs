@@ -1,29 +1,69 @@
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::Instant;
 
 use clap::{Parser, Subcommand};
 use clap_complete::Shell;
 
+use wald::bulk::{PARTIAL_FAILURE_EXIT, PartialFailure};
 use wald::commands;
+use wald::commands::export::{ExportFormat, ExportWhat};
+use wald::interrupt;
 use wald::output::{Output, OutputFormat, print_error};
-use wald::types::{DepthPolicy, FilterPolicy, LfsPolicy};
-use wald::workspace::Workspace;
+use wald::time::now_secs;
+use wald::topics;
+use wald::types::{Branching, DepthPolicy, FilterPolicy, LfsPolicy, TagPolicy};
+use wald::vocabulary::Vocabulary;
+use wald::workspace::{
+    Workspace, find_workspace_root, find_workspace_root_from, notify_finished, record_operation,
+    walk_config,
+};
 
 #[derive(Parser)]
 #[command(name = "wald")]
 #[command(about = "Git workspace manager: bare repos, worktrees, and cross-machine sync")]
 #[command(version = env!("WALD_VERSION"))]
+#[command(disable_help_subcommand = true)]
 struct Cli {
+    /// Without a command: a summary of the workspace (or this help
+    /// outside one)
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 
-    /// Enable verbose output
+    /// Enable verbose output, with full SHAs, timestamps and byte counts
     #[arg(short, long, global = true)]
     verbose: bool,
 
     /// Output in JSON format
     #[arg(long, global = true)]
     json: bool,
+
+    /// Workspace to operate on, instead of the one containing the current
+    /// directory (also read from WALD_WORKSPACE)
+    #[arg(long, global = true, value_name = "PATH")]
+    workspace: Option<PathBuf>,
+
+    /// Show the git commands and file writes a command would make, without
+    /// making them (repo add/remove/fetch/gc, branch, lock, unlock)
+    #[arg(long, global = true)]
+    simulate: bool,
+
+    /// Print how long each phase took (directory walks, git commands by
+    /// type, YAML reads and writes) to stderr when done
+    #[arg(long, global = true)]
+    timings: bool,
+
+    /// Show full paths in messages and JSON instead of paths relative to
+    /// the workspace root
+    #[arg(long, global = true)]
+    absolute_paths: bool,
+
+    /// Don't ask before destructive steps (deleting unpushed branches,
+    /// discarding local changes, purging the trash); needed without a
+    /// terminal
+    #[arg(long, short = 'y', global = true, visible_alias = "no-confirm")]
+    yes: bool,
 }
 
 #[derive(Subcommand)]
@@ -41,6 +81,20 @@ enum Commands {
         /// Don't run git init (error if not already a git repo)
         #[arg(long)]
         no_git: bool,
+
+        /// Standalone workspace: no workspace git repo, sync disabled
+        #[arg(long, conflicts_with = "no_git")]
+        no_sync: bool,
+
+        /// Add URL as the workspace's origin, push the initial commit and
+        /// track it
+        #[arg(long, value_name = "URL", conflicts_with = "no_sync")]
+        remote: Option<String>,
+
+        /// Create the private GitHub repository OWNER/NAME as origin
+        /// (needs GITHUB_TOKEN)
+        #[arg(long, value_name = "OWNER/NAME", conflicts_with = "no_sync")]
+        create_github: Option<String>,
     },
 
     /// Clone a wald workspace and hydrate all repos
@@ -59,39 +113,103 @@ enum Commands {
         action: RepoAction,
     },
 
+    /// List and manage repository aliases
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+
     /// Plant a baum (create container with worktrees)
     #[command(visible_alias = "create")]
     Plant {
-        /// Repository ID or alias
-        repo: String,
+        /// Repository ID or alias, or `.` to add branches to the baum
+        /// around the current directory
+        #[arg(required_unless_present_any = ["file", "group"])]
+        repo: Option<String>,
 
         /// Container path (relative to workspace root)
-        container: PathBuf,
+        #[arg(required_unless_present_any = ["file", "group", "template"])]
+        container: Option<PathBuf>,
 
         /// Branches to create worktrees for (default: default branch)
-        #[arg(trailing_var_arg = true)]
         branches: Vec<String>,
 
+        /// Plant every `{repo, container, branches}` entry of a YAML list
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["repo", "container", "branches"])]
+        file: Option<PathBuf>,
+
+        /// Plant a baum for each repo of a group, taking the first argument
+        /// as the container: `{repo}` in it stands for the repo's name
+        /// (e.g. `projects/{repo}`), else each goes in `<container>/<name>`
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["file", "track"])]
+        group: Option<String>,
+
+        /// Compute the container from a template of `plant_templates` in
+        /// .wald/config.yaml (e.g. `research/{year}/{repo}`)
+        #[arg(long, value_name = "NAME", conflicts_with = "file")]
+        template: Option<String>,
+
         /// Delete existing local branch, create fresh from origin
         #[arg(long, conflicts_with = "reuse")]
         force: bool,
 
+        /// With --force: also overwrite the default branch and protected_branches
+        #[arg(long, requires = "force")]
+        yes_protected: bool,
+
         /// Use existing local branch as-is (skip if has unpushed commits)
         #[arg(long)]
         reuse: bool,
+
+        /// Plant the branches that aren't in conflict with existing local
+        /// branches, reporting the ones that are, instead of failing
+        #[arg(long, conflicts_with = "force")]
+        continue_on_conflict: bool,
+
+        /// Track a branch of another remote instead of origin (pushes still go to origin)
+        #[arg(long, value_name = "REMOTE[/BRANCH]", conflicts_with = "file")]
+        track: Option<String>,
+
+        /// Plant into a directory that already holds files, keeping them
+        /// next to the worktrees
+        #[arg(long, conflicts_with = "file")]
+        into_existing: bool,
+
+        /// Start the branches at this commit, tag or revision (e.g.
+        /// `origin/main~20`) instead of the remote tip
+        #[arg(long, value_name = "REF", conflicts_with_all = ["file", "group", "reuse"])]
+        at: Option<String>,
     },
 
     /// Uproot a baum (remove container and worktrees)
-    #[command(visible_alias = "rm")]
+    #[command(visible_alias = "rm", visible_alias = "remove")]
     Uproot {
         /// Path to the baum container
         path: PathBuf,
 
-        /// Force removal even with uncommitted changes
+        /// Uproot even with uncommitted changes or unpushed commits
         #[arg(short, long)]
         force: bool,
     },
 
+    /// Undo the last destructive operation
+    Undo {
+        #[command(subcommand)]
+        action: UndoAction,
+    },
+
+    /// Manage uprooted baums kept in .wald/trash/
+    Trash {
+        #[command(subcommand)]
+        action: TrashAction,
+    },
+
+    /// Install git hooks that check the workspace
+    Hooks {
+        #[command(subcommand)]
+        action: HooksAction,
+    },
+
     /// Move a baum to a new location
     #[command(visible_alias = "graft", visible_alias = "mv")]
     Move {
@@ -102,21 +220,43 @@ enum Commands {
         new_path: PathBuf,
     },
 
-    /// Add a worktree for a branch to an existing baum
+    /// Add worktrees for branches to an existing baum
     Branch {
-        /// Path to the baum container
+        /// Path to the baum container, or any directory inside it (e.g. `.`)
         baum: PathBuf,
 
-        /// Branch name
-        branch: String,
+        /// Branches to add a worktree for
+        #[arg(required = true)]
+        branches: Vec<String>,
 
         /// Delete existing local branch, create fresh from origin
         #[arg(long, conflicts_with = "reuse")]
         force: bool,
 
+        /// With --force: also overwrite the default branch and protected_branches
+        #[arg(long, requires = "force")]
+        yes_protected: bool,
+
         /// Use existing local branch as-is (skip if has unpushed commits)
         #[arg(long)]
         reuse: bool,
+
+        /// Track a branch of another remote instead of origin (pushes still go to origin)
+        #[arg(long, value_name = "REMOTE[/BRANCH]")]
+        track: Option<String>,
+    },
+
+    /// Record a worktree made with `git worktree add` in its baum's manifest
+    AdoptWorktree {
+        /// Path to the baum container
+        baum: PathBuf,
+
+        /// Path to the worktree (directly inside the container)
+        path: PathBuf,
+
+        /// Rename its branch to wald/<baum-id>/<branch> like wald's own worktrees
+        #[arg(long)]
+        rename_branch: bool,
     },
 
     /// Remove worktrees for branches from a baum, or clean up orphan branches
@@ -136,12 +276,149 @@ enum Commands {
         /// Clean up orphan wald/* branches (workspace-wide)
         #[arg(long = "branches", conflicts_with_all = ["baum", "branches"])]
         cleanup_branches: bool,
+
+        /// With --branches --force: also delete unpushed branches of the
+        /// default branch and protected_branches
+        #[arg(long, requires_all = ["force", "cleanup_branches"])]
+        yes_protected: bool,
+
+        /// With --branches: only clean up branches of this repository
+        #[arg(long = "repo", value_name = "REPO")]
+        scope_repo: Option<String>,
+
+        /// With --branches: only clean up branches of this baum
+        #[arg(long = "baum", visible_alias = "container", value_name = "PATH")]
+        scope_baum: Option<PathBuf>,
+
+        /// With --branches: list orphan branches without deleting them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Fetch a baum's repo and fast-forward each of its worktrees
+    Pull {
+        /// Path to the baum container
+        baum: PathBuf,
+
+        /// Rebase branches that have diverged from their upstream instead
+        /// of skipping them
+        #[arg(long)]
+        rebase: bool,
+    },
+
+    /// Push a worktree's branch and open a pull request for it
+    Pr {
+        #[command(subcommand)]
+        action: PrAction,
+    },
+
+    /// Lock a worktree so prune, move and uproot leave it alone
+    Lock {
+        /// Path to the baum container
+        baum: PathBuf,
+
+        /// Branch whose worktree to lock
+        branch: String,
+
+        /// Why the worktree is locked (e.g., "on external drive")
+        #[arg(long)]
+        reason: Option<String>,
+    },
+
+    /// Unlock a worktree locked with `wald lock`
+    Unlock {
+        /// Path to the baum container
+        baum: PathBuf,
+
+        /// Branch whose worktree to unlock
+        branch: String,
     },
 
     /// List all worktrees in the workspace
     Worktrees {
         /// Filter by path
         filter: Option<PathBuf>,
+
+        /// Only list worktrees of this repository (ID or alias)
+        #[arg(long = "repo", value_name = "REPO")]
+        repo_ref: Option<String>,
+
+        /// Check each worktree against git (missing, locked, prunable)
+        #[arg(long)]
+        verify: bool,
+
+        /// Only list worktrees unused for this long (e.g. 90d), as prune candidates
+        #[arg(long, value_name = "AGE")]
+        stale: Option<String>,
+
+        /// Only list baums you own (see `team` in config.yaml)
+        #[arg(long)]
+        mine: bool,
+    },
+
+    /// Print the paths where a repository is checked out
+    Which {
+        /// Repository ID or alias
+        repo: String,
+
+        /// Only the worktree(s) for this branch
+        branch: Option<String>,
+    },
+
+    /// Print the baum, repo and branches of the worktree you're in (fails
+    /// outside one)
+    #[command(visible_alias = "which-baum", visible_alias = "which-container")]
+    Here {
+        /// Any path inside a worktree
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+
+    /// Print a short prompt segment for the current worktree, like
+    /// `repo@branch ↑2 ✚` (nothing outside one)
+    Prompt {
+        /// Any path inside a worktree
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+
+    /// Print a worktree's environment from .baum/manifest.local.yaml as
+    /// export lines (for `eval` or direnv)
+    Env {
+        /// Worktree (or baum container) path
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+
+    /// Search the workspace's repos with git grep
+    Grep {
+        /// Pattern (as for `git grep -e`)
+        pattern: String,
+
+        /// Only search this repository (ID or alias)
+        #[arg(long = "repo", value_name = "REPO")]
+        repo_ref: Option<String>,
+
+        /// Search this branch (its worktree, or the bare repo if it has none)
+        #[arg(long)]
+        branch: Option<String>,
+    },
+
+    /// List a repository's tags, newest first
+    Tags {
+        /// Repository ID or alias
+        repo: String,
+    },
+
+    /// Export an inventory of repos, baums and worktrees
+    Export {
+        /// Output format: json or csv
+        #[arg(long, default_value = "json", value_parser = parse_export_format)]
+        format: ExportFormat,
+
+        /// Only one table: repos, baums, or worktrees (required for csv)
+        #[arg(long, value_parser = parse_export_what)]
+        what: Option<ExportWhat>,
     },
 
     /// Sync workspace with remote
@@ -161,22 +438,272 @@ enum Commands {
         /// Skip cloning missing repos (metadata sync only)
         #[arg(long)]
         offline: bool,
+
+        /// On a rebase conflict, abort the rebase and leave the workspace as it was (default)
+        #[arg(long, conflicts_with = "interactive")]
+        abort_on_conflict: bool,
+
+        /// On a rebase conflict, stop with the rebase in progress for you to resolve
+        #[arg(long)]
+        interactive: bool,
+
+        /// Only clone the repos of baums you own (the default in team workspaces)
+        #[arg(long, conflicts_with = "all")]
+        mine: bool,
+
+        /// Clone the repos of every baum, also in a team workspace
+        #[arg(long)]
+        all: bool,
     },
 
     /// Show workspace status
-    Status,
+    Status {
+        /// Only count baums you own (see `team` in config.yaml)
+        #[arg(long)]
+        mine: bool,
+
+        /// Only show the repos of a group and count their baums
+        #[arg(long, value_name = "NAME")]
+        group: Option<String>,
+    },
+
+    /// Show the journal of operations that changed the workspace
+    Log {
+        /// Only show operations since then (e.g. 30m, 3d, 2w, 2026-10-01)
+        #[arg(long, value_name = "WHEN")]
+        since: Option<String>,
+    },
+
+    /// Clean up workspace-wide leftovers
+    Gc {
+        /// Prune git's registrations of worktrees whose directories were
+        /// removed by hand, and drop them from their baum manifests
+        #[arg(long)]
+        worktrees: bool,
+
+        /// Show what would be cleaned up without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check the workspace and baum manifests for mistakes (e.g. in a
+    /// pre-commit hook)
+    Lint,
 
     /// Check workspace health and repair issues
     Doctor {
         /// Attempt to fix issues
         #[arg(long)]
         fix: bool,
+
+        /// Warn about worktrees with unpushed commits older than DAYS
+        #[arg(long, value_name = "DAYS")]
+        unpushed_days: Option<u64>,
+
+        /// Keep checking and serve the results on ADDR (/metrics, /health)
+        #[arg(long, value_name = "ADDR", conflicts_with = "fix")]
+        serve: Option<String>,
+
+        /// Write the results to a Prometheus textfile
+        #[arg(long, value_name = "PATH", conflicts_with = "fix")]
+        textfile: Option<PathBuf>,
+
+        /// Seconds between checks with --serve (default 300) or --textfile (default: once)
+        #[arg(long, value_name = "SECS")]
+        interval: Option<u64>,
+
+        /// With --fix: record the branch a worktree was switched to in the
+        /// manifest instead of switching it back
+        #[arg(long, requires = "fix")]
+        record_branch: bool,
+
+        /// Report only errors and exit non-zero if there are any (e.g. in a
+        /// pre-push hook)
+        #[arg(long, conflicts_with_all = ["fix", "serve", "textfile"])]
+        errors_only: bool,
+
+        /// Also look up every registered repo on GitHub/GitLab and warn
+        /// about archived, renamed or vanished ones
+        #[arg(long, conflicts_with_all = ["serve", "textfile"])]
+        forge: bool,
+    },
+
+    /// Reconnect worktrees and repos after moving the whole workspace
+    Relocate {
+        /// Where the workspace was before (default: found from git's links)
+        #[arg(long, value_name = "PATH")]
+        old_root: Option<PathBuf>,
     },
 
     /// Generate shell completion scripts
     Completion {
         /// Shell to generate completions for
         shell: Shell,
+
+        /// Write the script where the shell loads it from instead of
+        /// printing it (bash, zsh, fish)
+        #[arg(long)]
+        install: bool,
+
+        /// With --install: also install the `wald jump <repo> [branch]`
+        /// shell function that changes to a worktree
+        #[arg(long, requires = "install")]
+        wrapper: bool,
+
+        /// With --install: replace files that weren't installed by wald
+        #[arg(long, requires = "install")]
+        force: bool,
+    },
+
+    /// Generate documentation (man pages)
+    Docs {
+        #[command(subcommand)]
+        action: DocsAction,
+    },
+
+    /// Describe wald for tools built on it (editor extensions, scripts)
+    Api {
+        #[command(subcommand)]
+        action: ApiAction,
+    },
+
+    /// Helpers for testing wald and tools built on it
+    #[command(hide = true)]
+    Debug {
+        #[command(subcommand)]
+        action: DebugAction,
+    },
+
+    /// Show help for a command or concept (run without arguments for topics)
+    Help {
+        /// Command path (e.g., `repo add`) or topic name (e.g., `baum`)
+        #[arg(value_name = "TOPIC")]
+        topic: Vec<String>,
+    },
+
+    /// Update wald to the latest release
+    SelfUpdate {
+        /// Only check whether an update is available
+        #[arg(long)]
+        check: bool,
+
+        /// Reinstall even if already up to date
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Any other command runs the `wald-<name>` executable on PATH
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
+}
+
+#[derive(Subcommand)]
+enum DocsAction {
+    /// Generate man pages for wald and every subcommand
+    Man {
+        /// Directory to write pages to (default: print wald.1 to stdout)
+        #[arg(value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ApiAction {
+    /// List the commands and the fields of their versioned JSON output
+    Capabilities,
+}
+
+#[derive(Subcommand)]
+enum DebugAction {
+    /// Build an offline workspace with local remotes, registered repos and
+    /// planted baums
+    MakeFixture {
+        /// Directory to build in (missing or empty); the workspace ends up
+        /// in DIR/workspace
+        #[arg(value_name = "DIR")]
+        dir: PathBuf,
+
+        /// Number of repos to register and plant
+        #[arg(long, default_value_t = 2)]
+        repos: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum UndoAction {
+    /// Restore the most recently uprooted baum
+    #[command(visible_alias = "remove")]
+    Uproot,
+}
+
+#[derive(Subcommand)]
+enum TrashAction {
+    /// Permanently delete all uprooted baums
+    Empty,
+}
+
+#[derive(Subcommand)]
+enum HooksAction {
+    /// Check the manifests before each commit of the workspace repo
+    /// (`wald lint`) and the workspace before each push (`wald doctor
+    /// --errors-only`)
+    InstallWorkspace {
+        /// Replace hooks that wald didn't install
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PrAction {
+    /// Push the worktree's branch to `origin/<branch>` and open a pull (or
+    /// merge) request into the baum's default branch, printing its URL
+    ///
+    /// Needs GITHUB_TOKEN for github.com repos and GITLAB_TOKEN for others.
+    Create {
+        /// Any path inside the worktree
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Title (default: the last commit's subject)
+        #[arg(long)]
+        title: Option<String>,
+
+        /// Branch to merge into (default: the baum's default branch)
+        #[arg(long)]
+        base: Option<String>,
+
+        /// Open it as a draft
+        #[arg(long)]
+        draft: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AliasAction {
+    /// List aliases and the repositories they name
+    List {
+        /// Only this repository's (ID or alias)
+        repo: Option<String>,
+    },
+
+    /// Give a repository another alias
+    Add {
+        /// Repository ID or alias
+        repo: String,
+
+        /// New alias
+        alias: String,
+    },
+
+    /// Drop one of a repository's aliases
+    #[command(visible_alias = "rm")]
+    Remove {
+        /// Repository ID or alias
+        repo: String,
+
+        /// Alias to drop
+        alias: String,
     },
 }
 
@@ -184,8 +711,16 @@ enum Commands {
 enum RepoAction {
     /// Add a repository to the registry
     Add {
-        /// Repository ID (host/path, e.g., github.com/user/repo)
-        repo_id: String,
+        /// Repository ID (host/path, e.g., github.com/user/repo) or clone URL
+        repo_id: Option<String>,
+
+        /// Derive the repository ID from the current checkout's origin
+        #[arg(long)]
+        detect: bool,
+
+        /// With --detect: turn the current checkout into a baum at its path
+        #[arg(long)]
+        adopt: bool,
 
         /// LFS fetch policy
         #[arg(long, value_parser = parse_lfs)]
@@ -199,17 +734,62 @@ enum RepoAction {
         #[arg(long, value_parser = parse_filter)]
         filter: Option<FilterPolicy>,
 
+        /// Tags to fetch: all, reachable (only those in fetched history), or none
+        #[arg(long, value_parser = parse_tags)]
+        tags: Option<TagPolicy>,
+
         /// Upstream repository for fork tracking
         #[arg(long)]
         upstream: Option<String>,
 
+        /// Borrow objects from this registered repo's clone (git alternates)
+        #[arg(long, value_name = "REPO")]
+        reference: Option<String>,
+
         /// Short aliases for this repo
         #[arg(long = "alias", action = clap::ArgAction::Append)]
         aliases: Vec<String>,
 
+        /// Git config for the bare repo and new worktrees (repeatable)
+        #[arg(long = "git-config", value_name = "KEY=VALUE", value_parser = parse_git_config)]
+        git_config: Vec<(String, String)>,
+
+        /// Expected user.email for commits (checked by doctor, pinned on plant)
+        #[arg(long, value_name = "EMAIL")]
+        identity: Option<String>,
+
+        /// Local branches: tracking (wald/<baum>/<branch>) or direct (the
+        /// branch itself, for single-branch hosts; default for Overleaf)
+        #[arg(long, value_parser = parse_branching)]
+        branching: Option<Branching>,
+
+        /// Put the repo in a group, for `--group` on fetch, plant and status
+        #[arg(long, value_name = "NAME")]
+        group: Option<String>,
+
+        /// Seed the clone from a local clone of the repo: its objects are
+        /// copied, and only what it lacks is fetched from the remote
+        #[arg(long, value_name = "PATH", conflicts_with = "no_clone")]
+        mirror_of: Option<PathBuf>,
+
         /// Skip cloning (only add to manifest)
         #[arg(long)]
         no_clone: bool,
+
+        /// Clone a repo that is registered but whose clone is missing
+        /// (e.g. after a failed clone or `--no-clone`)
+        #[arg(long, conflicts_with_all = ["no_clone", "detect"])]
+        retry_clone: bool,
+
+        /// Register in .wald/manifest.d/<NAME>.yaml (e.g. a team's file);
+        /// by default, once manifest.d/ exists, in the file of the repo's host
+        #[arg(long, value_name = "NAME")]
+        manifest: Option<String>,
+
+        /// Estimate what cloning would download (remote refs, size from the
+        /// forge, effect of --depth/--filter) without writing anything
+        #[arg(long, conflicts_with_all = ["no_clone", "retry_clone", "adopt", "mirror_of"])]
+        dry_run: bool,
     },
 
     /// List registered repositories
@@ -221,14 +801,44 @@ enum RepoAction {
         repo: String,
     },
 
+    /// Move a repository's bare clone (e.g. to a larger disk), record it
+    /// as its `bare_path` and reconnect its worktrees
+    Relocate {
+        /// Repository ID or alias
+        repo: String,
+
+        /// New location of the bare clone
+        path: PathBuf,
+    },
+
     /// Fetch updates for repositories
     Fetch {
         /// Repository ID or alias (all if not specified)
         repo: Option<String>,
 
+        /// Fetch the repos of a group (see `group` in the manifest)
+        #[arg(long, value_name = "NAME", conflicts_with = "repo")]
+        group: Option<String>,
+
         /// Convert partial clones to full and fetch all objects
         #[arg(long)]
         full: bool,
+
+        /// Fetch every tag, whatever the repo's tag policy
+        #[arg(long)]
+        tags: bool,
+
+        /// Show transfer progress (objects and bytes received) while fetching
+        #[arg(long)]
+        watch: bool,
+
+        /// Stop a repo's fetch that takes longer than SECS
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+
+        /// Run the repos' post_fetch commands from the manifest afterwards
+        #[arg(long)]
+        post_fetch: bool,
     },
 
     /// Run garbage collection on repositories
@@ -239,6 +849,14 @@ enum RepoAction {
         /// Aggressive garbage collection (slower but more thorough)
         #[arg(long)]
         aggressive: bool,
+
+        /// Drop unreachable objects older than this (e.g. now, 2.weeks.ago)
+        #[arg(long, value_name = "WHEN")]
+        prune: Option<String>,
+
+        /// Also clean repos whose worktrees have unfinished rebases, merges or stashes
+        #[arg(long)]
+        force: bool,
     },
 }
 
@@ -276,8 +894,67 @@ fn parse_filter(s: &str) -> Result<FilterPolicy, String> {
     }
 }
 
+fn parse_tags(s: &str) -> Result<TagPolicy, String> {
+    match s.to_lowercase().as_str() {
+        "all" => Ok(TagPolicy::All),
+        "reachable" => Ok(TagPolicy::Reachable),
+        "none" => Ok(TagPolicy::None),
+        _ => Err(format!(
+            "Invalid tag policy: {}. Use all, reachable, or none",
+            s
+        )),
+    }
+}
+
+fn parse_branching(s: &str) -> Result<Branching, String> {
+    match s.to_lowercase().as_str() {
+        "tracking" => Ok(Branching::Tracking),
+        "direct" => Ok(Branching::Direct),
+        _ => Err(format!(
+            "Invalid branching mode: {}. Use tracking or direct",
+            s
+        )),
+    }
+}
+
+fn parse_export_format(s: &str) -> Result<ExportFormat, String> {
+    match s.to_lowercase().as_str() {
+        "json" => Ok(ExportFormat::Json),
+        "csv" => Ok(ExportFormat::Csv),
+        _ => Err(format!("Invalid format: {}. Use json or csv", s)),
+    }
+}
+
+fn parse_export_what(s: &str) -> Result<ExportWhat, String> {
+    match s.to_lowercase().as_str() {
+        "repos" => Ok(ExportWhat::Repos),
+        "baums" | "containers" => Ok(ExportWhat::Baums),
+        "worktrees" => Ok(ExportWhat::Worktrees),
+        _ => Err(format!(
+            "Invalid table: {}. Use repos, baums, or worktrees",
+            s
+        )),
+    }
+}
+
+fn parse_git_config(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if key.contains('.') && !key.starts_with('.') && !key.ends_with('.') => {
+            Ok((key.to_string(), value.to_string()))
+        }
+        _ => Err(format!(
+            "Invalid git config: {}. Use section.key=value (e.g. user.email=me@example.com)",
+            s
+        )),
+    }
+}
+
 fn main() -> ExitCode {
-    let cli = Cli::parse();
+    // Help is printed while parsing, before --workspace is known
+    let cli = parse_cli(vocabulary(None));
+
+    // Ctrl-C during a checkout removes the half-created worktree
+    interrupt::install_handler();
 
     let format = if cli.json {
         OutputFormat::Json
@@ -285,10 +962,26 @@ fn main() -> ExitCode {
         OutputFormat::Human
     };
 
-    let out = Output::new(format, cli.verbose);
+    let mut out = Output::new(format, cli.verbose);
+    out.assume_yes = cli.yes;
+    out.vocabulary = vocabulary(cli.workspace.as_deref());
+
+    let started = Instant::now();
+    let timings = cli.timings;
+    if timings {
+        wald::timings::enable();
+    }
+
+    let result = run(cli, &out);
+    if timings {
+        wald::timings::print_report(started.elapsed(), format == OutputFormat::Json);
+    }
 
-    if let Err(e) = run(cli, &out) {
-        print_error(&e);
+    if let Err(e) = result {
+        print_error(&e, out.vocabulary);
+        if PartialFailure::find(&e).is_some_and(|p| p.is_partial()) {
+            return ExitCode::from(PARTIAL_FAILURE_EXIT);
+        }
         return ExitCode::FAILURE;
     }
 
@@ -296,21 +989,80 @@ fn main() -> ExitCode {
 }
 
 fn run(cli: Cli, out: &Output) -> anyhow::Result<()> {
+    let Some(command) = cli.command else {
+        return run_dashboard(cli.workspace, out);
+    };
+
+    if cli.simulate && !can_simulate(&command) {
+        anyhow::bail!(
+            "--simulate works with repo add/remove/fetch/gc, branch, lock and unlock; \
+             other commands change files directly"
+        );
+    }
+
     // Handle commands that don't require an existing workspace
-    match &cli.command {
-        Commands::Completion { shell } => {
-            generate_completions(*shell);
-            return Ok(());
+    match &command {
+        Commands::Completion {
+            shell,
+            install,
+            wrapper,
+            force,
+        } => {
+            if !*install {
+                generate_completions(*shell, &mut std::io::stdout());
+                return Ok(());
+            }
+            let opts = commands::completion::CompletionInstallOptions {
+                shell: commands::completion::InstallShell::from_name(&shell.to_string())?,
+                wrapper: *wrapper,
+                force: *force,
+            };
+            let mut script = Vec::new();
+            generate_completions(*shell, &mut script);
+            return commands::completion::install_completion(
+                opts,
+                &String::from_utf8_lossy(&script),
+                out,
+            );
+        }
+        Commands::Docs { action } => match action {
+            DocsAction::Man { out_dir } => return generate_man_pages(out_dir.as_deref(), out),
+        },
+        Commands::Api { action } => match action {
+            ApiAction::Capabilities => {
+                return commands::api::api_capabilities(&command_paths(), out);
+            }
+        },
+        Commands::Debug { action } => match action {
+            DebugAction::MakeFixture { dir, repos } => {
+                let opts = commands::debug::FixtureOptions {
+                    dir: dir.clone(),
+                    repos: *repos,
+                };
+                return commands::debug::make_fixture(opts, out);
+            }
+        },
+        Commands::Help { topic } => return print_help(topic, out),
+        // Reads only the worktree, to stay fast enough for every prompt
+        Commands::Prompt { path } => {
+            let opts = commands::prompt::PromptOptions { path: path.clone() };
+            return commands::prompt(opts, out);
         }
         Commands::Init {
             path,
             force,
             no_git,
+            no_sync,
+            remote,
+            create_github,
         } => {
             let opts = commands::init::InitOptions {
                 path: path.clone(),
                 force: *force,
                 no_git: *no_git,
+                no_sync: *no_sync,
+                remote: remote.clone(),
+                create_github: create_github.clone(),
             };
             return commands::init(opts, out);
         }
@@ -321,49 +1073,397 @@ fn run(cli: Cli, out: &Output) -> anyhow::Result<()> {
             };
             return commands::clone(opts, out);
         }
+        Commands::SelfUpdate { check, force } => {
+            let opts = commands::self_update::SelfUpdateOptions {
+                check: *check,
+                force: *force,
+            };
+            return commands::self_update(opts, out);
+        }
+        // Plugins run inside or outside a workspace
+        Commands::External(args) => {
+            let name = args[0].to_str().unwrap_or_default();
+            let Some(path) = commands::plugin::find_plugin(name) else {
+                unknown_command(out);
+            };
+            // Only a workspace asked for must exist
+            let asked = cli.workspace.is_some() || std::env::var_os("WALD_WORKSPACE").is_some();
+            let workspace = match workspace_root(cli.workspace) {
+                Ok(root) => Some(root),
+                Err(_) if !asked => None,
+                Err(e) => return Err(e),
+            };
+            let opts = commands::plugin::PluginOptions {
+                args: args[1..].to_vec(),
+                workspace,
+            };
+            return commands::plugin::run_plugin(&path, opts, out);
+        }
         _ => {}
     }
 
-    // Load workspace for all other commands
-    let mut ws = Workspace::load()?;
+    let root = workspace_root(cli.workspace)?;
+
+    // Checks the manifests without loading them, so broken ones are reported
+    if let Commands::Lint = command {
+        return commands::lint(commands::lint::LintOptions { root }, out);
+    }
+
+    // Load workspace for all other commands; doctor can still restore
+    // damaged files from backups
+    let mut ws = match Workspace::load_from(root.clone()) {
+        Ok(ws) => ws,
+        Err(e) => {
+            return match command {
+                Commands::Doctor { fix, .. } => {
+                    commands::doctor::doctor_unloadable(&root, fix, e, out)
+                }
+                _ => Err(with_corrupt_files_hint(&root, e)),
+            };
+        }
+    };
+    ws.absolute_paths = cli.absolute_paths;
+
+    if cli.simulate {
+        let simulation = wald::git::runner::simulate();
+        run_in_workspace(command, &mut ws, out)?;
+        let skipped = simulation.skipped();
+        for action in &skipped {
+            out.status("Would run", action);
+        }
+        if skipped.is_empty() {
+            out.info("Simulated: nothing would change");
+        } else {
+            out.info("Simulated: nothing was changed");
+        }
+        return Ok(());
+    }
+
+    let journaled = journal_target(&command);
+    if let Some((command, _)) = &journaled
+        && ws.is_read_only()
+    {
+        anyhow::bail!(
+            "`wald {}` changes the workspace, but {} is read-only; \
+             commands that only read it (status, worktrees, log, ...) still work",
+            command,
+            ws.wald_dir().display()
+        );
+    }
+    let started_at = now_secs();
+    let started = Instant::now();
+
+    let result = run_in_workspace(command, &mut ws, out);
+
+    if let Some((command, paths)) = journaled {
+        let error = result.as_ref().err().map(|e| format!("{:#}", e));
+        if let Err(e) = notify_finished(&ws, command, started.elapsed(), error.as_deref()) {
+            out.warn(&format!("Failed to send notification: {:#}", e));
+        }
+        if let Err(e) = record_operation(&ws, command, &paths, started_at, started.elapsed(), error)
+        {
+            out.warn(&format!("Failed to record operation in journal: {}", e));
+        }
+    }
+
+    result
+}
+
+/// The vocabulary of `WALD_VOCABULARY`, else of the workspace's config
+/// (the workspace given, else WALD_WORKSPACE's or the current one)
+fn vocabulary(workspace: Option<&Path>) -> Vocabulary {
+    if let Some(vocabulary) = Vocabulary::from_env() {
+        return vocabulary;
+    }
+    match workspace_root(workspace.map(Path::to_path_buf)) {
+        Ok(root) => walk_config(&root).vocabulary,
+        Err(_) => Vocabulary::default(),
+    }
+}
+
+/// Parse the command line, with help worded in `vocabulary`
+fn parse_cli(vocabulary: Vocabulary) -> Cli {
+    use clap::FromArgMatches;
+
+    let matches = cli_command(vocabulary).get_matches();
+    Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
+}
+
+/// The command line definition, its help worded in `vocabulary`
+fn cli_command(vocabulary: Vocabulary) -> clap::Command {
+    use clap::CommandFactory;
+
+    let mut cmd = Cli::command();
+    if vocabulary == Vocabulary::Wald {
+        return cmd;
+    }
+    // Subcommands only get their arguments when built
+    cmd.build();
+    reword(cmd, vocabulary)
+}
+
+/// Reword the help of `cmd` and its subcommands and arguments
+fn reword(cmd: clap::Command, vocabulary: Vocabulary) -> clap::Command {
+    let words = |text: Option<&clap::builder::StyledStr>| {
+        text.map(|text| vocabulary.apply(&text.to_string()).into_owned())
+    };
+    let about = words(cmd.get_about());
+    let long_about = words(cmd.get_long_about());
+    let after_help = words(cmd.get_after_help());
+    let after_long_help = words(cmd.get_after_long_help());
+
+    let mut cmd = cmd
+        .mut_args(|mut arg| {
+            if let Some(help) = words(arg.get_help()) {
+                arg = arg.help(help);
+            }
+            if let Some(help) = words(arg.get_long_help()) {
+                arg = arg.long_help(help);
+            }
+            // clap only takes static value names
+            let value_names: Option<Vec<clap::builder::Str>> = arg.get_value_names().map(|names| {
+                names
+                    .iter()
+                    .map(|name| match name.as_str() {
+                        "BAUM" => "CONTAINER".into(),
+                        "BAUMS" => "CONTAINERS".into(),
+                        _ => name.clone(),
+                    })
+                    .collect()
+            });
+            match value_names {
+                Some(names) => arg.value_names(names),
+                None => arg,
+            }
+        })
+        .mut_subcommands(|sub| reword(sub, vocabulary));
+    if let Some(about) = about {
+        cmd = cmd.about(about);
+    }
+    if let Some(long_about) = long_about {
+        cmd = cmd.long_about(long_about);
+    }
+    if let Some(after_help) = after_help {
+        cmd = cmd.after_help(after_help);
+    }
+    if let Some(after_long_help) = after_long_help {
+        cmd = cmd.after_long_help(after_long_help);
+    }
+    cmd
+}
+
+/// Exit with clap's error for an unknown subcommand (naming similar
+/// ones), as no plugin goes by that name either
+fn unknown_command(out: &Output) -> ! {
+    // Built first, as the derive only allows external subcommands when
+    // the command is built
+    let mut cmd = cli_command(out.vocabulary);
+    cmd.build();
+    let _ = cmd.allow_external_subcommands(false).get_matches();
+    // Unreachable unless clap accepts what it just rejected
+    std::process::exit(2);
+}
+
+/// The workspace given by `--workspace` or WALD_WORKSPACE, else the one
+/// containing the current directory
+fn workspace_root(workspace: Option<PathBuf>) -> anyhow::Result<PathBuf> {
+    match workspace.or_else(|| std::env::var_os("WALD_WORKSPACE").map(PathBuf::from)) {
+        Some(dir) => find_workspace_root_from(&dir),
+        None => find_workspace_root(),
+    }
+}
+
+/// Point at `wald doctor --fix` when damaged YAML files kept the
+/// workspace from loading
+fn with_corrupt_files_hint(root: &Path, error: anyhow::Error) -> anyhow::Error {
+    match commands::doctor::corrupt_files_hint(root) {
+        Some(hint) => error.context(hint),
+        None => error,
+    }
+}
+
+/// `wald` without a command: the workspace summary, or the help outside a
+/// workspace and with `dashboard: false` in .wald/config.yaml
+fn run_dashboard(workspace: Option<PathBuf>, out: &Output) -> anyhow::Result<()> {
+    let Ok(root) = workspace_root(workspace) else {
+        return print_help(&[], out);
+    };
+    let ws = Workspace::load_from(root.clone()).map_err(|e| with_corrupt_files_hint(&root, e))?;
+    if !ws.config.dashboard {
+        return print_help(&[], out);
+    }
+    commands::dashboard(&ws, out)
+}
+
+/// Whether a command's changes all go through git and the YAML files, so
+/// `--simulate` can hold them back
+fn can_simulate(command: &Commands) -> bool {
+    match command {
+        Commands::Repo { action } => match action {
+            RepoAction::Add { adopt, .. } => !adopt,
+            RepoAction::Remove { .. } | RepoAction::Fetch { .. } | RepoAction::Gc { .. } => true,
+            RepoAction::List | RepoAction::Relocate { .. } => false,
+        },
+        Commands::Alias { action } => !matches!(action, AliasAction::List { .. }),
+        Commands::Branch { .. } | Commands::Lock { .. } | Commands::Unlock { .. } => true,
+        _ => false,
+    }
+}
+
+/// Journal name and path arguments of a command that changes the workspace
+///
+/// Returns None for read-only commands, which are not journaled.
+fn journal_target(command: &Commands) -> Option<(&'static str, Vec<PathBuf>)> {
+    let target = match command {
+        Commands::Repo { action } => match action {
+            RepoAction::Add { dry_run: true, .. } => return None,
+            RepoAction::Add { .. } => ("repo add", vec![]),
+            RepoAction::Remove { .. } => ("repo remove", vec![]),
+            RepoAction::Fetch { .. } => ("repo fetch", vec![]),
+            RepoAction::Gc { .. } => ("repo gc", vec![]),
+            RepoAction::Relocate { .. } => ("repo relocate", vec![]),
+            RepoAction::List => return None,
+        },
+        Commands::Alias { action } => match action {
+            AliasAction::Add { .. } => ("alias add", vec![]),
+            AliasAction::Remove { .. } => ("alias remove", vec![]),
+            AliasAction::List { .. } => return None,
+        },
+        // The container comes from the template
+        Commands::Plant {
+            template: Some(_), ..
+        } => ("plant", vec![]),
+        Commands::Plant {
+            repo,
+            group: Some(_),
+            ..
+        } => ("plant", repo.iter().map(PathBuf::from).collect()),
+        // The baum is the one around the current directory
+        Commands::Plant {
+            repo: Some(repo), ..
+        } if repo == commands::plant::HERE => ("plant", vec![PathBuf::from(".")]),
+        Commands::Plant { container, .. } => ("plant", container.iter().cloned().collect()),
+        Commands::Uproot { path, .. } => ("uproot", vec![path.clone()]),
+        Commands::Undo { action } => match action {
+            UndoAction::Uproot => ("undo uproot", vec![]),
+        },
+        Commands::Trash { action } => match action {
+            TrashAction::Empty => ("trash empty", vec![]),
+        },
+        Commands::AdoptWorktree { baum, .. } => ("adopt-worktree", vec![baum.clone()]),
+        Commands::Move { old_path, new_path } => ("move", vec![old_path.clone(), new_path.clone()]),
+        Commands::Branch { baum, .. } => ("branch", vec![baum.clone()]),
+        Commands::Prune { dry_run: true, .. } => return None,
+        Commands::Prune {
+            baum, scope_baum, ..
+        } => ("prune", baum.iter().chain(scope_baum).cloned().collect()),
+        Commands::Pull { baum, .. } => ("pull", vec![baum.clone()]),
+        Commands::Lock { baum, .. } => ("lock", vec![baum.clone()]),
+        Commands::Unlock { baum, .. } => ("unlock", vec![baum.clone()]),
+        Commands::Sync { dry_run: false, .. } => ("sync", vec![]),
+        Commands::Gc { dry_run: false, .. } => ("gc", vec![]),
+        Commands::Doctor { fix: true, .. } => ("doctor", vec![]),
+        Commands::Relocate { .. } => ("relocate", vec![]),
+        _ => return None,
+    };
+    Some(target)
+}
 
-    match cli.command {
+fn run_in_workspace(command: Commands, ws: &mut Workspace, out: &Output) -> anyhow::Result<()> {
+    match command {
         Commands::Repo { action } => match action {
             RepoAction::Add {
                 repo_id,
+                detect,
+                adopt,
                 lfs,
                 depth,
                 filter,
+                tags,
                 upstream,
+                reference,
                 aliases,
+                git_config,
+                identity,
+                branching,
+                group,
+                mirror_of,
                 no_clone,
+                retry_clone,
+                manifest,
+                dry_run,
             } => {
+                if detect == repo_id.is_some() {
+                    anyhow::bail!("pass either a repository ID or --detect");
+                }
+                if adopt && !detect {
+                    anyhow::bail!("--adopt requires --detect");
+                }
                 let opts = commands::repo::RepoAddOptions {
-                    repo_id,
+                    repo_id: repo_id.unwrap_or_default(),
+                    detect,
+                    adopt,
                     lfs,
                     depth,
                     filter,
+                    tags,
                     upstream,
+                    reference,
                     aliases,
+                    git_config: git_config.into_iter().collect(),
+                    identity,
+                    branching,
+                    group,
+                    mirror_of,
                     clone: !no_clone, // Clone by default, --no-clone skips
+                    retry_clone,
+                    manifest_file: manifest,
+                    dry_run,
                 };
-                commands::repo_add(&mut ws, opts, out)
+                commands::repo_add(ws, opts, out)
             }
-            RepoAction::List => commands::repo_list(&ws, out),
-            RepoAction::Remove { repo } => commands::repo_remove(&mut ws, &repo, out),
-            RepoAction::Fetch { repo, full } => {
+            RepoAction::List => commands::repo_list(ws, out),
+            RepoAction::Remove { repo } => commands::repo_remove(ws, &repo, out),
+            RepoAction::Relocate { repo, path } => {
+                let opts = commands::repo::RepoRelocateOptions {
+                    repo_ref: repo,
+                    path,
+                };
+                commands::repo_relocate(ws, opts, out)
+            }
+            RepoAction::Fetch {
+                repo,
+                group,
+                full,
+                tags,
+                watch,
+                timeout,
+                post_fetch,
+            } => {
                 let opts = commands::repo::RepoFetchOptions {
                     repo_ref: repo,
+                    group,
                     full,
+                    tags,
+                    watch,
+                    timeout: timeout.map(std::time::Duration::from_secs),
+                    post_fetch,
                 };
-                commands::repo_fetch(&mut ws, opts, out)
+                commands::repo_fetch(ws, opts, out)
             }
-            RepoAction::Gc { repo, aggressive } => {
+            RepoAction::Gc {
+                repo,
+                aggressive,
+                prune,
+                force,
+            } => {
                 let opts = commands::repo::RepoGcOptions {
                     repo_ref: repo,
                     aggressive,
+                    prune,
+                    force,
                 };
-                commands::repo_gc(&ws, opts, out)
+                commands::repo_gc(ws, opts, out)
             }
         },
 
@@ -371,42 +1471,146 @@ fn run(cli: Cli, out: &Output) -> anyhow::Result<()> {
             repo,
             container,
             branches,
+            file,
+            group,
+            template,
             force,
+            yes_protected,
             reuse,
+            continue_on_conflict,
+            track,
+            into_existing,
+            at,
         } => {
+            // Without a repo or container argument, what clap parsed as
+            // one of them is the container or a branch
+            let container_arg = container.as_ref().map(|c| c.to_string_lossy().into_owned());
+            if let Some(group) = group {
+                let mut args = repo.into_iter().chain(container_arg).chain(branches);
+                let container = match template {
+                    Some(_) => None,
+                    None => args.next().map(PathBuf::from),
+                };
+                let opts = commands::plant::PlantGroupOptions {
+                    group,
+                    container,
+                    template,
+                    branches: args.collect(),
+                    force,
+                    yes_protected,
+                    reuse,
+                    continue_on_conflict,
+                };
+                return commands::plant_group(ws, opts, out);
+            }
+            if template.is_some() {
+                let opts = commands::plant::PlantOptions {
+                    repo_ref: repo.unwrap_or_default(),
+                    container: PathBuf::new(),
+                    branches: container_arg.into_iter().chain(branches).collect(),
+                    force,
+                    yes_protected,
+                    reuse,
+                    track,
+                    into_existing,
+                    template,
+                    continue_on_conflict,
+                    at,
+                };
+                return commands::plant(ws, opts, out);
+            }
+            if let Some(file) = file {
+                let opts = commands::plant::PlantFileOptions {
+                    file,
+                    force,
+                    yes_protected,
+                    reuse,
+                    continue_on_conflict,
+                };
+                return commands::plant_file(ws, opts, out);
+            }
+            // `wald plant . <branch>...`: the baum comes from the current
+            // directory, so every other argument is a branch
+            if repo.as_deref() == Some(commands::plant::HERE) {
+                let opts = commands::plant::PlantOptions {
+                    repo_ref: commands::plant::HERE.to_string(),
+                    container: PathBuf::new(),
+                    branches: container_arg.into_iter().chain(branches).collect(),
+                    force,
+                    yes_protected,
+                    reuse,
+                    track,
+                    into_existing,
+                    template: None,
+                    continue_on_conflict,
+                    at,
+                };
+                return commands::plant(ws, opts, out);
+            }
             let opts = commands::plant::PlantOptions {
-                repo_ref: repo,
-                container,
+                repo_ref: repo.unwrap_or_default(),
+                container: container.unwrap_or_default(),
                 branches,
                 force,
+                yes_protected,
                 reuse,
+                track,
+                into_existing,
+                template: None,
+                continue_on_conflict,
+                at,
             };
-            commands::plant(&mut ws, opts, out)
+            commands::plant(ws, opts, out)
         }
 
         Commands::Uproot { path, force } => {
             let opts = commands::uproot::UprootOptions { path, force };
-            commands::uproot(&ws, opts, out)
+            commands::uproot(ws, opts, out)
+        }
+
+        Commands::Undo { action } => match action {
+            UndoAction::Uproot => commands::undo_uproot(ws, out),
+        },
+
+        Commands::Trash { action } => match action {
+            TrashAction::Empty => commands::trash_empty(ws, out),
+        },
+
+        Commands::AdoptWorktree {
+            baum,
+            path,
+            rename_branch,
+        } => {
+            let opts = commands::adopt_worktree::AdoptWorktreeOptions {
+                baum_path: baum,
+                worktree_path: path,
+                rename_branch,
+            };
+            commands::adopt_worktree(ws, opts, out)
         }
 
         Commands::Move { old_path, new_path } => {
             let opts = commands::move_cmd::MoveOptions { old_path, new_path };
-            commands::move_baum(&ws, opts, out)
+            commands::move_baum(ws, opts, out)
         }
 
         Commands::Branch {
             baum,
-            branch,
+            branches,
             force,
+            yes_protected,
             reuse,
+            track,
         } => {
             let opts = commands::branch::BranchOptions {
                 baum_path: baum,
-                branch,
+                branches,
                 force,
+                yes_protected,
                 reuse,
+                track,
             };
-            commands::branch(&ws, opts, out)
+            commands::branch(ws, opts, out)
         }
 
         Commands::Prune {
@@ -414,22 +1618,137 @@ fn run(cli: Cli, out: &Output) -> anyhow::Result<()> {
             branches,
             force,
             cleanup_branches,
+            yes_protected,
+            scope_repo,
+            scope_baum,
+            dry_run,
         } => {
             if cleanup_branches {
-                commands::prune_branches(&ws, force, out)
+                let opts = commands::prune::PruneBranchesOptions {
+                    repo_ref: scope_repo,
+                    baum_path: scope_baum,
+                    force,
+                    yes_protected,
+                    dry_run,
+                };
+                commands::prune_branches(ws, opts, out)
             } else {
+                if scope_repo.is_some() || scope_baum.is_some() || dry_run {
+                    anyhow::bail!("--repo, --baum and --dry-run require --branches");
+                }
                 let opts = commands::prune::PruneOptions {
                     baum_path: baum.expect("baum required"),
                     branches,
                     force,
                 };
-                commands::prune(&ws, opts, out)
+                commands::prune(ws, opts, out)
+            }
+        }
+
+        Commands::Pull { baum, rebase } => {
+            let opts = commands::pull::PullOptions {
+                baum_path: baum,
+                rebase,
+            };
+            commands::pull(ws, opts, out)
+        }
+
+        Commands::Pr { action } => match action {
+            PrAction::Create {
+                path,
+                title,
+                base,
+                draft,
+            } => {
+                let opts = commands::pr::PrCreateOptions {
+                    path,
+                    title,
+                    base,
+                    draft,
+                };
+                commands::pr_create(ws, opts, out)
             }
+        },
+
+        Commands::Lock {
+            baum,
+            branch,
+            reason,
+        } => {
+            let opts = commands::lock::LockOptions {
+                baum_path: baum,
+                branch,
+                reason,
+            };
+            commands::lock(ws, opts, out)
+        }
+
+        Commands::Unlock { baum, branch } => {
+            let opts = commands::lock::UnlockOptions {
+                baum_path: baum,
+                branch,
+            };
+            commands::unlock(ws, opts, out)
         }
 
-        Commands::Worktrees { filter } => {
-            let opts = commands::worktrees::WorktreesOptions { filter };
-            commands::worktrees(&ws, opts, out)
+        Commands::Worktrees {
+            filter,
+            repo_ref,
+            verify,
+            stale,
+            mine,
+        } => {
+            let opts = commands::worktrees::WorktreesOptions {
+                filter,
+                repo_ref,
+                verify,
+                stale,
+                mine,
+            };
+            commands::worktrees(ws, opts, out)
+        }
+
+        Commands::Which { repo, branch } => {
+            let opts = commands::worktrees::WhichOptions {
+                repo_ref: repo,
+                branch,
+            };
+            commands::which(ws, opts, out)
+        }
+
+        Commands::Here { path } => {
+            let opts = commands::here::HereOptions { path };
+            commands::here(ws, opts, out)
+        }
+
+        Commands::Env { path } => {
+            let opts = commands::env::EnvOptions { path };
+            commands::env(ws, opts, out)
+        }
+
+        Commands::Grep {
+            pattern,
+            repo_ref,
+            branch,
+        } => {
+            let opts = commands::grep::GrepOptions {
+                pattern,
+                repo_ref,
+                branch,
+            };
+            commands::grep(ws, opts, out)
+        }
+
+        Commands::Tags { repo } => commands::tags(ws, &repo, out),
+        Commands::Alias { action } => match action {
+            AliasAction::List { repo } => commands::alias_list(ws, repo.as_deref(), out),
+            AliasAction::Add { repo, alias } => commands::alias_add(ws, &repo, &alias, out),
+            AliasAction::Remove { repo, alias } => commands::alias_remove(ws, &repo, &alias, out),
+        },
+
+        Commands::Export { format, what } => {
+            let opts = commands::export::ExportOptions { format, what };
+            commands::export(ws, opts, out)
         }
 
         Commands::Sync {
@@ -437,34 +1756,198 @@ fn run(cli: Cli, out: &Output) -> anyhow::Result<()> {
             force,
             push,
             offline,
+            abort_on_conflict: _,
+            interactive,
+            mine,
+            all,
         } => {
             let opts = commands::sync::SyncOptions {
                 dry_run,
                 force,
                 push,
                 offline,
+                mine,
+                all,
+                on_conflict: if interactive {
+                    commands::sync::ConflictMode::Resolve
+                } else {
+                    commands::sync::ConflictMode::Abort
+                },
+            };
+            commands::sync(ws, opts, out)
+        }
+
+        Commands::Status { mine, group } => {
+            let opts = commands::status::StatusOptions { mine, group };
+            commands::status(ws, opts, out)
+        }
+
+        Commands::Log { since } => {
+            let opts = commands::log::LogOptions { since };
+            commands::log(ws, opts, out)
+        }
+
+        Commands::Gc { worktrees, dry_run } => {
+            let opts = commands::gc::GcOptions { worktrees, dry_run };
+            commands::gc(ws, opts, out)
+        }
+
+        Commands::Doctor {
+            fix,
+            unpushed_days,
+            serve,
+            textfile,
+            interval,
+            record_branch,
+            errors_only,
+            forge,
+        } => {
+            let opts = commands::doctor::DoctorOptions {
+                fix,
+                unpushed_days,
+                serve,
+                textfile,
+                interval,
+                record_branch,
+                errors_only,
+                forge,
             };
-            commands::sync(&mut ws, opts, out)
+            commands::doctor(ws, opts, out)
         }
 
-        Commands::Status => commands::status(&ws, out),
+        Commands::Hooks { action } => match action {
+            HooksAction::InstallWorkspace { force } => {
+                let opts = commands::hooks::HooksInstallOptions { force };
+                commands::hooks_install_workspace(ws, opts, out)
+            }
+        },
 
-        Commands::Doctor { fix } => {
-            let opts = commands::doctor::DoctorOptions { fix };
-            commands::doctor(&ws, opts, out)
+        Commands::Relocate { old_root } => {
+            let opts = commands::relocate::RelocateOptions { old_root };
+            commands::relocate(ws, opts, out)
         }
 
         Commands::Init { .. } => unreachable!(),
         Commands::Clone { .. } => unreachable!(),
         Commands::Completion { .. } => unreachable!(),
+        Commands::Docs { .. } => unreachable!(),
+        Commands::Api { .. } => unreachable!(),
+        Commands::Debug { .. } => unreachable!(),
+        Commands::External(_) => unreachable!(),
+        Commands::Prompt { .. } => unreachable!(),
+        Commands::Help { .. } => unreachable!(),
+        Commands::SelfUpdate { .. } => unreachable!(),
+        Commands::Lint => unreachable!(),
     }
 }
 
-fn generate_completions(shell: Shell) {
+fn generate_completions(shell: Shell, buf: &mut dyn std::io::Write) {
     use clap::CommandFactory;
     use clap_complete::generate;
 
     let mut cmd = Cli::command();
     let name = cmd.get_name().to_string();
-    generate(shell, &mut cmd, name, &mut std::io::stdout());
+    generate(shell, &mut cmd, name, buf);
+}
+
+fn generate_man_pages(out_dir: Option<&Path>, out: &Output) -> anyhow::Result<()> {
+    use anyhow::Context;
+
+    let Some(dir) = out_dir else {
+        let man = clap_mangen::Man::new(cli_command(out.vocabulary));
+        man.render(&mut std::io::stdout())?;
+        return Ok(());
+    };
+
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create directory: {}", dir.display()))?;
+    clap_mangen::generate_to(cli_command(out.vocabulary), dir)
+        .with_context(|| format!("failed to write man pages to {}", dir.display()))?;
+
+    out.success(&format!("Wrote man pages to {}", dir.display()));
+    Ok(())
+}
+
+/// Every command path (e.g. `repo add`), in the order of `wald --help`
+fn command_paths() -> Vec<String> {
+    use clap::CommandFactory;
+
+    fn walk(cmd: &clap::Command, prefix: &str, paths: &mut Vec<String>) {
+        for sub in cmd.get_subcommands().filter(|sub| sub.get_name() != "help") {
+            let path = format!("{}{}", prefix, sub.get_name());
+            paths.push(path.clone());
+            walk(sub, &format!("{} ", path), paths);
+        }
+    }
+
+    let mut paths = Vec::new();
+    walk(&Cli::command(), "", &mut paths);
+    paths
+}
+
+/// Print help for a subcommand path or a concept topic
+fn print_help(topic: &[String], out: &Output) -> anyhow::Result<()> {
+    let mut cmd = cli_command(out.vocabulary);
+    cmd.build();
+
+    if topic.is_empty() {
+        cmd.print_long_help()?;
+        println!();
+        print_topic_list(out);
+        return Ok(());
+    }
+
+    let name = topic.join(" ");
+    if let Some(page) = topics::find_topic(&name) {
+        println!("{}", out.words(page.body));
+        return Ok(());
+    }
+
+    let mut current = &mut cmd;
+    for sub_name in topic {
+        let Some(sub) = current.find_subcommand_mut(sub_name) else {
+            print_topic_list(out);
+            anyhow::bail!("no help topic or command named '{}'", name);
+        };
+        current = sub;
+    }
+    current.print_long_help()?;
+    Ok(())
+}
+
+fn print_topic_list(out: &Output) {
+    println!("Concepts (wald help <topic>):");
+    for page in topics::TOPICS {
+        println!(
+            "  {:<10}  {}",
+            out.words(page.name),
+            out.words(page.summary)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_topic_names_distinct_from_commands() {
+        // Topics shadow subcommands in `wald help`, so they must not collide
+        let cmd = Cli::command();
+        for sub in cmd.get_subcommands() {
+            for name in std::iter::once(sub.get_name()).chain(sub.get_all_aliases()) {
+                assert!(
+                    topics::find_topic(name).is_none(),
+                    "topic shadows command {}",
+                    name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
 }
@@ -9,6 +9,8 @@
 //! This module provides normalization to create safe worktree directory names
 //! while preserving the original branch name in the manifest.
 
+use std::collections::HashSet;
+
 /// Normalize a branch name for use as a directory component
 ///
 /// Transformations:
@@ -62,6 +64,39 @@ pub fn worktree_dir_name(branch: &str) -> String {
     format!("_{}.wt", normalized)
 }
 
+/// Generate a worktree directory name that isn't in `taken`
+///
+/// Distinct branches can normalize to the same name (`feature/foo` and
+/// `feature--foo`). The later one gets a short hash of its branch name
+/// appended, e.g. `_feature--foo-3f9a2c.wt`; the baum manifest records which
+/// branch lives where.
+pub fn unique_worktree_dir_name(branch: &str, taken: &HashSet<String>) -> String {
+    let name = worktree_dir_name(branch);
+    if !taken.contains(&name) {
+        return name;
+    }
+
+    let normalized = normalize_branch_for_path(branch);
+    (0..)
+        .map(|salt| format!("_{}-{}.wt", normalized, short_hash(branch, salt)))
+        .find(|name| !taken.contains(name))
+        .expect("some salt gives a free name")
+}
+
+/// Six hex digits of the FNV-1a hash of `s` (stable across runs and builds)
+///
+/// A nonzero `salt` gives another hash for the same string.
+fn short_hash(s: &str, salt: u32) -> String {
+    let mut bytes = s.as_bytes().to_vec();
+    if salt > 0 {
+        bytes.extend(salt.to_le_bytes());
+    }
+    let hash = bytes.iter().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+    });
+    format!("{:06x}", hash & 0xff_ffff)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,6 +159,26 @@ mod tests {
         assert_eq!(worktree_dir_name("feature/foo"), "_feature--foo.wt");
     }
 
+    #[test]
+    fn test_unique_worktree_dir_name() {
+        let mut taken = HashSet::new();
+        assert_eq!(
+            unique_worktree_dir_name("feature/foo", &taken),
+            "_feature--foo.wt"
+        );
+
+        taken.insert("_feature--foo.wt".to_string());
+        let name = unique_worktree_dir_name("feature--foo", &taken);
+        assert!(name.starts_with("_feature--foo-") && name.ends_with(".wt"));
+        assert_ne!(name, "_feature--foo.wt");
+        // Same branch, same name
+        assert_eq!(unique_worktree_dir_name("feature--foo", &taken), name);
+
+        taken.insert(name.clone());
+        let next = unique_worktree_dir_name("feature--foo", &taken);
+        assert!(!taken.contains(&next));
+    }
+
     #[test]
     fn test_preserves_dots_underscores() {
         assert_eq!(normalize_branch_for_path("release_1.0"), "release_1.0");
@@ -1,6 +1,20 @@
-use std::io::{self, Write};
+use std::borrow::Cow;
+use std::io::{self, IsTerminal, Write};
+use std::time::Duration;
 
 use anyhow::{Result, bail};
+use serde::Serialize;
+
+use crate::humanize::{format_size, relative_time, short_sha};
+use crate::time::{format_iso, now_secs};
+use crate::vocabulary::Vocabulary;
+
+/// Version of the JSON documents printed by `worktrees`, `status`,
+/// `repo list` and `doctor` (see `wald api capabilities`)
+///
+/// Adding fields keeps the version; renaming, removing or retyping one
+/// bumps it.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
 
 /// Output format
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -14,6 +28,10 @@ pub enum OutputFormat {
 pub struct Output {
     pub format: OutputFormat,
     pub verbose: bool,
+    /// Answer every confirmation with yes (`--yes`)
+    pub assume_yes: bool,
+    /// Words for baums and their commands in messages and JSON
+    pub vocabulary: Vocabulary,
 }
 
 impl Default for Output {
@@ -21,13 +39,85 @@ impl Default for Output {
         Self {
             format: OutputFormat::Human,
             verbose: false,
+            assume_yes: false,
+            vocabulary: Vocabulary::default(),
         }
     }
 }
 
 impl Output {
     pub fn new(format: OutputFormat, verbose: bool) -> Self {
-        Self { format, verbose }
+        Self {
+            format,
+            verbose,
+            assume_yes: false,
+            vocabulary: Vocabulary::default(),
+        }
+    }
+
+    /// `text` in the output's vocabulary, for what commands print
+    /// themselves
+    pub fn words<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        self.vocabulary.apply(text)
+    }
+
+    /// A commit SHA for people: short, or in full with `--verbose`
+    pub fn sha<'a>(&self, sha: &'a str) -> &'a str {
+        if self.verbose { sha } else { short_sha(sha) }
+    }
+
+    /// A point in time (seconds since the Unix epoch) for people: `3 days
+    /// ago`, or a UTC timestamp with `--verbose`
+    pub fn time(&self, secs: u64) -> String {
+        if self.verbose {
+            format_iso(secs)
+        } else {
+            relative_time(secs, now_secs())
+        }
+    }
+
+    /// A size in bytes for people: `12.3 MiB`, or the exact count with
+    /// `--verbose`
+    pub fn size(&self, bytes: u64) -> String {
+        if self.verbose {
+            format!("{} bytes", bytes)
+        } else {
+            format_size(bytes)
+        }
+    }
+
+    /// Print a command's JSON document, its field names in the output's
+    /// vocabulary
+    pub fn print_json<T: Serialize>(&self, value: &T) -> Result<()> {
+        let value = self.vocabulary.rename_fields(serde_json::to_value(value)?);
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        Ok(())
+    }
+
+    /// Ask before a destructive step, e.g. `confirm("delete 2 branch(es)
+    /// with unpushed commits")`; true if the user agreed
+    ///
+    /// `--yes` agrees without asking. Without a terminal to ask on, and
+    /// without `--yes`, this fails rather than guess.
+    pub fn confirm(&self, action: &str) -> Result<bool> {
+        if self.assume_yes {
+            return Ok(true);
+        }
+        if !io::stdin().is_terminal() {
+            bail!(
+                "refusing to {} without confirmation; pass --yes to proceed",
+                self.words(action)
+            );
+        }
+
+        eprint!("Really {}? [y/N] ", self.words(action));
+        self.flush();
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        Ok(matches!(
+            answer.trim().to_ascii_lowercase().as_str(),
+            "y" | "yes"
+        ))
     }
 
     /// Check that JSON output is not requested for commands that don't support it.
@@ -43,35 +133,69 @@ impl Output {
     pub fn status(&self, action: &str, target: &str) {
         if self.format == OutputFormat::Human {
             // Right-align action in 12 chars, like cargo does
-            eprintln!("{:>12} {}", action, target);
+            eprintln!("{:>12} {}", self.words(action), self.words(target));
         }
     }
 
     /// Print a success message
     pub fn success(&self, message: &str) {
         if self.format == OutputFormat::Human {
-            eprintln!("{:>12} {}", "Done", message);
+            eprintln!("{:>12} {}", "Done", self.words(message));
         }
     }
 
     /// Print an info message
     pub fn info(&self, message: &str) {
         if self.format == OutputFormat::Human {
-            eprintln!("{}", message);
+            eprintln!("{}", self.words(message));
         }
     }
 
     /// Print a warning message
     pub fn warn(&self, message: &str) {
         if self.format == OutputFormat::Human {
-            eprintln!("{:>12} {}", "Warning", message);
+            eprintln!("{:>12} {}", "Warning", self.words(message));
         }
     }
 
     /// Print a verbose message (only if verbose mode is on)
     pub fn verbose(&self, message: &str) {
         if self.verbose && self.format == OutputFormat::Human {
-            eprintln!("{}", message);
+            eprintln!("{}", self.words(message));
+        }
+    }
+
+    /// Report a failed network operation that is about to be retried
+    pub fn retrying(&self, err: &anyhow::Error, wait: Duration) {
+        // The rest of git's output is advice that repeats on every try
+        let message = format!("{:#}", err);
+        let reason = message.lines().next().unwrap_or_default().trim();
+        self.status(
+            "Retrying",
+            &format!("in {}s after: {}", wait.as_secs().max(1), reason),
+        );
+    }
+
+    /// Whether to stream progress from long-running git operations
+    pub fn show_progress(&self) -> bool {
+        self.format == OutputFormat::Human && io::stderr().is_terminal()
+    }
+
+    /// Show a progress line of a long git operation (e.g. `Receiving
+    /// objects:  45% ...`), in place on a terminal; elsewhere only the
+    /// finished steps are printed
+    pub fn progress(&self, line: &str) {
+        if self.format != OutputFormat::Human {
+            return;
+        }
+        let finished = line.ends_with("done.");
+        if io::stderr().is_terminal() {
+            eprint!("\r{:>12} {}\x1b[K", "", line);
+            if finished {
+                eprintln!();
+            }
+        } else if finished {
+            eprintln!("{:>12} {}", "", line);
         }
     }
 
@@ -81,12 +205,12 @@ impl Output {
     }
 }
 
-/// Print an error message to stderr
-pub fn print_error(err: &anyhow::Error) {
-    eprintln!("error: {}", err);
+/// Print an error message to stderr, in `vocabulary`
+pub fn print_error(err: &anyhow::Error, vocabulary: Vocabulary) {
+    eprintln!("error: {}", vocabulary.apply(&err.to_string()));
 
     // Print cause chain
     for cause in err.chain().skip(1) {
-        eprintln!("  caused by: {}", cause);
+        eprintln!("  caused by: {}", vocabulary.apply(&cause.to_string()));
     }
 }
@@ -0,0 +1,126 @@
+//! Tiny HTTP endpoint for `wald doctor --serve`
+//!
+//! Serves the latest health snapshot to monitoring: `/metrics` in the
+//! Prometheus text format and `/health` as JSON, answering 503 while the
+//! workspace has errors. One request at a time is plenty for a scraper.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+/// Slow or stuck clients must not block the next scrape for long
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What the endpoint currently reports
+#[derive(Debug, Clone, Default)]
+pub struct HealthSnapshot {
+    /// Prometheus text exposition
+    pub metrics: String,
+    /// JSON health summary
+    pub health: String,
+    /// Whether the last check found no errors
+    pub healthy: bool,
+}
+
+/// Snapshot shared between the check loop and the server thread
+pub type SharedSnapshot = Arc<Mutex<HealthSnapshot>>;
+
+/// Start serving `snapshot` on `addr` in a background thread
+///
+/// Returns the bound address (useful with port 0).
+pub fn spawn(addr: &str, snapshot: SharedSnapshot) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(addr).with_context(|| format!("failed to bind {}", addr))?;
+    let local = listener.local_addr()?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // A broken connection only affects that one client
+            let _ = handle(stream, &snapshot);
+        }
+    });
+
+    Ok(local)
+}
+
+fn handle(mut stream: TcpStream, snapshot: &SharedSnapshot) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+    let path = path.split('?').next().unwrap_or_default();
+
+    let snapshot = snapshot.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/metrics") => ("200 OK", "text/plain; version=0.0.4", snapshot.metrics),
+        ("GET", "/health" | "/") if snapshot.healthy => {
+            ("200 OK", "application/json", snapshot.health)
+        }
+        ("GET", "/health" | "/") => (
+            "503 Service Unavailable",
+            "application/json",
+            snapshot.health,
+        ),
+        ("GET", _) => ("404 Not Found", "text/plain", "not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n".to_string(),
+        ),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_serves_snapshot() {
+        let snapshot = SharedSnapshot::default();
+        let addr = spawn("127.0.0.1:0", snapshot.clone()).unwrap();
+
+        *snapshot.lock().unwrap() = HealthSnapshot {
+            metrics: "wald_up 1\n".to_string(),
+            health: "{\"healthy\":false}".to_string(),
+            healthy: false,
+        };
+
+        let metrics = get(addr, "/metrics");
+        assert!(metrics.starts_with("HTTP/1.1 200 OK"));
+        assert!(metrics.ends_with("\r\n\r\nwald_up 1\n"));
+
+        let health = get(addr, "/health");
+        assert!(health.starts_with("HTTP/1.1 503"));
+        assert!(health.ends_with("{\"healthy\":false}"));
+
+        snapshot.lock().unwrap().healthy = true;
+        assert!(get(addr, "/health").starts_with("HTTP/1.1 200 OK"));
+        assert!(get(addr, "/nope").starts_with("HTTP/1.1 404"));
+    }
+}
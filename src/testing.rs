@@ -0,0 +1,112 @@
+//! Fixtures for tests of wald and of tools built on it
+//!
+//! Built for wald's own unit tests and, with the `testing` feature, for
+//! other crates. The helpers panic rather than return errors, as a failed
+//! fixture means the test can't run. Every fixture lives in a [`TempDir`]
+//! that is removed when dropped.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use git2::{Repository, Signature};
+pub use tempfile::TempDir;
+
+use crate::commands::debug::{Fixture, FixtureOptions, build_fixture};
+use crate::output::Output;
+
+/// Create a repository with one commit adding `README.md`
+pub fn create_test_repo() -> (TempDir, Repository) {
+    let dir = TempDir::new().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+
+    {
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test User").unwrap();
+        config.set_str("user.email", "test@test.com").unwrap();
+    }
+
+    {
+        let sig = Signature::now("Test User", "test@test.com").unwrap();
+        let tree_id = {
+            let mut index = repo.index().unwrap();
+            fs::write(dir.path().join("README.md"), "# Test").unwrap();
+            index.add_path(Path::new("README.md")).unwrap();
+            index.write().unwrap();
+            index.write_tree().unwrap()
+        };
+        let tree = repo.find_tree(tree_id).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+            .unwrap();
+    }
+
+    (dir, repo)
+}
+
+/// Create a bare repository holding the commit of [`create_test_repo`]
+pub fn create_bare_repo_with_commit() -> (TempDir, Repository) {
+    let (temp_dir, _repo) = create_test_repo();
+
+    // git is more reliable than git2 for bare clones
+    let bare_dir = TempDir::new().unwrap();
+    let output = Command::new("git")
+        .arg("clone")
+        .arg("--bare")
+        .arg(temp_dir.path())
+        .arg(bare_dir.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git clone --bare failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let bare_repo = Repository::open_bare(bare_dir.path()).unwrap();
+    (bare_dir, bare_repo)
+}
+
+/// Create an empty workspace: a `.wald/` with a manifest and a config but
+/// no repos
+pub fn create_workspace() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let wald = dir.path().join(".wald");
+    fs::create_dir_all(&wald).unwrap();
+    fs::write(wald.join("manifest.yaml"), "repos: {}").unwrap();
+    fs::write(
+        wald.join("config.yaml"),
+        "default_lfs: minimal\ndefault_depth: 100",
+    )
+    .unwrap();
+    dir
+}
+
+/// Create a workspace with `repos` cloned and planted repos, as `wald
+/// debug make-fixture` does
+///
+/// The workspace is at [`Fixture::root`], inside the returned directory.
+pub fn create_fixture(repos: usize) -> (TempDir, Fixture) {
+    let dir = TempDir::new().unwrap();
+    let opts = FixtureOptions {
+        dir: dir.path().to_path_buf(),
+        repos,
+    };
+    let fixture = build_fixture(&opts, &Output::default()).unwrap();
+    (dir, fixture)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::Workspace;
+
+    #[test]
+    fn test_create_fixture() {
+        let (_dir, fixture) = create_fixture(1);
+        assert_eq!(fixture.repos, vec!["fixture.local/test/repo-1"]);
+
+        let ws = Workspace::load_from(fixture.root.clone()).unwrap();
+        assert!(ws.manifest.repos.contains_key("fixture.local/test/repo-1"));
+        assert!(fixture.root.join("repo-1/_main.wt/README.md").exists());
+    }
+}
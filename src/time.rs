@@ -0,0 +1,190 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, bail};
+
+/// Current time as seconds since the Unix epoch
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format seconds since the Unix epoch as a compact UTC timestamp
+///
+/// Returns e.g. `20261016T142300Z`, which sorts chronologically and is safe
+/// in file names.
+pub fn format_compact(secs: u64) -> String {
+    let (year, month, day, hour, minute, second) = to_utc(secs);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+/// Format seconds since the Unix epoch as an ISO 8601 UTC timestamp
+///
+/// Returns e.g. `2026-10-16T14:23:00Z`. Timestamps of this shape compare
+/// chronologically as strings.
+pub fn format_iso(secs: u64) -> String {
+    let (year, month, day, hour, minute, second) = to_utc(secs);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+/// Format a duration in seconds in its largest whole unit
+///
+/// Returns e.g. `45s`, `12m`, `3h` or `5d`, the units `parse_since` reads.
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3_600 => format!("{}m", s / 60),
+        s if s < 86_400 => format!("{}h", s / 3_600),
+        s => format!("{}d", s / 86_400),
+    }
+}
+
+/// Parse a point in time given either relative to `now` or as a UTC date
+///
+/// Accepts durations like `30m`, `12h`, `3d` and `2w` (that long before
+/// `now`), dates like `2026-10-01`, and times like `2026-10-01T12:00` or
+/// `2026-10-01T12:00:00Z`.
+pub fn parse_since(s: &str, now: u64) -> Result<u64> {
+    let s = s.trim();
+
+    if let Some(unit) = s.chars().last().filter(|c| c.is_ascii_alphabetic())
+        && let Ok(n) = s[..s.len() - 1].parse::<u64>()
+    {
+        let unit_secs = match unit {
+            'm' => 60,
+            'h' => 3_600,
+            'd' => 86_400,
+            'w' => 7 * 86_400,
+            _ => bail!("invalid time unit in '{}' (use m, h, d or w)", s),
+        };
+        return Ok(now.saturating_sub(n.saturating_mul(unit_secs)));
+    }
+
+    let (date, time) = s.split_once('T').unwrap_or((s, ""));
+    let time = time.strip_suffix('Z').unwrap_or(time);
+
+    let Some(date) = parse_fields(date, '-', 3) else {
+        bail!(
+            "invalid time '{}' (use e.g. 3d, 12h, 2026-10-01 or 2026-10-01T12:00)",
+            s
+        );
+    };
+    let time = if time.is_empty() {
+        vec![0, 0, 0]
+    } else {
+        match parse_fields(time, ':', 2).or_else(|| parse_fields(time, ':', 3)) {
+            Some(t) => t,
+            None => bail!("invalid time of day in '{}' (use HH:MM or HH:MM:SS)", s),
+        }
+    };
+
+    let (year, month, day) = (date[0], date[1], date[2]);
+    let (hour, minute, second) = (time[0], time[1], time.get(2).copied().unwrap_or(0));
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        bail!("invalid date or time: {}", s);
+    }
+    if year < 1970 {
+        bail!("dates before 1970 are not supported: {}", s);
+    }
+
+    let days = days_from_civil(year as i64, month as u32, day as u32);
+    Ok(days as u64 * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+/// Split `s` into exactly `count` numbers separated by `sep`
+fn parse_fields(s: &str, sep: char, count: usize) -> Option<Vec<u64>> {
+    let fields: Vec<u64> = s
+        .split(sep)
+        .map(|f| f.parse().ok())
+        .collect::<Option<_>>()?;
+    (fields.len() == count).then_some(fields)
+}
+
+/// Days since the Unix epoch of a UTC calendar date
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Split seconds since the Unix epoch into UTC calendar fields
+fn to_utc(secs: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (
+        year,
+        month,
+        day,
+        (rem / 3_600) as u32,
+        (rem % 3_600 / 60) as u32,
+        (rem % 60) as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_compact() {
+        assert_eq!(format_compact(0), "19700101T000000Z");
+        assert_eq!(format_compact(951_782_400), "20000229T000000Z");
+        assert_eq!(format_compact(1_792_160_580), "20261016T142300Z");
+        assert_eq!(format_iso(1_792_160_580), "2026-10-16T14:23:00Z");
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(45), "45s");
+        assert_eq!(format_age(12 * 60 + 5), "12m");
+        assert_eq!(format_age(3 * 3_600), "3h");
+        assert_eq!(format_age(5 * 86_400 + 7), "5d");
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = 1_792_160_580;
+        assert_eq!(parse_since("30m", now).unwrap(), now - 1_800);
+        assert_eq!(parse_since("3d", now).unwrap(), now - 3 * 86_400);
+        assert_eq!(parse_since("1w", now).unwrap(), now - 7 * 86_400);
+        assert_eq!(parse_since("2000-02-29", now).unwrap(), 951_782_400);
+        assert_eq!(parse_since("2026-10-16T14:23", now).unwrap(), 1_792_160_580);
+        assert_eq!(
+            parse_since("2026-10-16T14:23:00Z", now).unwrap(),
+            1_792_160_580
+        );
+
+        assert!(parse_since("3y", now).is_err());
+        assert!(parse_since("yesterday", now).is_err());
+        assert!(parse_since("2026-13-01", now).is_err());
+        assert!(parse_since("2026-10-16T25:00", now).is_err());
+    }
+}
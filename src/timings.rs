@@ -0,0 +1,187 @@
+//! Wall time per internal phase, reported by `--timings`
+//!
+//! Nothing leaves the machine: the breakdown is printed to stderr when the
+//! command finishes, for pasting into bug reports.
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::io;
+use std::process::{Command, ExitStatus, Output};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::git::runner;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PHASES: Mutex<BTreeMap<String, Phase>> = Mutex::new(BTreeMap::new());
+
+/// Time spent in one phase
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Phase {
+    /// How often the phase ran
+    pub count: u32,
+    pub total: Duration,
+}
+
+/// Start recording phases
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether phases are being recorded
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Add one run of `phase` that took `elapsed`
+pub fn record(phase: &str, elapsed: Duration) {
+    if !enabled() {
+        return;
+    }
+    let mut phases = PHASES.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = phases.entry(phase.to_string()).or_default();
+    entry.count += 1;
+    entry.total += elapsed;
+}
+
+/// Run `f`, recording its wall time under `phase`
+pub fn time<T>(phase: &str, f: impl FnOnce() -> T) -> T {
+    if !enabled() {
+        return f();
+    }
+    let started = Instant::now();
+    let result = f();
+    record(phase, started.elapsed());
+    result
+}
+
+/// The phases recorded so far, by name
+pub fn phases() -> BTreeMap<String, Phase> {
+    PHASES.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Running a subprocess, timed under `git <subcommand>` (or the program name)
+///
+/// git commands run through the active [`GitRunner`](crate::git::runner::GitRunner).
+pub trait TimedCommand {
+    fn timed_output(&mut self) -> io::Result<Output>;
+    fn timed_status(&mut self) -> io::Result<ExitStatus>;
+}
+
+impl TimedCommand for Command {
+    fn timed_output(&mut self) -> io::Result<Output> {
+        let phase = command_phase(self);
+        time(&phase, || runner::output(self))
+    }
+
+    fn timed_status(&mut self) -> io::Result<ExitStatus> {
+        let phase = command_phase(self);
+        time(&phase, || runner::status(self))
+    }
+}
+
+fn command_phase(cmd: &Command) -> String {
+    if !enabled() {
+        return String::new();
+    }
+    let program = cmd.get_program().to_string_lossy().to_string();
+    match subcommand(cmd.get_args()) {
+        Some(sub) if program == "git" => format!("git {}", sub),
+        _ => program,
+    }
+}
+
+/// Commands whose own subcommand says what they do (`worktree add`)
+const NESTED: &[&str] = &["lfs", "remote", "stash", "worktree"];
+
+/// The first argument that isn't a global option (`-C <dir>`, `-c <k=v>`,
+/// `--no-pager`, ...), plus the next one for [`NESTED`] commands
+fn subcommand<'a>(args: impl Iterator<Item = &'a OsStr>) -> Option<String> {
+    let mut args = args.map(|a| a.to_string_lossy());
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "-C" | "-c" => {
+                args.next();
+            }
+            a if a.starts_with('-') => {}
+            a if NESTED.contains(&a) => {
+                return Some(match args.next() {
+                    Some(sub) if !sub.starts_with('-') => format!("{} {}", a, sub),
+                    _ => a.to_string(),
+                });
+            }
+            a => return Some(a.to_string()),
+        }
+    }
+    None
+}
+
+/// Print the breakdown to stderr, slowest phase first
+///
+/// Phases run in parallel (checkouts, fetches) can add up to more than the
+/// total.
+pub fn print_report(total: Duration, json: bool) {
+    let mut phases: Vec<_> = phases().into_iter().collect();
+    phases.sort_by(|a, b| b.1.total.cmp(&a.1.total).then(a.0.cmp(&b.0)));
+
+    if json {
+        let phases: Vec<_> = phases
+            .iter()
+            .map(|(name, phase)| {
+                serde_json::json!({
+                    "phase": name,
+                    "count": phase.count,
+                    "ms": phase.total.as_micros() as f64 / 1000.0,
+                })
+            })
+            .collect();
+        let report = serde_json::json!({
+            "timings": {"total_ms": total.as_micros() as f64 / 1000.0, "phases": phases}
+        });
+        eprintln!("{}", report);
+        return;
+    }
+
+    let width = phases
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max("total".len());
+    eprintln!();
+    eprintln!("Timings:");
+    for (name, phase) in &phases {
+        eprintln!(
+            "  {:<width$}  {:>10}  {:>5}x",
+            name,
+            format_duration(phase.total),
+            phase.count,
+        );
+    }
+    eprintln!("  {:<width$}  {:>10}", "total", format_duration(total));
+}
+
+fn format_duration(d: Duration) -> String {
+    format!("{:.1}ms", d.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subcommand() {
+        let sub = |args: &[&str]| subcommand(args.iter().map(OsStr::new));
+        assert_eq!(
+            sub(&["-C", "/repo", "fetch", "--all"]).as_deref(),
+            Some("fetch")
+        );
+        assert_eq!(
+            sub(&["-c", "core.x=y", "--no-pager", "worktree", "add"]).as_deref(),
+            Some("worktree add")
+        );
+        assert_eq!(sub(&["stash", "--help"]).as_deref(), Some("stash"));
+        assert_eq!(sub(&["--version"]), None);
+    }
+}
@@ -0,0 +1,146 @@
+//! Concept help pages shown by `wald help <topic>`.
+//!
+//! Subcommand help comes from clap; these pages explain the ideas that span
+//! several commands (baums, tracking branches, syncing) and don't fit in a
+//! single `--help` screen.
+
+use crate::vocabulary::Vocabulary;
+
+/// A named help page
+pub struct Topic {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub body: &'static str,
+}
+
+/// All concept topics, in display order
+pub const TOPICS: &[Topic] = &[
+    Topic {
+        name: "baum",
+        summary: "Containers that group the worktrees of one repository",
+        body: "\
+A baum (\"tree\") is a directory in the workspace that holds worktrees of a
+single registered repository. It is marked by a `.baum/` directory:
+
+    path/to/project/
+    ├── .baum/manifest.yaml   # repo_id, baum id, worktree list (tracked)
+    ├── .gitignore            # ignores the worktree directories (tracked)
+    ├── _main.wt/             # worktree for branch `main` (gitignored)
+    └── _dev.wt/              # worktree for branch `dev` (gitignored)
+
+Only the manifest and .gitignore are committed to the workspace repo; the
+worktrees themselves are recreated from the bare repo on each machine.
+
+Where a container's own .gitignore belongs to someone else (e.g. the
+container sits in a directory with its own ignore conventions), set
+`worktree_ignore: exclude` in .wald/config.yaml: the worktree lines then go
+to .baum/gitignore (tracked) and wald applies them through the workspace
+repo's info/exclude, rewritten on each machine by plant, prune, move and
+sync.
+
+Each baum gets a random 6-character hex id on first use. The id namespaces
+its local branches (see `wald help tracking`) so that several baums can check
+out the same logical branch.
+
+Related commands: plant, uproot, move, branch, prune, worktrees",
+    },
+    Topic {
+        name: "tracking",
+        summary: "How worktrees map to wald/<id>/<branch> local branches",
+        body: "\
+Git allows a branch to be checked out in only one worktree at a time. To let
+several baums work on `main` independently, wald never checks out the
+logical branch directly. Instead each worktree gets a local branch
+
+    wald/<baum-id>/<branch>
+
+created from `origin/<branch>` with its upstream set to `origin/<branch>`.
+Pushing and pulling inside the worktree therefore works as usual.
+
+When creating a worktree and the local branch already exists:
+  (default)  overwrite it unless it has unpushed commits
+  --force    delete it and recreate from origin
+  --reuse    keep it as-is (refused if it has unpushed commits)
+
+Removing a worktree leaves its branch behind. `wald prune --branches` deletes
+wald/* branches whose baum or worktree no longer exists, skipping branches
+with unpushed commits unless --force is given.
+
+Hosts with a single branch that reject any other, like Overleaf, get direct
+branching instead: the worktree checks out `<branch>` itself, tracking
+`origin/<branch>`, so there can be only one worktree per branch. It is
+chosen for git.overleaf.com repos and set per repo with
+`wald repo add --branching tracking|direct`.",
+    },
+    Topic {
+        name: "syncing",
+        summary: "What `wald sync` transfers between machines",
+        body: "\
+The workspace itself is a git repository. Tracked: .wald/manifest.yaml,
+.wald/config.yaml, every .baum/manifest.yaml and container .gitignore.
+Ignored: bare repos in .wald/repos/, .wald/state.yaml, and all _*.wt
+worktree directories.
+
+`wald sync` then:
+  1. refuses to run with uncommitted workspace changes
+  2. pulls the workspace repo with --rebase (refusing a diverged history
+     unless --force is given); a rebase that conflicts is aborted, or with
+     --interactive left stopped for you to resolve
+  3. detects baum moves since the last sync via git rename detection on
+     .baum/manifest.yaml and replays them locally with `git worktree move`
+  4. clones bare repos that baums reference but this machine lacks
+     (skipped with --offline; with `team:` in config.yaml, only for the
+     baums this user or machine owns unless --all is given)
+  5. pushes local commits when --push is given
+  6. records the synced commit in .wald/state.yaml
+
+Use `wald move` rather than `mv` so that moves are staged as renames and
+can be replayed on other machines.",
+    },
+    Topic {
+        name: "repo-ids",
+        summary: "Repository identifiers, aliases and fuzzy matching",
+        body: "\
+Repositories are identified by `host/path`, for example
+
+    github.com/user/repo
+    git.zib.de/group/subgroup/repo
+
+The bare clone lives at .wald/repos/<host>/<path>.git and the clone URL is
+derived from the id (SSH for most hosts, HTTPS for Overleaf).
+
+Wherever a command takes a repository, you may also give:
+  - an alias registered with `wald repo add --alias`
+  - the repository name alone (`repo`) if it is unique
+  - `owner/repo` if the last two segments are unique
+
+Ambiguous references are rejected with the list of candidates.",
+    },
+];
+
+/// Look up a topic by name, in either vocabulary (`baum` or `container`)
+pub fn find_topic(name: &str) -> Option<&'static Topic> {
+    TOPICS
+        .iter()
+        .find(|t| t.name == name || Vocabulary::Plain.apply(t.name) == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_topic() {
+        assert_eq!(find_topic("baum").map(|t| t.name), Some("baum"));
+        assert_eq!(find_topic("container").map(|t| t.name), Some("baum"));
+        assert!(find_topic("nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_topic_names_unique() {
+        let mut names: Vec<_> = TOPICS.iter().map(|t| t.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), TOPICS.len());
+    }
+}
@@ -0,0 +1,321 @@
+//! Rotating backups of the workspace's YAML files
+//!
+//! Whenever wald saves a file in `.wald/` or `.wald/manifest.d/`, the
+//! version on disk before and after the save is copied to `.wald/backups/`
+//! if it parses and differs from the newest backup, named after the file,
+//! the time and a checksum of its content. The newest [`BACKUPS_KEPT`] of
+//! each file are kept. When a file no longer parses (a crashed save, an
+//! editor mishap), `wald doctor --fix` restores it from the newest backup
+//! whose content still matches its checksum.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+
+use super::manifest::MANIFEST_DIR;
+use crate::timings::TimedCommand;
+
+/// Directory in `.wald/` holding the backups
+pub const BACKUP_DIR: &str = "backups";
+
+/// Versions kept per file
+pub const BACKUPS_KEPT: usize = 5;
+
+/// Separates a backup's file key from its time and checksum
+const SEPARATOR: &str = "~";
+
+/// One saved version of a workspace file
+#[derive(Debug)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// When it was taken (milliseconds since the Unix epoch)
+    pub saved_at_ms: u128,
+    checksum: String,
+}
+
+impl Backup {
+    /// The backup's content, if it still matches its checksum and parses
+    pub fn verified_content(&self) -> Option<String> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        (checksum(&content) == self.checksum && parses(&content)).then_some(content)
+    }
+}
+
+/// A workspace file that no longer parses
+#[derive(Debug)]
+pub struct CorruptFile {
+    pub path: PathBuf,
+    pub error: String,
+    /// The newest backup that can replace it
+    pub backup: Option<Backup>,
+}
+
+/// What restoring a corrupt file did
+pub struct Restored {
+    /// Where the corrupt version was kept
+    pub corrupt_copy: PathBuf,
+    /// The corrupt version's lines the restore dropped, as a diff
+    pub lost: String,
+}
+
+/// Copy a workspace file's current version to its backups
+///
+/// Best effort: files outside `.wald/`, files that don't parse and
+/// versions identical to the newest backup are skipped, and failures are
+/// ignored; a missing backup must never stop a save.
+pub(crate) fn keep_backup(path: &Path) {
+    let Some((wald_dir, key)) = wald_file(path) else {
+        return;
+    };
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
+    };
+    if !parses(&content) {
+        return;
+    }
+    let sum = checksum(&content);
+    let backups = list_backups(&wald_dir, &key);
+    if backups.first().is_some_and(|newest| newest.checksum == sum) {
+        return;
+    }
+
+    let dir = wald_dir.join(BACKUP_DIR);
+    let name = format!("{}{}{}{}{}", key, SEPARATOR, now_ms(), SEPARATOR, sum);
+    if fs::create_dir_all(&dir).is_err() || fs::write(dir.join(name), &content).is_err() {
+        return;
+    }
+    for old in list_backups(&wald_dir, &key).iter().skip(BACKUPS_KEPT) {
+        let _ = fs::remove_file(&old.path);
+    }
+}
+
+/// The workspace's YAML files that no longer parse, with the backup each
+/// could be restored from
+pub fn find_corrupt_files(wald_dir: &Path) -> Vec<CorruptFile> {
+    let mut files = yaml_files(wald_dir);
+    files.extend(yaml_files(&wald_dir.join(MANIFEST_DIR)));
+
+    files
+        .into_iter()
+        .filter_map(|path| {
+            let content = fs::read_to_string(&path).ok()?;
+            let error = serde_yml::from_str::<serde_yml::Value>(&content).err()?;
+            let (_, key) = wald_file(&path)?;
+            let backup = list_backups(wald_dir, &key)
+                .into_iter()
+                .find(|b| b.verified_content().is_some());
+            Some(CorruptFile {
+                path,
+                error: error.to_string(),
+                backup,
+            })
+        })
+        .collect()
+}
+
+/// Replace a corrupt file with its backup, keeping the corrupt version
+/// next to the backups
+pub fn restore_backup(path: &Path, backup: &Backup) -> Result<Restored> {
+    let Some((wald_dir, key)) = wald_file(path) else {
+        bail!("{} is not a workspace file", path.display());
+    };
+    let Some(content) = backup.verified_content() else {
+        bail!(
+            "backup {} no longer matches its checksum",
+            backup.path.display()
+        );
+    };
+
+    let corrupt_copy = wald_dir.join(BACKUP_DIR).join(format!(
+        "{}{}{}{}corrupt",
+        key,
+        SEPARATOR,
+        now_ms(),
+        SEPARATOR
+    ));
+    fs::copy(path, &corrupt_copy)
+        .with_context(|| format!("failed to keep a copy of {}", path.display()))?;
+    let lost = diff(&backup.path, &corrupt_copy);
+
+    super::persist::write_atomic(path, content.as_bytes())
+        .with_context(|| format!("failed to restore {}", path.display()))?;
+
+    Ok(Restored { corrupt_copy, lost })
+}
+
+/// Backups of the file `key`, newest first
+fn list_backups(wald_dir: &Path, key: &str) -> Vec<Backup> {
+    let Ok(entries) = fs::read_dir(wald_dir.join(BACKUP_DIR)) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}{}", key, SEPARATOR);
+    let mut backups: Vec<Backup> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let (saved_at, sum) = name.strip_prefix(&prefix)?.split_once(SEPARATOR)?;
+            Some(Backup {
+                path: entry.path(),
+                saved_at_ms: saved_at.parse().ok()?,
+                checksum: sum.to_string(),
+            })
+        })
+        .filter(|b| b.checksum != "corrupt")
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.saved_at_ms));
+    backups
+}
+
+/// The `.wald/` directory a file belongs to, and the name its backups go
+/// by (`manifest.yaml`, `manifest.d%team.yaml`)
+fn wald_file(path: &Path) -> Option<(PathBuf, String)> {
+    let name = path.file_name()?.to_str()?;
+    if !name.ends_with(".yaml") || name.starts_with('.') {
+        return None;
+    }
+    let parent = path.parent()?;
+    match parent.file_name()?.to_str()? {
+        ".wald" => Some((parent.to_path_buf(), name.to_string())),
+        MANIFEST_DIR if parent.parent()?.file_name()? == ".wald" => Some((
+            parent.parent()?.to_path_buf(),
+            format!("{}%{}", MANIFEST_DIR, name),
+        )),
+        _ => None,
+    }
+}
+
+fn yaml_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && wald_file(path).is_some())
+        .collect();
+    files.sort();
+    files
+}
+
+fn parses(content: &str) -> bool {
+    serde_yml::from_str::<serde_yml::Value>(content).is_ok()
+}
+
+/// Short content checksum (git's blob hash)
+fn checksum(content: &str) -> String {
+    git2::Oid::hash_object(git2::ObjectType::Blob, content.as_bytes())
+        .map(|oid| oid.to_string()[..12].to_string())
+        .unwrap_or_default()
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+/// What `to` has that `from` doesn't, as a unified diff
+fn diff(from: &Path, to: &Path) -> String {
+    Command::new("git")
+        .arg("diff")
+        .arg("--no-index")
+        .arg("--no-color")
+        .arg("--no-prefix")
+        .arg(from)
+        .arg(to)
+        .timed_output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn wald_dir() -> (TempDir, PathBuf) {
+        let dir = TempDir::new().unwrap();
+        let wald = dir.path().join(".wald");
+        fs::create_dir_all(wald.join(MANIFEST_DIR)).unwrap();
+        (dir, wald)
+    }
+
+    #[test]
+    fn test_wald_file() {
+        let (_dir, wald) = wald_dir();
+        assert_eq!(
+            wald_file(&wald.join("manifest.yaml")).unwrap().1,
+            "manifest.yaml"
+        );
+        assert_eq!(
+            wald_file(&wald.join("manifest.d/team.yaml")).unwrap().1,
+            "manifest.d%team.yaml"
+        );
+        assert!(wald_file(&wald.join(".manifest.yaml.tmp.1")).is_none());
+        assert!(wald_file(Path::new("/ws/tools/repo/.baum/manifest.yaml")).is_none());
+    }
+
+    #[test]
+    fn test_backups_rotate_and_skip_duplicates() {
+        let (_dir, wald) = wald_dir();
+        let path = wald.join("manifest.yaml");
+
+        for i in 0..BACKUPS_KEPT + 2 {
+            fs::write(&path, format!("repos: {{}}\n# version {}\n", i)).unwrap();
+            keep_backup(&path);
+            // Same content again: no new backup
+            keep_backup(&path);
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let backups = list_backups(&wald, "manifest.yaml");
+        assert_eq!(backups.len(), BACKUPS_KEPT);
+        let newest = backups[0].verified_content().unwrap();
+        assert!(newest.contains(&format!("version {}", BACKUPS_KEPT + 1)));
+    }
+
+    #[test]
+    fn test_restore_from_newest_valid_backup() {
+        let (_dir, wald) = wald_dir();
+        let path = wald.join("manifest.yaml");
+        fs::write(&path, "repos:\n  github.com/a/b: {}\n").unwrap();
+        keep_backup(&path);
+
+        // Never backed up: it doesn't parse
+        fs::write(&path, "repos:\n  github.com/a/b: {\n  github.com/c/d: {}\n").unwrap();
+        keep_backup(&path);
+        assert_eq!(list_backups(&wald, "manifest.yaml").len(), 1);
+
+        let corrupt = find_corrupt_files(&wald);
+        assert_eq!(corrupt.len(), 1);
+        let backup = corrupt[0].backup.as_ref().unwrap();
+        let restored = restore_backup(&path, backup).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "repos:\n  github.com/a/b: {}\n"
+        );
+        assert!(restored.lost.contains("+  github.com/c/d: {}"));
+        assert!(restored.corrupt_copy.exists());
+        assert!(find_corrupt_files(&wald).is_empty());
+    }
+
+    #[test]
+    fn test_tampered_backup_is_not_used() {
+        let (_dir, wald) = wald_dir();
+        let path = wald.join("config.yaml");
+        fs::write(&path, "default_depth: 100\n").unwrap();
+        keep_backup(&path);
+        let backup = &list_backups(&wald, "config.yaml")[0];
+        fs::write(&backup.path, "default_depth: 1\n").unwrap();
+
+        fs::write(&path, "default_depth: [\n").unwrap();
+        let corrupt = find_corrupt_files(&wald);
+        assert_eq!(corrupt.len(), 1);
+        assert!(corrupt[0].backup.is_none());
+    }
+}
@@ -1,14 +1,21 @@
-use std::fs;
+use std::collections::BTreeMap;
 use std::path::Path;
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
-use super::{DepthPolicy, FilterPolicy, LfsPolicy};
+use super::persist::save_yaml;
+use super::schema::{Migration, Versioned, adopt_versioning, current_version, load_yaml};
+use super::{DepthPolicy, FilterPolicy, LfsPolicy, TagPolicy};
+use crate::vocabulary::Vocabulary;
 
 /// Workspace configuration (.wald/config.yaml)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// File format version
+    #[serde(default = "current_version::<Config>")]
+    pub version: u32,
+
     /// Default LFS policy for new repos
     #[serde(default)]
     pub default_lfs: LfsPolicy,
@@ -20,34 +27,335 @@ pub struct Config {
     /// Default partial clone filter for new repos
     #[serde(default)]
     pub default_filter: FilterPolicy,
+
+    /// Default tag policy for new repos
+    #[serde(default)]
+    pub default_tags: TagPolicy,
+
+    /// Standalone workspace: no workspace git repo and no cross-machine sync
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub standalone: bool,
+
+    /// Days an uprooted baum stays in .wald/trash/ before it is purged
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u64,
+
+    /// Globs for directories never searched for baums (e.g. `node_modules`,
+    /// `archive/**`)
+    #[serde(default = "default_ignore")]
+    pub ignore: Vec<String>,
+
+    /// How far and through which symlinks baums are searched for
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+
+    /// Where this machine's transient data (the journal) is kept
+    #[serde(default, skip_serializing_if = "is_default_cache")]
+    pub cache: CacheLocation,
+
+    /// Where the patterns ignoring each baum's worktrees are kept
+    #[serde(default, skip_serializing_if = "is_default_worktree_ignore")]
+    pub worktree_ignore: WorktreeIgnore,
+
+    /// Words for baums and their commands: `wald` (baum, plant, uproot) or
+    /// `plain` (container, create, remove); WALD_VOCABULARY overrides it
+    #[serde(default, skip_serializing_if = "is_default_vocabulary")]
+    pub vocabulary: Vocabulary,
+
+    /// Branch globs (`release/*`) that `--force` only overwrites or deletes
+    /// with `--yes-protected`; a repo's default branch always is protected
+    #[serde(default = "default_protected_branches")]
+    pub protected_branches: Vec<String>,
+
+    /// Shared workspace: baums record who planted them, and `sync` only
+    /// clones the repos of this user's (or machine's) baums
+    #[serde(default, skip_serializing_if = "is_default_team")]
+    pub team: TeamMode,
+
+    /// Where clones and fetches get their token for hosts that need one
+    /// (e.g. `git.overleaf.com`), keyed by host; the token itself is
+    /// never stored here
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub credentials: BTreeMap<String, HostCredentials>,
+
+    /// Container paths for `plant --template`, by name (e.g. `research:
+    /// research/{year}/{repo}`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub plant_templates: BTreeMap<String, String>,
+
+    /// How `sync` and `clone` clone the repos this machine is missing
+    #[serde(default, skip_serializing_if = "is_default_hydration")]
+    pub hydration: HydrationConfig,
+
+    /// Clone URL prefixes to replace, like git's `insteadOf` (e.g.
+    /// `git@github.com:acme/: https://mirror.acme.internal/acme/`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub url_rewrites: BTreeMap<String, String>,
+
+    /// Show a workspace summary when `wald` runs without a command (else
+    /// the help)
+    #[serde(
+        default = "default_dashboard",
+        skip_serializing_if = "is_default_dashboard"
+    )]
+    pub dashboard: bool,
+
+    /// Command run when a long command (clone, sync, fetch, ...) finishes
+    #[serde(default, skip_serializing_if = "is_default_notify")]
+    pub notify: NotifyConfig,
+}
+
+/// A command announcing that a long wald command is done
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    /// Shell command to run, e.g. `notify-send wald "$WALD_SUMMARY"`;
+    /// the `WALD_NOTIFY` environment variable takes precedence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Commands finishing faster than this many seconds are not announced
+    #[serde(default = "default_notify_after_secs")]
+    pub after_secs: u64,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            command: None,
+            after_secs: default_notify_after_secs(),
+        }
+    }
+}
+
+fn default_notify_after_secs() -> u64 {
+    60
+}
+
+fn is_default_notify(notify: &NotifyConfig) -> bool {
+    *notify == NotifyConfig::default()
+}
+
+/// Limits on cloning many missing repos at once
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HydrationConfig {
+    /// Clones running at once
+    #[serde(default = "default_hydration_jobs")]
+    pub jobs: usize,
+    /// Clones running at once from one host
+    #[serde(default = "default_hydration_per_host")]
+    pub per_host: usize,
+    /// Least time between starting two clones from one host, in
+    /// milliseconds
+    #[serde(default = "default_host_interval_ms")]
+    pub host_interval_ms: u64,
+}
+
+impl Default for HydrationConfig {
+    fn default() -> Self {
+        Self {
+            jobs: default_hydration_jobs(),
+            per_host: default_hydration_per_host(),
+            host_interval_ms: default_host_interval_ms(),
+        }
+    }
+}
+
+fn default_hydration_jobs() -> usize {
+    4
+}
+
+fn default_hydration_per_host() -> usize {
+    2
+}
+
+fn default_host_interval_ms() -> u64 {
+    500
+}
+
+fn is_default_hydration(hydration: &HydrationConfig) -> bool {
+    *hydration == HydrationConfig::default()
+}
+
+/// How to get the credentials for one host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostCredentials {
+    #[serde(flatten)]
+    pub source: SecretSource,
+    /// User name sent with a token from `pass` or `op` (default `git`,
+    /// which Overleaf expects)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+/// A store holding a host's token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretSource {
+    /// A git credential helper (e.g. `osxkeychain`, `store --file <path>`)
+    Helper(String),
+    /// A `pass` entry whose first line is the token
+    Pass(String),
+    /// A 1Password secret reference (`op://vault/item/field`)
+    Op(String),
+}
+
+/// Where a workspace keeps the data only this machine needs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheLocation {
+    /// In `.wald/`
+    #[default]
+    Workspace,
+    /// In `$XDG_CACHE_HOME/wald/<workspace-id>/` (`~/.cache` by default),
+    /// out of backups and workspace copies
+    Xdg,
+}
+
+fn is_default_cache(cache: &CacheLocation) -> bool {
+    *cache == CacheLocation::default()
+}
+
+/// Where a baum's worktree patterns (`/_main.wt`) are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorktreeIgnore {
+    /// In the container's `.gitignore`
+    #[default]
+    Gitignore,
+    /// In the container's `.baum/gitignore`, applied through the workspace
+    /// repo's `info/exclude`, for containers inside repos with their own
+    /// `.gitignore` conventions
+    Exclude,
+}
+
+/// Who owns the baums of a shared workspace
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TeamMode {
+    /// Not shared: baums have no owner
+    #[default]
+    Off,
+    /// Baums belong to the git user (`user.email`) who planted them
+    User,
+    /// Baums belong to the machine (host name) they were planted on
+    Machine,
+}
+
+fn is_default_team(team: &TeamMode) -> bool {
+    *team == TeamMode::default()
+}
+
+fn default_dashboard() -> bool {
+    true
+}
+
+fn is_default_dashboard(dashboard: &bool) -> bool {
+    *dashboard == default_dashboard()
+}
+
+fn is_default_worktree_ignore(mode: &WorktreeIgnore) -> bool {
+    *mode == WorktreeIgnore::default()
+}
+
+fn is_default_vocabulary(vocabulary: &Vocabulary) -> bool {
+    *vocabulary == Vocabulary::default()
+}
+
+/// Limits on the search for baums
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveryConfig {
+    /// Deepest directory level (below the workspace root) holding baums;
+    /// None searches the whole tree
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+
+    /// Which symlinked directories to descend into
+    #[serde(default)]
+    pub symlinks: SymlinkPolicy,
+}
+
+/// Which symlinked directories the search for baums follows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymlinkPolicy {
+    /// Never follow symlinks
+    #[default]
+    Never,
+    /// Follow symlinks that point inside the workspace
+    Workspace,
+    /// Follow every symlink (e.g. to network mounts)
+    Always,
+}
+
+fn default_trash_retention_days() -> u64 {
+    30
+}
+
+fn default_protected_branches() -> Vec<String> {
+    ["main", "master", "release/*"].map(String::from).to_vec()
+}
+
+/// Directories that hold dependencies or build output, never baums
+fn default_ignore() -> Vec<String> {
+    ["node_modules", ".venv", "venv", "__pycache__", ".tox"]
+        .map(String::from)
+        .to_vec()
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: Self::VERSION,
             default_lfs: LfsPolicy::Minimal,
             default_depth: DepthPolicy::Full,
             default_filter: FilterPolicy::BlobNone, // Fast clones, blobs fetched on demand
+            default_tags: TagPolicy::All,
+            standalone: false,
+            trash_retention_days: default_trash_retention_days(),
+            ignore: default_ignore(),
+            discovery: DiscoveryConfig::default(),
+            cache: CacheLocation::default(),
+            worktree_ignore: WorktreeIgnore::default(),
+            vocabulary: Vocabulary::default(),
+            protected_branches: default_protected_branches(),
+            team: TeamMode::default(),
+            credentials: BTreeMap::new(),
+            plant_templates: BTreeMap::new(),
+            hydration: HydrationConfig::default(),
+            url_rewrites: BTreeMap::new(),
+            dashboard: default_dashboard(),
+            notify: NotifyConfig::default(),
         }
     }
 }
 
+impl Versioned for Config {
+    const KIND: &'static str = "config";
+    const MIGRATIONS: &'static [Migration] = &[adopt_versioning];
+}
+
 impl Config {
-    /// Load config from a YAML file
+    /// Load config from a YAML file, migrating older formats
     pub fn load(path: &Path) -> Result<Self> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("failed to read config: {}", path.display()))?;
-        let config: Config = serde_yml::from_str(&content)
-            .with_context(|| format!("failed to parse config: {}", path.display()))?;
-        Ok(config)
+        load_yaml(path)
     }
 
     /// Save config to a YAML file
     pub fn save(&self, path: &Path) -> Result<()> {
-        let content = serde_yml::to_string(self).context("failed to serialize config")?;
-        fs::write(path, content)
-            .with_context(|| format!("failed to write config: {}", path.display()))?;
-        Ok(())
+        save_yaml(self, path, "config")
+    }
+
+    /// Apply `url_rewrites` to a clone URL
+    ///
+    /// As with git's `insteadOf`, the longest matching prefix wins; a
+    /// trailing `*` on a prefix is allowed and ignored.
+    pub fn rewrite_url(&self, url: &str) -> String {
+        self.url_rewrites
+            .iter()
+            .map(|(pattern, replacement)| (pattern.trim_end_matches('*'), replacement))
+            .filter(|(prefix, _)| !prefix.is_empty() && url.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(prefix, replacement)| format!("{}{}", replacement, &url[prefix.len()..]))
+            .unwrap_or_else(|| url.to_string())
     }
 }
 
@@ -66,9 +374,35 @@ mod tests {
     #[test]
     fn test_config_roundtrip() {
         let config = Config {
+            version: Config::VERSION,
             default_lfs: LfsPolicy::Full,
             default_depth: DepthPolicy::Depth(50),
             default_filter: FilterPolicy::BlobNone,
+            default_tags: TagPolicy::None,
+            standalone: true,
+            trash_retention_days: 7,
+            ignore: vec!["archive/**".to_string()],
+            discovery: DiscoveryConfig {
+                max_depth: Some(4),
+                symlinks: SymlinkPolicy::Workspace,
+            },
+            cache: CacheLocation::Xdg,
+            worktree_ignore: WorktreeIgnore::Exclude,
+            vocabulary: Vocabulary::Plain,
+            protected_branches: vec!["prod".to_string()],
+            team: TeamMode::Machine,
+            credentials: BTreeMap::new(),
+            plant_templates: BTreeMap::new(),
+            hydration: HydrationConfig::default(),
+            url_rewrites: BTreeMap::from([(
+                "git@github.com:acme/".to_string(),
+                "https://mirror.acme.internal/acme/".to_string(),
+            )]),
+            dashboard: false,
+            notify: NotifyConfig {
+                command: Some("notify-send wald \"$WALD_SUMMARY\"".to_string()),
+                after_secs: 300,
+            },
         };
 
         let yaml = serde_yml::to_string(&config).unwrap();
@@ -77,5 +411,84 @@ mod tests {
         assert_eq!(parsed.default_lfs, LfsPolicy::Full);
         assert_eq!(parsed.default_depth, DepthPolicy::Depth(50));
         assert_eq!(parsed.default_filter, FilterPolicy::BlobNone);
+        assert_eq!(parsed.default_tags, TagPolicy::None);
+        assert!(parsed.standalone);
+        assert_eq!(parsed.trash_retention_days, 7);
+        assert_eq!(parsed.ignore, vec!["archive/**"]);
+        assert_eq!(parsed.discovery.max_depth, Some(4));
+        assert_eq!(parsed.discovery.symlinks, SymlinkPolicy::Workspace);
+        assert_eq!(parsed.team, TeamMode::Machine);
+        assert_eq!(parsed.cache, CacheLocation::Xdg);
+        assert_eq!(parsed.worktree_ignore, WorktreeIgnore::Exclude);
+        assert_eq!(parsed.vocabulary, Vocabulary::Plain);
+        assert_eq!(parsed.protected_branches, vec!["prod"]);
+        assert_eq!(parsed.url_rewrites, config.url_rewrites);
+        assert!(!parsed.dashboard);
+        assert_eq!(parsed.notify, config.notify);
+    }
+
+    #[test]
+    fn test_rewrite_url() {
+        let config = Config {
+            url_rewrites: BTreeMap::from([
+                (
+                    "git@github.com:".to_string(),
+                    "https://github.com/".to_string(),
+                ),
+                (
+                    "git@github.com:acme/*".to_string(),
+                    "https://mirror.acme.internal/acme/".to_string(),
+                ),
+            ]),
+            ..Config::default()
+        };
+        assert_eq!(
+            config.rewrite_url("git@github.com:acme/api.git"),
+            "https://mirror.acme.internal/acme/api.git"
+        );
+        assert_eq!(
+            config.rewrite_url("git@github.com:other/api.git"),
+            "https://github.com/other/api.git"
+        );
+        assert_eq!(
+            config.rewrite_url("git@gitlab.com:acme/api.git"),
+            "git@gitlab.com:acme/api.git"
+        );
+    }
+
+    #[test]
+    fn test_standalone_omitted_when_false() {
+        let yaml = serde_yml::to_string(&Config::default()).unwrap();
+        assert!(!yaml.contains("standalone"));
+        assert!(!yaml.lines().any(|line| line.starts_with("cache:")));
+        assert!(!yaml.contains("worktree_ignore"));
+        assert!(!yaml.contains("vocabulary"));
+    }
+
+    #[test]
+    fn test_credentials_by_host() {
+        let yaml = "credentials:\n  git.overleaf.com:\n    pass: overleaf/token\n  gitlab.work.example:\n    op: op://Work/GitLab/token\n    username: me\n  github.com:\n    helper: osxkeychain\n";
+        let config: Config = serde_yml::from_str(yaml).unwrap();
+
+        assert_eq!(
+            config.credentials["git.overleaf.com"],
+            HostCredentials {
+                source: SecretSource::Pass("overleaf/token".to_string()),
+                username: None,
+            }
+        );
+        assert_eq!(
+            config.credentials["gitlab.work.example"]
+                .username
+                .as_deref(),
+            Some("me")
+        );
+        assert_eq!(
+            config.credentials["github.com"].source,
+            SecretSource::Helper("osxkeychain".to_string())
+        );
+
+        let parsed: Config = serde_yml::from_str(&serde_yml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(parsed.credentials, config.credentials);
     }
 }
@@ -1,10 +1,14 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::path::Path;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
 
+use super::persist::save_yaml;
+use super::schema::{Migration, Versioned, adopt_versioning, current_version, load_yaml};
+use crate::time::{format_iso, now_secs};
+
 /// LFS fetch policy
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -56,6 +60,46 @@ impl FilterPolicy {
     }
 }
 
+/// Which tags to fetch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagPolicy {
+    /// Every tag on the remote
+    #[default]
+    All,
+    /// Only tags pointing into fetched history (git's default for fetch)
+    Reachable,
+    /// No tags
+    None,
+}
+
+impl TagPolicy {
+    /// Value for the remote's `tagOpt` config, or None for git's default
+    pub fn as_tag_opt(&self) -> Option<&'static str> {
+        match self {
+            TagPolicy::All => Some("--tags"),
+            TagPolicy::Reachable => None,
+            TagPolicy::None => Some("--no-tags"),
+        }
+    }
+}
+
+/// How worktrees get their local branches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Branching {
+    /// A `wald/<baum_id>/<branch>` branch per worktree, tracking the remote
+    /// branch, so several baums can check out the same branch
+    #[default]
+    Tracking,
+    /// The branch itself, for hosts that reject other branches (one
+    /// worktree per branch)
+    Direct,
+}
+
+/// Hosts whose repos have a single branch and reject new ones
+const SINGLE_BRANCH_HOSTS: &[&str] = &["git.overleaf.com"];
+
 /// Entry for a single repository in the manifest
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RepoEntry {
@@ -71,46 +115,290 @@ pub struct RepoEntry {
     #[serde(default)]
     pub filter: FilterPolicy,
 
+    /// Tags fetched on clone and fetch
+    #[serde(default, skip_serializing_if = "is_default_tags")]
+    pub tags: TagPolicy,
+
+    /// Clone URL, when it differs from the one inferred from the repo ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
     /// Upstream repo ID for fork tracking
     #[serde(skip_serializing_if = "Option::is_none")]
     pub upstream: Option<String>,
 
+    /// Registered repo whose bare clone lends this one its objects (via
+    /// git alternates), e.g. the upstream of a fork
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+
     /// Short aliases for this repo
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub aliases: Vec<String>,
+
+    /// Git config applied to the bare repo on clone and to each new
+    /// worktree (e.g. `user.email`, `http.proxy`, `commit.gpgsign`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub git_config: BTreeMap<String, String>,
+
+    /// Expected `user.email` for commits in this repo's worktrees
+    /// (overrides the per-host identity)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+
+    /// Branching mode; unset means chosen by host (direct for Overleaf)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branching: Option<Branching>,
+
+    /// Named group for operating on several repos at once (`--group`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    /// Size of the bare clone in KiB when it was registered, so a machine
+    /// cloning many repos starts with the small ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+
+    /// Where the bare clone lives instead of `.wald/repos/` (absolute, or
+    /// relative to the workspace root), e.g. on a larger disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bare_path: Option<String>,
+
+    /// Shell commands run in the bare repo after `repo fetch --post-fetch`
+    /// fetched it (e.g. `git lfs fetch`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_fetch: Vec<String>,
+}
+
+fn is_default_tags(tags: &TagPolicy) -> bool {
+    *tags == TagPolicy::default()
 }
 
+/// Directory (in .wald/) of manifest files merged into manifest.yaml
+pub const MANIFEST_DIR: &str = "manifest.d";
+
 /// Central manifest (.wald/manifest.yaml)
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Manifest {
+    /// File format version
+    #[serde(default = "current_version::<Manifest>")]
+    pub version: u32,
+
+    /// Expected `user.email` per host (e.g. a work address for the
+    /// company GitLab), checked by `doctor` and pinned on `plant`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub identities: BTreeMap<String, String>,
+
     /// Registered repositories keyed by repo_id (host/path)
     #[serde(default)]
     pub repos: HashMap<String, RepoEntry>,
+
+    /// Which manifest.d/ file each entry was loaded from
+    #[serde(skip)]
+    pub sources: ManifestSources,
+}
+
+/// Where the entries of a split manifest live: file names in manifest.d/
+/// by repo ID and by identity host; entries not listed are in
+/// manifest.yaml
+#[derive(Debug, Clone, Default)]
+pub struct ManifestSources {
+    /// Every manifest.d/ file loaded, so one left empty is still written
+    pub files: BTreeSet<String>,
+    pub repos: HashMap<String, String>,
+    pub identities: HashMap<String, String>,
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            identities: BTreeMap::new(),
+            repos: HashMap::new(),
+            sources: ManifestSources::default(),
+        }
+    }
+}
+
+impl Versioned for Manifest {
+    const KIND: &'static str = "manifest";
+    const MIGRATIONS: &'static [Migration] = &[adopt_versioning];
 }
 
 impl Manifest {
-    /// Load manifest from a YAML file
+    /// Load manifest from a YAML file, migrating older formats
     pub fn load(path: &Path) -> Result<Self> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("failed to read manifest: {}", path.display()))?;
-        let manifest: Manifest = serde_yml::from_str(&content)
-            .with_context(|| format!("failed to parse manifest: {}", path.display()))?;
-        Ok(manifest)
+        load_yaml(path)
     }
 
     /// Save manifest to a YAML file
     pub fn save(&self, path: &Path) -> Result<()> {
-        let content = serde_yml::to_string(self).context("failed to serialize manifest")?;
-        fs::write(path, content)
-            .with_context(|| format!("failed to write manifest: {}", path.display()))?;
+        save_yaml(self, path, "manifest")
+    }
+
+    /// Load `manifest.yaml` from `wald_dir` and merge in every
+    /// `manifest.d/*.yaml`, in file name order
+    ///
+    /// A repo or identity defined in two files is an error rather than one
+    /// of them silently winning.
+    pub fn load_split(wald_dir: &Path) -> Result<Self> {
+        let mut manifest = Self::load(&wald_dir.join("manifest.yaml"))?;
+
+        let dir = wald_dir.join(MANIFEST_DIR);
+        if !dir.is_dir() {
+            return Ok(manifest);
+        }
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .with_context(|| format!("failed to read {}", dir.display()))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name.ends_with(".yaml") && !name.starts_with('.'))
+            .collect();
+        names.sort();
+
+        for name in names {
+            let part = Self::load(&dir.join(&name))
+                .with_context(|| format!("failed to load {}/{}", MANIFEST_DIR, name))?;
+            for (repo_id, entry) in part.repos {
+                if manifest.repos.contains_key(&repo_id) {
+                    bail!(
+                        "repository {} is defined in both {} and {}/{}",
+                        repo_id,
+                        manifest.repo_file(&repo_id),
+                        MANIFEST_DIR,
+                        name
+                    );
+                }
+                manifest.sources.repos.insert(repo_id.clone(), name.clone());
+                manifest.repos.insert(repo_id, entry);
+            }
+            for (host, email) in part.identities {
+                if manifest.identities.contains_key(&host) {
+                    let first = match manifest.sources.identities.get(&host) {
+                        Some(file) => format!("{}/{}", MANIFEST_DIR, file),
+                        None => "manifest.yaml".to_string(),
+                    };
+                    bail!(
+                        "identity for {} is defined in both {} and {}/{}",
+                        host,
+                        first,
+                        MANIFEST_DIR,
+                        name
+                    );
+                }
+                manifest
+                    .sources
+                    .identities
+                    .insert(host.clone(), name.clone());
+                manifest.identities.insert(host, email);
+            }
+            manifest.sources.files.insert(name);
+        }
+
+        Ok(manifest)
+    }
+
+    /// Save to `manifest.yaml` and `manifest.d/` in `wald_dir`, each entry
+    /// to the file it came from (or was assigned to)
+    pub fn save_split(&self, wald_dir: &Path) -> Result<()> {
+        let mut files = self.sources.files.clone();
+        files.extend(self.sources.repos.values().cloned());
+        files.extend(self.sources.identities.values().cloned());
+
+        self.part(None).save(&wald_dir.join("manifest.yaml"))?;
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let dir = wald_dir.join(MANIFEST_DIR);
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        for name in &files {
+            self.part(Some(name)).save(&dir.join(name))?;
+        }
         Ok(())
     }
 
+    /// The entries that belong in one file (None for manifest.yaml)
+    fn part(&self, file: Option<&String>) -> Manifest {
+        Manifest {
+            version: self.version,
+            identities: self
+                .identities
+                .iter()
+                .filter(|(host, _)| self.sources.identities.get(*host) == file)
+                .map(|(host, email)| (host.clone(), email.clone()))
+                .collect(),
+            repos: self
+                .repos
+                .iter()
+                .filter(|(id, _)| self.sources.repos.get(*id) == file)
+                .map(|(id, entry)| (id.clone(), entry.clone()))
+                .collect(),
+            sources: ManifestSources::default(),
+        }
+    }
+
+    /// The file a repo is defined in, relative to .wald/
+    pub fn repo_file(&self, repo_id: &str) -> String {
+        match self.sources.repos.get(repo_id) {
+            Some(file) => format!("{}/{}", MANIFEST_DIR, file),
+            None => "manifest.yaml".to_string(),
+        }
+    }
+
     /// Check if a repo ID exists in the manifest
     pub fn has_repo(&self, repo_id: &str) -> bool {
         self.repos.contains_key(repo_id)
     }
 
+    /// Expected commit identity (`user.email`) for a repo, if configured
+    ///
+    /// A repo's own `identity` takes precedence over its host's entry in
+    /// `identities`.
+    pub fn expected_identity(&self, repo_id: &str) -> Option<&str> {
+        if let Some(identity) = self.repos.get(repo_id).and_then(|e| e.identity.as_deref()) {
+            return Some(identity);
+        }
+        let host = repo_id.split('/').next()?;
+        self.identities.get(host).map(|s| s.as_str())
+    }
+
+    /// Branching mode for a repo: its own setting, else by host
+    pub fn branching(&self, repo_id: &str) -> Branching {
+        if let Some(branching) = self.repos.get(repo_id).and_then(|e| e.branching) {
+            return branching;
+        }
+        match repo_id.split('/').next() {
+            Some(host) if SINGLE_BRANCH_HOSTS.contains(&host) => Branching::Direct,
+            _ => Branching::Tracking,
+        }
+    }
+
+    /// Repos in a group, sorted
+    pub fn group_repos(&self, group: &str) -> Vec<String> {
+        let mut repos: Vec<String> = self
+            .repos
+            .iter()
+            .filter(|(_, e)| e.group.as_deref() == Some(group))
+            .map(|(id, _)| id.clone())
+            .collect();
+        repos.sort();
+        repos
+    }
+
+    /// Repos that borrow objects from `repo_id`'s bare clone, sorted
+    pub fn borrowers(&self, repo_id: &str) -> Vec<String> {
+        let mut borrowers: Vec<String> = self
+            .repos
+            .iter()
+            .filter(|(_, e)| e.reference.as_deref() == Some(repo_id))
+            .map(|(id, _)| id.clone())
+            .collect();
+        borrowers.sort();
+        borrowers
+    }
+
     /// Resolve a reference to a repo ID
     ///
     /// Resolution order:
@@ -165,6 +453,18 @@ impl Manifest {
         }
     }
 
+    /// Repos that `reference` names by repo name or owner/repo, sorted
+    ///
+    /// These are the repos fuzzy resolution would pick from; an alias
+    /// equal to one of these names takes precedence over them.
+    pub fn name_matches(&self, reference: &str) -> Vec<&str> {
+        match self.resolve_fuzzy(reference) {
+            FuzzyResult::Unique(repo_id) => vec![repo_id],
+            FuzzyResult::Ambiguous(matches) => matches,
+            FuzzyResult::None => Vec::new(),
+        }
+    }
+
     /// Fuzzy resolution by repo name or owner/repo pattern
     fn resolve_fuzzy(&self, reference: &str) -> FuzzyResult<'_> {
         let mut matches: Vec<&str> = Vec::new();
@@ -233,38 +533,83 @@ pub struct WorktreeEntry {
     /// None for legacy worktrees that check out the remote branch directly
     #[serde(skip_serializing_if = "Option::is_none")]
     pub local_branch: Option<String>,
+    /// Remote branch the local branch tracks when it isn't
+    /// `origin/<branch>` (e.g., "upstream/main"); pushes still go to origin
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track: Option<String>,
+    /// When wald created the worktree (ISO 8601 UTC); None for older ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// Remote branch the worktree was created from (e.g. "origin/main"),
+    /// so another machine recreating it can tell the branch has moved on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_from: Option<String>,
+    /// Commit the worktree's branch started at when it was created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_commit: Option<String>,
+    /// Last activity seen in the worktree when the manifest was last
+    /// written (ISO 8601 UTC), so other machines know it's in use
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<String>,
 }
 
 /// Baum manifest (container/.baum/manifest.yaml)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BaumManifest {
+    /// File format version
+    #[serde(default = "current_version::<BaumManifest>")]
+    pub version: u32,
     /// Unique baum ID (6-char hex)
     /// None for legacy baums; generated on first modification
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     /// Repository ID this baum is linked to
     pub repo_id: String,
+    /// The repo's default branch when the baum was planted, so later
+    /// operations don't depend on (or requery) the remote's `HEAD`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_branch: Option<String>,
+    /// Who planted the baum in a team workspace (a git user or a machine);
+    /// None for baums everyone shares
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
     /// Worktrees in this baum
     #[serde(default)]
     pub worktrees: Vec<WorktreeEntry>,
 }
 
+impl Versioned for BaumManifest {
+    const KIND: &'static str = "baum manifest";
+    const MIGRATIONS: &'static [Migration] = &[adopt_versioning];
+}
+
 impl BaumManifest {
-    /// Load baum manifest from a YAML file
+    /// Create an empty manifest for a new baum (ID assigned on first save)
+    pub fn new(repo_id: &str) -> Self {
+        Self {
+            version: Self::VERSION,
+            id: None,
+            repo_id: repo_id.to_string(),
+            default_branch: None,
+            owner: None,
+            worktrees: Vec::new(),
+        }
+    }
+
+    /// Whether the baum belongs to `owner` (baums without one belong to
+    /// everyone)
+    pub fn is_owned_by(&self, owner: &str) -> bool {
+        self.owner.as_deref().is_none_or(|o| o == owner)
+    }
+
+    /// Load baum manifest from a YAML file, migrating older formats
     pub fn load(path: &Path) -> Result<Self> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("failed to read baum manifest: {}", path.display()))?;
-        let manifest: BaumManifest = serde_yml::from_str(&content)
-            .with_context(|| format!("failed to parse baum manifest: {}", path.display()))?;
-        Ok(manifest)
+        load_yaml(path)
     }
 
     /// Save baum manifest to a YAML file
     pub fn save(&self, path: &Path) -> Result<()> {
-        let content = serde_yml::to_string(self).context("failed to serialize baum manifest")?;
-        fs::write(path, content)
-            .with_context(|| format!("failed to write baum manifest: {}", path.display()))?;
-        Ok(())
+        save_yaml(self, path, "baum manifest")
     }
 
     /// Add a worktree entry (legacy style, no local tracking branch)
@@ -273,16 +618,32 @@ impl BaumManifest {
             branch: branch.to_string(),
             path: path.to_string(),
             local_branch: None,
+            track: None,
+            created_at: Some(format_iso(now_secs())),
+            created_from: None,
+            created_commit: None,
+            last_used: None,
         });
     }
 
     /// Add a worktree entry with a local tracking branch
-    pub fn add_worktree_with_local(&mut self, branch: &str, path: &str, local_branch: &str) {
+    pub fn add_worktree_with_local(
+        &mut self,
+        branch: &str,
+        path: &str,
+        local_branch: &str,
+    ) -> &mut WorktreeEntry {
         self.worktrees.push(WorktreeEntry {
             branch: branch.to_string(),
             path: path.to_string(),
             local_branch: Some(local_branch.to_string()),
+            track: None,
+            created_at: Some(format_iso(now_secs())),
+            created_from: None,
+            created_commit: None,
+            last_used: None,
         });
+        self.worktrees.last_mut().expect("just pushed")
     }
 
     /// Get or generate the baum ID
@@ -297,6 +658,62 @@ impl BaumManifest {
     }
 }
 
+/// Machine-local baum settings (`.baum/manifest.local.yaml`, not committed)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaumLocal {
+    /// File format version
+    #[serde(default = "current_version::<BaumLocal>")]
+    pub version: u32,
+    /// Environment for every worktree of the baum (`wald env`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Settings for single worktrees, by branch
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub worktrees: BTreeMap<String, WorktreeLocal>,
+}
+
+/// Machine-local settings of one worktree
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorktreeLocal {
+    /// Environment added to (and overriding) the baum's
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+impl Default for BaumLocal {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            env: BTreeMap::new(),
+            worktrees: BTreeMap::new(),
+        }
+    }
+}
+
+impl Versioned for BaumLocal {
+    const KIND: &'static str = "baum local manifest";
+    const MIGRATIONS: &'static [Migration] = &[adopt_versioning];
+}
+
+impl BaumLocal {
+    /// Load local settings from a YAML file; a missing file means none
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        load_yaml(path)
+    }
+
+    /// The environment of a branch's worktree, or of the baum as a whole
+    pub fn env_for(&self, branch: Option<&str>) -> BTreeMap<String, String> {
+        let mut env = self.env.clone();
+        if let Some(worktree) = branch.and_then(|b| self.worktrees.get(b)) {
+            env.extend(worktree.env.clone());
+        }
+        env
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -310,8 +727,21 @@ mod tests {
                 lfs: LfsPolicy::Full,
                 depth: DepthPolicy::Depth(50),
                 filter: FilterPolicy::BlobNone,
+                tags: TagPolicy::All,
+                url: None,
                 upstream: None,
+                reference: None,
                 aliases: vec!["repo".to_string()],
+                git_config: BTreeMap::from([(
+                    "user.email".to_string(),
+                    "me@example.com".to_string(),
+                )]),
+                identity: None,
+                branching: None,
+                group: None,
+                size: None,
+                bare_path: Some("/data/repo.git".to_string()),
+                post_fetch: vec!["git lfs fetch".to_string()],
             },
         );
 
@@ -320,6 +750,102 @@ mod tests {
 
         assert!(parsed.repos.contains_key("github.com/user/repo"));
         assert_eq!(parsed.repos["github.com/user/repo"].lfs, LfsPolicy::Full);
+        assert_eq!(
+            parsed.repos["github.com/user/repo"].git_config["user.email"],
+            "me@example.com"
+        );
+        assert_eq!(
+            parsed.repos["github.com/user/repo"].bare_path.as_deref(),
+            Some("/data/repo.git")
+        );
+        assert_eq!(
+            parsed.repos["github.com/user/repo"].post_fetch,
+            vec!["git lfs fetch"]
+        );
+    }
+
+    #[test]
+    fn test_expected_identity() {
+        let mut manifest = Manifest::default();
+        manifest.identities.insert(
+            "gitlab.work.example".to_string(),
+            "me@work.example".to_string(),
+        );
+        manifest.repos.insert(
+            "gitlab.work.example/team/app".to_string(),
+            RepoEntry::default(),
+        );
+        manifest.repos.insert(
+            "gitlab.work.example/team/oss".to_string(),
+            RepoEntry {
+                identity: Some("me@personal.example".to_string()),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            manifest.expected_identity("gitlab.work.example/team/app"),
+            Some("me@work.example")
+        );
+        // Per-repo identity wins over the host's
+        assert_eq!(
+            manifest.expected_identity("gitlab.work.example/team/oss"),
+            Some("me@personal.example")
+        );
+        assert_eq!(manifest.expected_identity("github.com/user/repo"), None);
+    }
+
+    #[test]
+    fn test_branching() {
+        let mut manifest = Manifest::default();
+        manifest
+            .repos
+            .insert("git.overleaf.com/abc123".to_string(), RepoEntry::default());
+        manifest.repos.insert(
+            "github.com/user/paper".to_string(),
+            RepoEntry {
+                branching: Some(Branching::Direct),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            manifest.branching("git.overleaf.com/abc123"),
+            Branching::Direct
+        );
+        assert_eq!(
+            manifest.branching("github.com/user/paper"),
+            Branching::Direct
+        );
+        assert_eq!(
+            manifest.branching("github.com/user/repo"),
+            Branching::Tracking
+        );
+    }
+
+    #[test]
+    fn test_group_repos() {
+        let mut manifest = Manifest::default();
+        for (repo_id, group) in [
+            ("github.com/org/billing", Some("backend")),
+            ("github.com/org/api", Some("backend")),
+            ("github.com/org/web", Some("frontend")),
+            ("github.com/org/docs", None),
+        ] {
+            manifest.repos.insert(
+                repo_id.to_string(),
+                RepoEntry {
+                    group: group.map(str::to_string),
+                    ..Default::default()
+                },
+            );
+        }
+
+        assert_eq!(
+            manifest.group_repos("backend"),
+            vec!["github.com/org/api", "github.com/org/billing"]
+        );
+        assert!(manifest.group_repos("ops").is_empty());
     }
 
     #[test]
@@ -347,18 +873,26 @@ mod tests {
     #[test]
     fn test_baum_manifest_roundtrip() {
         let mut baum = BaumManifest {
+            version: BaumManifest::VERSION,
             id: Some("abc123".to_string()),
             repo_id: "github.com/user/repo".to_string(),
+            default_branch: Some("main".to_string()),
+            owner: Some("me@example.com".to_string()),
             worktrees: vec![],
         };
         baum.add_worktree("main", "_main.wt");
-        baum.add_worktree_with_local("dev", "_dev.wt", "wald/abc123/dev");
+        let dev = baum.add_worktree_with_local("dev", "_dev.wt", "wald/abc123/dev");
+        dev.created_from = Some("origin/dev".to_string());
+        dev.created_commit = Some("1f2e3d4c".to_string());
 
         let yaml = serde_yml::to_string(&baum).unwrap();
         let parsed: BaumManifest = serde_yml::from_str(&yaml).unwrap();
 
         assert_eq!(parsed.id, Some("abc123".to_string()));
         assert_eq!(parsed.repo_id, "github.com/user/repo");
+        assert_eq!(parsed.default_branch.as_deref(), Some("main"));
+        assert!(parsed.is_owned_by("me@example.com"));
+        assert!(!parsed.is_owned_by("you@example.com"));
         assert_eq!(parsed.worktrees.len(), 2);
         assert_eq!(parsed.worktrees[0].branch, "main");
         assert_eq!(parsed.worktrees[0].local_branch, None);
@@ -366,6 +900,15 @@ mod tests {
             parsed.worktrees[1].local_branch,
             Some("wald/abc123/dev".to_string())
         );
+        assert_eq!(parsed.worktrees[0].created_from, None);
+        assert_eq!(
+            parsed.worktrees[1].created_from.as_deref(),
+            Some("origin/dev")
+        );
+        assert_eq!(
+            parsed.worktrees[1].created_commit.as_deref(),
+            Some("1f2e3d4c")
+        );
     }
 
     #[test]
@@ -382,13 +925,57 @@ worktrees:
         assert_eq!(parsed.worktrees[0].local_branch, None);
     }
 
+    #[test]
+    fn test_baum_manifest_load_versions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("manifest.yaml");
+
+        // Unversioned files load as the current version
+        std::fs::write(
+            &path,
+            "repo_id: github.com/user/repo
+",
+        )
+        .unwrap();
+        let baum = BaumManifest::load(&path).unwrap();
+        assert_eq!(baum.version, BaumManifest::VERSION);
+
+        // Files from a newer wald are refused
+        std::fs::write(
+            &path,
+            "version: 999
+repo_id: github.com/user/repo
+",
+        )
+        .unwrap();
+        let err = BaumManifest::load(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains("newer"));
+    }
+
+    #[test]
+    fn test_baum_local_env_for() {
+        let local: BaumLocal = serde_yml::from_str(
+            "env:\n  PORT: \"8080\"\n  DATA: /data\nworktrees:\n  feature:\n    env:\n      PORT: \"8081\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(local.env_for(None)["PORT"], "8080");
+        let feature = local.env_for(Some("feature"));
+        assert_eq!(feature["PORT"], "8081");
+        assert_eq!(feature["DATA"], "/data");
+        assert_eq!(local.env_for(Some("main"))["PORT"], "8080");
+    }
+
     #[test]
     fn test_baum_ensure_id() {
         use std::collections::HashSet;
 
         let mut baum = BaumManifest {
+            version: BaumManifest::VERSION,
             id: None,
             repo_id: "github.com/user/repo".to_string(),
+            default_branch: None,
+            owner: None,
             worktrees: vec![],
         };
 
@@ -402,6 +989,26 @@ worktrees:
         assert_eq!(id, id2);
     }
 
+    #[test]
+    fn test_name_matches() {
+        let mut manifest = Manifest::default();
+        for id in [
+            "github.com/a/tools",
+            "gitlab.com/b/tools",
+            "github.com/a/dots",
+        ] {
+            manifest.repos.insert(id.to_string(), RepoEntry::default());
+        }
+
+        assert_eq!(
+            manifest.name_matches("tools"),
+            vec!["github.com/a/tools", "gitlab.com/b/tools"]
+        );
+        assert_eq!(manifest.name_matches("a/dots"), vec!["github.com/a/dots"]);
+        assert!(manifest.name_matches("github.com/a/dots").is_empty());
+        assert!(manifest.name_matches("other").is_empty());
+    }
+
     // Edge case tests for alias resolution
 
     #[test]
@@ -611,4 +1218,64 @@ worktrees:
             Some("git.zib.de/cspiegel/group/repo")
         );
     }
+
+    #[test]
+    fn test_split_manifest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let wald = dir.path();
+        fs::create_dir(wald.join(MANIFEST_DIR)).unwrap();
+        fs::write(
+            wald.join("manifest.yaml"),
+            "version: 1\nrepos:\n  github.com/me/own: {}\n",
+        )
+        .unwrap();
+        fs::write(
+            wald.join(MANIFEST_DIR).join("team.yaml"),
+            "version: 1\nidentities:\n  gitlab.com: me@team.org\nrepos:\n  gitlab.com/team/app: {}\n",
+        )
+        .unwrap();
+
+        let mut manifest = Manifest::load_split(wald).unwrap();
+        assert!(manifest.has_repo("github.com/me/own"));
+        assert!(manifest.has_repo("gitlab.com/team/app"));
+        assert_eq!(
+            manifest.repo_file("gitlab.com/team/app"),
+            "manifest.d/team.yaml"
+        );
+        assert_eq!(manifest.repo_file("github.com/me/own"), "manifest.yaml");
+
+        // New entries go where they are assigned; the rest stay put
+        manifest
+            .repos
+            .insert("github.com/other/lib".to_string(), RepoEntry::default());
+        manifest.sources.repos.insert(
+            "github.com/other/lib".to_string(),
+            "github.com.yaml".to_string(),
+        );
+        manifest.save_split(wald).unwrap();
+
+        let main = Manifest::load(&wald.join("manifest.yaml")).unwrap();
+        assert_eq!(main.repos.keys().collect::<Vec<_>>(), ["github.com/me/own"]);
+        let team = Manifest::load(&wald.join(MANIFEST_DIR).join("team.yaml")).unwrap();
+        assert!(team.has_repo("gitlab.com/team/app"));
+        assert_eq!(team.identities.len(), 1);
+        let host = Manifest::load(&wald.join(MANIFEST_DIR).join("github.com.yaml")).unwrap();
+        assert_eq!(
+            host.repos.keys().collect::<Vec<_>>(),
+            ["github.com/other/lib"]
+        );
+
+        // A repo defined twice is an error naming both files
+        fs::write(
+            wald.join(MANIFEST_DIR).join("dup.yaml"),
+            "version: 1\nrepos:\n  github.com/me/own: {}\n",
+        )
+        .unwrap();
+        let err = Manifest::load_split(wald).unwrap_err().to_string();
+        assert!(
+            err.contains("manifest.yaml and manifest.d/dup.yaml"),
+            "{}",
+            err
+        );
+    }
 }
@@ -1,12 +1,21 @@
+mod backup;
 mod config;
 mod manifest;
+mod persist;
 mod repo_id;
+mod schema;
 mod state;
 
-pub use config::Config;
+pub use backup::{
+    BACKUP_DIR, BACKUPS_KEPT, Backup, CorruptFile, Restored, find_corrupt_files, restore_backup,
+};
+pub use config::{
+    CacheLocation, Config, DiscoveryConfig, HostCredentials, SecretSource, SymlinkPolicy, TeamMode,
+    WorktreeIgnore,
+};
 pub use manifest::{
-    BaumManifest, DepthPolicy, FilterPolicy, LfsPolicy, Manifest, RepoEntry, ResolveResult,
-    WorktreeEntry,
+    BaumLocal, BaumManifest, Branching, DepthPolicy, FilterPolicy, LfsPolicy, MANIFEST_DIR,
+    Manifest, RepoEntry, ResolveResult, TagPolicy, WorktreeEntry, WorktreeLocal,
 };
-pub use repo_id::RepoId;
-pub use state::SyncState;
+pub use repo_id::{RepoId, strip_credentials};
+pub use state::{DoctorCheck, SyncState};
@@ -0,0 +1,561 @@
+//! Crash-safe, comment-preserving persistence for wald's YAML files.
+//!
+//! serde_yml round-trips data but not formatting: comments vanish and
+//! `HashMap` keys come out in arbitrary order. Since the manifests are meant
+//! to be edited by hand and committed, saving merges the freshly serialized
+//! document with the file already on disk:
+//!
+//! - comment (and blank) lines stay attached to the entry that follows them
+//! - inline `# comments` stay on their entry's line
+//! - mapping keys keep their on-disk order; new keys slot in after their
+//!   predecessor in the serialized output
+//!
+//! Entries are matched by key (or, for sequence items, by their first line),
+//! so values wald changes are written as serialized while everything around
+//! them is kept. Block scalar bodies (`|`, `>`) are opaque: their lines are
+//! data, never comments, and are always written as serialized.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::backup;
+use crate::git::runner;
+use crate::timings;
+
+/// Serialize `value` and write it to `path`, keeping the existing file's
+/// comments and key order
+pub(crate) fn save_yaml<T: Serialize>(value: &T, path: &Path, what: &str) -> Result<()> {
+    if runner::simulated(|| format!("write {}", path.display())) {
+        return Ok(());
+    }
+    timings::time("yaml write", || {
+        let fresh =
+            serde_yml::to_string(value).with_context(|| format!("failed to serialize {}", what))?;
+
+        let content = match fs::read_to_string(path) {
+            Ok(existing) => preserve_format(&existing, &fresh),
+            Err(_) => fresh,
+        };
+        // Both hand edits and what wald writes can be restored later
+        backup::keep_backup(path);
+        write_atomic(path, content.as_bytes())
+            .with_context(|| format!("failed to write {}: {}", what, path.display()))?;
+        backup::keep_backup(path);
+        Ok(())
+    })
+}
+
+/// Write a file atomically: write a sibling temp file, sync, then rename over
+/// the target so readers never observe a truncated file
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let tmp = temp_path(path);
+
+    let result = (|| -> Result<()> {
+        let mut file = fs::File::create(&tmp)
+            .with_context(|| format!("failed to create {}", tmp.display()))?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp.{}", name, std::process::id()))
+}
+
+/// A YAML entry (mapping key or sequence item) with its nested entries
+#[derive(Debug)]
+struct Entry<'a> {
+    /// Comment and blank lines directly above the entry
+    leading: Vec<&'a str>,
+    /// The entry's first line, without any inline comment
+    line: &'a str,
+    /// Inline comment on the first line, including the `#`
+    comment: Option<&'a str>,
+    /// Matching identity among siblings
+    id: String,
+    /// Whether this entry is a sequence item (`- ...`)
+    is_item: bool,
+    /// Lines of a block scalar value (`key: |`), kept verbatim
+    body: Vec<&'a str>,
+    children: Vec<Entry<'a>>,
+}
+
+/// Parsed document: top-level entries plus comments after the last one
+struct Document<'a> {
+    entries: Vec<Entry<'a>>,
+    trailing: Vec<&'a str>,
+}
+
+/// Merge comments and key order from `existing` into `fresh`
+fn preserve_format(existing: &str, fresh: &str) -> String {
+    let old = parse(existing);
+    let new = parse(fresh);
+
+    let mut out = String::new();
+    emit_merged(&new.entries, Some(&old.entries), &mut out);
+    for line in &old.trailing {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+fn parse(text: &str) -> Document<'_> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut pos = 0;
+    let entries = parse_block(&lines, &mut pos, None);
+
+    // Anything left is comments/blank lines after the last entry
+    let trailing = lines[pos..].to_vec();
+    Document { entries, trailing }
+}
+
+/// Parse sibling entries until a line that belongs to an enclosing block.
+///
+/// `parent` is the enclosing entry's (indent, is_bare_key) — a bare `key:`
+/// line may own a sequence at its own indent, the style serde_yml emits.
+fn parse_block<'a>(
+    lines: &[&'a str],
+    pos: &mut usize,
+    parent: Option<(usize, bool)>,
+) -> Vec<Entry<'a>> {
+    let mut entries = Vec::new();
+    let mut sibling_indent: Option<usize> = None;
+
+    loop {
+        // Collect comments up to the next content line
+        let start = *pos;
+        let mut next = start;
+        while next < lines.len() && is_comment(lines[next]) {
+            next += 1;
+        }
+        if next >= lines.len() {
+            // Leave trailing comments for the caller
+            return entries;
+        }
+
+        let line = lines[next];
+        let indent = indent_of(line);
+        let item = is_item(line);
+
+        let belongs = match parent {
+            None => true,
+            Some((p_indent, bare_key)) => {
+                indent > p_indent || (bare_key && item && indent == p_indent)
+            }
+        };
+        if !belongs || sibling_indent.is_some_and(|s| indent < s) {
+            return entries;
+        }
+        sibling_indent.get_or_insert(indent);
+
+        let (content, comment) = split_comment(line);
+        *pos = next + 1;
+
+        let (body, children) = match block_scalar_chomping(content) {
+            Some(chomping) => {
+                let body = parse_block_scalar(lines, pos, key_column(line), chomping);
+                (body, Vec::new())
+            }
+            None => {
+                let bare_key = !item && content.trim_end().ends_with(':');
+                (
+                    Vec::new(),
+                    parse_block(lines, pos, Some((indent, bare_key))),
+                )
+            }
+        };
+
+        entries.push(Entry {
+            leading: lines[start..next].to_vec(),
+            line: content,
+            comment,
+            id: entry_id(content),
+            is_item: item,
+            body,
+            children,
+        });
+    }
+}
+
+/// If the line opens a block scalar (`key: |-`, `- >`), return its
+/// chomping indicator (`+`, `-` or none)
+fn block_scalar_chomping(content: &str) -> Option<Option<char>> {
+    let t = content.trim();
+    let header = t
+        .rsplit_once(": ")
+        .map(|(_, h)| h)
+        .or_else(|| t.strip_prefix("- "))
+        .or(Some(t).filter(|_| is_item(t)))?
+        .trim();
+
+    let mut chars = header.chars();
+    if !matches!(chars.next(), Some('|' | '>')) {
+        return None;
+    }
+    let indicators: Vec<char> = chars.collect();
+    if !indicators
+        .iter()
+        .all(|c| c.is_ascii_digit() || *c == '+' || *c == '-')
+    {
+        return None;
+    }
+    Some(indicators.into_iter().find(|c| *c == '+' || *c == '-'))
+}
+
+/// Consume the body of a block scalar: blank lines and lines indented past
+/// the owning key. Trailing blank lines only belong to the value with the
+/// keep (`+`) indicator.
+fn parse_block_scalar<'a>(
+    lines: &[&'a str],
+    pos: &mut usize,
+    key_column: usize,
+    chomping: Option<char>,
+) -> Vec<&'a str> {
+    let start = *pos;
+    let mut end = start;
+    while end < lines.len() && (lines[end].trim().is_empty() || indent_of(lines[end]) > key_column)
+    {
+        end += 1;
+    }
+    if chomping != Some('+') {
+        while end > start && lines[end - 1].trim().is_empty() {
+            end -= 1;
+        }
+    }
+    *pos = end;
+    lines[start..end].to_vec()
+}
+
+/// Column of the key on a line, past any `- ` sequence markers
+fn key_column(line: &str) -> usize {
+    let mut col = indent_of(line);
+    let mut rest = &line[col..];
+    while let Some(r) = rest.strip_prefix("- ") {
+        let r_trimmed = r.trim_start_matches(' ');
+        col += rest.len() - r_trimmed.len();
+        rest = r_trimmed;
+    }
+    col
+}
+
+fn is_comment(line: &str) -> bool {
+    let t = line.trim();
+    t.is_empty() || t.starts_with('#') || t == "---"
+}
+
+fn is_item(line: &str) -> bool {
+    let t = line.trim_start();
+    t == "-" || t.starts_with("- ")
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Split off an inline comment (` # ...`) that is outside quotes.
+/// Returns the content with trailing whitespace removed.
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        match c {
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single && prev != '\\' => in_double = !in_double,
+            '#' if !in_single && !in_double && prev.is_whitespace() => {
+                return (line[..i].trim_end(), Some(&line[i..]));
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    (line.trim_end(), None)
+}
+
+/// Identity used to match an entry with its counterpart in another version
+fn entry_id(content: &str) -> String {
+    let t = content.trim();
+    if is_item(t) {
+        return t.to_string();
+    }
+    // Mapping key: everything up to the first `:` followed by space/end
+    let bytes = t.as_bytes();
+    let mut quote = None;
+    for (i, &b) in bytes.iter().enumerate() {
+        match (quote, b) {
+            (None, b'"' | b'\'') => quote = Some(b),
+            (Some(q), c) if c == q => quote = None,
+            (None, b':') if i + 1 == bytes.len() || bytes[i + 1] == b' ' => {
+                return t[..i].trim_matches(|c| c == '"' || c == '\'').to_string();
+            }
+            _ => {}
+        }
+    }
+    t.to_string()
+}
+
+/// Find the old counterpart of each new entry, matching ids in order
+fn match_entries(new: &[Entry], old: &[Entry]) -> Vec<Option<usize>> {
+    let mut used = vec![false; old.len()];
+    new.iter()
+        .map(|n| {
+            let found = old
+                .iter()
+                .enumerate()
+                .position(|(i, o)| !used[i] && o.id == n.id);
+            if let Some(i) = found {
+                used[i] = true;
+            }
+            found
+        })
+        .collect()
+}
+
+/// Order new entries: mapping keys follow the old file, new keys go right
+/// after their predecessor. Sequence items keep the serialized order, since
+/// their order is data.
+fn merged_order(new: &[Entry], matches: &[Option<usize>]) -> Vec<usize> {
+    if new.iter().any(|e| e.is_item) {
+        return (0..new.len()).collect();
+    }
+
+    let mut matched: Vec<usize> = (0..new.len()).filter(|&i| matches[i].is_some()).collect();
+    matched.sort_by_key(|&i| matches[i]);
+
+    let mut order = matched;
+    for (i, m) in matches.iter().enumerate() {
+        if m.is_some() {
+            continue;
+        }
+        let at = if i == 0 {
+            0
+        } else {
+            order
+                .iter()
+                .position(|&j| j == i - 1)
+                .map(|p| p + 1)
+                .unwrap_or(order.len())
+        };
+        order.insert(at, i);
+    }
+    order
+}
+
+fn emit_merged(new: &[Entry], old: Option<&[Entry]>, out: &mut String) {
+    let old = old.unwrap_or(&[]);
+    let matches = match_entries(new, old);
+
+    for i in merged_order(new, &matches) {
+        let entry = &new[i];
+        let counterpart = matches[i].map(|j| &old[j]);
+
+        // Comments come from the file on disk; the serialized document only
+        // has blank lines, which are kept when the entry is new
+        let leading = counterpart.map_or(&entry.leading, |o| &o.leading);
+        for line in leading {
+            out.push_str(line);
+            out.push('\n');
+        }
+
+        out.push_str(entry.line);
+        if let Some(comment) = entry.comment.or(counterpart.and_then(|o| o.comment)) {
+            out.push(' ');
+            out.push_str(comment);
+        }
+        out.push('\n');
+
+        for line in &entry.body {
+            out.push_str(line);
+            out.push('\n');
+        }
+
+        emit_merged(
+            &entry.children,
+            counterpart.map(|o| o.children.as_slice()),
+            out,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_preserve_without_comments_is_identity() {
+        let yaml = "repo_id: github.com/a/b\nworktrees:\n- branch: main\n  path: _main.wt\n";
+        assert_eq!(preserve_format(yaml, yaml), yaml);
+    }
+
+    #[test]
+    fn test_preserve_comments() {
+        let existing = "\
+# My project
+id: abc123 # do not change
+repo_id: github.com/a/b
+worktrees:
+# the main checkout
+- branch: main
+  path: _main.wt
+";
+        let fresh = "\
+id: abc123
+repo_id: github.com/a/b
+worktrees:
+- branch: main
+  path: _main.wt
+- branch: dev
+  path: _dev.wt
+";
+        let merged = preserve_format(existing, fresh);
+        assert_eq!(
+            merged,
+            "\
+# My project
+id: abc123 # do not change
+repo_id: github.com/a/b
+worktrees:
+# the main checkout
+- branch: main
+  path: _main.wt
+- branch: dev
+  path: _dev.wt
+"
+        );
+    }
+
+    #[test]
+    fn test_preserve_key_order() {
+        let existing = "\
+repos:
+  github.com/z/last:
+    lfs: minimal
+  # first one
+  github.com/a/first:
+    lfs: full
+";
+        // HashMap serialization order differs from the file
+        let fresh = "\
+repos:
+  github.com/a/first:
+    lfs: full
+  github.com/m/new:
+    lfs: skip
+  github.com/z/last:
+    lfs: minimal
+";
+        let merged = preserve_format(existing, fresh);
+        assert_eq!(
+            merged,
+            "\
+repos:
+  github.com/z/last:
+    lfs: minimal
+  # first one
+  github.com/a/first:
+    lfs: full
+  github.com/m/new:
+    lfs: skip
+"
+        );
+    }
+
+    #[test]
+    fn test_removed_entry_drops_its_comments() {
+        let existing = "a: 1\n# about b\nb: 2\n# trailing\n";
+        let fresh = "a: 1\n";
+        assert_eq!(preserve_format(existing, fresh), "a: 1\n# trailing\n");
+    }
+
+    #[test]
+    fn test_multiline_values_roundtrip() {
+        #[derive(Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct S {
+            reason: String,
+            keep: String,
+            items: Vec<Item>,
+            after: u32,
+        }
+        #[derive(Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Item {
+            note: String,
+            name: String,
+        }
+
+        let value = S {
+            reason: "line one\n\n# not a comment\nline three".to_string(),
+            keep: "a\n\n".to_string(),
+            items: vec![Item {
+                note: "  indented\n\n# x\nend".to_string(),
+                name: "first".to_string(),
+            }],
+            after: 1,
+        };
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("doc.yaml");
+        fs::write(&path, "# header\nreason: old\n").unwrap();
+
+        // Save twice: once over a plain file, once over its own output
+        save_yaml(&value, &path, "doc").unwrap();
+        save_yaml(&value, &path, "doc").unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# header\n"));
+        let parsed: S = serde_yml::from_str(&content).unwrap();
+        assert_eq!(parsed, value);
+    }
+
+    #[test]
+    fn test_block_scalar_detection() {
+        assert_eq!(block_scalar_chomping("reason: |-"), Some(Some('-')));
+        assert_eq!(block_scalar_chomping("- keep: |+"), Some(Some('+')));
+        assert_eq!(block_scalar_chomping("lead: |2-"), Some(Some('-')));
+        assert_eq!(block_scalar_chomping("z: >"), Some(None));
+        assert_eq!(block_scalar_chomping("a: b|c"), None);
+        assert_eq!(block_scalar_chomping("a: '|'"), None);
+    }
+
+    #[test]
+    fn test_split_comment_ignores_quoted_hash() {
+        assert_eq!(split_comment("a: '#x' # c"), ("a: '#x'", Some("# c")));
+        assert_eq!(split_comment("a: b#c"), ("a: b#c", None));
+    }
+
+    #[test]
+    fn test_save_yaml_atomic_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state.yaml");
+        fs::write(&path, "# keep me\nlast_sync: old\n").unwrap();
+
+        #[derive(Serialize)]
+        struct S {
+            last_sync: &'static str,
+        }
+        save_yaml(&S { last_sync: "new" }, &path, "state").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# keep me\nlast_sync: new\n"
+        );
+        // No temp files left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,144 @@
+//! Format versions and migrations for wald's YAML files.
+//!
+//! Every persisted type carries a `version:` field. Loading reads the raw
+//! YAML, upgrades it step by step to the current version, and only then
+//! deserializes. Migrations happen in memory: the upgraded file is written
+//! back the next time a command saves it, so read-only commands never touch
+//! the workspace. Files from a newer wald are rejected rather than misread.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::de::DeserializeOwned;
+use serde_yml::{Mapping, Value};
+
+use crate::timings;
+
+/// Upgrades a document from version N to N + 1
+pub(crate) type Migration = fn(&mut Mapping) -> Result<()>;
+
+/// A persisted type with a versioned on-disk format
+pub(crate) trait Versioned: DeserializeOwned {
+    /// Human-readable file kind for error messages
+    const KIND: &'static str;
+
+    /// `MIGRATIONS[n]` upgrades version n to n + 1
+    const MIGRATIONS: &'static [Migration];
+
+    /// Current format version
+    const VERSION: u32 = Self::MIGRATIONS.len() as u32;
+}
+
+/// Serde default for `version:` when a type is deserialized directly
+pub(crate) fn current_version<T: Versioned>() -> u32 {
+    T::VERSION
+}
+
+/// Version 0 is any file written before versioning existed. The layout is
+/// unchanged; the file only gains its `version:` field.
+pub(crate) fn adopt_versioning(_doc: &mut Mapping) -> Result<()> {
+    Ok(())
+}
+
+/// Load a versioned YAML file, migrating it to the current version
+pub(crate) fn load_yaml<T: Versioned>(path: &Path) -> Result<T> {
+    timings::time("yaml read", || {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}: {}", T::KIND, path.display()))?;
+        parse_yaml(&content)
+            .with_context(|| format!("failed to parse {}: {}", T::KIND, path.display()))
+    })
+}
+
+/// Parse versioned YAML content, migrating it to the current version
+pub(crate) fn parse_yaml<T: Versioned>(content: &str) -> Result<T> {
+    let mut doc = match serde_yml::from_str::<Value>(content)? {
+        Value::Mapping(map) => map,
+        // An empty file holds no data yet
+        Value::Null => Mapping::new(),
+        _ => bail!("expected a mapping at the top level"),
+    };
+
+    migrate::<T>(&mut doc)?;
+
+    Ok(serde_yml::from_value(Value::Mapping(doc))?)
+}
+
+fn migrate<T: Versioned>(doc: &mut Mapping) -> Result<()> {
+    let version = match doc.get("version") {
+        None => 0,
+        Some(v) => match v.as_u64() {
+            Some(v) => v,
+            None => bail!("invalid version field: expected a number"),
+        },
+    };
+
+    if version > u64::from(T::VERSION) {
+        bail!(
+            "{} format version {} is newer than this wald supports ({}); upgrade wald",
+            T::KIND,
+            version,
+            T::VERSION
+        );
+    }
+
+    for step in &T::MIGRATIONS[version as usize..] {
+        step(doc)?;
+    }
+    doc.insert("version".into(), T::VERSION.into());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Doc {
+        version: u32,
+        name: String,
+    }
+
+    fn rename_title(doc: &mut Mapping) -> Result<()> {
+        if let Some(title) = doc.remove("title") {
+            doc.insert("name".into(), title);
+        }
+        Ok(())
+    }
+
+    impl Versioned for Doc {
+        const KIND: &'static str = "doc";
+        const MIGRATIONS: &'static [Migration] = &[adopt_versioning, rename_title];
+    }
+
+    #[test]
+    fn test_unversioned_is_migrated() {
+        let doc: Doc = parse_yaml("title: x").unwrap();
+        assert_eq!(doc.version, 2);
+        assert_eq!(doc.name, "x");
+    }
+
+    #[test]
+    fn test_partial_migration() {
+        // Already at v2: rename_title must not run again
+        let doc: Doc = parse_yaml("version: 2\nname: y").unwrap();
+        assert_eq!(doc.name, "y");
+
+        let doc: Doc = parse_yaml("version: 1\ntitle: z").unwrap();
+        assert_eq!(doc.name, "z");
+    }
+
+    #[test]
+    fn test_newer_version_rejected() {
+        let err = parse_yaml::<Doc>("version: 3\nname: x").unwrap_err();
+        assert!(err.to_string().contains("newer than this wald supports"));
+    }
+
+    #[test]
+    fn test_invalid_version_rejected() {
+        assert!(parse_yaml::<Doc>("version: abc\nname: x").is_err());
+    }
+}
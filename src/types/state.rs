@@ -1,29 +1,92 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::Path;
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+use super::persist::save_yaml;
+use super::schema::{Migration, Versioned, adopt_versioning, current_version, load_yaml};
+
 /// Sync state (.wald/state.yaml, gitignored)
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncState {
+    /// File format version
+    #[serde(default = "current_version::<SyncState>")]
+    pub version: u32,
+
     /// Last sync commit hash
     #[serde(default)]
     pub last_sync: Option<String>,
+
+    /// Bulk operations (e.g. `repo fetch`) that didn't finish, by operation
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub unfinished: BTreeMap<String, BulkProgress>,
+
+    /// Repos whose clone failed on the last hydration (`sync`, `clone`),
+    /// retried after the others on the next
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_clones: Vec<String>,
+
+    /// IDs of the baums known to be in the workspace, so a new baum's ID
+    /// avoids them without a walk of the whole tree
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub baum_ids: BTreeSet<String>,
+
+    /// What the last `wald doctor` found, for the summary `wald` shows
+    /// without a command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_doctor: Option<DoctorCheck>,
+}
+
+/// Outcome of a `wald doctor` run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DoctorCheck {
+    /// When it ran (seconds since the Unix epoch)
+    pub checked_at: u64,
+    /// The errors it left (with `--fix`, those it had no fix for)
+    #[serde(default)]
+    pub errors: Vec<String>,
+    #[serde(default)]
+    pub warnings: usize,
+}
+
+/// Progress of a bulk operation over repos, kept so a rerun can resume
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkProgress {
+    /// When the run started (seconds since the Unix epoch)
+    pub started_at: u64,
+    /// Repos already done
+    #[serde(default)]
+    pub done: Vec<String>,
+}
+
+impl Default for SyncState {
+    fn default() -> Self {
+        Self {
+            version: Self::VERSION,
+            last_sync: None,
+            unfinished: BTreeMap::new(),
+            failed_clones: Vec::new(),
+            baum_ids: BTreeSet::new(),
+            last_doctor: None,
+        }
+    }
+}
+
+impl Versioned for SyncState {
+    const KIND: &'static str = "state";
+    const MIGRATIONS: &'static [Migration] = &[adopt_versioning];
 }
 
 impl SyncState {
-    /// Load state from a YAML file
+    /// Load state from a YAML file, migrating older formats
     pub fn load(path: &Path) -> Result<Self> {
         if !path.exists() {
             return Ok(Self::default());
         }
 
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("failed to read state: {}", path.display()))?;
-        let state: SyncState = serde_yml::from_str(&content)
-            .with_context(|| format!("failed to parse state: {}", path.display()))?;
-        Ok(state)
+        load_yaml(path)
     }
 
     /// Save state to a YAML file
@@ -37,16 +100,46 @@ impl SyncState {
             })?;
         }
 
-        let content = serde_yml::to_string(self).context("failed to serialize state")?;
-        fs::write(path, content)
-            .with_context(|| format!("failed to write state: {}", path.display()))?;
-        Ok(())
+        save_yaml(self, path, "state")
     }
 
     /// Update last sync to a new commit
     pub fn update_last_sync(&mut self, commit: &str) {
         self.last_sync = Some(commit.to_string());
     }
+
+    /// Start or resume a bulk operation; returns the repos already done
+    ///
+    /// An unfinished run older than `max_age` seconds is started over.
+    pub fn resume_bulk(&mut self, operation: &str, now: u64, max_age: u64) -> Vec<String> {
+        if let Some(progress) = self.unfinished.get(operation)
+            && now.saturating_sub(progress.started_at) <= max_age
+        {
+            return progress.done.clone();
+        }
+        self.unfinished.insert(
+            operation.to_string(),
+            BulkProgress {
+                started_at: now,
+                done: Vec::new(),
+            },
+        );
+        Vec::new()
+    }
+
+    /// Record a repo as done by a bulk operation
+    pub fn mark_bulk_done(&mut self, operation: &str, repo_id: &str) {
+        if let Some(progress) = self.unfinished.get_mut(operation)
+            && !progress.done.iter().any(|id| id == repo_id)
+        {
+            progress.done.push(repo_id.to_string());
+        }
+    }
+
+    /// Forget a bulk operation that completed
+    pub fn finish_bulk(&mut self, operation: &str) {
+        self.unfinished.remove(operation);
+    }
 }
 
 #[cfg(test)]
@@ -66,15 +159,50 @@ mod tests {
         assert_eq!(state.last_sync, Some("abc123".to_string()));
     }
 
+    #[test]
+    fn test_resume_bulk() {
+        let mut state = SyncState::default();
+        assert!(state.resume_bulk("repo fetch", 1_000, 100).is_empty());
+        state.mark_bulk_done("repo fetch", "github.com/a/b");
+        state.mark_bulk_done("repo fetch", "github.com/a/b");
+
+        // Rerun within the window resumes
+        assert_eq!(
+            state.resume_bulk("repo fetch", 1_050, 100),
+            vec!["github.com/a/b"]
+        );
+
+        // Too old: start over
+        assert!(state.resume_bulk("repo fetch", 2_000, 100).is_empty());
+        assert_eq!(state.unfinished["repo fetch"].started_at, 2_000);
+
+        state.finish_bulk("repo fetch");
+        assert!(state.unfinished.is_empty());
+    }
+
     #[test]
     fn test_state_roundtrip() {
         let state = SyncState {
+            version: SyncState::VERSION,
             last_sync: Some("def456".to_string()),
+            unfinished: BTreeMap::new(),
+            failed_clones: vec!["github.com/a/b".to_string()],
+            baum_ids: BTreeSet::from(["a1b2c3".to_string()]),
+            last_doctor: Some(DoctorCheck {
+                checked_at: 1_700_000_000,
+                errors: vec!["Missing bare repo".to_string()],
+                warnings: 2,
+            }),
         };
 
         let yaml = serde_yml::to_string(&state).unwrap();
         let parsed: SyncState = serde_yml::from_str(&yaml).unwrap();
 
         assert_eq!(parsed.last_sync, Some("def456".to_string()));
+        assert_eq!(parsed.failed_clones, vec!["github.com/a/b"]);
+        assert!(parsed.baum_ids.contains("a1b2c3"));
+        let check = parsed.last_doctor.unwrap();
+        assert_eq!(check.errors, vec!["Missing bare repo"]);
+        assert_eq!(check.warnings, 2);
     }
 }
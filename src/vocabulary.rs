@@ -0,0 +1,227 @@
+//! The words wald uses for its concepts
+//!
+//! wald calls a directory of worktrees a baum, creating one planting and
+//! removing one uprooting. With `vocabulary: plain` in .wald/config.yaml
+//! (or `WALD_VOCABULARY=plain`) help, messages, errors and JSON field names
+//! say container, create and remove instead. Commands answer to both sets
+//! of names either way, and file names like `.baum/` stay as they are.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Which words wald uses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Vocabulary {
+    /// baum, plant, uproot
+    #[default]
+    Wald,
+    /// container, create, remove
+    Plain,
+}
+
+/// wald's terms and their plain replacements, longer phrases first
+const TERMS: &[(&str, &str)] = &[
+    ("baum containers", "containers"),
+    ("baum container", "container"),
+    ("Baum containers", "Containers"),
+    ("Baum container", "Container"),
+    ("baums", "containers"),
+    ("Baums", "Containers"),
+    ("BAUMS", "CONTAINERS"),
+    ("baum", "container"),
+    ("Baum", "Container"),
+    ("BAUM", "CONTAINER"),
+    ("planting", "creating"),
+    ("Planting", "Creating"),
+    ("planted", "created"),
+    ("Planted", "Created"),
+    ("plants", "creates"),
+    ("plant", "create"),
+    ("Plant", "Create"),
+    ("uprooting", "removing"),
+    ("Uprooting", "Removing"),
+    ("uprooted", "removed"),
+    ("Uprooted", "Removed"),
+    ("uproots", "removes"),
+    ("uproot", "remove"),
+    ("Uproot", "Remove"),
+];
+
+impl Vocabulary {
+    /// The vocabulary named by `WALD_VOCABULARY`, if it names one
+    pub fn from_env() -> Option<Self> {
+        match std::env::var("WALD_VOCABULARY").ok()?.trim() {
+            "wald" => Some(Self::Wald),
+            "plain" => Some(Self::Plain),
+            _ => None,
+        }
+    }
+
+    /// `text` in this vocabulary
+    ///
+    /// Only whole words are replaced, so paths (`.baum/manifest.yaml`),
+    /// identifiers (`baum_id`) and flags (`--baum`) are left alone.
+    pub fn apply(self, text: &str) -> Cow<'_, str> {
+        if self == Self::Wald || !TERMS.iter().any(|(term, _)| text.contains(term)) {
+            return Cow::Borrowed(text);
+        }
+
+        let mut result = String::with_capacity(text.len() + 16);
+        let mut rest = text;
+        let mut prev: Option<char> = None;
+        while let Some(c) = rest.chars().next() {
+            let found = prev
+                .is_none_or(starts_word)
+                .then(|| {
+                    TERMS
+                        .iter()
+                        .find(|(term, _)| rest.starts_with(term) && ends_word(&rest[term.len()..]))
+                })
+                .flatten();
+            match found {
+                Some((term, plain)) => {
+                    result.push_str(plain);
+                    rest = &rest[term.len()..];
+                    prev = term.chars().last();
+                }
+                None => {
+                    result.push(c);
+                    rest = &rest[c.len_utf8()..];
+                    prev = Some(c);
+                }
+            }
+        }
+        Cow::Owned(result)
+    }
+
+    /// A JSON field name in this vocabulary (`baums_count` is
+    /// `containers_count` in plain words)
+    pub fn field(self, name: &str) -> Cow<'_, str> {
+        if self == Self::Wald {
+            return Cow::Borrowed(name);
+        }
+        let parts: Vec<&str> = name
+            .split('_')
+            .map(|part| match part {
+                "baum" => "container",
+                "baums" => "containers",
+                "planted" => "created",
+                "uprooted" => "removed",
+                part => part,
+            })
+            .collect();
+        Cow::Owned(parts.join("_"))
+    }
+
+    /// A field path of `wald api capabilities` (`baums[].container`) in
+    /// this vocabulary
+    pub fn field_path(self, path: &str) -> String {
+        path.split('.')
+            .map(|segment| match segment.strip_suffix("[]") {
+                Some(name) => format!("{}[]", self.field(name)),
+                None => self.field(segment).into_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Rename the fields of a JSON document into this vocabulary
+    ///
+    /// A field keeps its name where the new one is taken by another field
+    /// of the same object.
+    pub fn rename_fields(self, value: Value) -> Value {
+        if self == Self::Wald {
+            return value;
+        }
+        match value {
+            Value::Object(map) => {
+                let names: Vec<String> = map.keys().cloned().collect();
+                let mut renamed = Map::new();
+                for (name, value) in map {
+                    let new_name = self.field(&name);
+                    let name = if new_name != name && !names.iter().any(|n| *n == new_name) {
+                        new_name.into_owned()
+                    } else {
+                        name
+                    };
+                    renamed.insert(name, self.rename_fields(value));
+                }
+                Value::Object(renamed)
+            }
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|v| self.rename_fields(v)).collect())
+            }
+            value => value,
+        }
+    }
+}
+
+/// Whether a term may start after `c`
+fn starts_word(c: char) -> bool {
+    c.is_whitespace() || "(`'\"[<|".contains(c)
+}
+
+/// Whether a term followed by `rest` ends a word
+fn ends_word(rest: &str) -> bool {
+    let mut chars = rest.chars();
+    match chars.next() {
+        None => true,
+        Some('.') => chars.next().is_none_or(char::is_whitespace),
+        Some(c) => c.is_whitespace() || ",;:!?)(`'\"]>|".contains(c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_replaces_whole_words() {
+        let plain = Vocabulary::Plain;
+        assert_eq!(
+            plain.apply("Planted repo with 2 worktree(s) in the baum."),
+            "Created repo with 2 worktree(s) in the container."
+        );
+        assert_eq!(
+            plain.apply("Baums: 3 planted; run `wald uproot <BAUM>`"),
+            "Containers: 3 created; run `wald remove <CONTAINER>`"
+        );
+        assert_eq!(
+            plain.apply("the baum container's owner"),
+            "the container's owner"
+        );
+        assert_eq!(
+            plain.apply("see .baum/manifest.yaml, baum_id, --baum and tools/plant"),
+            "see .baum/manifest.yaml, baum_id, --baum and tools/plant"
+        );
+        assert_eq!(
+            plain.apply("transplanted baumkuchen"),
+            "transplanted baumkuchen"
+        );
+        assert_eq!(Vocabulary::Wald.apply("a baum"), "a baum");
+    }
+
+    #[test]
+    fn test_rename_fields() {
+        let plain = Vocabulary::Plain;
+        let doc = json!({
+            "baums_count": 1,
+            "baums": [{"container": "a", "baum_id": "x"}],
+            "worktrees": [{"baum": "a", "container": "b"}],
+        });
+        assert_eq!(
+            plain.rename_fields(doc),
+            json!({
+                "containers_count": 1,
+                "containers": [{"container": "a", "container_id": "x"}],
+                "worktrees": [{"baum": "a", "container": "b"}],
+            })
+        );
+        assert_eq!(plain.field_path("baums[].repo_id"), "containers[].repo_id");
+        assert_eq!(plain.field_path("repos.*.baum_id"), "repos.*.container_id");
+    }
+}
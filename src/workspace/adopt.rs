@@ -0,0 +1,243 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use super::baum::{create_baum, is_baum, save_baum};
+use super::gitignore::add_worktree_to_gitignore;
+use super::{Workspace, configure_worktree, new_baum_owner};
+use crate::git::{self, CheckoutInfo};
+use crate::id::format_wald_branch;
+use crate::naming::worktree_dir_name;
+use crate::types::BaumManifest;
+
+/// A checkout turned into a baum by `adopt_checkout`
+#[derive(Debug)]
+pub struct Adopted {
+    /// Container path (the checkout's former location)
+    pub container: PathBuf,
+    /// Branch the checkout was on
+    pub branch: String,
+    /// Worktree directory name inside the container
+    pub worktree_name: String,
+    /// Identity pinned in the new worktree, if any
+    pub pinned_identity: Option<String>,
+}
+
+/// Turn a standalone clone inside the workspace into a baum at its path
+///
+/// The checkout's directory becomes the baum container and its files move,
+/// untouched, into a worktree of the registered bare repo for the branch it
+/// was on. Uncommitted, untracked and ignored files are all kept. Refuses
+/// when the clone holds state the bare repo would not have afterwards:
+/// stashes, or local branches with commits the bare repo lacks.
+pub fn adopt_checkout(ws: &Workspace, repo_id: &str, checkout: &CheckoutInfo) -> Result<Adopted> {
+    let root = checkout
+        .root
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", checkout.root.display()))?;
+    let ws_root = ws.root.canonicalize().unwrap_or_else(|_| ws.root.clone());
+
+    let Ok(rel) = root.strip_prefix(&ws_root) else {
+        bail!(
+            "cannot adopt {}: it is outside the workspace at {}",
+            root.display(),
+            ws_root.display()
+        );
+    };
+    if rel.as_os_str().is_empty() {
+        bail!("cannot adopt the workspace root itself");
+    }
+    if rel.starts_with(".wald") {
+        bail!("cannot adopt a checkout inside .wald/");
+    }
+    if let Some(baum) = root.ancestors().skip(1).find(|p| is_baum(p)) {
+        bail!(
+            "cannot adopt {}: it is inside the baum at {}",
+            root.display(),
+            baum.display()
+        );
+    }
+    if checkout.is_linked_worktree {
+        bail!("cannot adopt {}: it is a linked worktree", root.display());
+    }
+    let Some(branch) = checkout.branch.clone() else {
+        bail!(
+            "cannot adopt {}: HEAD is detached; check out a branch first",
+            root.display()
+        );
+    };
+
+    let bare_path = ws.bare_repo_path(repo_id)?;
+    if !bare_path.exists() {
+        bail!(
+            "bare repo not cloned: {} (adopting needs it; don't pass --no-clone)",
+            bare_path.display()
+        );
+    }
+
+    ensure_nothing_lost(&root, &bare_path, &branch)?;
+
+    let Some(name) = root.file_name() else {
+        bail!("cannot adopt {}", root.display());
+    };
+    let staging = root.with_file_name(format!(".{}.wald-adopt", name.to_string_lossy()));
+    if staging.exists() {
+        bail!(
+            "staging directory already exists: {} (left over from an interrupted adopt?)",
+            staging.display()
+        );
+    }
+
+    fs::rename(&root, &staging)
+        .with_context(|| format!("failed to move {} aside", root.display()))?;
+
+    let worktree_name = worktree_dir_name(&branch);
+    let worktree_path = root.join(&worktree_name);
+
+    // Until the files are in the worktree, a failure restores the checkout
+    // as it was
+    let mut manifest = match register_worktree(ws, repo_id, &bare_path, &staging, &root, &branch) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            restore_checkout(&root, &staging, &bare_path);
+            return Err(e);
+        }
+    };
+
+    let mut moved = Vec::new();
+    if let Err(e) = move_checkout_files(&staging, &worktree_path, &mut moved) {
+        if let Err(back) = move_back(&staging, &worktree_path, &moved) {
+            bail!(
+                "{:#}; putting the files back failed too: {:#}\n\
+                 The checkout's files are split between {} and {}. To restore it, move the \
+                 files in the worktree back, remove {}, rename {} to {} and run \
+                 `git -C {} worktree prune`",
+                e,
+                back,
+                staging.display(),
+                worktree_path.display(),
+                root.display(),
+                staging.display(),
+                root.display(),
+                bare_path.display()
+            );
+        }
+        restore_checkout(&root, &staging, &bare_path);
+        return Err(e);
+    }
+
+    git::reset_index(&worktree_path)?;
+
+    // Only the old .git directory is left; its branches are in the bare repo
+    fs::remove_dir_all(&staging)
+        .with_context(|| format!("failed to remove {}", staging.display()))?;
+
+    let local_branch = format_wald_branch(manifest.id.as_deref().unwrap_or_default(), &branch);
+    manifest.add_worktree_with_local(&branch, &worktree_name, &local_branch);
+    save_baum(&root, &manifest)?;
+    add_worktree_to_gitignore(&root, &worktree_name)?;
+
+    let pinned_identity =
+        configure_worktree(ws, repo_id, &bare_path, &worktree_path)?.map(|s| s.to_string());
+
+    Ok(Adopted {
+        container: root,
+        branch,
+        worktree_name,
+        pinned_identity,
+    })
+}
+
+/// Move the checkout's files (all but `.git`) from `staging` into the new
+/// worktree, recording each one moved in `moved`
+fn move_checkout_files(
+    staging: &Path,
+    worktree_path: &Path,
+    moved: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in fs::read_dir(staging)? {
+        let entry = entry?;
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        let target = worktree_path.join(entry.file_name());
+        fs::rename(entry.path(), &target)
+            .with_context(|| format!("failed to move {}", entry.path().display()))?;
+        moved.push(PathBuf::from(entry.file_name()));
+    }
+    Ok(())
+}
+
+/// Return the files `move_checkout_files` moved to `staging`
+fn move_back(staging: &Path, worktree_path: &Path, moved: &[PathBuf]) -> Result<()> {
+    for name in moved {
+        let from = worktree_path.join(name);
+        fs::rename(&from, staging.join(name))
+            .with_context(|| format!("failed to move {} back", from.display()))?;
+    }
+    Ok(())
+}
+
+/// Undo `register_worktree` and put the checkout back at `root`
+///
+/// Best effort: the bare repo keeps the fetched branch.
+fn restore_checkout(root: &Path, staging: &Path, bare_path: &Path) {
+    let _ = fs::remove_dir_all(root);
+    let _ = git::shell::worktree_prune(bare_path);
+    let _ = fs::rename(staging, root);
+}
+
+/// Refuse to adopt a clone whose stashes or local commits would be lost
+fn ensure_nothing_lost(checkout: &Path, bare_path: &Path, branch: &str) -> Result<()> {
+    if git::has_stash(checkout)? {
+        bail!(
+            "cannot adopt {}: it has stashed changes; apply or drop them first",
+            checkout.display()
+        );
+    }
+
+    // The current branch is fetched into the bare repo; others must be there
+    let missing: Vec<String> = git::local_branch_tips(checkout)?
+        .into_iter()
+        .filter(|(name, tip)| name != branch && !git::has_commit(bare_path, tip))
+        .map(|(name, _)| name)
+        .collect();
+
+    if !missing.is_empty() {
+        bail!(
+            "cannot adopt {}: local branches have commits the bare repo lacks: {}; push them first",
+            checkout.display(),
+            missing.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Create the baum and an empty worktree for the checkout's branch
+fn register_worktree(
+    ws: &Workspace,
+    repo_id: &str,
+    bare_path: &Path,
+    staging: &Path,
+    container: &Path,
+    branch: &str,
+) -> Result<BaumManifest> {
+    let mut manifest = create_baum(container, repo_id)?;
+    manifest.default_branch = git::bare::get_default_branch(bare_path).ok();
+    manifest.owner = new_baum_owner(ws);
+    let baum_id = ws.ensure_baum_id(&mut manifest).to_string();
+    save_baum(container, &manifest)?;
+
+    let local_branch = format_wald_branch(&baum_id, branch);
+    git::fetch_branch_from(bare_path, staging, branch, &local_branch)?;
+    git::set_branch_upstream(bare_path, &local_branch, &format!("origin/{}", branch));
+    git::add_worktree_no_checkout(
+        bare_path,
+        &container.join(worktree_dir_name(branch)),
+        &local_branch,
+    )?;
+
+    Ok(manifest)
+}
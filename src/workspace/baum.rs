@@ -1,10 +1,12 @@
 use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
 
-use crate::types::BaumManifest;
+use crate::git;
+use crate::time::{format_iso, parse_since};
+use crate::types::{BaumLocal, BaumManifest, WorktreeEntry};
 
 /// The baum directory name within a container
 pub const BAUM_DIR: &str = ".baum";
@@ -14,6 +16,17 @@ pub fn is_baum(path: &Path) -> bool {
     path.join(BAUM_DIR).is_dir()
 }
 
+/// The nearest baum container holding `path` (or `path` itself), without
+/// leaving `root` if given
+pub fn find_enclosing_baum(path: &Path, root: Option<&Path>) -> Option<PathBuf> {
+    let root = root.map(|r| r.canonicalize().unwrap_or_else(|_| r.to_path_buf()));
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    path.ancestors()
+        .take_while(|dir| root.as_ref().is_none_or(|r| dir.starts_with(r)))
+        .find(|dir| is_baum(dir))
+        .map(Path::to_path_buf)
+}
+
 /// Create a new baum in a container directory
 /// Returns the BaumManifest for the new baum
 pub fn create_baum(container: &Path, repo_id: &str) -> Result<BaumManifest> {
@@ -43,11 +56,7 @@ pub fn create_baum(container: &Path, repo_id: &str) -> Result<BaumManifest> {
 
     // Create initial manifest
     // ID will be generated on first save when worktrees are added
-    let manifest = BaumManifest {
-        id: None,
-        repo_id: repo_id.to_string(),
-        worktrees: vec![],
-    };
+    let manifest = BaumManifest::new(repo_id);
 
     // Save manifest
     manifest.save(&baum_dir.join("manifest.yaml"))?;
@@ -61,12 +70,41 @@ pub fn load_baum(container: &Path) -> Result<BaumManifest> {
     BaumManifest::load(&manifest_path)
 }
 
+/// Load a baum's machine-local settings (`.baum/manifest.local.yaml`)
+pub fn load_baum_local(container: &Path) -> Result<BaumLocal> {
+    BaumLocal::load(&container.join(BAUM_DIR).join("manifest.local.yaml"))
+}
+
 /// Save a baum manifest to a container directory
+///
+/// Each worktree's `last_used` is first brought up to date with the latest
+/// activity in its directory on this machine.
 pub fn save_baum(container: &Path, manifest: &BaumManifest) -> Result<()> {
     let manifest_path = container.join(BAUM_DIR).join("manifest.yaml");
+    let mut manifest = manifest.clone();
+    for wt in &mut manifest.worktrees {
+        if let Some(secs) = git::last_activity(&container.join(&wt.path))
+            && recorded_use(wt).is_none_or(|recorded| secs > recorded)
+        {
+            wt.last_used = Some(format_iso(secs));
+        }
+    }
     manifest.save(&manifest_path)
 }
 
+/// When a worktree was last used, here or as recorded by another machine
+/// (seconds since the Unix epoch)
+pub fn last_used(container: &Path, wt: &WorktreeEntry) -> Option<u64> {
+    let local = git::last_activity(&container.join(&wt.path));
+    local.max(recorded_use(wt))
+}
+
+fn recorded_use(wt: &WorktreeEntry) -> Option<u64> {
+    wt.last_used
+        .as_deref()
+        .and_then(|at| parse_since(at, 0).ok())
+}
+
 /// Save a baum manifest, auto-generating an ID if missing
 ///
 /// The existing_ids set is used to avoid ID collisions.
@@ -96,6 +134,33 @@ mod tests {
         assert!(is_baum(dir.path()));
     }
 
+    #[test]
+    fn test_find_enclosing_baum() {
+        let dir = TempDir::new().unwrap();
+        let container = dir.path().join("tools/repo");
+        fs::create_dir_all(container.join(".baum")).unwrap();
+        fs::create_dir_all(container.join("main/src")).unwrap();
+        let container = container.canonicalize().unwrap();
+
+        assert_eq!(
+            find_enclosing_baum(&container.join("main/src"), Some(dir.path())),
+            Some(container.clone())
+        );
+        assert_eq!(
+            find_enclosing_baum(&container, Some(dir.path())),
+            Some(container.clone())
+        );
+        assert_eq!(
+            find_enclosing_baum(&dir.path().join("tools"), Some(dir.path())),
+            None
+        );
+        // Not above the root
+        assert_eq!(
+            find_enclosing_baum(&container.join("main/src"), Some(&container.join("main"))),
+            None
+        );
+    }
+
     #[test]
     fn test_create_baum() {
         let dir = TempDir::new().unwrap();
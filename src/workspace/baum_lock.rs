@@ -0,0 +1,217 @@
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use super::baum::BAUM_DIR;
+use crate::time::{format_iso, now_secs};
+
+/// Lock file in .baum/ held while a command changes the baum
+const LOCK_FILE: &str = "lock";
+
+/// A lock taken on another machine (shared filesystem) can't be checked for
+/// a live process; it counts as stale after this long
+const FOREIGN_LOCK_STALE_SECS: u64 = 24 * 3_600;
+
+/// A lock file that can't be read yet may still be being written
+const UNREADABLE_LOCK_GRACE: Duration = Duration::from_secs(60);
+
+/// Who holds a baum lock, as written to `.baum/lock`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaumLockInfo {
+    pub pid: u32,
+    pub host: String,
+    /// Subcommand holding the lock, e.g. `branch`
+    pub command: String,
+    /// Seconds since the Unix epoch
+    pub acquired_at: u64,
+}
+
+impl BaumLockInfo {
+    /// Whether the process that took the lock is gone
+    pub fn is_stale(&self, now: u64) -> bool {
+        if self.host != hostname() {
+            return now.saturating_sub(self.acquired_at) > FOREIGN_LOCK_STALE_SECS;
+        }
+        !process_alive(self.pid)
+    }
+
+    /// e.g. "`wald branch` (pid 4242 on host, since 2026-10-16T14:23:00Z)"
+    pub fn describe(&self) -> String {
+        format!(
+            "`wald {}` (pid {} on {}, since {})",
+            self.command,
+            self.pid,
+            self.host,
+            format_iso(self.acquired_at)
+        )
+    }
+}
+
+/// Exclusive lock on a baum; released when dropped
+#[derive(Debug)]
+pub struct BaumLock {
+    path: PathBuf,
+}
+
+impl BaumLock {
+    /// Follow the container to where it was moved (e.g. into the trash)
+    pub fn moved_to(&mut self, container: &Path) {
+        self.path = lock_path(container);
+    }
+}
+
+impl Drop for BaumLock {
+    fn drop(&mut self) {
+        // Gone already if the command removed the .baum directory
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Lock a baum against concurrent changes by other wald processes
+///
+/// Fails if another live process holds the lock. A stale lock (its process
+/// is gone) is taken over.
+pub fn lock_baum(container: &Path, command: &str) -> Result<BaumLock> {
+    let path = lock_path(container);
+    let info = BaumLockInfo {
+        pid: process::id(),
+        host: hostname(),
+        command: command.to_string(),
+        acquired_at: now_secs(),
+    };
+    let content = serde_yml::to_string(&info)?;
+
+    // Second try after removing a stale lock
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                let lock = BaumLock { path: path.clone() };
+                file.write_all(content.as_bytes())
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                return Ok(lock);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                if let Some(holder) = held_by(&path) {
+                    bail!(
+                        "baum {} is busy: locked by {}\nIf that process is gone, run `wald doctor --fix` or remove {}",
+                        container.display(),
+                        holder,
+                        path.display()
+                    );
+                }
+                match fs::remove_file(&path) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == ErrorKind::NotFound => {}
+                    Err(e) => {
+                        return Err(e)
+                            .with_context(|| format!("failed to remove {}", path.display()));
+                    }
+                }
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("failed to create {}", path.display()));
+            }
+        }
+    }
+
+    bail!("failed to lock baum {}", container.display())
+}
+
+/// The baum's current lock, if any (stale or not)
+pub fn read_baum_lock(container: &Path) -> Option<(PathBuf, BaumLockInfo)> {
+    let path = lock_path(container);
+    let info = serde_yml::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+    Some((path, info))
+}
+
+/// Description of the live holder of a lock file, or None if it's stale
+fn held_by(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_yml::from_str::<BaumLockInfo>(&content) {
+        Ok(info) if info.is_stale(now_secs()) => None,
+        Ok(info) => Some(info.describe()),
+        Err(_) => {
+            let age = fs::metadata(path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| SystemTime::now().duration_since(t).ok())?;
+            (age < UNREADABLE_LOCK_GRACE).then(|| "another wald process".to_string())
+        }
+    }
+}
+
+fn lock_path(container: &Path) -> PathBuf {
+    container.join(BAUM_DIR).join(LOCK_FILE)
+}
+
+pub(super) fn hostname() -> String {
+    Command::new("uname")
+        .arg("-n")
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn process_alive(pid: u32) -> bool {
+    if Path::new("/proc/self").exists() {
+        return Path::new("/proc").join(pid.to_string()).exists();
+    }
+    // Signal 0 only checks that the process exists
+    Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_excludes_and_releases() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(BAUM_DIR)).unwrap();
+
+        let lock = lock_baum(dir.path(), "branch").unwrap();
+        let (_, info) = read_baum_lock(dir.path()).unwrap();
+        assert_eq!(info.pid, process::id());
+        assert_eq!(info.command, "branch");
+
+        let err = lock_baum(dir.path(), "prune").unwrap_err();
+        assert!(err.to_string().contains("locked by `wald branch`"));
+
+        drop(lock);
+        assert!(read_baum_lock(dir.path()).is_none());
+        lock_baum(dir.path(), "prune").unwrap();
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(BAUM_DIR)).unwrap();
+
+        // A pid that can't be running
+        let stale = BaumLockInfo {
+            pid: u32::MAX,
+            host: hostname(),
+            command: "branch".to_string(),
+            acquired_at: 0,
+        };
+        assert!(stale.is_stale(now_secs()));
+        fs::write(lock_path(dir.path()), serde_yml::to_string(&stale).unwrap()).unwrap();
+
+        let _lock = lock_baum(dir.path(), "prune").unwrap();
+        let (_, info) = read_baum_lock(dir.path()).unwrap();
+        assert_eq!(info.command, "prune");
+    }
+}
@@ -4,11 +4,14 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
-use walkdir::WalkDir;
 
-use crate::types::{BaumManifest, Config, Manifest, SyncState};
-use crate::workspace::baum::{BAUM_DIR, is_baum, load_baum};
+use crate::git::{self, CloneOptions};
+use crate::types::{
+    BaumManifest, CacheLocation, Config, DepthPolicy, MANIFEST_DIR, Manifest, RepoEntry, SyncState,
+};
+use crate::workspace::baum::load_baum;
 use crate::workspace::gitignore::ensure_gitignore_section;
+use crate::workspace::walk::{find_baum_dirs, walk_config};
 
 /// The wald directory name
 pub const WALD_DIR: &str = ".wald";
@@ -39,6 +42,24 @@ pub fn find_workspace_root_from(start: &Path) -> Result<PathBuf> {
     }
 }
 
+/// Names a workspace checkout in shared directories: the root's name and
+/// a hash of its path, so two checkouts of one workspace repo differ
+fn workspace_id(root: &Path) -> String {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    // FNV-1a, stable across Rust versions unlike the std hasher
+    let hash = root
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    let name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "workspace".to_string());
+    format!("{}-{:016x}", name, hash)
+}
+
 /// Workspace context holding paths and loaded configurations
 #[derive(Debug)]
 pub struct Workspace {
@@ -50,6 +71,8 @@ pub struct Workspace {
     pub config: Config,
     /// Sync state
     pub state: SyncState,
+    /// Show full paths instead of workspace-relative ones (`--absolute-paths`)
+    pub absolute_paths: bool,
 }
 
 impl Workspace {
@@ -59,12 +82,17 @@ impl Workspace {
         Self::load_from(root)
     }
 
+    /// Load the workspace containing `start` (the root or any directory below it)
+    pub fn load_containing(start: &Path) -> Result<Self> {
+        let root = find_workspace_root_from(start)?;
+        Self::load_from(root)
+    }
+
     /// Load workspace from a specific root
     pub fn load_from(root: PathBuf) -> Result<Self> {
         let wald_dir = root.join(WALD_DIR);
 
-        let manifest =
-            Manifest::load(&wald_dir.join("manifest.yaml")).context("failed to load manifest")?;
+        let manifest = Manifest::load_split(&wald_dir).context("failed to load manifest")?;
 
         let config = Config::load(&wald_dir.join("config.yaml")).unwrap_or_default();
 
@@ -75,9 +103,34 @@ impl Workspace {
             manifest,
             config,
             state,
+            absolute_paths: false,
         })
     }
 
+    /// A path for messages and output: relative to the workspace root when
+    /// it's inside the workspace, unchanged otherwise
+    pub fn display_path(&self, path: &Path) -> String {
+        if self.absolute_paths || path.is_relative() {
+            return path.display().to_string();
+        }
+        let relative = path.strip_prefix(&self.root).ok().or_else(|| {
+            // The root may be spelled differently (symlinks, `..`)
+            let root = self.root.canonicalize().ok()?;
+            path.strip_prefix(root).ok()
+        });
+        match relative {
+            Some(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+            Some(rel) => rel.display().to_string(),
+            None => path.display().to_string(),
+        }
+    }
+
+    /// Whether `.wald/` can't be written (e.g. a read-only mount), so only
+    /// commands that read the workspace can run
+    pub fn is_read_only(&self) -> bool {
+        !crate::filesystem::is_writable(&self.wald_dir())
+    }
+
     /// Get the .wald directory path
     pub fn wald_dir(&self) -> PathBuf {
         self.root.join(WALD_DIR)
@@ -88,11 +141,56 @@ impl Workspace {
         self.wald_dir().join("repos")
     }
 
+    /// Get the trash directory path (.wald/trash/)
+    pub fn trash_dir(&self) -> PathBuf {
+        self.wald_dir().join("trash")
+    }
+
+    /// Get the operation journal directory path (`journal/` in the cache
+    /// directory)
+    pub fn journal_dir(&self) -> PathBuf {
+        self.cache_dir().join("journal")
+    }
+
+    /// Where this machine's transient data goes: `.wald/`, or with
+    /// `cache: xdg` a directory of this workspace under the XDG cache home
+    pub fn cache_dir(&self) -> PathBuf {
+        self.cache_dir_for(&self.root)
+    }
+
+    /// The cache directory the workspace had at `root` (with `cache: xdg`
+    /// it's named after the workspace's location)
+    pub fn cache_dir_for(&self, root: &Path) -> PathBuf {
+        match self.config.cache {
+            CacheLocation::Workspace => self.wald_dir(),
+            CacheLocation::Xdg => {
+                let cache_home = env::var_os("XDG_CACHE_HOME")
+                    .filter(|dir| !dir.is_empty())
+                    .map(PathBuf::from)
+                    .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")));
+                match cache_home {
+                    Some(dir) => dir.join("wald").join(workspace_id(root)),
+                    None => self.wald_dir(),
+                }
+            }
+        }
+    }
+
     /// Get the manifest file path
     pub fn manifest_path(&self) -> PathBuf {
         self.wald_dir().join("manifest.yaml")
     }
 
+    /// Directory of manifest files merged into the manifest (.wald/manifest.d/)
+    pub fn manifest_dir(&self) -> PathBuf {
+        self.wald_dir().join(MANIFEST_DIR)
+    }
+
+    /// Get the config file path
+    pub fn config_path(&self) -> PathBuf {
+        self.wald_dir().join("config.yaml")
+    }
+
     /// Get the state file path
     pub fn state_path(&self) -> PathBuf {
         self.wald_dir().join("state.yaml")
@@ -100,7 +198,12 @@ impl Workspace {
 
     /// Save manifest to disk
     pub fn save_manifest(&self) -> Result<()> {
-        self.manifest.save(&self.manifest_path())
+        self.manifest.save_split(&self.wald_dir())
+    }
+
+    /// Save config to disk
+    pub fn save_config(&self) -> Result<()> {
+        self.config.save(&self.config_path())
     }
 
     /// Save state to disk
@@ -108,10 +211,24 @@ impl Workspace {
         self.state.save(&self.state_path())
     }
 
-    /// Get the bare repo path for a repo ID
+    /// Get the bare repo path for a repo ID: its `bare_path` if it has
+    /// one, else its place in `.wald/repos/`
     pub fn bare_repo_path(&self, repo_id: &str) -> Result<PathBuf> {
         let id = crate::types::RepoId::parse(repo_id)?;
-        Ok(self.repos_dir().join(id.to_bare_path()))
+        match self
+            .manifest
+            .repos
+            .get(repo_id)
+            .and_then(|e| e.bare_path.as_deref())
+        {
+            Some(path) => Ok(self.root.join(path)),
+            None => Ok(self.default_bare_repo_path(&id)),
+        }
+    }
+
+    /// Where a repo's bare clone goes without a `bare_path`
+    pub fn default_bare_repo_path(&self, id: &crate::types::RepoId) -> PathBuf {
+        self.repos_dir().join(id.to_bare_path())
     }
 
     /// Check if a bare repo exists
@@ -121,11 +238,66 @@ impl Workspace {
             .unwrap_or(false)
     }
 
+    /// How to clone a registered repo: its depth, filter, tags, config,
+    /// credentials and the clone it borrows objects from
+    pub fn clone_options(&self, repo_id: &str, entry: &RepoEntry) -> Result<CloneOptions> {
+        let reference = match &entry.reference {
+            Some(r) => Some(self.bare_repo_path(r)?),
+            None => None,
+        };
+        Ok(CloneOptions {
+            depth: match &entry.depth {
+                DepthPolicy::Full => None,
+                DepthPolicy::Depth(d) => Some(*d),
+            },
+            filter: entry.filter.as_git_arg().map(|s| s.to_string()),
+            url: Some(self.clone_url(repo_id, entry)?),
+            tags: entry.tags.clone(),
+            reference,
+            config: entry.git_config.clone(),
+            seed: None,
+            credential_helper: self.credential_helper(repo_id),
+            workspace_root: Some(self.root.clone()),
+        })
+    }
+
+    /// Where to clone a registered repo from: its `url`, or the one derived
+    /// from its ID, after the config's `url_rewrites`
+    pub fn clone_url(&self, repo_id: &str, entry: &RepoEntry) -> Result<String> {
+        let url = match &entry.url {
+            Some(url) => url.clone(),
+            None => crate::types::RepoId::parse(repo_id)?.to_clone_url(),
+        };
+        Ok(self.config.rewrite_url(&url))
+    }
+
+    /// The credential helper configured for a repo's host, if any
+    pub fn credential_helper(&self, repo_id: &str) -> Option<String> {
+        let host = repo_id.split('/').next()?;
+        self.config
+            .credentials
+            .get(host)
+            .map(git::credential_helper)
+    }
+
     /// Resolve a repo reference (ID or alias) to a repo ID
     pub fn resolve_repo(&self, reference: &str) -> Option<&str> {
         self.manifest.resolve_alias(reference)
     }
 
+    /// Repos of a group, failing if it has none
+    pub fn group_repos(&self, group: &str) -> Result<Vec<String>> {
+        let repos = self.manifest.group_repos(group);
+        if repos.is_empty() {
+            bail!(
+                "no repositories in group '{}'; set `group: {}` on them in the manifest or use `wald repo add --group`",
+                group,
+                group
+            );
+        }
+        Ok(repos)
+    }
+
     /// Initialize a new workspace at the given path
     ///
     /// Creates the .wald/ directory structure with:
@@ -212,56 +384,43 @@ impl Workspace {
     pub fn collect_baum_ids(&self) -> HashSet<String> {
         collect_baum_ids(&self.root)
     }
+
+    /// Give a baum an ID if it has none, avoiding the IDs issued in the
+    /// workspace so far
+    ///
+    /// Those are kept in state.yaml, so only the first ID issued on a
+    /// machine walks the workspace for them; `wald sync` adds the IDs of
+    /// baums planted elsewhere.
+    pub fn ensure_baum_id<'a>(&self, manifest: &'a mut BaumManifest) -> &'a str {
+        if manifest.id.is_none() {
+            let path = self.state_path();
+            let mut state = SyncState::load(&path).unwrap_or_default();
+            if state.baum_ids.is_empty() {
+                state.baum_ids = self.collect_baum_ids().into_iter().collect();
+            }
+            let id = manifest
+                .ensure_id(&state.baum_ids.iter().cloned().collect())
+                .to_string();
+            state.baum_ids.insert(id);
+            // Only a cache: if it can't be written, the next ID walks again
+            let _ = state.save(&path);
+        }
+        manifest.id.as_deref().expect("baum ID was just ensured")
+    }
 }
 
 /// Find all baums in a workspace directory
 ///
 /// Returns a list of (path, manifest) pairs for all discovered baums.
 pub fn find_all_baums(workspace_root: &Path) -> Vec<(PathBuf, BaumManifest)> {
-    let mut baums = Vec::new();
-
-    for entry in WalkDir::new(workspace_root)
-        .follow_links(false)
+    let config = walk_config(workspace_root);
+    find_baum_dirs(workspace_root, workspace_root, &config)
         .into_iter()
-        .filter_entry(|e| {
-            // Skip .git directories, .wald/repos, and _*.wt worktree directories
-            let name = e.file_name().to_string_lossy();
-            if name == ".git" {
-                return false;
-            }
-            if name == "repos"
-                && e.path()
-                    .parent()
-                    .map(|p| p.ends_with(".wald"))
-                    .unwrap_or(false)
-            {
-                return false;
-            }
-            // Skip worktree directories (no need to descend into them)
-            if e.file_type().is_dir() && name.starts_with('_') && name.ends_with(".wt") {
-                return false;
-            }
-            // Skip .baum directories themselves
-            if name == BAUM_DIR {
-                return false;
-            }
-            true
+        .filter_map(|path| {
+            let manifest = load_baum(&path).ok()?;
+            Some((path, manifest))
         })
-    {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
-
-        if entry.file_type().is_dir()
-            && is_baum(entry.path())
-            && let Ok(manifest) = load_baum(entry.path())
-        {
-            baums.push((entry.path().to_path_buf(), manifest));
-        }
-    }
-
-    baums
+        .collect()
 }
 
 /// Collect all baum IDs in a workspace directory
@@ -277,32 +436,20 @@ pub fn collect_baum_ids(workspace_root: &Path) -> HashSet<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::create_workspace;
     use std::fs;
     use tempfile::TempDir;
 
-    fn setup_workspace() -> TempDir {
-        let dir = TempDir::new().unwrap();
-        let wald = dir.path().join(".wald");
-        fs::create_dir_all(&wald).unwrap();
-        fs::write(wald.join("manifest.yaml"), "repos: {}").unwrap();
-        fs::write(
-            wald.join("config.yaml"),
-            "default_lfs: minimal\ndefault_depth: 100",
-        )
-        .unwrap();
-        dir
-    }
-
     #[test]
     fn test_find_workspace_root() {
-        let dir = setup_workspace();
+        let dir = create_workspace();
         let root = find_workspace_root_from(dir.path()).unwrap();
         assert_eq!(root, dir.path());
     }
 
     #[test]
     fn test_find_workspace_root_from_subdir() {
-        let dir = setup_workspace();
+        let dir = create_workspace();
         let subdir = dir.path().join("sub/deep/dir");
         fs::create_dir_all(&subdir).unwrap();
         let root = find_workspace_root_from(&subdir).unwrap();
@@ -311,12 +458,26 @@ mod tests {
 
     #[test]
     fn test_workspace_load() {
-        let dir = setup_workspace();
+        let dir = create_workspace();
         let ws = Workspace::load_from(dir.path().to_path_buf()).unwrap();
         assert_eq!(ws.root, dir.path());
         assert!(ws.manifest.repos.is_empty());
     }
 
+    #[test]
+    fn test_display_path() {
+        let dir = create_workspace();
+        let mut ws = Workspace::load_from(dir.path().to_path_buf()).unwrap();
+        let baum = dir.path().join("tools/repo");
+        assert_eq!(ws.display_path(&baum), "tools/repo");
+        assert_eq!(ws.display_path(dir.path()), ".");
+        assert_eq!(ws.display_path(Path::new("/elsewhere")), "/elsewhere");
+        assert_eq!(ws.display_path(Path::new("rel/path")), "rel/path");
+
+        ws.absolute_paths = true;
+        assert_eq!(ws.display_path(&baum), baum.display().to_string());
+    }
+
     #[test]
     fn test_workspace_init_creates_structure() {
         let dir = TempDir::new().unwrap();
@@ -397,4 +558,45 @@ mod tests {
         fs::create_dir_all(dir.path().join(".git")).unwrap();
         assert!(Workspace::is_git_repo(dir.path()));
     }
+
+    #[test]
+    fn test_workspace_id() {
+        let id = workspace_id(Path::new("/nonexistent/home/me/ws"));
+        assert!(id.starts_with("ws-"));
+        assert_eq!(id, workspace_id(Path::new("/nonexistent/home/me/ws")));
+        // Another checkout of the same workspace repo gets its own
+        assert_ne!(id, workspace_id(Path::new("/nonexistent/home/me/ws2/ws")));
+    }
+
+    #[test]
+    fn test_ensure_baum_id_caches_issued_ids() {
+        let dir = create_workspace();
+        let ws = Workspace::load_from(dir.path().to_path_buf()).unwrap();
+        let mut planted =
+            crate::workspace::create_baum(&dir.path().join("a"), "github.com/o/a").unwrap();
+        planted.id = Some("aaaaaa".to_string());
+        crate::workspace::baum::save_baum(&dir.path().join("a"), &planted).unwrap();
+
+        // The first ID walks the workspace and records what it found
+        let mut first = BaumManifest::new("github.com/o/b");
+        let first_id = ws.ensure_baum_id(&mut first).to_string();
+        assert_ne!(first_id, "aaaaaa");
+        let state = SyncState::load(&ws.state_path()).unwrap();
+        assert!(state.baum_ids.contains("aaaaaa"));
+        assert!(state.baum_ids.contains(&first_id));
+
+        // Later ones only read the cache
+        let mut planted =
+            crate::workspace::create_baum(&dir.path().join("c"), "github.com/o/c").unwrap();
+        planted.id = Some("cccccc".to_string());
+        crate::workspace::baum::save_baum(&dir.path().join("c"), &planted).unwrap();
+        let mut second = BaumManifest::new("github.com/o/d");
+        let second_id = ws.ensure_baum_id(&mut second).to_string();
+        let state = SyncState::load(&ws.state_path()).unwrap();
+        assert!(!state.baum_ids.contains("cccccc"));
+        assert!(state.baum_ids.contains(&second_id));
+
+        // A baum that has an ID keeps it
+        assert_eq!(ws.ensure_baum_id(&mut first), first_id);
+    }
 }
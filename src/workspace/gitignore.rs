@@ -1,8 +1,14 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 
+use super::baum::BAUM_DIR;
+use super::discovery::find_workspace_root_from;
+use super::walk::{find_baum_dirs, walk_config};
+use crate::git;
+use crate::types::WorktreeIgnore;
+
 /// Markers for wald-managed gitignore section (per ADR-004)
 const GITIGNORE_MARKER_START: &str = "# wald:start (managed by wald, do not edit)";
 const GITIGNORE_MARKER_END: &str = "# wald:end";
@@ -11,7 +17,11 @@ const GITIGNORE_MARKER_END: &str = "# wald:end";
 const GITIGNORE_PATTERNS: &[&str] = &[
     ".wald/repos/",
     ".wald/state.yaml",
+    ".wald/trash/",
+    ".wald/journal/",
+    ".wald/backups/",
     "**/.baum/manifest.local.yaml",
+    "**/.baum/lock",
     "**/_*.wt/",
 ];
 
@@ -25,12 +35,7 @@ pub fn ensure_gitignore_section(workspace_root: &Path) -> Result<()> {
         String::new()
     };
 
-    // Check if section exists and is complete (has markers and all patterns)
-    let has_complete_section = content.contains(GITIGNORE_MARKER_START)
-        && content.contains(GITIGNORE_MARKER_END)
-        && GITIGNORE_PATTERNS.iter().all(|p| content.contains(p));
-
-    if has_complete_section {
+    if is_complete_section(&content) {
         return Ok(());
     }
 
@@ -59,6 +64,19 @@ pub fn ensure_gitignore_section(workspace_root: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Whether the workspace .gitignore has the complete wald managed section
+pub fn has_gitignore_section(workspace_root: &Path) -> bool {
+    fs::read_to_string(workspace_root.join(".gitignore"))
+        .is_ok_and(|content| is_complete_section(&content))
+}
+
+/// Whether the section exists and is complete (has markers and all patterns)
+fn is_complete_section(content: &str) -> bool {
+    content.contains(GITIGNORE_MARKER_START)
+        && content.contains(GITIGNORE_MARKER_END)
+        && GITIGNORE_PATTERNS.iter().all(|p| content.contains(p))
+}
+
 /// Remove existing wald section from gitignore content (inclusive of markers)
 fn remove_wald_section(content: &str) -> String {
     let start_idx = content.find(GITIGNORE_MARKER_START);
@@ -89,11 +107,52 @@ fn remove_wald_section(content: &str) -> String {
     }
 }
 
+/// Where a container's worktree patterns are kept, and the workspace
+/// whose `info/exclude` applies them (`worktree_ignore: exclude`)
+struct IgnoreTarget {
+    file: PathBuf,
+    exclude_root: Option<PathBuf>,
+}
+
+impl IgnoreTarget {
+    fn of(container: &Path) -> Self {
+        match find_workspace_root_from(container) {
+            Ok(root) if walk_config(&root).worktree_ignore == WorktreeIgnore::Exclude => Self {
+                file: container.join(BAUM_DIR).join("gitignore"),
+                exclude_root: Some(root),
+            },
+            _ => Self {
+                file: container.join(".gitignore"),
+                exclude_root: None,
+            },
+        }
+    }
+
+    /// Apply the file's patterns where git reads them
+    fn apply(&self) -> Result<()> {
+        match &self.exclude_root {
+            Some(root) => update_worktree_exclude(root),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The file holding a container's worktree patterns: its `.gitignore`, or
+/// `.baum/gitignore` with `worktree_ignore: exclude`
+pub fn worktree_ignore_file(container: &Path) -> PathBuf {
+    IgnoreTarget::of(container).file
+}
+
 /// Add a worktree pattern to the container's .gitignore
 pub fn add_worktree_to_gitignore(container: &Path, worktree_path: &str) -> Result<()> {
-    let gitignore_path = container.join(".gitignore");
+    let target = IgnoreTarget::of(container);
+    add_pattern(&target.file, worktree_path)?;
+    target.apply()
+}
+
+fn add_pattern(gitignore_path: &Path, worktree_path: &str) -> Result<()> {
     let content = if gitignore_path.exists() {
-        fs::read_to_string(&gitignore_path)
+        fs::read_to_string(gitignore_path)
             .with_context(|| format!("failed to read .gitignore: {}", gitignore_path.display()))?
     } else {
         String::new()
@@ -114,12 +173,183 @@ pub fn add_worktree_to_gitignore(container: &Path, worktree_path: &str) -> Resul
         format!("{}\n{}\n", content, pattern)
     };
 
-    fs::write(&gitignore_path, new_content)
+    fs::write(gitignore_path, new_content)
         .with_context(|| format!("failed to write .gitignore: {}", gitignore_path.display()))?;
 
     Ok(())
 }
 
+/// Remove a worktree's pattern from the container's .gitignore
+///
+/// The .gitignore is deleted if nothing else is left in it.
+pub fn remove_worktree_from_gitignore(container: &Path, worktree_path: &str) -> Result<()> {
+    let target = IgnoreTarget::of(container);
+    remove_patterns(&target.file, &[format!("/{}", worktree_path)])?;
+    target.apply()
+}
+
+/// Worktree patterns in a container's .gitignore that don't belong to any
+/// of `worktree_paths`, and worktrees without a pattern
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GitignoreDrift {
+    /// Patterns to remove (e.g. "/_old.wt")
+    pub stale: Vec<String>,
+    /// Worktree paths to add (e.g. "_main.wt")
+    pub missing: Vec<String>,
+}
+
+impl GitignoreDrift {
+    pub fn is_empty(&self) -> bool {
+        self.stale.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Compare a container's worktree patterns with its worktrees
+///
+/// Only lines shaped like the ones wald writes (`/_<name>.wt`) count as
+/// worktree patterns; anything else in the file is left alone.
+pub fn worktree_gitignore_drift(container: &Path, worktree_paths: &[String]) -> GitignoreDrift {
+    pattern_drift(&IgnoreTarget::of(container).file, worktree_paths)
+}
+
+fn pattern_drift(gitignore_path: &Path, worktree_paths: &[String]) -> GitignoreDrift {
+    let content = fs::read_to_string(gitignore_path).unwrap_or_default();
+    let patterns: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| is_worktree_pattern(line))
+        .collect();
+
+    let stale = patterns
+        .iter()
+        .filter(|p| !worktree_paths.iter().any(|wt| p[1..] == *wt))
+        .map(|p| p.to_string())
+        .collect();
+    let missing = worktree_paths
+        .iter()
+        .filter(|wt| {
+            !content
+                .lines()
+                .any(|line| line.trim() == format!("/{}", wt))
+        })
+        .cloned()
+        .collect();
+    GitignoreDrift { stale, missing }
+}
+
+/// Make a container's worktree patterns match its worktrees
+pub fn reconcile_worktree_gitignore(container: &Path, worktree_paths: &[String]) -> Result<()> {
+    let target = IgnoreTarget::of(container);
+    let drift = pattern_drift(&target.file, worktree_paths);
+    remove_patterns(&target.file, &drift.stale)?;
+    for path in &drift.missing {
+        add_pattern(&target.file, path)?;
+    }
+    target.apply()
+}
+
+fn is_worktree_pattern(line: &str) -> bool {
+    line.starts_with("/_") && line.ends_with(".wt") && !line[1..].contains('/')
+}
+
+fn remove_patterns(gitignore_path: &Path, patterns: &[String]) -> Result<()> {
+    let Ok(content) = fs::read_to_string(gitignore_path) else {
+        return Ok(());
+    };
+
+    let kept: Vec<&str> = content
+        .lines()
+        .filter(|line| !patterns.iter().any(|p| line.trim() == p))
+        .collect();
+    if kept.len() == content.lines().count() {
+        return Ok(());
+    }
+
+    if kept.iter().all(|line| line.trim().is_empty()) {
+        fs::remove_file(gitignore_path).with_context(|| {
+            format!("failed to remove .gitignore: {}", gitignore_path.display())
+        })?;
+    } else {
+        fs::write(gitignore_path, format!("{}\n", kept.join("\n")))
+            .with_context(|| format!("failed to write .gitignore: {}", gitignore_path.display()))?;
+    }
+    Ok(())
+}
+
+/// The workspace repo's `info/exclude`, its current content, and the
+/// content that applies every baum's `.baum/gitignore`
+///
+/// None for workspaces without a repo of their own.
+fn worktree_exclude(workspace_root: &Path) -> Option<(PathBuf, String, String)> {
+    if !workspace_root.join(".git").exists() {
+        return None;
+    }
+    let exclude_path = git::git_path(workspace_root, "info/exclude")?;
+    let current = fs::read_to_string(&exclude_path).unwrap_or_default();
+
+    let config = walk_config(workspace_root);
+    let mut patterns = Vec::new();
+    if config.worktree_ignore == WorktreeIgnore::Exclude {
+        let mut containers = find_baum_dirs(workspace_root, workspace_root, &config);
+        containers.sort();
+        for container in containers {
+            let Ok(rel) = container.strip_prefix(workspace_root) else {
+                continue;
+            };
+            let content =
+                fs::read_to_string(container.join(BAUM_DIR).join("gitignore")).unwrap_or_default();
+            patterns.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| is_worktree_pattern(line))
+                    .map(|line| format!("/{}{}", rel.to_string_lossy(), line)),
+            );
+        }
+    }
+
+    let mut expected = remove_wald_section(&current);
+    if !patterns.is_empty() {
+        if !expected.is_empty() && !expected.ends_with('\n') {
+            expected.push('\n');
+        }
+        expected.push_str(&format!(
+            "{}\n{}\n{}\n",
+            GITIGNORE_MARKER_START,
+            patterns.join("\n"),
+            GITIGNORE_MARKER_END
+        ));
+    }
+    Some((exclude_path, current, expected))
+}
+
+/// Whether the workspace repo's `info/exclude` is missing worktree
+/// patterns from a `.baum/gitignore` (or has ones no baum lists any more)
+pub fn worktree_exclude_outdated(workspace_root: &Path) -> bool {
+    worktree_exclude(workspace_root).is_some_and(|(_, current, expected)| current != expected)
+}
+
+/// Rewrite the wald section of the workspace repo's `info/exclude` from
+/// the baums' `.baum/gitignore` files
+///
+/// `info/exclude` isn't committed, so each machine regenerates it: after
+/// worktree changes, moves, and syncs. Without `worktree_ignore: exclude`
+/// the section is removed.
+pub fn update_worktree_exclude(workspace_root: &Path) -> Result<()> {
+    let Some((exclude_path, current, expected)) = worktree_exclude(workspace_root) else {
+        return Ok(());
+    };
+    if current == expected {
+        return Ok(());
+    }
+    if let Some(parent) = exclude_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(&exclude_path, expected)
+        .with_context(|| format!("failed to write {}", exclude_path.display()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,6 +393,26 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[test]
+    fn test_has_gitignore_section() {
+        let dir = TempDir::new().unwrap();
+        assert!(!has_gitignore_section(dir.path()));
+
+        // A section missing patterns doesn't count
+        fs::write(
+            dir.path().join(".gitignore"),
+            format!(
+                "{}\n.wald/repos/\n{}\n",
+                GITIGNORE_MARKER_START, GITIGNORE_MARKER_END
+            ),
+        )
+        .unwrap();
+        assert!(!has_gitignore_section(dir.path()));
+
+        ensure_gitignore_section(dir.path()).unwrap();
+        assert!(has_gitignore_section(dir.path()));
+    }
+
     #[test]
     fn test_add_worktree_to_gitignore() {
         let dir = TempDir::new().unwrap();
@@ -227,4 +477,43 @@ mod tests {
         let result = remove_wald_section(content);
         assert_eq!(result, content);
     }
+
+    #[test]
+    fn test_remove_worktree_from_gitignore() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".gitignore"),
+            "*.log\n/_main.wt\n/_dev.wt\n",
+        )
+        .unwrap();
+
+        remove_worktree_from_gitignore(dir.path(), "_dev.wt").unwrap();
+        let content = fs::read_to_string(dir.path().join(".gitignore")).unwrap();
+        assert_eq!(content, "*.log\n/_main.wt\n");
+
+        // Nothing left but the removed pattern: the file goes
+        fs::write(dir.path().join(".gitignore"), "/_main.wt\n").unwrap();
+        remove_worktree_from_gitignore(dir.path(), "_main.wt").unwrap();
+        assert!(!dir.path().join(".gitignore").exists());
+    }
+
+    #[test]
+    fn test_reconcile_worktree_gitignore() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join(".gitignore"),
+            "/build\n/_main.wt\n/_old.wt\n",
+        )
+        .unwrap();
+        let worktrees = vec!["_main.wt".to_string(), "_dev.wt".to_string()];
+
+        let drift = worktree_gitignore_drift(dir.path(), &worktrees);
+        assert_eq!(drift.stale, vec!["/_old.wt"]);
+        assert_eq!(drift.missing, vec!["_dev.wt"]);
+
+        reconcile_worktree_gitignore(dir.path(), &worktrees).unwrap();
+        let content = fs::read_to_string(dir.path().join(".gitignore")).unwrap();
+        assert_eq!(content, "/build\n/_main.wt\n/_dev.wt\n");
+        assert!(worktree_gitignore_drift(dir.path(), &worktrees).is_empty());
+    }
 }
@@ -0,0 +1,249 @@
+//! Cloning the repos a workspace's baums need but this machine lacks
+//!
+//! A fresh machine may be missing dozens of clones at once. They are cloned
+//! a few at a time, with fewer at once (and spaced out) per host, smallest
+//! first by the size recorded in the manifest, so the quick ones are ready
+//! early and no git server gets hammered.
+
+use std::collections::HashMap;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use rayon::prelude::*;
+
+use super::{Workspace, find_all_baums};
+use crate::bulk::BulkResult;
+use crate::git;
+use crate::output::Output;
+use crate::types::RepoId;
+
+/// A missing clone to make
+struct MissingRepo {
+    repo_id: String,
+    id: RepoId,
+    bare_path: std::path::PathBuf,
+    opts: git::CloneOptions,
+    /// Recorded size in KiB; unknown sizes go last
+    size: Option<u64>,
+    /// Borrows objects from another clone, so waits for the lenders
+    borrower: bool,
+}
+
+/// Clone missing bare repos referenced by baums in the workspace, or only
+/// by those of `owner`
+///
+/// Repos that fail are recorded in the state and reported; the next run
+/// retries them, after the rest.
+pub fn clone_missing_repos(ws: &mut Workspace, owner: Option<&str>, out: &Output) -> Result<()> {
+    let baums = find_all_baums(&ws.root);
+
+    // Collect unique repo_ids that are missing
+    let mut missing: Vec<MissingRepo> = Vec::new();
+    let mut others = 0;
+    for (_path, manifest) in &baums {
+        if let Some(owner) = owner
+            && !manifest.is_owned_by(owner)
+        {
+            others += 1;
+            continue;
+        }
+        let repo_id = &manifest.repo_id;
+        if ws.has_bare_repo(repo_id) || missing.iter().any(|m| &m.repo_id == repo_id) {
+            continue;
+        }
+        let Some(entry) = ws.manifest.repos.get(repo_id) else {
+            continue;
+        };
+        let id = RepoId::parse(repo_id)?;
+        missing.push(MissingRepo {
+            repo_id: repo_id.clone(),
+            bare_path: ws.bare_repo_path(repo_id)?,
+            opts: ws.clone_options(repo_id, entry)?,
+            size: entry.size,
+            borrower: entry.reference.is_some(),
+            id,
+        });
+    }
+
+    if others > 0 {
+        out.verbose(&format!(
+            "Not hydrating {} baum(s) owned by others (use --all to include them)",
+            others
+        ));
+    }
+
+    // Clones that are no longer missing or wanted don't count as failed
+    ws.state
+        .failed_clones
+        .retain(|id| missing.iter().any(|m| &m.repo_id == id));
+    if missing.is_empty() {
+        return ws.save_state();
+    }
+
+    // Smallest first; repos that failed last time after the rest
+    let retried = ws.state.failed_clones.clone();
+    missing.sort_by(|a, b| {
+        let key = |m: &MissingRepo| (retried.contains(&m.repo_id), m.size.unwrap_or(u64::MAX));
+        key(a).cmp(&key(b)).then_with(|| a.repo_id.cmp(&b.repo_id))
+    });
+
+    let total = missing.len();
+    let jobs = ws.config.hydration.jobs.clamp(1, total);
+    out.info(&format!(
+        "Cloning {} missing repo(s), {} at a time...",
+        total, jobs
+    ));
+    if !retried.is_empty() {
+        out.verbose(&format!(
+            "Retrying {} repo(s) that failed to clone last time",
+            retried.len()
+        ));
+    }
+
+    let limiter = HostLimiter::new(
+        ws.config.hydration.per_host,
+        Duration::from_millis(ws.config.hydration.host_interval_ms),
+    );
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?;
+
+    // Clones that lend their objects come first, so borrowers can use them
+    let (borrowers, lenders): (Vec<_>, Vec<_>) = missing.into_iter().partition(|m| m.borrower);
+    let mut results = BulkResult::new("repo", "clone", total);
+    for wave in [lenders, borrowers] {
+        let wave_results: Vec<(String, Result<()>)> = pool.install(|| {
+            wave.into_par_iter()
+                .map(|repo| {
+                    let _permit = limiter.acquire(&repo.id.host);
+                    out.status("Cloning", &repo.repo_id);
+                    let result = git::with_network_retry(
+                        || git::clone_bare(&repo.id, &repo.bare_path, repo.opts.clone()),
+                        |e, wait| out.retrying(e, wait),
+                    );
+                    (repo.repo_id, result)
+                })
+                .collect()
+        });
+        for (repo_id, result) in wave_results {
+            results.record(&repo_id, result, out);
+        }
+    }
+
+    // A failed clone leaves nothing behind, so the next sync retries just
+    // the repos still missing
+    let mut failed = results.failed();
+    failed.sort();
+    ws.state.failed_clones = failed;
+    ws.save_state()?;
+
+    results.finish(Some("Re-run `wald sync` to retry them"))
+}
+
+/// Caps the clones running against each host and spaces out their starts
+struct HostLimiter {
+    per_host: usize,
+    interval: Duration,
+    hosts: Mutex<HashMap<String, HostSlots>>,
+    freed: Condvar,
+}
+
+#[derive(Default)]
+struct HostSlots {
+    running: usize,
+    last_start: Option<Instant>,
+}
+
+/// A clone's slot on its host, given back when dropped
+struct HostPermit<'a> {
+    limiter: &'a HostLimiter,
+    host: String,
+}
+
+impl HostLimiter {
+    fn new(per_host: usize, interval: Duration) -> Self {
+        Self {
+            per_host: per_host.max(1),
+            interval,
+            hosts: Mutex::new(HashMap::new()),
+            freed: Condvar::new(),
+        }
+    }
+
+    /// Wait for a free slot on `host` and the interval since its last start
+    fn acquire(&self, host: &str) -> HostPermit<'_> {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let slots = hosts.entry(host.to_string()).or_default();
+            if slots.running >= self.per_host {
+                hosts = self.freed.wait(hosts).unwrap_or_else(|e| e.into_inner());
+                continue;
+            }
+            let wait = slots
+                .last_start
+                .map(|at| self.interval.saturating_sub(at.elapsed()))
+                .unwrap_or_default();
+            if wait.is_zero() {
+                slots.running += 1;
+                slots.last_start = Some(Instant::now());
+                return HostPermit {
+                    limiter: self,
+                    host: host.to_string(),
+                };
+            }
+            hosts = self
+                .freed
+                .wait_timeout(hosts, wait)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+}
+
+impl Drop for HostPermit<'_> {
+    fn drop(&mut self) {
+        let mut hosts = self.limiter.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(slots) = hosts.get_mut(&self.host) {
+            slots.running -= 1;
+        }
+        self.limiter.freed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_host_limiter_caps_each_host() {
+        let limiter = HostLimiter::new(2, Duration::ZERO);
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        std::thread::scope(|scope| {
+            for _ in 0..6 {
+                scope.spawn(|| {
+                    let _permit = limiter.acquire("github.com");
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+            // Another host isn't held up by the busy one
+            let _permit = limiter.acquire("gitlab.com");
+        });
+
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_host_limiter_spaces_out_starts() {
+        let limiter = HostLimiter::new(4, Duration::from_millis(50));
+        let started = Instant::now();
+        drop(limiter.acquire("github.com"));
+        drop(limiter.acquire("github.com"));
+        drop(limiter.acquire("github.com"));
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,195 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{Workspace, validate_workspace_path};
+use crate::time::format_iso;
+
+/// How a journaled operation ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Ok,
+    Failed,
+}
+
+/// One mutating wald operation, as recorded in .wald/journal/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalRecord {
+    /// When the operation started (ISO 8601, UTC)
+    pub time: String,
+    /// User who ran it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Directory it was run from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// Subcommand, e.g. `plant` or `repo add`
+    pub command: String,
+    /// Command-line arguments after `wald`
+    pub args: Vec<String>,
+    /// Affected paths, relative to the workspace root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathBuf>,
+    pub outcome: Outcome,
+    /// Error message if the operation failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Record a finished operation run from the current process
+///
+/// `paths` are the command's path arguments; they are stored relative to
+/// the workspace root. `error` is None if the operation succeeded.
+pub fn record_operation(
+    ws: &Workspace,
+    command: &str,
+    paths: &[PathBuf],
+    started_at: u64,
+    duration: Duration,
+    error: Option<String>,
+) -> Result<()> {
+    let record = JournalRecord {
+        time: format_iso(started_at),
+        user: env::var("USER").or_else(|_| env::var("USERNAME")).ok(),
+        cwd: env::current_dir().ok(),
+        command: command.to_string(),
+        args: env::args_os()
+            .skip(1)
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect(),
+        paths: paths.iter().map(|p| workspace_relative(ws, p)).collect(),
+        outcome: if error.is_none() {
+            Outcome::Ok
+        } else {
+            Outcome::Failed
+        },
+        error,
+        duration_ms: duration.as_millis() as u64,
+    };
+    append_journal(ws, &record)
+}
+
+/// A path argument as it resolves inside the workspace (as given if it
+/// doesn't)
+fn workspace_relative(ws: &Workspace, path: &Path) -> PathBuf {
+    validate_workspace_path(&ws.root, path)
+        .ok()
+        .and_then(|abs| abs.strip_prefix(&ws.root).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Append a record to this month's journal file
+///
+/// Files are JSON lines named by month (`2026-10.jsonl`); each record is a
+/// single append so concurrent runs don't interleave.
+pub fn append_journal(ws: &Workspace, record: &JournalRecord) -> Result<()> {
+    let dir = ws.journal_dir();
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+
+    let path = dir.join(format!("{}.jsonl", &record.time[..7]));
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    file.write_all(line.as_bytes())
+        .with_context(|| format!("failed to write {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Journal records from `since` (seconds since the Unix epoch) on, oldest first
+pub fn read_journal(ws: &Workspace, since: Option<u64>) -> Result<Vec<JournalRecord>> {
+    let dir = ws.journal_dir();
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let since = since.map(format_iso);
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    files.sort();
+
+    let mut records = Vec::new();
+    for path in files {
+        // Whole months before `since` can be skipped by name
+        if let (Some(since), Some(stem)) = (&since, path.file_stem())
+            && *stem.to_string_lossy() < since[..7]
+        {
+            continue;
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            // A torn last line (e.g. from a full disk) shouldn't hide the rest
+            let Ok(record) = serde_json::from_str::<JournalRecord>(line) else {
+                continue;
+            };
+            if since.as_ref().is_none_or(|since| record.time >= *since) {
+                records.push(record);
+            }
+        }
+    }
+
+    records.sort_by(|a, b| a.time.cmp(&b.time));
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(time: &str, command: &str) -> JournalRecord {
+        JournalRecord {
+            time: time.to_string(),
+            user: Some("alice".to_string()),
+            cwd: None,
+            command: command.to_string(),
+            args: vec![command.to_string()],
+            paths: vec![PathBuf::from("code/api")],
+            outcome: Outcome::Ok,
+            error: None,
+            duration_ms: 5,
+        }
+    }
+
+    #[test]
+    fn test_journal_roundtrip_and_since() {
+        let dir = TempDir::new().unwrap();
+        Workspace::init(dir.path(), false).unwrap();
+        let ws = Workspace::load_from(dir.path().to_path_buf()).unwrap();
+
+        append_journal(&ws, &record("2026-09-30T23:00:00Z", "plant")).unwrap();
+        append_journal(&ws, &record("2026-10-16T14:23:00Z", "uproot")).unwrap();
+        fs::write(
+            ws.journal_dir().join("2026-10.jsonl"),
+            fs::read_to_string(ws.journal_dir().join("2026-10.jsonl")).unwrap() + "{\"time\":",
+        )
+        .unwrap();
+
+        let all = read_journal(&ws, None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].command, "plant");
+        assert_eq!(all[1].paths, vec![PathBuf::from("code/api")]);
+
+        // 2026-10-01T00:00:00Z
+        let recent = read_journal(&ws, Some(1_790_812_800)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].command, "uproot");
+    }
+}
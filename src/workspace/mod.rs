@@ -1,9 +1,40 @@
+mod adopt;
 pub mod baum;
+mod baum_lock;
 mod discovery;
 pub mod gitignore;
+mod hydrate;
+mod journal;
+mod notify;
+mod owner;
 mod path_safety;
+mod post_fetch;
+mod protected;
+mod stale;
+mod tracking;
+mod trash;
+mod walk;
+mod worktree_config;
 
-pub use baum::{create_baum, is_baum, save_baum_with_id};
-pub use discovery::{Workspace, collect_baum_ids, find_all_baums, find_workspace_root};
-pub use gitignore::ensure_gitignore_section;
-pub use path_safety::validate_workspace_path;
+pub use adopt::{Adopted, adopt_checkout};
+pub use baum::{create_baum, find_enclosing_baum, is_baum, save_baum_with_id};
+pub use baum_lock::{BaumLock, BaumLockInfo, lock_baum, read_baum_lock};
+pub use discovery::{
+    WALD_DIR, Workspace, collect_baum_ids, find_all_baums, find_workspace_root,
+    find_workspace_root_from,
+};
+pub use gitignore::{ensure_gitignore_section, has_gitignore_section};
+pub use hydrate::clone_missing_repos;
+pub use journal::{JournalRecord, Outcome, append_journal, read_journal, record_operation};
+pub use notify::notify_finished;
+pub use owner::{current_owner, new_baum_owner};
+pub use path_safety::{validate_container_path, validate_workspace_path};
+pub use post_fetch::{RefUpdate, ref_updates, run_post_fetch};
+pub use protected::{ensure_force_allowed, protection_reason};
+pub use stale::{StaleWorktree, find_stale_worktrees, forget_worktree};
+pub use tracking::{Tracking, configure_tracking, ensure_tracked_branch};
+pub use trash::{
+    TrashEntry, apply_trash_retention, list_trash, purge_trash, restore_from_trash, trash_container,
+};
+pub use walk::{find_baum_dirs, walk_config};
+pub use worktree_config::{configure_worktree, pin_identity};
@@ -0,0 +1,82 @@
+use std::env;
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+
+use super::Workspace;
+use crate::time::format_age;
+
+/// Run the workspace's notification command for a finished command
+///
+/// Does nothing without a command (`notify.command` in config.yaml, or
+/// `WALD_NOTIFY`) or when `command` took less than `notify.after_secs`.
+/// The notification command runs through the shell in the workspace root
+/// and gets the outcome in `WALD_COMMAND`, `WALD_OUTCOME` (`ok` or
+/// `failed`), `WALD_DURATION_SECS` and `WALD_SUMMARY`. `error` is None if
+/// the command succeeded.
+pub fn notify_finished(
+    ws: &Workspace,
+    command: &str,
+    elapsed: Duration,
+    error: Option<&str>,
+) -> Result<()> {
+    let notify_command = match env::var("WALD_NOTIFY") {
+        Ok(cmd) if !cmd.trim().is_empty() => cmd,
+        _ => match &ws.config.notify.command {
+            Some(cmd) => cmd.clone(),
+            None => return Ok(()),
+        },
+    };
+    if elapsed.as_secs() < ws.config.notify.after_secs {
+        return Ok(());
+    }
+
+    let summary = match error {
+        None => format!(
+            "wald {} finished after {}",
+            command,
+            format_age(elapsed.as_secs())
+        ),
+        Some(e) => format!(
+            "wald {} failed after {}: {}",
+            command,
+            format_age(elapsed.as_secs()),
+            e
+        ),
+    };
+    let status = shell(&notify_command)
+        .current_dir(&ws.root)
+        .env("WALD_COMMAND", command)
+        .env(
+            "WALD_OUTCOME",
+            if error.is_none() { "ok" } else { "failed" },
+        )
+        .env("WALD_DURATION_SECS", elapsed.as_secs().to_string())
+        .env("WALD_SUMMARY", &summary)
+        .status()
+        .with_context(|| format!("failed to run notification command: {}", notify_command))?;
+    if !status.success() {
+        bail!(
+            "notification command failed ({}): {}",
+            status,
+            notify_command
+        );
+    }
+    Ok(())
+}
+
+/// A command that runs `command` through the platform's shell
+#[cfg(unix)]
+pub(super) fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(not(unix))]
+pub(super) fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
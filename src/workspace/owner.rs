@@ -0,0 +1,28 @@
+//! Baum ownership in team workspaces
+//!
+//! With `team:` set in config.yaml, `plant` records who a baum belongs to,
+//! and `--mine` narrows `worktrees`, `status` and `sync` to those baums.
+//! Everyone still sees every baum; baums without an owner are shared.
+
+use crate::git;
+use crate::types::TeamMode;
+
+use super::Workspace;
+use super::baum_lock::hostname;
+
+/// Who this machine acts for: its host name with `team: machine`,
+/// otherwise the workspace repo's `user.email` (the host name if unset)
+pub fn current_owner(ws: &Workspace) -> String {
+    if ws.config.team != TeamMode::Machine
+        && let Ok(Some(email)) = git::get_config(&ws.root, "user.email")
+        && !email.is_empty()
+    {
+        return email;
+    }
+    hostname()
+}
+
+/// The owner to record on a new baum: None unless the workspace is shared
+pub fn new_baum_owner(ws: &Workspace) -> Option<String> {
+    (ws.config.team != TeamMode::Off).then(|| current_owner(ws))
+}
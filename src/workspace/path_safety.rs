@@ -8,6 +8,9 @@ use std::path::{Component, Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
 
+use super::discovery::WALD_DIR;
+use crate::filesystem::normalize_path;
+
 /// Validate and resolve a user-provided path relative to a workspace root.
 ///
 /// This function:
@@ -76,6 +79,52 @@ pub fn validate_workspace_path(root: &Path, path: &Path) -> Result<PathBuf> {
     Ok(resolved)
 }
 
+/// Validate a path where a baum container will live.
+///
+/// On top of [`validate_workspace_path`], rejects:
+/// - The workspace root itself
+/// - Anything under `.wald/`
+/// - Paths inside a git checkout below the root (a worktree or a plain
+///   clone), including the checkout directory itself
+///
+/// # Errors
+///
+/// Returns an error naming the offending location.
+pub fn validate_container_path(root: &Path, path: &Path) -> Result<PathBuf> {
+    let resolved = validate_workspace_path(root, path)?;
+
+    let canonical_root = root.canonicalize().unwrap_or_else(|_| normalize_path(root));
+    let canonical_resolved = canonicalize_partial(&resolved);
+    let rel = canonical_resolved
+        .strip_prefix(&canonical_root)
+        .unwrap_or(&canonical_resolved);
+
+    if rel.as_os_str().is_empty() {
+        bail!("cannot use the workspace root as a baum container; pick a subdirectory");
+    }
+    if rel.starts_with(WALD_DIR) {
+        bail!(
+            "cannot use a path under {}/ as a baum container: {}",
+            WALD_DIR,
+            resolved.display()
+        );
+    }
+
+    // Each directory from the first below the root down to the container
+    let mut dir = canonical_root.clone();
+    for component in rel.components() {
+        dir.push(component);
+        if dir.join(".git").exists() {
+            bail!(
+                "cannot use a path inside the git checkout {} as a baum container",
+                dir.strip_prefix(&canonical_root).unwrap_or(&dir).display()
+            );
+        }
+    }
+
+    Ok(resolved)
+}
+
 /// Canonicalize as much of a path as exists.
 ///
 /// For paths where only part exists (e.g., `/existing/dir/new_file`),
@@ -116,28 +165,6 @@ fn canonicalize_partial(path: &Path) -> PathBuf {
     result
 }
 
-/// Normalize a path by resolving `.` and `..` components without requiring the path to exist.
-fn normalize_path(path: &Path) -> PathBuf {
-    let mut normalized = PathBuf::new();
-
-    for component in path.components() {
-        match component {
-            Component::ParentDir => {
-                // Go up one level if possible
-                normalized.pop();
-            }
-            Component::CurDir => {
-                // Skip current dir markers
-            }
-            component => {
-                normalized.push(component);
-            }
-        }
-    }
-
-    normalized
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,6 +340,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_container_path_structure() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("tools/repo/_main.wt")).unwrap();
+        fs::write(root.join("tools/repo/_main.wt/.git"), "gitdir: x").unwrap();
+        fs::create_dir_all(root.join("clone/.git")).unwrap();
+
+        assert!(validate_container_path(root, Path::new("tools/other")).is_ok());
+
+        for (path, expected) in [
+            ("tools/..", "workspace root"),
+            (".wald/repos/foo", ".wald/"),
+            ("tools/repo/_main.wt/nested", "tools/repo/_main.wt"),
+            ("tools/repo/_main.wt", "tools/repo/_main.wt"),
+            ("clone/sub", "git checkout clone"),
+        ] {
+            let err = validate_container_path(root, Path::new(path)).unwrap_err();
+            assert!(err.to_string().contains(expected), "{}: {}", path, err);
+        }
+    }
+
     #[test]
     fn test_canonicalize_partial() {
         let dir = TempDir::new().unwrap();
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use super::Workspace;
+use super::notify::shell;
+use crate::git::runner;
+use crate::output::Output;
+
+/// What git hooks write for a ref that doesn't exist on one side
+const NO_COMMIT: &str = "0000000000000000000000000000000000000000";
+
+/// A branch a fetch moved, created or deleted
+#[derive(Debug, PartialEq, Eq)]
+pub struct RefUpdate {
+    /// Full ref name, e.g. `refs/remotes/origin/main`
+    pub name: String,
+    /// Commit before the fetch (None if the fetch created it)
+    pub old: Option<String>,
+    /// Commit after the fetch (None if the fetch deleted it)
+    pub new: Option<String>,
+}
+
+/// The branches that differ between two [`crate::git::branch_commits`]
+/// snapshots, by ref name
+pub fn ref_updates(
+    before: &BTreeMap<String, String>,
+    after: &BTreeMap<String, String>,
+) -> Vec<RefUpdate> {
+    let mut names: Vec<&String> = before.keys().chain(after.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter(|name| before.get(*name) != after.get(*name))
+        .map(|name| RefUpdate {
+            name: name.clone(),
+            old: before.get(name).cloned(),
+            new: after.get(name).cloned(),
+        })
+        .collect()
+}
+
+/// Run a repo's `post_fetch` commands after a successful fetch
+///
+/// Each command runs through the shell in the bare repo, in order, and gets
+/// `WALD_REPO_ID`, `WALD_BARE_REPO`, `WALD_WORKSPACE` and the moved branches
+/// in `WALD_UPDATED_REFS`, one `<old> <new> <ref>` line each as in git's
+/// hooks (zeros for a created or deleted branch). The first command that
+/// fails stops the rest. Under `--simulate` the commands are only listed.
+pub fn run_post_fetch(
+    ws: &Workspace,
+    repo_id: &str,
+    bare_path: &Path,
+    updates: &[RefUpdate],
+    out: &Output,
+) -> Result<()> {
+    let Some(entry) = ws.manifest.repos.get(repo_id) else {
+        return Ok(());
+    };
+    let updated_refs: Vec<String> = updates
+        .iter()
+        .map(|u| {
+            format!(
+                "{} {} {}",
+                u.old.as_deref().unwrap_or(NO_COMMIT),
+                u.new.as_deref().unwrap_or(NO_COMMIT),
+                u.name
+            )
+        })
+        .collect();
+
+    for command in &entry.post_fetch {
+        if runner::simulated(|| format!("post_fetch: {}", command)) {
+            continue;
+        }
+        out.status("Running", &format!("post_fetch: {}", command));
+        let status = shell(command)
+            .current_dir(bare_path)
+            .env("WALD_REPO_ID", repo_id)
+            .env("WALD_BARE_REPO", bare_path)
+            .env("WALD_WORKSPACE", &ws.root)
+            .env("WALD_UPDATED_REFS", updated_refs.join("\n"))
+            .status()
+            .with_context(|| format!("failed to run post_fetch command: {}", command))?;
+        if !status.success() {
+            bail!("post_fetch command failed ({}): {}", status, command);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ref_updates() {
+        let before = BTreeMap::from([
+            ("refs/heads/gone".to_string(), "a".to_string()),
+            ("refs/heads/main".to_string(), "b".to_string()),
+            ("refs/heads/same".to_string(), "c".to_string()),
+        ]);
+        let after = BTreeMap::from([
+            ("refs/heads/main".to_string(), "d".to_string()),
+            ("refs/heads/new".to_string(), "e".to_string()),
+            ("refs/heads/same".to_string(), "c".to_string()),
+        ]);
+        let updates = ref_updates(&before, &after);
+        assert_eq!(
+            updates,
+            vec![
+                RefUpdate {
+                    name: "refs/heads/gone".to_string(),
+                    old: Some("a".to_string()),
+                    new: None,
+                },
+                RefUpdate {
+                    name: "refs/heads/main".to_string(),
+                    old: Some("b".to_string()),
+                    new: Some("d".to_string()),
+                },
+                RefUpdate {
+                    name: "refs/heads/new".to_string(),
+                    old: None,
+                    new: Some("e".to_string()),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+
+use super::Workspace;
+use super::walk::match_components;
+use crate::git;
+
+/// Why `--force` needs confirmation before discarding a logical branch's
+/// local commits: it's the repo's default branch or matches one of
+/// `protected_branches`
+pub fn protection_reason(ws: &Workspace, bare_repo: &Path, branch: &str) -> Option<String> {
+    if git::bare::get_default_branch(bare_repo).is_ok_and(|default| default == branch) {
+        return Some("the repository's default branch".to_string());
+    }
+    ws.config
+        .protected_branches
+        .iter()
+        .find(|pattern| branch_matches(pattern, branch))
+        .map(|pattern| format!("protected ('{}' in protected_branches)", pattern))
+}
+
+/// Refuse to let `--force` overwrite the existing `local_branch` of a
+/// protected logical branch unless `--yes-protected` confirms it
+pub fn ensure_force_allowed(
+    ws: &Workspace,
+    bare_repo: &Path,
+    branch: &str,
+    local_branch: &str,
+    yes_protected: bool,
+) -> Result<()> {
+    if yes_protected || !git::check_branch_exists(bare_repo, local_branch)? {
+        return Ok(());
+    }
+    if let Some(reason) = protection_reason(ws, bare_repo, branch) {
+        bail!(
+            "'{}' is {}; --force would discard {} with any commits only it has\nAdd --yes-protected to overwrite it anyway",
+            branch,
+            reason,
+            local_branch
+        );
+    }
+    Ok(())
+}
+
+/// Match a whole branch name against a glob whose `*` stays within one
+/// `/`-separated part and `**` spans any number of them
+fn branch_matches(pattern: &str, branch: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let parts: Vec<String> = branch.split('/').map(String::from).collect();
+    match_components(&pattern, &parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branch_matches() {
+        assert!(branch_matches("main", "main"));
+        assert!(!branch_matches("main", "feature/main"));
+        assert!(branch_matches("release/*", "release/1.2"));
+        assert!(!branch_matches("release/*", "release/1.2/hotfix"));
+        assert!(branch_matches("release/**", "release/1.2/hotfix"));
+        assert!(!branch_matches("release/*", "releases"));
+    }
+}
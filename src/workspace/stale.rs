@@ -0,0 +1,116 @@
+//! Worktrees git still registers though their directories are gone
+//!
+//! Deleting a worktree directory by hand (instead of `wald prune`) leaves
+//! its registration in the bare repo, which git reports as `prunable`, and
+//! its entry in the baum manifest. A registration only exists on the machine
+//! the worktree lived on, so a prunable one means it was removed here, not
+//! that it was never materialized.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+use super::baum::{load_baum, save_baum};
+use super::gitignore::remove_worktree_from_gitignore;
+use super::{Workspace, find_all_baums, lock_baum};
+use crate::git;
+use crate::types::BaumManifest;
+
+/// A worktree registration git would prune
+#[derive(Debug)]
+pub struct StaleWorktree {
+    pub repo_id: String,
+    pub bare_path: PathBuf,
+    /// Where the worktree was
+    pub path: PathBuf,
+    /// The baum whose manifest still lists it, with the entry's path
+    pub baum: Option<(PathBuf, String)>,
+}
+
+/// Find the prunable worktree registrations of every cloned repo
+///
+/// Only worktrees that were inside the workspace count, and not those
+/// whose directory (or baum) was moved with `mv`: they are still there, and
+/// `wald relocate` or `wald doctor --fix` reconnects them. Locked worktrees
+/// are never prunable; git keeps them on purpose.
+pub fn find_stale_worktrees(ws: &Workspace) -> Vec<StaleWorktree> {
+    let baums = find_all_baums(&ws.root);
+    let root = ws.root.canonicalize().unwrap_or_else(|_| ws.root.clone());
+    let moved = moved_worktrees(&baums);
+    let mut stale = Vec::new();
+
+    for repo_id in ws.manifest.repos.keys() {
+        let Ok(bare_path) = ws.bare_repo_path(repo_id) else {
+            continue;
+        };
+        if !bare_path.exists() {
+            continue;
+        }
+        let Ok(list) = git::list_worktrees(&bare_path) else {
+            continue;
+        };
+        for info in list.iter().filter(|w| w.prunable && !w.locked) {
+            let path = PathBuf::from(&info.path);
+            if (!path.starts_with(&root) && !path.starts_with(&ws.root)) || moved.contains(&path) {
+                continue;
+            }
+            let baum = baums
+                .iter()
+                .filter(|(_, manifest)| &manifest.repo_id == repo_id)
+                .find_map(|(container, manifest)| {
+                    manifest
+                        .worktrees
+                        .iter()
+                        .find(|wt| {
+                            git::match_worktree(
+                                std::slice::from_ref(info),
+                                &container.join(&wt.path),
+                            )
+                            .is_some()
+                        })
+                        .map(|wt| (container.clone(), wt.path.clone()))
+                });
+            stale.push(StaleWorktree {
+                repo_id: repo_id.clone(),
+                bare_path: bare_path.clone(),
+                path,
+                baum,
+            });
+        }
+    }
+
+    stale
+}
+
+/// Where git still has the worktrees found in a baum under another path
+fn moved_worktrees(baums: &[(PathBuf, BaumManifest)]) -> Vec<PathBuf> {
+    baums
+        .iter()
+        .filter_map(|(container, _)| fs::read_dir(container).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|entry| match git::check_worktree_link(&entry.path()) {
+            Some(git::BrokenLink::StaleBacklink(recorded)) => Some(recorded),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Drop a worktree whose directory is gone from its baum manifest and the
+/// container's ignore file
+pub fn forget_worktree(container: &Path, entry_path: &str, command: &str) -> Result<()> {
+    let _lock = lock_baum(container, command)?;
+    let mut baum = load_baum(container)?;
+    let Some(idx) = baum.worktrees.iter().position(|wt| wt.path == entry_path) else {
+        bail!("worktree {} no longer in the baum manifest", entry_path);
+    };
+    if container.join(entry_path).exists() {
+        bail!(
+            "worktree directory {} exists again",
+            container.join(entry_path).display()
+        );
+    }
+    baum.worktrees.remove(idx);
+    remove_worktree_from_gitignore(container, entry_path)?;
+    save_baum(container, &baum)
+}
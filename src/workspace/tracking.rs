@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+
+use super::Workspace;
+use crate::git;
+use crate::types::RepoId;
+
+/// Remote a new worktree's branch tracks instead of `origin` (`--track`)
+///
+/// Written `<remote>` (track `<remote>/<branch>`) or `<remote>/<branch>`
+/// (track that branch whatever the worktree's branch is called). Either
+/// way the worktree still pushes to `origin/<branch>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tracking {
+    pub remote: String,
+    pub branch: Option<String>,
+}
+
+impl Tracking {
+    pub fn parse(s: &str) -> Result<Self> {
+        let (remote, branch) = match s.split_once('/') {
+            Some((remote, branch)) => (remote, Some(branch)),
+            None => (s, None),
+        };
+        if remote.is_empty() || branch.is_some_and(str::is_empty) {
+            bail!(
+                "invalid --track '{}' (use <remote> or <remote>/<branch>)",
+                s
+            );
+        }
+        Ok(Self {
+            remote: remote.to_string(),
+            branch: branch.map(str::to_string),
+        })
+    }
+
+    /// Remote-tracking branch to start from and pull from, e.g. `upstream/main`
+    pub fn upstream_for(&self, branch: &str) -> String {
+        format!(
+            "{}/{}",
+            self.remote,
+            self.branch.as_deref().unwrap_or(branch)
+        )
+    }
+}
+
+/// Make sure the bare repo has the remote branch a worktree will track
+///
+/// A missing `upstream` remote is added from the repo's `upstream` entry in
+/// the manifest. The remote is fetched if the branch isn't known yet.
+pub fn ensure_tracked_branch(
+    ws: &Workspace,
+    repo_id: &str,
+    bare_path: &Path,
+    tracking: &Tracking,
+    branch: &str,
+) -> Result<()> {
+    let remote = &tracking.remote;
+    let remote_branch = tracking.branch.as_deref().unwrap_or(branch);
+
+    if !git::has_remote(bare_path, remote) {
+        let upstream = ws
+            .manifest
+            .repos
+            .get(repo_id)
+            .and_then(|e| e.upstream.as_deref());
+        match upstream {
+            Some(upstream) if remote == "upstream" => {
+                let url = ws
+                    .config
+                    .rewrite_url(&RepoId::parse(upstream)?.to_clone_url());
+                git::add_remote(bare_path, remote, &url)?;
+            }
+            _ => bail!(
+                "{} has no remote '{}'; add it with `git -C {} remote add {} <url>`",
+                repo_id,
+                remote,
+                bare_path.display(),
+                remote
+            ),
+        }
+    }
+
+    if !git::has_remote_branch(bare_path, remote, remote_branch) {
+        git::fetch_remote(bare_path, remote)?;
+        if !git::has_remote_branch(bare_path, remote, remote_branch) {
+            bail!("remote branch not found: {}/{}", remote, remote_branch);
+        }
+    }
+
+    Ok(())
+}
+
+/// Set up a tracking worktree to push to `origin/<branch>`
+pub fn configure_tracking(
+    bare_path: &Path,
+    worktree_path: &Path,
+    local_branch: &str,
+    branch: &str,
+) -> Result<()> {
+    git::configure_push_to(bare_path, worktree_path, local_branch, "origin", branch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tracking() {
+        let t = Tracking::parse("upstream").unwrap();
+        assert_eq!(t.remote, "upstream");
+        assert_eq!(t.upstream_for("main"), "upstream/main");
+
+        let t = Tracking::parse("upstream/release/2.0").unwrap();
+        assert_eq!(t.branch.as_deref(), Some("release/2.0"));
+        assert_eq!(t.upstream_for("fix-x"), "upstream/release/2.0");
+
+        assert!(Tracking::parse("").is_err());
+        assert!(Tracking::parse("/main").is_err());
+        assert!(Tracking::parse("upstream/").is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use super::Workspace;
+use crate::time::{format_compact, now_secs};
+
+/// Records where a trashed container came from (inside the trash entry)
+const TRASH_MARKER: &str = ".wald-trash.yaml";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TrashMarker {
+    /// Container path relative to the workspace root
+    origin: PathBuf,
+    /// Seconds since the Unix epoch
+    trashed_at: u64,
+}
+
+/// An uprooted container kept in .wald/trash/
+#[derive(Debug)]
+pub struct TrashEntry {
+    /// The trashed container
+    pub path: PathBuf,
+    /// Where it was, relative to the workspace root
+    pub origin: PathBuf,
+    /// When it was trashed (seconds since the Unix epoch)
+    pub trashed_at: u64,
+}
+
+/// Move a container into `.wald/trash/<timestamp>-<name>/`
+///
+/// Its worktrees must already be removed; whatever is left (the baum
+/// manifest, notes, other files) is kept for `restore_from_trash`.
+pub fn trash_container(ws: &Workspace, container: &Path) -> Result<PathBuf> {
+    let origin = container
+        .strip_prefix(&ws.root)
+        .with_context(|| format!("{} is outside the workspace", container.display()))?
+        .to_path_buf();
+    let Some(name) = container.file_name() else {
+        bail!("cannot trash {}", container.display());
+    };
+
+    let trash_dir = ws.trash_dir();
+    fs::create_dir_all(&trash_dir)
+        .with_context(|| format!("failed to create {}", trash_dir.display()))?;
+
+    let trashed_at = now_secs();
+    let base = format!("{}-{}", format_compact(trashed_at), name.to_string_lossy());
+    let mut target = trash_dir.join(&base);
+    let mut n = 1;
+    while target.exists() {
+        n += 1;
+        target = trash_dir.join(format!("{}.{}", base, n));
+    }
+
+    fs::rename(container, &target).with_context(|| {
+        format!(
+            "failed to move {} to {}",
+            container.display(),
+            target.display()
+        )
+    })?;
+
+    let marker = TrashMarker { origin, trashed_at };
+    fs::write(target.join(TRASH_MARKER), serde_yml::to_string(&marker)?)
+        .with_context(|| format!("failed to write trash marker in {}", target.display()))?;
+
+    Ok(target)
+}
+
+/// Trashed containers, oldest first
+pub fn list_trash(ws: &Workspace) -> Result<Vec<TrashEntry>> {
+    let trash_dir = ws.trash_dir();
+    if !trash_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(&trash_dir)
+        .with_context(|| format!("failed to read {}", trash_dir.display()))?
+    {
+        let path = entry?.path();
+        let Ok(content) = fs::read_to_string(path.join(TRASH_MARKER)) else {
+            continue;
+        };
+        let marker: TrashMarker = serde_yml::from_str(&content)
+            .with_context(|| format!("invalid trash marker in {}", path.display()))?;
+        entries.push(TrashEntry {
+            path,
+            origin: marker.origin,
+            trashed_at: marker.trashed_at,
+        });
+    }
+
+    entries.sort_by(|a, b| (a.trashed_at, &a.path).cmp(&(b.trashed_at, &b.path)));
+    Ok(entries)
+}
+
+/// Move a trashed container back to where it was; returns its path
+pub fn restore_from_trash(ws: &Workspace, entry: &TrashEntry) -> Result<PathBuf> {
+    let container = ws.root.join(&entry.origin);
+    if container.exists() {
+        bail!(
+            "cannot restore {}: the path is taken",
+            entry.origin.display()
+        );
+    }
+    if let Some(parent) = container.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+
+    fs::rename(&entry.path, &container).with_context(|| {
+        format!(
+            "failed to move {} to {}",
+            entry.path.display(),
+            container.display()
+        )
+    })?;
+    fs::remove_file(container.join(TRASH_MARKER))
+        .with_context(|| format!("failed to remove trash marker in {}", container.display()))?;
+
+    Ok(container)
+}
+
+/// Delete trashed containers trashed before `cutoff` (all if None)
+///
+/// Returns the number of entries deleted.
+pub fn purge_trash(ws: &Workspace, cutoff: Option<u64>) -> Result<usize> {
+    let mut purged = 0;
+    for entry in list_trash(ws)? {
+        if cutoff.is_some_and(|cutoff| entry.trashed_at >= cutoff) {
+            continue;
+        }
+        fs::remove_dir_all(&entry.path)
+            .with_context(|| format!("failed to remove {}", entry.path.display()))?;
+        purged += 1;
+    }
+    Ok(purged)
+}
+
+/// Delete trashed containers older than the configured retention
+pub fn apply_trash_retention(ws: &Workspace) -> Result<usize> {
+    let retention = ws.config.trash_retention_days.saturating_mul(86_400);
+    purge_trash(ws, Some(now_secs().saturating_sub(retention)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_trash_and_restore() {
+        let dir = TempDir::new().unwrap();
+        Workspace::init(dir.path(), false).unwrap();
+        let ws = Workspace::load_from(dir.path().to_path_buf()).unwrap();
+
+        let container = dir.path().join("code/api");
+        fs::create_dir_all(container.join(".baum")).unwrap();
+        fs::write(container.join("notes.md"), "keep me").unwrap();
+
+        let trashed = trash_container(&ws, &container).unwrap();
+        assert!(!container.exists());
+        assert!(trashed.starts_with(ws.trash_dir()));
+
+        let entries = list_trash(&ws).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].origin, PathBuf::from("code/api"));
+
+        let restored = restore_from_trash(&ws, &entries[0]).unwrap();
+        assert_eq!(restored, container);
+        assert_eq!(
+            fs::read_to_string(container.join("notes.md")).unwrap(),
+            "keep me"
+        );
+        assert!(!container.join(TRASH_MARKER).exists());
+        assert!(list_trash(&ws).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_purge_respects_cutoff() {
+        let dir = TempDir::new().unwrap();
+        Workspace::init(dir.path(), false).unwrap();
+        let ws = Workspace::load_from(dir.path().to_path_buf()).unwrap();
+
+        fs::create_dir_all(dir.path().join("a")).unwrap();
+        trash_container(&ws, &dir.path().join("a")).unwrap();
+
+        assert_eq!(purge_trash(&ws, Some(0)).unwrap(), 0);
+        assert_eq!(apply_trash_retention(&ws).unwrap(), 0);
+        assert_eq!(purge_trash(&ws, None).unwrap(), 1);
+        assert!(list_trash(&ws).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use walkdir::{DirEntry, WalkDir};
+
+use super::baum::{BAUM_DIR, is_baum};
+use super::discovery::WALD_DIR;
+use crate::timings;
+use crate::types::{Config, SymlinkPolicy};
+
+/// Find the baum containers under `search_root`
+///
+/// Never descends into `.git`, `.wald/repos`, `.wald/trash`, `.baum` or
+/// worktree (`_*.wt`) directories, nor into anything matching one of the
+/// workspace's `ignore` patterns (see [`is_ignored`]). `discovery` in the
+/// config limits the depth (counted from the workspace root) and which
+/// symlinks are followed. Baums are returned in walk order; one reachable
+/// both directly and through a symlink is returned once, by its own path.
+pub fn find_baum_dirs(workspace_root: &Path, search_root: &Path, config: &Config) -> Vec<PathBuf> {
+    timings::time("discovery walk", || {
+        walk_baum_dirs(workspace_root, search_root, config)
+    })
+}
+
+fn walk_baum_dirs(workspace_root: &Path, search_root: &Path, config: &Config) -> Vec<PathBuf> {
+    let symlinks = config.discovery.symlinks;
+    let canonical_root = workspace_root
+        .canonicalize()
+        .unwrap_or_else(|_| workspace_root.to_path_buf());
+
+    // walkdir reports (and doesn't enter) symlink loops when following links
+    let mut walker = WalkDir::new(search_root)
+        .follow_links(symlinks != SymlinkPolicy::Never)
+        .sort_by_file_name();
+    if let Some(max_depth) = config.discovery.max_depth {
+        let offset = search_root
+            .strip_prefix(workspace_root)
+            .map(|rel| rel.components().count())
+            .unwrap_or(0);
+        walker = walker.max_depth(max_depth.saturating_sub(offset));
+    }
+
+    let baums = walker
+        .into_iter()
+        .filter_entry(|e| !skip_entry(workspace_root, &canonical_root, e, config))
+        .flatten()
+        .filter(|e| e.file_type().is_dir() && is_baum(e.path()))
+        .map(DirEntry::into_path);
+
+    if symlinks == SymlinkPolicy::Never {
+        return baums.collect();
+    }
+
+    let mut found: Vec<PathBuf> = Vec::new();
+    let mut seen: HashMap<PathBuf, usize> = HashMap::new();
+    for path in baums {
+        let Ok(canonical) = path.canonicalize() else {
+            continue;
+        };
+        let direct = path
+            .strip_prefix(workspace_root)
+            .is_ok_and(|rel| canonical_root.join(rel) == canonical);
+        match seen.get(&canonical) {
+            Some(&i) if direct => found[i] = path,
+            Some(_) => {}
+            None => {
+                seen.insert(canonical, found.len());
+                found.push(path);
+            }
+        }
+    }
+    found
+}
+
+/// The config that steers a workspace's walks (the defaults if it can't be
+/// read)
+pub fn walk_config(workspace_root: &Path) -> Config {
+    Config::load(&workspace_root.join(WALD_DIR).join("config.yaml")).unwrap_or_default()
+}
+
+fn skip_entry(workspace_root: &Path, canonical_root: &Path, e: &DirEntry, config: &Config) -> bool {
+    let name = e.file_name().to_string_lossy();
+    if name == ".git" || name == BAUM_DIR {
+        return true;
+    }
+    if (name == "repos" || name == "trash")
+        && e.path()
+            .parent()
+            .map(|p| p.ends_with(WALD_DIR))
+            .unwrap_or(false)
+    {
+        return true;
+    }
+    if !e.file_type().is_dir() {
+        return false;
+    }
+    // Skip worktree directories (no need to descend into them)
+    if name.starts_with('_') && name.ends_with(".wt") {
+        return true;
+    }
+    if config.discovery.symlinks == SymlinkPolicy::Workspace
+        && e.path_is_symlink()
+        && !e
+            .path()
+            .canonicalize()
+            .is_ok_and(|target| target.starts_with(canonical_root))
+    {
+        return true;
+    }
+    e.path()
+        .strip_prefix(workspace_root)
+        .is_ok_and(|rel| is_ignored(&config.ignore, rel))
+}
+
+/// Whether a path relative to the workspace root matches an ignore pattern
+///
+/// Patterns are globs: `*` and `?` match within one path component, `**`
+/// matches any number of components. A pattern without `/` matches a
+/// component anywhere (`node_modules`, `*.egg-info`); one with `/` matches
+/// from the workspace root (`archive/**`, `scratch/old`).
+pub fn is_ignored(patterns: &[String], rel: &Path) -> bool {
+    let components: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if components.is_empty() {
+        return false;
+    }
+
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim_end_matches('/');
+        if pattern.contains('/') {
+            let parts: Vec<&str> = pattern.trim_start_matches('/').split('/').collect();
+            match_components(&parts, &components)
+        } else {
+            components
+                .last()
+                .is_some_and(|name| glob_match(pattern, name))
+        }
+    })
+}
+
+pub(super) fn match_components(pattern: &[&str], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_components(rest, &path[skip..])),
+        Some((first, rest)) => path
+            .split_first()
+            .is_some_and(|(name, path)| glob_match(first, name) && match_components(rest, path)),
+    }
+}
+
+/// Match one path component against a glob with `*` and `?`
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // Backtrack to the last `*` on a mismatch
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ignored(patterns: &[&str], rel: &str) -> bool {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        is_ignored(&patterns, Path::new(rel))
+    }
+
+    #[test]
+    fn test_max_depth_and_symlinks() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        for baum in ["a/shallow", "b/c/d/deep", "outside/linked"] {
+            fs::create_dir_all(root.join(baum).join(BAUM_DIR)).unwrap();
+        }
+        std::os::unix::fs::symlink(root.join("outside"), root.join("a/link")).unwrap();
+        // A loop back up the tree
+        std::os::unix::fs::symlink(root, root.join("b/loop")).unwrap();
+
+        let rel = |found: Vec<PathBuf>| -> Vec<String> {
+            let mut found: Vec<String> = found
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+                .collect();
+            found.sort();
+            found
+        };
+
+        let mut config = Config::default();
+        assert_eq!(
+            rel(find_baum_dirs(root, root, &config)),
+            vec!["a/shallow", "b/c/d/deep", "outside/linked"]
+        );
+
+        config.discovery.max_depth = Some(2);
+        assert_eq!(
+            rel(find_baum_dirs(root, root, &config)),
+            vec!["a/shallow", "outside/linked"]
+        );
+
+        // Each baum once, under its own path, despite the link and the loop
+        config.discovery.max_depth = None;
+        config.discovery.symlinks = SymlinkPolicy::Always;
+        assert_eq!(
+            rel(find_baum_dirs(root, root, &config)),
+            vec!["a/shallow", "b/c/d/deep", "outside/linked"]
+        );
+
+        // Only the link is found when its target is searched no other way
+        config.ignore = vec!["outside".to_string()];
+        assert_eq!(
+            rel(find_baum_dirs(root, root, &config)),
+            vec!["a/link/linked", "a/shallow", "b/c/d/deep"]
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("node_modules", "node_modules"));
+        assert!(glob_match("*.egg-info", "wald.egg-info"));
+        assert!(glob_match("build*", "build-release"));
+        assert!(glob_match("v?nv", "venv"));
+        assert!(!glob_match("*.egg-info", "egg-info.txt"));
+        assert!(!glob_match("build", "builds"));
+    }
+
+    #[test]
+    fn test_is_ignored() {
+        // Bare names match anywhere
+        assert!(ignored(&["node_modules"], "web/app/node_modules"));
+        assert!(!ignored(&["node_modules"], "web/app"));
+
+        // Patterns with a slash match from the root
+        assert!(ignored(&["archive/**"], "archive/old/repo"));
+        assert!(ignored(&["scratch/old"], "scratch/old"));
+        assert!(!ignored(&["scratch/old"], "other/scratch/old"));
+        assert!(ignored(&["**/dist"], "a/b/dist"));
+        assert!(ignored(&["/tmp/"], "tmp"));
+    }
+}
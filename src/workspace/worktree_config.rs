@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::Workspace;
+use crate::git;
+
+/// Apply a repo's git config and expected identity to a new worktree
+///
+/// If the worktree would commit with a different `user.email` than the
+/// manifest expects for this repo (or its host), the expected address is
+/// pinned in the worktree's own config. Returns the pinned address, if any.
+pub fn configure_worktree<'a>(
+    ws: &'a Workspace,
+    repo_id: &str,
+    bare_path: &Path,
+    worktree_path: &Path,
+) -> Result<Option<&'a str>> {
+    if let Some(entry) = ws.manifest.repos.get(repo_id) {
+        git::apply_worktree_config(bare_path, worktree_path, &entry.git_config)?;
+    }
+
+    let Some(expected) = ws.manifest.expected_identity(repo_id) else {
+        return Ok(None);
+    };
+
+    if git::get_config(worktree_path, "user.email")?.as_deref() == Some(expected) {
+        return Ok(None);
+    }
+
+    pin_identity(bare_path, worktree_path, expected)?;
+    Ok(Some(expected))
+}
+
+/// Set `user.email` in a worktree's own config
+pub fn pin_identity(bare_path: &Path, worktree_path: &Path, email: &str) -> Result<()> {
+    let config = BTreeMap::from([("user.email".to_string(), email.to_string())]);
+    git::apply_worktree_config(bare_path, worktree_path, &config)
+}
@@ -0,0 +1,86 @@
+#!/usr/bin/env bash
+# Tests for 'wald alias' command
+
+# Source test libraries (run_tests.sh handles this, but allow standalone execution)
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Managing aliases
+# ====================================================================================
+
+begin_test "wald alias add, list and remove"
+    setup_wald_workspace
+
+    $WALD_BIN repo add --no-clone github.com/user/dotfiles
+    $WALD_BIN repo add --no-clone --alias=t github.com/user/tools
+
+    _result=$($WALD_BIN alias add dotfiles dots 2>&1)
+    assert_contains "$_result" "Added alias dots for github.com/user/dotfiles"
+    assert_file_contains ".wald/manifest.yaml" "    - dots"
+
+    # The new alias resolves
+    _result=$($WALD_BIN alias list dots 2>&1)
+    assert_contains "$_result" "dots"
+    assert_not_contains "$_result" "github.com/user/tools"
+
+    _result=$($WALD_BIN alias list 2>&1)
+    assert_contains "$_result" "dots  github.com/user/dotfiles"
+    assert_contains "$_result" "t     github.com/user/tools"
+
+    _result=$($WALD_BIN alias remove dotfiles dots 2>&1)
+    assert_contains "$_result" "Removed alias dots"
+    if grep -q "    - dots" .wald/manifest.yaml; then
+        _fail "alias should be removed from manifest"
+    fi
+
+    _result=$($WALD_BIN alias remove dotfiles dots 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code" "removing a missing alias should fail"
+    assert_contains "$_result" "not an alias of github.com/user/dotfiles"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald alias add refuses repo IDs and aliases in use"
+    setup_wald_workspace
+
+    $WALD_BIN repo add --no-clone --alias=dots github.com/user/dotfiles
+    $WALD_BIN repo add --no-clone github.com/user/tools
+
+    _result=$($WALD_BIN alias add tools dots 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code" "taken alias should be refused"
+    assert_contains "$_result" "already in use by repository: github.com/user/dotfiles"
+
+    _result=$($WALD_BIN alias add tools github.com/user/dotfiles 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code" "repo ID should be refused as alias"
+    assert_contains "$_result" "is a repository ID"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald alias add warns when it shadows another repo's name"
+    setup_wald_workspace
+
+    $WALD_BIN repo add --no-clone github.com/user/dotfiles
+    $WALD_BIN repo add --no-clone github.com/user/tools
+
+    _result=$($WALD_BIN alias add tools dotfiles 2>&1)
+    assert_contains "$_result" "alias 'dotfiles' shadows github.com/user/dotfiles"
+    assert_contains "$_result" "Added alias dotfiles for github.com/user/tools"
+
+    # The alias now wins over the name
+    _result=$($WALD_BIN alias list dotfiles 2>&1)
+    assert_contains "$_result" "github.com/user/tools"
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
@@ -111,6 +111,44 @@ begin_test "wald branch with nested baum path"
     teardown_wald_workspace
 end_test
 
+begin_test "wald branch adds several branches at once"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    $WALD_BIN branch "tools/repo" dev feature/a feature/b
+
+    assert_baum_worktree_count "tools/repo" 4
+    assert_baum_has_worktree "tools/repo" "dev"
+    assert_baum_has_worktree "tools/repo" "feature/a"
+    assert_baum_has_worktree "tools/repo" "feature/b"
+    assert_worktree_exists "tools/repo/_dev.wt"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald branch with several branches keeps going past a failed one"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    _result=$($WALD_BIN branch "tools/repo" feature main dev 2>&1) && _code=0 || _code=$?
+    assert_eq "3" "$_code" "partial failure exits 3"
+    assert_contains "$_result" "1 of 3 branch(s) failed to add"
+    assert_contains "$_result" "Added 2 of 3 worktree(s)"
+
+    # The branches that went through are recorded
+    assert_baum_worktree_count "tools/repo" 3
+    assert_baum_has_worktree "tools/repo" "feature"
+    assert_baum_has_worktree "tools/repo" "dev"
+
+    teardown_wald_workspace
+end_test
+
 # ====================================================================================
 # Error cases
 # ====================================================================================
@@ -129,6 +167,41 @@ begin_test "wald branch fails if branch already has worktree"
     teardown_wald_workspace
 end_test
 
+begin_test "wald branch keeps branches with the same directory name apart"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" feature/foo
+
+    # feature--foo normalizes to the same directory as feature/foo
+    $WALD_BIN branch "tools/repo" feature--foo
+
+    assert_worktree_exists "tools/repo/_feature--foo.wt"
+    assert_baum_worktree_count "tools/repo" 2
+    assert_baum_has_worktree "tools/repo" "feature--foo"
+    _path=$(grep -A1 "branch: feature--foo" tools/repo/.baum/manifest.yaml | grep "path:")
+    assert_contains "$_path" "_feature--foo-"
+    assert_worktree_exists "tools/repo/${_path##*path: }"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald branch . finds the baum from inside a worktree"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    (cd tools/repo/_main.wt && $WALD_BIN branch . feature)
+
+    assert_worktree_exists "tools/repo/_feature.wt"
+    assert_baum_has_worktree "tools/repo" "feature"
+
+    teardown_wald_workspace
+end_test
+
 begin_test "wald branch fails if baum doesn't exist"
     setup_wald_workspace
 
@@ -155,6 +228,86 @@ begin_test "wald branch fails if bare repo missing"
     teardown_wald_workspace
 end_test
 
+begin_test "wald branch --track follows another remote and pushes to origin"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    create_bare_repo "github.com/test/upstream" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    _bare=".wald/repos/github.com/test/repo.git"
+    git -C "$_bare" remote add upstream "$PWD/.wald/repos/github.com/test/upstream.git"
+
+    $WALD_BIN branch "tools/repo" fix-x --track upstream/main
+
+    assert_worktree_exists "tools/repo/_fix-x.wt"
+    _local=$(git -C "tools/repo/_fix-x.wt" rev-parse --abbrev-ref HEAD)
+    assert_eq "upstream" "$(git -C "$_bare" config "branch.$_local.remote")"
+    assert_eq "refs/heads/main" "$(git -C "$_bare" config "branch.$_local.merge")"
+    assert_eq "origin" "$(git -C "$_bare" config "branch.$_local.pushRemote")"
+    assert_eq "refs/heads/$_local:refs/heads/fix-x" \
+        "$(git -C "tools/repo/_fix-x.wt" config remote.origin.push)"
+    assert_file_contains "tools/repo/.baum/manifest.yaml" "track: upstream/main"
+
+    # Unknown remotes are rejected with a hint
+    _result=$($WALD_BIN branch "tools/repo" other --track nope 2>&1 || true)
+    assert_contains "$_result" "no remote 'nope'"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald branch refuses a baum locked by another wald process"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    # Held by a live process (this shell)
+    printf 'pid: %s\nhost: %s\ncommand: prune\nacquired_at: 0\n' "$$" "$(uname -n)" \
+        > tools/repo/.baum/lock
+    _result=$($WALD_BIN branch "tools/repo" feature 2>&1 || true)
+    assert_contains "$_result" "locked by \`wald prune\`"
+    assert_dir_not_exists "tools/repo/_feature.wt"
+
+    # Left behind by a process that is gone
+    printf 'pid: 4294967295\nhost: %s\ncommand: prune\nacquired_at: 0\n' "$(uname -n)" \
+        > tools/repo/.baum/lock
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "Stale lock on baum"
+    $WALD_BIN doctor --fix >/dev/null 2>&1
+    assert_file_not_exists "tools/repo/.baum/lock"
+
+    $WALD_BIN branch "tools/repo" feature
+    assert_worktree_exists "tools/repo/_feature.wt"
+    assert_file_not_exists "tools/repo/.baum/lock"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald branch --force asks for --yes-protected to overwrite a protected branch"
+    setup_wald_workspace
+    echo "protected_branches: [release/*]" >> .wald/config.yaml
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    git -C "$_bare" branch release/1.0 main
+    $WALD_BIN branch "tools/repo" release/1.0
+    $WALD_BIN prune "tools/repo" release/1.0
+
+    _result=$($WALD_BIN branch --force "tools/repo" release/1.0 2>&1 || true)
+    assert_contains "$_result" "'release/*' in protected_branches"
+    assert_contains "$_result" "--yes-protected"
+
+    $WALD_BIN branch --force --yes-protected "tools/repo" release/1.0
+    assert_baum_has_worktree "tools/repo" "release/1.0"
+
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
@@ -21,6 +21,7 @@ begin_test "wald doctor finds no issues in healthy workspace"
     $WALD_BIN repo add "github.com/test/repo"
     $WALD_BIN plant "github.com/test/repo" "tools/repo" main
 
+    publish_workspace
     _result=$($WALD_BIN doctor 2>&1)
 
     assert_contains "$_result" "No issues found"
@@ -44,6 +45,26 @@ begin_test "wald doctor detects missing bare repo"
     teardown_wald_workspace
 end_test
 
+begin_test "wald doctor --fix clones a registered repo that is missing"
+    setup_wald_workspace
+
+    _src=$(mktemp -d)
+    create_bare_repo "github.com/test/repo" "with_commits"
+    mv .wald/repos/github.com/test/repo.git "$_src/repo.git"
+    export GIT_CONFIG_COUNT=1 GIT_CONFIG_KEY_0="url.$_src/.insteadOf" GIT_CONFIG_VALUE_0="git@github.com:test/"
+    $WALD_BIN repo add --no-clone "github.com/test/repo"
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "--retry-clone"
+
+    $WALD_BIN doctor --fix >/dev/null 2>&1
+    assert_dir_exists ".wald/repos/github.com/test/repo.git"
+
+    unset GIT_CONFIG_COUNT GIT_CONFIG_KEY_0 GIT_CONFIG_VALUE_0
+    rm -rf "$_src"
+    teardown_wald_workspace
+end_test
+
 begin_test "wald doctor detects orphaned worktree in manifest"
     setup_wald_workspace
 
@@ -61,6 +82,53 @@ begin_test "wald doctor detects orphaned worktree in manifest"
     teardown_wald_workspace
 end_test
 
+begin_test "wald doctor --fix forgets a worktree removed without wald prune"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+
+    publish_workspace
+    rm -rf "tools/repo/_dev.wt"
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "removed here without \`wald prune\`"
+
+    $WALD_BIN doctor --fix >/dev/null 2>&1
+    assert_baum_worktree_count "tools/repo" 1
+    assert_not_contains "$(git -C "$_bare" worktree list --porcelain)" "prunable"
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "No issues found"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor reports a missing worktree's remote branch moving since planting"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    _bare=".wald/repos/github.com/test/repo.git"
+    git -C "$_bare" update-ref refs/remotes/origin/main main
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    _planted=$(git -C "$_bare" rev-parse main)
+    assert_file_contains "tools/repo/.baum/manifest.yaml" "created_from: origin/main"
+    assert_file_contains "tools/repo/.baum/manifest.yaml" "created_commit: '$_planted'"
+
+    # Another machine fetched a newer origin/main and lacks the worktree
+    git -C "$_bare" update-ref refs/remotes/origin/main dev
+    rm -rf "tools/repo/_main.wt"
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "Missing worktree"
+    assert_contains "$_result" "origin/main has moved 1 commit(s) since it was planted at ${_planted:0:7}"
+
+    teardown_wald_workspace
+end_test
+
 begin_test "wald doctor detects corrupted baum manifest"
     setup_wald_workspace
 
@@ -177,6 +245,7 @@ begin_test "wald doctor handles moved baums correctly (symlink path canonicaliza
     $WALD_BIN move tools/repo admin/repo
 
     # Doctor should find no issues (paths should match after canonicalization)
+    publish_workspace
     _result=$($WALD_BIN doctor 2>&1)
 
     assert_contains "$_result" "No issues found"
@@ -196,6 +265,7 @@ begin_test "wald doctor --fix repairs worktree registry after manual move"
     bare_path=$(get_bare_repo_path "github.com/test/repo")
 
     # Verify initial state is healthy
+    publish_workspace
     _result=$($WALD_BIN doctor 2>&1)
     assert_contains "$_result" "No issues found"
 
@@ -207,9 +277,10 @@ begin_test "wald doctor --fix repairs worktree registry after manual move"
     # Clean up tools directory (may have .gitignore and other files)
     rm -rf tools
 
-    # Doctor should detect the issue (path mismatch)
+    # Doctor should detect the issue (git still has the old path)
     _result=$($WALD_BIN doctor 2>&1)
-    assert_contains "$_result" "not in git's list"
+    assert_contains "$_result" "disconnected from its repo"
+    assert_contains "$_result" "tools/repo/_main.wt"
 
     # Doctor --fix should repair it using git worktree repair
     _result=$($WALD_BIN doctor --fix 2>&1)
@@ -222,6 +293,392 @@ begin_test "wald doctor --fix repairs worktree registry after manual move"
     teardown_wald_workspace
 end_test
 
+begin_test "wald doctor --fix pins the expected per-host identity"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    git -C tools/repo/_main.wt config user.email personal@example.com
+
+    # Require a work address for everything on this host
+    printf 'identities:\n  github.com: work@example.com\n' >> .wald/manifest.yaml
+
+    publish_workspace
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "commits as personal@example.com, expected work@example.com"
+
+    $WALD_BIN doctor --fix >/dev/null 2>&1
+    assert_eq "work@example.com" "$(git -C tools/repo/_main.wt config --get user.email)"
+    assert_exit_code 0 git -C tools/repo/_main.wt status
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "No issues found"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --textfile reports unpushed work for monitoring"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    _bare=".wald/repos/github.com/test/repo.git"
+    git -C "$_bare" config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+    git -C "$_bare" update-ref refs/remotes/origin/main refs/heads/main
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    # A commit from ten days ago that was never pushed
+    echo "local" > tools/repo/_main.wt/local.txt
+    git -C tools/repo/_main.wt add local.txt
+    GIT_COMMITTER_DATE="$(date -d '10 days ago' +%s) +0000" \
+        git -C tools/repo/_main.wt -c user.name=Test -c user.email=test@example.com \
+        commit --quiet -m "local work"
+
+    publish_workspace
+    $WALD_BIN doctor --textfile metrics.prom
+    assert_file_contains "metrics.prom" 'severity="error"} 0'
+    assert_file_contains "metrics.prom" "wald_baums{"
+    assert_file_contains "metrics.prom" "wald_unpushed_worktrees{workspace=\"$PWD\"} 1"
+
+    _result=$($WALD_BIN doctor --unpushed-days 7 2>&1)
+    assert_contains "$_result" "has unpushed commits from 10 day(s) ago"
+    _result=$($WALD_BIN doctor --unpushed-days 30 2>&1)
+    assert_contains "$_result" "No issues found"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --fix handles a worktree switched to another branch"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    _expected=$(git -C tools/repo/_main.wt branch --show-current)
+
+    git -C tools/repo/_main.wt switch --quiet dev
+    publish_workspace
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "is on branch dev, expected $_expected"
+
+    # Default fix switches back
+    $WALD_BIN doctor --fix
+    assert_eq "$_expected" "$(git -C tools/repo/_main.wt branch --show-current)"
+
+    # --record-branch keeps the new branch and updates the manifest
+    git -C tools/repo/_main.wt switch --quiet dev
+    $WALD_BIN doctor --fix --record-branch
+    assert_eq "dev" "$(git -C tools/repo/_main.wt branch --show-current)"
+    assert_file_contains "tools/repo/.baum/manifest.yaml" "local_branch: dev"
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "No issues found"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor suggests adopting a worktree added with git"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    _bare=".wald/repos/github.com/test/repo.git"
+    git -C "$_bare" worktree add --quiet "$PWD/tools/repo/hotfix" dev
+
+    publish_workspace
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "is not in the baum manifest"
+    assert_contains "$_result" "wald adopt-worktree"
+
+    $WALD_BIN adopt-worktree tools/repo tools/repo/hotfix --rename-branch
+    assert_worktree_exists "tools/repo/_dev.wt"
+    assert_dir_not_exists "tools/repo/hotfix"
+    assert_baum_has_worktree "tools/repo" "dev"
+    assert_contains "$(git -C tools/repo/_dev.wt branch --show-current)" "wald/"
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "No issues found"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --fix adopts or removes orphaned worktree directories"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    _bare=".wald/repos/github.com/test/repo.git"
+
+    # Left behind by a crash: a worktree git knows, an empty directory and
+    # one holding files
+    git -C "$_bare" worktree add --quiet "$PWD/tools/repo/_dev.wt" dev
+    mkdir tools/repo/_junk.wt tools/repo/_notes.wt
+    echo "draft" > tools/repo/_notes.wt/todo.txt
+
+    publish_workspace
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "_dev.wt is not in the baum manifest"
+    assert_contains "$_result" "tools/repo/_junk.wt looks like a worktree"
+    assert_contains "$_result" "tools/repo/_notes.wt looks like a worktree"
+
+    _result=$($WALD_BIN doctor --fix 2>&1 || true)
+    assert_baum_has_worktree "tools/repo" "dev"
+    assert_dir_not_exists "tools/repo/_junk.wt"
+    assert_file_contains "tools/repo/_notes.wt/todo.txt" "draft"
+    assert_contains "$_result" "holds files outside a git checkout"
+
+    rm -rf tools/repo/_notes.wt
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_not_contains "$_result" "looks like a worktree"
+    assert_not_contains "$_result" "not in the baum manifest"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor checks the object stores forks borrow from"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    create_bare_repo "github.com/fork/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN repo add "github.com/fork/repo" --reference "github.com/test/repo"
+    _alternates=".wald/repos/github.com/fork/repo.git/objects/info/alternates"
+    assert_eq "../../../test/repo.git/objects" "$(cat "$_alternates")"
+    assert_file_contains ".wald/manifest.yaml" "reference: github.com/test/repo"
+    # The lender keeps the unreachable objects the fork may still use
+    assert_eq "never" "$(git -C .wald/repos/github.com/test/repo.git config gc.pruneExpire)"
+
+    publish_workspace
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "No issues found"
+
+    # The lender can't be unregistered while the fork relies on it
+    _result=$($WALD_BIN repo remove "github.com/test/repo" 2>&1 || true)
+    assert_contains "$_result" "lends its objects to github.com/fork/repo"
+
+    rm "$_alternates"
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "doesn't share objects with github.com/test/repo"
+    $WALD_BIN doctor --fix >/dev/null 2>&1
+    assert_eq "../../../test/repo.git/objects" "$(cat "$_alternates")"
+
+    mv .wald/repos/github.com/test/repo.git .wald/repos/github.com/test/moved.git
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "which is missing"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --fix reconciles the container .gitignore with the worktrees"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    # Left behind by a prune, plus a worktree that lost its pattern
+    printf '/build\n/_old.wt\n' > tools/repo/.gitignore
+
+    publish_workspace
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" ".gitignore doesn't match its worktrees (stale: /_old.wt; not ignored: _main.wt)"
+
+    $WALD_BIN doctor --fix
+    assert_gitignore_not_contains "tools/repo" "/_old.wt"
+    assert_gitignore_contains "tools/repo" "/_main.wt"
+    assert_gitignore_contains "tools/repo" "/build"
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "No issues found"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --fix rewrites info/exclude from .baum/gitignore"
+    setup_wald_workspace
+    echo "worktree_ignore: exclude" >> .wald/config.yaml
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    assert_file_not_exists "tools/repo/.gitignore"
+
+    # As on a machine that just cloned the workspace
+    : > .git/info/exclude
+
+    publish_workspace
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "info/exclude doesn't match the baums' .baum/gitignore files"
+
+    $WALD_BIN doctor --fix
+    assert_file_contains ".git/info/exclude" "/tools/repo/_main.wt"
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "No issues found"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --fix relinks a worktree directory renamed with mv"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    bare_path=$(get_bare_repo_path "github.com/test/repo")
+
+    mv tools/repo/_main.wt tools/repo/_renamed.wt
+
+    publish_workspace
+    _result=$($WALD_BIN doctor 2>&1 || true)
+    assert_contains "$_result" "renamed to _renamed.wt"
+
+    _result=$($WALD_BIN doctor --fix 2>&1)
+    assert_contains "$_result" "Fixed"
+    assert_file_contains "tools/repo/.baum/manifest.yaml" "path: _renamed.wt"
+    assert_gitignore_contains "tools/repo" "/_renamed.wt"
+    assert_contains "$(git -C "$bare_path" worktree list)" "tools/repo/_renamed.wt"
+    assert_eq "0" "$(git -C tools/repo/_renamed.wt status --porcelain | wc -l | tr -d ' ')"
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "No issues found"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --json reports issues with a schema version"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    rm -rf ".wald/repos/github.com/test/repo.git"
+
+    _result=$($WALD_BIN --json doctor 2>/dev/null)
+
+    assert_json_valid "$_result"
+    assert_contains "$_result" '"schema_version": 1'
+    assert_contains "$_result" '"severity": "warn"'
+    assert_contains "$_result" '"fixable": true'
+    assert_contains "$_result" "not cloned"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor checks the workspace repo is ready to sync"
+    setup_wald_workspace
+
+    $WALD_BIN repo add --no-clone "github.com/test/repo"
+    printf '.wald/repos/\n' > .gitignore
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" ".wald/manifest.yaml has uncommitted changes"
+    assert_contains "$_result" "no upstream"
+    assert_contains "$_result" "git push -u origin main"
+    assert_contains "$_result" "wald-managed section"
+
+    $WALD_BIN doctor --fix >/dev/null 2>&1
+    assert_eq "" "$(git status --porcelain -- .wald/manifest.yaml)"
+    assert_contains "$(git log -1 --format=%s)" "Update wald manifest"
+    assert_file_contains ".gitignore" "# wald:start"
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_not_contains "$_result" "uncommitted changes"
+    assert_not_contains "$_result" "wald-managed section"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --forge warns about archived, moved and vanished repos"
+    setup_wald_workspace
+
+    for _name in repo archived old gone; do
+        $WALD_BIN repo add --no-clone "github.com/test/$_name"
+    done
+
+    # A fake GitHub API, answering by the URL (curl's last argument)
+    mkdir -p "$TEST_WS-bin"
+    cat > "$TEST_WS-bin/curl" <<'CURL'
+#!/bin/sh
+for _url; do :; done
+case "$_url" in
+    */repos/test/archived) echo '{"full_name":"test/archived","archived":true}'; echo 200 ;;
+    */repos/test/old) echo '{"full_name":"test/new","archived":false}'; echo 200 ;;
+    */repos/test/gone) echo '{"message":"Not Found"}'; echo 404; exit 22 ;;
+    *) echo '{"full_name":"test/repo","archived":false}'; echo 200 ;;
+esac
+CURL
+    chmod +x "$TEST_WS-bin/curl"
+
+    _result=$(PATH="$TEST_WS-bin:$PATH" $WALD_BIN doctor --forge 2>&1)
+    assert_contains "$_result" "Repo github.com/test/archived is archived at github.com/test/archived"
+    assert_contains "$_result" "Repo github.com/test/old moved to github.com/test/new"
+    assert_contains "$_result" "wald repo add github.com/test/new"
+    assert_contains "$_result" "Repo github.com/test/gone not found at github.com/test/gone"
+    assert_not_contains "$_result" "Repo github.com/test/repo "
+
+    # Without --forge nothing is looked up
+    _result=$(PATH="$TEST_WS-bin:$PATH" $WALD_BIN doctor 2>&1)
+    assert_not_contains "$_result" "is archived at"
+
+    rm -rf "$TEST_WS-bin"
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --forge only sends the GitLab token to configured GitLab hosts"
+    setup_wald_workspace
+
+    $WALD_BIN repo add --no-clone "git.example.org/team/repo"
+
+    # A fake GitLab API, logging the headers it was sent
+    mkdir -p "$TEST_WS-bin"
+    printf '#!/bin/sh\nprintf "%%s\\n" "$@" >> "%s"\necho '"'"'{"path_with_namespace":"team/repo"}'"'"'\necho 200\n' \
+        "$TEST_WS-curl.args" > "$TEST_WS-bin/curl"
+    chmod +x "$TEST_WS-bin/curl"
+
+    GITLAB_TOKEN=secret PATH="$TEST_WS-bin:$PATH" $WALD_BIN doctor --forge >/dev/null 2>&1
+    assert_contains "$(cat "$TEST_WS-curl.args")" "https://git.example.org/api/v4/projects/team%2Frepo"
+    assert_not_contains "$(cat "$TEST_WS-curl.args")" "secret"
+
+    rm -f "$TEST_WS-curl.args"
+    GITLAB_TOKEN=secret WALD_GITLAB_HOST=git.example.org PATH="$TEST_WS-bin:$PATH" \
+        $WALD_BIN doctor --forge >/dev/null 2>&1
+    assert_contains "$(cat "$TEST_WS-curl.args")" "PRIVATE-TOKEN: secret"
+
+    rm -rf "$TEST_WS-bin" "$TEST_WS-curl.args"
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --fix restores a damaged manifest from its backup"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add --no-clone "github.com/test/repo"
+    assert_dir_exists ".wald/backups"
+    assert_gitignore_contains ".wald/backups/"
+
+    # A botched hand edit
+    printf 'repos:\n  github.com/test/repo: {\n  github.com/test/other: {}\n' > .wald/manifest.yaml
+
+    _result=$($WALD_BIN status 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" ".wald/manifest.yaml no longer parse(s); \`wald doctor --fix\` restores from backups"
+
+    _result=$($WALD_BIN doctor 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "[ERROR] .wald/manifest.yaml doesn't parse"
+    assert_contains "$_result" "--fix restores the backup from"
+
+    _result=$($WALD_BIN doctor --fix 2>&1)
+    assert_contains "$_result" "Fixed!"
+    assert_contains "$_result" "Lost by restoring:"
+    assert_contains "$_result" "+  github.com/test/other: {}"
+    assert_file_contains ".wald/manifest.yaml" "github.com/test/repo"
+    assert_exit_code 0 $WALD_BIN status
+    assert_eq "1" "$(ls .wald/backups | grep -c 'corrupt$')"
+
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
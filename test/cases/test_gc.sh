@@ -0,0 +1,78 @@
+#!/usr/bin/env bash
+# Tests for 'wald gc'
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Stale worktree registrations
+# ====================================================================================
+
+begin_test "wald gc --worktrees prunes worktrees removed by hand and their baum entries"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+
+    assert_gitignore_contains "tools/repo" "/_dev.wt"
+    rm -rf "tools/repo/_dev.wt"
+
+    _result=$($WALD_BIN gc --worktrees --dry-run 2>&1)
+    assert_contains "$_result" "Would prune"
+    assert_contains "$_result" "listed in tools/repo"
+    assert_baum_worktree_count "tools/repo" 2
+
+    _result=$($WALD_BIN gc --worktrees 2>&1)
+    assert_contains "$_result" "Pruned 1 stale worktree registration(s), 1 removed from baum manifests"
+    assert_baum_worktree_count "tools/repo" 1
+    assert_baum_has_worktree "tools/repo" "main"
+    assert_not_contains "$(git -C "$_bare" worktree list --porcelain)" "prunable"
+    assert_gitignore_not_contains "tools/repo" "/_dev.wt"
+
+    _result=$($WALD_BIN gc --worktrees 2>&1)
+    assert_contains "$_result" "No stale worktrees"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald gc --worktrees leaves renamed and locked worktrees alone"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    $WALD_BIN lock "tools/repo" main
+
+    mv "tools/repo/_dev.wt" "tools/repo/_feature.wt"
+    mv "tools/repo/_main.wt" "$TEST_WS-unmounted"
+
+    _result=$($WALD_BIN gc --worktrees 2>&1)
+    assert_contains "$_result" "No stale worktrees"
+    assert_baum_worktree_count "tools/repo" 2
+
+    mv "$TEST_WS-unmounted" "tools/repo/_main.wt"
+    teardown_wald_workspace
+end_test
+
+begin_test "wald gc needs something to clean up"
+    setup_wald_workspace
+
+    _result=$($WALD_BIN gc 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "use --worktrees"
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
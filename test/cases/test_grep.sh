@@ -0,0 +1,65 @@
+#!/usr/bin/env bash
+# Tests for 'wald grep' command
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Searching across repos
+# ====================================================================================
+
+begin_test "wald grep searches worktrees and repos without one"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo1" "with_commits"
+    create_bare_repo "github.com/test/repo2" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo1"
+    $WALD_BIN repo add "github.com/test/repo2"
+    $WALD_BIN plant "github.com/test/repo1" "tools/repo1" main
+
+    # Uncommitted changes in worktrees are searched too
+    echo "Project notes" > tools/repo1/_main.wt/notes.txt
+    git -C tools/repo1/_main.wt add notes.txt
+
+    _result=$($WALD_BIN grep "Project" 2>&1)
+    assert_contains "$_result" "github.com/test/repo1@main:README.md:2:Project description"
+    assert_contains "$_result" "github.com/test/repo1@main:notes.txt:1:Project notes"
+    # repo2 has no worktree: its default branch is read from the bare repo
+    assert_contains "$_result" "github.com/test/repo2@main:README.md:2:Project description"
+
+    _result=$($WALD_BIN grep "Project" --repo github.com/test/repo2 2>&1)
+    assert_not_contains "$_result" "repo1"
+
+    _result=$($WALD_BIN grep "no such text" 2>&1)
+    assert_contains "$_result" "No matches"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald grep --branch reads branches without a worktree from the bare repo"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    _result=$($WALD_BIN grep "Feature" --branch dev 2>&1)
+    assert_contains "$_result" "github.com/test/repo@dev:feature.txt:1:Feature in progress"
+
+    _json=$($WALD_BIN grep "Feature" --branch dev --json 2>&1)
+    assert_contains "$_json" '"path": "feature.txt"'
+    assert_contains "$_json" '"worktree": null'
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
@@ -0,0 +1,142 @@
+#!/usr/bin/env bash
+# Tests for 'wald help' and 'wald docs' commands
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Help topics
+# ====================================================================================
+
+begin_test "wald help lists concept topics"
+    _result=$($WALD_BIN help 2>&1)
+
+    assert_contains "$_result" "Concepts"
+    assert_contains "$_result" "baum"
+    assert_contains "$_result" "tracking"
+    assert_contains "$_result" "syncing"
+end_test
+
+begin_test "wald help <topic> shows concept page"
+    _result=$($WALD_BIN help tracking 2>&1)
+
+    assert_contains "$_result" "wald/<baum-id>/<branch>"
+end_test
+
+begin_test "wald help <command> shows command help"
+    _result=$($WALD_BIN help repo add 2>&1)
+
+    assert_contains "$_result" "Usage: wald repo add"
+end_test
+
+begin_test "wald help unknown topic fails"
+    assert_exit_code 1 $WALD_BIN help nonexistent
+end_test
+
+# ====================================================================================
+# Man pages
+# ====================================================================================
+
+begin_test "wald docs man writes page per subcommand"
+    _dir=$(mktemp -d)
+
+    $WALD_BIN docs man "$_dir"
+
+    assert_file_exists "$_dir/wald.1"
+    assert_file_exists "$_dir/wald-plant.1"
+    assert_file_exists "$_dir/wald-repo-add.1"
+
+    rm -rf "$_dir"
+end_test
+
+begin_test "wald docs man without directory prints top-level page"
+    _result=$($WALD_BIN docs man 2>&1)
+
+    assert_contains "$_result" ".TH wald 1"
+end_test
+
+# ====================================================================================
+# Completion install
+# ====================================================================================
+
+begin_test "wald completion --install writes script and wrapper"
+    _home=$(mktemp -d)
+
+    HOME="$_home" XDG_DATA_HOME= $WALD_BIN completion bash --install --wrapper >/dev/null 2>&1
+
+    assert_file_exists "$_home/.local/share/bash-completion/completions/wald"
+    assert_file_contains "$_home/.local/share/wald/wald.bash" "jump"
+    assert_file_contains "$_home/.bashrc" "wald.bash"
+
+    # Reinstalling doesn't add a second source line
+    HOME="$_home" XDG_DATA_HOME= $WALD_BIN completion bash --install --wrapper >/dev/null 2>&1
+    assert_eq "1" "$(grep -c 'wald.bash' "$_home/.bashrc")"
+
+    rm -rf "$_home"
+end_test
+
+begin_test "wald completion --install keeps foreign files without --force"
+    _home=$(mktemp -d)
+    mkdir -p "$_home/.zfunc"
+    echo "# my own" > "$_home/.zfunc/_wald"
+
+    _result=$(HOME="$_home" ZDOTDIR= $WALD_BIN completion zsh --install 2>&1) && _code=0 || _code=$?
+
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "--force"
+    assert_file_contains "$_home/.zfunc/_wald" "my own"
+
+    HOME="$_home" ZDOTDIR= $WALD_BIN completion zsh --install --force >/dev/null 2>&1
+    assert_file_contains "$_home/.zfunc/_wald" "#compdef wald"
+
+    rm -rf "$_home"
+end_test
+
+begin_test "wald api capabilities lists commands and JSON fields"
+    _result=$($WALD_BIN --json api capabilities)
+
+    assert_json_valid "$_result"
+    assert_contains "$_result" '"schema_version": 1'
+    assert_contains "$_result" '"repo add"'
+    assert_contains "$_result" '"worktrees[].branch"'
+    assert_contains "$_result" '"issues[].fixable"'
+end_test
+
+begin_test "vocabulary: plain words help, messages and JSON with container, create and remove"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    echo "vocabulary: plain" >> .wald/config.yaml
+    $WALD_BIN repo add "github.com/test/repo"
+
+    _result=$($WALD_BIN create "github.com/test/repo" "tools/repo" main 2>&1)
+    assert_contains "$_result" "Created github.com/test/repo"
+    assert_not_contains "$_result" "Plant"
+
+    _result=$($WALD_BIN status 2>&1)
+    assert_contains "$_result" "Containers: 1 created"
+    _result=$($WALD_BIN --json status)
+    assert_contains "$_result" '"containers_count": 1'
+    assert_contains "$($WALD_BIN --json api capabilities)" '"containers[].repo_id"'
+
+    _result=$($WALD_BIN uproot --help)
+    assert_contains "$_result" "Remove a container"
+    assert_contains "$($WALD_BIN help container)" ".baum/manifest.yaml"
+
+    # WALD_VOCABULARY wins over the config
+    _result=$(WALD_VOCABULARY=wald $WALD_BIN status 2>&1)
+    assert_contains "$_result" "Baums: 1 planted"
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
@@ -0,0 +1,92 @@
+#!/usr/bin/env bash
+# Tests for 'wald hooks' and 'wald doctor --errors-only'
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Workspace repo hooks
+# ====================================================================================
+
+begin_test "wald hooks install-workspace blocks commits of broken manifests"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    publish_workspace
+
+    _result=$($WALD_BIN hooks install-workspace 2>&1)
+    assert_contains "$_result" "Installed"
+    assert_eq "executable" "$([[ -x .git/hooks/pre-commit && -x .git/hooks/pre-push ]] && echo executable)"
+    assert_file_contains ".git/hooks/pre-commit" "wald lint"
+    assert_file_contains ".git/hooks/pre-push" "wald doctor --errors-only"
+
+    # The hooks find wald on PATH
+    mkdir -p "$TEST_WS-bin"
+    printf '#!/bin/sh\nexec %s "$@"\n' "$WALD_BIN" > "$TEST_WS-bin/wald"
+    chmod +x "$TEST_WS-bin/wald"
+
+    printf '  github.com/test/repo:\n    lfs: full\n' >> .wald/manifest.yaml
+    git add .wald/manifest.yaml
+    _result=$(PATH="$TEST_WS-bin:$PATH" git commit -m "Break the manifest" 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "[ERROR]"
+
+    git checkout --quiet HEAD -- .wald/manifest.yaml
+    _result=$(PATH="$TEST_WS-bin:$PATH" git commit --allow-empty -m "Fine" 2>&1) && _code=0 || _code=$?
+    assert_eq "0" "$_code"
+
+    # Re-running only updates wald's own hooks
+    _result=$($WALD_BIN hooks install-workspace 2>&1)
+    assert_contains "$_result" "Up to date"
+
+    rm -rf "$TEST_WS-bin"
+    teardown_wald_workspace
+end_test
+
+begin_test "wald hooks install-workspace leaves other hooks alone without --force"
+    setup_wald_workspace
+
+    printf '#!/bin/sh\nexit 0\n' > .git/hooks/pre-push
+
+    _result=$($WALD_BIN hooks install-workspace 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "wasn't installed by wald"
+    assert_file_not_exists ".git/hooks/pre-commit"
+
+    $WALD_BIN hooks install-workspace --force >/dev/null 2>&1
+    assert_file_contains ".git/hooks/pre-push" "wald doctor --errors-only"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor --errors-only fails only on errors"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    # Unpublished workspace repo: a warning
+    _result=$($WALD_BIN doctor --errors-only 2>&1) && _code=0 || _code=$?
+    assert_eq "0" "$_code"
+    assert_not_contains "$_result" "[WARN]"
+
+    echo "invalid: [yaml" > "tools/repo/.baum/manifest.yaml"
+    _result=$($WALD_BIN doctor --errors-only 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "Invalid baum manifest"
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
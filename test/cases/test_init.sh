@@ -151,6 +151,39 @@ begin_test "wald init --no-git fails if not a git repo"
     rm -rf "$_test_dir"
 end_test
 
+begin_test "wald init --no-sync creates standalone workspace without git"
+    _test_dir=$(mktemp -d /tmp/wald-init-test.XXXXXX)
+    cd "$_test_dir"
+
+    _result=$($WALD_BIN init --no-sync 2>&1)
+    assert_contains "$_result" "standalone"
+
+    assert_dir_not_exists ".git"
+    assert_dir_exists ".wald"
+    assert_file_contains ".wald/config.yaml" "standalone: true"
+
+    # Cleanup
+    cd /tmp
+    rm -rf "$_test_dir"
+end_test
+
+begin_test "standalone workspace skips sync and workspace git status"
+    _test_dir=$(mktemp -d /tmp/wald-init-test.XXXXXX)
+    cd "$_test_dir"
+
+    $WALD_BIN init --no-sync
+
+    _result=$($WALD_BIN sync 2>&1)
+    assert_contains "$_result" "nothing to sync"
+
+    _result=$($WALD_BIN status 2>&1)
+    assert_contains "$_result" "standalone"
+
+    # Cleanup
+    cd /tmp
+    rm -rf "$_test_dir"
+end_test
+
 begin_test "wald init fails inside existing wald workspace (no nesting)"
     _test_dir=$(mktemp -d /tmp/wald-init-test.XXXXXX)
     cd "$_test_dir"
@@ -201,6 +234,50 @@ begin_test "wald init is idempotent with --force"
     rm -rf "$_test_dir"
 end_test
 
+begin_test "wald init --remote pushes the workspace and tracks origin"
+    _test_dir=$(mktemp -d /tmp/wald-init-test.XXXXXX)
+    cd "$_test_dir"
+    git init --quiet --initial-branch=main ws
+    git init --quiet --bare remote.git
+    cd ws
+    git config user.name "Test User"
+    git config user.email "test@example.com"
+
+    $WALD_BIN init --remote "$_test_dir/remote.git"
+
+    assert_eq "origin/main" "$(git rev-parse --abbrev-ref @{upstream})"
+    assert_eq "$(git rev-parse HEAD)" "$(git -C "$_test_dir/remote.git" rev-parse main)"
+    assert_contains "$(git -C "$_test_dir/remote.git" ls-tree -r --name-only main)" ".wald/manifest.yaml"
+
+    # An existing origin is left alone
+    _result=$($WALD_BIN init --force --remote "$_test_dir/other.git" 2>&1 || true)
+    assert_contains "$_result" "already has an origin"
+
+    cd /tmp
+    rm -rf "$_test_dir"
+end_test
+
+begin_test "wald debug make-fixture builds an offline workspace with planted repos"
+    _test_dir=$(mktemp -d /tmp/wald-init-test.XXXXXX)
+
+    $WALD_BIN debug make-fixture --repos 2 "$_test_dir/fx"
+
+    cd "$_test_dir/fx/workspace"
+    assert_file_exists "repo-1/_main.wt/README.md"
+    assert_file_exists "repo-2/_main.wt/README.md"
+    assert_contains "$(git -C "$_test_dir/fx/remotes/repo-1.git" branch)" "dev"
+    _result=$($WALD_BIN repo fetch 2>&1)
+    assert_contains "$_result" "Fetch complete"
+
+    # Never builds over existing files
+    _result=$($WALD_BIN debug make-fixture "$_test_dir/fx" 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "not empty"
+
+    cd /tmp
+    rm -rf "$_test_dir"
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
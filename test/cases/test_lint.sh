@@ -0,0 +1,84 @@
+#!/usr/bin/env bash
+# Tests for 'wald lint'
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Manifest linting
+# ====================================================================================
+
+begin_test "wald lint passes a healthy workspace"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo" --alias r
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+
+    _result=$($WALD_BIN lint 2>&1) && _code=0 || _code=$?
+    assert_eq "0" "$_code"
+    assert_contains "$_result" "No issues in the manifest and 1 baum manifest(s)"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald lint reports errors and fails, even when the manifest doesn't load"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    mkdir -p .wald/manifest.d
+    cat > .wald/manifest.d/team.yaml <<'YAML'
+repos:
+  github.com/test/repo:
+    colour: red
+YAML
+    sed -i 's/path: _main.wt/path: _main.wt\n  pinned: true/' tools/repo/.baum/manifest.yaml
+
+    # Other commands stop at the duplicate
+    _result=$($WALD_BIN status 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+
+    _result=$($WALD_BIN lint 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "[ERROR] .wald/manifest.d/team.yaml: repository github.com/test/repo is also defined in .wald/manifest.yaml"
+    assert_contains "$_result" "[WARN] .wald/manifest.d/team.yaml: unknown key 'colour' in repo github.com/test/repo"
+    assert_contains "$_result" "[WARN] tools/repo/.baum/manifest.yaml: unknown key 'pinned' in worktree 1"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald lint --json lists findings with a schema version"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/one" "with_commits"
+    $WALD_BIN repo add "github.com/test/one" --alias shared
+
+    # A hand edit (or a merge) gives a second repo the same alias
+    cat >> .wald/manifest.yaml <<'YAML'
+  github.com/test/two:
+    aliases:
+    - shared
+YAML
+
+    _result=$($WALD_BIN --json lint 2>/dev/null) || true
+    assert_json_valid "$_result"
+    assert_json_field "$_result" ".valid" "false"
+    assert_json_field "$_result" ".findings[0].severity" "error"
+    assert_contains "$_result" "alias 'shared' is used by github.com/test/one and github.com/test/two"
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
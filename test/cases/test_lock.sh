@@ -0,0 +1,171 @@
+#!/usr/bin/env bash
+# Tests for 'wald lock' and 'wald unlock' commands
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Locking and unlocking
+# ====================================================================================
+
+begin_test "wald lock locks worktree in git"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+
+    $WALD_BIN lock "tools/repo" dev --reason "on usb drive"
+
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    _list=$(git -C "$_bare" worktree list --porcelain)
+    assert_contains "$_list" "locked on usb drive"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald unlock removes lock"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    $WALD_BIN lock "tools/repo" main
+    $WALD_BIN unlock "tools/repo" main
+
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    _list=$(git -C "$_bare" worktree list --porcelain)
+    assert_not_contains "$_list" "locked"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald lock fails for unknown branch"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    _result=$($WALD_BIN lock "tools/repo" nope 2>&1 || true)
+    assert_contains "$_result" "no worktree for branch"
+
+    teardown_wald_workspace
+end_test
+
+# ====================================================================================
+# Locked worktrees are protected
+# ====================================================================================
+
+begin_test "wald prune refuses locked worktree even with --force"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    $WALD_BIN lock "tools/repo" dev
+
+    _result=$($WALD_BIN prune --force "tools/repo" dev 2>&1 || true)
+    assert_contains "$_result" "locked"
+    assert_worktree_exists "tools/repo/_dev.wt"
+    assert_baum_worktree_count "tools/repo" 2
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald prune removes nothing when any requested worktree is locked"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    $WALD_BIN lock "tools/repo" dev
+
+    # main comes first and is unlocked, but must survive the failed prune
+    _result=$($WALD_BIN prune "tools/repo" main dev 2>&1 || true)
+    assert_contains "$_result" "locked"
+    assert_worktree_exists "tools/repo/_main.wt"
+    assert_worktree_exists "tools/repo/_dev.wt"
+    assert_baum_has_worktree "tools/repo" "main"
+    assert_baum_worktree_count "tools/repo" 2
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald uproot refuses baum with locked worktree"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    $WALD_BIN lock "tools/repo" main
+
+    _result=$($WALD_BIN uproot --force "tools/repo" 2>&1 || true)
+    assert_contains "$_result" "locked"
+    assert_worktree_exists "tools/repo/_main.wt"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald move refuses baum with locked worktree"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    $WALD_BIN lock "tools/repo" main
+
+    _result=$($WALD_BIN move "tools/repo" "other/repo" 2>&1 || true)
+    assert_contains "$_result" "wald unlock"
+    assert_worktree_exists "tools/repo/_main.wt"
+
+    teardown_wald_workspace
+end_test
+
+# ====================================================================================
+# Lock state reporting
+# ====================================================================================
+
+begin_test "wald worktrees --verify shows lock state"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    $WALD_BIN lock "tools/repo" dev --reason "external"
+
+    _result=$($WALD_BIN worktrees --verify 2>&1)
+    assert_contains "$_result" "main -> _main.wt [ok]"
+    assert_contains "$_result" "dev -> _dev.wt [locked: external]"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor reports locked worktree as info"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    $WALD_BIN lock "tools/repo" main --reason "external"
+
+    publish_workspace
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "No issues found"
+    assert_contains "$_result" "[INFO]"
+    assert_contains "$_result" "external"
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
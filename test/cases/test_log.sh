@@ -0,0 +1,102 @@
+#!/usr/bin/env bash
+# Tests for the operation journal and 'wald log'
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Journal recording
+# ====================================================================================
+
+begin_test "wald log lists mutating operations with their outcome"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    $WALD_BIN uproot "tools/nonexistent" 2>/dev/null
+    $WALD_BIN uproot "tools/repo"
+
+    # Read-only commands are not journaled
+    $WALD_BIN worktrees >/dev/null
+    $WALD_BIN status >/dev/null
+
+    _result=$($WALD_BIN log)
+    assert_eq "4" "$(echo "$_result" | grep -c '^just now ')"
+    # --verbose shows the timestamps
+    assert_eq "4" "$($WALD_BIN --verbose log 2>/dev/null | grep -c '^[0-9]')"
+    assert_contains "$_result" "wald plant github.com/test/repo tools/repo main"
+    assert_contains "$_result" "FAILED  wald uproot tools/nonexistent"
+    assert_contains "$_result" "error: not a baum"
+    assert_not_contains "$_result" "wald worktrees"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald log records affected paths relative to the workspace"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    mkdir -p tools
+    (cd tools && $WALD_BIN plant "github.com/test/repo" "./repo" main)
+
+    _result=$($WALD_BIN --json log)
+    assert_json_valid "$_result"
+    assert_contains "$_result" '"command": "plant"'
+    assert_contains "$_result" '"tools/repo"'
+    assert_contains "$_result" '"outcome": "ok"'
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald log --since filters old operations"
+    setup_wald_workspace
+
+    mkdir -p .wald/journal
+    cat > .wald/journal/2020-01.jsonl <<'JSONL'
+{"time":"2020-01-05T10:00:00Z","command":"uproot","args":["uproot","old/baum"],"outcome":"ok","duration_ms":3}
+JSONL
+    $WALD_BIN repo add "github.com/test/repo" --no-clone
+
+    _result=$($WALD_BIN log)
+    assert_contains "$_result" "wald uproot old/baum"
+
+    _result=$($WALD_BIN log --since 1d)
+    assert_not_contains "$_result" "old/baum"
+    assert_contains "$_result" "wald repo add"
+
+    _result=$($WALD_BIN log --since 2020-01-01)
+    assert_contains "$_result" "old/baum"
+
+    assert_exit_code 1 $WALD_BIN log --since yesterday
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald log reads the journal from the XDG cache with cache: xdg"
+    setup_wald_workspace
+
+    _cache=$(mktemp -d)
+    echo "cache: xdg" >> .wald/config.yaml
+    XDG_CACHE_HOME="$_cache" $WALD_BIN repo add --no-clone "github.com/test/repo"
+
+    assert_dir_not_exists ".wald/journal"
+    _journal=$(find "$_cache/wald" -path "*/journal/*.jsonl")
+    assert_contains "$_journal" "$_cache/wald/$(basename "$PWD")-"
+    assert_contains "$(XDG_CACHE_HOME="$_cache" $WALD_BIN log)" "repo add"
+
+    rm -rf "$_cache"
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
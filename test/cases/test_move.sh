@@ -177,6 +177,25 @@ begin_test "wald move fails if destination already exists"
     teardown_wald_workspace
 end_test
 
+begin_test "wald move leaves no new directories behind when it fails"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    $WALD_BIN lock "tools/repo" dev --reason "on usb drive"
+
+    _result=$($WALD_BIN move tools/repo archive/2024/q1/repo 2>&1 || true)
+    assert_contains "$_result" "locked"
+
+    # The baum is where it was, and the destination's parents are gone
+    assert_worktree_exists "tools/repo/_main.wt"
+    assert_worktree_exists "tools/repo/_dev.wt"
+    assert_dir_not_exists "archive"
+
+    teardown_wald_workspace
+end_test
+
 # ====================================================================================
 # Git integration
 # ====================================================================================
@@ -202,6 +221,37 @@ begin_test "wald move stages changes in git"
     teardown_wald_workspace
 end_test
 
+begin_test "wald move carries .baum/gitignore and updates info/exclude with worktree_ignore: exclude"
+    setup_wald_workspace
+    echo "worktree_ignore: exclude" >> .wald/config.yaml
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    mkdir -p tools/repo
+    echo "build/" > tools/repo/.gitignore
+    $WALD_BIN plant --into-existing "github.com/test/repo" "tools/repo" main dev
+
+    # The container's own .gitignore stays untouched
+    assert_eq "build/" "$(cat tools/repo/.gitignore)"
+    assert_file_contains "tools/repo/.baum/gitignore" "/_main.wt"
+    assert_file_contains ".git/info/exclude" "/tools/repo/_main.wt"
+    assert_file_contains ".git/info/exclude" "/tools/repo/_dev.wt"
+
+    $WALD_BIN prune "tools/repo" dev
+    _exclude=$(cat .git/info/exclude)
+    assert_not_contains "$_exclude" "/tools/repo/_dev.wt"
+
+    $WALD_BIN move tools/repo admin/repo
+
+    assert_file_contains "admin/repo/.baum/gitignore" "/_main.wt"
+    _exclude=$(cat .git/info/exclude)
+    assert_contains "$_exclude" "/admin/repo/_main.wt"
+    assert_not_contains "$_exclude" "/tools/repo/"
+    assert_eq "build/" "$(cat tools/repo/.gitignore)"
+
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
@@ -0,0 +1,64 @@
+#!/usr/bin/env bash
+# Tests for notifications when long commands finish
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Notification command
+# ====================================================================================
+
+begin_test "wald runs the notify command with the outcome once a command finishes"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    cat >> .wald/config.yaml <<'YAML'
+notify:
+  command: 'echo "$WALD_COMMAND $WALD_OUTCOME: $WALD_SUMMARY" >> .wald/notified'
+  after_secs: 0
+YAML
+
+    $WALD_BIN repo add "github.com/test/repo"
+    assert_file_contains ".wald/notified" "repo add ok: wald repo add finished after 0s"
+
+    $WALD_BIN plant "github.com/test/missing" "tools/x" main 2>/dev/null || true
+    assert_file_contains ".wald/notified" "plant failed: wald plant failed after 0s: repository not found"
+
+    # Read-only commands are never announced
+    $WALD_BIN status > /dev/null
+    assert_eq 2 "$(wc -l < .wald/notified | tr -d ' ')"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald notifies only after notify.after_secs, and WALD_NOTIFY overrides the command"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    cat >> .wald/config.yaml <<'YAML'
+notify:
+  command: 'touch .wald/from-config'
+YAML
+
+    # Faster than the default minute
+    $WALD_BIN repo add "github.com/test/repo"
+    assert_file_not_exists ".wald/from-config"
+
+    sed -i.bak 's/^  command: .*/&\n  after_secs: 0/' .wald/config.yaml
+    WALD_NOTIFY='touch .wald/from-env' $WALD_BIN repo remove "github.com/test/repo"
+    assert_file_exists ".wald/from-env"
+    assert_file_not_exists ".wald/from-config"
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
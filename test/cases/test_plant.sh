@@ -171,6 +171,30 @@ begin_test "wald plant adds worktrees to existing baum"
     teardown_wald_workspace
 end_test
 
+begin_test "wald plant . adds branches to the baum around the current directory"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    mkdir -p tools/repo/_main.wt/src
+
+    (cd tools/repo/_main.wt/src && $WALD_BIN plant . dev feature)
+
+    assert_baum_worktree_count "tools/repo" 3
+    assert_baum_has_worktree "tools/repo" "dev"
+    assert_baum_has_worktree "tools/repo" "feature"
+    assert_worktree_exists "tools/repo/_dev.wt"
+
+    # Outside a baum there is nothing to infer
+    mkdir -p notes
+    _result=$(cd notes && $WALD_BIN plant . other 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code" "plant . outside a baum should fail"
+    assert_contains "$_result" "not inside a baum"
+
+    teardown_wald_workspace
+end_test
+
 begin_test "wald plant on existing baum fails if branch exists"
     setup_wald_workspace
 
@@ -213,6 +237,372 @@ begin_test "wald plant warns for partial clone"
     teardown_wald_workspace
 end_test
 
+begin_test "wald plant applies repo git config to new worktrees"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add --git-config user.email=work@example.com "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    # Set in the bare repo (it already existed, so add applies it directly)
+    _bare_path=$(get_bare_repo_path "github.com/test/repo")
+    assert_eq "work@example.com" "$(git -C "$_bare_path" config --get user.email)"
+
+    # And in the worktree's own config
+    _email=$(git -C tools/repo/_main.wt config --worktree --get user.email)
+    assert_eq "work@example.com" "$_email"
+
+    # Per-worktree config must not turn the worktree (or its siblings) bare
+    assert_exit_code 0 git -C tools/repo/_main.wt status
+    $WALD_BIN branch "tools/repo" dev
+    assert_exit_code 0 git -C tools/repo/_dev.wt status
+    assert_eq "true" "$(git -C "$_bare_path" rev-parse --is-bare-repository)"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant pins the repo's expected identity"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    git -C "$(get_bare_repo_path "github.com/test/repo")" config user.email wrong@example.com
+    $WALD_BIN repo add --identity right@example.com "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    assert_eq "right@example.com" "$(git -C tools/repo/_main.wt config --get user.email)"
+    assert_exit_code 0 git -C tools/repo/_main.wt status
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant --file plants every entry and continues past failures"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/api" "with_commits"
+    create_bare_repo "github.com/test/web" "with_commits"
+    $WALD_BIN repo add "github.com/test/api"
+    $WALD_BIN repo add "github.com/test/web"
+
+    cat > plants.yaml <<'YAML'
+- repo: github.com/test/api
+  container: ws24/api
+  branches: [main, dev]
+- repo: github.com/test/missing
+  container: ws24/missing
+- repo: web
+  container: ws24/web
+YAML
+
+    _result=$($WALD_BIN plant --file plants.yaml 2>&1)
+    _exit=$?
+    assert_eq "1" "$_exit"
+    assert_contains "$_result" "[3/3]"
+    assert_contains "$_result" "1 of 3 entries failed"
+    assert_contains "$_result" "github.com/test/missing at ws24/missing"
+
+    assert_baum_has_worktree "ws24/api" "main"
+    assert_baum_has_worktree "ws24/api" "dev"
+    assert_baum_has_worktree "ws24/web" "main"
+    assert_dir_not_exists "ws24/missing"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant creates many worktrees at once"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+
+    assert_exit_code 0 $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev f1 f2 f3 f4
+
+    for _branch in main dev f1 f2 f3 f4; do
+        assert_baum_has_worktree "tools/repo" "$_branch"
+        assert_worktree_exists "tools/repo/_${_branch}.wt"
+    done
+    assert_bare_worktree_count "github.com/test/repo" 6
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant keeps the worktrees that succeeded when one fails"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    git -C "$(get_bare_repo_path "github.com/test/repo")" branch feature main
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    # A non-empty directory in the way makes the dev checkout fail (a new
+    # container holding one is refused up front)
+    mkdir -p "tools/repo/_dev.wt"
+    echo "in the way" > "tools/repo/_dev.wt/file.txt"
+
+    _result=$($WALD_BIN plant "github.com/test/repo" "tools/repo" dev feature 2>&1)
+    assert_eq "1" "$?"
+    assert_contains "$_result" "failed to create 1 of 2 worktree(s)"
+
+    assert_baum_has_worktree "tools/repo" "feature"
+    assert_worktree_exists "tools/repo/_feature.wt"
+    assert_file_contains "tools/repo/_dev.wt/file.txt" "in the way"
+    assert_bare_worktree_count "github.com/test/repo" 2
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant refuses the workspace root, .wald/ and existing checkouts"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    _result=$($WALD_BIN plant "github.com/test/repo" . main 2>&1 || true)
+    assert_contains "$_result" "workspace root"
+
+    _result=$($WALD_BIN plant "github.com/test/repo" .wald/repos/foo main 2>&1 || true)
+    assert_contains "$_result" "under .wald/"
+    assert_dir_not_exists ".wald/repos/foo"
+
+    _result=$($WALD_BIN plant "github.com/test/repo" tools/repo/_main.wt/nested main 2>&1 || true)
+    assert_contains "$_result" "inside the git checkout tools/repo/_main.wt"
+    assert_dir_not_exists "tools/repo/_main.wt/nested"
+
+    _result=$($WALD_BIN move tools/repo tools/repo/_main.wt/moved 2>&1 || true)
+    assert_contains "$_result" "inside the git checkout"
+    assert_worktree_exists "tools/repo/_main.wt"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant into a directory with files requires --into-existing"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+
+    mkdir -p research/project
+    echo "my notes" > research/project/notes.md
+
+    _result=$($WALD_BIN plant "github.com/test/repo" "research/project" main 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "--into-existing"
+    assert_dir_not_exists "research/project/.baum"
+
+    $WALD_BIN plant --into-existing "github.com/test/repo" "research/project" main
+
+    assert_baum_has_worktree "research/project" "main"
+    assert_file_contains "research/project/notes.md" "my notes"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant --into-existing rejects worktree-like directories"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+
+    mkdir -p research/project/_main.wt
+
+    _result=$($WALD_BIN plant --into-existing "github.com/test/repo" "research/project" main 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "_main.wt"
+    assert_dir_not_exists "research/project/.baum"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant checks out the branch itself for repos with direct branching"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/paper" "with_commits"
+    $WALD_BIN repo add "github.com/test/paper" --branching direct
+    assert_file_contains ".wald/manifest.yaml" "branching: direct"
+
+    $WALD_BIN plant "github.com/test/paper" "papers/draft" main
+    assert_eq "main" "$(git -C papers/draft/_main.wt branch --show-current)"
+    assert_file_contains "papers/draft/.baum/manifest.yaml" "local_branch: main"
+
+    # A branch can only be checked out once
+    _result=$($WALD_BIN plant "github.com/test/paper" "papers/copy" main 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "already checked out"
+
+    _result=$($WALD_BIN plant --track upstream "github.com/test/paper" "papers/fork" dev 2>&1) && _code=0 || _code=$?
+    assert_contains "$_result" "direct branching"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant reports every branch in conflict and plants the others with --continue-on-conflict"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/paper" "with_commits"
+    $WALD_BIN repo add "github.com/test/paper" --branching direct
+    _bare="$(get_bare_repo_path "github.com/test/paper")"
+
+    # main and dev each have a commit their upstream lacks
+    git -C "$_bare" config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+    for _branch in main dev; do
+        git -C "$_bare" update-ref "refs/remotes/origin/$_branch" "$_branch~1"
+        git -C "$_bare" config "branch.$_branch.remote" origin
+        git -C "$_bare" config "branch.$_branch.merge" "refs/heads/$_branch"
+    done
+
+    _result=$($WALD_BIN plant "github.com/test/paper" "papers/draft" main dev fresh 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "2 of 3 branch(es) already exist with unpushed commits"
+    assert_contains "$_result" 'main: 1 commit(s) ahead, last "Add description"'
+    assert_contains "$_result" 'dev: 1 commit(s) ahead, last "Start feature development"'
+    assert_contains "$_result" "--continue-on-conflict to plant the others"
+    assert_dir_not_exists "papers/draft/_fresh.wt"
+
+    _result=$($WALD_BIN plant --continue-on-conflict "github.com/test/paper" "papers/draft" main dev fresh 2>&1)
+    assert_contains "$_result" "2 of 3 branch(es) already exist with unpushed commits"
+    assert_contains "$_result" "skipped 2 in conflict"
+    assert_dir_exists "papers/draft/_fresh.wt"
+    assert_dir_not_exists "papers/draft/_main.wt"
+    # The conflicting branches are left as they were
+    assert_eq "Start feature development" "$(git -C "$_bare" log -1 --format=%s dev)"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald shows paths relative to the workspace unless --absolute-paths"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    # From a subdirectory the path is still relative to the workspace root
+    mkdir -p notes
+    _result=$(cd notes && $WALD_BIN plant "github.com/test/repo" "tools/repo" main 2>&1 || true)
+    assert_contains "$_result" "already exists in baum at tools/repo"
+    assert_not_contains "$_result" "$PWD"
+
+    _result=$($WALD_BIN --absolute-paths plant "github.com/test/repo" "tools/repo" main 2>&1 || true)
+    assert_contains "$_result" "already exists in baum at /"
+    assert_contains "$_result" "/tools/repo"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant pins the repo's default branch in the baum"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo"
+    assert_file_contains "tools/repo/.baum/manifest.yaml" "default_branch: main"
+
+    # The remote's default changes; new baums follow, the existing one doesn't
+    git -C ".wald/repos/github.com/test/repo.git" symbolic-ref HEAD refs/heads/dev
+    $WALD_BIN plant "github.com/test/repo" "tools/other"
+    assert_worktree_exists "tools/other/_dev.wt"
+
+    $WALD_BIN prune "tools/repo" main
+    assert_exit_code 0 $WALD_BIN plant "github.com/test/repo" "tools/repo"
+    assert_worktree_exists "tools/repo/_main.wt"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant --group plants a baum for each repo of the group"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/api" "with_commits"
+    create_bare_repo "github.com/test/billing" "with_commits"
+    create_bare_repo "github.com/test/web" "with_commits"
+    $WALD_BIN repo add --group backend "github.com/test/api"
+    $WALD_BIN repo add --group backend "github.com/test/billing"
+    $WALD_BIN repo add "github.com/test/web"
+    assert_file_contains ".wald/manifest.yaml" "group: backend"
+
+    $WALD_BIN plant --group backend projects/backend main dev
+    assert_dir_exists "projects/backend/api/_main.wt"
+    assert_dir_exists "projects/backend/api/_dev.wt"
+    assert_dir_exists "projects/backend/billing/_main.wt"
+    assert_dir_not_exists "projects/backend/web"
+
+    # A template places each baum
+    $WALD_BIN plant --group backend "svc/{repo}-review" main
+    assert_dir_exists "svc/api-review/_main.wt"
+    assert_dir_exists "svc/billing-review/_main.wt"
+
+    _result=$($WALD_BIN plant --group frontend projects/frontend 2>&1 || true)
+    assert_contains "$_result" "no repositories in group 'frontend'"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant --template computes the container from config"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/paper" "with_commits"
+    create_bare_repo "github.com/test/data" "with_commits"
+    $WALD_BIN repo add --alias paper "github.com/test/paper"
+    $WALD_BIN repo add --group research "github.com/test/data"
+    cat >> .wald/config.yaml <<'YAML'
+plant_templates:
+  research: research/{year}/{repo}
+YAML
+    _year=$(date -u +%Y)
+
+    $WALD_BIN plant paper --template research main dev
+    assert_dir_exists "research/$_year/paper/_main.wt"
+    assert_dir_exists "research/$_year/paper/_dev.wt"
+
+    $WALD_BIN plant --group research --template research
+    assert_dir_exists "research/$_year/data/_main.wt"
+
+    _result=$($WALD_BIN plant paper --template nope 2>&1 || true)
+    assert_contains "$_result" "no plant template 'nope' (have: research)"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald plant --at starts the branch at an older commit"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    _old=$(git -C "$_bare" rev-parse main~1)
+
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main --at main~1
+    assert_eq "$_old" "$(git -C tools/repo/_main.wt rev-parse HEAD)"
+    assert_file_contains "tools/repo/.baum/manifest.yaml" "$_old"
+
+    _result=$($WALD_BIN plant "github.com/test/repo" "tools/other" main --at nope 2>&1) && _code=0 || _code=$?
+    assert_eq 1 "$_code"
+    assert_contains "$_result" "--at nope: no such commit"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald ignores GIT_DIR and GIT_WORK_TREE exported by the caller"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    # As in a git hook or CI step of another repo
+    _other=$(mktemp -d)
+    git init --quiet "$_other"
+
+    GIT_DIR="$_other/.git" GIT_WORK_TREE="$_other" $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    assert_worktree_exists "tools/repo/_main.wt"
+    assert_bare_worktree_count "github.com/test/repo" 1
+    assert_not_contains "$(git -C "$_other" worktree list)" "_main.wt"
+
+    _result=$(GIT_DIR="$_other/.git" $WALD_BIN status 2>&1)
+    assert_contains "$_result" "github.com/test/repo"
+
+    rm -rf "$_other"
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
@@ -0,0 +1,82 @@
+#!/usr/bin/env bash
+# Tests for running wald-<name> executables as subcommands
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# Write an executable wald-<name> into $1 that reports what it got
+_write_plugin() {
+    mkdir -p "$1"
+    cat > "$1/wald-$2" <<'SH'
+#!/bin/sh
+echo "args: $*"
+echo "workspace: $WALD_WORKSPACE"
+echo "context: $WALD_CONTEXT"
+exit 7
+SH
+    chmod +x "$1/wald-$2"
+}
+
+# ====================================================================================
+# Plugin dispatch
+# ====================================================================================
+
+begin_test "wald runs wald-<name> from PATH for unknown commands, with the workspace context"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    _write_plugin "$TEST_WS/.bin" deploy
+
+    cd tools/repo/_main.wt
+    _result=$(PATH="$TEST_WS/.bin:$PATH" $WALD_BIN --json deploy staging --force 2>&1) && _code=0 || _code=$?
+    cd "$TEST_WS"
+
+    # The plugin's own exit code, and its arguments untouched
+    assert_eq "7" "$_code"
+    assert_contains "$_result" "args: staging --force"
+    assert_contains "$_result" "workspace: $(cd "$TEST_WS" && pwd -P)"
+    assert_contains "$_result" '"repo_id":"github.com/test/repo"'
+    assert_contains "$_result" '"json":true'
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald runs plugins outside a workspace too"
+    _test_dir=$(mktemp -d /tmp/wald-plugin-test.XXXXXX)
+    _write_plugin "$_test_dir/bin" hello
+
+    cd "$_test_dir"
+    _result=$(PATH="$_test_dir/bin:$PATH" $WALD_BIN hello 2>&1) || true
+    assert_contains "$_result" '"workspace":null'
+
+    cd /tmp
+    rm -rf "$_test_dir"
+end_test
+
+begin_test "wald still rejects unknown commands that no plugin provides"
+    setup_wald_workspace
+
+    # Not executable, so not a plugin
+    mkdir -p .bin
+    echo "#!/bin/sh" > .bin/wald-stauts
+
+    _result=$(PATH="$TEST_WS/.bin:$PATH" $WALD_BIN stauts 2>&1) && _code=0 || _code=$?
+    assert_eq "2" "$_code"
+    assert_contains "$_result" "unrecognized subcommand 'stauts'"
+    assert_contains "$_result" "status"
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
@@ -0,0 +1,120 @@
+#!/usr/bin/env bash
+# Tests for 'wald pr create'
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# Plant main and dev of $1 in tools/repo, with origin pointing at a
+# writable source repo, and put a fake curl answering $2 on $TEST_WS-bin
+setup_pr_baum() {
+    local repo_id="$1" response="$2"
+    local source_id="${repo_id%/*}/source"
+    create_bare_repo "$source_id" "with_commits"
+    create_bare_repo "$repo_id" "with_commits"
+    $WALD_BIN repo add --no-clone "$repo_id"
+    _src="$PWD/$(get_bare_repo_path "$source_id")"
+    _bare="$(get_bare_repo_path "$repo_id")"
+    git -C "$_bare" remote set-url origin "$_src"
+    git -C "$_bare" config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+    $WALD_BIN repo fetch "$repo_id"
+    $WALD_BIN plant "$repo_id" "tools/repo" main dev
+
+    mkdir -p "$TEST_WS-bin"
+    printf '#!/bin/sh\nprintf "%%s\\n" "$@" > "%s"\necho '"'"'%s'"'"'\necho 201\n' \
+        "$TEST_WS-curl.args" "$response" > "$TEST_WS-bin/curl"
+    chmod +x "$TEST_WS-bin/curl"
+}
+
+# ====================================================================================
+# Opening pull requests
+# ====================================================================================
+
+begin_test "wald pr create pushes the branch and opens a GitHub pull request"
+    setup_wald_workspace
+
+    setup_pr_baum "github.com/test/repo" '{"html_url":"https://github.com/test/repo/pull/7"}'
+    git -C tools/repo/_dev.wt -c user.name=Test -c user.email=test@wald.local \
+        commit --quiet --allow-empty -m "Add the widget" -m "It frobs."
+
+    _result=$(cd tools/repo/_dev.wt && GITHUB_TOKEN=secret PATH="$TEST_WS-bin:$PATH" $WALD_BIN pr create 2>&1)
+    assert_contains "$_result" "https://github.com/test/repo/pull/7"
+    assert_eq "Add the widget" "$(git -C "$_src" log -1 --format=%s dev)"
+
+    _args=$(cat "$TEST_WS-curl.args")
+    assert_contains "$_args" "https://api.github.com/repos/test/repo/pulls"
+    assert_contains "$_args" "Authorization: Bearer secret"
+    assert_contains "$_args" '"base":"main"'
+    assert_contains "$_args" '"head":"dev"'
+    assert_contains "$_args" '"title":"Add the widget"'
+    assert_contains "$_args" '"body":"It frobs."'
+
+    rm -rf "$TEST_WS-bin" "$TEST_WS-curl.args"
+    teardown_wald_workspace
+end_test
+
+begin_test "wald pr create opens a GitLab merge request on other hosts"
+    setup_wald_workspace
+
+    setup_pr_baum "git.example.org/team/repo" '{"web_url":"https://git.example.org/team/repo/-/merge_requests/3"}'
+
+    _result=$(cd tools/repo/_dev.wt && GITLAB_TOKEN=secret WALD_GITLAB_HOST=git.example.org \
+        PATH="$TEST_WS-bin:$PATH" $WALD_BIN pr create --draft --title "Try it" 2>&1)
+    assert_contains "$_result" "https://git.example.org/team/repo/-/merge_requests/3"
+
+    _args=$(cat "$TEST_WS-curl.args")
+    assert_contains "$_args" "https://git.example.org/api/v4/projects/team%2Frepo/merge_requests"
+    assert_contains "$_args" "PRIVATE-TOKEN: secret"
+    assert_contains "$_args" '"source_branch":"dev"'
+    assert_contains "$_args" '"target_branch":"main"'
+    assert_contains "$_args" '"title":"Draft: Try it"'
+
+    rm -rf "$TEST_WS-bin" "$TEST_WS-curl.args"
+    teardown_wald_workspace
+end_test
+
+begin_test "wald pr create only sends the GitLab token to configured GitLab hosts"
+    setup_wald_workspace
+
+    setup_pr_baum "git.example.org/team/repo" '{}'
+
+    _result=$(cd tools/repo/_dev.wt && GITLAB_TOKEN=secret PATH="$TEST_WS-bin:$PATH" \
+        $WALD_BIN pr create 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "git.example.org isn't a known GitLab host; add it to WALD_GITLAB_HOST"
+    assert_file_not_exists "$TEST_WS-curl.args"
+
+    rm -rf "$TEST_WS-bin" "$TEST_WS-curl.args"
+    teardown_wald_workspace
+end_test
+
+begin_test "wald pr create refuses the base branch and paths outside worktrees"
+    setup_wald_workspace
+
+    setup_pr_baum "github.com/test/repo" '{}'
+
+    _result=$(cd tools/repo/_main.wt && GITHUB_TOKEN=secret $WALD_BIN pr create 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "main is the base branch"
+
+    _result=$($WALD_BIN pr create 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "not inside a wald worktree"
+
+    _result=$(cd tools/repo/_dev.wt && env -u GITHUB_TOKEN -u GH_TOKEN $WALD_BIN pr create 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "set GITHUB_TOKEN"
+
+    rm -rf "$TEST_WS-bin" "$TEST_WS-curl.args"
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
@@ -82,6 +82,22 @@ begin_test "wald prune updates baum manifest correctly"
     teardown_wald_workspace
 end_test
 
+begin_test "wald prune removes the worktree from the container .gitignore"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    assert_gitignore_contains "tools/repo" "/_dev.wt"
+
+    $WALD_BIN prune "tools/repo" dev
+
+    assert_gitignore_not_contains "tools/repo" "/_dev.wt"
+    assert_gitignore_contains "tools/repo" "/_main.wt"
+
+    teardown_wald_workspace
+end_test
+
 # ====================================================================================
 # Warning cases
 # ====================================================================================
@@ -167,6 +183,192 @@ begin_test "wald prune fails if path is not a baum"
     teardown_wald_workspace
 end_test
 
+# ====================================================================================
+# Orphan branch cleanup (--branches)
+# ====================================================================================
+
+begin_test "wald prune --branches deletes orphan tracking branches"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    $WALD_BIN prune "tools/repo" dev
+
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    assert_contains "$(git -C "$_bare" branch --list 'wald/*')" "/dev"
+
+    $WALD_BIN prune --branches
+
+    assert_not_contains "$(git -C "$_bare" branch --list 'wald/*')" "/dev"
+    assert_contains "$(git -C "$_bare" branch --list 'wald/*')" "/main"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald prune --branches keeps the branches of uprooted baums in the trash"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    _branches=$(git -C "$_bare" for-each-ref --format='%(refname)' 'refs/heads/wald/*')
+
+    $WALD_BIN uproot "tools/repo"
+    _result=$($WALD_BIN prune --branches 2>&1)
+    assert_not_contains "$_result" "Deleted"
+    assert_eq "$_branches" "$(git -C "$_bare" for-each-ref --format='%(refname)' 'refs/heads/wald/*')"
+
+    $WALD_BIN undo uproot
+    assert_worktree_exists "tools/repo/_main.wt"
+    assert_worktree_exists "tools/repo/_dev.wt"
+    assert_eq "$_branches" "$(git -C "$_bare" for-each-ref --format='%(refname)' 'refs/heads/wald/*')"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald prune --branches --dry-run keeps branches"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    $WALD_BIN prune "tools/repo" dev
+
+    _result=$($WALD_BIN prune --branches --dry-run 2>&1)
+    assert_contains "$_result" "Would delete"
+
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    assert_contains "$(git -C "$_bare" branch --list 'wald/*')" "/dev"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald prune --branches --repo only touches that repo"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo1" "with_commits"
+    create_bare_repo "github.com/test/repo2" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo1"
+    $WALD_BIN repo add "github.com/test/repo2"
+    $WALD_BIN plant "github.com/test/repo1" "tools/repo1" main dev
+    $WALD_BIN plant "github.com/test/repo2" "tools/repo2" main dev
+    $WALD_BIN prune "tools/repo1" dev
+    $WALD_BIN prune "tools/repo2" dev
+
+    $WALD_BIN prune --branches --repo repo1
+
+    _bare1=$(get_bare_repo_path "github.com/test/repo1")
+    _bare2=$(get_bare_repo_path "github.com/test/repo2")
+    assert_not_contains "$(git -C "$_bare1" branch --list 'wald/*')" "/dev"
+    assert_contains "$(git -C "$_bare2" branch --list 'wald/*')" "/dev"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald prune --branches --baum only touches that baum"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/a" main dev
+    $WALD_BIN plant "github.com/test/repo" "tools/b" main dev
+    $WALD_BIN prune "tools/a" dev
+    $WALD_BIN prune "tools/b" dev
+
+    _id_a=$(grep '^id:' tools/a/.baum/manifest.yaml | awk '{print $2}' | tr -d "'\"")
+    _id_b=$(grep '^id:' tools/b/.baum/manifest.yaml | awk '{print $2}' | tr -d "'\"")
+
+    $WALD_BIN prune --branches --baum tools/a
+
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    assert_not_contains "$(git -C "$_bare" branch --list 'wald/*')" "wald/$_id_a/dev"
+    assert_contains "$(git -C "$_bare" branch --list 'wald/*')" "wald/$_id_b/dev"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald prune --repo requires --branches"
+    setup_wald_workspace
+
+    _result=$($WALD_BIN prune --repo repo "tools/repo" main 2>&1 || true)
+    assert_contains "$_result" "require --branches"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald prune --branches --force keeps unpushed protected branches without --yes-protected"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    git -C "$_bare" config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+    git -C "$_bare" update-ref refs/remotes/origin/main refs/heads/main
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    git -C tools/repo/_main.wt -c user.name=Test -c user.email=test@example.com \
+        commit --allow-empty --quiet -m "local work"
+    $WALD_BIN prune "tools/repo" main
+
+    _result=$($WALD_BIN prune --branches --force 2>&1)
+    assert_contains "$_result" "the repository's default branch"
+    assert_contains "$(git -C "$_bare" branch --list 'wald/*')" "/main"
+
+    $WALD_BIN prune --branches --force --yes-protected --yes
+    assert_not_contains "$(git -C "$_bare" branch --list 'wald/*')" "/main"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald prune --branches --force asks before deleting unpushed branches"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    git -C "$_bare" config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+    git -C "$_bare" update-ref refs/remotes/origin/dev refs/heads/dev
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" dev
+    git -C tools/repo/_dev.wt -c user.name=Test -c user.email=test@example.com \
+        commit --allow-empty --quiet -m "local work"
+    $WALD_BIN prune "tools/repo" dev
+
+    # No terminal to ask on, so nothing is deleted without --yes
+    _result=$($WALD_BIN prune --branches --force 2>&1 </dev/null) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "refusing to delete 1 branch(es) with unpushed commits without confirmation; pass --yes"
+    assert_contains "$(git -C "$_bare" branch --list 'wald/*')" "/dev"
+
+    $WALD_BIN prune --branches --force --no-confirm
+    assert_not_contains "$(git -C "$_bare" branch --list 'wald/*')" "/dev"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald prune --branches --json reports each orphan branch"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    $WALD_BIN prune "tools/repo" dev
+
+    _result=$($WALD_BIN --json prune --branches --dry-run 2>/dev/null)
+    assert_json_valid "$_result"
+    assert_contains "$_result" '"schema_version": 1'
+    assert_contains "$_result" '"dry_run": true'
+    assert_contains "$_result" '"logical_branch": "dev"'
+    assert_contains "$_result" '"reason": "worktree_gone"'
+    assert_contains "$_result" '"unpushed": false'
+    assert_contains "$_result" '"action": "would_delete"'
+
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    assert_contains "$(git -C "$_bare" branch --list 'wald/*')" "/dev"
+
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
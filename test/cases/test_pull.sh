@@ -0,0 +1,105 @@
+#!/usr/bin/env bash
+# Tests for 'wald pull'
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Updating a baum's worktrees
+# ====================================================================================
+
+begin_test "wald pull fast-forwards clean worktrees and skips dirty ones"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/source" "with_commits"
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add --no-clone "github.com/test/repo"
+    _src="$PWD/$(get_bare_repo_path "github.com/test/source")"
+    _bare="$(get_bare_repo_path "github.com/test/repo")"
+    git -C "$_bare" remote set-url origin "$_src"
+    git -C "$_bare" config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+    $WALD_BIN repo fetch "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+
+    # Both branches move on upstream
+    _clone="$TEST_WS-clone"
+    git clone --quiet "$_src" "$_clone"
+    git -C "$_clone" -c user.name=Test -c user.email=test@wald.local commit --quiet --allow-empty -m "Upstream main"
+    git -C "$_clone" checkout --quiet dev
+    git -C "$_clone" -c user.name=Test -c user.email=test@wald.local commit --quiet --allow-empty -m "Upstream dev"
+    git -C "$_clone" push --quiet origin main dev
+    rm -rf "$_clone"
+
+    echo "local" > "tools/repo/_dev.wt/notes.txt"
+
+    _result=$($WALD_BIN pull "tools/repo" 2>&1)
+    assert_contains "$_result" "Updated main"
+    assert_contains "$_result" "Skipped dev: worktree has local changes"
+    assert_contains "$_result" "1 updated, 0 up to date, 1 skipped"
+    assert_eq "Upstream main" "$(git -C tools/repo/_main.wt log -1 --format=%s)"
+    assert_eq "Start feature development" "$(git -C tools/repo/_dev.wt log -1 --format=%s)"
+
+    _result=$($WALD_BIN pull "tools/repo" 2>&1)
+    assert_contains "$_result" "0 updated, 1 up to date, 1 skipped"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald pull skips diverged branches unless --rebase"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/source" "with_commits"
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add --no-clone "github.com/test/repo"
+    _src="$PWD/$(get_bare_repo_path "github.com/test/source")"
+    _bare="$(get_bare_repo_path "github.com/test/repo")"
+    git -C "$_bare" remote set-url origin "$_src"
+    git -C "$_bare" config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+    $WALD_BIN repo fetch "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    _clone="$TEST_WS-clone"
+    git clone --quiet "$_src" "$_clone"
+    echo "upstream" > "$_clone/upstream.txt"
+    git -C "$_clone" add upstream.txt
+    git -C "$_clone" -c user.name=Test -c user.email=test@wald.local commit --quiet -m "Upstream change"
+    git -C "$_clone" push --quiet origin main
+    rm -rf "$_clone"
+
+    git -C tools/repo/_main.wt config user.name "Wald Test"
+    git -C tools/repo/_main.wt config user.email "test@wald.local"
+    echo "local" > "tools/repo/_main.wt/local.txt"
+    git -C tools/repo/_main.wt add local.txt
+    git -C tools/repo/_main.wt commit --quiet -m "Local change"
+
+    _result=$($WALD_BIN pull "tools/repo" 2>&1)
+    assert_contains "$_result" "Skipped main: branch has diverged (1 ahead, 1 behind)"
+    assert_eq "Local change" "$(git -C tools/repo/_main.wt log -1 --format=%s)"
+
+    _result=$($WALD_BIN pull --rebase "tools/repo" 2>&1)
+    assert_contains "$_result" "1 updated"
+    assert_eq "Local change|Upstream change" "$(git -C tools/repo/_main.wt log -2 --format=%s | paste -sd'|')"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald pull fails for a path that is not a baum"
+    setup_wald_workspace
+
+    _result=$($WALD_BIN pull "tools/nothing" 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "not a baum"
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
@@ -0,0 +1,164 @@
+#!/usr/bin/env bash
+# Tests for 'wald relocate' after moving a whole workspace
+
+# Source test libraries
+if [[ -z "$WALD_BIN" ]]; then
+    SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+    source "$SCRIPT_DIR/lib/assertions.sh"
+    source "$SCRIPT_DIR/lib/setup.sh"
+    source "$SCRIPT_DIR/lib/helpers.sh"
+    WALD_BIN="${WALD_BIN:-cargo run --quiet --}"
+fi
+
+# ====================================================================================
+# Relocation
+# ====================================================================================
+
+begin_test "wald relocate reconnects worktrees and alternates of a moved workspace"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    create_bare_repo "github.com/fork/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN repo add "github.com/fork/repo" --reference "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    $WALD_BIN plant "github.com/fork/repo" "forks/repo" main
+    publish_workspace
+
+    # Move the whole workspace; every link git recorded now points at the old place
+    _old="$TEST_WS"
+    cd /tmp
+    mv "$_old" "$_old-moved"
+    TEST_WS="$_old-moved"
+    cd "$TEST_WS"
+    assert_exit_code 128 git -C tools/repo/_main.wt status
+
+    _result=$($WALD_BIN relocate 2>&1)
+    assert_contains "$_result" "Relocating from $_old to $TEST_WS"
+    # Alternates inside the workspace are relative and moved along with it
+    assert_contains "$_result" "Reconnected 2 worktree(s) and 0 alternate(s)"
+    assert_exit_code 0 git -C tools/repo/_main.wt status
+    assert_exit_code 0 git -C forks/repo/_main.wt log -1
+    assert_eq "../../../test/repo.git/objects" "$(cat .wald/repos/github.com/fork/repo.git/objects/info/alternates)"
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_not_contains "$_result" "disconnected from its repo"
+    assert_not_contains "$_result" "which is missing"
+
+    # Running it again finds nothing left to do
+    _result=$($WALD_BIN relocate 2>&1)
+    assert_contains "$_result" "Nothing to relocate"
+
+    rm -rf "$_old-remote.git"
+    teardown_wald_workspace
+end_test
+
+begin_test "wald relocate --old-root carries the XDG cache over"
+    setup_wald_workspace
+
+    _cache=$(mktemp -d)
+    echo "cache: xdg" >> .wald/config.yaml
+    create_bare_repo "github.com/test/repo" "with_commits"
+    XDG_CACHE_HOME="$_cache" $WALD_BIN repo add "github.com/test/repo"
+
+    _old="$TEST_WS"
+    cd /tmp
+    mv "$_old" "$_old-moved"
+    TEST_WS="$_old-moved"
+    cd "$TEST_WS"
+
+    XDG_CACHE_HOME="$_cache" $WALD_BIN relocate --old-root "$_old" >/dev/null 2>&1
+    assert_dir_exists "$_cache/wald/$(basename "$TEST_WS")-"*
+    assert_contains "$(XDG_CACHE_HOME="$_cache" $WALD_BIN log)" "repo add"
+
+    rm -rf "$_cache"
+    teardown_wald_workspace
+end_test
+
+# ====================================================================================
+# Relocating one repo's bare clone
+# ====================================================================================
+
+begin_test "wald repo relocate moves a bare clone and reconnects its worktrees"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    create_bare_repo "github.com/fork/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN repo add "github.com/fork/repo" --reference "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    $WALD_BIN plant "github.com/fork/repo" "forks/repo" main
+
+    _disk="$TEST_WS-disk"
+    _result=$($WALD_BIN repo relocate github.com/test/repo "$_disk/repo.git" 2>&1)
+    assert_contains "$_result" "reconnected 1 worktree(s) and 1 alternate(s)"
+    assert_dir_exists "$_disk/repo.git"
+    assert_dir_not_exists ".wald/repos/github.com/test/repo.git"
+    assert_file_contains ".wald/manifest.yaml" "bare_path: $_disk/repo.git"
+    assert_exit_code 0 git -C tools/repo/_main.wt status
+    assert_exit_code 0 git -C forks/repo/_main.wt log -1
+
+    # New worktrees come from the new place
+    $WALD_BIN branch tools/repo dev
+    assert_worktree_exists "tools/repo/_dev.wt"
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_not_contains "$_result" "ERROR"
+    assert_not_contains "$_result" "not cloned"
+
+    # Moving it back home drops the override
+    $WALD_BIN repo relocate github.com/test/repo .wald/repos/github.com/test/repo.git
+    assert_dir_exists ".wald/repos/github.com/test/repo.git"
+    if grep -q "bare_path" .wald/manifest.yaml; then
+        _fail "bare_path should be dropped"
+    fi
+    assert_exit_code 0 git -C tools/repo/_dev.wt status
+
+    rm -rf "$_disk"
+    teardown_wald_workspace
+end_test
+
+begin_test "wald repo relocate keeps a moved fork borrowing from its lender"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    create_bare_repo "github.com/fork/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN repo add "github.com/fork/repo" --reference "github.com/test/repo"
+    $WALD_BIN plant "github.com/fork/repo" "forks/repo" main
+
+    _result=$($WALD_BIN repo relocate github.com/fork/repo mirrors/fork.git 2>&1)
+    assert_contains "$_result" "reconnected 1 worktree(s) and 1 alternate(s)"
+    assert_eq "../../../.wald/repos/github.com/test/repo.git/objects" "$(cat mirrors/fork.git/objects/info/alternates)"
+    assert_exit_code 0 git -C forks/repo/_main.wt log -1
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_not_contains "$_result" "which is missing"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald doctor checks bare_path overrides"
+    setup_wald_workspace
+
+    $WALD_BIN repo add --no-clone github.com/test/repo
+    $WALD_BIN repo add --no-clone github.com/test/other
+    sed -i "/github.com\/test\/repo:/a\    bare_path: /nonexistent-disk/repo.git" .wald/manifest.yaml
+    sed -i "/github.com\/test\/other:/a\    bare_path: shared.git" .wald/manifest.yaml
+    mkdir shared.git
+
+    _result=$($WALD_BIN doctor 2>&1)
+    assert_contains "$_result" "bare_path of github.com/test/repo (/nonexistent-disk/repo.git) is in a directory that doesn't exist"
+    assert_contains "$_result" "bare_path of github.com/test/other holds no bare repo: shared.git"
+
+    # Not cloned onto a disk that isn't there
+    $WALD_BIN doctor --fix >/dev/null 2>&1 || true
+    assert_dir_not_exists "/nonexistent-disk"
+
+    teardown_wald_workspace
+end_test
+
+# Print summary if running standalone
+if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
+    print_summary
+fi
@@ -124,6 +124,165 @@ begin_test "wald repo fetch --full removes promisor config"
     teardown_wald_workspace
 end_test
 
+# ====================================================================================
+# Resuming an interrupted fetch
+# ====================================================================================
+
+begin_test "wald repo fetch resumes after a failed repo"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/api" with_commits
+    create_bare_repo "github.com/test/web" with_commits
+    create_bare_repo "github.com/test/source" with_commits
+    $WALD_BIN repo add --no-clone github.com/test/api
+    $WALD_BIN repo add --no-clone github.com/test/web
+
+    # api can be fetched, web's origin is gone
+    _source="$PWD/$(get_bare_repo_path "github.com/test/source")"
+    git -C "$(get_bare_repo_path "github.com/test/api")" remote set-url origin "$_source"
+    git -C "$(get_bare_repo_path "github.com/test/web")" remote set-url origin "$PWD/missing.git"
+
+    _result=$($WALD_BIN repo fetch 2>&1) && _code=0 || _code=$?
+    # Partly failed: exit code 3, failures listed under their cause
+    assert_eq "3" "$_code"
+    assert_contains "$_result" "1 of 2 repo(s) failed to fetch"
+    assert_contains "$_result" "does not appear to be a git repository
+    github.com/test/web"
+    assert_file_contains ".wald/state.yaml" "github.com/test/api"
+
+    # The rerun only fetches what failed
+    git -C "$(get_bare_repo_path "github.com/test/web")" remote set-url origin "$_source"
+    _result=$($WALD_BIN repo fetch 2>&1)
+    assert_contains "$_result" "Resuming: 1 of 2 repo(s) already fetched"
+    assert_not_contains "$_result" "Fetching github.com/test/api"
+    assert_contains "$_result" "Fetch complete"
+    assert_not_contains "$(cat .wald/state.yaml)" "unfinished"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald repo fetch --post-fetch runs the manifest's post_fetch commands"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/api" with_commits
+    create_bare_repo "github.com/test/source" with_commits
+    $WALD_BIN repo add --no-clone github.com/test/api
+    _bare="$(get_bare_repo_path "github.com/test/api")"
+    git -C "$_bare" remote set-url origin "$PWD/$(get_bare_repo_path "github.com/test/source")"
+    git -C "$_bare" config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+    cat > .wald/manifest.yaml <<'EOF'
+repos:
+  github.com/test/api:
+    post_fetch:
+    - echo "$WALD_REPO_ID" > "$WALD_WORKSPACE/hook.out"; echo "$WALD_UPDATED_REFS" >> "$WALD_WORKSPACE/hook.out"
+EOF
+
+    # Commands from the manifest only run when asked for
+    _result=$($WALD_BIN repo fetch 2>&1)
+    assert_contains "$_result" "Skipping post_fetch commands of 1 repo(s); pass --post-fetch to run them"
+    assert_file_not_exists "hook.out"
+
+    # The command is told which branches moved
+    git -C "$_bare" update-ref -d refs/remotes/origin/main
+    _result=$($WALD_BIN repo fetch --post-fetch 2>&1)
+    assert_contains "$_result" "they run unsandboxed"
+    assert_contains "$_result" "Fetch complete"
+    assert_file_contains "hook.out" "github.com/test/api"
+    assert_file_contains "hook.out" "0000000000000000000000000000000000000000 $(git -C "$_bare" rev-parse refs/remotes/origin/main) refs/remotes/origin/main"
+    assert_not_contains "$(cat hook.out)" "refs/remotes/origin/dev"
+
+    teardown_wald_workspace
+end_test
+
+# ====================================================================================
+# Tag policy
+# ====================================================================================
+
+begin_test "wald repo fetch follows the tag policy unless --tags"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/source" with_commits
+    create_bare_repo "github.com/test/repo" with_commits
+    _source="$PWD/$(get_bare_repo_path "github.com/test/source")"
+    git -C "$_source" tag ci-1234 main
+
+    $WALD_BIN repo add --no-clone --tags none github.com/test/repo
+    assert_file_contains ".wald/manifest.yaml" "tags: none"
+    git -C "$(get_bare_repo_path "github.com/test/repo")" remote set-url origin "$_source"
+
+    $WALD_BIN repo fetch github.com/test/repo
+    _result=$($WALD_BIN tags github.com/test/repo 2>&1)
+    assert_contains "$_result" "No tags"
+
+    $WALD_BIN repo fetch --tags github.com/test/repo
+    _result=$($WALD_BIN tags github.com/test/repo 2>&1)
+    assert_contains "$_result" "ci-1234"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald tags shows short SHAs and relative dates, in full with --verbose"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" with_commits
+    $WALD_BIN repo add github.com/test/repo
+    _bare="$(get_bare_repo_path "github.com/test/repo")"
+    GIT_COMMITTER_DATE="$(date -d '3 days ago' +%s) +0000" \
+        git -C "$_bare" -c user.name=Test -c user.email=test@example.com tag -a v1.0 -m "v1.0" main
+    _commit=$(git -C "$_bare" rev-parse main)
+
+    _result=$($WALD_BIN tags github.com/test/repo 2>&1)
+    assert_contains "$_result" "3 days ago  ${_commit:0:8}  v1.0"
+    assert_not_contains "$_result" "$_commit"
+
+    _result=$($WALD_BIN --verbose tags github.com/test/repo 2>&1)
+    assert_contains "$_result" "$_commit  v1.0"
+
+    # JSON keeps the full values
+    _result=$($WALD_BIN --json tags github.com/test/repo)
+    assert_contains "$_result" "\"commit\": \"$_commit\""
+
+    teardown_wald_workspace
+end_test
+
+# ====================================================================================
+# Long fetches
+# ====================================================================================
+
+begin_test "wald repo fetch --watch shows transfer progress"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/source" with_commits
+    create_bare_repo "github.com/test/repo" with_commits
+    $WALD_BIN repo add --no-clone github.com/test/repo
+    git -C "$(get_bare_repo_path "github.com/test/repo")" remote set-url origin "$PWD/$(get_bare_repo_path "github.com/test/source")"
+
+    _result=$($WALD_BIN repo fetch --watch github.com/test/repo 2>&1)
+    assert_contains "$_result" "objects: 100%"
+    assert_contains "$_result" "done."
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald repo fetch --timeout stops a fetch that hangs"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" with_commits
+    $WALD_BIN repo add --no-clone github.com/test/repo
+    _bare="$(get_bare_repo_path "github.com/test/repo")"
+    git -C "$_bare" remote set-url origin "git@github.com:test/repo.git"
+
+    # The "server" never answers
+    _started=$SECONDS
+    _result=$(GIT_SSH_COMMAND="sleep 60; true" $WALD_BIN repo fetch --timeout 1 github.com/test/repo 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "stopped after 1s (--timeout)"
+    assert_eq "stopped in time" "$( (( SECONDS - _started < 20 )) && echo "stopped in time")"
+    assert_eq "" "$(find "$_bare" -name '*.lock')"
+
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
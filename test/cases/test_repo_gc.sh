@@ -124,6 +124,30 @@ begin_test "wald repo gc on repo with worktrees"
     teardown_wald_workspace
 end_test
 
+begin_test "wald repo gc reports sizes and skips repos with work in progress"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" with_commits
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    _result=$($WALD_BIN repo gc --prune=now github.com/test/repo 2>&1)
+    assert_contains "$_result" "github.com/test/repo: "
+    assert_contains "$_result" " -> "
+    assert_contains "$_result" "reclaimed"
+
+    # A stash only lives in a reflog gc may expire
+    echo "wip" >> tools/repo/_main.wt/README.md
+    git -C tools/repo/_main.wt -c user.name=Test -c user.email=test@example.com stash --quiet
+    _result=$($WALD_BIN repo gc 2>&1)
+    assert_contains "$_result" "Skipping github.com/test/repo: it has stashed changes"
+
+    _result=$($WALD_BIN repo gc --force 2>&1)
+    assert_contains "$_result" "Cleaning"
+
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
@@ -106,6 +106,166 @@ begin_test "wald status shows last sync info"
     teardown_wald_workspace
 end_test
 
+begin_test "wald status shows how far each repo is behind upstream"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN repo add --no-clone "github.com/test/missing"
+
+    _result=$($WALD_BIN status 2>&1)
+    assert_contains "$_result" "github.com/test/repo: up to date, never fetched"
+    assert_contains "$_result" "github.com/test/missing: not cloned"
+
+    # origin/main moved on, local main tracks it
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    git -C "$_bare" config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+    _newer=$(git -C "$_bare" -c user.name=Test -c user.email=test@example.com \
+        commit-tree -p main -m "upstream work" "main^{tree}")
+    git -C "$_bare" update-ref refs/remotes/origin/main "$_newer"
+    git -C "$_bare" update-ref refs/remotes/origin/dev refs/heads/dev
+    git -C "$_bare" config branch.main.remote origin
+    git -C "$_bare" config branch.main.merge refs/heads/main
+    git -C "$_bare" config branch.dev.remote origin
+    git -C "$_bare" config branch.dev.merge refs/heads/dev
+    touch "$_bare/FETCH_HEAD"
+
+    _result=$($WALD_BIN status 2>&1)
+    assert_contains "$_result" "github.com/test/repo: 1 of 2 branch(es) behind, fetched"
+
+    _result=$($WALD_BIN --json status 2>&1)
+    assert_json_valid "$_result"
+    assert_contains "$_result" "branches_behind"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald export lists repos, baums and worktrees"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add --tags none "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+
+    _result=$($WALD_BIN export 2>&1)
+    assert_json_valid "$_result"
+    assert_contains "$_result" '"baums"'
+
+    _result=$($WALD_BIN export --format csv --what repos 2>&1)
+    assert_contains "$_result" "repo_id,url,upstream,reference,aliases,lfs,depth,filter,tags,identity,cloned"
+    assert_contains "$_result" "github.com/test/repo,,,,,minimal,100,blob-none,none,,true"
+
+    _result=$($WALD_BIN export --format csv --what baums 2>&1)
+    assert_contains "$_result" "tools/repo,"
+    assert_contains "$_result" ",github.com/test/repo,main;dev"
+
+    _commit=$(git -C tools/repo/_dev.wt rev-parse HEAD)
+    _result=$($WALD_BIN export --format csv --what worktrees 2>&1)
+    assert_contains "$_result" ",dev,"
+    assert_contains "$_result" "$_commit"
+
+    _result=$($WALD_BIN export --format csv 2>&1 || true)
+    assert_contains "$_result" "--what"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald --timings reports phases on stderr"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo1" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo1"
+
+    _result=$($WALD_BIN --timings plant "github.com/test/repo1" "tools/repo1" main 2>&1 >/dev/null)
+
+    assert_contains "$_result" "Timings:"
+    assert_contains "$_result" "git worktree add"
+    assert_contains "$_result" "yaml write"
+    assert_contains "$_result" "total"
+
+    # Nothing extra on stdout, so JSON output stays parseable
+    _json=$($WALD_BIN --timings --json status 2>/dev/null)
+    assert_json_valid "$_json"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald status --group shows only the group's repos and baums"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/api" "with_commits"
+    create_bare_repo "github.com/test/web" "with_commits"
+    $WALD_BIN repo add --group backend "github.com/test/api"
+    $WALD_BIN repo add "github.com/test/web"
+    $WALD_BIN plant "github.com/test/api" "tools/api" main
+    $WALD_BIN plant "github.com/test/web" "tools/web" main dev
+
+    _result=$($WALD_BIN status --group backend 2>&1)
+    assert_contains "$_result" "Repos: 1 in group backend"
+    assert_contains "$_result" "github.com/test/api"
+    assert_not_contains "$_result" "github.com/test/web"
+    assert_contains "$_result" "Baums: 1 planted (1 worktrees)"
+
+    _json=$($WALD_BIN --json status --group backend)
+    assert_json_valid "$_json"
+    assert_contains "$_json" '"group": "backend"'
+    assert_contains "$_json" '"repos_count": 1'
+
+    _result=$($WALD_BIN repo fetch --group backend 2>&1 || true)
+    assert_contains "$_result" "Fetching github.com/test/api"
+    assert_not_contains "$_result" "github.com/test/web"
+
+    teardown_wald_workspace
+end_test
+
+# ====================================================================================
+# Dashboard
+# ====================================================================================
+
+begin_test "wald without a command summarizes the workspace"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN repo add --no-clone "github.com/test/other"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    _result=$($WALD_BIN 2>&1)
+    assert_contains "$_result" "Repos: 2 registered (1 not cloned)"
+    assert_contains "$_result" "Baums: 1 planted (1 worktrees)"
+    assert_contains "$_result" "Doctor: not run yet"
+
+    # The last doctor run's errors are shown without checking again
+    mkdir -p broken/.baum
+    echo "repo_id: [" > broken/.baum/manifest.yaml
+    $WALD_BIN doctor >/dev/null 2>&1 || true
+    rm -rf broken
+
+    _result=$($WALD_BIN 2>&1)
+    assert_contains "$_result" "Doctor: 1 error(s)"
+    assert_contains "$_result" "Invalid baum manifest at broken"
+
+    _json=$($WALD_BIN --json)
+    assert_json_valid "$_json"
+    assert_contains "$_json" '"baums_count": 1'
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald without a command prints the help outside a workspace or when disabled"
+    setup_wald_workspace
+
+    echo "dashboard: false" >> .wald/config.yaml
+    _result=$($WALD_BIN 2>&1)
+    assert_contains "$_result" "Usage: wald"
+    assert_not_contains "$_result" "Repos:"
+
+    _result=$(cd /tmp && $WALD_BIN 2>&1)
+    assert_contains "$_result" "Usage: wald"
+
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
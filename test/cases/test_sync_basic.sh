@@ -162,6 +162,42 @@ begin_test "wald sync detects diverged history"
     teardown_multi_machine
 end_test
 
+begin_test "wald sync aborts a conflicting rebase unless --interactive"
+    setup_multi_machine
+
+    # Both machines change the same line
+    cd "$TEST_ALPHA" || exit 1
+    echo "alpha" > notes.md
+    workspace_commit "$TEST_ALPHA" "Alpha notes"
+
+    cd "$TEST_BETA" || exit 1
+    echo "beta" > notes.md
+    git add notes.md
+    git commit --quiet -m "Beta notes"
+    _head=$(git rev-parse HEAD)
+
+    # Default: the rebase is undone and the conflicted files are named
+    _result=$($WALD_BIN sync --force 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "notes.md"
+    assert_contains "$_result" "rebase was aborted"
+    assert_eq "$_head" "$(git rev-parse HEAD)"
+    assert_eq "beta" "$(cat notes.md)"
+
+    # --interactive leaves the rebase for resolution
+    _result=$($WALD_BIN sync --force --interactive 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "notes.md"
+    assert_contains "$_result" "rebase --continue"
+    assert_dir_exists "$(git rev-parse --git-path rebase-merge)"
+
+    # Until it's finished, sync says so instead of complaining about changes
+    _result=$($WALD_BIN sync 2>&1) && _code=0 || _code=$?
+    assert_contains "$_result" "a rebase is in progress"
+
+    teardown_multi_machine
+end_test
+
 # ====================================================================================
 # Uncommitted changes handling
 # ====================================================================================
@@ -223,6 +259,50 @@ begin_test "wald sync initializes state.yaml if missing"
     teardown_wald_workspace
 end_test
 
+begin_test "wald sync clones missing repos smallest first and records failures"
+    setup_wald_workspace
+
+    _src=$(mktemp -d)
+    for _name in big small gone; do
+        create_bare_repo "github.com/test/$_name" "with_commits"
+        $WALD_BIN repo add "github.com/test/$_name"
+        $WALD_BIN plant "github.com/test/$_name" "tools/$_name" main
+    done
+    # Sizes as recorded on the machine that registered them
+    sed -i '/^  github.com\/test\/big:/,/^  [^ ]/ s/^    size: .*/    size: 9000/' .wald/manifest.yaml
+    sed -i '/^  github.com\/test\/small:/,/^  [^ ]/ s/^    size: .*/    size: 10/' .wald/manifest.yaml
+    sed -i '/^  github.com\/test\/gone:/,/^  [^ ]/ s/^    size: .*/    size: 90000/' .wald/manifest.yaml
+    assert_file_contains ".wald/manifest.yaml" "size: 9000"
+    cat >> .wald/config.yaml <<'YAML'
+hydration:
+  jobs: 1
+  host_interval_ms: 0
+YAML
+    git add -A && git commit -qm "Plant baums"
+    publish_workspace
+
+    # A new machine: the clones are missing, and one repo is gone upstream
+    mv .wald/repos/github.com/test/big.git .wald/repos/github.com/test/small.git "$_src/"
+    rm -rf .wald/repos/github.com/test/gone.git
+    export GIT_CONFIG_COUNT=1 GIT_CONFIG_KEY_0="url.$_src/.insteadOf" GIT_CONFIG_VALUE_0="git@github.com:test/"
+
+    _result=$($WALD_BIN sync 2>&1 || true)
+    assert_contains "$_result" "Failed to clone github.com/test/gone"
+    assert_dir_exists ".wald/repos/github.com/test/big.git"
+    assert_dir_exists ".wald/repos/github.com/test/small.git"
+    _order=$(echo "$_result" | grep "Cloning github.com" | awk '{print $2}' | tr '\n' ' ')
+    assert_eq "github.com/test/small github.com/test/big github.com/test/gone " "$_order"
+    assert_file_contains ".wald/state.yaml" "failed_clones"
+    assert_file_contains ".wald/state.yaml" "github.com/test/gone"
+
+    _result=$($WALD_BIN status 2>&1)
+    assert_contains "$_result" "github.com/test/gone: not cloned (failed on the last sync)"
+
+    unset GIT_CONFIG_COUNT GIT_CONFIG_KEY_0 GIT_CONFIG_VALUE_0
+    rm -rf "$_src"
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
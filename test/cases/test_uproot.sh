@@ -131,8 +131,36 @@ begin_test "wald uproot fails with uncommitted changes without --force"
     create_uncommitted_changes "tools/repo/_main.wt"
 
     _result=$($WALD_BIN uproot "tools/repo" 2>&1 || true)
-    # Should fail - git worktree remove fails on unclean worktrees
-    assert_contains "$_result" "failed to remove"
+    assert_contains "$_result" "tools/repo has work that uprooting would put at risk"
+    assert_contains "$_result" "main: 1 uncommitted file(s)"
+    assert_contains "$_result" "use --force to uproot anyway"
+    assert_dir_exists "tools/repo/_main.wt"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald uproot refuses unpushed commits without --force"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    _bare=$(get_bare_repo_path "github.com/test/repo")
+    git -C "$_bare" config remote.origin.fetch "+refs/heads/*:refs/remotes/origin/*"
+    git -C "$_bare" update-ref refs/remotes/origin/main refs/heads/main
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    git -C tools/repo/_main.wt -c user.name=Test -c user.email=test@example.com \
+        commit --allow-empty --quiet -m "local work"
+
+    _result=$($WALD_BIN uproot "tools/repo" 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "main: 1 unpushed commit(s), last \"local work\""
+    assert_dir_exists "tools/repo/_main.wt"
+
+    # --force asks first, then uproots
+    _result=$($WALD_BIN uproot --force "tools/repo" 2>&1 </dev/null) && _code=0 || _code=$?
+    assert_contains "$_result" "refusing to leave the unpushed commits of main behind without confirmation"
+    $WALD_BIN uproot --force --yes "tools/repo"
+    assert_dir_not_exists "tools/repo"
 
     teardown_wald_workspace
 end_test
@@ -147,8 +175,14 @@ begin_test "wald uproot --force removes despite uncommitted changes"
     # Create uncommitted changes
     create_uncommitted_changes "tools/repo/_main.wt"
 
+    # Discarding the changes needs confirmation
+    _result=$($WALD_BIN uproot --force "tools/repo" 2>&1 </dev/null) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "refusing to discard the local changes in main without confirmation"
+    assert_dir_exists "tools/repo/_main.wt"
+
     # Force uproot
-    $WALD_BIN uproot --force "tools/repo"
+    $WALD_BIN uproot --force --yes "tools/repo"
 
     # Verify removed
     assert_dir_not_exists "tools/repo"
@@ -156,6 +190,114 @@ begin_test "wald uproot --force removes despite uncommitted changes"
     teardown_wald_workspace
 end_test
 
+begin_test "wald undo uproot restores the baum with its worktrees"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    echo "notes" > tools/repo/NOTES.md
+
+    # Commit on the worktree's branch; the branch survives uproot
+    (cd tools/repo/_dev.wt && echo change > file.txt && git add file.txt \
+        && git -c user.name=Test -c user.email=test@example.com commit --quiet -m "local work")
+    _head=$(git -C tools/repo/_dev.wt rev-parse HEAD)
+
+    $WALD_BIN uproot "tools/repo"
+    assert_dir_not_exists "tools/repo"
+    assert_eq "1" "$(ls .wald/trash | wc -l | tr -d ' ')"
+
+    $WALD_BIN undo uproot
+    assert_worktree_exists "tools/repo/_main.wt"
+    assert_worktree_exists "tools/repo/_dev.wt"
+    assert_file_contains "tools/repo/NOTES.md" "notes"
+    assert_eq "$_head" "$(git -C tools/repo/_dev.wt rev-parse HEAD)"
+    assert_bare_worktree_count "github.com/test/repo" 2
+    assert_eq "0" "$(ls .wald/trash | wc -l | tr -d ' ')"
+
+    # Nothing left to undo
+    assert_exit_code 1 $WALD_BIN undo uproot
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald undo uproot refuses when the path is taken again"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    $WALD_BIN uproot "tools/repo"
+    mkdir -p tools/repo
+
+    _result=$($WALD_BIN undo uproot 2>&1)
+    assert_contains "$_result" "path is taken"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald trash empty deletes uprooted baums"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/a" main
+    $WALD_BIN plant "github.com/test/repo" "tools/b" main
+    $WALD_BIN uproot "tools/a"
+    $WALD_BIN uproot "tools/b"
+
+    _result=$($WALD_BIN trash empty 2>&1 </dev/null) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "refusing to permanently delete 2 trashed baum(s)"
+    assert_eq "2" "$(ls .wald/trash | wc -l | tr -d ' ')"
+
+    assert_exit_code 0 $WALD_BIN trash empty --yes
+    assert_eq "0" "$(ls .wald/trash | wc -l | tr -d ' ')"
+    assert_exit_code 1 $WALD_BIN undo uproot
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald uproot never fetches to check a partial clone's worktree"
+    setup_wald_workspace
+
+    # A source that serves blobless clones, with a branch renaming the file
+    _src=$(mktemp -d)
+    git init -q -b main "$_src/repo"
+    seq 1 50 | sed 's/^/line /' > "$_src/repo/a.txt"
+    git -C "$_src/repo" add a.txt
+    git -C "$_src/repo" -c user.name=Test -c user.email=test@example.com commit -qm init
+    git -C "$_src/repo" checkout -qb moved
+    sed 's/line 50/LINE 50/' "$_src/repo/a.txt" > "$_src/repo/c.txt"
+    git -C "$_src/repo" rm -q a.txt
+    git -C "$_src/repo" add c.txt
+    git -C "$_src/repo" -c user.name=Test -c user.email=test@example.com commit -qm move
+    git -C "$_src/repo" checkout -q main
+    git -C "$_src/repo" config uploadpack.allowFilter true
+
+    $WALD_BIN repo add --no-clone "github.com/test/repo"
+    _bare=".wald/repos/github.com/test/repo.git"
+    git clone -q --bare --filter=blob:none "file://$_src/repo" "$_bare"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    # Checking the worktree for changes now needs the missing blob of c.txt
+    git -C tools/repo/_main.wt reset -q --soft moved
+
+    _result=$($WALD_BIN uproot tools/repo 2>&1) && _code=0 || _code=$?
+    assert_eq "1" "$_code"
+    assert_contains "$_result" "without fetching objects missing from the partial clone"
+    assert_dir_exists "tools/repo/_main.wt"
+    _blob=$(git -C "$_bare" rev-parse moved:c.txt)
+    _missing=$(git -C "$_bare" rev-list --objects --missing=print moved | grep '^?' || true)
+    assert_contains "$_missing" "?$_blob"
+
+    # --force removes it without the check
+    assert_exit_code 0 $WALD_BIN uproot --force --yes tools/repo
+
+    rm -rf "$_src"
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
@@ -108,6 +108,7 @@ begin_test "wald worktrees --json produces valid JSON"
     assert_contains "$_result" "repo_id"
     assert_contains "$_result" "branch"
     assert_contains "$_result" "container"
+    assert_contains "$_result" '"schema_version": 1'
 
     teardown_wald_workspace
 end_test
@@ -123,6 +124,228 @@ begin_test "wald worktrees shows empty message when none exist"
     teardown_wald_workspace
 end_test
 
+begin_test "wald worktrees --repo limits to one repository"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/api" "with_commits"
+    create_bare_repo "github.com/test/web" "with_commits"
+    $WALD_BIN repo add "github.com/test/api"
+    $WALD_BIN repo add "github.com/test/web"
+    $WALD_BIN plant "github.com/test/api" "code/api" main
+    $WALD_BIN plant "github.com/test/web" "code/web" main
+
+    _result=$($WALD_BIN worktrees --repo api 2>&1)
+    assert_contains "$_result" "code/api"
+    assert_not_contains "$_result" "code/web"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald which prints worktree paths of a repository"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/api" "with_commits"
+    $WALD_BIN repo add "github.com/test/api"
+    $WALD_BIN plant "github.com/test/api" "code/api" main dev
+    $WALD_BIN plant "github.com/test/api" "scratch/api" main
+
+    _result=$($WALD_BIN which api)
+    assert_eq "3" "$(echo "$_result" | wc -l | tr -d ' ')"
+
+    _result=$($WALD_BIN which github.com/test/api dev)
+    assert_eq "$TEST_WS/code/api/_dev.wt" "$_result"
+
+    _result=$($WALD_BIN --json which api main)
+    assert_json_valid "$_result"
+    assert_contains "$_result" "scratch/api"
+
+    assert_exit_code 1 $WALD_BIN which api nonexistent
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald worktrees skips directories matching ignore patterns"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    $WALD_BIN plant "github.com/test/repo" "archive/old/repo" dev
+
+    printf 'ignore:\n  - archive/**\n' >> .wald/config.yaml
+
+    _result=$($WALD_BIN worktrees 2>&1)
+    assert_contains "$_result" "tools/repo"
+    assert_not_contains "$_result" "archive/old/repo"
+
+    _result=$($WALD_BIN status 2>&1)
+    assert_contains "$_result" "Baums: 1 planted"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald worktrees --stale lists worktrees unused for that long"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+
+    # Both worktrees were just used
+    assert_contains "$(cat tools/repo/.baum/manifest.yaml)" "created_at:"
+    assert_contains "$(cat tools/repo/.baum/manifest.yaml)" "last_used:"
+    _result=$($WALD_BIN worktrees --stale 90d 2>&1)
+    assert_contains "$_result" "No worktrees unused for 90d"
+
+    # Nothing happened in dev for half a year
+    sed -i '/last_used:/d' tools/repo/.baum/manifest.yaml
+    _gitdir=$(sed 's/^gitdir: //' tools/repo/_dev.wt/.git)
+    touch -d "200 days ago" "$_gitdir/HEAD" "$_gitdir/index" "$_gitdir/logs/HEAD" 2>/dev/null || true
+
+    _result=$($WALD_BIN worktrees --stale 90d 2>&1)
+    assert_contains "$_result" "dev -> _dev.wt [last used 6 months ago]"
+    assert_not_contains "$_result" "main"
+
+    _json=$($WALD_BIN worktrees --stale 90d --json 2>&1)
+    assert_contains "$_json" '"last_used"'
+    assert_contains "$_json" '"created_at"'
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald env prints a worktree's environment from manifest.local.yaml"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    cat > tools/repo/.baum/manifest.local.yaml <<'YAML'
+env:
+  PORT: "8080"
+  DATA_DIR: /scratch/it's here
+worktrees:
+  dev:
+    env:
+      PORT: "8081"
+YAML
+
+    _result=$(cd tools/repo/_main.wt && $WALD_BIN env)
+    assert_contains "$_result" "export PORT='8080'"
+    assert_contains "$_result" "export DATA_DIR='/scratch/it'\\''s here'"
+
+    # Worktree settings override the baum's, and the output evals cleanly
+    _result=$($WALD_BIN env tools/repo/_dev.wt)
+    assert_contains "$_result" "export PORT='8081'"
+    assert_eq "8081 /scratch/it's here" "$(eval "$_result"; echo "$PORT $DATA_DIR")"
+
+    _result=$($WALD_BIN env tools/repo --json)
+    assert_json_valid "$_result"
+    assert_contains "$_result" '"PORT": "8080"'
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald worktrees marks broken baums"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main dev
+    $WALD_BIN plant "github.com/test/repo" "tools/other" main
+    rm -rf tools/repo/_dev.wt
+    mkdir -p tools/broken/.baum
+    echo "not: [valid" > tools/broken/.baum/manifest.yaml
+
+    _result=$($WALD_BIN worktrees 2>&1)
+    assert_contains "$_result" "tools/other (github.com/test/repo) [ok]"
+    assert_contains "$_result" "tools/repo (github.com/test/repo) [broken: 1 worktree dir(s) missing]"
+    assert_contains "$_result" "tools/broken (?) [broken: manifest unreadable"
+
+    _json=$($WALD_BIN --json worktrees 2>&1)
+    assert_json_valid "$_json"
+    assert_contains "$_json" '"problems": []'
+    assert_contains "$_json" "1 worktree dir(s) missing"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald worktrees --mine lists the baums you own in a team workspace"
+    setup_wald_workspace
+
+    echo "team: user" >> .wald/config.yaml
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    git config user.email "alice@wald.local"
+    $WALD_BIN plant "github.com/test/repo" "alice/repo" main
+    git config user.email "bob@wald.local"
+    $WALD_BIN plant "github.com/test/repo" "bob/repo" main
+
+    assert_file_contains "alice/repo/.baum/manifest.yaml" "owner: alice@wald.local"
+
+    # Everyone's baums are listed, with their owners
+    _result=$($WALD_BIN worktrees 2>&1)
+    assert_contains "$_result" "alice/repo (github.com/test/repo, owner alice@wald.local) [ok]"
+    assert_contains "$_result" "bob/repo (github.com/test/repo, owner bob@wald.local) [ok]"
+
+    _result=$($WALD_BIN worktrees --mine 2>&1)
+    assert_contains "$_result" "bob/repo"
+    assert_not_contains "$_result" "alice/repo"
+
+    _result=$($WALD_BIN status --mine 2>&1)
+    assert_contains "$_result" "Baums: 1 planted for bob@wald.local (1 worktrees)"
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald here describes the worktree you are in"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+    mkdir -p tools/repo/_main.wt/src/deep
+    _id=$(grep '^id:' tools/repo/.baum/manifest.yaml | awk '{print $2}' | tr -d "'\"")
+
+    _result=$(cd tools/repo/_main.wt/src/deep && $WALD_BIN here)
+    assert_contains "$_result" "baum:         tools/repo"
+    assert_contains "$_result" "repo:         github.com/test/repo"
+    assert_contains "$_result" "baum id:      $_id"
+    assert_contains "$_result" "branch:       main"
+    assert_contains "$_result" "local branch: wald/$_id/main"
+    assert_contains "$_result" "upstream:     origin/main"
+
+    _json=$($WALD_BIN --json here tools/repo/_main.wt)
+    assert_json_valid "$_json"
+    assert_contains "$_json" "\"local_branch\": \"wald/$_id/main\""
+
+    # Outside a worktree it fails, also in the baum container itself
+    assert_exit_code 1 $WALD_BIN here tools/repo
+    assert_exit_code 1 $WALD_BIN which-baum tools
+
+    teardown_wald_workspace
+end_test
+
+begin_test "wald prompt prints a segment for the current worktree"
+    setup_wald_workspace
+
+    create_bare_repo "github.com/test/repo" "with_commits"
+    $WALD_BIN repo add "github.com/test/repo"
+    $WALD_BIN plant "github.com/test/repo" "tools/repo" main
+
+    assert_eq "repo@main" "$(cd tools/repo/_main.wt && $WALD_BIN prompt)"
+
+    # Staging rewrites the index, so the cached segment is recomputed
+    echo "change" > tools/repo/_main.wt/new.txt
+    git -C tools/repo/_main.wt add new.txt
+    assert_eq "repo@main ✚" "$(cd tools/repo/_main.wt && $WALD_BIN prompt)"
+
+    # Outside a worktree it prints nothing and still succeeds
+    assert_eq "" "$(cd tools && $WALD_BIN prompt)"
+    assert_exit_code 0 $WALD_BIN prompt tools
+
+    teardown_wald_workspace
+end_test
+
 # Print summary if running standalone
 if [[ "${BASH_SOURCE[0]}" == "${0}" ]]; then
     print_summary
@@ -725,6 +725,19 @@ create_untracked_file() {
     return 0
 }
 
+# Commit the workspace manifest and push to a local remote as upstream,
+# like a workspace in use, so doctor has nothing to say about its repo
+# Usage: publish_workspace
+publish_workspace() {
+    local remote="$TEST_WS-remote.git"
+
+    git init --quiet --bare "$remote"
+    git -C "$TEST_WS" remote add origin "$remote"
+    git -C "$TEST_WS" add .wald/manifest.yaml
+    git -C "$TEST_WS" diff --cached --quiet || git -C "$TEST_WS" commit --quiet -m "Update wald manifest"
+    git -C "$TEST_WS" push --quiet -u origin main
+}
+
 # Assert .gitignore contains entry
 # Usage: assert_gitignore_contains <container_path> <entry>
 assert_gitignore_contains() {
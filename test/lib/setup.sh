@@ -68,7 +68,11 @@ EOF
 # wald:start (managed by wald, do not edit)
 .wald/repos/
 .wald/state.yaml
+.wald/trash/
+.wald/journal/
+.wald/backups/
 **/.baum/manifest.local.yaml
+**/.baum/lock
 **/_*.wt/
 # wald:end
 EOF
@@ -96,6 +100,8 @@ teardown_wald_workspace() {
         # Force remove - worktrees may have read-only files
         chmod -R u+w "$TEST_WS" 2>/dev/null || true
         rm -rf "$TEST_WS"
+        # Its remote, if the test published it
+        rm -rf "$TEST_WS-remote.git"
     fi
 
     # Clear variables
@@ -174,7 +180,11 @@ EOF
 # wald:start (managed by wald, do not edit)
 .wald/repos/
 .wald/state.yaml
+.wald/trash/
+.wald/journal/
+.wald/backups/
 **/.baum/manifest.local.yaml
+**/.baum/lock
 **/_*.wt/
 # wald:end
 EOF